@@ -0,0 +1,105 @@
+//! Feed random traces of a single tx to
+//! [`CircuitInputBuilder::try_handle_block`], which must reject them with
+//! errors. libfuzzer-sys aborts on any panic, which is reported as a crash.
+//!
+//! Run with `cargo fuzz run try_handle_block` from `bus-mapping`.
+
+#![no_main]
+
+use bus_mapping::{circuit_input_builder::CircuitInputBuilder, mock::BlockData};
+use eth_types::{
+    evm_types::{Gas, GasCost, Memory, OpcodeId, ProgramCounter, Stack, Storage},
+    geth_types::{Account, GethData},
+    Block, Bytes, GethExecStep, GethExecTrace, Word,
+};
+use libfuzzer_sys::{
+    arbitrary::{Result, Unstructured},
+    fuzz_target,
+};
+use mock::{eth, MockTransaction, MOCK_ACCOUNTS, MOCK_CHAIN_ID, MOCK_COINBASE};
+
+fn arbitrary_word(u: &mut Unstructured) -> Result<Word> {
+    Ok(Word::from_big_endian(&u.arbitrary::<[u8; 32]>()?))
+}
+
+fn arbitrary_step(u: &mut Unstructured) -> Result<GethExecStep> {
+    let stack_len = u.int_in_range(0..=16)?;
+    let memory_len = 32 * u.int_in_range(0..=4)?;
+    Ok(GethExecStep {
+        pc: ProgramCounter(u.int_in_range(0..=64)?),
+        op: OpcodeId::from(u.arbitrary::<u8>()?),
+        gas: Gas(u.int_in_range(0..=1_000_000)?),
+        gas_cost: GasCost(u.int_in_range(0..=30_000)?),
+        refund: Gas(0),
+        depth: u.int_in_range(1..=3)?,
+        error: if u.ratio(1, 16)? {
+            Some("out of gas".to_string())
+        } else {
+            None
+        },
+        stack: Stack(
+            (0..stack_len)
+                .map(|_| arbitrary_word(u))
+                .collect::<Result<_>>()?,
+        ),
+        memory: Memory(u.bytes(memory_len)?.to_vec()),
+        storage: Storage::default(),
+    })
+}
+
+/// A block with a single tx calling a contract with random code, and a
+/// random trace for it.
+fn arbitrary_block(u: &mut Unstructured) -> Result<GethData> {
+    let (sender, contract) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+    let code = Bytes::from(u.bytes(u.int_in_range(0..=64)?)?.to_vec());
+    let num_steps = u.int_in_range(0..=64)?;
+    let struct_logs = (0..num_steps)
+        .map(|_| arbitrary_step(u))
+        .collect::<Result<_>>()?;
+    let tx = MockTransaction::default()
+        .from(sender)
+        .to(contract)
+        .build()
+        .into();
+
+    Ok(GethData {
+        chain_id: *MOCK_CHAIN_ID,
+        history_hashes: vec![],
+        eth_block: Block {
+            author: Some(*MOCK_COINBASE),
+            number: Some(1.into()),
+            transactions: vec![tx],
+            ..Default::default()
+        },
+        geth_traces: vec![GethExecTrace {
+            l1_fee: 0,
+            gas: Gas(u.int_in_range(0..=1_000_000)?),
+            failed: u.arbitrary()?,
+            return_value: String::new(),
+            struct_logs,
+            validation: None,
+        }],
+        accounts: vec![
+            Account {
+                address: sender,
+                balance: eth(10),
+                ..Default::default()
+            },
+            Account {
+                address: contract,
+                code,
+                ..Default::default()
+            },
+        ],
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let block = match arbitrary_block(&mut Unstructured::new(data)) {
+        Ok(block) => block,
+        Err(_) => return,
+    };
+    let mut builder: CircuitInputBuilder =
+        BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    let _ = builder.try_handle_block(&block.eth_block, &block.geth_traces);
+});
@@ -4,24 +4,33 @@
 mod access;
 mod block;
 mod call;
+mod event_builder;
 mod execution;
+mod fallible;
 mod input_state_ref;
+mod rw_audit;
+mod trace_files;
+mod trace_stream;
 #[cfg(test)]
 mod tracer_tests;
 mod transaction;
+mod witness_export;
 
 use self::access::gen_state_access_trace;
 pub use self::block::BlockHead;
 use crate::{
-    error::Error,
+    error::{Error, ErrorContext},
     evm::opcodes::{gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops},
+    l2_predeployed::system_sender,
     operation::{self, CallContextField, Operation, RWCounter, StartOp, StorageOp, RW},
+    precompile::{PrecompileProvider, PrecompileRegistry},
+    progress::{ProgressReporter, Stage},
     rpc::GethClient,
     state_db::{self, CodeDB, StateDB},
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
 pub use block::{Block, BlockContext};
-pub use call::{Call, CallContext, CallKind};
+pub use call::{Call, CallContext, CallKind, ReturnData};
 use core::fmt::Debug;
 use eth_types::{
     self,
@@ -32,26 +41,38 @@ use eth_types::{
 };
 use ethers_core::{
     k256::ecdsa::SigningKey,
-    types::{Bytes, Signature, TransactionRequest},
+    types::{Bloom, Bytes, Signature, TransactionRequest},
 };
 use ethers_providers::JsonRpcClient;
+pub use event_builder::{CopyEventBuilder, ExpEventBuilder};
 pub use execution::{
-    CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
+    copy_rows, CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep,
+    MemoryCopy, NumberOrHash, PrecompileEvent,
 };
 use hex::decode_to_slice;
 
 use ethers_core::utils::keccak256;
+pub use fallible::check_geth_trace;
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
 use log::warn;
+pub use rw_audit::{audit_rw_counters, RwAuditLocation, RwAuditViolation, RwAuditViolationKind};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashMap},
     iter,
+    sync::Arc,
+};
+pub use trace_stream::stream_geth_trace;
+pub use transaction::{
+    Transaction, TransactionContext, TxL1Fee, TxReceipt, TxReceiptLog, TX_L1_FEE_PRECISION,
+};
+pub use witness_export::{
+    export_witness, CircuitCost, StepExport, TxExport, WitnessExport, WITNESS_EXPORT_VERSION,
 };
-pub use transaction::{Transaction, TransactionContext, TxL1Fee, TX_L1_FEE_PRECISION};
 
 /// Circuit Setup Parameters
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CircuitsParams {
     /// Maximum number of rw operations in the state circuit (RwTable length /
     /// number of rows). This must be at least the number of rw operations
@@ -87,6 +108,40 @@ pub struct CircuitsParams {
     /// calculated, so the same circuit will not be able to prove different
     /// witnesses.
     pub max_keccak_rows: usize,
+    /// Number of rows used by each round of the Keccak circuit.  More rows
+    /// per round reduce the number of columns at the cost of a longer circuit.
+    /// When 0, the `KECCAK_ROWS` env var or the circuit default is used.  It
+    /// must match the rows per round the keccak circuit is configured with.
+    pub keccak_rows_per_round: usize,
+    /// Pad the signature verifications of the ECRecover calls to this
+    /// number.  When 0, the number of signatures of the block is used, so the
+    /// same circuit will not be able to prove different witnesses.
+    #[serde(default)]
+    pub max_ecrecover_sigs: usize,
+    /// Pad the addresses and topics of the logs, which the PI circuit binds
+    /// to the logs bloom of their block, to this number.  When 0, the number
+    /// of the block is used, so the same circuit will not be able to prove
+    /// different witnesses.
+    #[serde(default)]
+    pub max_log_items: usize,
+    /// Sub-circuits checked by the provers which support profiles, see
+    /// `zkevm_circuits::super_circuit::profile`.
+    #[serde(default)]
+    pub profile: CircuitProfile,
+}
+
+/// Sub-circuits of the super circuit checked for a block. The minimal
+/// profiles make lighter test runs and audits of a few sub-circuits, and load
+/// the tables they look up into from the witness instead of proving them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitProfile {
+    /// All the sub-circuits
+    #[default]
+    Full,
+    /// The EVM and state circuits
+    EvmState,
+    /// The tx and PI circuits
+    TxPi,
 }
 
 impl Default for CircuitsParams {
@@ -105,6 +160,10 @@ impl Default for CircuitsParams {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_rlp_rows: 1000,
         }
     }
@@ -138,6 +197,11 @@ pub struct CircuitInputBuilder {
     pub block: Block,
     /// Block Context
     pub block_ctx: BlockContext,
+    /// Progress reported while handling a block, and its cancellation
+    pub progress: ProgressReporter,
+    /// Precompiles of the chain on top of the ones from
+    /// [`crate::precompile::PrecompileCalls`]
+    pub precompiles: PrecompileRegistry,
 }
 
 impl<'a> CircuitInputBuilder {
@@ -149,8 +213,27 @@ impl<'a> CircuitInputBuilder {
             code_db,
             block: block.clone(),
             block_ctx: BlockContext::new(),
+            progress: ProgressReporter::default(),
+            precompiles: PrecompileRegistry::default(),
         }
     }
+
+    /// Report the progress of the handling of a block to `progress`, and stop
+    /// it with [`Error::Cancelled`] between two txs once `progress` is
+    /// cancelled.
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Trace the calls to the precompile of `provider` like calls to a
+    /// builtin precompile.  Fails if `provider` is at the address of a
+    /// precompile from [`crate::precompile::PrecompileCalls`].
+    pub fn with_precompile(mut self, provider: Arc<dyn PrecompileProvider>) -> Result<Self, Error> {
+        self.precompiles.register(provider)?;
+        Ok(self)
+    }
+
     /// Create a new CircuitInputBuilder from the given `eth_block` and
     /// `constants`.
     pub fn new_from_headers(
@@ -181,6 +264,7 @@ impl<'a> CircuitInputBuilder {
             block_ctx: &mut self.block_ctx,
             tx,
             tx_ctx,
+            precompiles: &self.precompiles,
         }
     }
 
@@ -203,7 +287,38 @@ impl<'a> CircuitInputBuilder {
             ),
         );
 
-        Transaction::new(call_id, &self.sdb, &mut self.code_db, eth_tx, is_success)
+        let mut tx = Transaction::new(call_id, &self.sdb, &mut self.code_db, eth_tx, is_success)?;
+        tx.is_system = self.block.gas_free_txs
+            && tx.gas_price.is_zero()
+            && tx.gas_fee_cap.is_zero()
+            && tx.from == *system_sender::ADDRESS;
+        tx.is_prague = match self.block.prague_time {
+            Some(prague_time) => {
+                let header = self
+                    .block
+                    .headers
+                    .get(&tx.block_num)
+                    .ok_or(Error::InternalError("block header of the tx not found"))?;
+                header.timestamp >= prague_time.into()
+            }
+            None => false,
+        };
+        if self.block.include_invalid_txs {
+            let sender = self.sdb.get_account(&tx.from).1;
+            // The circuit needs the fee of a tx, even an invalid one.
+            let fee = Word::from(tx.gas)
+                .checked_mul(tx.gas_price)
+                .and_then(|fee| fee.checked_add(tx.l1_fee().into()))
+                .ok_or(Error::InternalError("tx fee overflows 256 bits"))?;
+            // A cost that overflows is more than any balance, and a nonce at
+            // its maximum can't be increased (EIP-2681).
+            tx.is_invalid = sender.nonce != tx.nonce.into()
+                || tx.nonce == u64::MAX
+                || fee
+                    .checked_add(tx.value)
+                    .map_or(true, |cost| sender.balance < cost);
+        }
+        Ok(tx)
     }
 
     /// Iterate over all generated CallContext RwCounterEndOfReversion
@@ -245,13 +360,40 @@ impl<'a> CircuitInputBuilder {
         handle_rwc_reversion: bool,
         check_last_tx: bool,
     ) -> Result<(), Error> {
+        self.handle_block_with(
+            eth_block,
+            handle_rwc_reversion,
+            check_last_tx,
+            |builder, tx_index, tx, is_last_tx| {
+                builder.handle_tx(tx, &geth_traces[tx_index], is_last_tx)
+            },
+        )
+    }
+
+    /// Handle a block, generating the operations of the transaction at each
+    /// index with `handle_tx`.
+    pub(crate) fn handle_block_with(
+        &mut self,
+        eth_block: &EthBlock,
+        handle_rwc_reversion: bool,
+        check_last_tx: bool,
+        mut handle_tx: impl FnMut(&mut Self, usize, &eth_types::Transaction, bool) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let _span = tracing::info_span!(
+            "handle_block",
+            number = ?eth_block.number,
+            txs = eth_block.transactions.len()
+        )
+        .entered();
         // accumulates gas across all txs in the block
         log::info!(
             "handling block {:?}, tx num {}",
             eth_block.number,
             eth_block.transactions.len()
         );
+        let num_txs = eth_block.transactions.len();
         for (tx_index, tx) in eth_block.transactions.iter().enumerate() {
+            self.progress.report(Stage::Witness, tx_index, num_txs)?;
             let batch_tx_idx = self.block.txs.len();
             if self.block.txs.len() >= self.block.circuits_params.max_txs {
                 log::warn!(
@@ -263,7 +405,6 @@ impl<'a> CircuitInputBuilder {
                 );
                 continue;
             }
-            let geth_trace = &geth_traces[tx_index];
             log::info!(
                 "handling {}th tx(inner idx: {}): {:?} rwc {:?}, to: {:?}, input_len {:?}",
                 batch_tx_idx,
@@ -273,14 +414,26 @@ impl<'a> CircuitInputBuilder {
                 tx.to,
                 tx.input.len(),
             );
+            let _tx_span =
+                tracing::info_span!("handle_tx", index = batch_tx_idx, hash = ?tx.hash).entered();
+            let rwc_before = self.block_ctx.rwc.0;
             let mut tx = tx.clone();
             // needed for multi block feature
             tx.transaction_index = Some(self.block.txs.len().into());
-            self.handle_tx(
+            handle_tx(
+                self,
+                tx_index,
                 &tx,
-                geth_trace,
                 check_last_tx && tx_index + 1 == eth_block.transactions.len(),
             )?;
+            // Counters in the convention of the tracing-opentelemetry metrics
+            // layer.
+            tracing::info!(
+                monotonic_counter.steps_processed =
+                    self.block.txs.last().map_or(0, |tx| tx.steps().len()) as u64,
+                monotonic_counter.rws_generated = (self.block_ctx.rwc.0 - rwc_before) as u64,
+                "tx handled"
+            );
             log::debug!(
                 "after handle {}th tx: rwc {:?}, block total gas {:?}",
                 batch_tx_idx,
@@ -288,6 +441,9 @@ impl<'a> CircuitInputBuilder {
                 self.block_ctx.cumulative_gas_used
             );
         }
+        self.progress.report(Stage::Witness, num_txs, num_txs)?;
+        // The analyzed codes are only shared within the block.
+        self.code_db.clear_bytecode_cache();
         if handle_rwc_reversion {
             self.set_value_ops_call_context_rwc_eor();
             self.set_end_block()?;
@@ -439,6 +595,8 @@ impl<'a> CircuitInputBuilder {
         self.block.prev_withdraw_root = withdraw_root_before;
         self.block.block_steps.end_block_not_last = end_block_not_last;
         self.block.block_steps.end_block_last = end_block_last;
+        // No more operations will be pushed for this block.
+        self.block.container.shrink_to_fit();
         Ok(())
     }
 
@@ -453,7 +611,37 @@ impl<'a> CircuitInputBuilder {
         geth_trace: &GethExecTrace,
         is_last_tx: bool,
     ) -> Result<(), Error> {
+        let tx_index = self.block.txs.len();
+        let in_tx = move |err: Error| err.with_context(ErrorContext::tx(tx_index));
+        let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace, is_last_tx).map_err(in_tx)?;
+        let mut tx = self
+            .begin_tx(eth_tx, geth_trace, &mut tx_ctx)
+            .map_err(in_tx)?;
+        for index in 0..geth_trace.struct_logs.len() {
+            self.handle_tx_step(
+                &mut tx,
+                &mut tx_ctx,
+                index,
+                &geth_trace.struct_logs[index..],
+            )?;
+        }
+        self.end_tx(tx, tx_ctx).map_err(in_tx)
+    }
+
+    /// Create the transaction and generate its BeginTx step. `geth_trace`
+    /// needs only its first step.
+    pub(crate) fn begin_tx(
+        &mut self,
+        eth_tx: &eth_types::Transaction,
+        geth_trace: &GethExecTrace,
+        tx_ctx: &mut TransactionContext,
+    ) -> Result<Transaction, Error> {
         let mut tx = self.new_tx(eth_tx, !geth_trace.failed)?;
+        if tx.is_invalid && (!geth_trace.failed || !geth_trace.struct_logs.is_empty()) {
+            return Err(Error::InternalError(
+                "invalid tx must have a failed trace without steps",
+            ));
+        }
 
         // Sanity check for transaction L1 fee.
         let tx_l1_fee = tx.l1_fee();
@@ -465,10 +653,35 @@ impl<'a> CircuitInputBuilder {
             );
         }
 
-        let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace, is_last_tx)?;
+        // Sanity check for the validation of the tx, if the tracer gives the
+        // one of geth.
+        if let Some(validation) = &geth_trace.validation {
+            if tx.is_invalid != validation.error.is_some() {
+                log::error!(
+                    "Mismatch tx validation: is_invalid = {}, geth error = {:?}",
+                    tx.is_invalid,
+                    validation.error
+                );
+                return Err(Error::InternalError(
+                    "tx validity differs from the validation of geth",
+                ));
+            }
+            if tx.gas_price != validation.effective_gas_price {
+                log::error!(
+                    "Mismatch tx gas_price: calculated = {}, real = {}",
+                    tx.gas_price,
+                    validation.effective_gas_price
+                );
+            }
+        }
+
         let mut debug_tx = tx.clone();
         debug_tx.input.clear();
         log::trace!("handle_tx tx {:?}", debug_tx);
+        // the registered precompiles are warm, like the builtin ones
+        for address in self.precompiles.addresses() {
+            self.sdb.add_account_to_access_list(*address);
+        }
         if let Some(al) = &eth_tx.access_list {
             for item in &al.0 {
                 self.sdb.add_account_to_access_list(item.address);
@@ -482,89 +695,117 @@ impl<'a> CircuitInputBuilder {
         // - execution_state: BeginTx
         // - op: None
         // Generate BeginTx step
-        gen_begin_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx), geth_trace)?;
-
-        for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
-            let tx_gas = tx.gas;
-            let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
-            log::trace!(
-                "handle {}th tx depth {} {}th/{} opcode {:?} pc: {} gas_left: {} gas_used: {} rwc: {} call_id: {} msize: {} args: {}",
-                eth_tx.transaction_index.unwrap_or_default(),
-                geth_step.depth,
-                index,
-                geth_trace.struct_logs.len(),
-                geth_step.op,
-                geth_step.pc.0,
-                geth_step.gas.0,
-                tx_gas - geth_step.gas.0,
-                state_ref.block_ctx.rwc.0,
-                state_ref.call().map(|c| c.call_id).unwrap_or(0),
-                state_ref.call_ctx()?.memory.len(),
-                if geth_step.op.is_push_with_data() {
-                    format!("{:?}", geth_trace.struct_logs[index + 1].stack.last())
-                } else if geth_step.op.is_call_without_value() {
-                    format!(
-                        "{:?} {:40x} {:?} {:?} {:?} {:?}",
-                        geth_step.stack.nth_last(0),
-                        geth_step.stack.nth_last(1).unwrap(),
-                        geth_step.stack.nth_last(2),
-                        geth_step.stack.nth_last(3),
-                        geth_step.stack.nth_last(4),
-                        geth_step.stack.nth_last(5)
-                    )
-                } else if geth_step.op.is_call_with_value() {
-                    format!(
-                        "{:?} {:40x} {:?} {:?} {:?} {:?} {:?}",
-                        geth_step.stack.nth_last(0),
-                        geth_step.stack.nth_last(1).unwrap(),
-                        geth_step.stack.nth_last(2),
-                        geth_step.stack.nth_last(3),
-                        geth_step.stack.nth_last(4),
-                        geth_step.stack.nth_last(5),
-                        geth_step.stack.nth_last(6),
-                    )
-                } else if geth_step.op.is_create() {
-                    format!(
-                        "value {:?} offset {:?} size {:?} {}",
-                        geth_step.stack.nth_last(0),
-                        geth_step.stack.nth_last(1),
-                        geth_step.stack.nth_last(2),
-                        if geth_step.op == OpcodeId::CREATE2 {
-                            format!("salt {:?}", geth_step.stack.nth_last(3))
-                        } else {
-                            "".to_string()
-                        }
-                    )
-                } else if matches!(geth_step.op, OpcodeId::MLOAD) {
-                    format!(
-                        "{:?}",
-                        geth_step.stack.nth_last(0),
-                    )
-                } else if matches!(geth_step.op, OpcodeId::MSTORE | OpcodeId::MSTORE8) {
-                    format!(
-                        "{:?} {:?}",
-                        geth_step.stack.nth_last(0),
-                        geth_step.stack.nth_last(1),
-                    )
-                } else {
-                    "".to_string()
-                }
-            );
-            debug_assert_eq!(
-                geth_step.depth as usize,
-                state_ref.call().unwrap().depth,
-                "call {:?} calls {:?}",
-                state_ref.call(),
-                state_ref.tx.calls()
-            );
-            let exec_steps = gen_associated_ops(
-                &geth_step.op,
-                &mut state_ref,
-                &geth_trace.struct_logs[index..],
-            )?;
-            tx.steps_mut().extend(exec_steps);
+        gen_begin_tx_ops(&mut self.state_ref(&mut tx, tx_ctx), geth_trace)?;
+        Ok(tx)
+    }
+
+    /// Generate the steps of the `index`th step of the trace, `geth_steps[0]`,
+    /// which only looks ahead at `geth_steps[1]`. Failures are returned with
+    /// the [`ErrorContext`] of the step.
+    pub(crate) fn handle_tx_step(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+        index: usize,
+        geth_steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let call_id = tx_ctx
+            .call_index()
+            .map(|call_idx| tx.calls()[call_idx].call_id)
+            .unwrap_or_default();
+        let context = ErrorContext::step(self.block.txs.len(), call_id, &geth_steps[0]);
+        self.gen_tx_step(tx, tx_ctx, index, geth_steps)
+            .map_err(|err| err.with_context(context))
+    }
+
+    fn gen_tx_step(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+        index: usize,
+        geth_steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let geth_step = &geth_steps[0];
+        let mut state_ref = self.state_ref(tx, tx_ctx);
+        log::trace!(
+            "handle {}th tx depth {} {}th opcode {:?} pc: {} gas_left: {} gas_used: {} rwc: {} call_id: {} msize: {} args: {}",
+            state_ref.tx_ctx.id() - 1,
+            geth_step.depth,
+            index,
+            geth_step.op,
+            geth_step.pc.0,
+            geth_step.gas.0,
+            state_ref.tx.gas - geth_step.gas.0,
+            state_ref.block_ctx.rwc.0,
+            state_ref.call().map(|c| c.call_id).unwrap_or(0),
+            state_ref.call_ctx()?.memory.len(),
+            if geth_step.op.is_push_with_data() {
+                format!("{:?}", geth_steps.get(1).map(|step| step.stack.last()))
+            } else if geth_step.op.is_call_without_value() {
+                format!(
+                    "{:?} {:40x} {:?} {:?} {:?} {:?}",
+                    geth_step.stack.nth_last(0),
+                    geth_step.stack.nth_last(1).unwrap_or_default(),
+                    geth_step.stack.nth_last(2),
+                    geth_step.stack.nth_last(3),
+                    geth_step.stack.nth_last(4),
+                    geth_step.stack.nth_last(5)
+                )
+            } else if geth_step.op.is_call_with_value() {
+                format!(
+                    "{:?} {:40x} {:?} {:?} {:?} {:?} {:?}",
+                    geth_step.stack.nth_last(0),
+                    geth_step.stack.nth_last(1).unwrap_or_default(),
+                    geth_step.stack.nth_last(2),
+                    geth_step.stack.nth_last(3),
+                    geth_step.stack.nth_last(4),
+                    geth_step.stack.nth_last(5),
+                    geth_step.stack.nth_last(6),
+                )
+            } else if geth_step.op.is_create() {
+                format!(
+                    "value {:?} offset {:?} size {:?} {}",
+                    geth_step.stack.nth_last(0),
+                    geth_step.stack.nth_last(1),
+                    geth_step.stack.nth_last(2),
+                    if geth_step.op == OpcodeId::CREATE2 {
+                        format!("salt {:?}", geth_step.stack.nth_last(3))
+                    } else {
+                        "".to_string()
+                    }
+                )
+            } else if matches!(geth_step.op, OpcodeId::MLOAD) {
+                format!(
+                    "{:?}",
+                    geth_step.stack.nth_last(0),
+                )
+            } else if matches!(geth_step.op, OpcodeId::MSTORE | OpcodeId::MSTORE8) {
+                format!(
+                    "{:?} {:?}",
+                    geth_step.stack.nth_last(0),
+                    geth_step.stack.nth_last(1),
+                )
+            } else {
+                "".to_string()
+            }
+        );
+        if geth_step.depth as usize != state_ref.call()?.depth {
+            return Err(Error::InvalidGethExecStep(
+                "depth of the step differs from the depth of its call",
+                Box::new(geth_step.clone()),
+            ));
         }
+        let exec_steps = gen_associated_ops(&geth_step.op, &mut state_ref, geth_steps)?;
+        tx.steps_mut().extend(exec_steps);
+        Ok(())
+    }
 
+    /// Generate the EndTx step and add the transaction to the block.
+    pub(crate) fn end_tx(
+        &mut self,
+        mut tx: Transaction,
+        mut tx_ctx: TransactionContext,
+    ) -> Result<(), Error> {
         // TODO: Move into gen_associated_steps with
         // - execution_state: EndTx
         // - op: None
@@ -601,6 +842,12 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
         block.txs(),
         block.circuits_params.max_txs,
     ));
+    // Addresses and topics of the logs, set in the logs bloom by the PI circuit
+    for tx in block.txs() {
+        for log in tx.receipt.logs.iter() {
+            keccak_inputs.extend(log.bloom_inputs().map(|input| input.to_vec()));
+        }
+    }
     // Bytecode Circuit
     for _bytecode in code_db.0.values() {
         // keccak_inputs.push(bytecode.clone());
@@ -615,6 +862,8 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
         "keccak total len after opcodes: {}",
         keccak_inputs.iter().map(|i| i.len()).sum::<usize>()
     );
+    // Sig Circuit
+    keccak_inputs.extend_from_slice(&keccak_inputs_sign_verify(&block.ecrecover_sign_datas()));
 
     let inputs_len: usize = keccak_inputs.iter().map(|k| k.len()).sum();
     let inputs_num = keccak_inputs.len();
@@ -710,6 +959,10 @@ fn keccak_inputs_pi_circuit(
             let parent_hash = block.eth_block.parent_hash;
             let block_hash = block.eth_block.hash.unwrap_or(H256::zero());
             let num_l1_msgs = 0_u16; // 0 for now
+            let logs_bloom = transactions
+                .iter()
+                .filter(|tx| tx.block_num == *block_num)
+                .fold(Bloom::zero(), |bloom, tx| bloom | tx.receipt.logs_bloom());
 
             iter::empty()
                 // Block Values
@@ -721,6 +974,8 @@ fn keccak_inputs_pi_circuit(
                 .chain(block.gas_limit.to_be_bytes())
                 .chain(num_txs.to_be_bytes())
                 .chain(num_l1_msgs.to_be_bytes())
+                .chain(block.eth_block.receipts_root.to_fixed_bytes())
+                .chain(logs_bloom.to_fixed_bytes())
         }))
         // Tx Hashes
         .chain(transactions.iter().flat_map(|tx| tx.hash.to_fixed_bytes()))
@@ -729,6 +984,17 @@ fn keccak_inputs_pi_circuit(
                 .into_iter()
                 .flat_map(|_| dummy_tx_hash.to_fixed_bytes()),
         )
+        // Tx results
+        .chain(
+            transactions
+                .iter()
+                .map(|tx| (tx.receipt.status as u8, tx.receipt.revert_data_hash))
+                .chain(iter::repeat((0, H256::zero())))
+                .take(max_txs)
+                .flat_map(|(status, revert_data_hash)| {
+                    iter::once(status).chain(revert_data_hash.to_fixed_bytes())
+                }),
+        )
         .collect::<Vec<u8>>();
 
     result
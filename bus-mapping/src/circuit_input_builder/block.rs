@@ -2,12 +2,17 @@
 
 use super::{
     execution::ExecState, transaction::Transaction, CircuitsParams, CopyEvent, ExecStep, ExpEvent,
+    PrecompileEvent,
 };
 use crate::{
     operation::{OperationContainer, RWCounter},
+    precompile::P256VerifyInput,
     Error,
 };
-use eth_types::{Address, Hash, ToWord, Word, U256};
+use eth_types::{
+    evm_types::gas_utils::blob_base_fee, geth_types::header_quantity, sign_types::SignData,
+    Address, Hash, ToWord, Word, U256,
+};
 use std::collections::{BTreeMap, HashMap};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -86,6 +91,8 @@ pub struct BlockHead {
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
+    /// blob base fee, from the excess blob gas of the header
+    pub blob_base_fee: Word,
     /// Original block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
 }
@@ -126,6 +133,7 @@ impl BlockHead {
                 eth_block.difficulty
             },
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            blob_base_fee: blob_base_fee(header_quantity(eth_block, "excessBlobGas")?.as_u64()),
             eth_block: eth_block.clone(),
         })
     }
@@ -157,10 +165,29 @@ pub struct Block {
     pub block_steps: BlockSteps,
     /// Exponentiation events in the block.
     pub exp_events: Vec<ExpEvent>,
+    /// Events of the precompile calls in the block.
+    pub precompile_events: Vec<PrecompileEvent>,
+    /// Addresses of the calls to precompiles registered in
+    /// [`crate::precompile::PrecompileConstraintMode::Stub`], which the
+    /// circuits can't prove.
+    pub stub_precompile_calls: Vec<Address>,
     /// Circuits Setup Paramteres
     pub circuits_params: CircuitsParams,
     /// chain id
     pub chain_id: Word,
+    /// Whether a tx from [`crate::l2_predeployed::system_sender::ADDRESS`]
+    /// with a zero gas price is a gas-free system tx, exempt from the base
+    /// fee as some L2s do for their system contracts. Set from
+    /// `TraceConfig::gas_free_txs`.
+    pub gas_free_txs: bool,
+    /// Whether txs that fail the intrinsic checks (nonce mismatch or
+    /// insufficient balance) are included in the block as no-ops instead of
+    /// being rejected, as L2 sequencers must do for forced L1 txs.
+    pub include_invalid_txs: bool,
+    /// Activation time of the Prague fork, from which the txs of a block pay
+    /// at least the floor cost of their call data of EIP-7623. `None` if the
+    /// chain isn't on Prague. Set from `ChainConfig::prague_time`.
+    pub prague_time: Option<u64>,
 }
 
 impl Block {
@@ -246,4 +273,28 @@ impl Block {
     pub fn add_exp_event(&mut self, event: ExpEvent) {
         self.exp_events.push(event);
     }
+    /// Push a precompile event to the block.
+    pub fn add_precompile_event(&mut self, event: PrecompileEvent) {
+        self.precompile_events.push(event);
+    }
+    /// Signatures recovered by the ECRecover calls of the block.
+    pub fn ecrecover_sign_datas(&self) -> Vec<SignData> {
+        self.precompile_events
+            .iter()
+            .filter_map(|event| match event {
+                PrecompileEvent::Ecrecover(sign_data) => Some(sign_data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    /// Inputs of the P256VERIFY calls of the block with a valid signature.
+    pub fn p256_verify_inputs(&self) -> Vec<P256VerifyInput> {
+        self.precompile_events
+            .iter()
+            .filter_map(|event| match event {
+                PrecompileEvent::P256Verify(input) => Some(input.clone()),
+                _ => None,
+            })
+            .collect()
+    }
 }
@@ -2,7 +2,7 @@ use super::CodeSource;
 use crate::{exec_trace::OperationRef, Error};
 use eth_types::{
     evm_types::{Memory, OpcodeId},
-    Address, Hash, Word,
+    Address, CallTrace, Hash, Word,
 };
 
 /// Type of a *CALL*/CREATE* Function.
@@ -127,6 +127,68 @@ impl Call {
     }
 }
 
+impl Call {
+    /// The field of this call differing from the call frame traced by geth,
+    /// if any.
+    pub(crate) fn frame_mismatch(&self, frame: &CallTrace) -> Option<&'static str> {
+        if CallKind::try_from(frame.call_type).ok() != Some(self.kind) {
+            return Some("kind");
+        }
+        if frame.depth.map_or(false, |depth| depth != self.depth) {
+            return Some("depth");
+        }
+        // the frames of CALLCODE and DELEGATECALL are to the code address
+        let address = if self.is_create() {
+            Some(self.address)
+        } else {
+            self.code_address()
+        };
+        if frame.to != address {
+            return Some("address");
+        }
+        // geth reports the contract running the call instruction, which is
+        // the callee of a DELEGATECALL, as it keeps the context of its caller
+        let from = if self.is_delegatecall() {
+            self.address
+        } else {
+            self.caller_address
+        };
+        if frame.from != from {
+            return Some("from");
+        }
+        // geth gives no value to the frames of DELEGATECALL and STATICCALL
+        if frame.value.map_or(false, |value| value != self.value) {
+            return Some("value");
+        }
+        if frame.is_success() != self.is_success {
+            return Some("is_success");
+        }
+        None
+    }
+}
+
+/// Return data buffer of a call (EIP-211), read by RETURNDATASIZE and
+/// RETURNDATACOPY. It holds the data returned by the last callee of the call,
+/// and is replaced when any callee ends, including the calls to precompiles and
+/// the calls failing before running any code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReturnData {
+    /// Id of the last callee, or 0 if the last callee ended before running
+    /// any code or if there is none
+    pub callee_id: usize,
+    /// Offset of the data in the memory of the last callee
+    pub offset: u64,
+    /// Returned bytes
+    pub data: Vec<u8>,
+}
+
+impl ReturnData {
+    /// Length of the returned data
+    pub fn length(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
 /// Context of a [`Call`].
 #[derive(Debug, Default)]
 pub struct CallContext {
@@ -141,8 +203,8 @@ pub struct CallContext {
     pub call_data: Vec<u8>,
     /// memory context of current call
     pub memory: Memory,
-    /// return data buffer
-    pub return_data: Vec<u8>,
+    /// return data buffer, set when each callee of the call ends
+    pub return_data: ReturnData,
 }
 
 impl CallContext {
@@ -0,0 +1,228 @@
+//! Builders of the copy and exponentiation events, to craft the inputs of the
+//! copy and exp circuits directly in tests instead of tracing a block.
+
+use super::{CopyDataType, CopyEvent, ExpEvent, NumberOrHash};
+use crate::{evm::opcodes::exp_by_squaring, operation::RWCounter, Error};
+use eth_types::Word;
+
+/// Builder of a [`CopyEvent`], checking on [`CopyEventBuilder::build`] that
+/// its source and destination are consistent.
+#[derive(Clone, Debug)]
+pub struct CopyEventBuilder {
+    event: CopyEvent,
+}
+
+impl CopyEventBuilder {
+    /// Copy from `src_type` identified by `src_id` to `dst_type` identified by
+    /// `dst_id`, starting at address 0 of both, with no bytes.
+    pub fn new(
+        src_type: CopyDataType,
+        src_id: NumberOrHash,
+        dst_type: CopyDataType,
+        dst_id: NumberOrHash,
+    ) -> Self {
+        Self {
+            event: CopyEvent {
+                src_addr: 0,
+                src_addr_end: 0,
+                src_type,
+                src_id,
+                dst_addr: 0,
+                dst_type,
+                dst_id,
+                log_id: None,
+                rw_counter_start: RWCounter(1),
+                bytes: Vec::new(),
+            },
+        }
+    }
+
+    /// Set the start and end addresses at the source.
+    pub fn src_addr(mut self, start: u64, end: u64) -> Self {
+        self.event.src_addr = start;
+        self.event.src_addr_end = end;
+        self
+    }
+
+    /// Set the start address at the destination.
+    pub fn dst_addr(mut self, start: u64) -> Self {
+        self.event.dst_addr = start;
+        self
+    }
+
+    /// Set the log ID, for a copy to a tx log.
+    pub fn log_id(mut self, log_id: u64) -> Self {
+        self.event.log_id = Some(log_id);
+        self
+    }
+
+    /// Set the rw counter at the start of the copy.
+    pub fn rw_counter_start(mut self, rw_counter: usize) -> Self {
+        self.event.rw_counter_start = RWCounter(rw_counter);
+        self
+    }
+
+    /// Set the copied bytes, with whether each one is an opcode.
+    pub fn bytes(mut self, bytes: Vec<(u8, bool)>) -> Self {
+        self.event.bytes = bytes;
+        self
+    }
+
+    /// Set the copied bytes, none of which is an opcode.
+    pub fn data(self, data: &[u8]) -> Self {
+        self.bytes(data.iter().map(|&byte| (byte, false)).collect())
+    }
+
+    /// Build the event, checking that:
+    /// - the source and destination types can be copied from and to,
+    /// - bytecodes are identified by their hash, and the others by a number,
+    /// - only a copy to a tx log has a log ID,
+    /// - only bytes copied from or to a bytecode can be opcodes,
+    /// - the copied range does not overflow the addresses.
+    pub fn build(self) -> Result<CopyEvent, Error> {
+        let event = self.event;
+        if !event.src_type.is_copy_source() || !event.dst_type.is_copy_destination() {
+            return Err(Error::InvalidCopyDataType {
+                src_type: event.src_type,
+                dst_type: event.dst_type,
+            });
+        }
+        for (ty, id) in [
+            (event.src_type, &event.src_id),
+            (event.dst_type, &event.dst_id),
+        ] {
+            let is_hash = matches!(id, NumberOrHash::Hash(_));
+            if is_hash != (ty == CopyDataType::Bytecode) {
+                return Err(Error::InternalError(
+                    "copy event ID must be a hash for a bytecode only",
+                ));
+            }
+        }
+        if event.log_id.is_some() != (event.dst_type == CopyDataType::TxLog) {
+            return Err(Error::InternalError(
+                "copy event must have a log ID for a tx log only",
+            ));
+        }
+        let has_code =
+            event.src_type == CopyDataType::Bytecode || event.dst_type == CopyDataType::Bytecode;
+        if !has_code && event.bytes.iter().any(|(_, is_code)| *is_code) {
+            return Err(Error::InternalError(
+                "copy event byte is an opcode outside of a bytecode",
+            ));
+        }
+        let len = event.bytes.len() as u64;
+        if event.src_addr.checked_add(len).is_none() || event.dst_addr.checked_add(len).is_none() {
+            return Err(Error::InternalError("copy event addresses overflow"));
+        }
+        Ok(event)
+    }
+}
+
+/// Builder of an [`ExpEvent`], generating its steps by exponentiation by
+/// squaring.
+#[derive(Clone, Debug)]
+pub struct ExpEventBuilder {
+    identifier: usize,
+    base: Word,
+    exponent: Word,
+}
+
+impl ExpEventBuilder {
+    /// Exponentiation of `base` by `exponent`.
+    pub fn new(base: Word, exponent: Word) -> Self {
+        Self {
+            identifier: 0,
+            base,
+            exponent,
+        }
+    }
+
+    /// Set the identifier of the event, the rw counter of its EXP step.
+    pub fn identifier(mut self, identifier: usize) -> Self {
+        self.identifier = identifier;
+        self
+    }
+
+    /// Build the event, checking its steps with [`ExpEvent::verify`].
+    pub fn build(self) -> Result<ExpEvent, Error> {
+        let mut steps = Vec::new();
+        let exponentiation = exp_by_squaring(self.base, self.exponent, &mut steps);
+        let event = ExpEvent {
+            identifier: self.identifier,
+            base: self.base,
+            exponent: self.exponent,
+            exponentiation,
+            steps,
+        };
+        event.verify()?;
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod event_builder_tests {
+    use super::*;
+    use eth_types::H256;
+
+    #[test]
+    fn copy_event_builder() {
+        let event = CopyEventBuilder::new(
+            CopyDataType::Memory,
+            NumberOrHash::Number(1),
+            CopyDataType::TxLog,
+            NumberOrHash::Number(1),
+        )
+        .src_addr(0x20, 0x23)
+        .log_id(1)
+        .rw_counter_start(5)
+        .data(&[1, 2, 3])
+        .build()
+        .unwrap();
+        assert_eq!(event.rw_counter_delta().unwrap(), 6);
+
+        let bytecode_as_number = CopyEventBuilder::new(
+            CopyDataType::Bytecode,
+            NumberOrHash::Number(1),
+            CopyDataType::Memory,
+            NumberOrHash::Number(1),
+        );
+        assert!(bytecode_as_number.build().is_err());
+
+        let missing_log_id = CopyEventBuilder::new(
+            CopyDataType::Memory,
+            NumberOrHash::Number(1),
+            CopyDataType::TxLog,
+            NumberOrHash::Number(1),
+        );
+        assert!(missing_log_id.build().is_err());
+
+        let opcode_in_memory = CopyEventBuilder::new(
+            CopyDataType::Memory,
+            NumberOrHash::Number(1),
+            CopyDataType::Memory,
+            NumberOrHash::Number(2),
+        )
+        .bytes(vec![(0x60, true)]);
+        assert!(opcode_in_memory.build().is_err());
+
+        let code = CopyEventBuilder::new(
+            CopyDataType::Bytecode,
+            NumberOrHash::Hash(H256::zero()),
+            CopyDataType::Memory,
+            NumberOrHash::Number(1),
+        )
+        .bytes(vec![(0x60, true), (0x01, false)]);
+        assert!(code.build().is_ok());
+    }
+
+    #[test]
+    fn exp_event_builder() {
+        let event = ExpEventBuilder::new(3.into(), 13.into())
+            .identifier(7)
+            .build()
+            .unwrap();
+        assert_eq!(event.identifier, 7);
+        assert_eq!(event.exponentiation, 1594323.into());
+        assert_eq!(event.steps.len(), 5);
+    }
+}
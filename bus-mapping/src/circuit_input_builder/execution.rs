@@ -3,11 +3,19 @@
 use std::marker::PhantomData;
 
 use crate::{
-    circuit_input_builder::CallContext, error::ExecError, exec_trace::OperationRef,
-    operation::RWCounter, precompile::PrecompileCalls,
+    circuit_input_builder::CallContext,
+    error::ExecError,
+    exec_trace::OperationRef,
+    operation::RWCounter,
+    precompile::{P256VerifyInput, PrecompileCalls},
+    Error,
 };
 use eth_types::{
-    evm_types::{Gas, GasCost, OpcodeId, ProgramCounter},
+    evm_types::{
+        gas_utils::{memory_copier_gas_cost, memory_expansion_gas_cost, next_memory_word_size},
+        Gas, GasCost, OpcodeId, ProgramCounter, Stack,
+    },
+    sign_types::SignData,
     GethExecStep, Word, H256,
 };
 use gadgets::impl_expr;
@@ -93,6 +101,18 @@ impl ExecStep {
     pub fn is_precompiled(&self) -> bool {
         matches!(self.exec_state, ExecState::Precompile(_))
     }
+
+    /// Returns the memory word size after accessing the `(offset, length)`
+    /// memory ranges in this step, and the gas cost of the memory expansion.
+    pub fn memory_expansion(&self, ranges: impl IntoIterator<Item = (u64, u64)>) -> (u64, u64) {
+        debug_assert_eq!(self.memory_size % 32, 0);
+        let curr_memory_word_size = (self.memory_size as u64) / 32;
+        let next_memory_word_size = next_memory_word_size(curr_memory_word_size, ranges);
+        (
+            next_memory_word_size,
+            memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size),
+        )
+    }
 }
 
 impl Default for ExecStep {
@@ -197,7 +217,7 @@ impl CopyDataType {
         PrecompileCalls::iter().map(Self::Precompile).collect()
     }
 }
-const NUM_COPY_DATA_TYPES: usize = 15usize;
+const NUM_COPY_DATA_TYPES: usize = 16usize;
 pub struct CopyDataTypeIter {
     idx: usize,
     back_idx: usize,
@@ -221,6 +241,7 @@ impl CopyDataTypeIter {
             12usize => Some(CopyDataType::Precompile(PrecompileCalls::Bn128Mul)),
             13usize => Some(CopyDataType::Precompile(PrecompileCalls::Bn128Pairing)),
             14usize => Some(CopyDataType::Precompile(PrecompileCalls::Blake2F)),
+            15usize => Some(CopyDataType::Precompile(PrecompileCalls::P256Verify)),
             _ => None,
         }
     }
@@ -287,6 +308,8 @@ impl From<CopyDataType> for usize {
             CopyDataType::TxCalldata => 3,
             CopyDataType::TxLog => 4,
             CopyDataType::RlcAcc => 5,
+            // Not contiguous with the other precompile addresses.
+            CopyDataType::Precompile(PrecompileCalls::P256Verify) => 15,
             CopyDataType::Precompile(prec_call) => 5 + usize::from(prec_call),
         }
     }
@@ -301,6 +324,8 @@ impl From<&CopyDataType> for u64 {
             CopyDataType::TxCalldata => 3,
             CopyDataType::TxLog => 4,
             CopyDataType::RlcAcc => 5,
+            // Not contiguous with the other precompile addresses.
+            CopyDataType::Precompile(PrecompileCalls::P256Verify) => 15,
             CopyDataType::Precompile(prec_call) => 5 + u64::from(*prec_call),
         }
     }
@@ -334,6 +359,63 @@ pub enum NumberOrHash {
     Hash(H256),
 }
 
+/// Copy to memory of a CALLDATACOPY, CODECOPY, EXTCODECOPY or RETURNDATACOPY,
+/// as given by the stack of its step. Both the gas cost of the copy and the
+/// size of its copy event follow from its length, so that an estimation of the
+/// rows of a tx can predict the copy events from the steps of its trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryCopy {
+    /// Offset in memory of the copied bytes
+    pub dst_offset: Word,
+    /// Offset of the copied bytes in the source
+    pub src_offset: Word,
+    /// Number of copied bytes
+    pub length: Word,
+}
+
+impl MemoryCopy {
+    /// Reads the copy of a step of `opcode` from its `stack`, or returns `None`
+    /// if `opcode` doesn't copy to memory.
+    pub fn from_stack(opcode: OpcodeId, stack: &Stack) -> Result<Option<Self>, Error> {
+        // EXTCODECOPY pops the address before the arguments of the copy.
+        let first = match opcode {
+            OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => 0,
+            OpcodeId::EXTCODECOPY => 1,
+            _ => return Ok(None),
+        };
+        Ok(Some(Self {
+            dst_offset: stack.nth_last(first)?,
+            src_offset: stack.nth_last(first + 1)?,
+            length: stack.nth_last(first + 2)?,
+        }))
+    }
+
+    /// Number of copied bytes, which is also the number of bytes of the copy
+    /// event. A length that doesn't fit in 64 bits, which no tx can pay, is
+    /// saturated to `u64::MAX`.
+    pub fn num_copy_bytes(&self) -> u64 {
+        u64::try_from(self.length).unwrap_or(u64::MAX)
+    }
+
+    /// Dynamic gas cost of the copy from a memory of `curr_memory_word_size`
+    /// words: the gas per copied word and the memory expansion.
+    pub fn gas_cost(&self, curr_memory_word_size: u64) -> u64 {
+        let num_copy_bytes = self.num_copy_bytes();
+        let dst_offset = u64::try_from(self.dst_offset).unwrap_or(u64::MAX);
+        memory_copier_gas_cost(
+            curr_memory_word_size,
+            next_memory_word_size(curr_memory_word_size, [(dst_offset, num_copy_bytes)]),
+            num_copy_bytes,
+            GasCost::COPY.as_u64(),
+        )
+    }
+
+    /// Number of rows of the copy circuit used by the copy event of the copy
+    pub fn copy_rows(&self) -> usize {
+        copy_rows(usize::try_from(self.num_copy_bytes()).unwrap_or(usize::MAX))
+    }
+}
+
 /// Defines a copy event associated with EVM opcodes such as CALLDATACOPY,
 /// CODECOPY, CREATE, etc. More information:
 /// <https://github.com/privacy-scaling-explorations/zkevm-specs/blob/master/specs/copy-proof.md>.
@@ -362,24 +444,58 @@ pub struct CopyEvent {
     pub bytes: Vec<(u8, bool)>,
 }
 
+impl CopyDataType {
+    /// Whether a copy event can read from this type.
+    pub fn is_copy_source(&self) -> bool {
+        matches!(
+            self,
+            Self::Bytecode | Self::Memory | Self::TxCalldata | Self::Precompile(_)
+        )
+    }
+
+    /// Whether a copy event can write to this type.
+    pub fn is_copy_destination(&self) -> bool {
+        matches!(
+            self,
+            Self::Bytecode | Self::Memory | Self::TxLog | Self::RlcAcc | Self::Precompile(_)
+        )
+    }
+}
+
+/// Number of rows of the copy circuit used by a copy event of `num_bytes`
+/// bytes, which has a read row and a write row per byte.
+pub fn copy_rows(num_bytes: usize) -> usize {
+    num_bytes.saturating_mul(2)
+}
+
 impl CopyEvent {
+    /// Number of rows of the copy circuit used by this copy event
+    pub fn num_rows(&self) -> usize {
+        copy_rows(self.bytes.len())
+    }
+
     /// rw counter at step index
-    pub fn rw_counter(&self, step_index: usize) -> u64 {
-        u64::try_from(self.rw_counter_start.0).unwrap() + self.rw_counter_increase(step_index)
+    pub fn rw_counter(&self, step_index: usize) -> Result<u64, Error> {
+        Ok(u64::try_from(self.rw_counter_start.0).unwrap()
+            + self.rw_counter_increase(step_index)?)
     }
 
     /// rw counter increase left at step index
-    pub fn rw_counter_increase_left(&self, step_index: usize) -> u64 {
-        self.rw_counter(self.bytes.len() * 2) - self.rw_counter(step_index)
+    pub fn rw_counter_increase_left(&self, step_index: usize) -> Result<u64, Error> {
+        Ok(self.rw_counter_delta()? - self.rw_counter_increase(step_index)?)
     }
 
     /// Number of rw operations performed by this copy event
-    pub fn rw_counter_delta(&self) -> u64 {
-        self.rw_counter_increase(self.bytes.len() * 2)
+    pub fn rw_counter_delta(&self) -> Result<u64, Error> {
+        self.rw_counter_increase(self.num_rows())
     }
 
     // increase in rw counter from the start of the copy event to step index
-    fn rw_counter_increase(&self, step_index: usize) -> u64 {
+    fn rw_counter_increase(&self, step_index: usize) -> Result<u64, Error> {
+        let invalid_type = || Error::InvalidCopyDataType {
+            src_type: self.src_type,
+            dst_type: self.dst_type,
+        };
         let source_rw_increase = match self.src_type {
             CopyDataType::Bytecode | CopyDataType::TxCalldata | CopyDataType::Precompile(_) => 0,
             CopyDataType::Memory => std::cmp::min(
@@ -388,16 +504,16 @@ impl CopyEvent {
                     .checked_sub(self.src_addr)
                     .unwrap_or_default(),
             ),
-            CopyDataType::RlcAcc | CopyDataType::TxLog | CopyDataType::Padding => unreachable!(),
+            CopyDataType::RlcAcc | CopyDataType::TxLog | CopyDataType::Padding => {
+                return Err(invalid_type())
+            }
         };
         let destination_rw_increase = match self.dst_type {
             CopyDataType::RlcAcc | CopyDataType::Bytecode | CopyDataType::Precompile(_) => 0,
             CopyDataType::TxLog | CopyDataType::Memory => u64::try_from(step_index).unwrap() / 2,
-            CopyDataType::TxCalldata | CopyDataType::Padding => {
-                unreachable!()
-            }
+            CopyDataType::TxCalldata | CopyDataType::Padding => return Err(invalid_type()),
         };
-        source_rw_increase + destination_rw_increase
+        Ok(source_rw_increase + destination_rw_increase)
     }
 }
 
@@ -452,3 +568,175 @@ impl Default for ExpEvent {
         }
     }
 }
+
+impl ExpEvent {
+    /// Check that the steps are the square-and-multiply trace of the
+    /// exponentiation: going through the bits of the exponent from the most
+    /// significant one, each bit squares the running product and each set bit
+    /// then multiplies it by the base. An exponent of 0 or 1 has no steps.
+    pub fn verify(&self) -> Result<(), Error> {
+        let (exponentiation, _) = self.base.overflowing_pow(self.exponent);
+        if self.exponentiation != exponentiation {
+            return Err(Error::InternalError(
+                "exp event result is not base^exponent",
+            ));
+        }
+
+        let mut steps = self.steps.iter();
+        let mut product = self.base;
+        for bit in (0..self.exponent.bits().saturating_sub(1)).rev() {
+            product = verify_exp_step(&mut steps, product, product)?;
+            if self.exponent.bit(bit) {
+                product = verify_exp_step(&mut steps, product, self.base)?;
+            }
+        }
+        if steps.next().is_some() {
+            return Err(Error::InternalError("exp event has extra steps"));
+        }
+        if !self.exponent.is_zero() && product != self.exponentiation {
+            return Err(Error::InternalError(
+                "exp event steps do not end at the result",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Check that the next step multiplies `a` by `b`, returning its product.
+fn verify_exp_step<'a>(
+    steps: &mut impl Iterator<Item = &'a ExpStep>,
+    a: Word,
+    b: Word,
+) -> Result<Word, Error> {
+    let step = steps
+        .next()
+        .ok_or(Error::InternalError("exp event is missing steps"))?;
+    if (step.a, step.b) != (a, b) {
+        return Err(Error::InternalError(
+            "exp event step multiplies the wrong operands",
+        ));
+    }
+    if step.d != a.overflowing_mul(b).0 {
+        return Err(Error::InternalError("exp event step product is wrong"));
+    }
+    Ok(step.d)
+}
+
+/// Event of a precompile call whose computation is verified outside of the
+/// EVM circuit.
+#[derive(Clone, Debug)]
+pub enum PrecompileEvent {
+    /// Signature whose public key was recovered by a successful ECRecover
+    /// call.
+    Ecrecover(SignData),
+    /// Input of a P256VERIFY call whose signature is valid.
+    P256Verify(P256VerifyInput),
+}
+
+#[cfg(test)]
+mod copy_event_tests {
+    use super::*;
+    use proptest::{prelude::*, sample::select};
+
+    fn copy_data_types() -> Vec<CopyDataType> {
+        CopyDataType::iter().collect()
+    }
+
+    fn not_sources() -> Vec<CopyDataType> {
+        CopyDataType::iter()
+            .filter(|ty| !ty.is_copy_source())
+            .collect()
+    }
+
+    fn not_destinations() -> Vec<CopyDataType> {
+        CopyDataType::iter()
+            .filter(|ty| !ty.is_copy_destination())
+            .collect()
+    }
+
+    fn copy_event(
+        src_type: CopyDataType,
+        dst_type: CopyDataType,
+        src_addr: u64,
+        src_len: u64,
+        len: usize,
+    ) -> CopyEvent {
+        CopyEvent {
+            src_addr,
+            src_addr_end: src_addr + src_len,
+            src_type,
+            src_id: NumberOrHash::Number(1),
+            dst_addr: 0,
+            dst_type,
+            dst_id: NumberOrHash::Number(2),
+            log_id: None,
+            rw_counter_start: RWCounter(7),
+            bytes: vec![(0, false); len],
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn rw_counter_of_valid_types(
+            src_type in select(copy_data_types()).prop_filter("source", |ty| ty.is_copy_source()),
+            dst_type in select(copy_data_types())
+                .prop_filter("destination", |ty| ty.is_copy_destination()),
+            src_addr in 0..0x1000u64,
+            src_len in 0..0x100u64,
+            len in 0..0x100usize,
+        ) {
+            let event = copy_event(src_type, dst_type, src_addr, src_len, len);
+
+            // a read of the source memory for each byte in its range and a
+            // write for each byte to the destination memory or log
+            let reads = if src_type == CopyDataType::Memory {
+                src_len.min(len as u64)
+            } else {
+                0
+            };
+            let writes = if matches!(dst_type, CopyDataType::Memory | CopyDataType::TxLog) {
+                len as u64
+            } else {
+                0
+            };
+            let delta = event.rw_counter_delta().unwrap();
+            prop_assert_eq!(delta, reads + writes);
+
+            prop_assert_eq!(event.rw_counter(0).unwrap(), 7);
+            prop_assert_eq!(event.rw_counter(2 * len).unwrap(), 7 + delta);
+            for step_index in 0..=2 * len {
+                let rw_counter = event.rw_counter(step_index).unwrap();
+                prop_assert_eq!(
+                    rw_counter + event.rw_counter_increase_left(step_index).unwrap(),
+                    7 + delta
+                );
+                if step_index > 0 {
+                    // each step is a single read or write, if any
+                    let step_increase = rw_counter - event.rw_counter(step_index - 1).unwrap();
+                    prop_assert!(step_increase <= 1);
+                }
+            }
+        }
+
+        #[test]
+        fn rw_counter_of_invalid_types(
+            (src_type, dst_type) in prop_oneof![
+                (select(not_sources()), select(copy_data_types())),
+                (select(copy_data_types()), select(not_destinations())),
+            ],
+            len in 0..0x10usize,
+        ) {
+            let event = copy_event(src_type, dst_type, 0, len as u64, len);
+            let is_invalid_type = |result: Result<u64, Error>| {
+                matches!(
+                    result,
+                    Err(Error::InvalidCopyDataType { src_type: src, dst_type: dst })
+                        if (src, dst) == (src_type, dst_type)
+                )
+            };
+            prop_assert!(is_invalid_type(event.rw_counter_delta()));
+            prop_assert!(is_invalid_type(event.rw_counter(len)));
+            prop_assert!(is_invalid_type(event.rw_counter_increase_left(0)));
+        }
+    }
+}
@@ -0,0 +1,525 @@
+//! Fallible witness generation for blocks with untrusted txs: malformed
+//! traces are rejected with typed errors before being handled, and the
+//! inconsistencies only found while handling them are returned as errors by
+//! the witness generation.
+
+use super::{CircuitInputBuilder, EthBlock};
+use crate::{error::ErrorContext, Error};
+use eth_types::{
+    evm_types::{
+        gas_utils::{memory_expansion_gas_cost, memory_word_size},
+        OpcodeId,
+    },
+    GethExecStep, GethExecTrace, Word,
+};
+
+/// Maximum number of words in the stack.
+const STACK_LIMIT: usize = 1024;
+
+fn invalid_step(msg: &'static str, step: &GethExecStep) -> Error {
+    Error::InvalidGethExecStep(msg, Box::new(step.clone()))
+}
+
+/// Returns whether the step halts its call.
+fn is_halt(op: OpcodeId) -> bool {
+    matches!(
+        op,
+        OpcodeId::STOP | OpcodeId::RETURN | OpcodeId::REVERT | OpcodeId::SELFDESTRUCT
+    )
+}
+
+/// Returns whether the step may fail without geth reporting an error on it:
+/// the invalid jumps, the out of bounds return data copies, the writes in a
+/// static call and the failed deployments of a RETURN.
+fn may_fail_unreported(op: OpcodeId) -> bool {
+    op.is_log()
+        || matches!(
+            op,
+            OpcodeId::JUMP
+                | OpcodeId::JUMPI
+                | OpcodeId::RETURNDATACOPY
+                | OpcodeId::SSTORE
+                | OpcodeId::TSTORE
+                | OpcodeId::CREATE
+                | OpcodeId::CREATE2
+                | OpcodeId::CALL
+                | OpcodeId::RETURN
+                | OpcodeId::SELFDESTRUCT
+        )
+}
+
+/// Returns the number of values of the stack read by the step.
+fn stack_reads(op: OpcodeId) -> usize {
+    match op.postfix() {
+        Some(n) if op.is_dup() => n as usize,
+        Some(n) if op.is_swap() => n as usize + 1,
+        _ => op.stack_pops_pushes().0,
+    }
+}
+
+/// Returns the `(offset, length)` memory ranges accessed by the step, read
+/// from the operands on its stack.
+fn memory_ranges(step: &GethExecStep) -> Result<Vec<(Word, Word)>, Error> {
+    let arg = |n| step.stack.nth_last(n).map_err(Error::from);
+    let word = |length: u64| Word::from(length);
+    Ok(match step.op {
+        OpcodeId::MLOAD | OpcodeId::MSTORE => vec![(arg(0)?, word(32))],
+        OpcodeId::MSTORE8 => vec![(arg(0)?, word(1))],
+        OpcodeId::SHA3 | OpcodeId::RETURN | OpcodeId::REVERT => vec![(arg(0)?, arg(1)?)],
+        op if op.is_log() => vec![(arg(0)?, arg(1)?)],
+        OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => {
+            vec![(arg(0)?, arg(2)?)]
+        }
+        OpcodeId::EXTCODECOPY => vec![(arg(1)?, arg(3)?)],
+        OpcodeId::CREATE | OpcodeId::CREATE2 => vec![(arg(1)?, arg(2)?)],
+        OpcodeId::CALL | OpcodeId::CALLCODE => vec![(arg(3)?, arg(4)?), (arg(5)?, arg(6)?)],
+        OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            vec![(arg(2)?, arg(3)?), (arg(4)?, arg(5)?)]
+        }
+        _ => vec![],
+    })
+}
+
+/// Check that the memory ranges accessed by a step could be paid with the
+/// gas limit of the tx, so that the memory of the witness generation stays
+/// within what a valid trace can expand it to.
+fn check_memory_ranges(step: &GethExecStep, gas_limit: u64) -> Result<(), Error> {
+    for (offset, length) in memory_ranges(step)? {
+        if length.is_zero() {
+            continue;
+        }
+        if offset.bits() > 64 || length.bits() > 64 {
+            return Err(invalid_step("memory range above 64 bits", step));
+        }
+        let word_size = memory_word_size(offset.as_u64(), length.as_u64());
+        if memory_expansion_gas_cost(0, word_size) > gas_limit {
+            return Err(invalid_step(
+                "memory expansion above the gas limit of the tx",
+                step,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check the pc, stack and gas of the step following `step` in the same call.
+fn check_next_step(step: &GethExecStep, next: &GethExecStep) -> Result<(), Error> {
+    if next.gas.0 > step.gas.0 {
+        return Err(invalid_step("gas increased in the call", step));
+    }
+    if is_halt(step.op) {
+        return Err(invalid_step("step after a halt in the same call", step));
+    }
+    let next_pc = match step.op {
+        OpcodeId::JUMP => step.stack.last()?,
+        OpcodeId::JUMPI if !step.stack.nth_last(1)?.is_zero() => step.stack.last()?,
+        op => Word::from(step.pc.0 as u64 + 1 + op.data_len() as u64),
+    };
+    if Word::from(next.pc.0) != next_pc {
+        return Err(invalid_step("invalid pc of the next step", step));
+    }
+
+    let (stack, next_stack) = (&step.stack.0, &next.stack.0);
+    let expected = match step.op.postfix() {
+        Some(n) if step.op.is_dup() => {
+            let mut expected = stack.clone();
+            expected.push(stack[stack.len() - n as usize]);
+            expected
+        }
+        Some(n) if step.op.is_swap() => {
+            let mut expected = stack.clone();
+            let top = expected.len() - 1;
+            expected.swap(top, top - n as usize);
+            expected
+        }
+        _ => {
+            // The pushed values aren't known here, only the values below the
+            // popped ones are checked.
+            let (pops, pushes) = step.op.stack_pops_pushes();
+            let kept = stack.len() - pops;
+            if next_stack.len() != kept + pushes || next_stack[..kept] != stack[..kept] {
+                return Err(invalid_step("invalid stack of the next step", step));
+            }
+            return Ok(());
+        }
+    };
+    if *next_stack != expected {
+        return Err(invalid_step("invalid stack of the next step", step));
+    }
+    Ok(())
+}
+
+/// Check the first step of the caller after its callee returned: it resumes
+/// after the call with the result of the call pushed on its stack.
+fn check_return(caller: &GethExecStep, next: &GethExecStep) -> Result<(), Error> {
+    if next.gas.0 > caller.gas.0 {
+        return Err(invalid_step("gas increased in the call", caller));
+    }
+    if next.pc.0 != caller.pc.0 + 1 {
+        return Err(invalid_step("call not resumed after its callee", caller));
+    }
+    let kept = caller.stack.0.len() - caller.op.stack_pops_pushes().0;
+    if next.stack.0.len() != kept + 1 || next.stack.0[..kept] != caller.stack.0[..kept] {
+        return Err(invalid_step("invalid stack after the callee", caller));
+    }
+    Ok(())
+}
+
+/// Check the invariants of the steps of a trace that the witness generation
+/// relies on, so that it doesn't panic on a malformed trace:
+/// - the stack is within its limit and holds the operands of the step unless the step fails;
+/// - the gas never exceeds `gas_limit`, the gas limit of the tx, nor increases in a call, and the
+///   memory accessed by a step succeeding could be paid with it;
+/// - the depth only increases by one after a call or a create, and the callee starts at pc 0 with
+///   an empty stack;
+/// - in a call, the pc of each step follows from the previous step, and its stack keeps the values
+///   below the ones the previous step popped;
+/// - after a callee returns, the caller resumes after the call with one more value on the stack
+///   than it had without the arguments of the call;
+/// - the trace ends in the root call with a halting or failing step.
+pub fn check_geth_trace(trace: &GethExecTrace, gas_limit: u64) -> Result<(), Error> {
+    let steps = &trace.struct_logs;
+    if let Some(step) = steps.first().filter(|step| step.depth != 1) {
+        return Err(invalid_step("first step not in the root call", step));
+    }
+    // The steps calling or creating the callee of each call being executed.
+    let mut callers: Vec<&GethExecStep> = vec![];
+    for (index, step) in steps.iter().enumerate() {
+        if step.gas.0 > gas_limit {
+            return Err(invalid_step("gas above the gas limit of the tx", step));
+        }
+        if step.stack.0.len() > STACK_LIMIT {
+            return Err(invalid_step("stack above its limit", step));
+        }
+        if step.error.is_none() && step.stack.0.len() < stack_reads(step.op) {
+            return Err(invalid_step("stack underflow without error", step));
+        }
+        let next = steps.get(index + 1);
+        // The memory of a step is paid unless it fails, and RETURN and
+        // REVERT pay for it whether the call fails or not.
+        let is_paid = next.map_or(false, |next| next.depth >= step.depth)
+            || matches!(step.op, OpcodeId::RETURN | OpcodeId::REVERT);
+        if step.error.is_none() && is_paid {
+            check_memory_ranges(step, gas_limit)?;
+        }
+        match next {
+            Some(next) => {
+                if next.depth == 0
+                    || next.depth > step.depth.saturating_add(1)
+                    || next.depth.saturating_add(1) < step.depth
+                {
+                    return Err(invalid_step("invalid depth of the next step", step));
+                }
+                if next.depth > step.depth {
+                    if !step.op.is_call_or_create() || step.error.is_some() {
+                        return Err(invalid_step("depth increased without a call", step));
+                    }
+                    if next.gas.0 > step.gas.0 {
+                        return Err(invalid_step("gas increased in the call", step));
+                    }
+                    if next.pc.0 != 0 || !next.stack.0.is_empty() {
+                        return Err(invalid_step(
+                            "callee not starting at pc 0 with an empty stack",
+                            step,
+                        ));
+                    }
+                    callers.push(step);
+                } else if next.depth < step.depth {
+                    let caller = callers
+                        .pop()
+                        .ok_or_else(|| invalid_step("return without a call", step))?;
+                    check_return(caller, next)?;
+                } else if step.error.is_none() {
+                    check_next_step(step, next)?;
+                }
+            }
+            None => {
+                if step.depth != 1 {
+                    return Err(invalid_step("trace ends in a callee", step));
+                }
+                if step.error.is_none() && !is_halt(step.op) && !may_fail_unreported(step.op) {
+                    return Err(invalid_step("trace ends without halting", step));
+                }
+            }
+        }
+        if index == 0 && (step.pc.0 != 0 || !step.stack.0.is_empty()) {
+            return Err(invalid_step(
+                "first step not at pc 0 with an empty stack",
+                step,
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl CircuitInputBuilder {
+    /// Fallible version of [`Self::handle_block`] for blocks with untrusted
+    /// txs. The traces are checked with [`check_geth_trace`] first, so that
+    /// the witness generation returns an error rather than panicking on them.
+    ///
+    /// The block may be partially handled after an error, so the builder
+    /// must be dropped.
+    pub fn try_handle_block(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[GethExecTrace],
+    ) -> Result<(), Error> {
+        if geth_traces.len() != eth_block.transactions.len() {
+            return Err(Error::InvalidGethExecTrace("expected one trace per tx"));
+        }
+        let num_txs = self.block.txs.len();
+        for (index, (tx, geth_trace)) in eth_block.transactions.iter().zip(geth_traces).enumerate()
+        {
+            let gas_limit = tx.gas.min(u64::MAX.into()).as_u64();
+            check_geth_trace(geth_trace, gas_limit)
+                .map_err(|err| err.with_context(ErrorContext::tx(num_txs + index)))?;
+        }
+        self.handle_block(eth_block, geth_traces)
+    }
+}
+
+#[cfg(test)]
+mod fallible_tests {
+    use super::*;
+    use eth_types::evm_types::{Gas, GasCost, Memory, ProgramCounter, Stack, Storage};
+
+    const GAS_LIMIT: u64 = 1_000_000;
+
+    fn step(op: OpcodeId, pc: usize, depth: u16, stack: Vec<Word>) -> GethExecStep {
+        GethExecStep {
+            pc: ProgramCounter(pc),
+            op,
+            gas: Gas(100),
+            gas_cost: GasCost(3),
+            refund: Gas(0),
+            depth,
+            error: None,
+            stack: Stack(stack),
+            memory: Memory::default(),
+            storage: Storage::default(),
+        }
+    }
+
+    fn words(values: &[u64]) -> Vec<Word> {
+        values.iter().copied().map(Word::from).collect()
+    }
+
+    fn check(steps: Vec<GethExecStep>) -> Result<(), Error> {
+        check_geth_trace(
+            &GethExecTrace {
+                l1_fee: 0,
+                gas: Gas(0),
+                failed: false,
+                return_value: String::new(),
+                struct_logs: steps,
+                validation: None,
+            },
+            GAS_LIMIT,
+        )
+    }
+
+    fn is_invalid_step(steps: Vec<GethExecStep>, expected: &str) {
+        assert!(
+            matches!(check(steps), Err(Error::InvalidGethExecStep(msg, _)) if msg == expected),
+            "{}",
+            expected
+        );
+    }
+
+    /// Steps of a root call calling a callee which returns, leaving `result`
+    /// on the stack of the caller.
+    fn call_steps(result: u64) -> Vec<GethExecStep> {
+        let call_args = words(&[0, 0, 0, 0, 0, 1, 1000]);
+        let mut steps: Vec<_> = (0..7)
+            .map(|i| step(OpcodeId::PUSH1, 2 * i, 1, call_args[..i].to_vec()))
+            .collect();
+        steps.push(step(OpcodeId::CALL, 14, 1, call_args));
+        steps.push(step(OpcodeId::STOP, 0, 2, vec![]));
+        steps.push(step(OpcodeId::STOP, 15, 1, words(&[result])));
+        steps
+    }
+
+    #[test]
+    fn check_well_formed_trace() {
+        assert!(check(vec![]).is_ok());
+        assert!(check(vec![
+            step(OpcodeId::PUSH1, 0, 1, vec![]),
+            step(OpcodeId::POP, 2, 1, words(&[1])),
+            step(OpcodeId::STOP, 3, 1, vec![]),
+        ])
+        .is_ok());
+        assert!(check(vec![
+            step(OpcodeId::PUSH1, 0, 1, vec![]),
+            step(OpcodeId::PUSH1, 2, 1, words(&[1])),
+            step(OpcodeId::DUP2, 4, 1, words(&[1, 2])),
+            step(OpcodeId::SWAP2, 5, 1, words(&[1, 2, 1])),
+            step(OpcodeId::ADD, 6, 1, words(&[1, 2, 1])),
+            step(OpcodeId::PUSH1, 7, 1, words(&[1, 3])),
+            step(OpcodeId::JUMP, 9, 1, words(&[1, 3, 12])),
+            step(OpcodeId::JUMPDEST, 12, 1, words(&[1, 3])),
+            step(OpcodeId::STOP, 13, 1, words(&[1, 3])),
+        ])
+        .is_ok());
+        assert!(check(call_steps(1)).is_ok());
+        // a failing step ends the trace, whatever its stack
+        let mut underflow = step(OpcodeId::ADD, 0, 1, vec![]);
+        underflow.error = Some("stack underflow (0 <=> 2)".to_string());
+        assert!(check(vec![underflow]).is_ok());
+        // so does a jump to an invalid destination, without an error
+        assert!(check(vec![
+            step(OpcodeId::PUSH1, 0, 1, vec![]),
+            step(OpcodeId::JUMP, 2, 1, words(&[5])),
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn check_malformed_trace() {
+        is_invalid_step(
+            vec![step(OpcodeId::STOP, 0, 2, vec![])],
+            "first step not in the root call",
+        );
+        is_invalid_step(
+            vec![step(OpcodeId::STOP, 1, 1, vec![])],
+            "first step not at pc 0 with an empty stack",
+        );
+        is_invalid_step(
+            vec![step(
+                OpcodeId::STOP,
+                0,
+                1,
+                vec![Word::zero(); STACK_LIMIT + 1],
+            )],
+            "stack above its limit",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::ADD, 0, 1, words(&[1])),
+                step(OpcodeId::STOP, 1, 1, vec![]),
+            ],
+            "stack underflow without error",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::STOP, 0, 2, vec![]),
+            ],
+            "depth increased without a call",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::CALL, 0, 1, vec![Word::zero(); 7]),
+                step(OpcodeId::STOP, 0, 3, vec![]),
+            ],
+            "invalid depth of the next step",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::STOP, 2, 0, vec![]),
+            ],
+            "invalid depth of the next step",
+        );
+        is_invalid_step(
+            vec![step(OpcodeId::PUSH1, 0, 1, vec![])],
+            "trace ends without halting",
+        );
+    }
+
+    #[test]
+    fn check_inconsistent_trace() {
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::STOP, 1, 1, words(&[1])),
+            ],
+            "invalid pc of the next step",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::PUSH1, 2, 1, words(&[1])),
+                step(OpcodeId::JUMPI, 4, 1, words(&[1, 7])),
+                step(OpcodeId::STOP, 5, 1, vec![]),
+            ],
+            "invalid pc of the next step",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::PUSH1, 2, 1, words(&[1])),
+                step(OpcodeId::STOP, 4, 1, words(&[2, 1])),
+            ],
+            "invalid stack of the next step",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::PUSH1, 2, 1, words(&[1])),
+                step(OpcodeId::SWAP1, 4, 1, words(&[1, 2])),
+                step(OpcodeId::STOP, 5, 1, words(&[1, 2])),
+            ],
+            "invalid stack of the next step",
+        );
+        is_invalid_step(
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::STOP, 2, 1, words(&[1])),
+                step(OpcodeId::STOP, 3, 1, words(&[1])),
+            ],
+            "step after a halt in the same call",
+        );
+        let mut gas_increase = vec![
+            step(OpcodeId::PUSH1, 0, 1, vec![]),
+            step(OpcodeId::STOP, 2, 1, words(&[1])),
+        ];
+        gas_increase[1].gas = Gas(101);
+        is_invalid_step(gas_increase, "gas increased in the call");
+        let mut above_gas_limit = vec![step(OpcodeId::STOP, 0, 1, vec![])];
+        above_gas_limit[0].gas = Gas(GAS_LIMIT + 1);
+        is_invalid_step(above_gas_limit, "gas above the gas limit of the tx");
+
+        let mut steps = call_steps(1);
+        steps[8].pc = ProgramCounter(1);
+        is_invalid_step(steps, "callee not starting at pc 0 with an empty stack");
+        let mut steps = call_steps(1);
+        steps[9].pc = ProgramCounter(16);
+        is_invalid_step(steps, "call not resumed after its callee");
+        let mut steps = call_steps(1);
+        steps[9].stack = Stack(words(&[1, 1]));
+        is_invalid_step(steps, "invalid stack after the callee");
+        let mut steps = call_steps(1);
+        steps.pop();
+        is_invalid_step(steps, "trace ends in a callee");
+    }
+
+    #[test]
+    fn check_memory_of_trace() {
+        let mstore = |offset: Word| {
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::PUSH32, 2, 1, words(&[1])),
+                step(OpcodeId::MSTORE, 35, 1, vec![Word::one(), offset]),
+                step(OpcodeId::STOP, 36, 1, vec![]),
+            ]
+        };
+        assert!(check(mstore(Word::from(1024))).is_ok());
+        is_invalid_step(
+            mstore(Word::from(u64::MAX)),
+            "memory expansion above the gas limit of the tx",
+        );
+        is_invalid_step(mstore(Word::MAX), "memory range above 64 bits");
+
+        // an empty range doesn't access memory, whatever its offset
+        let sha3 = |length: u64| {
+            vec![
+                step(OpcodeId::PUSH1, 0, 1, vec![]),
+                step(OpcodeId::PUSH32, 2, 1, words(&[length])),
+                step(OpcodeId::SHA3, 35, 1, vec![Word::from(length), Word::MAX]),
+                step(OpcodeId::STOP, 36, 1, words(&[1])),
+            ]
+        };
+        assert!(check(sha3(0)).is_ok());
+        is_invalid_step(sha3(1), "memory range above 64 bits");
+    }
+}
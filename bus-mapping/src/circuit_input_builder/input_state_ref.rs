@@ -2,7 +2,7 @@
 
 use super::{
     get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, Transaction,
+    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, ReturnData, Transaction,
     TransactionContext,
 };
 use crate::{
@@ -16,18 +16,19 @@ use crate::{
         StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
         RW,
     },
-    precompile::is_precompiled,
+    precompile::PrecompileRegistry,
     state_db::{CodeDB, StateDB},
     Error,
 };
 use eth_types::{
-    evm_types::{
-        gas_utils::memory_expansion_gas_cost, Gas, GasCost, MemoryAddress, OpcodeId, StackAddress,
-    },
-    Address, Bytecode, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256, U256,
+    evm_types::{Gas, GasCost, MemoryAddress, OpcodeId, StackAddress, MAX_CODE_SIZE},
+    Address, Bytecode, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256,
 };
 use ethers_core::utils::{get_contract_address, get_create2_address, keccak256};
-use std::cmp::max;
+use std::{
+    cmp::{max, Ordering},
+    sync::Arc,
+};
 
 /// Reference to the internal state of the CircuitInputBuilder in a particular
 /// [`ExecStep`].
@@ -44,6 +45,8 @@ pub struct CircuitInputStateRef<'a> {
     pub tx: &'a mut Transaction,
     /// Transaction Context
     pub tx_ctx: &'a mut TransactionContext,
+    /// Precompiles registered by the chain
+    pub precompiles: &'a PrecompileRegistry,
 }
 
 impl<'a> CircuitInputStateRef<'a> {
@@ -71,27 +74,33 @@ impl<'a> CircuitInputStateRef<'a> {
     }
 
     /// Create a new EndTx step
-    pub fn new_end_tx_step(&self) -> ExecStep {
+    pub fn new_end_tx_step(&self) -> Result<ExecStep, Error> {
         let prev_step = self
             .tx
             .steps()
             .last()
             .expect("steps should have at least one BeginTx step");
-        ExecStep {
+        Ok(ExecStep {
             exec_state: ExecState::EndTx,
             gas_left: if prev_step.error.is_none() {
-                let mut gas_left = prev_step.gas_left.0 - prev_step.gas_cost.0;
-                // handling for contract creation tx
+                let mut gas_left = prev_step
+                    .gas_left
+                    .0
+                    .checked_sub(prev_step.gas_cost.0)
+                    .ok_or(Error::InvalidGethExecTrace("gas cost above the gas left"))?;
+                // handling for contract creation tx, which deploys nothing
+                // when invalid
                 let call = self.tx.calls()[0].clone();
-                if call.is_create() {
+                if call.is_create() && !self.tx.is_invalid {
                     let code_hash = self.sdb.get_account(&call.address).1.code_hash;
-                    let bytecode_len = self.code(code_hash).unwrap().len() as u64;
+                    let bytecode_len = self.bytecode(code_hash)?.code.len() as u64;
                     let deposit_cost = bytecode_len * GasCost::CODE_DEPOSIT_BYTE_COST.as_u64();
-                    assert!(
-                        gas_left >= deposit_cost,
-                        "gas left {gas_left} is not enough for deposit cost {deposit_cost}"
-                    );
-                    gas_left -= deposit_cost;
+                    gas_left =
+                        gas_left
+                            .checked_sub(deposit_cost)
+                            .ok_or(Error::InvalidGethExecTrace(
+                                "gas left below the code deposit cost",
+                            ))?;
                 }
 
                 Gas(gas_left)
@@ -108,7 +117,7 @@ impl<'a> CircuitInputStateRef<'a> {
             },
             log_id: self.tx_ctx.log_id,
             ..Default::default()
-        }
+        })
     }
 
     /// Push an [`Operation`](crate::operation::Operation) into the
@@ -545,15 +554,10 @@ impl<'a> CircuitInputStateRef<'a> {
             return Err(Error::AccountNotFound(sender));
         }
         let mut sender_balance_prev = sender_account.balance;
-        debug_assert!(
-            sender_account.balance >= value + fee.unwrap_or_default(),
-            "invalid amount balance {:?} value {:?} fee {:?}",
-            sender_balance_prev,
-            value,
-            fee
-        );
         if let Some(fee) = fee {
-            let sender_balance = sender_balance_prev - fee;
+            let sender_balance = sender_balance_prev
+                .checked_sub(fee)
+                .ok_or(Error::InternalError("sender balance is insufficient"))?;
             log::trace!(
                 "sender balance update with fee (not reversible): {:?} {:?}->{:?}",
                 sender,
@@ -572,7 +576,9 @@ impl<'a> CircuitInputStateRef<'a> {
             );
             sender_balance_prev = sender_balance;
         }
-        let sender_balance = sender_balance_prev - value;
+        let sender_balance = sender_balance_prev
+            .checked_sub(value)
+            .ok_or(Error::InternalError("sender balance is insufficient"))?;
         log::trace!(
             "sender balance update with value: {:?} {:?}->{:?}",
             sender,
@@ -608,7 +614,9 @@ impl<'a> CircuitInputStateRef<'a> {
 
         let (_found, receiver_account) = self.sdb.get_account(&receiver);
         let receiver_balance_prev = receiver_account.balance;
-        let receiver_balance = receiver_account.balance + value;
+        let receiver_balance = receiver_balance_prev
+            .checked_add(value)
+            .ok_or(Error::InternalError("receiver balance overflows 256 bits"))?;
         log::trace!(
             "receiver balance update: {:?} {:?}->{:?}",
             receiver,
@@ -658,6 +666,47 @@ impl<'a> CircuitInputStateRef<'a> {
             .ok_or(Error::CodeNotFound(code_hash))
     }
 
+    /// Copy `length` bytes of the code for the given code hash from
+    /// `src_offset` to `dst_offset` in the memory of the current call,
+    /// without copying the code out of the code DB.
+    pub fn copy_code_to_memory(
+        &mut self,
+        code_hash: H256,
+        dst_offset: Word,
+        src_offset: Word,
+        length: Word,
+    ) -> Result<(), Error> {
+        let code = self
+            .code_db
+            .get(&code_hash)
+            .ok_or(Error::CodeNotFound(code_hash))?;
+        self.tx_ctx
+            .call_ctx_mut()?
+            .memory
+            .copy_from(dst_offset, src_offset, length, code);
+        Ok(())
+    }
+
+    /// Fetch the analyzed code for the given code hash from the code DB,
+    /// without copying it when it was already read in the block.
+    pub fn bytecode(&self, code_hash: H256) -> Result<Arc<Bytecode>, Error> {
+        self.code_db
+            .bytecode(&code_hash)
+            .ok_or(Error::CodeNotFound(code_hash))
+    }
+
+    /// Check whether `dest` is a valid jump destination in the code of the
+    /// current call.
+    pub fn is_jumpdest(&self, dest: Word) -> Result<bool, Error> {
+        if dest > Word::from(u64::MAX) {
+            return Ok(false);
+        }
+        let bytecode = self.bytecode(self.call()?.code_hash)?;
+        Ok(bytecode.get(dest.as_usize()).map_or(false, |element| {
+            element.is_code && element.value == OpcodeId::JUMPDEST.as_u8()
+        }))
+    }
+
     /// Reference to the caller's Call
     pub fn caller(&self) -> Result<&Call, Error> {
         self.tx_ctx
@@ -793,11 +842,19 @@ impl<'a> CircuitInputStateRef<'a> {
             .tx_ctx
             .call_is_success
             .get(self.tx.calls().len())
-            .unwrap();
+            .ok_or_else(|| {
+                Error::InvalidGethExecStep("call without a result", Box::new(step.clone()))
+            })?;
         let kind = CallKind::try_from(step.op)?;
         let caller = self.call()?;
         let caller_ctx = self.call_ctx()?;
 
+        // The context of the callee is derived from the context of the caller
+        // only, never from the code address, so that chains of CALLCODE and
+        // DELEGATECALL compose: a CALLCODE runs in the storage of the
+        // contract running it, which becomes the msg.sender, while a
+        // DELEGATECALL keeps the msg.sender, the storage and the value of the
+        // caller, whatever the kind of the caller.
         let (caller_address, address, value) = match kind {
             CallKind::Call => (
                 caller.address,
@@ -832,14 +889,22 @@ impl<'a> CircuitInputStateRef<'a> {
                     }
                     _ => address,
                 };
-                if is_precompiled(&code_address) {
+                if self.precompiles.is_precompiled(&code_address) {
                     (CodeSource::Address(code_address), CodeDB::empty_code_hash())
                 } else {
                     let (found, account) = self.sdb.get_account(&code_address);
                     if !found {
                         (CodeSource::Address(code_address), CodeDB::empty_code_hash())
                     } else {
-                        (CodeSource::Address(code_address), account.code_hash)
+                        #[cfg(feature = "prague")]
+                        let (code_address, code_hash) = self.code_db.resolve_delegation(
+                            self.sdb,
+                            code_address,
+                            account.code_hash,
+                        );
+                        #[cfg(not(feature = "prague"))]
+                        let code_hash = account.code_hash;
+                        (CodeSource::Address(code_address), code_hash)
                     }
                 }
             }
@@ -932,6 +997,14 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TransientStorage, idx) => {
+                let operation = &self.block.container.transient_storage[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TransientStorage(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -964,19 +1037,28 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
+            OpEnum::TransientStorage(op) => {
+                self.sdb
+                    .set_transient_storage(&op.address, &op.key, &op.value);
+            }
             _ => unreachable!(),
         };
     }
 
-    /// Handle a reversion group
-    pub fn handle_reversion(&mut self) {
+    /// Handle the reversion group of the call ending with failure in `step`.
+    /// The reversions of the reversible writes of a step are added to its
+    /// bus-mapping instance, `step` being the one not pushed into the tx yet.
+    pub fn handle_reversion(&mut self, step: &mut ExecStep) -> Result<(), Error> {
         let reversion_group = self
             .tx_ctx
             .reversion_groups
             .pop()
-            .expect("reversion_groups should not be empty for non-persistent call");
+            .ok_or(Error::InternalError(
+                "reversion_groups should not be empty for non-persistent call",
+            ))?;
 
         // Apply reversions
+        let steps_len = self.tx.steps().len();
         for (step_index, op_ref) in reversion_group.op_refs.iter().rev().copied() {
             if let Some(op) = self.get_rev_op_by_ref(&op_ref) {
                 self.check_apply_op(&op);
@@ -986,9 +1068,17 @@ impl<'a> CircuitInputStateRef<'a> {
                     false,
                     op,
                 );
-                self.tx.steps_mut()[step_index]
-                    .bus_mapping_instance
-                    .push(rev_op_ref);
+                match step_index.cmp(&steps_len) {
+                    Ordering::Less => self.tx.steps_mut()[step_index]
+                        .bus_mapping_instance
+                        .push(rev_op_ref),
+                    Ordering::Equal => step.bus_mapping_instance.push(rev_op_ref),
+                    Ordering::Greater => {
+                        return Err(Error::InternalError(
+                            "reversible write recorded in a step after the current one",
+                        ))
+                    }
+                }
             }
         }
 
@@ -998,6 +1088,7 @@ impl<'a> CircuitInputStateRef<'a> {
             self.tx.calls_mut()[call_idx].rw_counter_end_of_reversion =
                 rwc - reversible_write_counter_offset;
         }
+        Ok(())
     }
 
     /// Handle a restore and a return step caused by any opcode that causes a return to the
@@ -1008,67 +1099,89 @@ impl<'a> CircuitInputStateRef<'a> {
         geth_steps: &[GethExecStep],
         need_restore: bool,
     ) -> Result<(), Error> {
-        if need_restore {
-            self.handle_restore_context(exec_step, geth_steps)?;
-        }
+        let return_data = self.call_return_data(exec_step, &geth_steps[0])?;
+        self.handle_return_with_data(exec_step, geth_steps, need_restore, return_data)
+    }
 
-        let step = &geth_steps[0];
-        // handle return_data
-        let (return_data_offset, return_data_length) = {
-            if !self.call()?.is_root {
-                let (offset, length) = match step.op {
-                    OpcodeId::RETURN | OpcodeId::REVERT => {
-                        let (offset, length) = if step.error.is_some()
-                            || (self.call()?.is_create() && step.op == OpcodeId::RETURN)
-                        {
-                            (0, 0)
-                        } else {
-                            (
-                                step.stack.nth_last(0)?.low_u64() as usize,
-                                step.stack.nth_last(1)?.as_usize(),
-                            )
-                        };
-                        // At the moment it conflicts with `call_ctx` and `caller_ctx`.
-                        let callee_memory = self.call_ctx()?.memory.clone();
-                        let caller_ctx = self.caller_ctx_mut()?;
-                        caller_ctx.return_data.resize(length, 0);
-                        if length != 0 {
-                            caller_ctx.return_data[0..length]
-                                .copy_from_slice(&callee_memory.0[offset..offset + length]);
-                        }
-                        (offset, length)
-                    }
-                    OpcodeId::CALL
-                    | OpcodeId::CALLCODE
-                    | OpcodeId::STATICCALL
-                    | OpcodeId::DELEGATECALL => {
-                        if self
-                            .call()?
-                            .code_address()
-                            .map(|ref addr| is_precompiled(addr))
-                            .unwrap_or(false)
-                        {
-                            let caller_ctx = self.caller_ctx_mut()?;
-                            (0, caller_ctx.return_data.len())
-                        } else {
-                            let caller_ctx = self.caller_ctx_mut()?;
-                            caller_ctx.return_data.truncate(0);
-                            (0, 0)
-                        }
-                    }
-                    _ => {
-                        let caller_ctx = self.caller_ctx_mut()?;
-                        caller_ctx.return_data.truncate(0);
-                        (0, 0)
-                    }
-                };
+    /// Handle the return of a call to a precompile, whose output is `output`,
+    /// like [`Self::handle_return`].
+    pub fn handle_precompile_return(
+        &mut self,
+        exec_step: &mut ExecStep,
+        geth_steps: &[GethExecStep],
+        need_restore: bool,
+        output: Vec<u8>,
+    ) -> Result<(), Error> {
+        let return_data = ReturnData {
+            callee_id: self.call()?.call_id,
+            offset: 0,
+            data: output,
+        };
+        self.handle_return_with_data(exec_step, geth_steps, need_restore, return_data)
+    }
 
-                (offset.try_into().unwrap(), length.try_into().unwrap())
-            } else {
-                (0, 0)
-            }
+    /// Returns the data returned by the current call, which ends at `step`.
+    /// Only a RETURN or a REVERT without error returns data, except for the
+    /// RETURN of a CREATE or CREATE2, whose return data is empty (EIP-211).
+    fn call_return_data(
+        &self,
+        exec_step: &ExecStep,
+        step: &GethExecStep,
+    ) -> Result<ReturnData, Error> {
+        let call = self.call()?;
+        if call.is_root {
+            return Ok(ReturnData::default());
+        }
+        let returns_data = match step.op {
+            OpcodeId::RETURN => !call.is_create(),
+            OpcodeId::REVERT => true,
+            _ => false,
         };
+        // A callee ending at the step of its caller didn't run any code,
+        // unless it is a precompile.
+        let ran_code = call.depth == usize::from(step.depth)
+            || call
+                .code_address()
+                .map_or(false, |ref addr| self.precompiles.is_precompiled(addr));
+        if !returns_data || step.error.is_some() || exec_step.error.is_some() {
+            return Ok(ReturnData {
+                callee_id: if ran_code { call.call_id } else { 0 },
+                ..ReturnData::default()
+            });
+        }
+
+        let offset = step.stack.nth_last(0)?;
+        let length = step.stack.nth_last(1)?;
+        // This is the convention we are using for memory addresses so that there is no
+        // memory expansion cost when the length is 0.
+        if length.is_zero() {
+            return Ok(ReturnData {
+                callee_id: call.call_id,
+                ..ReturnData::default()
+            });
+        }
+        Ok(ReturnData {
+            callee_id: call.call_id,
+            offset: offset.low_u64(),
+            data: self
+                .call_ctx()?
+                .memory
+                .read_chunk(offset.low_u64().into(), length.low_u64().into()),
+        })
+    }
 
+    fn handle_return_with_data(
+        &mut self,
+        exec_step: &mut ExecStep,
+        geth_steps: &[GethExecStep],
+        need_restore: bool,
+        return_data: ReturnData,
+    ) -> Result<(), Error> {
+        if need_restore {
+            self.restore_context(exec_step, geth_steps, &return_data)?;
+        }
+
+        let step = &geth_steps[0];
         let call = self.call()?.clone();
         let call_ctx = self.call_ctx()?;
         let call_success_create: bool =
@@ -1094,28 +1207,17 @@ impl<'a> CircuitInputStateRef<'a> {
 
         // Handle reversion if this call doesn't end successfully
         if !call.is_success {
-            self.handle_reversion();
+            self.handle_reversion(exec_step)?;
         }
 
-        // If current call has caller.
+        // If current call has caller, it becomes its last callee.
         if let Ok(caller) = self.caller_mut() {
-            caller.last_callee_id = call.call_id;
-            // EIP-211 CREATE/CREATE2 call successful case should set RETURNDATASIZE = 0
-            if call_success_create {
-                caller.last_callee_return_data_length = 0u64;
-                caller.last_callee_return_data_offset = 0u64;
-            } else {
-                caller.last_callee_return_data_length = return_data_length;
-                caller.last_callee_return_data_offset = return_data_offset;
-            }
+            caller.last_callee_id = return_data.callee_id;
+            caller.last_callee_return_data_offset = return_data.offset;
+            caller.last_callee_return_data_length = return_data.length();
         }
-
-        // If current call has caller_ctx (has caller)
         if let Ok(caller_ctx) = self.caller_ctx_mut() {
-            // EIP-211 CREATE/CREATE2 call successful case should set RETURNDATASIZE = 0
-            if call_success_create {
-                caller_ctx.return_data.truncate(0);
-            }
+            caller_ctx.return_data = return_data;
         }
 
         self.tx_ctx.pop_call_ctx();
@@ -1128,6 +1230,21 @@ impl<'a> CircuitInputStateRef<'a> {
         &mut self,
         exec_step: &mut ExecStep,
         steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let geth_step = steps
+            .get(0)
+            .ok_or(Error::InternalError("invalid index 0"))?;
+        let return_data = self.call_return_data(exec_step, geth_step)?;
+        self.restore_context(exec_step, steps, &return_data)
+    }
+
+    /// Bus mapping for the RestoreContextGadget, with the `return_data` left to
+    /// the caller.
+    fn restore_context(
+        &mut self,
+        exec_step: &mut ExecStep,
+        steps: &[GethExecStep],
+        return_data: &ReturnData,
     ) -> Result<(), Error> {
         let call = self.call()?.clone();
         let geth_step = steps
@@ -1192,19 +1309,10 @@ impl<'a> CircuitInputStateRef<'a> {
         let gas_refund = if exec_step.error.is_some() || exec_step.is_precompiled() {
             0
         } else {
-            let curr_memory_word_size = (exec_step.memory_size as u64) / 32;
-            let next_memory_word_size = if !last_callee_return_data_length.is_zero() {
-                std::cmp::max(
-                    (last_callee_return_data_offset + last_callee_return_data_length + 31).as_u64()
-                        / 32,
-                    curr_memory_word_size,
-                )
-            } else {
-                curr_memory_word_size
-            };
-
-            let memory_expansion_gas_cost =
-                memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
+            let (_, memory_expansion_gas_cost) = exec_step.memory_expansion([(
+                last_callee_return_data_offset.as_u64(),
+                last_callee_return_data_length.as_u64(),
+            )]);
             let code_deposit_cost = if call.is_create() && call.is_success {
                 GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * last_callee_return_data_length.as_u64()
             } else {
@@ -1240,26 +1348,15 @@ impl<'a> CircuitInputStateRef<'a> {
             self.call_context_read(exec_step, caller.call_id, field, value);
         }
 
-        // EIP-211: CREATE/CREATE2 call successful case should set RETURNDATASIZE = 0
-        let discard_return_data =
-            call.is_create() && geth_step.op == OpcodeId::RETURN || exec_step.error.is_some();
         for (field, value) in [
             (CallContextField::LastCalleeId, call.call_id.into()),
             (
                 CallContextField::LastCalleeReturnDataOffset,
-                if discard_return_data {
-                    U256::zero()
-                } else {
-                    last_callee_return_data_offset
-                },
+                return_data.offset.into(),
             ),
             (
                 CallContextField::LastCalleeReturnDataLength,
-                if discard_return_data {
-                    U256::zero()
-                } else {
-                    last_callee_return_data_length
-                },
+                return_data.length().into(),
             ),
         ] {
             self.call_context_write(exec_step, caller.call_id, field, value);
@@ -1269,9 +1366,10 @@ impl<'a> CircuitInputStateRef<'a> {
     }
 
     /// Push a copy event to the state.
-    pub fn push_copy(&mut self, step: &mut ExecStep, event: CopyEvent) {
-        step.copy_rw_counter_delta += event.rw_counter_delta();
+    pub fn push_copy(&mut self, step: &mut ExecStep, event: CopyEvent) -> Result<(), Error> {
+        step.copy_rw_counter_delta += event.rw_counter_delta()?;
         self.block.add_copy_event(event);
+        Ok(())
     }
 
     /// Push a exponentiation event to the state.
@@ -1289,7 +1387,16 @@ impl<'a> CircuitInputStateRef<'a> {
         }
 
         if let Some(error) = &step.error {
-            return Ok(Some(get_step_reported_error(&step.op, error)));
+            let error = get_step_reported_error(&step.op, error).ok_or_else(|| {
+                Error::InvalidGethExecStep("unknown error", Box::new(step.clone()))
+            })?;
+            if error == ExecError::InvalidJump && self.is_jumpdest(step.stack.last()?)? {
+                return Err(Error::InvalidGethExecStep(
+                    "invalid jump reported to a valid JUMPDEST",
+                    Box::new(step.clone()),
+                ));
+            }
+            return Ok(Some(error));
         }
 
         let call = self.call()?;
@@ -1333,10 +1440,21 @@ impl<'a> CircuitInputStateRef<'a> {
             if !matches!(step.op, OpcodeId::RETURN) {
                 // Without calling RETURN
                 return Ok(match step.op {
-                    OpcodeId::JUMP | OpcodeId::JUMPI => Some(ExecError::InvalidJump),
+                    OpcodeId::JUMP | OpcodeId::JUMPI => {
+                        // Without an error reported by geth, a jump only
+                        // fails on a destination that isn't a JUMPDEST.
+                        if self.is_jumpdest(step.stack.last()?)? {
+                            return Err(Error::InvalidGethExecStep(
+                                "failed jump to a valid JUMPDEST",
+                                Box::new(step.clone()),
+                            ));
+                        }
+                        Some(ExecError::InvalidJump)
+                    }
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
                     // Break write protection (CALL with value will be handled below)
                     OpcodeId::SSTORE
+                    | OpcodeId::TSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
                     | OpcodeId::SELFDESTRUCT
@@ -1366,14 +1484,16 @@ impl<'a> CircuitInputStateRef<'a> {
                 if call.is_create() {
                     let offset = step.stack.nth_last(0)?;
                     let length = step.stack.nth_last(1)?;
-                    if length > Word::from(0x6000u64) {
+                    if length > Word::from(MAX_CODE_SIZE) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > Word::zero()
                         && !call_ctx.memory.is_empty()
                         && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
                     {
                         return Ok(Some(ExecError::InvalidCreationCode));
-                    } else if Word::from(200u64) * length > Word::from(step.gas.0) {
+                    } else if Word::from(GasCost::CODE_DEPOSIT_BYTE_COST.as_u64()) * length
+                        > Word::from(step.gas.0)
+                    {
                         return Ok(Some(ExecError::CodeStoreOutOfGas));
                     } else {
                         return Err(Error::UnexpectedExecStepError(
@@ -1452,11 +1572,20 @@ impl<'a> CircuitInputStateRef<'a> {
 
             //  Nonce Uint overflow
             if account.nonce >= u64::MAX.into() {
-                return Ok(Some(ExecError::NonceUintOverflow(match step.op {
-                    OpcodeId::CREATE => NonceUintOverflowError::Create,
-                    OpcodeId::CREATE2 => NonceUintOverflowError::Create2,
-                    op => unreachable!("Nonce Uint overflow error unexpected for opcode: {:?}", op),
-                })));
+                match step.op {
+                    OpcodeId::CREATE => {
+                        return Ok(Some(ExecError::NonceUintOverflow(
+                            NonceUintOverflowError::Create,
+                        )))
+                    }
+                    OpcodeId::CREATE2 => {
+                        return Ok(Some(ExecError::NonceUintOverflow(
+                            NonceUintOverflowError::Create2,
+                        )))
+                    }
+                    // the nonce of the caller doesn't limit its calls
+                    _ => {}
+                }
             }
 
             // Address collision
@@ -1491,7 +1620,7 @@ impl<'a> CircuitInputStateRef<'a> {
                 OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL
             ) {
                 let code_address = step.stack.nth_last(1)?.to_address();
-                if is_precompiled(&code_address) {
+                if self.precompiles.is_precompiled(&code_address) {
                     // Log the precompile address and gas left. Since this failure is mainly caused
                     // by out of gas.
                     log::trace!(
@@ -0,0 +1,463 @@
+//! Audit of the rw counters of the witness: each step must reference the
+//! operations it generated with counters following its own, and each counter
+//! must be used by a single operation. A violation of these invariants only
+//! shows up as a permutation failure of the state circuit, far from the step
+//! which caused it, so [`audit_rw_counters`] reports the first step breaking
+//! them instead.
+//!
+//! The operations of a step are the ones it generated, at the counters from
+//! [`ExecStep::rwc`] on, followed by the reversions of its reversible writes
+//! if its call reverted, which are generated later at the end of the call.
+
+use super::{Block, ExecState, ExecStep};
+use crate::{
+    exec_trace::OperationRef,
+    operation::{OperationContainer, Target, RW},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// Position of a violation in the block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RwAuditLocation {
+    /// Step of a tx
+    Step {
+        /// Index of the tx in [`Block::txs`]
+        tx_index: usize,
+        /// Index of the step in the tx
+        step_index: usize,
+        /// Execution state of the step
+        exec_state: ExecState,
+    },
+    /// Last EndBlock step
+    EndBlock,
+    /// Counters of the whole block
+    Block,
+}
+
+/// Invariant of the rw counters broken by the witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RwAuditViolationKind {
+    /// Reference to an operation which isn't in the container
+    DanglingRef(OperationRef),
+    /// Operation referenced twice, by this step or another one
+    DuplicateRef(OperationRef),
+    /// The first operation of the step isn't at the counter of the step
+    FirstRwc {
+        /// Counter of the step
+        expected: usize,
+        /// Counter of its first operation
+        found: usize,
+    },
+    /// The step starts before the end of the operations of the previous step
+    StepRwcBehind {
+        /// Counter following the operations of the previous step
+        expected_at_least: usize,
+        /// Counter of the step
+        found: usize,
+    },
+    /// Operation after the ones generated by the step which isn't the
+    /// reversion of one of them
+    InvalidReversion(OperationRef),
+    /// The step has more reversions than reversible writes
+    TooManyReversions {
+        /// Number of reversions of the step
+        reversions: usize,
+        /// Reversible write counter delta of the step
+        delta: usize,
+    },
+    /// The number of reversible writes differs from the reversible write
+    /// counter delta of the step
+    ReversibleWrites {
+        /// Number of reversible writes of the step
+        writes: usize,
+        /// Reversible write counter delta of the step
+        delta: usize,
+    },
+    /// The reversible write counter of a call decreased between two of its
+    /// steps
+    ReversibleCounterDecreased {
+        /// Index of the call in the tx
+        call_index: usize,
+        /// Counter of the previous step of the call
+        before: usize,
+        /// Counter of the step
+        after: usize,
+    },
+    /// Counter used by two operations
+    DuplicateRwc(usize),
+    /// Counter below the last one used by no operation
+    RwcGap(usize),
+}
+
+/// First violation of the invariants of the rw counters found in a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RwAuditViolation {
+    /// Where the invariant is broken
+    pub location: RwAuditLocation,
+    /// Which invariant is broken
+    pub kind: RwAuditViolationKind,
+}
+
+impl fmt::Display for RwAuditViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            RwAuditLocation::Step {
+                tx_index,
+                step_index,
+                exec_state,
+            } => write!(
+                f,
+                "tx {} step {} ({:?}): ",
+                tx_index, step_index, exec_state
+            )?,
+            RwAuditLocation::EndBlock => write!(f, "end block step: ")?,
+            RwAuditLocation::Block => write!(f, "block: ")?,
+        }
+        write!(f, "{:?}", self.kind)
+    }
+}
+
+impl std::error::Error for RwAuditViolation {}
+
+/// Counter, direction and reversibility of the referenced operation.
+pub(super) fn op_info(
+    container: &OperationContainer,
+    op_ref: &OperationRef,
+) -> Option<(usize, RW, bool)> {
+    macro_rules! info {
+        ($ops:ident) => {
+            container
+                .$ops
+                .get(op_ref.as_usize())
+                .map(|op| (op.rwc().0, op.rw(), op.reversible()))
+        };
+    }
+    match op_ref.target() {
+        Target::Start => info!(start),
+        Target::Memory => info!(memory),
+        Target::Stack => info!(stack),
+        Target::Storage => info!(storage),
+        Target::TxAccessListAccount => info!(tx_access_list_account),
+        Target::TxAccessListAccountStorage => info!(tx_access_list_account_storage),
+        Target::TxRefund => info!(tx_refund),
+        Target::Account => info!(account),
+        Target::CallContext => info!(call_context),
+        Target::TxReceipt => info!(tx_receipt),
+        Target::TxLog => info!(tx_log),
+        Target::TransientStorage => info!(transient_storage),
+    }
+}
+
+#[derive(Default)]
+struct Auditor {
+    refs: HashSet<OperationRef>,
+    rwcs: HashSet<usize>,
+    /// Counter following the operations generated by the previous step
+    next_rwc: usize,
+    /// Reversible write counter of the last step of each call of the tx
+    reversible_counters: HashMap<usize, usize>,
+}
+
+impl Auditor {
+    fn step(
+        &mut self,
+        container: &OperationContainer,
+        step: &ExecStep,
+    ) -> Result<(), RwAuditViolationKind> {
+        let mut ops = Vec::with_capacity(step.bus_mapping_instance.len());
+        for op_ref in &step.bus_mapping_instance {
+            // The padding operations of the EndBlock step have no counter of
+            // their own.
+            if op_ref.target() == Target::Start {
+                continue;
+            }
+            let info =
+                op_info(container, op_ref).ok_or(RwAuditViolationKind::DanglingRef(*op_ref))?;
+            if !self.refs.insert(*op_ref) {
+                return Err(RwAuditViolationKind::DuplicateRef(*op_ref));
+            }
+            if !self.rwcs.insert(info.0) {
+                return Err(RwAuditViolationKind::DuplicateRwc(info.0));
+            }
+            ops.push((*op_ref, info));
+        }
+
+        if step.rwc.0 < self.next_rwc {
+            return Err(RwAuditViolationKind::StepRwcBehind {
+                expected_at_least: self.next_rwc,
+                found: step.rwc.0,
+            });
+        }
+        let generated = ops
+            .iter()
+            .enumerate()
+            .take_while(|(index, (_, (rwc, _, _)))| *rwc == step.rwc.0 + index)
+            .count();
+        if let Some((_, (rwc, _, _))) = ops.first().filter(|_| generated == 0) {
+            return Err(RwAuditViolationKind::FirstRwc {
+                expected: step.rwc.0,
+                found: *rwc,
+            });
+        }
+        self.next_rwc = step.rwc.0 + generated;
+
+        let (generated, reversions) = ops.split_at(generated);
+        let delta = step.reversible_write_counter_delta;
+        if let Some((op_ref, _)) = reversions.iter().find(|(_, (rwc, rw, reversible))| {
+            !rw.is_write() || *reversible || *rwc < self.next_rwc
+        }) {
+            return Err(RwAuditViolationKind::InvalidReversion(*op_ref));
+        }
+        if reversions.len() > delta {
+            return Err(RwAuditViolationKind::TooManyReversions {
+                reversions: reversions.len(),
+                delta,
+            });
+        }
+        let writes = generated
+            .iter()
+            .filter(|(_, (_, rw, reversible))| rw.is_write() && *reversible)
+            .count();
+        if writes != delta {
+            return Err(RwAuditViolationKind::ReversibleWrites { writes, delta });
+        }
+
+        let counter = self
+            .reversible_counters
+            .entry(step.call_index)
+            .or_insert(step.reversible_write_counter);
+        if step.reversible_write_counter < *counter {
+            return Err(RwAuditViolationKind::ReversibleCounterDecreased {
+                call_index: step.call_index,
+                before: *counter,
+                after: step.reversible_write_counter,
+            });
+        }
+        *counter = step.reversible_write_counter;
+        Ok(())
+    }
+}
+
+/// Check the rw counters of the steps of the block, returning the first
+/// violation of their invariants:
+/// - each operation is referenced by a single step, and each counter is used by a single operation,
+/// - the operations generated by a step follow its counter, and the steps follow each other,
+/// - the operations after them are the reversions of its reversible writes,
+/// - the reversible write counter of each call only increases.
+///
+/// The EndBlock step is checked if the block was finalized.
+pub fn audit_rw_counters(block: &Block) -> Result<(), RwAuditViolation> {
+    let mut auditor = Auditor::default();
+    for (tx_index, tx) in block.txs.iter().enumerate() {
+        auditor.reversible_counters.clear();
+        for (step_index, step) in tx.steps().iter().enumerate() {
+            auditor
+                .step(&block.container, step)
+                .map_err(|kind| RwAuditViolation {
+                    location: RwAuditLocation::Step {
+                        tx_index,
+                        step_index,
+                        exec_state: step.exec_state.clone(),
+                    },
+                    kind,
+                })?;
+        }
+    }
+    let end_block = &block.block_steps.end_block_last;
+    if !end_block.bus_mapping_instance.is_empty() {
+        auditor.reversible_counters.clear();
+        auditor
+            .step(&block.container, end_block)
+            .map_err(|kind| RwAuditViolation {
+                location: RwAuditLocation::EndBlock,
+                kind,
+            })?;
+    }
+
+    if let Some(rwc) = (1..auditor.next_rwc).find(|rwc| !auditor.rwcs.contains(rwc)) {
+        return Err(RwAuditViolation {
+            location: RwAuditLocation::Block,
+            kind: RwAuditViolationKind::RwcGap(rwc),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod rw_audit_tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord, Word};
+    use mock::{
+        call_tree::{call_tree, CallNode},
+        TestContext, MOCK_ACCOUNTS,
+    };
+
+    fn handle_block(block: GethData) -> Block {
+        let block_data = BlockData::new_from_geth_data(block);
+        let mut builder = block_data.new_circuit_input_builder();
+        builder
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .unwrap();
+        builder.block
+    }
+
+    /// Block of a tx calling a contract which writes to storage and reverts.
+    fn block() -> Block {
+        let code_a = bytecode! {
+            PUSH1(0x0) // retLength
+            PUSH1(0x0) // retOffset
+            PUSH1(0x0) // argsLength
+            PUSH1(0x0) // argsOffset
+            PUSH1(0x0) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            POP
+            PUSH1(0x2a)
+            PUSH1(0x01)
+            SSTORE
+            STOP
+        };
+        let code_b = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        };
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 60));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        handle_block(block)
+    }
+
+    #[test]
+    fn audit_reverted_call() {
+        assert_eq!(audit_rw_counters(&block()), Ok(()));
+    }
+
+    #[test]
+    fn audit_swapped_ops() {
+        let mut block = block();
+        let (step_index, step) = block.txs[0]
+            .steps_mut()
+            .iter_mut()
+            .enumerate()
+            .find(|(_, step)| step.bus_mapping_instance.len() >= 2)
+            .unwrap();
+        step.bus_mapping_instance.swap(0, 1);
+        let exec_state = step.exec_state.clone();
+        let violation = audit_rw_counters(&block).unwrap_err();
+        assert_eq!(
+            violation.location,
+            RwAuditLocation::Step {
+                tx_index: 0,
+                step_index,
+                exec_state,
+            }
+        );
+    }
+
+    #[test]
+    fn audit_missing_op() {
+        let mut block = block();
+        let step = block.txs[0]
+            .steps_mut()
+            .iter_mut()
+            .find(|step| step.bus_mapping_instance.len() >= 2)
+            .unwrap();
+        step.bus_mapping_instance.pop();
+        assert!(audit_rw_counters(&block).is_err());
+    }
+
+    /// Checks that the reversion of each write of the SSTORE steps of the
+    /// reverted calls is at the counter the EVM circuit expects, which is the
+    /// `rw_counter_end_of_reversion` of the call minus the reversible write
+    /// counter of the write.
+    fn check_call_tree<const NACC: usize>(tree: CallNode) {
+        let block = handle_block(call_tree::<NACC>(&tree).unwrap().into());
+        assert_eq!(audit_rw_counters(&block), Ok(()));
+
+        let tx = &block.txs[0];
+        let mut reverted_sstores = 0;
+        for step in tx.steps() {
+            let call = &tx.calls()[step.call_index];
+            if call.is_persistent || step.exec_state != ExecState::Op(OpcodeId::SSTORE) {
+                continue;
+            }
+            let ops = step
+                .bus_mapping_instance
+                .iter()
+                .map(|op_ref| op_info(&block.container, op_ref).unwrap())
+                .collect::<Vec<_>>();
+            let generated = ops
+                .iter()
+                .enumerate()
+                .take_while(|(index, (rwc, _, _))| *rwc == step.rwc.0 + index)
+                .count();
+            let (generated, reversions) = ops.split_at(generated);
+            let writes = generated
+                .iter()
+                .filter(|(_, rw, reversible)| rw.is_write() && *reversible)
+                .count();
+            assert_eq!(reversions.len(), writes);
+            // The writes are reverted from the last one.
+            for (index, (rwc, _, _)) in reversions.iter().rev().enumerate() {
+                assert_eq!(
+                    *rwc,
+                    call.rw_counter_end_of_reversion - (step.reversible_write_counter + index)
+                );
+            }
+            reverted_sstores += 1;
+        }
+        assert!(reverted_sstores > 0);
+    }
+
+    #[test]
+    fn audit_reverted_call_tree() {
+        // Reverted calls under successful ones under reverted ones, with
+        // successful siblings before and after them.
+        check_call_tree::<11>(CallNode::revert(vec![
+            CallNode::stop(vec![CallNode::revert(vec![]), CallNode::stop(vec![])]),
+            CallNode::revert(vec![CallNode::stop(vec![CallNode::stop(vec![])])]),
+            CallNode::stop(vec![CallNode::revert(vec![CallNode::stop(vec![])])]),
+        ]));
+    }
+
+    #[test]
+    fn audit_alternating_call_chain() {
+        check_call_tree::<7>(CallNode::stop(vec![CallNode::revert(vec![
+            CallNode::stop(vec![CallNode::revert(vec![CallNode::stop(vec![
+                CallNode::revert(vec![]),
+            ])])]),
+        ])]));
+    }
+
+    #[test]
+    fn audit_reverted_call_tree_with_value() {
+        // The transfers of the calls are reversible writes of the callees.
+        check_call_tree::<7>(CallNode::revert(vec![
+            CallNode::stop(vec![]).value(1),
+            CallNode::revert(vec![CallNode::stop(vec![]).value(2)]).value(3),
+            CallNode::stop(vec![CallNode::revert(vec![]).value(4)]).value(5),
+        ]));
+    }
+}
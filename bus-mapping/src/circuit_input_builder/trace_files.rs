@@ -0,0 +1,325 @@
+//! Offline construction of the circuit inputs of a block from node output
+//! captured in files or held in memory, instead of querying a node or running
+//! the Go tracer. Built without the default features, which pull in the Go
+//! tracer through `mock`, this is how witnesses are generated on wasm32.
+
+use super::{get_state_accesses, AccessSet, BlockHead, CircuitInputBuilder, CircuitsParams};
+use crate::{
+    state_db::{self, CodeDB, StateDB},
+    Error,
+};
+use eth_types::{
+    Address, BigEndianHash, Block, Bytes, GethExecTrace, ToWord, Transaction, Word, H256,
+};
+use ethers_core::utils::keccak256;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// Account state as reported by geth's `prestateTracer`.
+#[derive(Debug, Default, Deserialize)]
+struct PrestateAccount {
+    #[serde(default)]
+    balance: Word,
+    #[serde(default)]
+    nonce: u64,
+    #[serde(default)]
+    code: Bytes,
+    #[serde(default)]
+    storage: HashMap<H256, H256>,
+}
+
+/// Entry of a `debug_traceBlock*` result, which geth wraps in a `result`
+/// field.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TraceEntry<T> {
+    Wrapped { result: T },
+    Bare(T),
+}
+
+impl<T> TraceEntry<T> {
+    fn into_inner(self) -> T {
+        match self {
+            Self::Wrapped { result } | Self::Bare(result) => result,
+        }
+    }
+}
+
+/// Read a JSON file, which may be a raw JSON-RPC response.
+fn read_json(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut json = Vec::new();
+    BufReader::new(File::open(path).map_err(Error::IoError)?)
+        .read_to_end(&mut json)
+        .map_err(Error::IoError)?;
+    Ok(json)
+}
+
+/// Parse JSON, which may be a raw JSON-RPC response.
+fn parse_json<T: DeserializeOwned>(json: &[u8]) -> Result<T, Error> {
+    let mut value: serde_json::Value = serde_json::from_slice(json).map_err(Error::SerdeError)?;
+    if let Some(result) = value
+        .as_object_mut()
+        .filter(|object| object.contains_key("jsonrpc"))
+        .and_then(|object| object.remove("result"))
+    {
+        value = result;
+    }
+    serde_json::from_value(value).map_err(Error::SerdeError)
+}
+
+/// Build the state before the block from the per tx prestates. The first
+/// prestate in which an account or a storage slot appears holds its value
+/// before the block, as earlier txs did not touch it.
+fn prestate_dbs(
+    prestates: Vec<HashMap<Address, PrestateAccount>>,
+    access_set: &AccessSet,
+) -> (StateDB, CodeDB) {
+    let mut sdb = StateDB::new();
+    let mut code_db = CodeDB::new();
+    // Accounts missing from the prestates do not exist before the block.
+    for address in access_set.state.keys() {
+        sdb.set_account(address, state_db::Account::zero());
+    }
+
+    let mut seen = HashMap::<Address, state_db::Account>::new();
+    for prestate in prestates {
+        for (address, account) in prestate {
+            let storage = account
+                .storage
+                .into_iter()
+                .map(|(key, value)| (key.to_word(), value.to_word()));
+            match seen.get_mut(&address) {
+                Some(known) => {
+                    for (key, value) in storage {
+                        known.storage.entry(key).or_insert(value);
+                    }
+                }
+                None => {
+                    let code = account.code.to_vec();
+                    let keccak_code_hash = H256(keccak256(&code));
+                    let code_size = code.len().to_word();
+                    let code_hash = code_db.insert(code);
+                    seen.insert(
+                        address,
+                        state_db::Account {
+                            nonce: account.nonce.into(),
+                            balance: account.balance,
+                            storage: storage.collect(),
+                            code_hash,
+                            keccak_code_hash,
+                            code_size,
+                        },
+                    );
+                }
+            }
+        }
+    }
+    for (address, account) in seen {
+        sdb.set_account(&address, account);
+    }
+    (sdb, code_db)
+}
+
+impl CircuitInputBuilder {
+    /// Build the circuit inputs of a block from previously captured node
+    /// output:
+    /// - `block`: `eth_getBlockByNumber` result, with full transactions.
+    /// - `traces`: `debug_traceBlockByNumber` result with the struct logger.
+    /// - `prestate`: `debug_traceBlockByNumber` result with the `prestateTracer`.
+    ///
+    /// Each file may hold either the result or the whole JSON-RPC response.
+    pub fn from_trace_files(
+        chain_id: Word,
+        circuits_params: CircuitsParams,
+        block: impl AsRef<Path>,
+        traces: impl AsRef<Path>,
+        prestate: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        Self::from_trace_json(
+            chain_id,
+            circuits_params,
+            &read_json(block.as_ref())?,
+            &read_json(traces.as_ref())?,
+            &read_json(prestate.as_ref())?,
+        )
+    }
+
+    /// Build the circuit inputs of a block from the JSON of the node output
+    /// read by [`Self::from_trace_files`], for callers without a filesystem,
+    /// like a browser fetching it.
+    pub fn from_trace_json(
+        chain_id: Word,
+        circuits_params: CircuitsParams,
+        block: &[u8],
+        traces: &[u8],
+        prestate: &[u8],
+    ) -> Result<Self, Error> {
+        let eth_block: Block<Transaction> = parse_json(block)?;
+        let geth_traces = parse_json::<Vec<TraceEntry<GethExecTrace>>>(traces)?
+            .into_iter()
+            .map(TraceEntry::into_inner)
+            .collect::<Vec<_>>();
+        let prestates = parse_json::<Vec<TraceEntry<HashMap<Address, PrestateAccount>>>>(prestate)?
+            .into_iter()
+            .map(TraceEntry::into_inner)
+            .collect::<Vec<_>>();
+        if geth_traces.len() != eth_block.transactions.len()
+            || prestates.len() != eth_block.transactions.len()
+        {
+            return Err(Error::InternalError(
+                "traces do not have one entry per transaction",
+            ));
+        }
+
+        let access_set: AccessSet = get_state_accesses(&eth_block, &geth_traces)?.into();
+        let (sdb, code_db) = prestate_dbs(prestates, &access_set);
+
+        let head = BlockHead::new(chain_id, Vec::new(), &eth_block)?;
+        let mut builder = Self::new_from_headers(circuits_params, sdb, code_db, &[head]);
+        builder.handle_block(&eth_block, &geth_traces)?;
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod trace_files_tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{eth, test_ctx::helpers::*, TestContext, MOCK_ACCOUNTS};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn write_json(name: &str, value: serde_json::Value) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("bus-mapping-{}-{}.json", std::process::id(), name));
+        serde_json::to_writer(File::create(&path).unwrap(), &value).unwrap();
+        path
+    }
+
+    /// Block reading and writing storage, and the node output captured for
+    /// it: the block, the traces and the prestates, mixing the raw results,
+    /// the JSON-RPC responses and the wrapped entries.
+    fn captured_block() -> (GethData, [serde_json::Value; 3]) {
+        let code = bytecode! {
+            PUSH1(0x01)
+            SLOAD
+            PUSH1(0x02)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code)
+                    .storage(vec![(1.into(), 7.into())].into_iter());
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let prestate: HashMap<_, _> = block
+            .accounts
+            .iter()
+            .map(|account| {
+                let storage: HashMap<_, _> = account
+                    .storage
+                    .iter()
+                    .map(|(key, value)| (H256::from_uint(key), H256::from_uint(value)))
+                    .collect();
+                let entry = json!({
+                    "balance": account.balance,
+                    "nonce": account.nonce.as_u64(),
+                    "code": account.code,
+                    "storage": storage,
+                });
+                (account.address, entry)
+            })
+            .collect();
+        let captured = [
+            json!({ "jsonrpc": "2.0", "id": 1, "result": block.eth_block }),
+            json!([{ "result": block.geth_traces[0] }]),
+            json!([prestate]),
+        ];
+        (block, captured)
+    }
+
+    fn assert_matches_geth_data(builder: CircuitInputBuilder, block: GethData) {
+        let block_data = BlockData::new_from_geth_data(block);
+        let mut expected = block_data.new_circuit_input_builder();
+        expected
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .unwrap();
+
+        assert_eq!(builder.block.container, expected.block.container);
+        for address in [MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]] {
+            assert_eq!(
+                builder.sdb.get_account(&address),
+                expected.sdb.get_account(&address)
+            );
+        }
+    }
+
+    #[test]
+    fn from_trace_files_matches_geth_data() {
+        let (block, [eth_block, traces, prestate]) = captured_block();
+        let paths = [
+            write_json("block", eth_block),
+            write_json("traces", traces),
+            write_json("prestate", prestate),
+        ];
+
+        let builder = CircuitInputBuilder::from_trace_files(
+            block.chain_id,
+            CircuitsParams::default(),
+            &paths[0],
+            &paths[1],
+            &paths[2],
+        );
+        for path in &paths {
+            std::fs::remove_file(path).unwrap();
+        }
+        assert_matches_geth_data(builder.unwrap(), block);
+    }
+
+    #[test]
+    fn from_trace_json_matches_geth_data() {
+        let (block, captured) = captured_block();
+        let [eth_block, traces, prestate] = captured.map(|value| value.to_string());
+
+        let builder = CircuitInputBuilder::from_trace_json(
+            block.chain_id,
+            CircuitsParams::default(),
+            eth_block.as_bytes(),
+            traces.as_bytes(),
+            prestate.as_bytes(),
+        )
+        .unwrap();
+        assert_matches_geth_data(builder, block);
+    }
+
+    #[test]
+    fn from_trace_json_rejects_missing_traces() {
+        let (block, [eth_block, _, prestate]) = captured_block();
+
+        let result = CircuitInputBuilder::from_trace_json(
+            block.chain_id,
+            CircuitsParams::default(),
+            eth_block.to_string().as_bytes(),
+            b"[]",
+            prestate.to_string().as_bytes(),
+        );
+        assert!(matches!(result, Err(Error::InternalError(_))));
+    }
+}
@@ -0,0 +1,288 @@
+//! Streaming handling of traces, for traces too large to be held in memory:
+//! the steps are deserialized one at a time, and each step, with its memory
+//! and storage snapshots, is dropped as soon as the builder has consumed it.
+
+use super::{transaction::CallSuccessTracker, CircuitInputBuilder, EthBlock, TransactionContext};
+use crate::{error::ErrorContext, Error};
+use eth_types::{GethExecStep, GethExecTrace};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::{
+    fmt,
+    io::{BufReader, Read},
+};
+
+/// Handler of each step of a streamed trace.
+type OnStep<'a> = &'a mut dyn FnMut(GethExecStep) -> Result<(), Error>;
+
+/// Deserializes a trace, passing its steps to `on_step`.
+struct TraceSeed<'a> {
+    on_step: OnStep<'a>,
+    /// Error returned by `on_step`, which serde can't carry
+    error: &'a mut Option<Error>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for TraceSeed<'a> {
+    type Value = GethExecTrace;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for TraceSeed<'a> {
+    type Value = GethExecTrace;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a geth execution trace")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let TraceSeed { on_step, error } = self;
+        let mut header = serde_json::Map::new();
+        let mut result = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "structLogs" => map.next_value_seed(StepsSeed {
+                    on_step: &mut *on_step,
+                    error: &mut *error,
+                })?,
+                // The trace is wrapped in a JSON-RPC response or in an entry
+                // of `debug_traceBlock*`.
+                "result" => {
+                    result = Some(map.next_value_seed(TraceSeed {
+                        on_step: &mut *on_step,
+                        error: &mut *error,
+                    })?)
+                }
+                _ => {
+                    header.insert(key, map.next_value()?);
+                }
+            }
+        }
+        match result {
+            Some(trace) => Ok(trace),
+            None => {
+                header.insert("structLogs".to_string(), serde_json::Value::Array(vec![]));
+                serde_json::from_value(serde_json::Value::Object(header)).map_err(de::Error::custom)
+            }
+        }
+    }
+}
+
+/// Deserializes the steps of a trace, passing each one to `on_step`.
+struct StepsSeed<'a> {
+    on_step: OnStep<'a>,
+    error: &'a mut Option<Error>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for StepsSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for StepsSeed<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of geth execution steps")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(step) = seq.next_element::<GethExecStep>()? {
+            if let Err(err) = (self.on_step)(step) {
+                *self.error = Some(err);
+                return Err(de::Error::custom("trace step handling failed"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deserialize the trace of a transaction from `reader`, passing its steps in
+/// order to `on_step` instead of collecting them. The trace may be wrapped in
+/// a JSON-RPC response. Returns the trace without its steps.
+pub fn stream_geth_trace<R: Read>(
+    reader: R,
+    mut on_step: impl FnMut(GethExecStep) -> Result<(), Error>,
+) -> Result<GethExecTrace, Error> {
+    let mut error = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let trace = TraceSeed {
+        on_step: &mut on_step,
+        error: &mut error,
+    }
+    .deserialize(&mut deserializer)
+    .and_then(|trace| deserializer.end().map(|()| trace));
+    trace.map_err(|err| error.take().unwrap_or(Error::SerdeError(err)))
+}
+
+impl CircuitInputBuilder {
+    /// Handle a block like [`CircuitInputBuilder::handle_block`], streaming
+    /// the trace of the transaction at each index from `open_trace`, so that
+    /// at most two steps of a trace are in memory at once.
+    ///
+    /// Each trace is read twice: the success of the calls is collected in a
+    /// first pass, as the operations of a call depend on it.
+    pub fn handle_block_streamed<R: Read>(
+        &mut self,
+        eth_block: &EthBlock,
+        mut open_trace: impl FnMut(usize) -> Result<R, Error>,
+    ) -> Result<(), Error> {
+        self.handle_block_with(
+            eth_block,
+            true,
+            true,
+            |builder, tx_index, tx, is_last_tx| {
+                builder.handle_tx_streamed(tx, || open_trace(tx_index), is_last_tx)
+            },
+        )
+    }
+
+    fn handle_tx_streamed<R: Read>(
+        &mut self,
+        eth_tx: &eth_types::Transaction,
+        mut open_trace: impl FnMut() -> Result<R, Error>,
+        is_last_tx: bool,
+    ) -> Result<(), Error> {
+        let mut call_is_success = CallSuccessTracker::default();
+        let mut first_step = None;
+        let mut prev_step: Option<GethExecStep> = None;
+        let mut num_steps = 0;
+        let mut header = stream_geth_trace(open_trace()?, |step| {
+            match &prev_step {
+                Some(prev_step) => call_is_success.step(prev_step, &step)?,
+                None => first_step = Some(step.clone()),
+            }
+            prev_step = Some(step);
+            num_steps += 1;
+            Ok(())
+        })?;
+        let tx_index = self.block.txs.len();
+        let in_tx = move |err: Error| err.with_context(ErrorContext::tx(tx_index));
+        let mut tx_ctx = TransactionContext::new_with_call_is_success(
+            eth_tx,
+            call_is_success.finish(header.failed),
+            header.l1_fee,
+            is_last_tx,
+        )
+        .map_err(in_tx)?;
+        // The BeginTx step only needs the first step.
+        header.struct_logs.extend(first_step);
+        let mut tx = self.begin_tx(eth_tx, &header, &mut tx_ctx).map_err(in_tx)?;
+
+        // Each step is handled once the following one is read.
+        let mut index = 0;
+        let mut geth_steps = Vec::with_capacity(2);
+        stream_geth_trace(open_trace()?, |step| {
+            geth_steps.push(step);
+            if geth_steps.len() == 2 {
+                self.handle_tx_step(&mut tx, &mut tx_ctx, index, &geth_steps)?;
+                geth_steps.remove(0);
+                index += 1;
+            }
+            Ok(())
+        })?;
+        if !geth_steps.is_empty() {
+            self.handle_tx_step(&mut tx, &mut tx_ctx, index, &geth_steps)?;
+            index += 1;
+        }
+        if index != num_steps {
+            return Err(in_tx(Error::InternalError(
+                "trace changed between the two passes",
+            )));
+        }
+        self.end_tx(tx, tx_ctx).map_err(in_tx)
+    }
+}
+
+#[cfg(test)]
+mod trace_stream_tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, ToWord, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
+    use serde_json::json;
+    use std::fs::File;
+
+    #[test]
+    fn handle_block_streamed_matches_handle_block() {
+        let code_a = bytecode! {
+            PUSH1(0x0) // retLength
+            PUSH1(0x0) // retOffset
+            PUSH1(0x0) // argsLength
+            PUSH1(0x0) // argsOffset
+            PUSH1(0x0) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            POP
+            STOP
+        };
+        let code_b = bytecode! {
+            PUSH1(0x42)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 60));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        // The trace is wrapped in a JSON-RPC response.
+        let path = std::env::temp_dir().join(format!(
+            "bus-mapping-{}-streamed-trace.json",
+            std::process::id()
+        ));
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": block.geth_traces[0] });
+        serde_json::to_writer(File::create(&path).unwrap(), &response).unwrap();
+
+        let mut num_steps = 0;
+        let header = stream_geth_trace(File::open(&path).unwrap(), |_| {
+            num_steps += 1;
+            Ok(())
+        });
+
+        let block_data = BlockData::new_from_geth_data(block);
+        let mut builder = block_data.new_circuit_input_builder();
+        let handled = builder.handle_block_streamed(&block_data.eth_block, |_| {
+            File::open(&path).map_err(Error::IoError)
+        });
+        std::fs::remove_file(&path).unwrap();
+        handled.unwrap();
+
+        let header = header.unwrap();
+        let trace = &block_data.geth_traces[0];
+        assert_eq!(num_steps, trace.struct_logs.len());
+        assert!(header.struct_logs.is_empty());
+        assert_eq!(header.gas, trace.gas);
+        assert_eq!(header.failed, trace.failed);
+
+        let mut expected = block_data.new_circuit_input_builder();
+        expected
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .unwrap();
+        assert_eq!(builder.block.container, expected.block.container);
+        assert_eq!(
+            builder.block.txs[0].steps().len(),
+            expected.block.txs[0].steps().len()
+        );
+    }
+}
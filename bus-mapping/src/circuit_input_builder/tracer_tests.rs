@@ -13,7 +13,7 @@ use crate::{
 };
 use eth_types::{
     address, bytecode,
-    evm_types::{stack::Stack, Gas, OpcodeId},
+    evm_types::{stack::Stack, Gas, GasCost, OpcodeId, MAX_CODE_SIZE},
     geth_types::GethData,
     word, Bytecode, Hash, ToAddress, ToWord, Word,
 };
@@ -51,6 +51,7 @@ impl CircuitInputBuilderTx {
                 failed: false,
                 return_value: "".to_owned(),
                 struct_logs: vec![geth_step.clone()],
+                validation: None,
             },
             false,
         )
@@ -621,7 +622,7 @@ fn check_err_code_store_out_of_gas(step: &GethExecStep, next_step: Option<&GethE
     step.op == OpcodeId::RETURN
         && step.error.is_none()
         && result(next_step).is_zero()
-        && Word::from(200) * length > Word::from(step.gas.0)
+        && Word::from(GasCost::CODE_DEPOSIT_BYTE_COST.as_u64()) * length > Word::from(step.gas.0)
 }
 
 #[test]
@@ -873,14 +874,14 @@ fn check_err_max_code_size_exceeded(step: &GethExecStep, next_step: Option<&Geth
     step.op == OpcodeId::RETURN
         && step.error.is_none()
         && result(next_step).is_zero()
-        && length > Word::from(0x6000)
+        && length > Word::from(MAX_CODE_SIZE)
 }
 
 #[test]
 fn tracer_err_max_code_size_exceeded() {
-    // code_creator outputs an empty array of length 0x6000 + 1, which will
-    // trigger the max code size limit.
-    let code_len = 0x6000 + 1;
+    // code_creator outputs an empty array of length MAX_CODE_SIZE + 1, which
+    // will trigger the max code size limit.
+    let code_len = MAX_CODE_SIZE + 1;
     let code_creator = bytecode! {
         .op_mstore(code_len, Word::zero())
         .op_return(0x00, code_len)
@@ -969,9 +970,9 @@ fn tracer_err_max_code_size_exceeded() {
 
 #[test]
 fn tracer_err_max_code_size_exceeded_tx_deploy() {
-    // code_creator outputs an empty array of length 0x6000 + 1, which will
-    // trigger the max code size limit.
-    let code_len = 0x6000 + 1;
+    // code_creator outputs an empty array of length MAX_CODE_SIZE + 1, which
+    // will trigger the max code size limit.
+    let code_len = MAX_CODE_SIZE + 1;
     let code_creator = bytecode! {
         .op_mstore(code_len, Word::zero())
         .op_return(0x00, code_len)
@@ -1018,6 +1019,58 @@ fn tracer_err_max_code_size_exceeded_tx_deploy() {
     );
 }
 
+#[test]
+fn tracer_err_code_store_out_of_gas_max_code_size_tx_deploy() {
+    // code_creator outputs an empty array of length MAX_CODE_SIZE, which is
+    // within the max code size limit but exhausts the gas to store the code.
+    let code_len = MAX_CODE_SIZE;
+    let code_creator = bytecode! {
+        .op_mstore(code_len, Word::zero())
+        .op_return(0x00, code_len)
+    };
+
+    // Get the execution steps from the external tracer
+    let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+        None,
+        |accs| {
+            accs[0].address(address!("0x0000000000000000000000000000000000000000"));
+            accs[1].address(*ADDR_B).balance(Word::from(1u64 << 30));
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .gas(60000u64.into())
+                .nonce(Word::zero())
+                .input(code_creator.into());
+        },
+        |block, _tx| block.number(0x0264),
+        LoggerConfig::enable_memory(),
+    )
+    .unwrap()
+    .into();
+
+    // get last RETURN
+    let (index, step) = block.geth_traces[0]
+        .struct_logs
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, s)| s.op == OpcodeId::RETURN)
+        .unwrap();
+    let next_step = block.geth_traces[0].struct_logs.get(index + 1);
+    assert!(!check_err_max_code_size_exceeded(step, next_step));
+    assert!(check_err_code_store_out_of_gas(step, next_step));
+
+    let mut builder = CircuitInputBuilderTx::new(&block, step);
+    // Set up call context at RETURN
+    builder.tx_ctx.call_is_success.push(false);
+    builder.state_ref().push_call(mock_root_create());
+    assert_eq!(
+        builder.state_ref().get_step_err(step, next_step).unwrap(),
+        Some(ExecError::CodeStoreOutOfGas)
+    );
+}
+
 #[test]
 fn tracer_create_stop() {
     // code_creator doesn't output anything because it stops.
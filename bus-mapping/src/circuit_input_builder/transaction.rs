@@ -2,13 +2,19 @@
 
 use std::collections::BTreeMap;
 
+#[cfg(feature = "prague")]
+use eth_types::eip7702::{self, Authorization};
 use eth_types::{
-    evm_types::{gas_utils::tx_data_gas_cost, Memory},
+    evm_types::{gas_utils::tx_data_gas_cost, Memory, OpcodeId},
     geth_types,
-    geth_types::{get_rlp_unsigned, TxType},
-    Address, GethExecTrace, Signature, Word, H256,
+    geth_types::{get_rlp_signed, get_rlp_unsigned, TxType},
+    AccessList, Address, CallTrace, GethExecStep, GethExecTrace, Signature, Word, H256,
 };
-use ethers_core::utils::get_contract_address;
+use ethers_core::{
+    types::Bloom,
+    utils::{get_contract_address, keccak256},
+};
+use itertools::Itertools;
 
 use crate::{
     l2_predeployed::l1_gas_price_oracle,
@@ -16,11 +22,58 @@ use crate::{
     Error,
 };
 
-use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep};
+use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep, ReturnData};
 
 /// Precision of transaction L1 fee
 pub const TX_L1_FEE_PRECISION: u64 = 1_000_000_000;
 
+/// Collects the `is_success` of each call of a transaction from its trace,
+/// where it is the top of the stack at the step after the call.
+#[derive(Debug, Default)]
+pub(crate) struct CallSuccessTracker {
+    call_is_success_map: BTreeMap<usize, bool>,
+    /// Indices of the steps that dived into the calls not returned yet
+    call_indices: Vec<usize>,
+    /// Index of the next step passed to `step`
+    index: usize,
+}
+
+impl CallSuccessTracker {
+    /// Inspect each step of the trace in order, with the step following it.
+    pub(crate) fn step(
+        &mut self,
+        geth_step: &GethExecStep,
+        geth_next_step: &GethExecStep,
+    ) -> Result<(), Error> {
+        let index = self.index;
+        self.index += 1;
+        // Dive into call
+        if geth_step.depth + 1 == geth_next_step.depth {
+            self.call_indices.push(index);
+        // Emerge from call
+        } else if geth_step.depth - 1 == geth_next_step.depth {
+            let is_success = !geth_next_step.stack.last()?.is_zero();
+            let call_index = self.call_indices.pop().ok_or_else(|| {
+                Error::InvalidGethExecStep("return without a call", Box::new(geth_step.clone()))
+            })?;
+            self.call_is_success_map.insert(call_index, is_success);
+        // Callee with empty code
+        } else if CallKind::try_from(geth_step.op).is_ok() {
+            let is_success = !geth_next_step.stack.last()?.is_zero();
+            self.call_is_success_map.insert(index, is_success);
+        }
+        Ok(())
+    }
+
+    /// Call `is_success` indexed by `call_index`, the root call being the
+    /// transaction.
+    pub(crate) fn finish(self, tx_failed: bool) -> Vec<bool> {
+        std::iter::once(!tx_failed)
+            .chain(self.call_is_success_map.into_values())
+            .collect()
+    }
+}
+
 #[derive(Debug, Default)]
 /// Context of a [`Transaction`] which can mutate in an [`ExecStep`].
 pub struct TransactionContext {
@@ -42,6 +95,9 @@ pub struct TransactionContext {
     /// in the inner most revert (which we track with the last element in
     /// the reversion groups stack), and skip it in the outer revert.
     pub(crate) reversion_groups: Vec<ReversionGroup>,
+    /// Hash of the data the root call reverted with, zero when it didn't end
+    /// with a REVERT.
+    pub(crate) revert_data_hash: H256,
 }
 
 impl TransactionContext {
@@ -51,33 +107,26 @@ impl TransactionContext {
         geth_trace: &GethExecTrace,
         is_last_tx: bool,
     ) -> Result<Self, Error> {
-        // Iterate over geth_trace to inspect and collect each call's is_success, which
-        // is at the top of stack at the step after a call.
-        let call_is_success = {
-            let mut call_is_success_map = BTreeMap::new();
-            let mut call_indices = Vec::new();
-            for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
-                if let Some(geth_next_step) = geth_trace.struct_logs.get(index + 1) {
-                    // Dive into call
-                    if geth_step.depth + 1 == geth_next_step.depth {
-                        call_indices.push(index);
-                    // Emerge from call
-                    } else if geth_step.depth - 1 == geth_next_step.depth {
-                        let is_success = !geth_next_step.stack.last()?.is_zero();
-                        call_is_success_map.insert(call_indices.pop().unwrap(), is_success);
-                    // Callee with empty code
-                    } else if CallKind::try_from(geth_step.op).is_ok() {
-                        let is_success = !geth_next_step.stack.last()?.is_zero();
-                        call_is_success_map.insert(index, is_success);
-                    }
-                }
-            }
-
-            std::iter::once(!geth_trace.failed)
-                .chain(call_is_success_map.into_values())
-                .collect()
-        };
+        let mut call_is_success = CallSuccessTracker::default();
+        for (geth_step, geth_next_step) in geth_trace.struct_logs.iter().tuple_windows() {
+            call_is_success.step(geth_step, geth_next_step)?;
+        }
+        Self::new_with_call_is_success(
+            eth_tx,
+            call_is_success.finish(geth_trace.failed),
+            geth_trace.l1_fee,
+            is_last_tx,
+        )
+    }
 
+    /// Create a new Self with the `is_success` of each call, as collected by
+    /// a [`CallSuccessTracker`].
+    pub(crate) fn new_with_call_is_success(
+        eth_tx: &eth_types::Transaction,
+        call_is_success: Vec<bool>,
+        l1_fee: u64,
+        is_last_tx: bool,
+    ) -> Result<Self, Error> {
         let mut tx_ctx = Self {
             id: eth_tx
                 .transaction_index
@@ -89,7 +138,8 @@ impl TransactionContext {
             call_is_success,
             calls: Vec::new(),
             reversion_groups: Vec::new(),
-            l1_fee: geth_trace.l1_fee,
+            revert_data_hash: H256::zero(),
+            l1_fee,
         };
         tx_ctx.push_call_ctx(0, eth_tx.input.to_vec());
 
@@ -149,22 +199,13 @@ impl TransactionContext {
             self.reversion_groups
                 .push(ReversionGroup::new(vec![(call_idx, 0)], Vec::new()))
         } else if let Some(reversion_group) = self.reversion_groups.last_mut() {
-            let caller_ctx = self.calls.last().expect("calls should not be empty");
-            let caller_reversible_write_counter = self
-                .calls
-                .last()
-                .expect("calls should not be empty")
-                .reversible_write_counter;
-            let caller_reversible_write_counter_offset = reversion_group
+            // Count the reversible writes of the group done before the call,
+            // whatever call did them: the ones of failed calls are in groups
+            // of their own.
+            let reversible_write_counter_offset = reversion_group.op_refs.len();
+            reversion_group
                 .calls
-                .iter()
-                .find(|(call_idx, _)| *call_idx == caller_ctx.index)
-                .expect("calls should not be empty")
-                .1;
-            reversion_group.calls.push((
-                call_idx,
-                caller_reversible_write_counter + caller_reversible_write_counter_offset,
-            ));
+                .push((call_idx, reversible_write_counter_offset));
         }
 
         self.calls.push(CallContext {
@@ -172,7 +213,7 @@ impl TransactionContext {
             reversible_write_counter: 0,
             call_data,
             memory: Memory::default(),
-            return_data: vec![],
+            return_data: ReturnData::default(),
         });
     }
 
@@ -188,6 +229,55 @@ impl TransactionContext {
     }
 }
 
+/// Log of a transaction receipt, emitted by a persistent call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxReceiptLog {
+    /// Address of the contract that emitted the log
+    pub address: Address,
+    /// Topics of the log
+    pub topics: Vec<H256>,
+    /// Data of the log
+    pub data: Vec<u8>,
+}
+
+/// Receipt of a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxReceipt {
+    /// Whether the transaction succeeded
+    pub status: bool,
+    /// Gas used by the transactions of the block up to this one included
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction, in order
+    pub logs: Vec<TxReceiptLog>,
+    /// Hash of the data the transaction reverted with, zero when it didn't end
+    /// with a REVERT
+    pub revert_data_hash: H256,
+}
+
+impl TxReceiptLog {
+    /// Address and topics of the log, which set the bits of the logs bloom.
+    pub fn bloom_inputs(&self) -> impl Iterator<Item = &[u8]> {
+        std::iter::once(self.address.as_bytes()).chain(self.topics.iter().map(|t| t.as_bytes()))
+    }
+}
+
+impl TxReceipt {
+    /// Bloom filter of the logs: for the address and each topic of each log,
+    /// the 3 bits at the first 3 pairs of bytes of its keccak hash modulo 2048
+    /// are set, with bit 0 the lowest bit of the last byte.
+    pub fn logs_bloom(&self) -> Bloom {
+        let mut bloom = [0u8; 256];
+        for input in self.logs.iter().flat_map(TxReceiptLog::bloom_inputs) {
+            let hash = keccak256(input);
+            for pair in hash[..6].chunks(2) {
+                let bit = (u16::from_be_bytes([pair[0], pair[1]]) & 0x7ff) as usize;
+                bloom[255 - bit / 8] |= 1 << (bit % 8);
+            }
+        }
+        Bloom::from(bloom)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Result of the parsing of an Ethereum Transaction.
 pub struct Transaction {
@@ -215,6 +305,8 @@ pub struct Transaction {
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>,
+    /// Access list, empty for the txs without one
+    pub access_list: AccessList,
     /// Chain_id
     pub chain_id: u64,
     /// Signature
@@ -227,6 +319,23 @@ pub struct Transaction {
     pub l1_fee: TxL1Fee,
     /// Committed values of L1 fee
     pub l1_fee_committed: TxL1Fee,
+    /// Whether this is a gas-free system tx: sent by the system sender with a
+    /// zero gas price when [`Block::gas_free_txs`](super::Block::gas_free_txs)
+    /// is set, so that it is not checked against the base fee.
+    pub is_system: bool,
+    /// Whether this tx fails the intrinsic checks and is included as a no-op,
+    /// which only happens with
+    /// [`Block::include_invalid_txs`](super::Block::include_invalid_txs).
+    pub is_invalid: bool,
+    /// Whether the block of this tx is on Prague, per
+    /// [`Block::prague_time`](super::Block::prague_time), so that it pays at
+    /// least the floor cost of its call data of EIP-7623.
+    pub is_prague: bool,
+    /// Authorization list of an EIP-7702 set-code tx
+    #[cfg(feature = "prague")]
+    pub authorization_list: Vec<Authorization>,
+    /// Receipt of the transaction, filled at its EndTx step
+    pub receipt: TxReceipt,
     /// Calls made in the transaction
     pub(crate) calls: Vec<Call>,
     /// Execution steps
@@ -269,6 +378,7 @@ impl Transaction {
             to: Address::zero(),
             value: Word::zero(),
             input: Vec::new(),
+            access_list: AccessList::default(),
             chain_id: 0,
             signature: Signature {
                 r: Word::zero(),
@@ -284,6 +394,12 @@ impl Transaction {
             tx_type: Default::default(),
             l1_fee: Default::default(),
             l1_fee_committed: Default::default(),
+            is_system: false,
+            is_invalid: false,
+            is_prague: false,
+            #[cfg(feature = "prague")]
+            authorization_list: vec![],
+            receipt: TxReceipt::default(),
         }
     }
 
@@ -306,7 +422,11 @@ impl Transaction {
             if !found {
                 return Err(Error::AccountNotFound(address));
             }
-            let code_hash = account.code_hash;
+            #[cfg(feature = "prague")]
+            let (code_address, code_hash) =
+                code_db.resolve_delegation(sdb, address, account.code_hash);
+            #[cfg(not(feature = "prague"))]
+            let (code_address, code_hash) = (address, account.code_hash);
             Call {
                 call_id,
                 kind: CallKind::Call,
@@ -315,7 +435,7 @@ impl Transaction {
                 is_success,
                 caller_address: eth_tx.from,
                 address,
-                code_source: CodeSource::Address(address),
+                code_source: CodeSource::Address(code_address),
                 code_hash,
                 depth: 1,
                 value: eth_tx.value,
@@ -365,10 +485,13 @@ impl Transaction {
         );
 
         Ok(Self {
-            block_num: eth_tx.block_number.unwrap().as_u64(),
+            block_num: eth_tx
+                .block_number
+                .ok_or(Error::InternalError("tx without block number"))?
+                .as_u64(),
             hash: eth_tx.hash,
             tx_type: TxType::get_tx_type(eth_tx),
-            rlp_bytes: eth_tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(eth_tx),
             rlp_unsigned_bytes: get_rlp_unsigned(eth_tx),
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
@@ -379,6 +502,7 @@ impl Transaction {
             to: eth_tx.to.unwrap_or_default(),
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            access_list: eth_tx.access_list.clone().unwrap_or_default(),
             chain_id: eth_tx.chain_id.unwrap_or_default().as_u64(), // FIXME
             calls: vec![call],
             steps: Vec::new(),
@@ -389,6 +513,12 @@ impl Transaction {
             },
             l1_fee,
             l1_fee_committed,
+            is_system: false,
+            is_invalid: false,
+            is_prague: false,
+            #[cfg(feature = "prague")]
+            authorization_list: eip7702::authorization_list(eth_tx)?,
+            receipt: TxReceipt::default(),
         })
     }
 
@@ -423,6 +553,45 @@ impl Transaction {
         self.calls.push(call);
     }
 
+    /// Check the calls of this transaction, built from its struct logs,
+    /// against the call frames traced by geth in the same run, as given by
+    /// `external_tracer::trace_with_calls`. The calls failing their prechecks,
+    /// like the calls above the maximum depth, end before geth enters them
+    /// and have no frame, so a failed call without sub calls may be missing
+    /// from the frames.
+    pub fn check_calls(&self, call_trace: &CallTrace) -> Result<(), Error> {
+        let mut frames = call_trace
+            .flatten()
+            .into_iter()
+            .filter(|frame| frame.call_type != OpcodeId::SELFDESTRUCT)
+            .peekable();
+        for call in self.calls() {
+            let mismatch = match frames.peek() {
+                Some(frame) => call.frame_mismatch(frame),
+                None => Some("frame"),
+            };
+            match mismatch {
+                None => {
+                    frames.next();
+                }
+                Some(_) if !call.is_success && call.last_callee_id == 0 => {}
+                Some(field) => {
+                    return Err(Error::CallTraceMismatch {
+                        call_id: Some(call.call_id),
+                        field,
+                    })
+                }
+            }
+        }
+        match frames.next() {
+            Some(_) => Err(Error::CallTraceMismatch {
+                call_id: None,
+                field: "frame",
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// Return last step in this transaction.
     pub fn last_step(&self) -> &ExecStep {
         if self.steps().is_empty() {
@@ -437,8 +606,11 @@ impl Transaction {
         self.steps.is_empty()
     }
 
-    /// Calculate L1 fee of this transaction.
+    /// Calculate L1 fee of this transaction, L1 message txs pay none.
     pub fn l1_fee(&self) -> u64 {
+        if self.tx_type.is_l1_msg() {
+            return 0;
+        }
         let tx_data_gas_cost = tx_data_gas_cost(&self.rlp_bytes);
 
         self.l1_fee.tx_l1_fee(tx_data_gas_cost).0
@@ -0,0 +1,336 @@
+//! Export of the rw activity of each step and of the circuit cost of each tx
+//! of a block, in a JSON format meant for explorers and indexers showing the
+//! "zk cost" of a tx, which don't want to depend on the witness types.
+//!
+//! A [`WitnessExport`] serializes as:
+//! ```json
+//! {
+//!   "version": 1,
+//!   "chain_id": 1,
+//!   "block_number": 42,
+//!   "txs": [{
+//!     "index": 0,
+//!     "hash": "0x…",
+//!     "cost": { "steps": 5, "reads": 30, "writes": 12, "rws_by_target": { "Stack": 8, … },
+//!               "copy_events": 0, "copy_rows": 0, "exp_events": 0, "exp_steps": 0 },
+//!     "steps": [{
+//!       "index": 1, "kind": "opcode", "name": "PUSH1", "call_index": 0, "depth": 1,
+//!       "pc": 0, "gas_left": 78976, "gas_cost": 3, "error": null,
+//!       "cost": { "steps": 1, "reads": 0, "writes": 1, … }
+//!     }, …]
+//!   }]
+//! }
+//! ```
+//! The cost of a step counts the rw operations it references, including the
+//! reversions of its writes if its call reverted, and the copy and
+//! exponentiation events it generated. The cost of a tx sums the costs of its
+//! steps.
+
+use super::{rw_audit::op_info, Block, ExecState, ExecStep, Transaction};
+use crate::operation::Target;
+use eth_types::H256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version of the [`WitnessExport`] format produced by this crate.
+pub const WITNESS_EXPORT_VERSION: u32 = 1;
+
+/// Circuit cost of a step or of a tx.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitCost {
+    /// Number of steps, each using rows of the EVM circuit
+    pub steps: usize,
+    /// Number of read operations, each a row of the state circuit
+    pub reads: usize,
+    /// Number of write operations, each a row of the state circuit
+    pub writes: usize,
+    /// Number of rw operations by target, named as in [`Target`]
+    pub rws_by_target: BTreeMap<String, usize>,
+    /// Number of copy events
+    pub copy_events: usize,
+    /// Number of rows of the copy circuit
+    pub copy_rows: usize,
+    /// Number of exponentiation events
+    pub exp_events: usize,
+    /// Number of multiplication steps of the exponentiation events, each
+    /// using rows of the exponentiation circuit
+    pub exp_steps: usize,
+}
+
+impl CircuitCost {
+    fn add(&mut self, other: &Self) {
+        self.steps += other.steps;
+        self.reads += other.reads;
+        self.writes += other.writes;
+        for (target, count) in &other.rws_by_target {
+            *self.rws_by_target.entry(target.clone()).or_default() += count;
+        }
+        self.copy_events += other.copy_events;
+        self.copy_rows += other.copy_rows;
+        self.exp_events += other.exp_events;
+        self.exp_steps += other.exp_steps;
+    }
+}
+
+/// Rw activity of a step.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepExport {
+    /// Index of the step in its tx
+    pub index: usize,
+    /// `opcode`, `precompile`, or `virtual` for the steps of the circuit
+    /// which aren't run by the EVM, like `BeginTx`
+    pub kind: String,
+    /// Name of the opcode, precompile or virtual step
+    pub name: String,
+    /// Index of the call of the step in its tx
+    pub call_index: usize,
+    /// Depth of the call of the step, 1 for the call of the tx
+    pub depth: usize,
+    /// Program counter
+    pub pc: u64,
+    /// Gas left before the step
+    pub gas_left: u64,
+    /// Gas cost of the step
+    pub gas_cost: u64,
+    /// Error of the step, if it failed
+    pub error: Option<String>,
+    /// Circuit cost of the step
+    pub cost: CircuitCost,
+}
+
+/// Circuit cost of a tx, with the rw activity of its steps.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxExport {
+    /// Index of the tx in the block
+    pub index: usize,
+    /// Hash of the tx
+    pub hash: H256,
+    /// Circuit cost of the tx
+    pub cost: CircuitCost,
+    /// Steps of the tx
+    pub steps: Vec<StepExport>,
+}
+
+/// Rw activity and circuit cost of the txs of a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessExport {
+    /// Version of the format
+    pub version: u32,
+    /// Chain id
+    pub chain_id: u64,
+    /// Number of the block
+    pub block_number: u64,
+    /// Txs of the block
+    pub txs: Vec<TxExport>,
+}
+
+/// Kind and name of an execution state.
+fn kind_and_name(exec_state: &ExecState) -> (&'static str, String) {
+    match exec_state {
+        ExecState::Op(op) => ("opcode", format!("{:?}", op)),
+        ExecState::Precompile(precompile) => ("precompile", format!("{:?}", precompile)),
+        ExecState::BeginTx | ExecState::EndTx | ExecState::EndBlock => {
+            ("virtual", format!("{:?}", exec_state))
+        }
+    }
+}
+
+fn step_cost(block: &Block, step: &ExecStep, next_rwc: Option<usize>) -> CircuitCost {
+    let mut cost = CircuitCost {
+        steps: 1,
+        ..Default::default()
+    };
+    for op_ref in &step.bus_mapping_instance {
+        // Padding operations aren't rows of their own.
+        if op_ref.target() == Target::Start {
+            continue;
+        }
+        if let Some((_, rw, _)) = op_info(&block.container, op_ref) {
+            if rw.is_write() {
+                cost.writes += 1;
+            } else {
+                cost.reads += 1;
+            }
+            *cost
+                .rws_by_target
+                .entry(format!("{:?}", op_ref.target()))
+                .or_default() += 1;
+        }
+    }
+
+    // The events generated by the step are identified by a rw counter from
+    // the one of the step up to the one of the next step.
+    let generated_by_step =
+        |rwc: usize| rwc >= step.rwc.0 && next_rwc.map_or(true, |next_rwc| rwc < next_rwc);
+    for copy_event in &block.copy_events {
+        if generated_by_step(copy_event.rw_counter_start.0) {
+            cost.copy_events += 1;
+            cost.copy_rows += copy_event.num_rows();
+        }
+    }
+    for exp_event in &block.exp_events {
+        if generated_by_step(exp_event.identifier) {
+            cost.exp_events += 1;
+            cost.exp_steps += exp_event.steps.len();
+        }
+    }
+    cost
+}
+
+/// Export of the tx, whose steps are followed by a step at `end_rwc`, the
+/// first step of the next tx, if any.
+fn tx_export(block: &Block, index: usize, tx: &Transaction, end_rwc: Option<usize>) -> TxExport {
+    let tx_steps = tx.steps();
+    let mut cost = CircuitCost::default();
+    let steps = tx_steps
+        .iter()
+        .enumerate()
+        .map(|(step_index, step)| {
+            let next_rwc = tx_steps
+                .get(step_index + 1)
+                .map(|next| next.rwc.0)
+                .or(end_rwc);
+            let step_cost = step_cost(block, step, next_rwc);
+            cost.add(&step_cost);
+            let (kind, name) = kind_and_name(&step.exec_state);
+            StepExport {
+                index: step_index,
+                kind: kind.to_string(),
+                name,
+                call_index: step.call_index,
+                depth: tx.calls().get(step.call_index).map_or(0, |call| call.depth),
+                pc: step.pc.0 as u64,
+                gas_left: step.gas_left.0,
+                gas_cost: step.gas_cost.0,
+                error: step.error.as_ref().map(|error| format!("{:?}", error)),
+                cost: step_cost,
+            }
+        })
+        .collect();
+    TxExport {
+        index,
+        hash: tx.hash,
+        cost,
+        steps,
+    }
+}
+
+/// Export the rw activity of each step and the circuit cost of each tx of
+/// the block.
+pub fn export_witness(block: &Block) -> WitnessExport {
+    let block_number = match block.txs.first() {
+        Some(tx) => tx.block_num,
+        None => block.eth_block.number.unwrap_or_default().as_u64(),
+    };
+    let txs = block
+        .txs
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            let end_rwc = block
+                .txs
+                .get(index + 1)
+                .and_then(|next| next.steps().first())
+                .map(|step| step.rwc.0);
+            tx_export(block, index, tx, end_rwc)
+        })
+        .collect();
+    WitnessExport {
+        version: WITNESS_EXPORT_VERSION,
+        chain_id: block.chain_id.as_u64(),
+        block_number,
+        txs,
+    }
+}
+
+#[cfg(test)]
+mod witness_export_tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Bytecode};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn export(code: Bytecode) -> WitnessExport {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let block_data = BlockData::new_from_geth_data(block);
+        let mut builder = block_data.new_circuit_input_builder();
+        builder
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .unwrap();
+        export_witness(&builder.block)
+    }
+
+    #[test]
+    fn export_step_activity() {
+        let export = export(bytecode! {
+            PUSH1(0x08)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            CODECOPY
+            PUSH1(0x03)
+            PUSH1(0x02)
+            EXP
+            STOP
+        });
+        assert_eq!(export.version, WITNESS_EXPORT_VERSION);
+        let tx = &export.txs[0];
+        let names: Vec<_> = tx.steps.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "BeginTx", "PUSH1", "PUSH1", "PUSH1", "CODECOPY", "PUSH1", "PUSH1", "EXP", "STOP",
+                "EndTx"
+            ]
+        );
+
+        let push = &tx.steps[1];
+        assert_eq!((push.kind.as_str(), push.depth), ("opcode", 1));
+        assert_eq!((push.cost.reads, push.cost.writes), (0, 1));
+        assert_eq!(push.cost.rws_by_target["Stack"], 1);
+
+        let codecopy = &tx.steps[4];
+        assert_eq!(codecopy.cost.copy_events, 1);
+        assert_eq!(codecopy.cost.copy_rows, 2 * 0x08);
+        let exp = &tx.steps[7];
+        assert_eq!(exp.cost.exp_events, 1);
+        assert_eq!(tx.cost.copy_events + tx.cost.exp_events, 2);
+
+        // The cost of the tx sums the costs of its steps.
+        assert_eq!(tx.cost.steps, tx.steps.len());
+        assert_eq!(
+            tx.cost.reads + tx.cost.writes,
+            tx.cost.rws_by_target.values().sum::<usize>()
+        );
+        assert_eq!(
+            tx.cost.writes,
+            tx.steps.iter().map(|step| step.cost.writes).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn export_serde_roundtrip() {
+        let export = export(bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        });
+        let json = serde_json::to_value(&export).unwrap();
+        assert_eq!(json["txs"][0]["steps"][3]["name"], "SSTORE");
+        assert_eq!(
+            json["txs"][0]["steps"][3]["cost"]["rws_by_target"]["Storage"],
+            1
+        );
+        assert_eq!(
+            serde_json::from_value::<WitnessExport>(json).unwrap(),
+            export
+        );
+    }
+}
@@ -5,9 +5,14 @@ use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    circuit_input_builder::CopyDataType,
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
+        GETH_ERR_STACK_UNDERFLOW,
+    },
+    precompile::PrecompileCalls,
+    progress::Cancelled,
 };
 
 /// Error type for any BusMapping related failure.
@@ -15,6 +20,8 @@ use crate::geth_errors::{
 pub enum Error {
     /// Serde de/serialization error.
     SerdeError(serde_json::error::Error),
+    /// IO error, e.g. while reading trace files.
+    IoError(std::io::Error),
     /// JSON-RPC related error.
     JSONRpcError(ProviderError),
     /// OpcodeId is not a call type.
@@ -40,6 +47,128 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// Call to a precompile registered through
+    /// [`crate::precompile::PrecompileProvider`] without circuit support.
+    UnsupportedPrecompile(Address),
+    /// Set-code tx of EIP-7702 with more authorizations than the circuits
+    /// support, `MAX_AUTHORIZATIONS_PER_TX` of `eth_types::eip7702`.
+    TooManyAuthorizations(usize),
+    /// Address which isn't the one of a precompile from [`PrecompileCalls`].
+    UnknownPrecompile(Address),
+    /// Registration of a precompile at the address of a precompile from
+    /// [`PrecompileCalls`].
+    BuiltinPrecompile(Address),
+    /// Call to a precompile with an input the circuits can't handle, see
+    /// [`PrecompileCalls::is_input_supported`].
+    PrecompileUnsupportedInput {
+        /// Index of the tx in the block, starting at 1
+        tx_id: usize,
+        /// Id of the call to the precompile
+        call_id: usize,
+        /// Id of the call to the precompile's caller
+        caller_id: usize,
+        /// The precompile called
+        precompile: PrecompileCalls,
+        /// Length in bytes of the input
+        input_len: usize,
+    },
+    /// Failure of the witness generation of a tx, at the position given by
+    /// the context. Use [`Error::root_cause`] to match on the failure.
+    WithContext {
+        /// Where witness generation failed
+        context: ErrorContext,
+        /// The failure
+        source: Box<Error>,
+    },
+    /// Copy event reading from a type which isn't a copy source or writing
+    /// to a type which isn't a copy destination.
+    InvalidCopyDataType {
+        /// Source of the copy event
+        src_type: CopyDataType,
+        /// Destination of the copy event
+        dst_type: CopyDataType,
+    },
+    /// The calls of a tx built from its struct logs differ from the call
+    /// frames traced by geth, see
+    /// [`crate::circuit_input_builder::Transaction::check_calls`].
+    CallTraceMismatch {
+        /// Id of the first call differing from its frame, `None` if geth
+        /// traced more calls
+        call_id: Option<usize>,
+        /// Field of the call differing from the frame, or `"frame"` when a
+        /// frame is missing
+        field: &'static str,
+    },
+    /// The witness generation was cancelled through its
+    /// [`crate::progress::ProgressReporter`].
+    Cancelled,
+}
+
+/// Position in the block of a failure of the witness generation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Index of the tx in [`crate::circuit_input_builder::Block::txs`]
+    pub tx_index: usize,
+    /// Id of the call executing the failing step, zero outside of steps
+    pub call_id: usize,
+    /// Program counter of the failing step, `None` for the BeginTx and EndTx
+    /// steps
+    pub pc: Option<u64>,
+    /// Opcode of the failing step, `None` for the BeginTx and EndTx steps
+    pub opcode: Option<OpcodeId>,
+}
+
+impl ErrorContext {
+    /// Context of a failure in the tx at `tx_index` outside of its steps.
+    pub fn tx(tx_index: usize) -> Self {
+        Self {
+            tx_index,
+            call_id: 0,
+            pc: None,
+            opcode: None,
+        }
+    }
+
+    /// Context of a failure while handling `geth_step` in the call `call_id`
+    /// of the tx at `tx_index`.
+    pub fn step(tx_index: usize, call_id: usize, geth_step: &GethExecStep) -> Self {
+        Self {
+            tx_index,
+            call_id,
+            pc: Some(geth_step.pc.0),
+            opcode: Some(geth_step.op),
+        }
+    }
+}
+
+impl Error {
+    /// Attach `context` to the error, unless it already has a context which
+    /// is more precise.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            Self::WithContext { .. } => self,
+            _ => Self::WithContext {
+                context,
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// Where witness generation failed, if known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without its context.
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::WithContext { source, .. } => source.root_cause(),
+            _ => self,
+        }
+    }
 }
 
 impl From<eth_types::Error> for Error {
@@ -48,6 +177,18 @@ impl From<eth_types::Error> for Error {
     }
 }
 
+impl From<Cancelled> for Error {
+    fn from(_: Cancelled) -> Self {
+        Error::Cancelled
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::IoError(err)
+    }
+}
+
 impl From<ProviderError> for Error {
     fn from(err: ProviderError) -> Self {
         Error::JSONRpcError(err)
@@ -56,11 +197,31 @@ impl From<ProviderError> for Error {
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{:?}", self)
+        match self {
+            Self::WithContext { context, source } => {
+                write!(f, "tx {} call {}", context.tx_index, context.call_id)?;
+                if let (Some(pc), Some(opcode)) = (context.pc, context.opcode) {
+                    write!(f, " pc {} {:?}", pc, opcode)?;
+                }
+                write!(f, ": {}", source)
+            }
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
-impl StdError for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::SerdeError(err) => Some(err),
+            Self::IoError(err) => Some(err),
+            Self::JSONRpcError(err) => Some(err),
+            Self::EthTypeError(err) => Some(err),
+            Self::WithContext { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 /// Out of Gas errors by opcode
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -176,7 +337,7 @@ pub enum ExecError {
 }
 
 // TODO: Move to impl block.
-pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
+pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> Option<ExecError> {
     if [GETH_ERR_OUT_OF_GAS, GETH_ERR_GAS_UINT_OVERFLOW].contains(&error) {
         // NOTE: We report a GasUintOverflow error as an OutOfGas error
         let oog_err = match op {
@@ -204,12 +365,12 @@ pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
             OpcodeId::SELFDESTRUCT => OogError::SelfDestruct,
             _ => OogError::Constant,
         };
-        ExecError::OutOfGas(oog_err)
+        Some(ExecError::OutOfGas(oog_err))
     } else if error.starts_with(GETH_ERR_STACK_OVERFLOW) {
-        ExecError::StackOverflow
+        Some(ExecError::StackOverflow)
     } else if error.starts_with(GETH_ERR_STACK_UNDERFLOW) {
-        ExecError::StackUnderflow
+        Some(ExecError::StackUnderflow)
     } else {
-        panic!("Unknown GethExecStep.error: {}", error);
+        None
     }
 }
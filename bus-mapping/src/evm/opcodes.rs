@@ -1,6 +1,6 @@
 //! Definition of each opcode of the EVM.
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, MemoryCopy, TxReceipt, TxReceiptLog},
     error::{
         ContractAddressCollisionError, DepthError, ExecError, InsufficientBalanceError,
         NonceUintOverflowError, OogError,
@@ -8,8 +8,8 @@ use crate::{
     evm::OpcodeId,
     l2_predeployed::l1_gas_price_oracle,
     operation::{
-        AccountField, AccountOp, CallContextField, StorageOp, TxAccessListAccountOp,
-        TxReceiptField, TxRefundOp, RW,
+        AccountField, AccountOp, CallContextField, Operation, StorageOp, TxAccessListAccountOp,
+        TxLogField, TxLogOp, TxReceiptField, TxRefundOp, RW,
     },
     state_db::CodeDB,
     Error,
@@ -17,10 +17,12 @@ use crate::{
 use core::fmt::Debug;
 use eth_types::{
     evm_types::{gas_utils::tx_data_gas_cost, GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    evm_unimplemented, GethExecStep, GethExecTrace, ToAddress, ToWord, Word,
+    evm_unimplemented, BigEndianHash, GethExecStep, GethExecTrace, ToAddress, ToWord, Word, H256,
 };
 use ethers_core::utils::get_contract_address;
 
+#[cfg(feature = "prague")]
+use crate::circuit_input_builder::CodeSource;
 use crate::util::CHECK_MEM_STRICT;
 
 #[cfg(any(feature = "test", test))]
@@ -61,6 +63,8 @@ mod sstore;
 mod stackonlyop;
 mod stop;
 mod swap;
+mod tload;
+mod tstore;
 
 mod error_codestore;
 mod error_contract_address_collision;
@@ -75,13 +79,15 @@ mod error_precompile_failed;
 mod error_return_data_outofbound;
 mod error_write_protection;
 
+#[cfg(test)]
+mod begin_end_tx_test;
 #[cfg(test)]
 mod memory_expansion_test;
 #[cfg(feature = "test")]
 pub use callop::tests::PrecompileCallArgs;
 
+pub(crate) use self::exp::exp_by_squaring;
 use self::sha3::Sha3;
-use crate::precompile::is_precompiled;
 use address::Address;
 use balance::Balance;
 use calldatacopy::Calldatacopy;
@@ -124,6 +130,8 @@ use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
 use stop::Stop;
 use swap::Swap;
+use tload::Tload;
+use tstore::Tstore;
 
 /// Generic opcode trait which defines the logic of the
 /// [`Operation`](crate::operation::Operation) that should be generated for one
@@ -216,12 +224,16 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CHAINID => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::SELFBALANCE => Selfbalance::gen_associated_ops,
         OpcodeId::BASEFEE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        OpcodeId::BLOBHASH => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        OpcodeId::BLOBBASEFEE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::POP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::MLOAD => Mload::gen_associated_ops,
         OpcodeId::MSTORE => Mstore::<false>::gen_associated_ops,
         OpcodeId::MSTORE8 => Mstore::<true>::gen_associated_ops,
         OpcodeId::SLOAD => Sload::gen_associated_ops,
         OpcodeId::SSTORE => Sstore::gen_associated_ops,
+        OpcodeId::TLOAD => Tload::gen_associated_ops,
+        OpcodeId::TSTORE => Tstore::gen_associated_ops,
         OpcodeId::JUMP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::JUMPI => StackOnlyOpcode::<2, 0>::gen_associated_ops,
         OpcodeId::PC => StackOnlyOpcode::<0, 1>::gen_associated_ops,
@@ -423,7 +435,7 @@ pub fn gen_associated_ops(
     } else {
         None
     };
-    if let Some(exec_error) = state.get_step_err(geth_step, next_step).unwrap() {
+    if let Some(exec_error) = state.get_step_err(geth_step, next_step)? {
         log::warn!(
             "geth error {:?} occurred in  {:?} at pc {:?}",
             exec_error,
@@ -473,8 +485,8 @@ pub fn gen_begin_tx_ops(
     let mut exec_step = state.new_begin_tx_step();
     let call = state.call()?.clone();
 
-    // Add 3 RW read operations for transaction L1 fee.
-    gen_tx_l1_fee_ops(state, &mut exec_step);
+    // Add RW operations for transaction L1 fee.
+    gen_tx_l1_fee_ops(state, &mut exec_step, call.call_id);
 
     for (field, value) in [
         (CallContextField::TxId, state.tx_ctx.id().into()),
@@ -491,19 +503,30 @@ pub fn gen_begin_tx_ops(
         state.call_context_write(&mut exec_step, call.call_id, field, value);
     }
 
-    // Increase caller's nonce
+    // Increase caller's nonce, which an invalid tx leaves unchanged.
     let caller_address = call.caller_address;
     let mut nonce_prev = state.sdb.get_account(&caller_address).1.nonce;
-    debug_assert!(nonce_prev <= state.tx.nonce.into());
-    while nonce_prev < state.tx.nonce.into() {
-        nonce_prev = state.sdb.increase_nonce(&caller_address).into();
-        log::warn!("[debug] increase nonce to {}", nonce_prev);
+    if !state.tx.is_invalid {
+        debug_assert!(nonce_prev <= state.tx.nonce.into());
+        while nonce_prev < state.tx.nonce.into() {
+            nonce_prev = state.sdb.increase_nonce(&caller_address).into();
+            log::warn!("[debug] increase nonce to {}", nonce_prev);
+        }
+        // EIP-2681: the nonce is limited to 2^64 - 1 and can't be increased
+        // from there.
+        if state.tx.nonce == u64::MAX {
+            return Err(Error::InternalError("caller nonce is at its maximum"));
+        }
     }
     state.account_write(
         &mut exec_step,
         caller_address,
         AccountField::Nonce,
-        nonce_prev + 1,
+        if state.tx.is_invalid {
+            nonce_prev
+        } else {
+            nonce_prev + 1
+        },
         nonce_prev,
     )?;
 
@@ -516,7 +539,7 @@ pub fn gen_begin_tx_ops(
             .block
             .headers
             .get(&state.tx.block_num)
-            .unwrap()
+            .ok_or(Error::InternalError("block header of the tx not found"))?
             .coinbase,
     ];
     #[cfg(not(feature = "shanghai"))]
@@ -532,6 +555,42 @@ pub fn gen_begin_tx_ops(
         )?;
     }
 
+    // An invalid tx is a no-op: only the balance of the caller is read, for
+    // the circuit to check that it can't pay for the tx, and no gas is used.
+    if state.tx.is_invalid {
+        let caller_balance = state.sdb.get_account(&caller_address).1.balance;
+        state.account_read(
+            &mut exec_step,
+            caller_address,
+            AccountField::Balance,
+            caller_balance,
+        );
+        exec_step.gas_cost = GasCost(0);
+        state.handle_reversion(&mut exec_step)?;
+        state.tx.steps_mut().push(exec_step);
+        return Ok(());
+    }
+
+    // The authorizations of a set-code tx are applied once the access list is
+    // warmed up, and the root call may then call one of the authorities.
+    #[cfg(feature = "prague")]
+    let call = if state.tx.authorization_list.is_empty() {
+        call
+    } else {
+        gen_authorization_ops(state, &mut exec_step)?;
+        if !call.is_create() {
+            let code_hash = state.sdb.get_account(&call.address).1.code_hash;
+            let (code_address, code_hash) =
+                state
+                    .code_db
+                    .resolve_delegation(state.sdb, call.address, code_hash);
+            let call = state.call_mut()?;
+            call.code_source = CodeSource::Address(code_address);
+            call.code_hash = code_hash;
+        }
+        state.call()?.clone()
+    };
+
     // Calculate gas cost of init code only for EIP-3860 of Shanghai.
     #[cfg(feature = "shanghai")]
     let init_code_gas_cost = if state.tx.is_create() {
@@ -542,6 +601,13 @@ pub fn gen_begin_tx_ops(
     #[cfg(not(feature = "shanghai"))]
     let init_code_gas_cost = 0;
 
+    // Calculate gas cost of the authorizations of a set-code tx of EIP-7702.
+    #[cfg(feature = "prague")]
+    let authorization_gas_cost =
+        state.tx.authorization_list.len() as u64 * eth_types::eip7702::PER_EMPTY_ACCOUNT_COST;
+    #[cfg(not(feature = "prague"))]
+    let authorization_gas_cost = 0;
+
     // Calculate intrinsic gas cost
     let call_data_gas_cost = tx_data_gas_cost(&state.tx.input);
     let intrinsic_gas_cost = if state.tx.is_create() {
@@ -549,15 +615,38 @@ pub fn gen_begin_tx_ops(
     } else {
         GasCost::TX.as_u64()
     } + call_data_gas_cost
-        + init_code_gas_cost;
+        + init_code_gas_cost
+        + authorization_gas_cost;
     exec_step.gas_cost = GasCost(intrinsic_gas_cost);
 
+    // The gas limit of a valid tx of a Prague block covers the floor cost of
+    // its call data of EIP-7623.
+    if state.tx.is_prague
+        && !state.tx.is_invalid
+        && state.tx.gas < eth_types::evm_types::gas_utils::tx_floor_data_gas(&state.tx.input)
+    {
+        return Err(Error::InternalError(
+            "tx gas is below the floor cost of its call data",
+        ));
+    }
+
+    // Sanity check for intrinsic gas, if the tracer gives the one of geth.
+    if let Some(validation) = &geth_trace.validation {
+        if intrinsic_gas_cost != validation.intrinsic_gas {
+            log::error!(
+                "Mismatch intrinsic_gas: calculated = {}, real = {}",
+                intrinsic_gas_cost,
+                validation.intrinsic_gas
+            );
+        }
+    }
+
     // Get code_hash of callee
     // FIXME: call with value to precompile will cause the codehash of precompile
     // address to `CodeDB::empty_code_hash()`. FIXME: we should have a
     // consistent codehash for precompile contract.
     let callee_account = &state.sdb.get_account(&call.address).1.clone();
-    let is_precompile = is_precompiled(&call.address);
+    let is_precompile = state.precompiles.is_precompiled(&call.address);
     let callee_exists = !callee_account.is_empty() || is_precompile;
     if !callee_exists && call.value.is_zero() {
         state.sdb.get_account_mut(&call.address).1.storage.clear();
@@ -567,7 +656,9 @@ pub fn gen_begin_tx_ops(
             && !callee_account.code_hash.eq(&CodeDB::empty_code_hash()))
             || !callee_account.nonce.is_zero())
     {
-        unimplemented!("deployment collision");
+        return Err(Error::InternalError(
+            "creation tx deploying to an existing contract",
+        ));
     }
     let (callee_code_hash, is_empty_code_hash) = match (state.tx.is_create(), callee_exists) {
         (true, _) => (call.code_hash.to_word(), false),
@@ -594,7 +685,12 @@ pub fn gen_begin_tx_ops(
     }
 
     // Transfer with fee
-    let fee = state.tx.gas_price * state.tx.gas + state.tx_ctx.l1_fee;
+    let fee = state
+        .tx
+        .gas_price
+        .checked_mul(state.tx.gas.into())
+        .and_then(|fee| fee.checked_add(state.tx_ctx.l1_fee.into()))
+        .ok_or(Error::InternalError("tx fee overflows 256 bits"))?;
     state.transfer_with_fee(
         &mut exec_step,
         call.caller_address,
@@ -705,26 +801,188 @@ pub fn gen_begin_tx_ops(
     exec_step.gas_cost = if geth_trace.struct_logs.is_empty() {
         GasCost(geth_trace.gas.0)
     } else {
-        GasCost(state.tx.gas - geth_trace.struct_logs[0].gas.0)
+        GasCost(
+            state
+                .tx
+                .gas
+                .checked_sub(geth_trace.struct_logs[0].gas.0)
+                .ok_or(Error::InvalidGethExecTrace(
+                    "first step gas above the tx gas",
+                ))?,
+        )
     };
 
-    log::trace!("begin_tx_step: {:?}", exec_step);
-    state.tx.steps_mut().push(exec_step);
-
     // TRICKY:
     // Process the reversion only for Precompile in begin TX. Since no associated
     // opcodes could process reversion afterwards.
     // TODO:
     // Move it to code of generating precompiled operations when implemented.
-    if is_precompile && !state.call().unwrap().is_success {
-        state.handle_reversion();
+    if is_precompile && !state.call()?.is_success {
+        state.handle_reversion(&mut exec_step)?;
     }
 
+    log::trace!("begin_tx_step: {:?}", exec_step);
+    state.tx.steps_mut().push(exec_step);
+
+    Ok(())
+}
+
+/// Generate the operations of the authorization list of an EIP-7702 set-code
+/// tx. Each authorization valid on the chain warms up its authority, writes
+/// its code hash and, if the authority exists or the authorization applies,
+/// its nonce. An authorization applies when its authority has no code other
+/// than a delegation designator and has the nonce of the authorization: the
+/// code of the authority is set to a delegation designator, or cleared when
+/// delegating to the zero address, and its nonce is increased. The
+/// designator is copied from the bytecode table for the circuit to check its
+/// bytes. The refund of the authorities which existed is written last. These
+/// updates are kept even if the tx reverts.
+#[cfg(feature = "prague")]
+fn gen_authorization_ops(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+) -> Result<(), Error> {
+    use crate::circuit_input_builder::{CopyDataType, CopyEvent, NumberOrHash};
+    use eth_types::eip7702::{self, PER_AUTH_BASE_COST, PER_EMPTY_ACCOUNT_COST};
+
+    let mut refund = state.sdb.refund();
+    let refund_prev = refund;
+    for auth in state.tx.authorization_list.clone() {
+        let authority = match auth.valid_authority(state.block.chain_id) {
+            Some(authority) => authority,
+            None => continue,
+        };
+        let is_warm_prev = !state.sdb.add_account_to_access_list(authority);
+        state.tx_accesslist_account_write(
+            exec_step,
+            state.tx_ctx.id(),
+            authority,
+            true,
+            is_warm_prev,
+        )?;
+
+        let account = state.sdb.get_account(&authority).1.clone();
+        let exists = !account.is_empty();
+        // a non-existing account has code hash 0 in the circuits
+        let code_hash_prev = if exists {
+            account.code_hash.to_word()
+        } else {
+            Word::zero()
+        };
+        let has_code = state.code_db.get(&account.code_hash).map_or(false, |code| {
+            !code.is_empty() && eip7702::delegated_address(code).is_none()
+        });
+        let is_applied = !has_code && account.nonce == Word::from(auth.nonce.as_u64());
+        if !is_applied {
+            state.account_write(
+                exec_step,
+                authority,
+                AccountField::CodeHash,
+                code_hash_prev,
+                code_hash_prev,
+            )?;
+            if exists {
+                state.account_write(
+                    exec_step,
+                    authority,
+                    AccountField::Nonce,
+                    account.nonce,
+                    account.nonce,
+                )?;
+            }
+            continue;
+        }
+
+        let code = if auth.address.is_zero() {
+            vec![]
+        } else {
+            eip7702::delegation_designator(auth.address)
+        };
+        let code_hash = state.code_db.insert(code.clone());
+        if !code.is_empty() {
+            let bytecode = state.bytecode(code_hash)?;
+            let call_id = state.call()?.call_id;
+            state.push_copy(
+                exec_step,
+                CopyEvent {
+                    src_type: CopyDataType::Bytecode,
+                    src_id: NumberOrHash::Hash(code_hash),
+                    src_addr: 0,
+                    src_addr_end: code.len() as u64,
+                    dst_type: CopyDataType::RlcAcc,
+                    dst_id: NumberOrHash::Number(call_id),
+                    dst_addr: 0,
+                    log_id: None,
+                    rw_counter_start: state.block_ctx.rwc,
+                    bytes: bytecode
+                        .code
+                        .iter()
+                        .map(|element| (element.value, element.is_code))
+                        .collect(),
+                },
+            )?;
+        }
+        #[cfg(feature = "poseidon-codehash")]
+        {
+            let keccak_code_hash = H256(ethers_core::utils::keccak256(&code));
+            if !code.is_empty() {
+                state.block.sha3_inputs.push(code.clone());
+            }
+            state.account_write(
+                exec_step,
+                authority,
+                AccountField::KeccakCodeHash,
+                keccak_code_hash.to_word(),
+                if exists {
+                    account.keccak_code_hash.to_word()
+                } else {
+                    Word::zero()
+                },
+            )?;
+        }
+        state.account_write(
+            exec_step,
+            authority,
+            AccountField::CodeHash,
+            code_hash.to_word(),
+            code_hash_prev,
+        )?;
+        #[cfg(feature = "poseidon-codehash")]
+        state.account_write(
+            exec_step,
+            authority,
+            AccountField::CodeSize,
+            code.len().into(),
+            account.code_size,
+        )?;
+        state.account_write(
+            exec_step,
+            authority,
+            AccountField::Nonce,
+            account.nonce + 1,
+            account.nonce,
+        )?;
+        if exists {
+            refund += PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST;
+        }
+    }
+
+    state.push_op(
+        exec_step,
+        RW::WRITE,
+        TxRefundOp {
+            tx_id: state.tx_ctx.id(),
+            value: refund,
+            value_prev: refund_prev,
+        },
+    );
+    state.sdb.set_refund(refund);
+
     Ok(())
 }
 
 pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
-    let mut exec_step = state.new_end_tx_step();
+    let mut exec_step = state.new_end_tx_step()?;
     let call = state.tx.calls()[0].clone();
 
     state.call_context_read(
@@ -739,6 +997,13 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         CallContextField::IsPersistent,
         Word::from(call.is_persistent as u8),
     );
+    #[cfg(feature = "scroll")]
+    state.call_context_read(
+        &mut exec_step,
+        call.call_id,
+        CallContextField::L1Fee,
+        state.tx.l1_fee().into(),
+    );
 
     let refund = state.sdb.refund();
     state.push_op(
@@ -751,15 +1016,33 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         },
     );
 
-    let effective_refund =
-        refund.min((state.tx.gas - exec_step.gas_left.0) / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    let gas_used = state
+        .tx
+        .gas
+        .checked_sub(exec_step.gas_left.0)
+        .ok_or(Error::InvalidGethExecTrace("gas left above the tx gas"))?;
+    let effective_refund = refund.min(gas_used / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    let gas_paid = gas_used - effective_refund;
+    // A valid tx of a Prague block uses at least the floor cost of its call
+    // data of EIP-7623, after the refund.
+    let (gas_used, gas_paid) = if state.tx.is_prague && !state.tx.is_invalid {
+        let floor = eth_types::evm_types::gas_utils::tx_floor_data_gas(&state.tx.input);
+        (gas_used.max(floor), gas_paid.max(floor))
+    } else {
+        (gas_used, gas_paid)
+    };
     let (found, caller_account) = state.sdb.get_account(&call.caller_address);
     if !found {
         return Err(Error::AccountNotFound(call.caller_address));
     }
     let caller_balance_prev = caller_account.balance;
-    let caller_balance =
-        caller_balance_prev + state.tx.gas_price * (exec_step.gas_left.0 + effective_refund);
+    // An invalid tx paid nothing upfront, so nothing is refunded.
+    let caller_balance = if state.tx.is_invalid {
+        caller_balance_prev
+    } else {
+        // The refund is part of the fee paid in BeginTx, so it can't overflow.
+        caller_balance_prev + state.tx.gas_price * (state.tx.gas - gas_paid)
+    };
     state.account_write(
         &mut exec_step,
         call.caller_address,
@@ -772,18 +1055,30 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         .block
         .headers
         .get(&state.tx.block_num)
-        .unwrap()
+        .ok_or(Error::InternalError("block header of the tx not found"))?
         .clone();
-    let effective_tip = state.tx.gas_price - block_info.base_fee;
-    let gas_cost = state.tx.gas - exec_step.gas_left.0 - effective_refund;
-    let coinbase_reward = effective_tip * gas_cost + state.tx_ctx.l1_fee;
+    // System txs pay no gas and are exempt from the base fee.
+    let effective_tip = if state.tx.is_system {
+        Word::zero()
+    } else {
+        state
+            .tx
+            .gas_price
+            .checked_sub(block_info.base_fee)
+            .ok_or(Error::InternalError("tx gas price below the base fee"))?
+    };
+    let l1_fee = if state.tx.is_invalid {
+        0
+    } else {
+        state.tx_ctx.l1_fee
+    };
+    let coinbase_reward = effective_tip * gas_paid + l1_fee;
     log::trace!(
-        "coinbase reward = ({} - {}) * ({} - {} - {}) = {}",
+        "coinbase reward = ({} - {}) * {} + {} = {}",
         state.tx.gas_price,
         block_info.base_fee,
-        state.tx.gas,
-        exec_step.gas_left.0,
-        effective_refund,
+        gas_paid,
+        l1_fee,
         coinbase_reward
     );
     let (found, coinbase_account) = state.sdb.get_account_mut(&block_info.coinbase);
@@ -792,7 +1087,9 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         return Err(Error::AccountNotFound(block_info.coinbase));
     }
     let coinbase_balance_prev = coinbase_account.balance;
-    let coinbase_balance = coinbase_balance_prev + coinbase_reward;
+    let coinbase_balance = coinbase_balance_prev
+        .checked_add(coinbase_reward)
+        .ok_or(Error::InternalError("coinbase balance overflows 256 bits"))?;
     state.account_write(
         &mut exec_step,
         block_info.coinbase,
@@ -827,7 +1124,7 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         )?;
     }
 
-    state.block_ctx.cumulative_gas_used += state.tx.gas - exec_step.gas_left.0;
+    state.block_ctx.cumulative_gas_used += gas_used;
     state.tx_receipt_write(
         &mut exec_step,
         state.tx_ctx.id(),
@@ -835,6 +1132,22 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         state.block_ctx.cumulative_gas_used,
     )?;
 
+    // written by the REVERT of the root call, zero otherwise
+    state.call_context_read(
+        &mut exec_step,
+        call.call_id,
+        CallContextField::RevertDataHash,
+        state.tx_ctx.revert_data_hash.to_word(),
+    );
+
+    let logs = tx_receipt_logs(&state.block.container.tx_log, state.tx_ctx.id());
+    state.tx.receipt = TxReceipt {
+        status: call.is_persistent,
+        cumulative_gas_used: state.block_ctx.cumulative_gas_used,
+        logs,
+        revert_data_hash: state.tx_ctx.revert_data_hash,
+    };
+
     if !state.tx_ctx.is_last_tx() {
         state.call_context_write(
             &mut exec_step,
@@ -847,8 +1160,67 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
     Ok(exec_step)
 }
 
-// Add 3 RW read operations for transaction L1 fee.
-fn gen_tx_l1_fee_ops(state: &mut CircuitInputStateRef, exec_step: &mut ExecStep) {
+/// Reads the copy of a CALLDATACOPY, CODECOPY or RETURNDATACOPY step, and
+/// checks that its gas cost is the one of geth.
+pub(crate) fn memory_copy(
+    exec_step: &ExecStep,
+    geth_step: &GethExecStep,
+) -> Result<MemoryCopy, Error> {
+    let memory_copy = MemoryCopy::from_stack(geth_step.op, &geth_step.stack)?
+        .ok_or(Error::InternalError("step doesn't copy to memory"))?;
+    let gas_cost =
+        GasCost::FASTEST.as_u64() + memory_copy.gas_cost(exec_step.memory_size as u64 / 32);
+    if gas_cost != geth_step.gas_cost.0 {
+        log::error!(
+            "{:?} gas cost {} differs from geth's {} ({:?}, memory_size {})",
+            geth_step.op,
+            gas_cost,
+            geth_step.gas_cost.0,
+            memory_copy,
+            exec_step.memory_size
+        );
+        return Err(Error::InvalidGethExecStep(
+            "memory copy gas cost differs from geth's",
+            Box::new(geth_step.clone()),
+        ));
+    }
+    Ok(memory_copy)
+}
+
+/// Logs of the receipt of tx `tx_id`, rebuilt from its tx log writes, which
+/// are the last ones of `tx_log` when the tx ends.
+fn tx_receipt_logs(tx_log: &[Operation<TxLogOp>], tx_id: usize) -> Vec<TxReceiptLog> {
+    let start = tx_log.len()
+        - tx_log
+            .iter()
+            .rev()
+            .take_while(|op| op.op().tx_id == tx_id)
+            .count();
+    let mut logs: Vec<TxReceiptLog> = Vec::new();
+    for op in &tx_log[start..] {
+        let op = op.op();
+        if op.field == TxLogField::Address {
+            logs.push(TxReceiptLog {
+                address: op.value.to_address(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let log = logs.last_mut().expect("tx log starts with its address");
+        match op.field {
+            TxLogField::Topic => log.topics.push(H256::from_uint(&op.value)),
+            TxLogField::Data => log.data.push(op.value.low_u64() as u8),
+            TxLogField::Address => unreachable!(),
+        }
+    }
+    logs
+}
+
+// Add 3 RW read operations for transaction L1 fee, and with the `scroll`
+// feature a call context write of the fee so that EndTx can credit it to the
+// coinbase.
+#[cfg_attr(not(feature = "scroll"), allow(unused_variables))]
+fn gen_tx_l1_fee_ops(state: &mut CircuitInputStateRef, exec_step: &mut ExecStep, call_id: usize) {
     let tx_id = state.tx_ctx.id();
 
     let base_fee = Word::from(state.tx.l1_fee.base_fee);
@@ -895,6 +1267,13 @@ fn gen_tx_l1_fee_ops(state: &mut CircuitInputStateRef, exec_step: &mut ExecStep)
             fee_scalar_committed,
         ),
     );
+    #[cfg(feature = "scroll")]
+    state.call_context_write(
+        exec_step,
+        call_id,
+        CallContextField::L1Fee,
+        state.tx.l1_fee().into(),
+    );
 }
 
 #[derive(Debug, Copy, Clone)]
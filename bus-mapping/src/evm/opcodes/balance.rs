@@ -64,7 +64,12 @@ impl Opcode for Balance {
         } else {
             H256::zero()
         };
-        debug_assert_eq!(balance, geth_steps[1].stack.nth_last(0)?);
+        if balance != geth_steps[1].stack.nth_last(0)? {
+            return Err(Error::InvalidGethExecStep(
+                "balance differs from the trace",
+                Box::new(geth_steps[1].clone()),
+            ));
+        }
         state.account_read(
             &mut exec_step,
             address,
@@ -0,0 +1,131 @@
+use crate::{circuit_input_builder::ExecState, mock::BlockData, state_db::CodeDB, Error};
+use eth_types::{
+    bytecode,
+    geth_types::{Account, GethData},
+    Address, Word,
+};
+use ethers_core::utils::get_contract_address;
+use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS};
+
+/// Trace a transfer of 1 ether from account 1 to account 0, then apply
+/// `modify` to the traced block before handling it, to reach states that the
+/// geth tracer refuses to execute.
+fn handle_modified_block(modify: impl FnOnce(&mut GethData)) -> Result<(), Error> {
+    let mut block: GethData = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(bytecode! { STOP }),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .value(eth(1));
+        },
+        |block, _tx| block,
+    )
+    .unwrap()
+    .into();
+    modify(&mut block);
+
+    let block_data = BlockData::new_from_geth_data(block);
+    let mut builder = block_data.new_circuit_input_builder();
+    builder.handle_block(&block_data.eth_block, &block_data.geth_traces)
+}
+
+fn account_mut(block: &mut GethData, address: Address) -> &mut Account {
+    block
+        .accounts
+        .iter_mut()
+        .find(|account| account.address == address)
+        .unwrap()
+}
+
+#[test]
+fn begin_tx_caller_nonce_at_max() {
+    let result = handle_modified_block(|block| {
+        let caller = block.eth_block.transactions[0].from;
+        account_mut(block, caller).nonce = u64::MAX.into();
+        block.eth_block.transactions[0].nonce = u64::MAX.into();
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InternalError("caller nonce is at its maximum"))
+    ));
+}
+
+#[test]
+fn begin_tx_receiver_balance_overflow() {
+    let result = handle_modified_block(|block| {
+        let receiver = block.eth_block.transactions[0].to.unwrap();
+        account_mut(block, receiver).balance = Word::MAX;
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InternalError("receiver balance overflows 256 bits"))
+    ));
+}
+
+#[test]
+fn begin_tx_fee_overflow() {
+    let result = handle_modified_block(|block| {
+        block.eth_block.transactions[0].gas_price = Some(Word::MAX);
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InternalError("tx fee overflows 256 bits"))
+    ));
+}
+
+#[test]
+fn begin_tx_insufficient_sender_balance() {
+    let result = handle_modified_block(|block| {
+        let caller = block.eth_block.transactions[0].from;
+        account_mut(block, caller).balance = Word::one();
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InternalError("sender balance is insufficient"))
+    ));
+}
+
+#[test]
+fn begin_tx_deploy_empty_init_code() {
+    let block: GethData = TestContext::<1, 1>::new(
+        None,
+        |accs| {
+            accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+        },
+        |mut txs, _accs| {
+            txs[0].from(MOCK_ACCOUNTS[0]).value(eth(1));
+        },
+        |block, _tx| block,
+    )
+    .unwrap()
+    .into();
+
+    let block_data = BlockData::new_from_geth_data(block);
+    let mut builder = block_data.new_circuit_input_builder();
+    builder
+        .handle_block(&block_data.eth_block, &block_data.geth_traces)
+        .unwrap();
+
+    // Nothing is executed between BeginTx and EndTx.
+    let tx = &builder.block.txs()[0];
+    assert!(tx.is_create());
+    let exec_states: Vec<_> = tx
+        .steps()
+        .iter()
+        .map(|step| step.exec_state.clone())
+        .collect();
+    assert_eq!(exec_states, [ExecState::BeginTx, ExecState::EndTx]);
+    assert!(tx.receipt.status);
+
+    // The contract is created without code.
+    let (found, contract) = builder
+        .sdb
+        .get_account(&get_contract_address(MOCK_ACCOUNTS[0], Word::zero()));
+    assert!(found);
+    assert_eq!(contract.nonce, Word::one());
+    assert_eq!(contract.balance, eth(1));
+    assert_eq!(contract.code_hash, CodeDB::empty_code_hash());
+    assert!(contract.code_size.is_zero());
+}
@@ -1,7 +1,7 @@
-use super::Opcode;
+use super::{memory_copy, Opcode};
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, MemoryCopy, NumberOrHash,
     },
     operation::CallContextField,
     Error,
@@ -18,18 +18,21 @@ impl Opcode for Calldatacopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_calldatacopy_step(state, geth_step)?];
+        let memory_copy = memory_copy(&exec_steps[0], geth_step)?;
 
         // reconstruction
-        let memory_offset = geth_step.stack.nth_last(0)?;
-        let data_offset = geth_step.stack.nth_last(1)?;
-        let length = geth_step.stack.nth_last(2)?;
         let call_ctx = state.call_ctx_mut()?;
         let memory = &mut call_ctx.memory;
 
-        memory.copy_from(memory_offset, data_offset, length, &call_ctx.call_data);
+        memory.copy_from(
+            memory_copy.dst_offset,
+            memory_copy.src_offset,
+            memory_copy.length,
+            &call_ctx.call_data,
+        );
 
-        let copy_event = gen_copy_event(state, geth_step)?;
-        state.push_copy(&mut exec_steps[0], copy_event);
+        let copy_event = gen_copy_event(state, geth_step, &memory_copy)?;
+        state.push_copy(&mut exec_steps[0], copy_event)?;
         Ok(exec_steps)
     }
 }
@@ -95,19 +98,22 @@ fn gen_calldatacopy_step(
 fn gen_copy_event(
     state: &mut CircuitInputStateRef,
     geth_step: &GethExecStep,
+    memory_copy: &MemoryCopy,
 ) -> Result<CopyEvent, Error> {
     let rw_counter_start = state.block_ctx.rwc;
 
-    let memory_offset = geth_step.stack.nth_last(0)?;
-    let data_offset = geth_step.stack.nth_last(1)?;
-    let length = geth_step.stack.nth_last(2)?.as_u64();
+    let memory_offset = memory_copy.dst_offset;
+    let data_offset = memory_copy.src_offset;
+    let length = memory_copy.num_copy_bytes();
 
     let call_data_offset = state.call()?.call_data_offset;
     let call_data_length = state.call()?.call_data_length;
 
     // Get low Uint64 of offset.
     let dst_addr = memory_offset.low_u64();
-    let src_addr_end = call_data_offset.checked_add(call_data_length).unwrap();
+    let src_addr_end = call_data_offset
+        .checked_add(call_data_length)
+        .ok_or(Error::InternalError("call data end overflows 64 bits"))?;
 
     // Reset offset to call_data_length if overflow, and set source start to the
     // minimum value of offset and call_data_length.
@@ -166,6 +172,7 @@ mod calldatacopy_tests {
         MockCallBytecodeParams,
     };
     use pretty_assertions::assert_eq;
+    use rand::random;
 
     #[test]
     fn calldatacopy_opcode_internal() {
@@ -518,4 +525,64 @@ mod calldatacopy_tests {
             assert!(!is_code);
         }
     }
+
+    #[test]
+    fn calldatacopy_opcode_root_data_offset_overflow() {
+        let size = 0x20;
+        let dst_offset = 0x00;
+        let calldata = vec![1, 3, 5, 7, 9, 2, 4, 6, 8];
+
+        // Data offsets beyond Uint64, at its boundary and a random one in [2^64,
+        // 2^256), copy only zeros.
+        let mut overflow_offset = Word::from_big_endian(&random::<[u8; 32]>());
+        overflow_offset |= Word::one() << 64;
+        for offset in [Word::from(u64::MAX), Word::one() << 64, overflow_offset] {
+            let code = bytecode! {
+                .op_calldatacopy(dst_offset, offset, size)
+                STOP
+            };
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(code),
+                |mut txs, accs| {
+                    txs[0]
+                        .to(accs[0].address)
+                        .from(accs[1].address)
+                        .input(calldata.clone().into());
+                },
+                |block, _tx| block,
+            )
+            .unwrap()
+            .into();
+
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+
+            let expected_call_id = builder.block.txs()[0].calls()[0].call_id;
+            assert_eq!(
+                builder
+                    .block
+                    .container
+                    .memory
+                    .iter()
+                    .map(|op| (op.rw(), op.op().clone()))
+                    .collect::<Vec<(RW, MemoryOp)>>(),
+                (0..size)
+                    .map(|idx| (
+                        RW::WRITE,
+                        MemoryOp::new(expected_call_id, (dst_offset + idx).into(), 0)
+                    ))
+                    .collect::<Vec<(RW, MemoryOp)>>(),
+            );
+
+            let copy_events = &builder.block.copy_events;
+            assert_eq!(copy_events.len(), 1);
+            assert_eq!(copy_events[0].src_addr, calldata.len() as u64);
+            assert_eq!(copy_events[0].src_addr_end, calldata.len() as u64);
+            assert_eq!(copy_events[0].bytes, vec![(0, false); size]);
+        }
+    }
 }
@@ -369,6 +369,105 @@ mod calldataload_tests {
         );
     }
 
+    fn test_offset_overflow_ok(offset: Word, is_root: bool) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        let code_b = bytecode! {
+            PUSH32(offset)
+            CALLDATALOAD
+            STOP
+        };
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            pushdata: rand_bytes(32),
+            call_data_length: 0x20,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                let to = if is_root {
+                    accs[0].address
+                } else {
+                    accs[1].address
+                };
+                txs[0]
+                    .to(to)
+                    .from(accs[2].address)
+                    .input(rand_bytes(64).into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALLDATALOAD))
+            .unwrap();
+
+        let call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
+
+        // Only 1 stack read and 1 stack write, and zero is pushed.
+        assert_eq!(step.bus_mapping_instance.len(), 2);
+        assert_eq!(
+            [0, 1]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), offset),
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(call_id, StackAddress::from(1023), Word::zero()),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn calldataload_opcode_offset_overflow() {
+        // A random offset in [2^64, 2^256).
+        let mut overflow_offset = Word::from_big_endian(&rand_bytes(32));
+        overflow_offset |= Word::one() << 64;
+        for offset in [Word::one() << 64, overflow_offset, Word::MAX] {
+            test_offset_overflow_ok(offset, true);
+            test_offset_overflow_ok(offset, false);
+        }
+    }
+
+    #[test]
+    fn calldataload_opcode_root_offset_boundary() {
+        let calldata = rand_bytes(64);
+        // The last byte of call data is read, right padded.
+        test_root_ok(63, calldata.clone(), {
+            let mut v = vec![0u8; 32];
+            v[0] = calldata[63];
+            Word::from_big_endian(&v)
+        });
+        // Offsets at and beyond the end of call data read zero.
+        let offset = random::<u64>().max(65);
+        for offset in [64, 65, offset, u64::MAX] {
+            test_root_ok(offset, calldata.clone(), Word::zero());
+        }
+    }
+
     #[test]
     fn calldataload_opcode_internal() {
         let pushdata = rand_bytes(0x08);
@@ -5,13 +5,13 @@ use crate::{
     },
     evm::opcodes::precompiles::gen_associated_ops as precompile_associated_ops,
     operation::{AccountField, CallContextField, MemoryOp, TxAccessListAccountOp, RW},
-    precompile::{execute_precompiled, is_precompiled, PrecompileCalls},
+    precompile::{execute_precompiled, PrecompileCalls, PrecompileConstraintMode},
     state_db::CodeDB,
     Error,
 };
 use eth_types::{
     evm_types::{
-        gas_utils::{eip150_gas, memory_expansion_gas_cost},
+        gas_utils::{self, eip150_gas},
         Gas, GasCost, OpcodeId,
     },
     GethExecStep, ToWord, Word,
@@ -143,7 +143,9 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         }
 
         let (found, sender_account) = state.sdb.get_account(&call.caller_address);
-        debug_assert!(found);
+        if !found {
+            return Err(Error::AccountNotFound(call.caller_address));
+        }
 
         let caller_balance = sender_account.balance;
         let is_call_or_callcode = call.kind == CallKind::Call || call.kind == CallKind::CallCode;
@@ -165,7 +167,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
 
         let code_address = call.code_address();
         let is_precompile = code_address
-            .map(|ref addr| is_precompiled(addr))
+            .map(|ref addr| state.precompiles.is_precompiled(addr))
             .unwrap_or(false);
         // TODO: What about transfer for CALLCODE?
         // Transfer value only for CALL opcode, is_precheck_ok = true.
@@ -182,20 +184,13 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
 
         // Calculate next_memory_word_size and callee_gas_left manually in case
         // there isn't next geth_step (e.g. callee doesn't have code).
-        debug_assert_eq!(exec_step.memory_size % 32, 0);
         let curr_memory_word_size = (exec_step.memory_size as u64) / 32;
-        let next_memory_word_size = [
-            curr_memory_word_size,
-            (call.call_data_offset + call.call_data_length + 31) / 32,
-            (call.return_data_offset + call.return_data_length + 31) / 32,
-        ]
-        .into_iter()
-        .max()
-        .unwrap();
+        let (next_memory_word_size, memory_expansion_gas_cost) = exec_step.memory_expansion([
+            (call.call_data_offset, call.call_data_length),
+            (call.return_data_offset, call.return_data_length),
+        ]);
 
         let has_value = !call.value.is_zero() && !call.is_delegatecall();
-        let memory_expansion_gas_cost =
-            memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
         let gas_cost = if is_warm {
             GasCost::WARM_ACCESS.as_u64()
         } else {
@@ -211,22 +206,23 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
             0
         } + memory_expansion_gas_cost;
         let gas_specified = geth_step.stack.last()?;
-        debug_assert!(
-            geth_step.gas.0 >= gas_cost,
-            "gas {:?} gas_cost {:?} memory_expansion_gas_cost {:?}",
-            geth_step.gas.0,
-            gas_cost,
-            memory_expansion_gas_cost
-        );
-        let callee_gas_left = eip150_gas(geth_step.gas.0 - gas_cost, gas_specified);
+        let gas_after_cost = geth_step.gas.0.checked_sub(gas_cost).ok_or_else(|| {
+            Error::InvalidGethExecStep(
+                "gas of the call below its cost",
+                Box::new(geth_step.clone()),
+            )
+        })?;
+        let callee_gas_left = eip150_gas(gas_after_cost, gas_specified);
+        let callee_gas_left_with_stipend =
+            gas_utils::callee_gas_left(gas_after_cost, gas_specified, has_value);
 
         // There are 4 branches from here.
         // add failure case for insufficient balance or error depth in the future.
         if geth_steps[0].op == OpcodeId::CALL
             && geth_steps[1].depth == geth_steps[0].depth + 1
-            && geth_steps[1].gas.0 != callee_gas_left + if has_value { 2300 } else { 0 }
+            && geth_steps[1].gas.0 != callee_gas_left_with_stipend
         {
-            // panic with full info
+            // log the full info before failing
             let info1 = format!("callee_gas_left {} gas_specified {} gas_cost {} is_warm {} has_value {} current_memory_word_size {} next_memory_word_size {}, memory_expansion_gas_cost {}",
                     callee_gas_left, gas_specified, gas_cost, is_warm, has_value, curr_memory_word_size, next_memory_word_size, memory_expansion_gas_cost);
             let info2 = format!("args gas:{:?} addr:{:?} value:{:?} cd_pos:{:?} cd_len:{:?} rd_pos:{:?} rd_len:{:?}",
@@ -242,32 +238,106 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 "step0 {:?} step1 {:?} call {:?}, {} {}",
                 geth_steps[0], geth_steps[1], call, info1, info2
             );
-            debug_assert_eq!(
-                geth_steps[1].gas.0,
-                callee_gas_left + if has_value { 2300 } else { 0 },
-                "{}",
-                full_ctx
-            );
+            log::error!("{}", full_ctx);
+            return Err(Error::InvalidGethExecStep(
+                "gas of the callee differs from the trace",
+                Box::new(geth_steps[1].clone()),
+            ));
         }
 
+        let custom_precompile =
+            code_address.and_then(|ref addr| state.precompiles.get(addr).cloned());
+
         match (!is_precheck_ok, is_precompile, is_empty_code_hash) {
-            // 1. Call to precompiled.
+            // 1. Call to a precompile registered by the embedding chain. The
+            // copy circuit has no tag for it, so only the memory writes of the
+            // returned bytes are recorded and the call is closed in this step.
+            // The circuits have no constraint for it, so the call is recorded
+            // for the witness of the block to be refused.
+            (false, true, _) if custom_precompile.is_some() => {
+                let code_address = code_address.unwrap();
+                let provider = custom_precompile.unwrap();
+                if provider.constraint_mode() != PrecompileConstraintMode::Stub {
+                    return Err(Error::UnsupportedPrecompile(code_address));
+                }
+
+                let caller_memory = state.caller_ctx()?.memory.0.clone();
+                let input: &[u8] = if args_length != 0 {
+                    &caller_memory[args_offset..args_offset + args_length]
+                } else {
+                    &[]
+                };
+                let (result, contract_gas_cost) = provider.execute(input, callee_gas_left);
+                state.block.stub_precompile_calls.push(code_address);
+
+                let caller_ctx_mut = state.caller_ctx_mut()?;
+                let length = min(result.len(), ret_length);
+                if length > 0 {
+                    caller_ctx_mut.memory.extend_at_least(ret_offset + length);
+                    caller_ctx_mut.memory.0[ret_offset..ret_offset + length]
+                        .copy_from_slice(&result[..length]);
+                }
+
+                for (field, value) in [
+                    (CallContextField::LastCalleeId, call.call_id.into()),
+                    (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                    (
+                        CallContextField::LastCalleeReturnDataLength,
+                        result.len().into(),
+                    ),
+                ] {
+                    state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+                }
+                if call.is_success() {
+                    for (i, byte) in result.iter().take(length).enumerate() {
+                        state.push_op(
+                            &mut exec_step,
+                            RW::WRITE,
+                            MemoryOp::new(
+                                call.caller_id,
+                                (call.return_data_offset + i as u64).into(),
+                                *byte,
+                            ),
+                        );
+                    }
+                }
+                state.handle_precompile_return(&mut exec_step, geth_steps, false, result)?;
+
+                let real_cost = real_gas_cost(geth_steps)?;
+                if real_cost != gas_cost + contract_gas_cost {
+                    return Err(Error::InvalidGethExecStep(
+                        "gas cost of the call differs from the trace",
+                        Box::new(geth_steps[0].clone()),
+                    ));
+                }
+                exec_step.gas_cost = GasCost(real_cost);
+
+                Ok(vec![exec_step])
+            }
+            // 2. Call to precompiled.
             (false, true, _) => {
                 let code_address = code_address.unwrap();
-                let precompile_call: PrecompileCalls = code_address.0[19].into();
+                let precompile_call = PrecompileCalls::try_from(code_address)?;
 
                 // get the result of the precompile call.
                 let caller_ctx = state.caller_ctx()?;
                 let caller_memory = caller_ctx.memory.0.clone();
-                let (result, contract_gas_cost) = execute_precompiled(
-                    &code_address,
-                    if args_length != 0 {
-                        &caller_memory[args_offset..args_offset + args_length]
-                    } else {
-                        &[]
-                    },
-                    callee_gas_left,
-                );
+                let input: &[u8] = if args_length != 0 {
+                    &caller_memory[args_offset..args_offset + args_length]
+                } else {
+                    &[]
+                };
+                if !precompile_call.is_input_supported(input) {
+                    return Err(Error::PrecompileUnsupportedInput {
+                        tx_id: state.tx_ctx.id(),
+                        call_id: call.call_id,
+                        caller_id: call.caller_id,
+                        precompile: precompile_call,
+                        input_len: input.len(),
+                    });
+                }
+                let (result, contract_gas_cost) =
+                    execute_precompiled(&code_address, input, callee_gas_left);
 
                 log::trace!(
                     "precompile returned data len {} gas {}",
@@ -277,7 +347,6 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
 
                 // mutate the caller memory.
                 let caller_ctx_mut = state.caller_ctx_mut()?;
-                caller_ctx_mut.return_data = result.clone();
                 let length = min(result.len(), ret_length);
                 if length > 0 {
                     caller_ctx_mut.memory.extend_at_least(ret_offset + length);
@@ -379,7 +448,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             rw_counter_start,
                             bytes,
                         },
-                    );
+                    )?;
                 }
 
                 // write the result in the callee's memory.
@@ -408,7 +477,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             rw_counter_start,
                             bytes,
                         },
-                    );
+                    )?;
                 }
 
                 // insert another copy event (output) for this step.
@@ -442,7 +511,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             rw_counter_start,
                             bytes,
                         },
-                    );
+                    )?;
                 }
 
                 // TODO: when more precompiles are supported and each have their own different
@@ -452,14 +521,15 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                     geth_steps[1].clone(),
                     call.clone(),
                     precompile_call,
+                    input,
+                    &result,
                 )?;
 
                 // Make the Precompile execution step to handle return logic and restore to caller
                 // context (similar as STOP and RETURN).
-                state.handle_return(&mut precompile_step, geth_steps, true)?;
+                state.handle_precompile_return(&mut precompile_step, geth_steps, true, result)?;
 
-                let real_cost = geth_steps[0].gas.0 - geth_steps[1].gas.0;
-                debug_assert_eq!(real_cost, gas_cost + contract_gas_cost);
+                let real_cost = real_gas_cost(geth_steps)?;
                 exec_step.gas_cost = GasCost(gas_cost + contract_gas_cost);
                 if real_cost != exec_step.gas_cost.0 {
                     log::warn!(
@@ -476,7 +546,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
 
                 Ok(vec![exec_step, precompile_step])
             }
-            // 2. Call to account with empty code.
+            // 3. Call to account with empty code.
             (false, _, true) => {
                 for (field, value) in [
                     (CallContextField::LastCalleeId, 0.into()),
@@ -501,7 +571,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
 
                 Ok(vec![exec_step])
             }
-            // 3. Call to account with non-empty code.
+            // 4. Call to account with non-empty code.
             (false, _, false) => {
                 for (field, value) in [
                     (
@@ -550,15 +620,9 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         CallContextField::ReturnDataLength,
                         call.return_data_length.into(),
                     ),
-                    (
-                        CallContextField::Value,
-                        // Should set to value of current call for DELEGATECALL.
-                        if call.kind == CallKind::DelegateCall {
-                            current_call.value
-                        } else {
-                            call.value
-                        },
-                    ),
+                    // The value of the current call for DELEGATECALL, as set
+                    // by `parse_call`.
+                    (CallContextField::Value, call.value),
                     (CallContextField::IsSuccess, (call.is_success as u64).into()),
                     (CallContextField::IsStatic, (call.is_static as u64).into()),
                     (CallContextField::LastCalleeId, 0.into()),
@@ -574,7 +638,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 Ok(vec![exec_step])
             }
 
-            // 4. insufficient balance or error depth cases.
+            // 5. insufficient balance or error depth cases.
             (true, _, _) => {
                 for (field, value) in [
                     (CallContextField::LastCalleeId, 0.into()),
@@ -590,6 +654,18 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
     }
 }
 
+/// Returns the gas spent by the call from the gas of its step and of the next
+/// step in the caller.
+fn real_gas_cost(geth_steps: &[GethExecStep]) -> Result<u64, Error> {
+    geth_steps[0]
+        .gas
+        .0
+        .checked_sub(geth_steps[1].gas.0)
+        .ok_or_else(|| {
+            Error::InvalidGethExecStep("gas increased by the call", Box::new(geth_steps[1].clone()))
+        })
+}
+
 #[cfg(any(test, feature = "test"))]
 pub mod tests {
     use eth_types::{evm_types::OpcodeId, Bytecode, Word};
@@ -986,6 +1062,385 @@ pub mod tests {
                     test_call.name
                 );
             }
+
+            // The recovered signature must be handed over for verification.
+            let expected_sign_datas = usize::from(test_call.address == Word::from(0x1));
+            assert_eq!(
+                builder.block.ecrecover_sign_datas().len(),
+                expected_sign_datas,
+                "{}",
+                test_call.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_precompiled_call_unsupported_input() {
+        use crate::{mock::BlockData, precompile::PrecompileCalls, Error};
+        use eth_types::{bytecode, geth_types::GethData, Word};
+        use mock::{
+            test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+            TestContext,
+        };
+
+        // base longer than supported by the circuits
+        let test_call = PrecompileCallArgs {
+            name: "modexp",
+            setup_code: bytecode! {
+                PUSH1(33) // Bsize
+                PUSH1(0)
+                MSTORE
+                PUSH1(1) // Esize
+                PUSH1(0x20)
+                MSTORE
+                PUSH1(1) // Msize
+                PUSH1(0x40)
+                MSTORE
+            },
+            ret_size: Word::from(0x01),
+            ret_offset: Word::from(0x9F),
+            call_data_length: Word::from(0x83),
+            address: Word::from(0x5),
+            ..Default::default()
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(test_call.with_call_op(OpcodeId::STATICCALL)),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+        let context = err.context().expect("failure in a step");
+        assert_eq!(
+            (context.tx_index, context.opcode),
+            (0, Some(OpcodeId::STATICCALL))
+        );
+        assert!(
+            matches!(
+                err.root_cause(),
+                Error::PrecompileUnsupportedInput {
+                    tx_id: 1,
+                    precompile: PrecompileCalls::Modexp,
+                    input_len: 0x83,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_calls_match_geth_frames() {
+        use crate::mock::BlockData;
+        use eth_types::{address, geth_types::GethData};
+        use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+        let callee = address!("0x000000000000000000000000000000000000cafe");
+        // the callee calls the identity precompile, then reverts
+        let mut callee_code = Bytecode::default();
+        callee_code
+            .op_staticcall(0x1000, 0x4, 0, 0x20, 0, 0x20)
+            .op_pop()
+            .op_revert(0, 0);
+        let mut code = Bytecode::default();
+        code.op_call(0x10000, callee, 0, 0, 0, 0, 0)
+            .op_pop()
+            .op_callcode(0x10000, callee, 0, 0, 0, 0, 0)
+            .op_pop()
+            .op_delegatecall(0x10000, callee, 0, 0, 0, 0)
+            .op_pop()
+            .op_staticcall(0x10000, callee, 0, 0, 0, 0)
+            .op_pop()
+            // transfers more than the balance, so fails before the callee
+            // is entered
+            .op_call(0x10000, callee, eth(100), 0, 0, 0, 0)
+            .op_pop()
+            .op_stop();
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(callee).code(callee_code);
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let call_traces = ctx.call_traces().unwrap();
+        let block: GethData = ctx.into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs[0];
+        // the tx, 4 calls to the callee with a call to the precompile each,
+        // and the call failing its prechecks
+        assert_eq!(tx.calls().len(), 10);
+        assert_eq!(call_traces[0].flatten().len(), 9);
+        tx.check_calls(&call_traces[0]).unwrap();
+
+        // a frame missing from the calls
+        let mut call_trace = call_traces[0].clone();
+        call_trace.calls.push(call_trace.calls[0].clone());
+        assert!(tx.check_calls(&call_trace).is_err());
+        // a depth differing from the one of the calls
+        let mut call_trace = call_traces[0].clone();
+        call_trace.calls[1].depth = Some(3);
+        assert!(tx.check_calls(&call_trace).is_err());
+        // a caller differing from the one of the calls
+        let mut call_trace = call_traces[0].clone();
+        call_trace.calls[1].from = callee;
+        assert!(tx.check_calls(&call_trace).is_err());
+    }
+
+    /// Run a tx calling a contract which makes the `first` call to a
+    /// contract making the `second` call to a contract storing the CALLER,
+    /// CALLVALUE and ADDRESS it sees, with CALLCODE or DELEGATECALL. Return
+    /// the calls of the tx, after checking them against the geth frames,
+    /// and the stored values.
+    fn run_context_chain(
+        first: OpcodeId,
+        second: OpcodeId,
+    ) -> (Vec<crate::circuit_input_builder::Call>, [Word; 3]) {
+        use crate::mock::BlockData;
+        use eth_types::{bytecode, geth_types::GethData, Address, ToWord};
+        use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+        let [root, middle, leaf] = [0xa1u64, 0xa2, 0xa3].map(Address::from_low_u64_be);
+        let call = |code: &mut Bytecode, op: OpcodeId, address: Address, value: u64| {
+            match op {
+                OpcodeId::CALLCODE => code.op_callcode(0x80000, address, value, 0, 0, 0, 0),
+                OpcodeId::DELEGATECALL => code.op_delegatecall(0x80000, address, 0, 0, 0, 0),
+                _ => unreachable!("not a call keeping the storage of the caller"),
+            };
+            code.op_pop().op_stop();
+        };
+        let mut root_code = Bytecode::default();
+        call(&mut root_code, first, middle, 2);
+        let mut middle_code = Bytecode::default();
+        call(&mut middle_code, second, leaf, 3);
+        let leaf_code = bytecode! {
+            CALLER
+            PUSH1(0)
+            SSTORE
+            CALLVALUE
+            PUSH1(1)
+            SSTORE
+            ADDRESS
+            PUSH1(2)
+            SSTORE
+            STOP
+        };
+
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(root).balance(eth(1)).code(root_code);
+                accs[1].address(middle).code(middle_code);
+                accs[2].address(leaf).code(leaf_code);
+                accs[3].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[3].address)
+                    .to(accs[0].address)
+                    .value(7.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let call_traces = ctx.call_traces().unwrap();
+        let block: GethData = ctx.into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let tx = &builder.block.txs[0];
+        tx.check_calls(&call_traces[0]).unwrap();
+
+        // all the calls run in the storage of the root contract
+        let stored = [0u64, 1, 2].map(|slot| *builder.sdb.get_storage(&root, &slot.into()).1);
+        assert_eq!(stored[2], root.to_word());
+        (tx.calls().to_vec(), stored)
+    }
+
+    #[test]
+    fn test_callcode_delegatecall_context_chains() {
+        use eth_types::{Address, ToWord};
+        use mock::MOCK_ACCOUNTS;
+
+        let root = Address::from_low_u64_be(0xa1);
+        let sender = MOCK_ACCOUNTS[0];
+        for (first, second, caller, value) in [
+            // the CALLCODE makes the root contract the msg.sender, which the
+            // DELEGATECALL keeps with the value of the CALLCODE
+            (OpcodeId::CALLCODE, OpcodeId::DELEGATECALL, root, 2u64),
+            (OpcodeId::DELEGATECALL, OpcodeId::CALLCODE, root, 3),
+            (OpcodeId::CALLCODE, OpcodeId::CALLCODE, root, 3),
+            (OpcodeId::DELEGATECALL, OpcodeId::DELEGATECALL, sender, 7),
+        ] {
+            let (calls, stored) = run_context_chain(first, second);
+            assert_eq!(
+                stored[..2],
+                [caller.to_word(), Word::from(value)],
+                "{:?} into {:?}",
+                first,
+                second
+            );
+            let leaf_call = calls.last().unwrap();
+            assert_eq!(
+                (leaf_call.caller_address, leaf_call.address, leaf_call.value),
+                (caller, root, Word::from(value))
+            );
+        }
+    }
+
+    /// Accounts and slots warmed by reverted calls, and by the successful
+    /// callees of reverted calls, are cold again once they revert, while the
+    /// ones warmed before stay warm (as in the `stEIP2929` and `stRevertTest`
+    /// tests of ethereum/tests).
+    #[test]
+    fn test_access_list_reverted_with_call() {
+        use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::Target};
+        use eth_types::{address, evm_types::GasCost, geth_types::GethData, Address};
+        use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+        let reverted = address!("0x00000000000000000000000000000000000000b1");
+        let nested = address!("0x00000000000000000000000000000000000000b2");
+        let delegated = address!("0x00000000000000000000000000000000000000b3");
+        let [x, y, z] = [0xa1u64, 0xa2, 0xa3].map(Address::from_low_u64_be);
+        let precompile = Address::from_low_u64_be(0x04);
+
+        // warms z and stops
+        let mut nested_code = Bytecode::default();
+        nested_code.op_balance(z).op_pop().op_stop();
+        // warms x, reads the warm y, warms z in a successful call and reverts
+        let mut reverted_code = Bytecode::default();
+        reverted_code
+            .op_balance(x)
+            .op_pop()
+            .op_balance(y)
+            .op_pop()
+            .op_call(0x10000, nested, 0, 0, 0, 0, 0)
+            .op_pop()
+            .op_revert(0, 0);
+        // warms the slot 5 of its caller and reverts
+        let mut delegated_code = Bytecode::default();
+        delegated_code.op_sload(5).op_pop().op_revert(0, 0);
+
+        let mut code = Bytecode::default();
+        code.op_balance(y)
+            .op_pop()
+            .op_call(0x10000, reverted, 0, 0, 0, 0, 0)
+            .op_pop()
+            .op_delegatecall(0x10000, delegated, 0, 0, 0, 0)
+            .op_pop();
+        // (opcode, address, warm) of the accesses after the reverted calls
+        let accesses = [
+            (OpcodeId::BALANCE, x, false),
+            (OpcodeId::BALANCE, y, true),
+            (OpcodeId::BALANCE, z, false),
+            (OpcodeId::SLOAD, MOCK_ACCOUNTS[0], false),
+            (OpcodeId::BALANCE, precompile, true),
+        ];
+        for (opcode, address, _) in accesses {
+            match opcode {
+                OpcodeId::SLOAD => code.op_sload(5),
+                _ => code.op_balance(address),
+            };
+            code.op_pop();
+        }
+        code.op_stop();
+
+        let ctx = TestContext::<5, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(reverted).code(reverted_code);
+                accs[3].address(nested).code(nested_code);
+                accs[4].address(delegated).code(delegated_code);
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let block: GethData = ctx.into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs[0];
+        let steps = tx
+            .steps()
+            .iter()
+            .filter(|step| {
+                step.call_index == 0
+                    && matches!(
+                        step.exec_state,
+                        ExecState::Op(OpcodeId::BALANCE | OpcodeId::SLOAD)
+                    )
+            })
+            .collect::<Vec<_>>();
+        // the first BALANCE of y, then the accesses after the calls
+        assert_eq!(steps.len(), 1 + accesses.len());
+        for (step, (opcode, address, warm)) in steps[1..].iter().zip(accesses) {
+            assert_eq!(step.exec_state, ExecState::Op(opcode));
+            let (is_warm_prev, cold_cost) = if opcode == OpcodeId::SLOAD {
+                let op_ref = step
+                    .bus_mapping_instance
+                    .iter()
+                    .find(|op_ref| op_ref.target() == Target::TxAccessListAccountStorage)
+                    .unwrap();
+                let op =
+                    builder.block.container.tx_access_list_account_storage[op_ref.as_usize()].op();
+                assert_eq!((op.address, op.key), (address, Word::from(5)));
+                (op.is_warm_prev, GasCost::COLD_SLOAD)
+            } else {
+                let op_ref = step
+                    .bus_mapping_instance
+                    .iter()
+                    .find(|op_ref| op_ref.target() == Target::TxAccessListAccount)
+                    .unwrap();
+                let op = builder.block.container.tx_access_list_account[op_ref.as_usize()].op();
+                assert_eq!(op.address, address);
+                (op.is_warm_prev, GasCost::COLD_ACCOUNT_ACCESS)
+            };
+            assert_eq!(is_warm_prev, warm, "{:?} of {:?}", opcode, address);
+            // geth charged the access as the bus-mapping found it
+            let expected_cost = if warm {
+                GasCost::WARM_ACCESS
+            } else {
+                cold_cost
+            };
+            assert_eq!(
+                step.gas_cost, expected_cost,
+                "{:?} of {:?}",
+                opcode, address
+            );
         }
     }
 }
@@ -1,12 +1,12 @@
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, MemoryCopy, NumberOrHash,
     },
     Error,
 };
-use eth_types::{Bytecode, GethExecStep};
+use eth_types::GethExecStep;
 
-use super::Opcode;
+use super::{memory_copy, Opcode};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Codecopy;
@@ -18,22 +18,19 @@ impl Opcode for Codecopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_codecopy_step(state, geth_step)?];
+        let memory_copy = memory_copy(&exec_steps[0], geth_step)?;
 
         // reconstruction
-        let dst_offset = geth_step.stack.nth_last(0)?;
-        let code_offset = geth_step.stack.nth_last(1)?;
-        let length = geth_step.stack.nth_last(2)?;
-
         let code_hash = state.call()?.code_hash;
-        let code = state.code(code_hash)?;
-
-        let call_ctx = state.call_ctx_mut()?;
-        let memory = &mut call_ctx.memory;
-
-        memory.copy_from(dst_offset, code_offset, length, &code);
+        state.copy_code_to_memory(
+            code_hash,
+            memory_copy.dst_offset,
+            memory_copy.src_offset,
+            memory_copy.length,
+        )?;
 
-        let copy_event = gen_copy_event(state, geth_step)?;
-        state.push_copy(&mut exec_steps[0], copy_event);
+        let copy_event = gen_copy_event(state, geth_step, &memory_copy)?;
+        state.push_copy(&mut exec_steps[0], copy_event)?;
         Ok(exec_steps)
     }
 }
@@ -67,15 +64,16 @@ fn gen_codecopy_step(
 fn gen_copy_event(
     state: &mut CircuitInputStateRef,
     geth_step: &GethExecStep,
+    memory_copy: &MemoryCopy,
 ) -> Result<CopyEvent, Error> {
     let rw_counter_start = state.block_ctx.rwc;
 
-    let dst_offset = geth_step.stack.nth_last(0)?;
-    let code_offset = geth_step.stack.nth_last(1)?;
-    let length = geth_step.stack.nth_last(2)?.as_u64();
+    let dst_offset = memory_copy.dst_offset;
+    let code_offset = memory_copy.src_offset;
+    let length = memory_copy.num_copy_bytes();
 
     let code_hash = state.call()?.code_hash;
-    let bytecode: Bytecode = state.code(code_hash)?.into();
+    let bytecode = state.bytecode(code_hash)?;
     let code_size = bytecode.code.len() as u64;
 
     // Get low Uint64 of offset.
@@ -130,6 +128,7 @@ mod codecopy_tests {
         mock::BlockData,
         operation::{MemoryOp, StackOp, RW},
         state_db::CodeDB,
+        Error,
     };
 
     #[test]
@@ -237,4 +236,38 @@ mod codecopy_tests {
             assert_eq!(*is_code, bytecode_element.is_code);
         }
     }
+
+    #[test]
+    fn codecopy_gas_cost_differing_from_geth() {
+        let code = bytecode! {
+            PUSH32(0x40)
+            PUSH32(0x00)
+            PUSH32(0x20)
+            CODECOPY
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let codecopy = block.geth_traces[0]
+            .struct_logs
+            .iter_mut()
+            .find(|step| step.op == OpcodeId::CODECOPY)
+            .unwrap();
+        // 3 + 2 words copied + expansion to 3 words
+        assert_eq!(codecopy.gas_cost.0, 3 + 2 * 3 + 9);
+        // charges the copy of a single word
+        codecopy.gas_cost.0 -= 3;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+        assert!(matches!(err.root_cause(), Error::InvalidGethExecStep(..)));
+    }
 }
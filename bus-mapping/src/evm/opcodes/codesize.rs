@@ -3,7 +3,7 @@ use crate::{
     Error,
 };
 
-use eth_types::GethExecStep;
+use eth_types::{GethExecStep, Word};
 
 use super::Opcode;
 
@@ -19,10 +19,14 @@ impl Opcode for Codesize {
         let mut exec_step = state.new_step(geth_step)?;
 
         let code_hash = state.call()?.code_hash;
-        let code = state.code(code_hash)?;
-        let codesize = code.len();
+        let codesize = state.bytecode(code_hash)?.code.len();
 
-        debug_assert_eq!(codesize, geth_steps[1].stack.last()?.as_usize());
+        if Word::from(codesize) != geth_steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "code size differs from the trace",
+                Box::new(geth_steps[1].clone()),
+            ));
+        }
 
         state.stack_write(
             &mut exec_step,
@@ -24,13 +24,15 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
 
         // Get low Uint64 of offset.
         let offset = geth_step.stack.nth_last(1)?.low_u64() as usize;
-        let length = geth_step.stack.nth_last(2)?.as_usize();
+        let length = usize::try_from(geth_step.stack.nth_last(2)?).map_err(|_| {
+            Error::InvalidGethExecStep("init code too long", Box::new(geth_step.clone()))
+        })?;
 
         if length != 0 {
-            state
-                .call_ctx_mut()?
-                .memory
-                .extend_at_least(offset + length);
+            let end = offset.checked_add(length).ok_or_else(|| {
+                Error::InvalidGethExecStep("init code out of memory", Box::new(geth_step.clone()))
+            })?;
+            state.call_ctx_mut()?.memory.extend_at_least(end);
         }
         let next_memory_word_size = state.call_ctx()?.memory_word_size();
 
@@ -359,7 +361,7 @@ fn handle_copy(
             log_id: None,
             bytes,
         },
-    );
+    )?;
 
     Ok((initialization_bytes, keccak_code_hash, code_hash))
 }
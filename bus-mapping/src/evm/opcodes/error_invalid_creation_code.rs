@@ -32,9 +32,17 @@ impl Opcode for ErrorCreationCode {
 
         assert!(length > U256::zero());
 
-        // read first byte and assert it is 0xef
-        let byte = state.call_ctx()?.memory.0[offset.as_usize()];
-        assert!(byte == 0xef);
+        // read first byte and check it is 0xef
+        let byte = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| state.call_ctx().ok()?.memory.0.get(offset).copied())
+            .filter(|byte| *byte == 0xef)
+            .ok_or_else(|| {
+                Error::InvalidGethExecStep(
+                    "invalid creation code not starting with 0xef",
+                    Box::new(geth_step.clone()),
+                )
+            })?;
 
         state.memory_read(&mut exec_step, offset.try_into()?, byte)?;
 
@@ -20,14 +20,20 @@ impl Opcode for InvalidJump {
         } else {
             None
         };
-        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
-        // assert op code can only be JUMP or JUMPI
-        assert!(geth_step.op == OpcodeId::JUMP || geth_step.op == OpcodeId::JUMPI);
+        exec_step.error = state.get_step_err(geth_step, next_step)?;
+        if !matches!(geth_step.op, OpcodeId::JUMP | OpcodeId::JUMPI) {
+            return Err(Error::InvalidGethExecStep(
+                "invalid jump of an opcode other than JUMP or JUMPI",
+                Box::new(geth_step.clone()),
+            ));
+        }
         let is_jumpi = geth_step.op == OpcodeId::JUMPI;
         let mut condition: Word = Word::zero();
         if is_jumpi {
             condition = geth_step.stack.nth_last(1)?;
         }
+        // The whole destination word is read, so that the error gadget can
+        // tell a destination above 64 bits from the one of its low bits.
         state.stack_read(
             &mut exec_step,
             geth_step.stack.last_filled(),
@@ -46,3 +52,109 @@ impl Opcode for InvalidJump {
         Ok(vec![exec_step])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState, error::ExecError, mock::BlockData, operation::RW,
+    };
+    use eth_types::{bytecode, geth_types::GethData, Bytecode};
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    /// Runs `code`, whose last JUMP or JUMPI must jump to `dest`, and checks
+    /// that the jump fails with the whole `dest` read from the stack.
+    fn test_invalid_jump(code: Bytecode, dest: Word) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| {
+                matches!(
+                    step.exec_state,
+                    ExecState::Op(OpcodeId::JUMP | OpcodeId::JUMPI)
+                )
+            })
+            .last()
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::InvalidJump));
+
+        let operation = &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(operation.rw(), RW::READ);
+        assert_eq!(operation.op().value, dest);
+    }
+
+    #[test]
+    fn invalid_jump_not_jumpdest() {
+        // jumps to the STOP
+        let code = bytecode! {
+            PUSH1(0x03)
+            JUMP
+            STOP
+        };
+        test_invalid_jump(code, 0x03.into());
+    }
+
+    #[test]
+    fn invalid_jump_in_push_data() {
+        // jumps to a 0x5b in the data of a PUSH, which isn't a JUMPDEST
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x06)
+            JUMPI
+            PUSH1(0x5b)
+            STOP
+        };
+        test_invalid_jump(code, 0x06.into());
+        let code = bytecode! {
+            PUSH1(0x08)
+            JUMP
+            PUSH1(0x00)
+            PUSH1(0x00)
+            PUSH2(0x5b5b)
+            STOP
+        };
+        test_invalid_jump(code, 0x08.into());
+    }
+
+    #[test]
+    fn invalid_jump_out_of_range() {
+        let code = bytecode! {
+            PUSH2(0x1000)
+            JUMP
+            JUMPDEST
+            STOP
+        };
+        test_invalid_jump(code, 0x1000.into());
+    }
+
+    #[test]
+    fn invalid_jump_above_u64() {
+        // the low 64 bits of the destination are the JUMPDEST
+        let dest = (Word::one() << 64) + Word::from(0x24);
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH32(dest)
+            JUMPI
+            JUMPDEST
+            STOP
+        };
+        test_invalid_jump(code, dest);
+    }
+}
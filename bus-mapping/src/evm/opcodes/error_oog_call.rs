@@ -31,7 +31,7 @@ impl Opcode for OOGCall {
         } else {
             None
         };
-        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        exec_step.error = state.get_step_err(geth_step, next_step)?;
 
         let tx_id = state.tx_ctx.id();
         let call_address = geth_step.stack.nth_last(1)?.to_address();
@@ -25,9 +25,9 @@ impl Opcode for PrecompileFailed {
         let mut exec_step = state.new_step(geth_step)?;
         exec_step.error = Some(ExecError::PrecompileFailed);
 
-        let args_offset = geth_step.stack.nth_last(stack_input_num - 4)?.as_usize();
+        let args_offset = geth_step.stack.nth_last(stack_input_num - 4)?.low_u64() as usize;
         let args_length = geth_step.stack.nth_last(stack_input_num - 3)?.as_usize();
-        let ret_offset = geth_step.stack.nth_last(stack_input_num - 2)?.as_usize();
+        let ret_offset = geth_step.stack.nth_last(stack_input_num - 2)?.low_u64() as usize;
         let ret_length = geth_step.stack.nth_last(stack_input_num - 1)?.as_usize();
 
         // we need to keep the memory until parse_call complete
@@ -20,8 +20,8 @@ impl Opcode for ErrorReturnDataOutOfBound {
         let next_step = geth_steps.get(1);
 
         exec_step.error = Some(ExecError::ReturnDataOutOfBounds);
-        assert_eq!(
-            state.get_step_err(geth_step, next_step).unwrap(),
+        debug_assert_eq!(
+            state.get_step_err(geth_step, next_step)?,
             Some(ExecError::ReturnDataOutOfBounds)
         );
 
@@ -42,14 +42,7 @@ impl Opcode for ErrorReturnDataOutOfBound {
         state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
 
         let call_id = state.call()?.call_id;
-        let call_ctx = state.call_ctx()?;
-        let return_data = &call_ctx.return_data;
-        let last_callee_return_data_length = state.call()?.last_callee_return_data_length;
-        assert_eq!(
-            last_callee_return_data_length as usize,
-            return_data.len(),
-            "callee return data size should be correct"
-        );
+        let last_callee_return_data_length = state.call_ctx()?.return_data.length();
 
         let remainder_end = data_offset.overflowing_add(length).0;
         // check data_offset or end is u64 overflow, or
@@ -59,13 +52,18 @@ impl Opcode for ErrorReturnDataOutOfBound {
         let remainder_end_exceed_length =
             Word::from(last_callee_return_data_length) < remainder_end;
         // one of three must hold at least one.
-        assert!(data_offset_overflow | remainder_end_overflow | remainder_end_exceed_length);
+        if !(data_offset_overflow | remainder_end_overflow | remainder_end_exceed_length) {
+            return Err(Error::InvalidGethExecStep(
+                "return data copy within bounds with error",
+                Box::new(geth_step.clone()),
+            ));
+        }
         // read last callee info
         state.call_context_read(
             &mut exec_step,
             call_id,
             CallContextField::LastCalleeReturnDataLength,
-            return_data.len().into(),
+            last_callee_return_data_length.into(),
         );
 
         // `IsSuccess` call context operation is added in handle_return
@@ -23,7 +23,7 @@ impl Opcode for ErrorWriteProtection {
         } else {
             None
         };
-        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        exec_step.error = state.get_step_err(geth_step, next_step)?;
         // assert error is targeting ExecError::WriteProtection.
         assert_eq!(exec_step.clone().error.unwrap(), ExecError::WriteProtection);
 
@@ -31,6 +31,7 @@ impl Opcode for ErrorWriteProtection {
         // assert op code can only be following codes
         assert!([
             OpcodeId::SSTORE,
+            OpcodeId::TSTORE,
             OpcodeId::CREATE,
             OpcodeId::CREATE2,
             OpcodeId::CALL,
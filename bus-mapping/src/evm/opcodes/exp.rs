@@ -9,7 +9,7 @@ use super::Opcode;
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Exponentiation;
 
-fn exp_by_squaring(base: U256, exponent: U256, steps: &mut Vec<ExpStep>) -> U256 {
+pub(crate) fn exp_by_squaring(base: U256, exponent: U256, steps: &mut Vec<ExpStep>) -> U256 {
     if exponent.is_zero() {
         return U256::one();
     }
@@ -54,15 +54,17 @@ impl Opcode for Exponentiation {
         )?;
 
         let mut steps = Vec::new();
-        let exponentiation_calc = exp_by_squaring(base, exponent, &mut steps);
-        debug_assert_eq!(exponentiation, exponentiation_calc);
-        state.push_exponentiation(ExpEvent {
+        exp_by_squaring(base, exponent, &mut steps);
+        let event = ExpEvent {
             identifier: state.block_ctx.rwc.0,
             base,
             exponent,
             exponentiation,
             steps,
-        });
+        };
+        // Catch a wrong trace here rather than as an unsatisfied exp circuit.
+        event.verify()?;
+        state.push_exponentiation(event);
 
         Ok(vec![exec_step])
     }
@@ -71,8 +73,28 @@ impl Opcode for Exponentiation {
 #[cfg(test)]
 mod tests {
     use eth_types::U256;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use super::exp_by_squaring;
+    use crate::circuit_input_builder::{ExpEvent, ExpStep};
+
+    fn exp_event(base: U256, exponent: U256) -> ExpEvent {
+        let mut steps = Vec::new();
+        let exponentiation = exp_by_squaring(base, exponent, &mut steps);
+        ExpEvent {
+            identifier: 0,
+            base,
+            exponent,
+            exponentiation,
+            steps,
+        }
+    }
+
+    fn rand_word(rng: &mut StdRng) -> U256 {
+        // Cover small values as well as full words.
+        let bits = rng.gen_range(0..=256);
+        U256::from_little_endian(&rng.gen::<[u8; 32]>()) >> (256 - bits)
+    }
 
     #[test]
     fn test_exp_by_squaring() {
@@ -100,4 +122,57 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn exp_event_verify_random() {
+        let mut rng = StdRng::seed_from_u64(0x0e4b);
+        for _ in 0..256 {
+            let (base, exponent) = (rand_word(&mut rng), rand_word(&mut rng));
+            let event = exp_event(base, exponent);
+            assert_eq!(event.exponentiation, base.overflowing_pow(exponent).0);
+            assert!(event.verify().is_ok(), "{:?}^{:?}", base, exponent);
+        }
+    }
+
+    #[test]
+    fn exp_event_verify_edge_cases() {
+        for (base, exponent) in [
+            (0.into(), 0.into()),
+            (0.into(), 1.into()),
+            (7.into(), 0.into()),
+            (7.into(), 1.into()),
+            (2.into(), 255.into()),
+            (2.into(), 256.into()),
+            (U256::MAX, U256::MAX),
+        ] {
+            assert!(exp_event(base, exponent).verify().is_ok());
+        }
+    }
+
+    #[test]
+    fn exp_event_verify_rejects_wrong_steps() {
+        let event = exp_event(3.into(), 13.into());
+
+        let mut wrong_product = event.clone();
+        wrong_product.steps[2].d += U256::one();
+        assert!(wrong_product.verify().is_err());
+
+        let mut swapped = event.clone();
+        swapped.steps.swap(0, 1);
+        assert!(swapped.verify().is_err());
+
+        let mut missing = event.clone();
+        missing.steps.pop();
+        assert!(missing.verify().is_err());
+
+        let mut extra = event.clone();
+        extra
+            .steps
+            .push(ExpStep::from((1.into(), 1.into(), 1.into())));
+        assert!(extra.verify().is_err());
+
+        let mut wrong_result = event;
+        wrong_result.exponentiation += U256::one();
+        assert!(wrong_result.verify().is_err());
+    }
 }
@@ -7,6 +7,7 @@ use crate::{
     Error,
 };
 use eth_types::{Bytecode, GethExecStep, ToAddress, ToWord, H256, U256};
+use std::sync::Arc;
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Extcodecopy;
@@ -30,15 +31,10 @@ impl Opcode for Extcodecopy {
 
         let (_, account) = state.sdb.get_account(&address);
         let code_hash = account.code_hash;
-        let code = state.code(code_hash)?;
-
-        let call_ctx = state.call_ctx_mut()?;
-        let memory = &mut call_ctx.memory;
-
-        memory.copy_from(dst_offset, code_offset, length, &code);
+        state.copy_code_to_memory(code_hash, dst_offset, code_offset, length)?;
 
         let copy_event = gen_copy_event(state, geth_step)?;
-        state.push_copy(&mut exec_steps[0], copy_event);
+        state.push_copy(&mut exec_steps[0], copy_event)?;
         Ok(exec_steps)
     }
 }
@@ -130,10 +126,10 @@ fn gen_copy_event(
         H256::zero()
     };
 
-    let bytecode: Bytecode = if exists {
-        state.code(code_hash)?.into()
+    let bytecode: Arc<Bytecode> = if exists {
+        state.bytecode(code_hash)?
     } else {
-        Bytecode::default()
+        Arc::default()
     };
     let code_size = bytecode.code.len() as u64;
 
@@ -68,7 +68,12 @@ impl Opcode for Extcodehash {
             AccountField::KeccakCodeHash,
             code_hash.to_word(),
         );
-        debug_assert_eq!(steps[1].stack.last()?, code_hash.to_word());
+        if steps[1].stack.last()? != code_hash.to_word() {
+            return Err(Error::InvalidGethExecStep(
+                "code hash differs from the trace",
+                Box::new(steps[1].clone()),
+            ));
+        }
         // Stack write of the result of EXTCODEHASH.
         state.stack_write(&mut exec_step, stack_address, steps[1].stack.last()?)?;
 
@@ -64,17 +64,22 @@ impl Opcode for Extcodesize {
             AccountField::CodeHash,
             code_hash.to_word(),
         );
-        // If "scroll" feature is enabled, CodeSize is read of AccountTrie,
+        // If "poseidon-codehash" feature is enabled, CodeSize is read of AccountTrie,
         // so the full code don't need to be put into bytecode circuit.
         // TODO: check the bytecode circuit assignment codes, to make sure this optimization
         // is correctly applied.
-        #[cfg(feature = "scroll")]
+        #[cfg(feature = "poseidon-codehash")]
         if exists {
             state.account_read(&mut exec_step, address, AccountField::CodeSize, code_size);
         }
 
         // Write the EXTCODESIZE result to stack.
-        debug_assert_eq!(code_size, geth_steps[1].stack.last()?);
+        if code_size != geth_steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "code size differs from the trace",
+                Box::new(geth_steps[1].clone()),
+            ));
+        }
         state.stack_write(
             &mut exec_step,
             geth_steps[1].stack.nth_last_filled(0),
@@ -248,7 +253,7 @@ mod extcodesize_tests {
                 value_prev: if exists { code_hash } else { Word::zero() },
             }
         );
-        #[cfg(feature = "scroll")]
+        #[cfg(feature = "poseidon-codehash")]
         if exists {
             let code_size = account.code.len().to_word();
             let operation = &container.account[indices[6].as_usize()];
@@ -264,7 +269,7 @@ mod extcodesize_tests {
             );
         }
         let rw_offset = 6;
-        #[cfg(feature = "scroll")]
+        #[cfg(feature = "poseidon-codehash")]
         let rw_offset = if exists { rw_offset + 1 } else { rw_offset };
         let operation = &container.stack[indices[rw_offset].as_usize()];
         assert_eq!(operation.rw(), RW::WRITE);
@@ -20,21 +20,25 @@ impl Opcode for Log {
         let mut exec_step = gen_log_step(state, geth_step)?;
         if state.call()?.is_persistent {
             let copy_event = gen_copy_event(state, geth_step, &mut exec_step)?;
-            state.push_copy(&mut exec_step, copy_event);
+            state.push_copy(&mut exec_step, copy_event)?;
             state.tx_ctx.log_id += 1;
         }
 
         // reconstruction
         let offset = geth_step.stack.nth_last(0)?;
-        let length = geth_step.stack.nth_last(1)?.as_u64();
+        let length = geth_step.stack.nth_last(1)?;
 
-        if length != 0 {
+        if !length.is_zero() {
             // Offset should be within range of Uint64 if length is non-zero.
             let memory_length = offset
-                .as_u64()
                 .checked_add(length)
                 .and_then(|val| usize::try_from(val).ok())
-                .unwrap();
+                .ok_or_else(|| {
+                    Error::InvalidGethExecStep(
+                        "log data out of memory",
+                        Box::new(geth_step.clone()),
+                    )
+                })?;
 
             state.call_ctx_mut()?.memory.extend_at_least(memory_length);
         }
@@ -146,9 +150,14 @@ fn gen_copy_event(
     // within range of Uint64, otherwise returns ErrGasUintOverflow.
     // https://github.com/ethereum/go-ethereum/blob/b80f05bde2c4e93ae64bb3813b6d67266b5fc0e6/core/vm/instructions.go#L850
     let memory_start = geth_step.stack.nth_last(0)?.low_u64();
-    let msize = geth_step.stack.nth_last(1)?.as_u64();
-
-    let (src_addr, src_addr_end) = (memory_start, memory_start.checked_add(msize).unwrap());
+    let msize = u64::try_from(geth_step.stack.nth_last(1)?)
+        .ok()
+        .filter(|msize| memory_start.checked_add(*msize).is_some())
+        .ok_or_else(|| {
+            Error::InvalidGethExecStep("log data out of memory", Box::new(geth_step.clone()))
+        })?;
+
+    let (src_addr, src_addr_end) = (memory_start, memory_start + msize);
     let steps = state.gen_copy_steps_for_log(exec_step, src_addr, msize)?;
 
     Ok(CopyEvent {
@@ -168,7 +177,7 @@ fn gen_copy_event(
 #[cfg(test)]
 mod log_tests {
     use crate::{
-        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash, TxReceiptLog},
         mock::BlockData,
         operation::{CallContextField, CallContextOp, MemoryOp, StackOp, TxLogField, TxLogOp, RW},
     };
@@ -176,10 +185,13 @@ mod log_tests {
         bytecode,
         evm_types::{OpcodeId, StackAddress},
         geth_types::GethData,
-        Bytecode, ToWord, Word,
+        BigEndianHash, Bytecode, ToWord, Word, H256,
     };
 
-    use mock::test_ctx::{helpers::*, TestContext};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -201,6 +213,114 @@ mod log_tests {
         ]);
     }
 
+    #[test]
+    fn logs_in_reverted_call_are_dropped() {
+        // The callee emits a log and reverts, between two logs of the caller.
+        let code_callee = bytecode! {
+            PUSH1(0xA0) // topic
+            PUSH1(0x20) // msize
+            PUSH1(0x00) // mstart
+            LOG1
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        };
+        let code_caller = bytecode! {
+            PUSH1(0x20) // msize
+            PUSH1(0x00) // mstart
+            LOG0
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            POP
+            PUSH1(0x10) // msize
+            PUSH1(0x00) // mstart
+            LOG0
+            STOP
+        };
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_caller);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_callee);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 60));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let log_steps = tx
+            .steps()
+            .iter()
+            .filter(|step| {
+                matches!(
+                    step.exec_state,
+                    ExecState::Op(OpcodeId::LOG0 | OpcodeId::LOG1)
+                )
+            })
+            .map(|step| (tx.calls()[step.call_index].is_persistent, step.log_id))
+            .collect::<Vec<_>>();
+        // The log of the reverted call doesn't take a log id.
+        assert_eq!(log_steps, vec![(true, 0), (false, 1), (true, 1)]);
+
+        // Only the logs of the caller are written, with consecutive log ids.
+        let tx_log_ops = builder
+            .block
+            .container
+            .tx_log
+            .iter()
+            .map(|op| op.op())
+            .collect::<Vec<_>>();
+        assert!(tx_log_ops.iter().all(|op| op.field != TxLogField::Topic));
+        assert_eq!(
+            tx_log_ops
+                .iter()
+                .filter(|op| op.field == TxLogField::Address)
+                .map(|op| (op.log_id, op.value))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, MOCK_ACCOUNTS[0].to_word()),
+                (2, MOCK_ACCOUNTS[0].to_word())
+            ]
+        );
+        assert_eq!(tx_log_ops.len(), 2 + 0x20 + 0x10);
+
+        let log_copy_events = builder
+            .block
+            .copy_events
+            .iter()
+            .filter(|event| event.dst_type == CopyDataType::TxLog)
+            .map(|event| (event.log_id, event.bytes.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(log_copy_events, vec![(Some(1), 0x20), (Some(2), 0x10)]);
+
+        let receipt_logs = &builder.block.txs()[0].receipt.logs;
+        assert_eq!(
+            receipt_logs
+                .iter()
+                .map(|log| (log.topics.len(), log.data.len()))
+                .collect::<Vec<_>>(),
+            vec![(0, 0x20), (0, 0x10)]
+        );
+    }
+
     fn test_logs_opcode(topics: &[Word]) {
         let log_codes = [
             OpcodeId::LOG0,
@@ -424,5 +544,16 @@ mod log_tests {
             assert_eq!(Some(byte), memory_data.get(mstart + idx));
             assert!(!*is_code);
         }
+
+        let receipt = &builder.block.txs()[0].receipt;
+        assert!(receipt.status);
+        assert_eq!(
+            receipt.logs,
+            vec![TxReceiptLog {
+                address: callee_address,
+                topics: topics.iter().rev().map(H256::from_uint).collect(),
+                data: memory_data[mstart..mstart + msize].to_vec(),
+            }]
+        );
     }
 }
@@ -0,0 +1,35 @@
+use eth_types::{
+    sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
+    Word,
+};
+use ethers_core::utils::keccak256;
+
+/// Signature recovered by an ECRecover call, or `None` if the recovery
+/// failed, in which case the precompile returns no data.
+pub(crate) fn sign_data(input: &[u8], output: &[u8]) -> Option<SignData> {
+    if output.is_empty() {
+        return None;
+    }
+
+    // The input is right padded with zeros to 128 bytes.
+    let mut padded = [0u8; 128];
+    let len = input.len().min(padded.len());
+    padded[..len].copy_from_slice(&input[..len]);
+
+    let msg_hash: [u8; 32] = padded[..32].try_into().unwrap();
+    let v = Word::from_big_endian(&padded[32..64]);
+    let r = Word::from_big_endian(&padded[64..96]);
+    let s = Word::from_big_endian(&padded[96..128]);
+    // ECRecover only accepts v in {27, 28}.
+    if v != Word::from(27) && v != Word::from(28) {
+        return None;
+    }
+
+    let sign_data = SignData::from_msg_hash(v.as_u64() as u8 - 27, r, s, msg_hash).ok()?;
+    debug_assert_eq!(
+        &keccak256(pk_bytes_swap_endianness(&pk_bytes_le(&sign_data.pk)))[12..],
+        &output[12..],
+        "recovered address doesn't match the ECRecover output"
+    );
+    Some(sign_data)
+}
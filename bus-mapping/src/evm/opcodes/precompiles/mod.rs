@@ -1,17 +1,21 @@
 use eth_types::{GethExecStep, ToWord, Word};
 
 use crate::{
-    circuit_input_builder::{Call, CircuitInputStateRef, ExecState, ExecStep},
+    circuit_input_builder::{Call, CircuitInputStateRef, ExecState, ExecStep, PrecompileEvent},
     operation::CallContextField,
-    precompile::PrecompileCalls,
+    precompile::{P256VerifyInput, PrecompileCalls},
     Error,
 };
 
+mod ecrecover;
+
 pub fn gen_associated_ops(
     state: &mut CircuitInputStateRef,
     geth_step: GethExecStep,
     call: Call,
     precompile: PrecompileCalls,
+    input: &[u8],
+    output: &[u8],
 ) -> Result<ExecStep, Error> {
     assert_eq!(call.code_address(), Some(precompile.into()));
     let mut exec_step = state.new_step(&geth_step)?;
@@ -19,6 +23,21 @@ pub fn gen_associated_ops(
 
     common_call_ctx_reads(state, &mut exec_step, &call);
 
+    if precompile == PrecompileCalls::ECRecover {
+        if let Some(sign_data) = ecrecover::sign_data(input, output) {
+            state
+                .block
+                .add_precompile_event(PrecompileEvent::Ecrecover(sign_data));
+        }
+    }
+    if precompile == PrecompileCalls::P256Verify && !output.is_empty() {
+        if let Some(input) = P256VerifyInput::parse(input) {
+            state
+                .block
+                .add_precompile_event(PrecompileEvent::P256Verify(input));
+        }
+    }
+
     Ok(exec_step)
 }
 
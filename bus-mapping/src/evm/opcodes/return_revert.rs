@@ -26,10 +26,16 @@ impl Opcode for ReturnRevert {
         state.stack_read(&mut exec_step, step.stack.nth_last_filled(1), length)?;
 
         if !length.is_zero() {
-            state
-                .call_ctx_mut()?
-                .memory
-                .extend_at_least((offset.low_u64() + length.low_u64()).try_into().unwrap());
+            let end = offset
+                .checked_add(length)
+                .and_then(|end| usize::try_from(end).ok())
+                .ok_or_else(|| {
+                    Error::InvalidGethExecStep(
+                        "returned data out of memory",
+                        Box::new(step.clone()),
+                    )
+                })?;
+            state.call_ctx_mut()?.memory.extend_at_least(end);
         }
 
         let call = state.call()?.clone();
@@ -70,7 +76,7 @@ impl Opcode for ReturnRevert {
                 state.call_context_read(&mut exec_step, state.call()?.call_id, field, value);
             }
 
-            #[cfg(feature = "scroll")]
+            #[cfg(feature = "poseidon-codehash")]
             state.push_op_reversible(
                 &mut exec_step,
                 AccountOp {
@@ -89,7 +95,7 @@ impl Opcode for ReturnRevert {
                     value_prev: CodeDB::empty_code_hash().to_word(),
                 },
             )?;
-            #[cfg(feature = "scroll")]
+            #[cfg(feature = "poseidon-codehash")]
             state.push_op_reversible(
                 &mut exec_step,
                 AccountOp {
@@ -101,6 +107,27 @@ impl Opcode for ReturnRevert {
             )?;
         }
 
+        // The hash of the data a root call reverted with goes to the receipt of
+        // the transaction.
+        if call.is_root && !call.is_success {
+            let revert_data_hash = handle_revert_data(
+                state,
+                &mut exec_step,
+                Source {
+                    id: call.call_id,
+                    offset,
+                    length,
+                },
+            )?;
+            state.call_context_write(
+                &mut exec_step,
+                call.call_id,
+                CallContextField::RevertDataHash,
+                revert_data_hash.to_word(),
+            );
+            state.tx_ctx.revert_data_hash = revert_data_hash;
+        }
+
         // Case B in the specs.
         if call.is_root {
             state.call_context_read(
@@ -127,11 +154,6 @@ impl Opcode for ReturnRevert {
 
             let callee_memory = state.call_ctx()?.memory.clone();
             let caller_ctx = state.caller_ctx_mut()?;
-            caller_ctx.return_data = callee_memory
-                .0
-                .get(offset..offset + length)
-                .unwrap_or_default()
-                .to_vec();
 
             let return_data_length = usize::try_from(call.return_data_length).unwrap();
             let copy_length = std::cmp::min(return_data_length, length);
@@ -215,11 +237,54 @@ fn handle_copy(
             log_id: None,
             bytes,
         },
-    );
+    )?;
 
     Ok(())
 }
 
+fn handle_revert_data(
+    state: &mut CircuitInputStateRef,
+    step: &mut ExecStep,
+    source: Source,
+) -> Result<H256, Error> {
+    let values = state
+        .call_ctx()?
+        .memory
+        .read_chunk(source.offset.into(), source.length.into());
+    let revert_data_hash = H256(keccak256(&values));
+    let bytes: Vec<_> = values.iter().map(|byte| (*byte, false)).collect();
+
+    if !bytes.is_empty() {
+        let rw_counter_start = state.block_ctx.rwc;
+        for (i, (byte, _)) in bytes.iter().enumerate() {
+            state.push_op(
+                step,
+                RW::READ,
+                MemoryOp::new(source.id, (source.offset + i).into(), *byte),
+            );
+        }
+
+        state.push_copy(
+            step,
+            CopyEvent {
+                rw_counter_start,
+                src_type: CopyDataType::Memory,
+                src_id: NumberOrHash::Number(source.id),
+                src_addr: source.offset.try_into().unwrap(),
+                src_addr_end: (source.offset + source.length).try_into().unwrap(),
+                dst_type: CopyDataType::RlcAcc,
+                dst_id: NumberOrHash::Number(source.id),
+                dst_addr: 0,
+                log_id: None,
+                bytes,
+            },
+        )?;
+    }
+    state.block.sha3_inputs.push(values);
+
+    Ok(revert_data_hash)
+}
+
 struct AccountCodeInfo {
     keccak_hash: H256,
     hash: H256,
@@ -265,7 +330,7 @@ fn handle_create(
             log_id: None,
             bytes,
         },
-    );
+    )?;
 
     Ok(AccountCodeInfo {
         keccak_hash,
@@ -277,7 +342,8 @@ fn handle_create(
 #[cfg(test)]
 mod return_tests {
     use crate::mock::BlockData;
-    use eth_types::{bytecode, geth_types::GethData, word};
+    use eth_types::{bytecode, geth_types::GethData, word, H256};
+    use ethers_core::utils::keccak256;
     use mock::{
         test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
         TestContext, MOCK_DEPLOYED_CONTRACT_BYTECODE,
@@ -378,4 +444,36 @@ mod return_tests {
             .handle_block(&block.eth_block, &block.geth_traces)
             .unwrap();
     }
+
+    #[test]
+    fn test_root_revert_data_hash() {
+        let code = bytecode! {
+            PUSH32(word!("0xdeadbeef"))
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x04)
+            PUSH1(0x1c)
+            REVERT
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let receipt = &builder.block.txs()[0].receipt;
+        assert!(!receipt.status);
+        assert_eq!(
+            receipt.revert_data_hash,
+            H256(keccak256([0xde, 0xad, 0xbe, 0xef]))
+        );
+    }
 }
@@ -1,8 +1,8 @@
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, MemoryCopy, NumberOrHash,
     },
-    evm::Opcode,
+    evm::{opcodes::memory_copy, Opcode},
     operation::{CallContextField, MemoryOp, RW},
     Error,
 };
@@ -18,22 +18,32 @@ impl Opcode for Returndatacopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_returndatacopy_step(state, geth_step)?];
+        let memory_copy = memory_copy(&exec_steps[0], geth_step)?;
+        // Without an error, the copy is within the return data.
+        let return_data_length = state.call_ctx()?.return_data.length();
+        let copy_end = memory_copy.src_offset.checked_add(memory_copy.length);
+        if copy_end.map_or(true, |end| end > return_data_length.into()) {
+            return Err(Error::InvalidGethExecStep(
+                "return data copy out of bounds without error",
+                Box::new(geth_step.clone()),
+            ));
+        }
 
         // reconstruction
-        let geth_step = &geth_steps[0];
-        let dst_offset = geth_step.stack.nth_last(0)?;
-        let src_offset = geth_step.stack.nth_last(1)?;
-        let length = geth_step.stack.nth_last(2)?;
-
         // can we reduce this clone?
-        let return_data = state.call_ctx()?.return_data.clone();
+        let return_data = state.call_ctx()?.return_data.data.clone();
 
         let call_ctx = state.call_ctx_mut()?;
         let memory = &mut call_ctx.memory;
-        memory.copy_from(dst_offset, src_offset, length, &return_data);
+        memory.copy_from(
+            memory_copy.dst_offset,
+            memory_copy.src_offset,
+            memory_copy.length,
+            &return_data,
+        );
 
-        let copy_event = gen_copy_event(state, geth_step)?;
-        state.push_copy(&mut exec_steps[0], copy_event);
+        let copy_event = gen_copy_event(state, geth_step, &memory_copy)?;
+        state.push_copy(&mut exec_steps[0], copy_event)?;
         Ok(exec_steps)
     }
 }
@@ -60,27 +70,18 @@ fn gen_returndatacopy_step(
     state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
 
     let call_id = state.call()?.call_id;
-    let call_ctx = state.call_ctx()?;
-    let return_data = call_ctx.return_data.clone();
-    let last_callee_id = state.call()?.last_callee_id;
-    let last_callee_return_data_offset = state.call()?.last_callee_return_data_offset;
-    let last_callee_return_data_length = state.call()?.last_callee_return_data_length;
-    assert_eq!(
-        last_callee_return_data_length as usize,
-        return_data.len(),
-        "callee return data size should be correct"
-    );
+    let return_data = &state.call_ctx()?.return_data;
 
     // read last callee info
     for (field, value) in [
-        (CallContextField::LastCalleeId, last_callee_id.into()),
+        (CallContextField::LastCalleeId, return_data.callee_id.into()),
         (
             CallContextField::LastCalleeReturnDataOffset,
-            last_callee_return_data_offset.into(),
+            return_data.offset.into(),
         ),
         (
             CallContextField::LastCalleeReturnDataLength,
-            return_data.len().into(),
+            return_data.length().into(),
         ),
     ] {
         state.call_context_read(&mut exec_step, call_id, field, value);
@@ -97,11 +98,12 @@ fn gen_copy_steps(
     bytes_left: u64,
 ) -> Result<Vec<(u8, bool)>, Error> {
     let mut copy_steps = Vec::with_capacity(bytes_left as usize);
-    let src_addr_base = state.call()?.last_callee_return_data_offset;
+    let src_addr_base = state.call_ctx()?.return_data.offset;
+    let callee_id = state.call_ctx()?.return_data.callee_id;
     for idx in 0..bytes_left {
         let addr = src_addr + idx;
         let value = if addr < src_addr_end {
-            state.call_ctx()?.return_data[(addr - src_addr_base) as usize]
+            state.call_ctx()?.return_data.data[(addr - src_addr_base) as usize]
         } else {
             unreachable!("return data copy out of bound")
         };
@@ -109,7 +111,7 @@ fn gen_copy_steps(
         state.push_op(
             exec_step,
             RW::READ,
-            MemoryOp::new(state.call()?.last_callee_id, addr.into(), value),
+            MemoryOp::new(callee_id, addr.into(), value),
         );
 
         // Write
@@ -122,17 +124,17 @@ fn gen_copy_steps(
 fn gen_copy_event(
     state: &mut CircuitInputStateRef,
     geth_step: &GethExecStep,
+    memory_copy: &MemoryCopy,
 ) -> Result<CopyEvent, Error> {
     // Get low Uint64 of offset.
-    let dst_addr = geth_step.stack.nth_last(0)?.low_u64();
-    let data_offset = geth_step.stack.nth_last(1)?.as_u64();
-    let length = geth_step.stack.nth_last(2)?.as_u64();
+    let dst_addr = memory_copy.dst_offset.low_u64();
+    let data_offset = memory_copy.src_offset.as_u64();
+    let length = memory_copy.num_copy_bytes();
 
-    let last_callee_return_data_offset = state.call()?.last_callee_return_data_offset;
-    let last_callee_return_data_length = state.call()?.last_callee_return_data_length;
+    let return_data = &state.call_ctx()?.return_data;
     let (src_addr, src_addr_end) = (
-        last_callee_return_data_offset + data_offset,
-        last_callee_return_data_offset + last_callee_return_data_length,
+        return_data.offset + data_offset,
+        return_data.offset + return_data.length(),
     );
 
     let rw_counter_start = state.block_ctx.rwc;
@@ -149,7 +151,7 @@ fn gen_copy_event(
     let (src_type, dst_type, src_id, dst_id) = (
         CopyDataType::Memory,
         CopyDataType::Memory,
-        NumberOrHash::Number(state.call()?.last_callee_id),
+        NumberOrHash::Number(state.call_ctx()?.return_data.callee_id),
         NumberOrHash::Number(state.call()?.call_id),
     );
 
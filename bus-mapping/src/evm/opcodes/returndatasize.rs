@@ -26,14 +26,18 @@ impl Opcode for Returndatasize {
             value,
         );
 
-        // TODO: fix error in deposit_ether.json...
-        if value.as_usize() != state.call_ctx()?.return_data.len() {
+        let return_data_length = state.call_ctx()?.return_data.length();
+        if value != return_data_length.into() {
             log::error!(
-                "return_data.len() != RETURNDATASIZE value, {} != {}, step: {:?}",
-                state.call_ctx()?.return_data.len(),
-                value.as_usize(),
+                "return data length != RETURNDATASIZE value, {} != {}, step: {:?}",
+                return_data_length,
+                value,
                 geth_step
             );
+            return Err(Error::InvalidGethExecStep(
+                "return data length differs from geth's",
+                Box::new(geth_step.clone()),
+            ));
         }
 
         state.stack_write(
@@ -57,11 +61,12 @@ mod returndatasize_tests {
         bytecode,
         evm_types::{OpcodeId, StackAddress},
         geth_types::GethData,
-        Word,
+        Address, Bytecode, ToWord, Word,
     };
     use mock::{
+        eth,
         test_ctx::{helpers::*, TestContext},
-        MOCK_DEPLOYED_CONTRACT_BYTECODE,
+        MOCK_ACCOUNTS, MOCK_DEPLOYED_CONTRACT_BYTECODE,
     };
     use pretty_assertions::assert_eq;
 
@@ -151,4 +156,139 @@ mod returndatasize_tests {
             )
         );
     }
+
+    fn callee_address() -> Address {
+        Address::from_low_u64_be(0x1000)
+    }
+
+    fn call(address: Address, gas: u64, args_length: u64) -> Bytecode {
+        bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(args_length) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH20(address.to_word()) // addr
+            PUSH32(gas) // gas
+            CALL
+            POP
+        }
+    }
+
+    /// Runs `calls` followed by a RETURNDATASIZE, with the contract at
+    /// [`callee_address`] running `callee_code`, and checks that the size
+    /// read and the last return data length written to the context of the
+    /// root call are `size`.
+    fn test_returndatasize_after(calls: Bytecode, callee_code: Bytecode, size: u64) {
+        let mut code = calls;
+        code.append(&bytecode! {
+            RETURNDATASIZE
+            STOP
+        });
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |mut accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(callee_address()).code(callee_code);
+                accs[2].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::RETURNDATASIZE))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+        let read = &builder.block.container.call_context[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(read.op().value, Word::from(size));
+
+        let last_write = builder
+            .block
+            .container
+            .call_context
+            .iter()
+            .filter(|op| {
+                op.rw() == RW::WRITE
+                    && op.op().call_id == call_id
+                    && op.op().field == CallContextField::LastCalleeReturnDataLength
+            })
+            .last()
+            .unwrap();
+        assert_eq!(last_write.op().value, Word::from(size));
+    }
+
+    #[test]
+    fn returndatasize_after_precompile() {
+        let identity = Address::from_low_u64_be(0x04);
+        test_returndatasize_after(call(identity, 0xffff, 5), Bytecode::default(), 5);
+    }
+
+    #[test]
+    fn returndatasize_after_failed_precompile() {
+        let identity = Address::from_low_u64_be(0x04);
+        test_returndatasize_after(call(identity, 1, 5), Bytecode::default(), 0);
+    }
+
+    #[test]
+    fn returndatasize_after_revert() {
+        let callee_code = bytecode! {
+            PUSH1(3)
+            PUSH1(0)
+            REVERT
+        };
+        test_returndatasize_after(call(callee_address(), 0xffff, 0), callee_code, 3);
+    }
+
+    #[test]
+    fn returndatasize_after_failed_call() {
+        let mut callee_code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+        };
+        callee_code.write_op(OpcodeId::INVALID(0xfe));
+        test_returndatasize_after(call(callee_address(), 0xffff, 0), callee_code, 0);
+    }
+
+    #[test]
+    fn returndatasize_after_call_to_empty_account() {
+        let callee_code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            RETURN
+        };
+        // The empty account resets the return data of the previous call.
+        let mut calls = call(callee_address(), 0xffff, 0);
+        calls.append(&call(Address::from_low_u64_be(0x2000), 0xffff, 0));
+        test_returndatasize_after(calls, callee_code, 0);
+    }
+
+    #[test]
+    fn returndatasize_after_create() {
+        let calls = bytecode! {
+            PUSH21(*MOCK_DEPLOYED_CONTRACT_BYTECODE)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x15)
+            PUSH1(0xB)
+            PUSH1(0)
+            CREATE
+            POP
+        };
+        test_returndatasize_after(calls, Bytecode::default(), 0);
+    }
 }
@@ -44,7 +44,12 @@ impl Opcode for Sha3 {
 
         // keccak-256 hash of the given data in memory.
         let sha3 = keccak256(&memory);
-        debug_assert_eq!(Word::from_big_endian(&sha3), expected_sha3);
+        if Word::from_big_endian(&sha3) != expected_sha3 {
+            return Err(Error::InvalidGethExecStep(
+                "hash differs from the trace",
+                Box::new(geth_steps[1].clone()),
+            ));
+        }
         state.stack_write(
             &mut exec_step,
             geth_steps[1].stack.last_filled(),
@@ -79,7 +84,7 @@ impl Opcode for Sha3 {
                 rw_counter_start,
                 bytes: steps,
             },
-        );
+        )?;
 
         Ok(vec![exec_step])
     }
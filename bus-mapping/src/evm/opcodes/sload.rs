@@ -62,7 +62,7 @@ impl Opcode for Sload {
         let value_from_statedb = *state.sdb.get_storage(&contract_addr, &key).1;
         {
             let value_from_step = geth_step.storage.get_or_err(&key)?;
-            let value_from_stack = geth_steps[1].stack.last().unwrap();
+            let value_from_stack = geth_steps[1].stack.last()?;
             if !(value_from_step == value_from_statedb && value_from_step == value_from_stack) {
                 log::error!(
                     "inconsistent sload: step proof {:?}, local statedb {:?}, result {:?}",
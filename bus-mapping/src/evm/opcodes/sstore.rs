@@ -6,7 +6,7 @@ use crate::{
 };
 
 use crate::operation::RW;
-use eth_types::{GethExecStep, ToWord, Word};
+use eth_types::{evm_types::gas_utils::sstore_refund, GethExecStep, ToWord, Word};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::SSTORE`](crate::evm::OpcodeId::SSTORE)
@@ -109,11 +109,30 @@ impl Opcode for Sstore {
             },
         )?;
 
+        // The refund counter of geth's step already includes the refund of the
+        // SSTORE, computed with its gas before the step is traced.
+        let refund_prev = state.sdb.refund();
+        let refund = sstore_refund(refund_prev, committed_value, value_prev, value);
+        if refund != Some(geth_step.refund.0) {
+            log::error!(
+                "SSTORE refund {:?} differs from geth's {} (refund_prev {}, original {}, prev {}, value {})",
+                refund,
+                geth_step.refund.0,
+                refund_prev,
+                committed_value,
+                value_prev,
+                value
+            );
+            return Err(Error::InvalidGethExecStep(
+                "SSTORE refund differs from the one of EIP-3529",
+                Box::new(geth_step.clone()),
+            ));
+        }
         state.push_op_reversible(
             &mut exec_step,
             TxRefundOp {
                 tx_id: state.tx_ctx.id(),
-                value_prev: state.sdb.refund(),
+                value_prev: refund_prev,
                 value: geth_step.refund.0,
             },
         )?;
@@ -132,7 +151,7 @@ mod sstore_tests {
     };
     use eth_types::{
         bytecode,
-        evm_types::{OpcodeId, StackAddress},
+        evm_types::{GasCost, OpcodeId, StackAddress},
         geth_types::GethData,
         Word,
     };
@@ -286,4 +305,45 @@ mod sstore_tests {
     fn sstore_opcode_impl_cold() {
         test_ok(false)
     }
+
+    #[test]
+    fn sstore_refund_differing_from_geth() {
+        // clears the slot, refunding SSTORE_CLEARS_SCHEDULE
+        let code = bytecode! {
+            PUSH1(0x00u64)
+            PUSH1(0x00u64)
+            SSTORE
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(code)
+                    .storage(vec![(0x00u64.into(), 0x6fu64.into())].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let sstore = block.geth_traces[0]
+            .struct_logs
+            .iter_mut()
+            .find(|step| step.op == OpcodeId::SSTORE)
+            .unwrap();
+        assert_eq!(sstore.refund.0, GasCost::SSTORE_CLEARS_SCHEDULE.as_u64());
+        sstore.refund.0 = 0;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+        assert!(matches!(err.root_cause(), Error::InvalidGethExecStep(..)));
+    }
 }
@@ -47,7 +47,7 @@ impl<const N_POP: usize, const N_PUSH: usize, const IS_ERR: bool> Opcode
 
         if IS_ERR {
             let next_step = geth_steps.get(1);
-            exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+            exec_step.error = state.get_step_err(geth_step, next_step)?;
 
             state.handle_return(&mut exec_step, geth_steps, true)?;
         }
@@ -417,4 +417,19 @@ mod stackonlyop_tests {
             vec![StackOp::new(1, StackAddress(1023), *MOCK_BASEFEE)],
         );
     }
+
+    #[cfg(feature = "cancun")]
+    #[test]
+    fn blobbasefee_opcode_impl() {
+        // the mock block has no excess blob gas
+        stack_only_opcode_impl::<0, 1>(
+            OpcodeId::BLOBBASEFEE,
+            bytecode! {
+                BLOBBASEFEE
+                STOP
+            },
+            vec![],
+            vec![StackOp::new(1, StackAddress(1023), Word::one())],
+        );
+    }
 }
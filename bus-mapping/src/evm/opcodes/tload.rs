@@ -0,0 +1,66 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TransientStorageOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::TLOAD`](crate::evm::OpcodeId::TLOAD)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Tload;
+
+impl Opcode for Tload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call_id = state.call()?.call_id;
+        let contract_addr = state.call()?.address;
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::CalleeAddress,
+            contract_addr.to_word(),
+        );
+
+        let key = geth_step.stack.last()?;
+        let stack_position = geth_step.stack.last_filled();
+        state.stack_read(&mut exec_step, stack_position, key)?;
+
+        // Transient storage read, of a value which is zero unless it was
+        // written by a TSTORE of the tx which wasn't reverted.
+        let value = *state.sdb.get_transient_storage(&contract_addr, &key);
+        if let Some(next_step) = geth_steps.get(1) {
+            let value_from_stack = next_step.stack.last()?;
+            if value_from_stack != value {
+                log::error!(
+                    "inconsistent tload: local statedb {:?}, result {:?}",
+                    value,
+                    value_from_stack
+                );
+            }
+        }
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TransientStorageOp::new(state.tx_ctx.id(), contract_addr, key, value, value),
+        );
+
+        state.stack_write(&mut exec_step, stack_position, value)?;
+
+        Ok(vec![exec_step])
+    }
+}
@@ -16,6 +16,17 @@ pub mod message_queue {
     pub static WITHDRAW_TRIE_ROOT_SLOT: Lazy<U256> = Lazy::new(U256::zero);
 }
 
+/// Helper for the sender of the gas-free system txs
+pub mod system_sender {
+    use eth_types::Address;
+    use once_cell::sync::Lazy;
+    use std::str::FromStr;
+
+    /// Address of the only sender allowed to send gas-free system txs
+    pub static ADDRESS: Lazy<Address> =
+        Lazy::new(|| Address::from_str("0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001").unwrap());
+}
+
 /// Helper for L1GasPriceOracle contract
 pub mod l1_gas_price_oracle {
     use eth_types::{Address, U256};
@@ -143,6 +143,7 @@
 //!     gas: Gas(block.eth_block.transactions[0].gas.as_u64()),
 //!     failed: false,
 //!     struct_logs: geth_steps,
+//!     validation: None,
 //! };
 //!
 //! // Get an ordered vector with all of the Stack operations of this trace.
@@ -239,6 +240,7 @@ pub mod l2_predeployed;
 pub mod mock;
 pub mod operation;
 pub mod precompile;
+pub mod progress;
 pub mod rpc;
 pub mod state_db;
 pub mod util;
@@ -3,10 +3,11 @@
 use crate::{
     circuit_input_builder::{
         get_state_accesses, AccessSet, Block, BlockHead, CircuitInputBuilder, CircuitsParams,
+        ExecState, Transaction,
     },
     state_db::{self, CodeDB, StateDB},
 };
-use eth_types::{geth_types::GethData, ToWord, Word, H256};
+use eth_types::{evm_types::OpcodeId, geth_types::GethData, ToWord, Word, H256};
 use ethers_core::utils::keccak256;
 
 const MOCK_OLD_STATE_ROOT: u64 = 0xcafeu64;
@@ -79,7 +80,7 @@ impl BlockData {
                 state_db::Account {
                     nonce: account.nonce,
                     balance: account.balance,
-                    storage: account.storage,
+                    storage: account.all_storage().expect("valid storage snapshot"),
                     code_hash,
                     keccak_code_hash,
                     code_size: account.code.len().to_word(),
@@ -104,9 +105,158 @@ impl BlockData {
     }
 }
 
+/// Outcome of a user operation executed by the mock EntryPoint of
+/// `mock::generate_entry_point_bytecode`, as found in the trace of the
+/// bundler tx.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserOperationTrace {
+    /// Index of the call to the sender in the calls of the tx
+    pub call_index: usize,
+    /// Whether the call to the sender succeeded
+    pub is_success: bool,
+    /// Gas between the `GAS` opcodes around the call to the sender, which
+    /// the EntryPoint reports in its log
+    pub gas_used: u64,
+}
+
+/// Gas accounting of each user operation of a bundler tx calling the mock
+/// EntryPoint, in order.
+pub fn user_operation_traces(tx: &Transaction) -> Vec<UserOperationTrace> {
+    let root_call_id = tx.calls()[0].call_id;
+    let sender_calls = tx
+        .calls()
+        .iter()
+        .enumerate()
+        .filter(|(_, call)| !call.is_root && call.caller_id == root_call_id);
+    let gas_steps = tx
+        .steps()
+        .iter()
+        .filter(|step| step.call_index == 0 && step.exec_state == ExecState::Op(OpcodeId::GAS))
+        .collect::<Vec<_>>();
+    sender_calls
+        .zip(gas_steps.chunks(2))
+        .map(|((call_index, call), gas_steps)| UserOperationTrace {
+            call_index,
+            is_success: call.is_success,
+            gas_used: gas_steps[0].gas_left.0 - gas_steps[1].gas_left.0,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 #[ctor::ctor]
 fn init_env_logger() {
     // Enable RUST_LOG during tests
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("error")).init();
 }
+
+#[cfg(test)]
+mod user_operation_tests {
+    use super::*;
+    use eth_types::{bytecode, BigEndianHash, Bytes, ToBigEndian};
+    use mock::{
+        bundle_call_data, generate_entry_point_bytecode, MockUserOperation, TestContext,
+        MOCK_ACCOUNTS, USER_OPERATION_EVENT_TOPIC,
+    };
+
+    #[test]
+    fn user_operation_gas_accounting() {
+        // Stores its first call data word.
+        let wallet_code = bytecode! {
+            PUSH1(0x00)
+            CALLDATALOAD
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let reverting_wallet_code = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        };
+        let user_ops = [
+            MockUserOperation {
+                sender: MOCK_ACCOUNTS[1],
+                call_gas_limit: 50_000,
+                call_data: Word::from(0xcafe).to_be_bytes().to_vec(),
+            },
+            MockUserOperation {
+                sender: MOCK_ACCOUNTS[2],
+                call_gas_limit: 10_000,
+                call_data: vec![],
+            },
+            // Runs out of gas in the SSTORE.
+            MockUserOperation {
+                sender: MOCK_ACCOUNTS[1],
+                call_gas_limit: 2_000,
+                call_data: Word::from(0xbeef).to_be_bytes().to_vec(),
+            },
+        ];
+        let entry_point_code = generate_entry_point_bytecode(&user_ops);
+        let call_data = bundle_call_data(&user_ops);
+
+        let block: GethData = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(entry_point_code);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(wallet_code);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .code(reverting_wallet_code);
+                accs[3]
+                    .address(MOCK_ACCOUNTS[3])
+                    .balance(Word::from(1u64 << 60));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[3].address)
+                    .input(Bytes::from(call_data));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let traces = user_operation_traces(tx);
+        assert_eq!(
+            traces
+                .iter()
+                .map(|trace| (tx.calls()[trace.call_index].address, trace.is_success))
+                .collect::<Vec<_>>(),
+            vec![
+                (MOCK_ACCOUNTS[1], true),
+                (MOCK_ACCOUNTS[2], false),
+                (MOCK_ACCOUNTS[1], false),
+            ]
+        );
+
+        // The EntryPoint logs the same outcome and gas as found in the trace.
+        assert_eq!(tx.receipt.logs.len(), user_ops.len());
+        for (index, (log, trace)) in tx.receipt.logs.iter().zip(traces).enumerate() {
+            assert_eq!(
+                log.topics,
+                vec![
+                    H256::from_uint(&USER_OPERATION_EVENT_TOPIC),
+                    H256::from_uint(&Word::from(index)),
+                ]
+            );
+            assert_eq!(
+                log.data,
+                [
+                    Word::from(trace.is_success as u64),
+                    Word::from(trace.gas_used)
+                ]
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use super::{
     AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
-    TxRefundOp, RW,
+    StorageOp, Target, TransientStorageOp, TxAccessListAccountOp, TxAccessListAccountStorageOp,
+    TxLogOp, TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
@@ -42,6 +42,8 @@ pub struct OperationContainer {
     pub tx_receipt: Vec<Operation<TxReceiptOp>>,
     /// Operations of TxLogOp
     pub tx_log: Vec<Operation<TxLogOp>>,
+    /// Operations of TransientStorageOp
+    pub transient_storage: Vec<Operation<TransientStorageOp>>,
     /// Operations of Start
     pub start: Vec<Operation<StartOp>>,
 }
@@ -67,6 +69,7 @@ impl OperationContainer {
             call_context: Vec::new(),
             tx_receipt: Vec::new(),
             tx_log: Vec::new(),
+            transient_storage: Vec::new(),
             start: Vec::new(),
         }
     }
@@ -160,6 +163,14 @@ impl OperationContainer {
                 self.tx_log.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::TxLog, self.tx_log.len() - 1))
             }
+            OpEnum::TransientStorage(op) => {
+                self.transient_storage.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((Target::TransientStorage, self.transient_storage.len() - 1))
+            }
             OpEnum::Start(op) => {
                 self.start.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Start, self.start.len() - 1))
@@ -167,6 +178,82 @@ impl OperationContainer {
         }
     }
 
+    /// Returns the total number of operations stored in the container,
+    /// across all of the targets.
+    pub fn len(&self) -> usize {
+        self.memory.len()
+            + self.stack.len()
+            + self.storage.len()
+            + self.tx_access_list_account.len()
+            + self.tx_access_list_account_storage.len()
+            + self.tx_refund.len()
+            + self.account.len()
+            + self.call_context.len()
+            + self.tx_receipt.len()
+            + self.tx_log.len()
+            + self.transient_storage.len()
+            + self.start.len()
+    }
+
+    /// Returns true if the container holds no operation at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Releases the spare capacity of every operation vector.  Blocks with
+    /// millions of operations otherwise keep up to twice the memory they need
+    /// until the witness is dropped.  Existing [`OperationRef`]s stay valid.
+    pub fn shrink_to_fit(&mut self) {
+        self.memory.shrink_to_fit();
+        self.stack.shrink_to_fit();
+        self.storage.shrink_to_fit();
+        self.tx_access_list_account.shrink_to_fit();
+        self.tx_access_list_account_storage.shrink_to_fit();
+        self.tx_refund.shrink_to_fit();
+        self.account.shrink_to_fit();
+        self.call_context.shrink_to_fit();
+        self.tx_receipt.shrink_to_fit();
+        self.tx_log.shrink_to_fit();
+        self.transient_storage.shrink_to_fit();
+        self.start.shrink_to_fit();
+    }
+
+    /// Returns the [`OperationRef`]s of all the operations in the container,
+    /// ordered by `key`.  Only the references are sorted, so this is cheap
+    /// compared to cloning and sorting every operation, and the result can be
+    /// computed once and reused by every consumer that needs the same order.
+    pub fn sorted_refs_by_key<K, F>(&self, mut key: F) -> Vec<OperationRef>
+    where
+        K: Ord,
+        F: FnMut(&OperationRef) -> K,
+    {
+        let mut refs = Vec::with_capacity(self.len());
+        for (target, len) in [
+            (Target::Start, self.start.len()),
+            (Target::Memory, self.memory.len()),
+            (Target::Stack, self.stack.len()),
+            (Target::Storage, self.storage.len()),
+            (
+                Target::TxAccessListAccount,
+                self.tx_access_list_account.len(),
+            ),
+            (
+                Target::TxAccessListAccountStorage,
+                self.tx_access_list_account_storage.len(),
+            ),
+            (Target::TxRefund, self.tx_refund.len()),
+            (Target::Account, self.account.len()),
+            (Target::CallContext, self.call_context.len()),
+            (Target::TxReceipt, self.tx_receipt.len()),
+            (Target::TxLog, self.tx_log.len()),
+            (Target::TransientStorage, self.transient_storage.len()),
+        ] {
+            refs.extend((0..len).map(|idx| OperationRef::from((target, idx))));
+        }
+        refs.sort_by_cached_key(|op_ref| key(op_ref));
+        refs
+    }
+
     /// Returns a sorted vector of all of the [`MemoryOp`]s contained inside of
     /// the container.
     pub fn sorted_memory(&self) -> Vec<Operation<MemoryOp>> {
@@ -229,6 +316,11 @@ mod container_test {
         assert_eq!(operation_container.sorted_stack()[0], stack_operation);
         assert_eq!(operation_container.sorted_memory()[0], memory_operation);
         assert_eq!(operation_container.sorted_storage()[0], storage_operation);
+        assert_eq!(operation_container.len(), 3);
+        assert_eq!(
+            operation_container.sorted_refs_by_key(|op_ref| op_ref.as_usize()),
+            vec![memory_ref, stack_ref, storage_ref]
+        );
         assert_eq!(stack_ref, OperationRef::from((Target::Stack, 0)));
         assert_eq!(memory_ref, OperationRef::from((Target::Memory, 0)));
         assert_eq!(storage_ref, OperationRef::from((Target::Storage, 0)));
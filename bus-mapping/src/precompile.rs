@@ -1,17 +1,99 @@
 //! precompile helpers
 
-use eth_types::{evm_types::GasCost, Address};
+use crate::Error;
+use eth_types::{evm_types::GasCost, Address, Word};
 use revm_precompile::{Precompile, Precompiles};
+use std::{collections::HashMap, fmt, sync::Arc};
 use strum::EnumIter;
 
-/// Check if address is a precompiled or not.
+/// Check if address is a precompiled or not. The precompiles registered in a
+/// [`PrecompileRegistry`] are checked by [`PrecompileRegistry::is_precompiled`].
 pub fn is_precompiled(address: &Address) -> bool {
     Precompiles::berlin()
         .get(address.as_fixed_bytes())
         .is_some()
+        || (cfg!(feature = "rip7212") && *address == PrecompileCalls::P256Verify.into())
+}
+
+/// How the circuits treat calls to a precompile registered through
+/// [`PrecompileProvider`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PrecompileConstraintMode {
+    /// Witness generation fails with [`crate::Error::UnsupportedPrecompile`]
+    /// when the precompile is called.
+    #[default]
+    Unsupported,
+    /// The call is traced as a call returning the precompile output, which
+    /// is enough to follow the state of the chain, but the circuits have no
+    /// constraint for it: the witness of a block calling the precompile is
+    /// refused with [`crate::Error::UnsupportedPrecompile`].
+    Stub,
+}
+
+/// Precompiled contract added by a downstream chain on top of the ones known
+/// to [`PrecompileCalls`].
+pub trait PrecompileProvider: Send + Sync {
+    /// Address the precompile is deployed at.
+    fn address(&self) -> Address;
+
+    /// Run the precompile, returning its output and the gas it consumed.
+    /// Failures are reported as an empty output consuming all of `gas`.
+    fn execute(&self, input: &[u8], gas: u64) -> (Vec<u8>, u64);
+
+    /// How the circuits treat calls to this precompile.
+    fn constraint_mode(&self) -> PrecompileConstraintMode {
+        PrecompileConstraintMode::default()
+    }
+}
+
+/// Precompiles registered by a downstream chain for a
+/// [`crate::circuit_input_builder::CircuitInputBuilder`].
+#[derive(Clone, Default)]
+pub struct PrecompileRegistry(HashMap<Address, Arc<dyn PrecompileProvider>>);
+
+impl fmt::Debug for PrecompileRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl PrecompileRegistry {
+    /// Register a precompile, returning the provider previously registered
+    /// at the same address.  The address of a precompile from
+    /// [`PrecompileCalls`] can't be registered.
+    pub fn register(
+        &mut self,
+        provider: Arc<dyn PrecompileProvider>,
+    ) -> Result<Option<Arc<dyn PrecompileProvider>>, Error> {
+        let address = provider.address();
+        if is_precompiled(&address) {
+            return Err(Error::BuiltinPrecompile(address));
+        }
+        Ok(self.0.insert(address, provider))
+    }
+
+    /// Get the precompile registered at `address`, if any.
+    pub fn get(&self, address: &Address) -> Option<&Arc<dyn PrecompileProvider>> {
+        self.0.get(address)
+    }
+
+    /// Addresses of the registered precompiles.
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.0.keys()
+    }
+
+    /// Check if address is a builtin or a registered precompile.
+    pub fn is_precompiled(&self, address: &Address) -> bool {
+        is_precompiled(address) || self.0.contains_key(address)
+    }
 }
 
 pub(crate) fn execute_precompiled(address: &Address, input: &[u8], gas: u64) -> (Vec<u8>, u64) {
+    #[cfg(feature = "rip7212")]
+    if *address == PrecompileCalls::P256Verify.into() {
+        return execute_p256_verify(input, gas);
+    }
+
     let Some(Precompile::Standard(precompile_fn)) = Precompiles::berlin()
         .get(address.as_fixed_bytes())  else {
         panic!("calling non-exist precompiled contract address")
@@ -23,6 +105,10 @@ pub(crate) fn execute_precompiled(address: &Address, input: &[u8], gas: u64) ->
     }
 }
 
+/// Maximum length in bytes of the base, exponent and modulus of a modexp call
+/// supported by the circuits.
+pub const MODEXP_SIZE_LIMIT: usize = 32;
+
 /// Addresses of the precompiled contracts.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter)]
 pub enum PrecompileCalls {
@@ -44,6 +130,8 @@ pub enum PrecompileCalls {
     Bn128Pairing = 0x08,
     /// Compression function
     Blake2F = 0x09,
+    /// secp256r1 (P-256) signature verification, from RIP-7212
+    P256Verify = 0x100,
 }
 
 impl Default for PrecompileCalls {
@@ -54,9 +142,21 @@ impl Default for PrecompileCalls {
 
 impl From<PrecompileCalls> for Address {
     fn from(value: PrecompileCalls) -> Self {
-        let mut addr = [0u8; 20];
-        addr[19] = value as u8;
-        Self(addr)
+        Self::from_low_u64_be(value.into())
+    }
+}
+
+impl TryFrom<Address> for PrecompileCalls {
+    type Error = Error;
+
+    fn try_from(address: Address) -> Result<Self, Self::Error> {
+        if !is_precompiled(&address) {
+            return Err(Error::UnknownPrecompile(address));
+        }
+        if address == Self::P256Verify.into() {
+            return Ok(Self::P256Verify);
+        }
+        address.0[19].try_into()
     }
 }
 
@@ -72,9 +172,11 @@ impl From<PrecompileCalls> for usize {
     }
 }
 
-impl From<u8> for PrecompileCalls {
-    fn from(value: u8) -> Self {
-        match value {
+impl TryFrom<u8> for PrecompileCalls {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
             0x01 => Self::ECRecover,
             0x02 => Self::Sha256,
             0x03 => Self::Ripemd160,
@@ -84,8 +186,12 @@ impl From<u8> for PrecompileCalls {
             0x07 => Self::Bn128Mul,
             0x08 => Self::Bn128Pairing,
             0x09 => Self::Blake2F,
-            _ => unreachable!("precompile contracts only from 0x01 to 0x09"),
-        }
+            _ => {
+                return Err(Error::UnknownPrecompile(Address::from_low_u64_be(
+                    value.into(),
+                )))
+            }
+        })
     }
 }
 
@@ -102,6 +208,7 @@ impl PrecompileCalls {
             Self::Bn128Mul => GasCost::PRECOMPILE_BN256MUL,
             Self::Bn128Pairing => GasCost::PRECOMPILE_BN256PAIRING,
             Self::Blake2F => GasCost::PRECOMPILE_BLAKE2F,
+            Self::P256Verify => GasCost::PRECOMPILE_P256VERIFY,
         }
     }
 
@@ -109,4 +216,224 @@ impl PrecompileCalls {
     pub fn address(&self) -> u64 {
         (*self).into()
     }
+
+    /// Whether the circuits can handle a call with `input`. Only the lengths
+    /// of the modexp operands are limited, to [`MODEXP_SIZE_LIMIT`] bytes.
+    pub fn is_input_supported(&self, input: &[u8]) -> bool {
+        match self {
+            Self::Modexp => modexp_lengths(input)
+                .iter()
+                .all(|len| *len <= Word::from(MODEXP_SIZE_LIMIT)),
+            _ => true,
+        }
+    }
+}
+
+/// Lengths of the base, exponent and modulus of a modexp call, the first three
+/// words of its input padded with zeros.
+fn modexp_lengths(input: &[u8]) -> [Word; 3] {
+    let mut header = [0u8; 96];
+    let len = input.len().min(header.len());
+    header[..len].copy_from_slice(&input[..len]);
+    [0, 1, 2].map(|i| Word::from_big_endian(&header[i * 32..(i + 1) * 32]))
+}
+
+/// Input of the P256VERIFY precompile: the message hash, the signature and
+/// the public key, as five 32 bytes big endian words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct P256VerifyInput {
+    /// Hash of the signed message
+    pub msg_hash: [u8; 32],
+    /// Signature `r`
+    pub r: [u8; 32],
+    /// Signature `s`
+    pub s: [u8; 32],
+    /// Public key `x` coordinate
+    pub x: [u8; 32],
+    /// Public key `y` coordinate
+    pub y: [u8; 32],
+}
+
+impl P256VerifyInput {
+    /// Size in bytes of a well formed input.
+    pub const LEN: usize = 160;
+
+    /// Parse the input of a P256VERIFY call, `None` if it isn't exactly
+    /// [`Self::LEN`] bytes long.
+    pub fn parse(input: &[u8]) -> Option<Self> {
+        if input.len() != Self::LEN {
+            return None;
+        }
+        let word = |i: usize| -> [u8; 32] { input[i * 32..(i + 1) * 32].try_into().unwrap() };
+        Some(Self {
+            msg_hash: word(0),
+            r: word(1),
+            s: word(2),
+            x: word(3),
+            y: word(4),
+        })
+    }
+
+    /// Verify the signature.  Invalid signatures, public keys not on the
+    /// curve and out of range scalars all fail the verification.
+    #[cfg(feature = "rip7212")]
+    pub fn verify(&self) -> bool {
+        use p256::{
+            ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey},
+            EncodedPoint,
+        };
+
+        let Ok(signature) = Signature::from_scalars(self.r, self.s) else {
+            return false;
+        };
+        let point = EncodedPoint::from_affine_coordinates(&self.x.into(), &self.y.into(), false);
+        let Ok(key) = VerifyingKey::from_encoded_point(&point) else {
+            return false;
+        };
+        key.verify_prehash(&self.msg_hash, &signature).is_ok()
+    }
+}
+
+/// Execute P256VERIFY: the output is 1 as a 32 bytes word for a valid
+/// signature and empty otherwise, and the gas cost is fixed.
+#[cfg(feature = "rip7212")]
+fn execute_p256_verify(input: &[u8], gas: u64) -> (Vec<u8>, u64) {
+    let gas_cost = GasCost::PRECOMPILE_P256VERIFY.0;
+    if gas < gas_cost {
+        return (vec![], gas);
+    }
+    let is_valid = P256VerifyInput::parse(input).map_or(false, |input| input.verify());
+    let output = if is_valid {
+        let mut output = vec![0u8; 32];
+        output[31] = 1;
+        output
+    } else {
+        vec![]
+    };
+    (output, gas_cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::ToBigEndian;
+    use strum::IntoEnumIterator;
+
+    struct Echo(Address);
+
+    impl PrecompileProvider for Echo {
+        fn address(&self) -> Address {
+            self.0
+        }
+
+        fn execute(&self, input: &[u8], _gas: u64) -> (Vec<u8>, u64) {
+            (input.to_vec(), 15)
+        }
+
+        fn constraint_mode(&self) -> PrecompileConstraintMode {
+            PrecompileConstraintMode::Stub
+        }
+    }
+
+    #[test]
+    fn modexp_input_size_limit() {
+        let input = |lengths: [Word; 3]| -> Vec<u8> {
+            lengths.iter().flat_map(|len| len.to_be_bytes()).collect()
+        };
+        let limit = Word::from(MODEXP_SIZE_LIMIT);
+
+        assert!(PrecompileCalls::Modexp.is_input_supported(&[]));
+        assert!(PrecompileCalls::Modexp.is_input_supported(&input([limit, limit, limit])));
+        assert!(!PrecompileCalls::Modexp.is_input_supported(&input([
+            Word::one(),
+            limit + 1,
+            Word::one()
+        ])));
+        // truncated lengths are padded with zeros
+        let giant_modulus = input([Word::zero(), Word::zero(), Word::MAX]);
+        assert!(!PrecompileCalls::Modexp.is_input_supported(&giant_modulus[..65]));
+        // other precompiles take any input
+        assert!(PrecompileCalls::Identity.is_input_supported(&giant_modulus));
+    }
+
+    #[cfg(feature = "rip7212")]
+    #[test]
+    fn p256_verify() {
+        let words = [
+            "9749ebd9e8d86c4c6e4fd13a95921bd0d6f93022fa55e21638beff533fcf1731", // hash
+            "4f347d910f71da7872ab141e818c06064461212c68014cf3cf643887497eff8b", // r
+            "c3e3014ce615311f86036a3b2d46499f0ef4e75e18bea5b185ab6240804fd7d6", // s
+            "b7e08afdfe94bad3f1dc8c734798ba1c62b3a0ad1e9ea2a38201cd0889bc7a19", // x
+            "3603f747959dbf7a4bb226e41928729063adc7ae43529e61b563bbc606cc5e09", // y
+        ];
+        let input = hex::decode(words.concat()).unwrap();
+        let gas = GasCost::PRECOMPILE_P256VERIFY.0;
+        let mut valid = vec![0u8; 32];
+        valid[31] = 1;
+        assert_eq!(
+            execute_precompiled(&PrecompileCalls::P256Verify.into(), &input, gas),
+            (valid, gas)
+        );
+
+        let mut invalid_sig = input.clone();
+        invalid_sig[95] ^= 1;
+        assert_eq!(
+            execute_precompiled(&PrecompileCalls::P256Verify.into(), &invalid_sig, gas),
+            (vec![], gas)
+        );
+        assert_eq!(
+            execute_precompiled(&PrecompileCalls::P256Verify.into(), &input[..128], gas),
+            (vec![], gas)
+        );
+    }
+
+    #[test]
+    fn custom_precompile_registration() {
+        let address = Address::from_low_u64_be(0xff01);
+        let mut registry = PrecompileRegistry::default();
+        assert!(!registry.is_precompiled(&address));
+
+        assert!(registry
+            .register(Arc::new(Echo(address)))
+            .unwrap()
+            .is_none());
+        assert!(registry.is_precompiled(&address));
+        assert!(!is_precompiled(&address));
+        assert_eq!(
+            registry.get(&address).unwrap().execute(&[1, 2, 3], 100),
+            (vec![1, 2, 3], 15)
+        );
+        assert!(registry
+            .register(Arc::new(Echo(address)))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn builtin_precompile_cannot_be_replaced() {
+        let address = PrecompileCalls::Sha256.into();
+        let mut registry = PrecompileRegistry::default();
+        assert!(matches!(
+            registry.register(Arc::new(Echo(address))),
+            Err(Error::BuiltinPrecompile(a)) if a == address
+        ));
+    }
+
+    #[test]
+    fn precompile_calls_from_address() {
+        for call in PrecompileCalls::iter() {
+            if is_precompiled(&call.into()) {
+                assert_eq!(
+                    PrecompileCalls::try_from(Address::from(call)).unwrap(),
+                    call
+                );
+            }
+        }
+        let address = Address::from_low_u64_be(0xff01);
+        assert!(matches!(
+            PrecompileCalls::try_from(address),
+            Err(Error::UnknownPrecompile(a)) if a == address
+        ));
+        assert!(PrecompileCalls::try_from(Address::from_low_u64_be(0x0a)).is_err());
+    }
 }
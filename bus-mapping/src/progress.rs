@@ -0,0 +1,190 @@
+//! Progress reporting and cancellation of the witness generation and of the
+//! assignment of the circuits, for proving services which report how far a
+//! block is and abort it on a reorg.
+//!
+//! Cancellation is checked between txs while handling a block, and between
+//! sub-circuits while assigning the super circuit, so a cancelled block stops
+//! at the end of the tx or sub-circuit being processed.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Stage of the pipeline a [`Progress`] is reported for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Handling of the txs of a block by the
+    /// [`crate::circuit_input_builder::CircuitInputBuilder`]
+    Witness,
+    /// Assignment of the sub-circuits. halo2 assigns the circuit again for
+    /// the keygen and for the proof, so this stage is reported once per
+    /// assignment.
+    Assignment,
+}
+
+/// Progress of a stage: `done` out of `total` txs or sub-circuits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// Stage in progress
+    pub stage: Stage,
+    /// Number of txs or sub-circuits done
+    pub done: usize,
+    /// Number of txs or sub-circuits of the stage
+    pub total: usize,
+}
+
+impl Progress {
+    /// Percent of the stage done, 100 for an empty stage.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            100.0 * self.done as f64 / self.total as f64
+        }
+    }
+}
+
+/// Token cancelling the witness generation and assignments it's given to.
+/// Clones share the cancellation.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Generates a token that isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the work given this token, including the work not started yet.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether the token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The work was stopped by a [`CancellationToken`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+/// Progress callback and cancellation token of a block. The default reporter
+/// reports nothing and is never cancelled.
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    on_progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("on_progress", &self.on_progress.is_some())
+            .field("cancellation", &self.cancellation)
+            .finish()
+    }
+}
+
+impl ProgressReporter {
+    /// Generates a reporter reporting nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `on_progress` before each tx or sub-circuit and at the end of
+    /// each stage.
+    pub fn on_progress(mut self, on_progress: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Stop the work once `cancellation` is cancelled.
+    pub fn cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Returns whether the work was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .map_or(false, CancellationToken::is_cancelled)
+    }
+
+    /// Report that `done` out of `total` items of `stage` are done, unless
+    /// the work was cancelled.
+    pub fn report(&self, stage: Stage, done: usize, total: usize) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            return Err(Cancelled);
+        }
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(Progress { stage, done, total });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+    use crate::{mock::BlockData, Error};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::test_ctx::{helpers::account_0_code_account_1_no_code, TestContext};
+    use std::sync::Mutex;
+
+    fn block() -> BlockData {
+        let block: GethData = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |txs, accs| {
+                for tx in txs {
+                    tx.from(accs[1].address).to(accs[0].address);
+                }
+            },
+            |block, _txs| block,
+        )
+        .unwrap()
+        .into();
+        BlockData::new_from_geth_data(block)
+    }
+
+    #[test]
+    fn report_witness_progress() {
+        let block = block();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress = ProgressReporter::new().on_progress({
+            let reports = reports.clone();
+            move |progress| reports.lock().unwrap().push(progress.done)
+        });
+        let mut builder = block.new_circuit_input_builder().with_progress(progress);
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert_eq!(*reports.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn cancel_witness_generation() {
+        let block = block();
+        let cancellation = CancellationToken::new();
+        let progress = ProgressReporter::new()
+            .cancellation(cancellation.clone())
+            .on_progress(move |progress| {
+                if progress.done == 1 {
+                    cancellation.cancel();
+                }
+            });
+        let mut builder = block.new_circuit_input_builder().with_progress(progress);
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::Cancelled)
+        ));
+        assert_eq!(builder.block.txs.len(), 2);
+    }
+}
@@ -5,9 +5,14 @@ use crate::{
     precompile::is_precompiled,
     util::{hash_code, KECCAK_CODE_HASH_ZERO},
 };
-use eth_types::{Address, Hash, Word, H256, U256};
+#[cfg(feature = "prague")]
+use eth_types::eip7702;
+use eth_types::{Address, Bytecode, Hash, Word, H256, U256};
 use lazy_static::lazy_static;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
 
 lazy_static! {
     static ref ACCOUNT_ZERO: Account = Account::zero();
@@ -22,13 +27,29 @@ lazy_static! {
 
 const VALUE_ZERO: Word = Word::zero();
 
-/// Memory storage for contract code by code hash.
+/// Memory storage for contract code by code hash, with a cache of the
+/// analyzed codes read during the block.
 #[derive(Debug)]
-pub struct CodeDB(pub HashMap<Hash, Vec<u8>>);
+pub struct CodeDB(pub HashMap<Hash, Vec<u8>>, BytecodeCache);
+
+/// Analyzed codes by code hash. A code is analyzed on its first read, and its
+/// later reads share it by reference counting instead of copying it, which
+/// matters for blocks calling the same large contract many times.
+#[derive(Debug, Default)]
+struct BytecodeCache(Mutex<HashMap<Hash, Arc<Bytecode>>>);
+
+impl BytecodeCache {
+    fn entries(&self) -> MutexGuard<'_, HashMap<Hash, Arc<Bytecode>>> {
+        self.0.lock().expect("bytecode cache lock poisoned")
+    }
+}
 
 impl Clone for CodeDB {
     fn clone(&self) -> Self {
-        CodeDB(self.0.clone())
+        CodeDB(
+            self.0.clone(),
+            BytecodeCache(Mutex::new(self.1.entries().clone())),
+        )
     }
 }
 
@@ -41,7 +62,7 @@ impl Default for CodeDB {
 impl CodeDB {
     /// Create a new empty Self.
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self(HashMap::new(), BytecodeCache::default())
     }
     /// Insert code indexed by code hash, and return the code hash.
     pub fn insert(&mut self, code: Vec<u8>) -> Hash {
@@ -50,6 +71,26 @@ impl CodeDB {
         self.0.insert(hash, code);
         hash
     }
+    /// Code for the given code hash, borrowed from the DB.
+    pub fn get(&self, code_hash: &Hash) -> Option<&[u8]> {
+        self.0.get(code_hash).map(Vec::as_slice)
+    }
+    /// Analyzed code for the given code hash, shared with the other reads
+    /// of the same code until [`CodeDB::clear_bytecode_cache`].
+    pub fn bytecode(&self, code_hash: &Hash) -> Option<Arc<Bytecode>> {
+        let mut cache = self.1.entries();
+        if let Some(bytecode) = cache.get(code_hash) {
+            return Some(bytecode.clone());
+        }
+        let bytecode = Arc::new(Bytecode::from(self.0.get(code_hash)?.clone()));
+        cache.insert(*code_hash, bytecode.clone());
+        Some(bytecode)
+    }
+    /// Drop the analyzed codes cached by [`CodeDB::bytecode`], which are
+    /// freed once their last reader drops them.
+    pub fn clear_bytecode_cache(&mut self) {
+        self.1.entries().clear();
+    }
     /// Specify code hash for empty code (nil)
     pub fn empty_code_hash() -> Hash {
         *EMPTY_CODE_HASH
@@ -59,6 +100,30 @@ impl CodeDB {
     pub fn hash(code: &[u8]) -> Hash {
         H256(hash_code(code).into())
     }
+
+    /// Resolve the code run when calling the account at `address` whose code
+    /// hash is `code_hash`, and return its address and code hash. With
+    /// EIP-7702, an account whose code is a delegation designator runs the
+    /// code of the account it delegates to, and the delegations of that
+    /// account are not followed.
+    #[cfg(feature = "prague")]
+    pub fn resolve_delegation(
+        &self,
+        sdb: &StateDB,
+        address: Address,
+        code_hash: Hash,
+    ) -> (Address, Hash) {
+        let delegate = self
+            .0
+            .get(&code_hash)
+            .and_then(|code| eip7702::delegated_address(code));
+        match delegate {
+            // precompiles run as empty code when delegated to
+            Some(delegate) if is_precompiled(&delegate) => (delegate, Self::empty_code_hash()),
+            Some(delegate) => (delegate, sdb.get_account(&delegate).1.code_hash),
+            None => (address, code_hash),
+        }
+    }
 }
 
 /// Account of the Ethereum State Trie, which contains an in-memory key-value
@@ -119,6 +184,9 @@ pub struct StateDB {
     // `true`. These accounts will be reset once `commit_tx` is called.
     destructed_account: HashSet<Address>,
     refund: u64,
+    // Transient storage of EIP-1153, which is discarded at the end of the
+    // transaction instead of being committed.
+    transient_storage: HashMap<(Address, Word), Word>,
 }
 
 impl StateDB {
@@ -202,6 +270,20 @@ impl StateDB {
         self.dirty_storage.insert((*addr, *key), *value);
     }
 
+    /// Get the transient storage value of EIP-1153 at `addr` and `key`, which
+    /// is zero if it wasn't written in the current transaction.
+    pub fn get_transient_storage(&self, addr: &Address, key: &Word) -> &Word {
+        self.transient_storage
+            .get(&(*addr, *key))
+            .unwrap_or(&VALUE_ZERO)
+    }
+
+    /// Set the transient storage value of EIP-1153 at `addr` and `key`.
+    /// It is cleared in `commit_tx` method.
+    pub fn set_transient_storage(&mut self, addr: &Address, key: &Word, value: &Word) {
+        self.transient_storage.insert((*addr, *key), *value);
+    }
+
     /// Get balance of account with the given address.
     pub fn get_balance(&self, addr: &Address) -> Word {
         let (_, account) = self.get_account(addr);
@@ -275,7 +357,8 @@ impl StateDB {
         self.refund = value;
     }
 
-    /// Clear access list and refund, and commit dirty storage.
+    /// Clear access list, refund and transient storage, and commit dirty
+    /// storage.
     /// It should be invoked before processing
     /// with new transaction with the same [`StateDB`].
     pub fn commit_tx(&mut self) {
@@ -291,6 +374,7 @@ impl StateDB {
             *account = ACCOUNT_ZERO.clone();
         }
         self.refund = 0;
+        self.transient_storage = HashMap::new();
     }
 }
 
@@ -353,4 +437,94 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn bytecode_cache_shares_codes() {
+        let mut code_db = CodeDB::new();
+        let code_hash = code_db.insert(vec![0x60, 0x01, 0x5b]);
+
+        let bytecode = code_db.bytecode(&code_hash).unwrap();
+        assert!(!bytecode.code[1].is_code);
+        assert!(bytecode.code[2].is_code);
+        assert!(Arc::ptr_eq(
+            &bytecode,
+            &code_db.bytecode(&code_hash).unwrap()
+        ));
+        assert!(code_db.bytecode(&H256::zero()).is_none());
+
+        code_db.clear_bytecode_cache();
+        assert_eq!(Arc::strong_count(&bytecode), 1);
+        assert!(!Arc::ptr_eq(
+            &bytecode,
+            &code_db.bytecode(&code_hash).unwrap()
+        ));
+    }
+
+    #[test]
+    fn transient_storage_cleared_at_tx_end() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let mut statedb = StateDB::new();
+        assert_eq!(
+            statedb.get_transient_storage(&addr, &Word::one()),
+            &Word::zero()
+        );
+
+        statedb.set_transient_storage(&addr, &Word::one(), &Word::from(7));
+        assert_eq!(
+            statedb.get_transient_storage(&addr, &Word::one()),
+            &Word::from(7)
+        );
+        // The transient storage is apart from the storage.
+        assert_eq!(statedb.get_storage(&addr, &Word::one()).1, &Word::zero());
+
+        statedb.commit_tx();
+        assert_eq!(
+            statedb.get_transient_storage(&addr, &Word::one()),
+            &Word::zero()
+        );
+    }
+
+    #[cfg(feature = "prague")]
+    #[test]
+    fn resolve_delegation() {
+        let authority = address!("0x00000000000000000000000000000000000000a1");
+        let delegate = address!("0x00000000000000000000000000000000000000a2");
+        let other = address!("0x00000000000000000000000000000000000000a3");
+        let mut code_db = CodeDB::new();
+        let mut statedb = StateDB::new();
+        let mut set_code = |address: Address, code: Vec<u8>| {
+            let code_hash = code_db.insert(code);
+            statedb.set_account(
+                &address,
+                Account {
+                    code_hash,
+                    ..Account::zero()
+                },
+            );
+            code_hash
+        };
+        let authority_hash = set_code(authority, eip7702::delegation_designator(delegate));
+        let delegate_hash = set_code(delegate, eip7702::delegation_designator(other));
+        let other_hash = set_code(other, vec![0x60, 0x2a]);
+
+        // the code of the delegate runs, without following its own delegation
+        assert_eq!(
+            code_db.resolve_delegation(&statedb, authority, authority_hash),
+            (delegate, delegate_hash)
+        );
+        assert_eq!(
+            code_db.resolve_delegation(&statedb, other, other_hash),
+            (other, other_hash)
+        );
+        // delegating to a missing account or to a precompile runs no code
+        let missing = address!("0x00000000000000000000000000000000000000a4");
+        let precompile = address!("0x0000000000000000000000000000000000000001");
+        for target in [missing, precompile] {
+            let code_hash = code_db.insert(eip7702::delegation_designator(target));
+            assert_eq!(
+                code_db.resolve_delegation(&statedb, authority, code_hash),
+                (target, CodeDB::empty_code_hash())
+            );
+        }
+    }
 }
@@ -19,9 +19,9 @@ pub const POSEIDON_HASH_BYTES_IN_FIELD: usize = 31;
 
 /// Default code hash
 pub(crate) fn hash_code(code: &[u8]) -> Hash {
-    #[cfg(feature = "scroll")]
+    #[cfg(feature = "poseidon-codehash")]
     return hash_code_poseidon(code);
-    #[cfg(not(feature = "scroll"))]
+    #[cfg(not(feature = "poseidon-codehash"))]
     return hash_code_keccak(code);
 }
 
@@ -67,7 +67,7 @@ pub(crate) fn hash_code_poseidon(code: &[u8]) -> Hash {
     Hash::from_slice(&buf)
 }
 
-#[cfg(feature = "scroll")]
+#[cfg(feature = "poseidon-codehash")]
 #[test]
 fn code_hashing() {
     assert_eq!(
@@ -0,0 +1,136 @@
+//! Benchmarks of the witness generation and of the EVM circuit assignment
+//! for blocks dominated by one category of opcodes, to track performance
+//! regressions of each category.
+//!
+//! Run with `cargo bench -p circuit-benchmarks --features benches --bench
+//! opcode_witness`. The traces are generated once by geth, outside of the
+//! measurements.
+
+use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use eth_types::{address, bytecode, bytecode::Bytecode, geth_types::GethData, Address, Word};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use mock::{eth, TestContext};
+use zkevm_circuits::{
+    evm_circuit::TestEvmCircuit,
+    witness::{block_convert, Block},
+};
+
+/// Number of times the opcodes of a category are repeated in its block
+const REPETITIONS: usize = 32;
+
+fn callee() -> Address {
+    address!("0x00000000000000000000000000000000000000bb")
+}
+
+/// Code exercising each category of opcodes.
+fn categories() -> Vec<(&'static str, Bytecode)> {
+    let mut memory = Bytecode::default();
+    let mut storage = Bytecode::default();
+    let mut calls = Bytecode::default();
+    let mut hashing = Bytecode::default();
+    for i in 0..REPETITIONS {
+        memory.append(&bytecode! {
+            .op_mstore(i * 0x20, i)
+            .op_mload(i * 0x10)
+            POP
+            .op_calldatacopy(i * 0x20, 0x00, 0x40)
+            .op_codecopy(i * 0x20, 0x00, 0x40)
+        });
+        storage.append(&bytecode! {
+            .op_sstore(i, i + 1)
+            .op_sload(i)
+            POP
+        });
+        calls.append(&bytecode! {
+            .op_call(0x1000, callee(), 0x00, 0x00, 0x20, 0x00, 0x20)
+            POP
+            .op_staticcall(0x1000, callee(), 0x00, 0x20, 0x00, 0x00)
+            POP
+        });
+        hashing.append(&bytecode! {
+            .op_sha3(i * 0x20, 0x100)
+            POP
+        });
+    }
+    let mut categories = vec![
+        ("memory", memory),
+        ("storage", storage),
+        ("calls", calls),
+        ("hashing", hashing),
+    ];
+    for (_, code) in categories.iter_mut() {
+        code.op_stop();
+    }
+    categories
+}
+
+/// Trace a tx running `code`, with a callee for the calls.
+fn geth_data(code: Bytecode) -> GethData {
+    TestContext::<3, 1>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(address!("0x000000000000000000000000000000000000cafe"))
+                .balance(eth(10));
+            accs[1]
+                .address(address!("0x00000000000000000000000000000000000000aa"))
+                .code(code);
+            accs[2].address(callee()).code(bytecode! {
+                .op_mstore(0x00, 0x2a)
+                .op_return(0x00, 0x20)
+            });
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[0].address)
+                .to(accs[1].address)
+                .gas(Word::from(10_000_000))
+                .input(vec![0xff; 0x40].into());
+        },
+        |block, _txs| block,
+    )
+    .unwrap()
+    .into()
+}
+
+fn witness(data: &GethData) -> Block<Fr> {
+    let mut builder =
+        BlockData::new_from_geth_data_with_params(data.clone(), CircuitsParams::default())
+            .new_circuit_input_builder();
+    builder
+        .handle_block(&data.eth_block, &data.geth_traces)
+        .unwrap();
+    block_convert(&builder.block, &builder.code_db).unwrap()
+}
+
+fn bench_opcode_categories(c: &mut Criterion) {
+    let data: Vec<_> = categories()
+        .into_iter()
+        .map(|(name, code)| (name, geth_data(code)))
+        .collect();
+
+    let mut group = c.benchmark_group("witness generation");
+    for (name, data) in data.iter() {
+        group.bench_function(*name, |b| b.iter(|| witness(data)));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("evm circuit assignment");
+    group.sample_size(10);
+    for (name, data) in data.iter() {
+        let block = witness(data);
+        let k = block.get_test_degree();
+        group.bench_function(*name, |b| {
+            b.iter_batched(
+                || TestEvmCircuit::<Fr>::new(block.clone()),
+                |circuit| MockProver::<Fr>::run(k, &circuit, vec![]).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_opcode_categories);
+criterion_main!(benches);
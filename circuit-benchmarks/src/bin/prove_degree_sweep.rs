@@ -0,0 +1,214 @@
+//! Proves the super circuit for a synthetic block at each degree of a range,
+//! and reports the proving time, the peak RSS and the proof size per degree.
+//!
+//! The range is read from the `MIN_DEGREE` and `MAX_DEGREE` env vars, 19 and
+//! 26 by default. The capacity of the sub-circuits grows with the degree, so
+//! that each proof is made for a circuit using all of its rows:
+//!
+//! `MIN_DEGREE=19 MAX_DEGREE=22 cargo run --release -p circuit-benchmarks
+//! --features benches --bin prove_degree_sweep`
+
+use bus_mapping::circuit_input_builder::{CircuitProfile, CircuitsParams};
+use eth_types::{address, bytecode, bytecode::Bytecode, geth_types::GethData, Word};
+use ethers_signers::{LocalWallet, Signer};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use mock::{TestContext, MOCK_CHAIN_ID};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use std::{collections::HashMap, env::var, fs, time::Instant};
+use zkevm_circuits::super_circuit::SuperCircuit;
+
+const MAX_TXS: usize = 8;
+const MAX_CALLDATA: usize = 4096;
+const MAX_INNER_BLOCKS: usize = 1;
+const MOCK_RANDOMNESS: u64 = 0x100;
+
+/// Rows left at the end of each sub-circuit for the blinding factors
+const RESERVED_ROWS: usize = 256;
+
+type BenchCircuit = SuperCircuit<Fr, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>;
+
+fn env_degree(name: &str, default: u32) -> u32 {
+    var(name).map_or(default, |degree| {
+        degree
+            .parse()
+            .unwrap_or_else(|_| panic!("Cannot parse {} env var as u32", name))
+    })
+}
+
+/// Capacity of the sub-circuits filling the rows of a circuit of `degree`.
+fn circuits_params(degree: u32) -> CircuitsParams {
+    let rows = (1 << degree) - RESERVED_ROWS;
+    CircuitsParams {
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_rws: rows,
+        max_copy_rows: rows,
+        // each exponentiation step takes 7 rows
+        max_exp_steps: rows / 7,
+        max_bytecode: rows,
+        max_evm_rows: rows,
+        max_mpt_rows: rows,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
+        max_rlp_rows: rows,
+    }
+}
+
+/// A block of `MAX_TXS` signed txs, each calling a contract which writes to
+/// storage, hashes and copies its call data.
+fn synthetic_block(rng: &mut ChaChaRng) -> GethData {
+    let mut code = Bytecode::default();
+    for i in 0..16u64 {
+        code.append(&bytecode! {
+            .op_calldatacopy(0x00, 0x00, 0x100)
+            .op_sha3(0x00, 0x100)
+            .op_sstore(i, i + 1)
+            .op_exp(3, i + 0x100)
+            POP
+        });
+    }
+    code.op_stop();
+
+    let wallet = LocalWallet::new(rng).with_chain_id(MOCK_CHAIN_ID.as_u64());
+    let sender = wallet.address();
+    let mut block: GethData = TestContext::<2, MAX_TXS>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(address!("0x000000000000000000000000000000000000bbbb"))
+                .code(code);
+            accs[1].address(sender).balance(Word::from(10u64.pow(19)));
+        },
+        |txs, accs| {
+            for tx in txs {
+                tx.from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(1_000_000u64))
+                    .input(vec![0xab; 0x100].into());
+            }
+        },
+        |block, _txs| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+    block.sign(&HashMap::from([(sender, wallet)]));
+    block
+}
+
+/// Reset the peak RSS of the process, if the kernel allows it.
+fn reset_peak_rss() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Peak RSS of the process in kB since the last [`reset_peak_rss`], read from
+/// `/proc/self/status`.
+fn peak_rss_kb() -> Option<u64> {
+    fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn main() {
+    let min_degree = env_degree("MIN_DEGREE", 19);
+    let max_degree = env_degree("MAX_DEGREE", 26);
+    let mut rng = ChaChaRng::seed_from_u64(2);
+    let block = synthetic_block(&mut rng);
+
+    println!("| k | setup (s) | keygen (s) | proving (s) | peak RSS (MiB) | proof size (bytes) |");
+    println!("|---|-----------|------------|-------------|----------------|--------------------|");
+    for degree in min_degree..=max_degree {
+        let (needed_degree, circuit, instance, _) =
+            BenchCircuit::build(block.clone(), circuits_params(degree)).unwrap();
+        if needed_degree > degree {
+            println!(
+                "| {} | skipped: the block needs k >= {} |",
+                degree, needed_degree
+            );
+            continue;
+        }
+        let instance_refs: Vec<&[Fr]> = instance.iter().map(|v| &v[..]).collect();
+        reset_peak_rss();
+
+        let start = Instant::now();
+        let params = ParamsKZG::<Bn256>::setup(degree, &mut rng);
+        let setup_time = start.elapsed();
+
+        let start = Instant::now();
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
+        let keygen_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            _,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+            BenchCircuit,
+        >(
+            &params,
+            &pk,
+            &[circuit],
+            &[&instance_refs],
+            &mut rng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let proof = transcript.finalize();
+        let proving_time = start.elapsed();
+        let peak_rss = peak_rss_kb();
+
+        let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+            SingleStrategy<'_, Bn256>,
+        >(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&instance_refs],
+            &mut verifier_transcript,
+        )
+        .expect("failed to verify bench circuit");
+
+        println!(
+            "| {} | {:.1} | {:.1} | {:.1} | {} | {} |",
+            degree,
+            setup_time.as_secs_f64(),
+            keygen_time.as_secs_f64(),
+            proving_time.as_secs_f64(),
+            peak_rss.map_or("n/a".to_string(), |kb| (kb / 1024).to_string()),
+            proof.len()
+        );
+    }
+}
@@ -49,9 +49,12 @@ mod tests {
             .expect("Cannot parse DEGREE env var as u32");
 
         let block = generate_block::<MAX_TXS, MAX_CALLDATA>();
-        let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(
-            PiCircuit::<Fr>::new(MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, &block),
-        );
+        let circuit = PiTestCircuit::<Fr, MAX_INNER_BLOCKS>(PiCircuit::<Fr>::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            MAX_INNER_BLOCKS,
+            &block,
+        ));
 
         let public_inputs = circuit.0.instance();
         let instance: Vec<&[Fr]> = public_inputs.iter().map(|input| &input[..]).collect();
@@ -87,7 +90,7 @@ mod tests {
             Challenge255<G1Affine>,
             XorShiftRng,
             Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            PiTestCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>,
+            PiTestCircuit<Fr, MAX_INNER_BLOCKS>,
         >(
             &general_params,
             &pk,
@@ -3,7 +3,7 @@
 #[cfg(test)]
 mod tests {
     use ark_std::{end_timer, start_timer};
-    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use bus_mapping::circuit_input_builder::{CircuitProfile, CircuitsParams};
     use eth_types::{address, bytecode, geth_types::GethData, Word};
     use ethers_signers::{LocalWallet, Signer};
     use halo2_proofs::{
@@ -93,14 +93,14 @@ mod tests {
             max_evm_rows: 0,
             max_inner_blocks: MAX_INNER_BLOCKS,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_rlp_rows: 256,
         };
         let (_, circuit, instance, _) =
-            SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>::build(
-                block,
-                circuits_params,
-            )
-            .unwrap();
+            SuperCircuit::<_, MAX_INNER_BLOCKS, 0x100>::build(block, circuits_params).unwrap();
         let instance_refs: Vec<&[Fr]> = instance.iter().map(|v| &v[..]).collect();
 
         // Bench setup generation
@@ -128,7 +128,7 @@ mod tests {
             Challenge255<G1Affine>,
             ChaChaRng,
             Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>,
+            SuperCircuit<Fr, MAX_INNER_BLOCKS, 0x100>,
         >(
             &general_params,
             &pk,
@@ -3,7 +3,7 @@
 #[cfg(test)]
 mod tests {
     use ark_std::{end_timer, start_timer};
-    use bus_mapping::circuit_input_builder::{BuilderClient, CircuitsParams};
+    use bus_mapping::circuit_input_builder::{BuilderClient, CircuitProfile, CircuitsParams};
     use env_logger::Env;
     use halo2_proofs::{
         halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -52,6 +52,10 @@ mod tests {
             max_mpt_rows: 3_000_000,
             max_bytecode: 3_000_000,
             max_keccak_rows: 0, // FIXME: can this be none?
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_exp_steps: 100_000,
             max_evm_rows: 4_000_000,
             max_rlp_rows: 4_000_000,
@@ -135,7 +139,7 @@ mod tests {
             &general_params,
             &pk,
             &[circuit],
-            &[&[&[]]],
+            &[&[]],
             rng,
             &mut transcript,
         )
@@ -158,7 +162,7 @@ mod tests {
             &verifier_params,
             pk.get_vk(),
             strategy,
-            &[&[&[]]],
+            &[&[]],
             &mut verifier_transcript,
         )
         .expect("failed to verify bench circuit");
@@ -0,0 +1,307 @@
+//! Preview of the set-code transactions of
+//! [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702), planned for the
+//! Prague hard fork.
+//!
+//! A set-code tx carries a list of authorizations, each signed by an EOA (its
+//! authority) to set its code to a delegation designator pointing to another
+//! account. Calling the authority then runs the code of the delegated account
+//! in the context of the authority.
+
+use crate::{
+    sign_types::{pk_bytes_le, pk_bytes_swap_endianness, recover_pk, SECP256K1_Q},
+    Address, Error, ToBigEndian, Transaction, Word, H256, U64,
+};
+use ethers_core::utils::{
+    keccak256,
+    rlp::{Encodable, RlpStream},
+};
+use ethers_signers::LocalWallet;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+/// EIP-2718 type of set-code txs.
+pub const SET_CODE_TX_TYPE: u64 = 0x04;
+/// Byte prepended to the RLP encoding of an authorization tuple to compute the
+/// hash signed by its authority.
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+/// Prefix of the code of an account delegating to another one, followed by
+/// the address of the delegated account.
+pub const DELEGATION_DESIGNATOR_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+/// Length of a delegation designator: the prefix and an address.
+pub const DELEGATION_DESIGNATOR_LEN: usize = DELEGATION_DESIGNATOR_PREFIX.len() + 20;
+/// Intrinsic gas charged per authorization of a set-code tx, part of which is
+/// refunded when its authority already exists.
+pub const PER_EMPTY_ACCOUNT_COST: u64 = 25000;
+/// Part of [`PER_EMPTY_ACCOUNT_COST`] kept when the authority already exists,
+/// the rest being refunded.
+pub const PER_AUTH_BASE_COST: u64 = 12500;
+/// Maximum number of authorizations of a set-code tx supported by the
+/// circuits, which lay them out in the fixed rows of the tx.
+pub const MAX_AUTHORIZATIONS_PER_TX: usize = 2;
+
+/// Key of the authorization list in the JSON of a set-code tx.
+const AUTHORIZATION_LIST_KEY: &str = "authorizationList";
+
+/// An authorization of a set-code tx: the authority delegates to `address`
+/// if its nonce is `nonce` and `chain_id` is zero or the current chain id.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    /// Chain id the authorization is valid on, zero for any chain
+    pub chain_id: Word,
+    /// Address of the account to delegate to
+    pub address: Address,
+    /// Nonce of the authority
+    pub nonce: U64,
+    /// Parity of the y coordinate of the signature point
+    pub y_parity: U64,
+    /// "r" value of the signature
+    pub r: Word,
+    /// "s" value of the signature
+    pub s: Word,
+}
+
+impl Authorization {
+    /// Hash signed by the authority: `keccak(MAGIC || rlp([chain_id, address,
+    /// nonce]))`.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut stream = RlpStream::new_list(3);
+        stream.append(&self.chain_id);
+        stream.append(&self.address);
+        stream.append(&self.nonce);
+        let mut msg = vec![AUTHORIZATION_MAGIC];
+        msg.extend_from_slice(&stream.out());
+        keccak256(msg)
+    }
+
+    /// Recover the address of the authority from the signature, which must
+    /// have a low s as tx signatures since EIP-2.
+    pub fn authority(&self) -> Result<Address, Error> {
+        if self.y_parity > U64::one() {
+            return Err(Error::Signature(libsecp256k1::Error::InvalidRecoveryId));
+        }
+        if BigUint::from_bytes_be(&self.s.to_be_bytes()) > &*SECP256K1_Q >> 1 {
+            return Err(Error::Signature(libsecp256k1::Error::InvalidSignature));
+        }
+        let pk = recover_pk(
+            self.y_parity.as_u64() as u8,
+            &self.r,
+            &self.s,
+            &self.signing_hash(),
+        )?;
+        let pk_be = pk_bytes_swap_endianness(&pk_bytes_le(&pk));
+        Ok(Address::from_slice(&keccak256(pk_be)[12..]))
+    }
+
+    /// Authority of the authorization if it is valid on the chain of
+    /// `chain_id`: its chain id is zero or `chain_id`, its nonce can be
+    /// increased and its signature is valid.
+    pub fn valid_authority(&self, chain_id: Word) -> Option<Address> {
+        if !self.chain_id.is_zero() && self.chain_id != chain_id {
+            return None;
+        }
+        if self.nonce == U64::MAX {
+            return None;
+        }
+        self.authority().ok()
+    }
+
+    /// Authorization to delegate to `address` signed by `wallet`.
+    pub fn new(chain_id: Word, address: Address, nonce: u64, wallet: &LocalWallet) -> Self {
+        let mut auth = Self {
+            chain_id,
+            address,
+            nonce: U64::from(nonce),
+            ..Default::default()
+        };
+        let sig = wallet.sign_hash(H256(auth.signing_hash()));
+        auth.y_parity = U64::from(sig.v - 27);
+        auth.r = sig.r;
+        auth.s = sig.s;
+        auth
+    }
+}
+
+impl Encodable for Authorization {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(6);
+        s.append(&self.chain_id);
+        s.append(&self.address);
+        s.append(&self.nonce);
+        s.append(&self.y_parity);
+        s.append(&self.r);
+        s.append(&self.s);
+    }
+}
+
+/// Authorization list of a set-code tx, empty for the other types of tx.
+pub fn authorization_list(tx: &Transaction) -> Result<Vec<Authorization>, Error> {
+    tx.other
+        .get_deserialized(AUTHORIZATION_LIST_KEY)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(Error::SerdeError)
+}
+
+fn rlp_append_fields(tx: &Transaction, s: &mut RlpStream) {
+    s.append(&tx.chain_id.unwrap_or_default());
+    s.append(&tx.nonce);
+    s.append(&tx.max_priority_fee_per_gas.unwrap_or_default());
+    s.append(&tx.max_fee_per_gas.unwrap_or_default());
+    s.append(&tx.gas);
+    // set-code txs can't create contracts, the callee is only missing from
+    // malformed txs
+    match tx.to {
+        Some(to) => s.append(&to),
+        None => s.append_empty_data(),
+    };
+    s.append(&tx.value);
+    s.append(&tx.input);
+    s.append(&tx.access_list.clone().unwrap_or_default());
+    s.append_list(&authorization_list(tx).unwrap_or_default());
+}
+
+/// RLP bytes of a set-code tx signed by its sender: `0x04 || rlp([chain_id,
+/// nonce, max_priority_fee_per_gas, max_fee_per_gas, gas, to, value, data,
+/// access_list, authorization_list])`.
+pub fn rlp_unsigned(tx: &Transaction) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(10);
+    rlp_append_fields(tx, &mut stream);
+    let mut rlp = vec![SET_CODE_TX_TYPE as u8];
+    rlp.extend_from_slice(&stream.out());
+    rlp
+}
+
+/// RLP bytes of a signed set-code tx, hashed into its tx hash: the fields of
+/// [`rlp_unsigned`] followed by the y parity, r and s of the signature.
+pub fn rlp_signed(tx: &Transaction) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(13);
+    rlp_append_fields(tx, &mut stream);
+    stream.append(&tx.v);
+    stream.append(&tx.r);
+    stream.append(&tx.s);
+    let mut rlp = vec![SET_CODE_TX_TYPE as u8];
+    rlp.extend_from_slice(&stream.out());
+    rlp
+}
+
+/// Code of an account delegating to `address`.
+pub fn delegation_designator(address: Address) -> Vec<u8> {
+    let mut code = DELEGATION_DESIGNATOR_PREFIX.to_vec();
+    code.extend_from_slice(address.as_bytes());
+    code
+}
+
+/// Address of the account delegated to if `code` is a delegation designator.
+pub fn delegated_address(code: &[u8]) -> Option<Address> {
+    (code.len() == DELEGATION_DESIGNATOR_LEN && code.starts_with(&DELEGATION_DESIGNATOR_PREFIX))
+        .then(|| Address::from_slice(&code[DELEGATION_DESIGNATOR_PREFIX.len()..]))
+}
+
+#[cfg(test)]
+mod eip7702_tests {
+    use super::*;
+    use crate::address;
+    use ethers_core::types::OtherFields;
+    use ethers_signers::Signer;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn delegation_designator_roundtrip() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let code = delegation_designator(address);
+        assert_eq!(code.len(), DELEGATION_DESIGNATOR_LEN);
+        assert_eq!(delegated_address(&code), Some(address));
+        // designators are matched exactly, other code starting with 0xef01 is
+        // not delegating
+        assert_eq!(delegated_address(&code[..code.len() - 1]), None);
+        assert_eq!(delegated_address(&[0xef, 0x01, 0x01]), None);
+        assert_eq!(delegated_address(&[]), None);
+    }
+
+    #[test]
+    fn recover_authority() {
+        let wallet = LocalWallet::new(&mut StdRng::seed_from_u64(2));
+        let auth = Authorization::new(
+            Word::from(534352),
+            address!("0x00000000000000000000000000000000000000aa"),
+            7,
+            &wallet,
+        );
+        assert_eq!(auth.authority().unwrap(), wallet.address());
+
+        // the signature does not hold for another nonce
+        let replayed = Authorization {
+            nonce: U64::from(8),
+            ..auth.clone()
+        };
+        assert_ne!(replayed.authority().ok(), Some(wallet.address()));
+        assert!(Authorization {
+            y_parity: U64::from(2),
+            ..auth.clone()
+        }
+        .authority()
+        .is_err());
+        // high s values are malleable
+        let high_s = Word::from_big_endian(&SECP256K1_Q.to_bytes_be()) - auth.s;
+        assert!(Authorization {
+            s: high_s,
+            y_parity: U64::one() - auth.y_parity,
+            ..auth
+        }
+        .authority()
+        .is_err());
+    }
+
+    #[test]
+    fn valid_authority() {
+        let wallet = LocalWallet::new(&mut StdRng::seed_from_u64(2));
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let chain_id = Word::from(534352);
+        let auth = Authorization::new(chain_id, address, 7, &wallet);
+        assert_eq!(auth.valid_authority(chain_id), Some(wallet.address()));
+        assert_eq!(auth.valid_authority(Word::one()), None);
+        // authorizations for chain id zero are valid on any chain
+        let any_chain = Authorization::new(Word::zero(), address, 7, &wallet);
+        assert_eq!(
+            any_chain.valid_authority(Word::one()),
+            Some(wallet.address())
+        );
+        // the nonce of the authority can't be increased past 2^64 - 1
+        let max_nonce = Authorization::new(chain_id, address, u64::MAX, &wallet);
+        assert_eq!(max_nonce.valid_authority(chain_id), None);
+    }
+
+    #[test]
+    fn parse_authorization_list() {
+        let mut tx = Transaction {
+            transaction_type: Some(U64::from(SET_CODE_TX_TYPE)),
+            ..Default::default()
+        };
+        assert_eq!(authorization_list(&tx).unwrap(), vec![]);
+
+        tx.other = serde_json::from_str::<OtherFields>(
+            r#"{"authorizationList": [{
+                "chainId": "0x1",
+                "address": "0x00000000000000000000000000000000000000aa",
+                "nonce": "0x2",
+                "yParity": "0x1",
+                "r": "0x3",
+                "s": "0x4"
+            }]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            authorization_list(&tx).unwrap(),
+            vec![Authorization {
+                chain_id: Word::one(),
+                address: address!("0x00000000000000000000000000000000000000aa"),
+                nonce: U64::from(2),
+                y_parity: U64::one(),
+                r: Word::from(3),
+                s: Word::from(4),
+            }]
+        );
+        assert_eq!(rlp_unsigned(&tx)[0], SET_CODE_TX_TYPE as u8);
+    }
+}
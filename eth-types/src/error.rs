@@ -1,6 +1,7 @@
 //! Error module for the eth-types crate
 
 use core::fmt::{Display, Formatter, Result as FmtResult};
+use serde::Deserialize;
 use std::error::Error as StdError;
 
 /// Error type for any BusMapping related failure.
@@ -9,7 +10,7 @@ pub enum Error {
     /// Serde de/serialization error.
     SerdeError(serde_json::error::Error),
     /// Error while generating a trace.
-    TracingError(String),
+    TracingError(TracingError),
     /// Block is missing information about number or base_fee
     IncompleteBlock,
     /// Denotes that the byte in the bytecode does not match with any Opcode ID.
@@ -33,6 +34,8 @@ pub enum Error {
     WordToMemAddr,
     /// Signature parsing error.
     Signature(libsecp256k1::Error),
+    /// A storage snapshot of an account can't be decoded.
+    InvalidStorageSnapshot(String),
 }
 
 impl From<libsecp256k1::Error> for Error {
@@ -49,6 +52,84 @@ impl Display for Error {
 
 impl StdError for Error {}
 
+/// Code of a [`TracingError`]. The external tracer gives the codes up to
+/// [`TracingErrorCode::Tracer`], the others are given by the Rust side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TracingErrorCode {
+    /// The config can't be traced, e.g. its txs exceed the block gas limit.
+    InvalidConfig,
+    /// The gas limit of a tx is below its intrinsic gas.
+    IntrinsicGas,
+    /// The nonce of a tx is below the nonce of its sender.
+    NonceTooLow,
+    /// The nonce of a tx is above the nonce of its sender.
+    NonceTooHigh,
+    /// The nonce of the sender of a tx is at its maximum (EIP-2681).
+    NonceMax,
+    /// The sender of a tx can't pay for its gas and value.
+    InsufficientFunds,
+    /// A tx fails another check of its validation, e.g. its sender has code
+    /// (EIP-3607).
+    InvalidTx,
+    /// The EVM failed to apply a tx for a reason that isn't a validation of
+    /// the tx.
+    EVMError,
+    /// The tracer failed to start or to give its result.
+    Tracer,
+    /// The trace was cancelled before it finished.
+    Cancelled,
+    /// The trace didn't finish in time.
+    TimedOut,
+    /// The tracer gave an output that can't be parsed, or failed to run.
+    Internal,
+}
+
+/// Error of the external tracer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracingError {
+    /// What failed
+    pub code: TracingErrorCode,
+    /// Index of the tx that failed, if the error is about a tx
+    #[serde(default)]
+    pub tx_index: Option<usize>,
+    /// Message of the error
+    pub message: String,
+}
+
+impl TracingError {
+    /// Generates an error that isn't about a tx.
+    pub fn new(code: TracingErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            tx_index: None,
+            message: message.into(),
+        }
+    }
+
+    /// Returns whether the error is an expected failure of a tx failing its
+    /// validation, rather than a bug of the config or of the environment.
+    pub fn is_tx_failure(&self) -> bool {
+        matches!(
+            self.code,
+            TracingErrorCode::IntrinsicGas
+                | TracingErrorCode::NonceTooLow
+                | TracingErrorCode::NonceTooHigh
+                | TracingErrorCode::NonceMax
+                | TracingErrorCode::InsufficientFunds
+                | TracingErrorCode::InvalidTx
+        )
+    }
+}
+
+impl Display for TracingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl StdError for TracingError {}
+
 /// Error type for a failure while parsing an Ethereum Address.
 #[derive(Debug)]
 pub enum EthAddressParsingError {
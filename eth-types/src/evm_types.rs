@@ -3,12 +3,16 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub mod code_analysis;
+#[cfg(feature = "eof")]
+pub mod eof;
 pub mod gas_utils;
 pub mod memory;
 pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
+pub use code_analysis::CodeAnalysis;
 pub use memory::{Memory, MemoryAddress};
 pub use opcode_ids::OpcodeId;
 pub use stack::{Stack, StackAddress};
@@ -161,6 +165,10 @@ impl GasCost {
     pub const TX: Self = Self(21000);
     /// Constant cost for a creation transaction
     pub const CREATION_TX: Self = Self(53000);
+    /// Constant cost for each address of the access list of a transaction
+    pub const ACCESS_LIST_ADDRESS: Self = Self(2400);
+    /// Constant cost for each storage key of the access list of a transaction
+    pub const ACCESS_LIST_STORAGE_KEY: Self = Self(1900);
     /// Constant cost for calling with non-zero value
     pub const CALL_WITH_VALUE: Self = Self(9000);
     /// Constant cost for turning empty account into non-empty account
@@ -200,6 +208,8 @@ impl GasCost {
     pub const PRECOMPILE_MODEXP: Self = Self(0);
     /// Base gas price for precompile call: BLAKE2F
     pub const PRECOMPILE_BLAKE2F: Self = Self(0);
+    /// Gas price for precompile call: P256VERIFY (RIP-7212)
+    pub const PRECOMPILE_P256VERIFY: Self = Self(3450);
 }
 
 impl GasCost {
@@ -0,0 +1,206 @@
+//! Static analysis of EVM bytecode.
+//!
+//! The witness generation and the bytecode circuit both need to know which
+//! bytes of a bytecode are opcodes and which are PUSH data, and JUMP/JUMPI
+//! need to know which offsets are valid jump destinations. This module
+//! computes both once per bytecode, following geth's `codeBitmap`
+//! (core/vm/analysis.go), so that every consumer agrees on the result.
+//!
+//! With the `eof` feature, a bytecode which is a valid EOF container only has
+//! opcodes in its code section, its header and data section are data.
+
+#[cfg(feature = "eof")]
+use super::eof::EofContainer;
+use super::OpcodeId;
+use std::ops::Range;
+
+/// Per-byte analysis of a bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeAnalysis {
+    len: usize,
+    /// Bit `i` is set if byte `i` is data: PUSH data, or a byte out of the
+    /// code section of an EOF container.
+    data: Vec<u64>,
+    /// Bit `i` is set if byte `i` is a `JUMPDEST` opcode.
+    jumpdests: Vec<u64>,
+}
+
+fn set_bit(bitmap: &mut [u64], index: usize) {
+    bitmap[index / 64] |= 1 << (index % 64);
+}
+
+fn get_bit(bitmap: &[u64], index: usize) -> bool {
+    bitmap[index / 64] & (1 << (index % 64)) != 0
+}
+
+impl CodeAnalysis {
+    /// Analyse `code`.
+    pub fn new(code: &[u8]) -> Self {
+        #[cfg(feature = "eof")]
+        if let Ok(container) = EofContainer::validate(code) {
+            let start = code.len() - container.code.len() - container.data.len();
+            return Self::analyse(code, start..start + container.code.len());
+        }
+        Self::analyse(code, 0..code.len())
+    }
+
+    /// Analyse the opcodes of `code` in `section`, the bytes out of it are
+    /// data.
+    fn analyse(code: &[u8], section: Range<usize>) -> Self {
+        let words = (code.len() + 63) / 64;
+        let mut data = vec![0; words];
+        let mut jumpdests = vec![0; words];
+        for index in (0..section.start).chain(section.end..code.len()) {
+            set_bit(&mut data, index);
+        }
+
+        let mut pc = section.start;
+        while pc < section.end {
+            let op = OpcodeId::from(code[pc]);
+            if op == OpcodeId::JUMPDEST {
+                set_bit(&mut jumpdests, pc);
+            }
+            // PUSH data running past the end of the code is allowed, the
+            // missing bytes are read as zeros.
+            let data_end = (pc + 1 + op.data_len()).min(section.end);
+            for index in pc + 1..data_end {
+                set_bit(&mut data, index);
+            }
+            pc += 1 + op.data_len();
+        }
+
+        Self {
+            len: code.len(),
+            data,
+            jumpdests,
+        }
+    }
+
+    /// Length of the analysed code.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the analysed code is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the byte at `pc` is an opcode, false if it is data or
+    /// out of the code.
+    pub fn is_code(&self, pc: usize) -> bool {
+        pc < self.len && !get_bit(&self.data, pc)
+    }
+
+    /// Returns true if `dest` is a valid jump destination, that is a
+    /// `JUMPDEST` opcode which is not part of PUSH data.
+    pub fn is_jumpdest(&self, dest: u64) -> bool {
+        usize::try_from(dest)
+            .map(|dest| dest < self.len && get_bit(&self.jumpdests, dest))
+            .unwrap_or(false)
+    }
+
+    /// Iterate over the `is_code` flag of every byte of the code.
+    pub fn is_code_iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|pc| self.is_code(pc))
+    }
+
+    /// Iterate over the valid jump destinations, in increasing order.
+    pub fn jumpdests(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&pc| get_bit(&self.jumpdests, pc))
+    }
+}
+
+#[cfg(test)]
+mod code_analysis_tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Straight port of geth's `codeBitmap` and `validJumpdest`, used as the
+    /// reference for the analysis.
+    fn geth_analysis(code: &[u8]) -> (Vec<bool>, Vec<usize>) {
+        // geth pads the bitvec so PUSH data past the end can be marked.
+        let mut bitvec = vec![0u8; code.len() / 8 + 1 + 4];
+        let mut pc = 0u64;
+        while (pc as usize) < code.len() {
+            let op = code[pc as usize];
+            pc += 1;
+            if !(0x60..=0x7f).contains(&op) {
+                continue;
+            }
+            let numbits = (op - 0x60 + 1) as u64;
+            for _ in 0..numbits {
+                bitvec[(pc / 8) as usize] |= 0x80 >> (pc % 8);
+                pc += 1;
+            }
+        }
+        let is_code = (0..code.len())
+            .map(|pc| bitvec[pc / 8] & (0x80 >> (pc % 8)) == 0)
+            .collect::<Vec<_>>();
+        let jumpdests = (0..code.len())
+            .filter(|&pc| code[pc] == 0x5b && is_code[pc])
+            .collect();
+        (is_code, jumpdests)
+    }
+
+    #[test]
+    fn push_data_is_not_code() {
+        // PUSH2 0x5b 0x5b JUMPDEST PUSH1
+        let code = [0x61, 0x5b, 0x5b, 0x5b, 0x60];
+        let analysis = CodeAnalysis::new(&code);
+        assert_eq!(
+            analysis.is_code_iter().collect::<Vec<_>>(),
+            vec![true, false, false, true, true]
+        );
+        assert_eq!(analysis.jumpdests().collect::<Vec<_>>(), vec![3]);
+        assert!(!analysis.is_jumpdest(1));
+        assert!(analysis.is_jumpdest(3));
+        assert!(!analysis.is_jumpdest(5));
+        assert!(!analysis.is_jumpdest(u64::MAX));
+    }
+
+    #[test]
+    fn matches_geth_analysis() {
+        let mut rng = StdRng::seed_from_u64(0xc0de);
+        for _ in 0..500 {
+            let len = rng.gen_range(0..200);
+            // Bias the bytes towards PUSH and JUMPDEST opcodes.
+            let code = (0..len)
+                .map(|_| match rng.gen_range(0..4) {
+                    0 => rng.gen_range(0x60..=0x7f),
+                    1 => 0x5b,
+                    _ => rng.gen(),
+                })
+                .collect::<Vec<u8>>();
+            let analysis = CodeAnalysis::new(&code);
+            let (is_code, jumpdests) = geth_analysis(&code);
+            assert_eq!(analysis.is_code_iter().collect::<Vec<_>>(), is_code);
+            assert_eq!(analysis.jumpdests().collect::<Vec<_>>(), jumpdests);
+        }
+    }
+
+    #[cfg(feature = "eof")]
+    #[test]
+    fn eof_container_code_section() {
+        // header: magic, version, code section of 4 bytes, data section of 2
+        // bytes, terminator
+        let header = [0xef, 0x00, 0x01, 0x01, 0x00, 0x04, 0x02, 0x00, 0x02, 0x00];
+        // PUSH1 0x5b JUMPDEST STOP
+        let code = [0x60, 0x5b, 0x5b, 0x00];
+        let data = [0x5b, 0x60];
+        let container = [&header[..], &code, &data].concat();
+
+        let analysis = CodeAnalysis::new(&container);
+        let is_code = analysis.is_code_iter().collect::<Vec<_>>();
+        assert_eq!(is_code[..10], [false; 10]);
+        assert_eq!(is_code[10..14], [true, false, true, true]);
+        assert_eq!(is_code[14..], [false; 2]);
+        assert_eq!(analysis.jumpdests().collect::<Vec<_>>(), vec![12]);
+
+        // An invalid container, with a truncated PUSH, is analysed as legacy
+        // code.
+        let mut invalid = container.clone();
+        invalid[13] = 0x61;
+        assert!(CodeAnalysis::new(&invalid).is_code(0));
+    }
+}
@@ -0,0 +1,242 @@
+//! Experimental support for the EVM Object Format (EOF) container, as
+//! specified by [EIP-3540](https://eips.ethereum.org/EIPS/eip-3540) and
+//! [EIP-3670](https://eips.ethereum.org/EIPS/eip-3670).
+//!
+//! Only the validation of the container is implemented, which the code
+//! analysis uses to tell the code section of a container from its header and
+//! data. Execution of EOF code is not supported by the tracer nor by the
+//! circuits yet.
+
+use super::OpcodeId;
+
+/// Magic bytes every EOF container starts with.
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+/// The only EOF version defined so far.
+pub const EOF_VERSION_1: u8 = 0x01;
+
+const SECTION_KIND_TERMINATOR: u8 = 0x00;
+const SECTION_KIND_CODE: u8 = 0x01;
+const SECTION_KIND_DATA: u8 = 0x02;
+
+/// Reasons for an EOF container to be rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EofError {
+    /// The container doesn't start with [`EOF_MAGIC`].
+    MissingMagic,
+    /// The version byte is not a supported one.
+    UnsupportedVersion(u8),
+    /// The container ends in the middle of its header.
+    TruncatedHeader,
+    /// The header declares a section of an unknown kind.
+    UnknownSectionKind(u8),
+    /// The header declares sections in the wrong order, or more than once.
+    InvalidSectionOrder(u8),
+    /// The header declares a section of size 0.
+    EmptySection(u8),
+    /// The header has no code section.
+    MissingCodeSection,
+    /// The container size doesn't match the sizes declared in its header.
+    InvalidContainerSize {
+        /// Size of the container according to its header.
+        expected: usize,
+        /// Actual size of the container.
+        actual: usize,
+    },
+    /// The code section uses an unassigned opcode.
+    UndefinedOpcode {
+        /// Offset of the opcode in the code section.
+        pc: usize,
+        /// The opcode byte.
+        byte: u8,
+    },
+    /// The code section uses an opcode which is rejected in EOF code.
+    DeprecatedOpcode {
+        /// Offset of the opcode in the code section.
+        pc: usize,
+        /// The opcode.
+        opcode: OpcodeId,
+    },
+    /// The code section ends in the middle of a PUSH's immediate data.
+    TruncatedPush {
+        /// Offset of the PUSH opcode in the code section.
+        pc: usize,
+    },
+}
+
+/// A parsed EOF version 1 container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EofContainer<'a> {
+    /// Version of the container.
+    pub version: u8,
+    /// The code section.
+    pub code: &'a [u8],
+    /// The data section, empty if the container has none.
+    pub data: &'a [u8],
+}
+
+/// Returns true if `bytes` starts with the EOF magic, so it must be validated
+/// as an EOF container instead of being treated as legacy code.
+pub fn is_eof(bytes: &[u8]) -> bool {
+    bytes.starts_with(&EOF_MAGIC)
+}
+
+impl<'a> EofContainer<'a> {
+    /// Parses the header of `bytes` and splits it into its sections, without
+    /// validating the code section.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, EofError> {
+        if !is_eof(bytes) {
+            return Err(EofError::MissingMagic);
+        }
+        let version = *bytes.get(2).ok_or(EofError::TruncatedHeader)?;
+        if version != EOF_VERSION_1 {
+            return Err(EofError::UnsupportedVersion(version));
+        }
+
+        let mut offset = 3;
+        let mut code_size = None;
+        let mut data_size = None;
+        loop {
+            let kind = *bytes.get(offset).ok_or(EofError::TruncatedHeader)?;
+            offset += 1;
+            if kind == SECTION_KIND_TERMINATOR {
+                break;
+            }
+            let size = bytes
+                .get(offset..offset + 2)
+                .map(|size| u16::from_be_bytes([size[0], size[1]]) as usize)
+                .ok_or(EofError::TruncatedHeader)?;
+            offset += 2;
+            if size == 0 {
+                return Err(EofError::EmptySection(kind));
+            }
+            match kind {
+                SECTION_KIND_CODE if code_size.is_none() => code_size = Some(size),
+                // The data section, if any, must follow the code section.
+                SECTION_KIND_DATA if code_size.is_some() && data_size.is_none() => {
+                    data_size = Some(size)
+                }
+                SECTION_KIND_CODE | SECTION_KIND_DATA => {
+                    return Err(EofError::InvalidSectionOrder(kind))
+                }
+                _ => return Err(EofError::UnknownSectionKind(kind)),
+            }
+        }
+
+        let code_size = code_size.ok_or(EofError::MissingCodeSection)?;
+        let data_size = data_size.unwrap_or_default();
+        let expected = offset + code_size + data_size;
+        if bytes.len() != expected {
+            return Err(EofError::InvalidContainerSize {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Self {
+            version,
+            code: &bytes[offset..offset + code_size],
+            data: &bytes[offset + code_size..],
+        })
+    }
+
+    /// Parses `bytes` and validates its code section.
+    pub fn validate(bytes: &'a [u8]) -> Result<Self, EofError> {
+        let container = Self::parse(bytes)?;
+        validate_code(container.code)?;
+        Ok(container)
+    }
+}
+
+/// Validates an EOF code section per EIP-3670: every opcode must be assigned,
+/// not deprecated in EOF code, and PUSH immediates must not be truncated.
+pub fn validate_code(code: &[u8]) -> Result<(), EofError> {
+    let mut pc = 0;
+    while pc < code.len() {
+        let byte = code[pc];
+        let opcode = OpcodeId::from(byte);
+        match opcode {
+            // 0xfe is the designated INVALID instruction, which EOF code may use.
+            OpcodeId::INVALID(_) if byte != 0xfe => {
+                return Err(EofError::UndefinedOpcode { pc, byte })
+            }
+            OpcodeId::CALLCODE | OpcodeId::SELFDESTRUCT => {
+                return Err(EofError::DeprecatedOpcode { pc, opcode })
+            }
+            _ => (),
+        }
+        let next_pc = pc + 1 + opcode.data_len();
+        if next_pc > code.len() {
+            return Err(EofError::TruncatedPush { pc });
+        }
+        pc = next_pc;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod eof_tests {
+    use super::*;
+
+    #[test]
+    fn parse_code_and_data() {
+        // PUSH1 0x00 PUSH1 0x00 RETURN, then a 2 bytes data section
+        let bytes = [
+            0xef, 0x00, 0x01, 0x01, 0x00, 0x05, 0x02, 0x00, 0x02, 0x00, 0x60, 0x00, 0x60, 0x00,
+            0xf3, 0xaa, 0xbb,
+        ];
+        let container = EofContainer::validate(&bytes).unwrap();
+        assert_eq!(container.code, &[0x60, 0x00, 0x60, 0x00, 0xf3]);
+        assert_eq!(container.data, &[0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn reject_malformed_header() {
+        assert_eq!(EofContainer::parse(&[0xef]), Err(EofError::MissingMagic));
+        assert_eq!(
+            EofContainer::parse(&[0xef, 0x00, 0x02]),
+            Err(EofError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            EofContainer::parse(&[0xef, 0x00, 0x01, 0x01, 0x00]),
+            Err(EofError::TruncatedHeader)
+        );
+        assert_eq!(
+            EofContainer::parse(&[0xef, 0x00, 0x01, 0x00]),
+            Err(EofError::MissingCodeSection)
+        );
+        assert_eq!(
+            EofContainer::parse(&[0xef, 0x00, 0x01, 0x02, 0x00, 0x01, 0x00, 0x00]),
+            Err(EofError::InvalidSectionOrder(SECTION_KIND_DATA))
+        );
+        assert_eq!(
+            EofContainer::parse(&[0xef, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00]),
+            Err(EofError::EmptySection(SECTION_KIND_CODE))
+        );
+        assert_eq!(
+            EofContainer::parse(&[0xef, 0x00, 0x01, 0x01, 0x00, 0x02, 0x00, 0x00]),
+            Err(EofError::InvalidContainerSize {
+                expected: 9,
+                actual: 8
+            })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_code() {
+        assert_eq!(
+            validate_code(&[0x00, 0x0c]),
+            Err(EofError::UndefinedOpcode { pc: 1, byte: 0x0c })
+        );
+        assert_eq!(
+            validate_code(&[0xff]),
+            Err(EofError::DeprecatedOpcode {
+                pc: 0,
+                opcode: OpcodeId::SELFDESTRUCT
+            })
+        );
+        assert_eq!(
+            validate_code(&[0x00, 0x61, 0x01]),
+            Err(EofError::TruncatedPush { pc: 1 })
+        );
+    }
+}
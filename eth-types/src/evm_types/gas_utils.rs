@@ -1,18 +1,48 @@
 //! Utility functions to help calculate gas
 
-use super::GasCost;
+use super::{GasCost, GAS_STIPEND_CALL_WITH_VALUE};
 use crate::Word;
 
+/// Calculate the memory word size needed to access `length` bytes from
+/// `offset`. An access of 0 bytes needs no memory, whatever its offset.
+pub fn memory_word_size(offset: u64, length: u64) -> u64 {
+    if length == 0 {
+        0
+    } else {
+        offset.saturating_add(length).saturating_add(31) / 32
+    }
+}
+
+/// Calculate the memory word size after accessing the `(offset, length)`
+/// ranges from a memory of `curr_memory_word_size` words.
+pub fn next_memory_word_size(
+    curr_memory_word_size: u64,
+    ranges: impl IntoIterator<Item = (u64, u64)>,
+) -> u64 {
+    ranges
+        .into_iter()
+        .map(|(offset, length)| memory_word_size(offset, length))
+        .fold(curr_memory_word_size, u64::max)
+}
+
+/// Calculate the memory cost of `memory_word_size` words:
+/// `Gmem * memory_word_size + floor(memory_word_size ** 2 / 512)`.
+/// The quadratic term is computed on 128 bits so that it can't overflow.
+fn memory_cost(memory_word_size: u64) -> u128 {
+    let memory_word_size = memory_word_size as u128;
+    GasCost::MEMORY_EXPANSION_LINEAR_COEFF.0 as u128 * memory_word_size
+        + memory_word_size * memory_word_size / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.0 as u128
+}
+
 /// Calculate memory expansion gas cost by current and next memory word size.
+/// A cost that doesn't fit in 64 bits, which no transaction can pay, is
+/// saturated to `u64::MAX`.
 pub fn memory_expansion_gas_cost(curr_memory_word_size: u64, next_memory_word_size: u64) -> u64 {
-    if next_memory_word_size == curr_memory_word_size {
+    if next_memory_word_size <= curr_memory_word_size {
         0
     } else {
-        GasCost::MEMORY_EXPANSION_LINEAR_COEFF.0 * (next_memory_word_size - curr_memory_word_size)
-            + next_memory_word_size * next_memory_word_size
-                / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.0
-            - curr_memory_word_size * curr_memory_word_size
-                / GasCost::MEMORY_EXPANSION_QUAD_DENOMINATOR.0
+        u64::try_from(memory_cost(next_memory_word_size) - memory_cost(curr_memory_word_size))
+            .unwrap_or(u64::MAX)
     }
 }
 
@@ -24,20 +54,27 @@ pub fn memory_copier_gas_cost(
     num_copy_bytes: u64,
     per_word_copy_gas: u64,
 ) -> u64 {
-    let num_words = (num_copy_bytes + 31) / 32;
-    num_words * per_word_copy_gas +
+    let num_words = memory_word_size(0, num_copy_bytes);
+    num_words.saturating_mul(per_word_copy_gas).saturating_add(
         // Note that opcodes with a byte size parameter of 0 will not trigger
         // memory expansion, regardless of their offset parameters.
         if num_words > 0 {
             memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size)
         } else {
             0
-        }
+        },
+    )
+}
+
+/// Calculate all but one 64th of `gas`, the most gas a caller can pass to its
+/// callee per EIP 150.
+pub fn all_but_one_64th_gas(gas: u64) -> u64 {
+    gas - gas / 64
 }
 
 /// Calculate EIP 150 gas passed to callee.
 pub fn eip150_gas(gas_left: u64, gas_specified: Word) -> u64 {
-    let capped_gas = gas_left - gas_left / 64;
+    let capped_gas = all_but_one_64th_gas(gas_left);
 
     if gas_specified.bits() <= 64 {
         let gas_specified = gas_specified.low_u64();
@@ -49,8 +86,205 @@ pub fn eip150_gas(gas_left: u64, gas_specified: Word) -> u64 {
     capped_gas
 }
 
+/// Calculate the gas a CALL, CALLCODE, DELEGATECALL or STATICCALL starts its
+/// callee with, out of the `gas_available` after its cost: the EIP 150 gas,
+/// plus the stipend of a call with value.
+pub fn callee_gas_left(gas_available: u64, gas_specified: Word, has_value: bool) -> u64 {
+    eip150_gas(gas_available, gas_specified)
+        + if has_value {
+            GAS_STIPEND_CALL_WITH_VALUE
+        } else {
+            0
+        }
+}
+
 /// Calculate gas cost for transaction data.
 pub fn tx_data_gas_cost(data: &[u8]) -> u64 {
     data.iter()
         .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 })
 }
+
+/// Gas per token of the transaction data in the floor cost of EIP-7623.
+pub const TOTAL_COST_FLOOR_PER_TOKEN: u64 = 10;
+
+/// Calculate the tokens of the transaction data of EIP-7623: 1 for a zero byte
+/// and 4 for a non-zero byte, so that [`tx_data_gas_cost`] is 4 per token.
+pub fn tx_data_tokens(data: &[u8]) -> u64 {
+    data.iter()
+        .fold(0, |acc, byte| acc + if *byte == 0 { 1 } else { 4 })
+}
+
+/// Calculate the floor of the gas used by a transaction of EIP-7623: the base
+/// cost of a transaction plus [`TOTAL_COST_FLOOR_PER_TOKEN`] per token of its
+/// data. The gas limit of the transaction must cover it, and the transaction
+/// pays it if it is above its gas used after the refund.
+pub fn tx_floor_data_gas(data: &[u8]) -> u64 {
+    GasCost::TX.as_u64() + TOTAL_COST_FLOOR_PER_TOKEN * tx_data_tokens(data)
+}
+
+/// Minimum blob base fee of EIP-4844, when the excess blob gas is zero.
+pub const MIN_BLOB_BASE_FEE: u64 = 1;
+/// Controls the maximum rate of change of the blob base fee of EIP-4844.
+pub const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3338477;
+
+/// Calculate the blob base fee of a block from its excess blob gas:
+/// `MIN_BLOB_BASE_FEE * e ** (excess_blob_gas /
+/// BLOB_BASE_FEE_UPDATE_FRACTION)`, approximated with the Taylor expansion
+/// of EIP-4844.
+pub fn blob_base_fee(excess_blob_gas: u64) -> Word {
+    let numerator = Word::from(excess_blob_gas);
+    let denominator = Word::from(BLOB_BASE_FEE_UPDATE_FRACTION);
+    let mut output = Word::zero();
+    let mut numerator_accum = Word::from(MIN_BLOB_BASE_FEE) * denominator;
+    let mut i = Word::one();
+    while !numerator_accum.is_zero() {
+        output += numerator_accum;
+        numerator_accum = numerator_accum.saturating_mul(numerator) / (denominator * i);
+        i += Word::one();
+    }
+    output / denominator
+}
+
+/// Calculate the refund counter after an SSTORE of `value` to a slot holding
+/// `value_prev`, and `original_value` at the start of the tx, following
+/// `makeGasSStoreFunc` of geth with the refunds of EIP-3529. Returns `None` if
+/// the refund counter would go below zero, which geth treats as a fatal error.
+pub fn sstore_refund(
+    refund: u64,
+    original_value: Word,
+    value_prev: Word,
+    value: Word,
+) -> Option<u64> {
+    // Same clause tags as in geth. The refunds of clearing and of resetting the
+    // slot are added together: (v, 0, v) gets both.
+    let mut refund = refund;
+    if value_prev == value {
+        return Some(refund);
+    }
+    if !original_value.is_zero() {
+        if value_prev.is_zero() {
+            // recreate slot (2.2.1.1)
+            refund = refund.checked_sub(GasCost::SSTORE_CLEARS_SCHEDULE.as_u64())?;
+        }
+        if value.is_zero() {
+            // delete slot (2.2.1.2), and (2.1.2b) when the slot was clean
+            refund += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64();
+        }
+    }
+    if original_value == value {
+        refund += if original_value.is_zero() {
+            // reset to original inexistent slot (2.2.2.1)
+            GasCost::SSTORE_SET.as_u64() - GasCost::WARM_ACCESS.as_u64()
+        } else {
+            // reset to original existing slot (2.2.2.2)
+            GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64()
+        };
+    }
+    Some(refund)
+}
+
+#[cfg(test)]
+mod gas_utils_tests {
+    use super::*;
+
+    #[test]
+    fn memory_expansion() {
+        assert_eq!(memory_word_size(0x40, 0), 0);
+        assert_eq!(memory_word_size(0x40, 1), 3);
+        assert_eq!(next_memory_word_size(2, [(0x20, 0x20), (0x100, 0)]), 2);
+        assert_eq!(next_memory_word_size(2, [(0x20, 0x20), (0x21, 0x40)]), 4);
+
+        assert_eq!(memory_expansion_gas_cost(0, 0), 0);
+        assert_eq!(memory_expansion_gas_cost(4, 2), 0);
+        assert_eq!(memory_expansion_gas_cost(0, 1), 3);
+        assert_eq!(memory_expansion_gas_cost(32, 1024), 3 * 992 + 2048 - 2);
+        // The quadratic cost of 2^40 words overflows 64 bits.
+        assert_eq!(memory_expansion_gas_cost(0, 1 << 40), u64::MAX);
+        assert_eq!(
+            memory_copier_gas_cost(0, u64::MAX / 32, u64::MAX, 3),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn callee_gas_at_one_64th_boundaries() {
+        assert_eq!(all_but_one_64th_gas(0), 0);
+        assert_eq!(all_but_one_64th_gas(63), 63);
+        assert_eq!(all_but_one_64th_gas(64), 63);
+        assert_eq!(all_but_one_64th_gas(127), 126);
+        assert_eq!(all_but_one_64th_gas(128), 126);
+
+        let cap = all_but_one_64th_gas(6400);
+        assert_eq!(cap, 6300);
+        assert_eq!(eip150_gas(6400, Word::from(cap - 1)), cap - 1);
+        assert_eq!(eip150_gas(6400, Word::from(cap)), cap);
+        assert_eq!(eip150_gas(6400, Word::from(cap + 1)), cap);
+        // a gas argument above 64 bits is capped too
+        assert_eq!(eip150_gas(6400, Word::from(u64::MAX) + 1), cap);
+        assert_eq!(eip150_gas(6400, Word::MAX), cap);
+
+        // the stipend comes on top of the capped gas
+        assert_eq!(
+            callee_gas_left(6400, Word::MAX, true),
+            cap + GAS_STIPEND_CALL_WITH_VALUE
+        );
+        assert_eq!(callee_gas_left(6400, Word::zero(), true), 2300);
+        assert_eq!(callee_gas_left(6400, Word::zero(), false), 0);
+    }
+
+    #[test]
+    fn calldata_floor_cost() {
+        let data = [0, 0, 1, 0xff];
+        assert_eq!(tx_data_tokens(&data), 2 + 2 * 4);
+        assert_eq!(tx_data_gas_cost(&data), 4 * tx_data_tokens(&data));
+        assert_eq!(tx_floor_data_gas(&[]), GasCost::TX.as_u64());
+        assert_eq!(tx_floor_data_gas(&data), 21000 + 10 * 10);
+    }
+
+    #[test]
+    fn blob_base_fee_by_excess_blob_gas() {
+        assert_eq!(blob_base_fee(0), Word::from(MIN_BLOB_BASE_FEE));
+        // the fee doubles roughly every ln(2) * BLOB_BASE_FEE_UPDATE_FRACTION
+        assert_eq!(blob_base_fee(BLOB_BASE_FEE_UPDATE_FRACTION), Word::from(2));
+        assert_eq!(
+            blob_base_fee(10 * BLOB_BASE_FEE_UPDATE_FRACTION),
+            Word::from(22026)
+        );
+    }
+
+    #[test]
+    fn sstore_refund_matrix() {
+        let [zero, one, two] = [0u64, 1, 2].map(Word::from);
+        let clears = GasCost::SSTORE_CLEARS_SCHEDULE.as_u64();
+        let set = GasCost::SSTORE_SET.as_u64() - GasCost::WARM_ACCESS.as_u64();
+        let reset = GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64();
+        // (original, current, new, refund delta) of the table of EIP-3529
+        for (original, current, new, refund) in [
+            (zero, zero, zero, 0),
+            (zero, zero, one, 0),
+            (zero, one, zero, set as i64),
+            (zero, one, two, 0),
+            (zero, one, one, 0),
+            (one, zero, zero, 0),
+            (one, zero, one, reset as i64 - clears as i64),
+            (one, zero, two, -(clears as i64)),
+            (one, two, zero, clears as i64),
+            (one, two, two, 0),
+            (one, two, one, reset as i64),
+            (one, one, zero, clears as i64),
+            (one, one, two, 0),
+            (one, one, one, 0),
+        ] {
+            assert_eq!(
+                sstore_refund(clears, original, current, new),
+                Some((clears as i64 + refund) as u64),
+                "{} {} {}",
+                original,
+                current,
+                new
+            );
+        }
+        // the refund of the clearing undone by a recreation isn't there
+        assert_eq!(sstore_refund(0, one, zero, two), None);
+    }
+}
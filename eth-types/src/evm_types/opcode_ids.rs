@@ -281,10 +281,18 @@ pub enum OpcodeId {
     SELFBALANCE,
     /// `BASEFEE`
     BASEFEE,
+    /// `BLOBHASH`
+    BLOBHASH,
+    /// `BLOBBASEFEE`
+    BLOBBASEFEE,
     /// `SLOAD`
     SLOAD,
     /// `SSTORE`
     SSTORE,
+    /// `TLOAD`
+    TLOAD,
+    /// `TSTORE`
+    TSTORE,
     /// `GAS`
     GAS,
 
@@ -514,8 +522,12 @@ impl OpcodeId {
             OpcodeId::CHAINID => 0x46u8,
             OpcodeId::SELFBALANCE => 0x47u8,
             OpcodeId::BASEFEE => 0x48u8,
+            OpcodeId::BLOBHASH => 0x49u8,
+            OpcodeId::BLOBBASEFEE => 0x4au8,
             OpcodeId::SLOAD => 0x54u8,
             OpcodeId::SSTORE => 0x55u8,
+            OpcodeId::TLOAD => 0x5cu8,
+            OpcodeId::TSTORE => 0x5du8,
             OpcodeId::GAS => 0x5au8,
             OpcodeId::LOG0 => 0xa0u8,
             OpcodeId::LOG1 => 0xa1u8,
@@ -592,12 +604,16 @@ impl OpcodeId {
             OpcodeId::CHAINID => GasCost::QUICK,
             OpcodeId::SELFBALANCE => GasCost::FAST,
             OpcodeId::BASEFEE => GasCost::QUICK,
+            OpcodeId::BLOBHASH => GasCost::FASTEST,
+            OpcodeId::BLOBBASEFEE => GasCost::QUICK,
             OpcodeId::POP => GasCost::QUICK,
             OpcodeId::MLOAD => GasCost::FASTEST,
             OpcodeId::MSTORE => GasCost::FASTEST,
             OpcodeId::MSTORE8 => GasCost::FASTEST,
             OpcodeId::SLOAD => GasCost::ZERO,
             OpcodeId::SSTORE => GasCost::ZERO,
+            OpcodeId::TLOAD => GasCost::WARM_ACCESS,
+            OpcodeId::TSTORE => GasCost::WARM_ACCESS,
             OpcodeId::JUMP => GasCost::MID,
             OpcodeId::JUMPI => GasCost::SLOW,
             OpcodeId::PC => GasCost::QUICK,
@@ -747,12 +763,16 @@ impl OpcodeId {
             OpcodeId::CHAINID => (1, 1024),
             OpcodeId::SELFBALANCE => (1, 1024),
             OpcodeId::BASEFEE => (1, 1024),
+            OpcodeId::BLOBHASH => (0, 1023),
+            OpcodeId::BLOBBASEFEE => (1, 1024),
             OpcodeId::POP => (0, 1023),
             OpcodeId::MLOAD => (0, 1023),
             OpcodeId::MSTORE => (0, 1022),
             OpcodeId::MSTORE8 => (0, 1022),
             OpcodeId::SLOAD => (0, 1023),
             OpcodeId::SSTORE => (0, 1022),
+            OpcodeId::TLOAD => (0, 1023),
+            OpcodeId::TSTORE => (0, 1022),
             OpcodeId::JUMP => (0, 1023),
             OpcodeId::JUMPI => (0, 1022),
             OpcodeId::PC => (1, 1024),
@@ -850,6 +870,68 @@ impl OpcodeId {
             .collect()
     }
 
+    /// Returns the number of values that the `OpcodeId` pops from the stack
+    /// and pushes to it when it succeeds. `DUPn` and `SWAPn` read deeper
+    /// values than they pop.
+    pub fn stack_pops_pushes(&self) -> (usize, usize) {
+        match self {
+            OpcodeId::STOP | OpcodeId::JUMPDEST | OpcodeId::INVALID(_) => (0, 0),
+            OpcodeId::ISZERO
+            | OpcodeId::NOT
+            | OpcodeId::BALANCE
+            | OpcodeId::CALLDATALOAD
+            | OpcodeId::EXTCODESIZE
+            | OpcodeId::EXTCODEHASH
+            | OpcodeId::BLOCKHASH
+            | OpcodeId::BLOBHASH
+            | OpcodeId::MLOAD
+            | OpcodeId::SLOAD
+            | OpcodeId::TLOAD => (1, 1),
+            OpcodeId::ADDMOD | OpcodeId::MULMOD => (3, 1),
+            OpcodeId::ADDRESS
+            | OpcodeId::ORIGIN
+            | OpcodeId::CALLER
+            | OpcodeId::CALLVALUE
+            | OpcodeId::CALLDATASIZE
+            | OpcodeId::CODESIZE
+            | OpcodeId::GASPRICE
+            | OpcodeId::RETURNDATASIZE
+            | OpcodeId::COINBASE
+            | OpcodeId::TIMESTAMP
+            | OpcodeId::NUMBER
+            | OpcodeId::DIFFICULTY
+            | OpcodeId::GASLIMIT
+            | OpcodeId::CHAINID
+            | OpcodeId::SELFBALANCE
+            | OpcodeId::BASEFEE
+            | OpcodeId::BLOBBASEFEE
+            | OpcodeId::PC
+            | OpcodeId::MSIZE
+            | OpcodeId::GAS => (0, 1),
+            OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => (3, 0),
+            OpcodeId::EXTCODECOPY => (4, 0),
+            OpcodeId::POP | OpcodeId::JUMP | OpcodeId::SELFDESTRUCT => (1, 0),
+            OpcodeId::MSTORE
+            | OpcodeId::MSTORE8
+            | OpcodeId::SSTORE
+            | OpcodeId::TSTORE
+            | OpcodeId::JUMPI
+            | OpcodeId::RETURN
+            | OpcodeId::REVERT => (2, 0),
+            OpcodeId::CREATE => (3, 1),
+            OpcodeId::CREATE2 => (4, 1),
+            OpcodeId::CALL | OpcodeId::CALLCODE => (7, 1),
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => (6, 1),
+            OpcodeId::PUSH0 => (0, 1),
+            op if op.is_push() => (0, 1),
+            op if op.is_dup() => (0, 1),
+            op if op.is_swap() => (0, 0),
+            op if op.is_log() => (2 + op.postfix().unwrap() as usize, 0),
+            // Binary arithmetic, comparison, bitwise and SHA3
+            _ => (2, 1),
+        }
+    }
+
     /// Returns `true` if the `OpcodeId` has memory access
     pub const fn has_memory_access(&self) -> bool {
         matches!(
@@ -1054,8 +1136,16 @@ impl From<u8> for OpcodeId {
             0x46u8 => OpcodeId::CHAINID,
             0x47u8 => OpcodeId::SELFBALANCE,
             0x48u8 => OpcodeId::BASEFEE,
+            #[cfg(feature = "cancun")]
+            0x49u8 => OpcodeId::BLOBHASH,
+            #[cfg(feature = "cancun")]
+            0x4au8 => OpcodeId::BLOBBASEFEE,
             0x54u8 => OpcodeId::SLOAD,
             0x55u8 => OpcodeId::SSTORE,
+            #[cfg(feature = "cancun")]
+            0x5cu8 => OpcodeId::TLOAD,
+            #[cfg(feature = "cancun")]
+            0x5du8 => OpcodeId::TSTORE,
             0x5au8 => OpcodeId::GAS,
             0xa0u8 => OpcodeId::LOG0,
             0xa1u8 => OpcodeId::LOG1,
@@ -1230,8 +1320,22 @@ impl FromStr for OpcodeId {
             "SELFDESTRUCT" => OpcodeId::SELFDESTRUCT,
             "CHAINID" => OpcodeId::CHAINID,
             "BASEFEE" => OpcodeId::BASEFEE,
-            "TLOAD" => OpcodeId::INVALID(0xb3),
-            "TSTORE" => OpcodeId::INVALID(0xb4),
+            #[cfg(feature = "cancun")]
+            "BLOBHASH" => OpcodeId::BLOBHASH,
+            #[cfg(not(feature = "cancun"))]
+            "BLOBHASH" => OpcodeId::INVALID(0x49),
+            #[cfg(feature = "cancun")]
+            "BLOBBASEFEE" => OpcodeId::BLOBBASEFEE,
+            #[cfg(not(feature = "cancun"))]
+            "BLOBBASEFEE" => OpcodeId::INVALID(0x4a),
+            #[cfg(feature = "cancun")]
+            "TLOAD" => OpcodeId::TLOAD,
+            #[cfg(not(feature = "cancun"))]
+            "TLOAD" => OpcodeId::INVALID(0x5c),
+            #[cfg(feature = "cancun")]
+            "TSTORE" => OpcodeId::TSTORE,
+            #[cfg(not(feature = "cancun"))]
+            "TSTORE" => OpcodeId::INVALID(0x5d),
             _ => {
                 // Parse an invalid opcode value as reported by geth
                 lazy_static! {
@@ -1308,4 +1412,20 @@ mod opcode_ids_tests {
         assert_eq!(OpcodeId::LOG2.data_len(), 0);
         assert_eq!(OpcodeId::CALLCODE.data_len(), 0);
     }
+
+    #[test]
+    fn stack_pops_pushes_match_stack_ptrs() {
+        for op in OpcodeId::valid_opcodes() {
+            if op.is_dup() || op.is_swap() {
+                continue;
+            }
+            let (pops, pushes) = op.stack_pops_pushes();
+            let expected: Vec<u32> = (0..pushes.saturating_sub(pops) as u32)
+                .chain(1024 - pops as u32 + 1..=1024)
+                .collect();
+            assert_eq!(op.invalid_stack_ptrs(), expected, "{:?}", op);
+        }
+        assert_eq!(OpcodeId::DUP3.stack_pops_pushes(), (0, 1));
+        assert_eq!(OpcodeId::SWAP3.stack_pops_pushes(), (0, 0));
+    }
 }
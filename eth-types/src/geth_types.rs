@@ -1,17 +1,17 @@
 //! Types needed for generating Ethereum traces
 
 use crate::{
-    sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
-    AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
-    Word, U64,
+    evm_types::gas_utils::blob_base_fee, sign_types::SignData, AccessList, Address, Block, Bytes,
+    Error, GethExecTrace, Hash, ToBigEndian, Word, U64,
 };
-use ethers_core::types::{
-    Eip1559TransactionRequest, Eip2930TransactionRequest, NameOrAddress, TransactionRequest, H256,
+use ethers_core::{
+    types::{
+        Eip1559TransactionRequest, Eip2930TransactionRequest, NameOrAddress, TransactionRequest,
+        H256,
+    },
+    utils::rlp::Rlp,
 };
 use ethers_signers::{LocalWallet, Signer};
-use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
-use num::Integer;
-use num_bigint::BigUint;
 use serde::{Serialize, Serializer};
 use serde_with::serde_as;
 use sha3::{Digest, Keccak256};
@@ -30,8 +30,12 @@ pub enum TxType {
     Eip1559,
     /// EIP 2930 tx
     Eip2930,
-    /// L1 Message tx
+    /// L1 Message tx. Unlike the deposit txs of OP-stack chains, it mints no
+    /// value: its value is transferred from its sender like for any tx.
     L1Msg,
+    /// EIP 7702 set-code tx
+    #[cfg(feature = "prague")]
+    Eip7702,
 }
 
 impl From<TxType> for usize {
@@ -58,6 +62,8 @@ impl TxType {
             Some(x) if x == U64::from(1) => Self::Eip2930,
             Some(x) if x == U64::from(2) => Self::Eip2930,
             Some(x) if x == U64::from(0x7e) => Self::L1Msg,
+            #[cfg(feature = "prague")]
+            Some(x) if x == U64::from(crate::eip7702::SET_CODE_TX_TYPE) => Self::Eip7702,
             _ => match tx.v.as_u64() {
                 0 | 1 | 27 | 28 => Self::PreEip155,
                 _ => Self::Eip155,
@@ -84,6 +90,11 @@ impl TxType {
             TxType::L1Msg => {
                 unreachable!("L1 msg does not have signature")
             }
+            #[cfg(feature = "prague")]
+            TxType::Eip7702 => {
+                assert!(v <= 1);
+                v
+            }
         };
 
         recovery_id as u8
@@ -113,9 +124,21 @@ pub fn get_rlp_unsigned(tx: &crate::Transaction) -> Vec<u8> {
             // L1 msg does not have signature
             vec![]
         }
+        #[cfg(feature = "prague")]
+        TxType::Eip7702 => crate::eip7702::rlp_unsigned(tx),
     }
 }
 
+/// Get the signed RLP bytes, whose hash is the tx hash
+pub fn get_rlp_signed(tx: &crate::Transaction) -> Vec<u8> {
+    #[cfg(feature = "prague")]
+    if TxType::get_tx_type(tx) == TxType::Eip7702 {
+        // not supported by ethers yet
+        return crate::eip7702::rlp_signed(tx);
+    }
+    tx.rlp().to_vec()
+}
+
 /// Definition of all of the data related to an account.
 #[serde_as]
 #[derive(PartialEq, Eq, Debug, Default, Clone, Serialize)]
@@ -131,6 +154,10 @@ pub struct Account {
     /// Storage
     #[serde(serialize_with = "serde_account_storage")]
     pub storage: HashMap<Word, Word>,
+    /// Storage in a compact format, for the accounts with too many slots for
+    /// the map of `storage`. Its slots are set after the ones of `storage`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_snapshot: Option<StorageSnapshot>,
 }
 
 impl Account {
@@ -140,6 +167,94 @@ impl Account {
             && self.balance.is_zero()
             && self.code.is_empty()
             && self.storage.is_empty()
+            && self.storage_snapshot.is_none()
+    }
+
+    /// Returns the slots of `storage` and of `storage_snapshot` together.
+    pub fn all_storage(&self) -> Result<HashMap<Word, Word>, Error> {
+        let mut storage = self.storage.clone();
+        if let Some(snapshot) = &self.storage_snapshot {
+            storage.extend(snapshot.slots()?);
+        }
+        Ok(storage)
+    }
+}
+
+/// Storage of an account in a compact format, passed to the external tracer
+/// as bytes instead of a map of hex strings.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+#[serde(tag = "format", content = "data", rename_all = "lowercase")]
+pub enum StorageSnapshot {
+    /// Pairs of 32-byte big-endian keys and values, sorted by key
+    Pairs(Bytes),
+    /// RLP list of `[key, value]` lists, with the 32-byte keys and the values
+    /// trimmed of their leading zeros, as in the storage trie
+    Rlp(Bytes),
+}
+
+impl StorageSnapshot {
+    /// Generates a snapshot of sorted pairs from the slots.
+    pub fn from_slots(slots: impl IntoIterator<Item = (Word, Word)>) -> Self {
+        let mut slots: Vec<_> = slots.into_iter().collect();
+        slots.sort_unstable_by_key(|(key, _)| *key);
+        let mut data = Vec::with_capacity(slots.len() * 64);
+        for (key, value) in slots {
+            data.extend_from_slice(&key.to_be_bytes());
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        Self::Pairs(data.into())
+    }
+
+    /// Returns the slots of the snapshot.
+    pub fn slots(&self) -> Result<Vec<(Word, Word)>, Error> {
+        match self {
+            Self::Pairs(data) => {
+                if data.len() % 64 != 0 {
+                    return Err(Error::InvalidStorageSnapshot(
+                        "pairs aren't 64 bytes long".to_string(),
+                    ));
+                }
+                let slots: Vec<_> = data
+                    .chunks(64)
+                    .map(|pair| {
+                        (
+                            Word::from_big_endian(&pair[..32]),
+                            Word::from_big_endian(&pair[32..]),
+                        )
+                    })
+                    .collect();
+                if slots.windows(2).any(|pairs| pairs[0].0 >= pairs[1].0) {
+                    return Err(Error::InvalidStorageSnapshot(
+                        "pairs aren't sorted by key".to_string(),
+                    ));
+                }
+                Ok(slots)
+            }
+            Self::Rlp(data) => {
+                let rlp = Rlp::new(data);
+                if !rlp.is_list() {
+                    return Err(Error::InvalidStorageSnapshot(
+                        "slots aren't an RLP list".to_string(),
+                    ));
+                }
+                rlp.iter()
+                    .map(|slot| {
+                        let decode = |index| {
+                            slot.val_at::<Vec<u8>>(index).map_err(|error| {
+                                Error::InvalidStorageSnapshot(format!("{:?}", error))
+                            })
+                        };
+                        let (key, value) = (decode(0)?, decode(1)?);
+                        if key.len() != 32 || value.len() > 32 {
+                            return Err(Error::InvalidStorageSnapshot(
+                                "slot key isn't 32 bytes or value is over 32 bytes".to_string(),
+                            ));
+                        }
+                        Ok((Word::from_big_endian(&key), Word::from_big_endian(&value)))
+                    })
+                    .collect()
+            }
+        }
     }
 }
 
@@ -170,6 +285,21 @@ pub struct BlockConstants {
     pub gas_limit: Word,
     /// base fee
     pub base_fee: Word,
+    /// blob gas used by the blob txs of the block
+    pub blob_gas_used: U64,
+    /// excess blob gas, from which the blob base fee is derived
+    pub excess_blob_gas: U64,
+}
+
+/// Read a quantity of a block header unknown to ethers, such as the blob gas
+/// fields of EIP-4844, which defaults to zero when the block has none.
+pub fn header_quantity<TX>(block: &Block<TX>, key: &str) -> Result<U64, Error> {
+    Ok(block
+        .other
+        .get_deserialized(key)
+        .transpose()
+        .map_err(Error::SerdeError)?
+        .unwrap_or_default())
 }
 
 impl<TX> TryFrom<&Block<TX>> for BlockConstants {
@@ -183,6 +313,8 @@ impl<TX> TryFrom<&Block<TX>> for BlockConstants {
             difficulty: block.difficulty,
             gas_limit: block.gas_limit,
             base_fee: block.base_fee_per_gas.ok_or(Error::IncompleteBlock)?,
+            blob_gas_used: header_quantity(block, "blobGasUsed")?,
+            excess_blob_gas: header_quantity(block, "excessBlobGas")?,
         })
     }
 }
@@ -196,6 +328,8 @@ impl BlockConstants {
         difficulty: Word,
         gas_limit: Word,
         base_fee: Word,
+        blob_gas_used: U64,
+        excess_blob_gas: U64,
     ) -> BlockConstants {
         BlockConstants {
             coinbase,
@@ -204,8 +338,15 @@ impl BlockConstants {
             difficulty,
             gas_limit,
             base_fee,
+            blob_gas_used,
+            excess_blob_gas,
         }
     }
+
+    /// Blob base fee of the block, returned by `BLOBBASEFEE`.
+    pub fn blob_base_fee(&self) -> Word {
+        blob_base_fee(self.excess_blob_gas.as_u64())
+    }
 }
 
 /// Definition of all of the constants related to an Ethereum transaction.
@@ -291,7 +432,7 @@ impl From<&crate::Transaction> for Transaction {
             v: tx.v.as_u64(),
             r: tx.r,
             s: tx.s,
-            rlp_bytes: tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(tx),
             rlp_unsigned_bytes: get_rlp_unsigned(tx),
             hash: tx.hash,
         }
@@ -316,38 +457,15 @@ impl From<&Transaction> for TransactionRequest {
 impl Transaction {
     /// Return the SignData associated with this Transaction.
     pub fn sign_data(&self) -> Result<SignData, Error> {
-        let sig_r_le = self.r.to_le_bytes();
-        let sig_s_le = self.s.to_le_bytes();
-        let sig_r = ct_option_ok_or(
-            secp256k1::Fq::from_repr(sig_r_le),
-            Error::Signature(libsecp256k1::Error::InvalidSignature),
-        )?;
-        let sig_s = ct_option_ok_or(
-            secp256k1::Fq::from_repr(sig_s_le),
-            Error::Signature(libsecp256k1::Error::InvalidSignature),
-        )?;
-        let msg = self.rlp_unsigned_bytes.clone().into();
+        let msg: Bytes = self.rlp_unsigned_bytes.clone().into();
         let msg_hash: [u8; 32] = Keccak256::digest(&msg)
             .as_slice()
             .to_vec()
             .try_into()
             .expect("hash length isn't 32 bytes");
         let v = self.tx_type.get_recovery_id(self.v);
-        let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
-        // msg_hash = msg_hash % q
-        let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
-        let msg_hash = msg_hash.mod_floor(&*SECP256K1_Q);
-        let msg_hash_le = biguint_to_32bytes_le(msg_hash);
-        let msg_hash = ct_option_ok_or(
-            secp256k1::Fq::from_repr(msg_hash_le),
-            libsecp256k1::Error::InvalidMessage,
-        )?;
-        Ok(SignData {
-            signature: (sig_r, sig_s),
-            pk,
-            msg,
-            msg_hash,
-        })
+        let sign_data = SignData::from_msg_hash(v, self.r, self.s, msg_hash)?;
+        Ok(SignData { msg, ..sign_data })
     }
 }
 
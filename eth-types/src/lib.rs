@@ -18,6 +18,8 @@ pub mod macros;
 pub mod error;
 #[macro_use]
 pub mod bytecode;
+#[cfg(feature = "prague")]
+pub mod eip7702;
 pub mod evm_types;
 pub mod geth_types;
 pub mod sign_types;
@@ -261,7 +263,7 @@ impl<F: Field> ToScalar<F> for usize {
 }
 
 /// Struct used to define the storage proof
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct StorageProof {
     /// Storage key
     pub key: U256,
@@ -272,7 +274,7 @@ pub struct StorageProof {
 }
 
 /// Struct used to define the result of `eth_getProof` call
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EIP1186ProofResponse {
     /// Account address
@@ -440,6 +442,96 @@ pub struct GethExecTrace {
     /// Vector of geth execution steps of the trace.
     #[serde(rename = "structLogs")]
     pub struct_logs: Vec<GethExecStep>,
+    /// How geth validated the transaction before running it. Only given by
+    /// the tracers of geth-utils.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<GethTxValidation>,
+}
+
+/// The validation of a transaction by geth before running it.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GethTxValidation {
+    /// Intrinsic gas charged before running the transaction
+    pub intrinsic_gas: u64,
+    /// Gas price paid by the transaction
+    pub effective_gas_price: Word,
+    /// Error of the checks of the nonce, balance and gas limit of the
+    /// transaction if it failed them
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A call frame returned by geth's native `callTracer`, with its sub calls.
+/// Corresponds to `callFrame` in `go-ethereum/eth/tracers/native/call.go`.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTrace {
+    /// Opcode of the call: CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE,
+    /// CREATE2 or SELFDESTRUCT.
+    #[serde(rename = "type")]
+    pub call_type: OpcodeId,
+    /// Caller address
+    pub from: Address,
+    /// Callee address, or the created address for CREATE/CREATE2
+    #[serde(default)]
+    pub to: Option<Address>,
+    /// Transferred value, if the call type has one
+    #[serde(default)]
+    pub value: Option<Word>,
+    /// Gas given to the call
+    pub gas: U64,
+    /// Gas used by the call
+    pub gas_used: U64,
+    /// Call data, or init code for creations
+    pub input: Bytes,
+    /// Return data, or deployed code for creations
+    #[serde(default)]
+    pub output: Bytes,
+    /// Error of the call if it failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Decoded revert reason if the call reverted with one
+    #[serde(default)]
+    pub revert_reason: Option<String>,
+    /// Depth of the call, 1 for the call of the transaction. Only given by
+    /// the struct logger with calls of geth-utils.
+    #[serde(default)]
+    pub depth: Option<usize>,
+    /// Refund counter when the call returned. Only given by the struct logger
+    /// with calls of geth-utils.
+    #[serde(default)]
+    pub refund: Option<U64>,
+    /// Sub calls made by this call, in execution order
+    #[serde(default)]
+    pub calls: Vec<CallTrace>,
+}
+
+impl CallTrace {
+    /// Returns true if the call did not fail.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Returns this call and all its sub calls, in execution (depth-first)
+    /// order.
+    pub fn flatten(&self) -> Vec<&CallTrace> {
+        let mut calls = vec![self];
+        for call in &self.calls {
+            calls.extend(call.flatten());
+        }
+        calls
+    }
+}
+
+/// The struct logs of a transaction with the call frames of the same run,
+/// traced by the struct logger with calls of geth-utils.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct GethExecTraceWithCalls {
+    /// Struct logs of the transaction
+    pub trace: GethExecTrace,
+    /// Call of the transaction, with its sub calls
+    pub call: CallTrace,
 }
 
 #[macro_export]
@@ -612,9 +704,115 @@ mod tests {
                         ]),
                     }
                 ],
+                validation: None,
             }
         );
     }
+    #[test]
+    fn deserialize_call_trace() {
+        let trace_json = r#"
+  {
+    "from": "0x00000000000000000000000000000000000000fe",
+    "gas": "0x1d4c0",
+    "gasUsed": "0x6a9c",
+    "to": "0x00000000000000000000000000000000000000ff",
+    "input": "0x",
+    "calls": [
+      {
+        "from": "0x00000000000000000000000000000000000000ff",
+        "gas": "0x12ef0",
+        "gasUsed": "0x2a",
+        "to": "0x0000000000000000000000000000000000000004",
+        "input": "0x1234",
+        "output": "0x1234",
+        "type": "STATICCALL"
+      },
+      {
+        "from": "0x00000000000000000000000000000000000000ff",
+        "gas": "0x12000",
+        "gasUsed": "0x12000",
+        "to": "0x0000000000000000000000000000000000000100",
+        "input": "0x",
+        "error": "out of gas",
+        "value": "0x1",
+        "type": "CALL"
+      }
+    ],
+    "value": "0x0",
+    "type": "CALL"
+  }
+        "#;
+        let trace: CallTrace =
+            serde_json::from_str(trace_json).expect("json-deserialize CallTrace");
+        assert_eq!(trace.call_type, OpcodeId::CALL);
+        assert_eq!(trace.gas_used, U64::from(0x6a9c));
+        assert_eq!(trace.value, Some(Word::zero()));
+        assert!(trace.is_success());
+
+        let calls = trace.flatten();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[1].call_type, OpcodeId::STATICCALL);
+        assert_eq!(calls[1].value, None);
+        assert_eq!(calls[1].output, Bytes::from(vec![0x12, 0x34]));
+        assert_eq!(
+            calls[2].to,
+            Some(address!("0x0000000000000000000000000000000000000100"))
+        );
+        assert!(!calls[2].is_success());
+        assert_eq!(calls[2].depth, None);
+    }
+
+    #[test]
+    fn deserialize_geth_exec_trace_with_calls() {
+        let trace_json = r#"
+  {
+    "trace": {
+      "gas": 21069,
+      "failed": false,
+      "returnValue": "",
+      "structLogs": [
+        {
+          "pc": 0,
+          "op": "STOP",
+          "gas": 79000,
+          "gasCost": 0,
+          "depth": 1,
+          "stack": []
+        }
+      ],
+      "validation": {
+        "intrinsicGas": 21000,
+        "effectiveGasPrice": "0x77359400"
+      }
+    },
+    "call": {
+      "type": "CALL",
+      "from": "0x00000000000000000000000000000000000000fe",
+      "to": "0x00000000000000000000000000000000000000ff",
+      "value": "0x0",
+      "gas": "0x13498",
+      "gasUsed": "0x0",
+      "input": "0x",
+      "depth": 1,
+      "refund": "0x12c0"
+    }
+  }
+        "#;
+        let trace: GethExecTraceWithCalls =
+            serde_json::from_str(trace_json).expect("json-deserialize GethExecTraceWithCalls");
+        assert_eq!(trace.trace.struct_logs.len(), 1);
+        assert_eq!(
+            trace.trace.validation,
+            Some(GethTxValidation {
+                intrinsic_gas: 21000,
+                effective_gas_price: Word::from(2_000_000_000u64),
+                error: None,
+            })
+        );
+        assert_eq!(trace.call.depth, Some(1));
+        assert_eq!(trace.call.refund, Some(U64::from(4800)));
+        assert!(trace.call.calls.is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 //! secp256k1 signature types and helper functions.
 
-use crate::{ToBigEndian, Word};
+use crate::{ToBigEndian, ToLittleEndian, Word};
 use ethers_core::types::Bytes;
 use halo2_proofs::{
     arithmetic::{CurveAffine, FieldExt},
@@ -57,6 +57,41 @@ pub struct SignData {
     pub msg_hash: secp256k1::Fq,
 }
 
+impl SignData {
+    /// Recover the public key of the signature `(v, r, s)` over `msg_hash` and
+    /// build its `SignData`.  `v` is the recovery id.  The message is left
+    /// empty, which is the case of the ECRecover precompile that only gets
+    /// the hash.
+    pub fn from_msg_hash(
+        v: u8,
+        r: Word,
+        s: Word,
+        msg_hash: [u8; 32],
+    ) -> Result<Self, libsecp256k1::Error> {
+        let sig_r = ct_option_ok_or(
+            secp256k1::Fq::from_repr(r.to_le_bytes()),
+            libsecp256k1::Error::InvalidSignature,
+        )?;
+        let sig_s = ct_option_ok_or(
+            secp256k1::Fq::from_repr(s.to_le_bytes()),
+            libsecp256k1::Error::InvalidSignature,
+        )?;
+        let pk = recover_pk(v, &r, &s, &msg_hash)?;
+        // msg_hash = msg_hash % q
+        let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice()) % &*SECP256K1_Q;
+        let msg_hash = ct_option_ok_or(
+            secp256k1::Fq::from_repr(biguint_to_32bytes_le(msg_hash)),
+            libsecp256k1::Error::InvalidMessage,
+        )?;
+        Ok(Self {
+            signature: (sig_r, sig_s),
+            pk,
+            msg: Bytes::default(),
+            msg_hash,
+        })
+    }
+}
+
 lazy_static! {
     static ref SIGN_DATA_DEFAULT: SignData = {
         let generator = Secp256k1Affine::generator();
@@ -0,0 +1,217 @@
+//! Async versions of the tracing functions, for services running
+//! witness generation on a tokio runtime.
+//!
+//! A trace can take seconds, and the call into geth-utils blocks its thread,
+//! so each trace runs on tokio's blocking thread pool. A Go call can't be
+//! interrupted: when a trace is cancelled or times out, the future returns an
+//! error right away, and the result of geth is dropped once it returns.
+//! Dropping the future behaves the same.
+
+use crate::TraceConfig;
+use eth_types::{
+    error::{TracingError, TracingErrorCode},
+    CallTrace, Error, GethExecTrace, GethExecTraceWithCalls,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{sync::Notify, task};
+
+/// Token cancelling the traces it's given to. Clones share the cancellation.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<CancellationState>);
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Generates a token that isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the traces given this token, including the ones not started
+    /// yet.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    /// Returns whether the token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    async fn cancelled(&self) {
+        loop {
+            // Created before checking the flag, so that a cancel in between
+            // notifies it.
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Options of an async trace.
+#[derive(Clone, Debug, Default)]
+pub struct TraceOptions {
+    /// Time after which the trace fails, if given.
+    pub timeout: Option<Duration>,
+    /// Token cancelling the trace, if given.
+    pub cancellation: Option<CancellationToken>,
+}
+
+/// Runs `f` on the blocking thread pool, failing early if the trace is
+/// cancelled or times out.
+async fn run_blocking<T: Send + 'static>(
+    options: TraceOptions,
+    f: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    let cancellation = options.cancellation.unwrap_or_default();
+    if cancellation.is_cancelled() {
+        return Err(Error::TracingError(TracingError::new(
+            TracingErrorCode::Cancelled,
+            "trace cancelled",
+        )));
+    }
+
+    let handle = task::spawn_blocking(f);
+    let result = async {
+        handle.await.map_err(|error| {
+            Error::TracingError(TracingError::new(
+                TracingErrorCode::Internal,
+                format!("trace task failed: {}", error),
+            ))
+        })?
+    };
+    let result = async {
+        match options.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, result).await.map_err(|_| {
+                Error::TracingError(TracingError::new(
+                    TracingErrorCode::TimedOut,
+                    format!("trace timed out after {:?}", timeout),
+                ))
+            })?,
+            None => result.await,
+        }
+    };
+
+    tokio::select! {
+        result = result => result,
+        _ = cancellation.cancelled() => {
+            Err(Error::TracingError(TracingError::new(
+            TracingErrorCode::Cancelled,
+            "trace cancelled",
+        )))
+        }
+    }
+}
+
+/// Async version of [`crate::trace`].
+pub async fn trace(
+    config: TraceConfig,
+    options: TraceOptions,
+) -> Result<Vec<GethExecTrace>, Error> {
+    run_blocking(options, move || crate::trace(&config)).await
+}
+
+/// Async version of [`crate::trace_calls`].
+pub async fn trace_calls(
+    config: TraceConfig,
+    options: TraceOptions,
+) -> Result<Vec<CallTrace>, Error> {
+    run_blocking(options, move || crate::trace_calls(&config)).await
+}
+
+/// Async version of [`crate::trace_with_calls`].
+pub async fn trace_with_calls(
+    config: TraceConfig,
+    options: TraceOptions,
+) -> Result<Vec<GethExecTraceWithCalls>, Error> {
+    run_blocking(options, move || crate::trace_with_calls(&config)).await
+}
+
+/// Async version of [`crate::trace_raw`].
+pub async fn trace_raw(
+    config: TraceConfig,
+    options: TraceOptions,
+) -> Result<Vec<serde_json::Value>, Error> {
+    run_blocking(options, move || crate::trace_raw(&config)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TraceConfigBuilder;
+    use eth_types::{
+        geth_types::{Account, Transaction},
+        Address, Word,
+    };
+
+    fn config() -> TraceConfig {
+        let from = Address::from_low_u64_be(0xfe);
+        TraceConfigBuilder::new()
+            .account(Account {
+                address: from,
+                balance: Word::from(10u64).pow(18.into()),
+                ..Default::default()
+            })
+            .transaction(Transaction {
+                from,
+                to: Some(Address::from_low_u64_be(0xff)),
+                gas_limit: Word::from(21000),
+                ..Default::default()
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn trace_on_blocking_pool() {
+        let traces = trace(config(), TraceOptions::default()).await.unwrap();
+        assert_eq!(traces.len(), 1);
+        assert!(!traces[0].failed);
+    }
+
+    #[tokio::test]
+    async fn cancelled_trace() {
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let options = TraceOptions {
+            cancellation: Some(cancellation),
+            ..Default::default()
+        };
+        assert!(matches!(
+            trace(config(), options).await,
+            Err(Error::TracingError(TracingError {
+                code: TracingErrorCode::Cancelled,
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn timed_out_trace() {
+        let options = TraceOptions {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert!(matches!(
+            trace(config(), options).await,
+            Err(Error::TracingError(TracingError {
+                code: TracingErrorCode::TimedOut,
+                ..
+            }))
+        ));
+    }
+}
@@ -0,0 +1,422 @@
+//! Builder of a [`TraceConfig`], which fills in the block constants and history
+//! hashes a test usually doesn't care about, and checks the config for the
+//! mistakes that geth-utils would only report with an opaque error, or a
+//! panic.
+
+use crate::{ChainConfig, LoggerConfig, TraceConfig, Tracer};
+use eth_types::{
+    evm_types::{gas_utils::tx_data_gas_cost, GasCost},
+    geth_types::{Account, BlockConstants, Transaction},
+    Address, Word, H160,
+};
+use sha3::{Digest, Keccak256};
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Number of the most recent blocks whose hashes are reachable by
+/// `BLOCKHASH`.
+const HISTORY_HASHES_LEN: u64 = 256;
+/// Gas per word of the init code of a creation tx since Shanghai (EIP-3860),
+/// whether eth-types is built for Shanghai or not.
+const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// Coinbase of the block when the config has none.
+pub const DEFAULT_COINBASE: Address = H160([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xc0, 0x14, 0xba, 0x5e,
+]);
+/// Difficulty of the block when the config has none, which is also the value
+/// of `PREVRANDAO` in geth-utils.
+pub const DEFAULT_DIFFICULTY: u64 = 0x200000;
+
+/// Mistake found in a [`TraceConfig`] by [`TraceConfig::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceConfigError {
+    /// The sender of a tx has no account in the config
+    MissingSender {
+        /// Index of the tx
+        tx_index: usize,
+        /// Sender of the tx
+        from: Address,
+    },
+    /// The gas limit of a tx is below its intrinsic gas
+    GasLimitBelowIntrinsic {
+        /// Index of the tx
+        tx_index: usize,
+        /// Gas limit of the tx
+        gas_limit: Word,
+        /// Intrinsic gas of the tx
+        intrinsic_gas: u64,
+    },
+    /// The txs have more gas than the block gas limit
+    BlockGasLimitExceeded {
+        /// Sum of the gas limits of the txs
+        txs_gas_limit: Word,
+        /// Gas limit of the block
+        block_gas_limit: Word,
+    },
+    /// There are fewer history hashes than the blocks reachable by
+    /// `BLOCKHASH`, which geth-utils would read out of bounds.
+    MissingHistoryHashes {
+        /// Number of hashes reachable by `BLOCKHASH`
+        expected: usize,
+        /// Number of hashes in the config
+        given: usize,
+    },
+}
+
+impl Display for TraceConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl StdError for TraceConfigError {}
+
+/// Returns the hash of the block `number` used by the [`TraceConfigBuilder`]
+/// when no history hashes are given, like geth's runtime environment does:
+/// the keccak of the decimal number.
+pub fn default_history_hash(number: u64) -> Word {
+    Word::from_big_endian(&Keccak256::digest(number.to_string().as_bytes()))
+}
+
+/// Returns the intrinsic gas of a tx, charged before running it.
+pub fn intrinsic_gas(tx: &Transaction, is_shanghai: bool) -> u64 {
+    let is_create = tx.to.is_none();
+    let mut gas = if is_create {
+        GasCost::CREATION_TX.as_u64()
+    } else {
+        GasCost::TX.as_u64()
+    } + tx_data_gas_cost(&tx.call_data);
+    if let Some(access_list) = &tx.access_list {
+        for item in access_list.0.iter() {
+            gas += GasCost::ACCESS_LIST_ADDRESS.as_u64()
+                + item.storage_keys.len() as u64 * GasCost::ACCESS_LIST_STORAGE_KEY.as_u64();
+        }
+    }
+    if is_create && is_shanghai {
+        gas += (tx.call_data.len() as u64 + 31) / 32 * INIT_CODE_WORD_GAS;
+    }
+    gas
+}
+
+impl TraceConfig {
+    /// Returns whether the block of the config is on Shanghai.
+    pub fn is_shanghai(&self) -> bool {
+        self.chain_config
+            .as_ref()
+            .and_then(|chain_config| chain_config.shanghai_time)
+            .map_or(false, |time| {
+                self.block_constants.timestamp >= Word::from(time)
+            })
+    }
+
+    /// Check the config for the mistakes that geth-utils reports with an
+    /// opaque error. The txs failing the checks of their nonce and balance
+    /// are left to geth, as [`TraceConfig::include_invalid_txs`] may trace
+    /// them.
+    pub fn validate(&self) -> Result<(), TraceConfigError> {
+        let number = self.block_constants.number.as_u64();
+        let expected = number.min(HISTORY_HASHES_LEN) as usize;
+        if self.history_hashes.len() < expected {
+            return Err(TraceConfigError::MissingHistoryHashes {
+                expected,
+                given: self.history_hashes.len(),
+            });
+        }
+
+        let is_shanghai = self.is_shanghai();
+        let mut txs_gas_limit = Word::zero();
+        for (tx_index, tx) in self.transactions.iter().enumerate() {
+            if !self.accounts.contains_key(&tx.from) {
+                return Err(TraceConfigError::MissingSender {
+                    tx_index,
+                    from: tx.from,
+                });
+            }
+            let intrinsic_gas = intrinsic_gas(tx, is_shanghai);
+            if tx.gas_limit < Word::from(intrinsic_gas) {
+                return Err(TraceConfigError::GasLimitBelowIntrinsic {
+                    tx_index,
+                    gas_limit: tx.gas_limit,
+                    intrinsic_gas,
+                });
+            }
+            txs_gas_limit = txs_gas_limit.saturating_add(tx.gas_limit);
+        }
+        if txs_gas_limit > self.block_constants.gas_limit {
+            return Err(TraceConfigError::BlockGasLimitExceeded {
+                txs_gas_limit,
+                block_gas_limit: self.block_constants.gas_limit,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder of a [`TraceConfig`], validated when built.
+///
+/// ```
+/// use eth_types::{address, geth_types::{Account, Transaction}, Word};
+/// use external_tracer::TraceConfigBuilder;
+///
+/// let from = address!("0x00000000000000000000000000000000000000fe");
+/// let config = TraceConfigBuilder::new()
+///     .account(Account {
+///         address: from,
+///         balance: Word::from(10u64).pow(18.into()),
+///         ..Default::default()
+///     })
+///     .transaction(Transaction {
+///         from,
+///         to: Some(address!("0x00000000000000000000000000000000000000ff")),
+///         gas_limit: Word::from(21000),
+///         ..Default::default()
+///     })
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.block_constants.gas_limit, Word::from(21000));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TraceConfigBuilder {
+    config: TraceConfig,
+}
+
+impl TraceConfigBuilder {
+    /// Generates a builder of a config without accounts and txs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the chain id.
+    pub fn chain_id(mut self, chain_id: Word) -> Self {
+        self.config.chain_id = chain_id;
+        self
+    }
+
+    /// Sets the history hashes, the latest one last. By default, the hash of
+    /// each block is [`default_history_hash`].
+    pub fn history_hashes(mut self, history_hashes: Vec<Word>) -> Self {
+        self.config.history_hashes = history_hashes;
+        self
+    }
+
+    /// Sets the block constants. A zero coinbase or difficulty is replaced by
+    /// [`DEFAULT_COINBASE`] or [`DEFAULT_DIFFICULTY`], and a zero gas limit
+    /// by the sum of the gas limits of the txs.
+    pub fn block_constants(mut self, block_constants: BlockConstants) -> Self {
+        self.config.block_constants = block_constants;
+        self
+    }
+
+    /// Adds an account, replacing the account at the same address.
+    pub fn account(mut self, account: Account) -> Self {
+        self.config.accounts.insert(account.address, account);
+        self
+    }
+
+    /// Adds the accounts, replacing the accounts at the same addresses.
+    pub fn accounts(self, accounts: impl IntoIterator<Item = Account>) -> Self {
+        accounts.into_iter().fold(self, Self::account)
+    }
+
+    /// Adds a tx after the txs already added.
+    pub fn transaction(mut self, transaction: Transaction) -> Self {
+        self.config.transactions.push(transaction);
+        self
+    }
+
+    /// Adds the txs after the txs already added.
+    pub fn transactions(mut self, transactions: impl IntoIterator<Item = Transaction>) -> Self {
+        self.config.transactions.extend(transactions);
+        self
+    }
+
+    /// Sets the config of the struct logger.
+    pub fn logger_config(mut self, logger_config: LoggerConfig) -> Self {
+        self.config.logger_config = logger_config;
+        self
+    }
+
+    /// Sets the chain config.
+    pub fn chain_config(mut self, chain_config: ChainConfig) -> Self {
+        self.config.chain_config = Some(chain_config);
+        self
+    }
+
+    /// Sets the tracer and its config.
+    pub fn tracer(mut self, tracer: Tracer, tracer_config: Option<serde_json::Value>) -> Self {
+        self.config.tracer = tracer;
+        self.config.tracer_config = tracer_config;
+        self
+    }
+
+    /// Sets whether the txs failing validation are traced as failed txs.
+    pub fn include_invalid_txs(mut self, include_invalid_txs: bool) -> Self {
+        self.config.include_invalid_txs = include_invalid_txs;
+        self
+    }
+
+    /// Sets whether the zero priced txs of the system sender are gas-free.
+    pub fn gas_free_txs(mut self, gas_free_txs: bool) -> Self {
+        self.config.gas_free_txs = gas_free_txs;
+        self
+    }
+
+    /// Fills in the defaults and returns the config if it is valid.
+    pub fn build(self) -> Result<TraceConfig, TraceConfigError> {
+        let mut config = self.config;
+
+        let block_constants = &mut config.block_constants;
+        if block_constants.coinbase.is_zero() {
+            block_constants.coinbase = DEFAULT_COINBASE;
+        }
+        if block_constants.difficulty.is_zero() {
+            block_constants.difficulty = DEFAULT_DIFFICULTY.into();
+        }
+        if block_constants.gas_limit.is_zero() {
+            block_constants.gas_limit = config
+                .transactions
+                .iter()
+                .fold(Word::zero(), |gas, tx| gas.saturating_add(tx.gas_limit));
+        }
+        if config.history_hashes.is_empty() {
+            let number = block_constants.number.as_u64();
+            config.history_hashes = (number.saturating_sub(HISTORY_HASHES_LEN)..number)
+                .map(default_history_hash)
+                .collect();
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{AccessList, Bytes, U64};
+
+    fn sender() -> Address {
+        Address::from_low_u64_be(0xfe)
+    }
+
+    fn receiver() -> Address {
+        Address::from_low_u64_be(0xff)
+    }
+
+    fn builder() -> TraceConfigBuilder {
+        TraceConfigBuilder::new().account(Account {
+            address: sender(),
+            balance: Word::from(10u64).pow(18.into()),
+            ..Default::default()
+        })
+    }
+
+    fn tx(to: Option<Address>, call_data: Vec<u8>, gas_limit: u64) -> Transaction {
+        Transaction {
+            from: sender(),
+            to,
+            call_data: Bytes::from(call_data),
+            gas_limit: gas_limit.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn build_fills_in_defaults() {
+        let config = builder()
+            .block_constants(BlockConstants {
+                number: U64::from(300),
+                ..Default::default()
+            })
+            .transactions([tx(Some(receiver()), vec![], 21000), tx(None, vec![], 53000)])
+            .build()
+            .unwrap();
+
+        assert_eq!(config.block_constants.coinbase, DEFAULT_COINBASE);
+        assert_eq!(
+            config.block_constants.difficulty,
+            Word::from(DEFAULT_DIFFICULTY)
+        );
+        assert_eq!(config.block_constants.gas_limit, Word::from(74000));
+        assert_eq!(config.history_hashes.len(), 256);
+        assert_eq!(config.history_hashes[0], default_history_hash(44));
+        assert_eq!(config.history_hashes[255], default_history_hash(299));
+    }
+
+    #[test]
+    fn validate_catches_mistakes() {
+        assert_eq!(
+            TraceConfigBuilder::new()
+                .transaction(tx(Some(receiver()), vec![], 21000))
+                .build()
+                .unwrap_err(),
+            TraceConfigError::MissingSender {
+                tx_index: 0,
+                from: sender()
+            }
+        );
+        // 21000 + 4 + 16
+        assert_eq!(
+            builder()
+                .transaction(tx(Some(receiver()), vec![0, 1], 21019))
+                .build()
+                .unwrap_err(),
+            TraceConfigError::GasLimitBelowIntrinsic {
+                tx_index: 0,
+                gas_limit: Word::from(21019),
+                intrinsic_gas: 21020,
+            }
+        );
+        assert_eq!(
+            builder()
+                .block_constants(BlockConstants {
+                    gas_limit: Word::from(30000),
+                    ..Default::default()
+                })
+                .transactions([
+                    tx(Some(receiver()), vec![], 21000),
+                    tx(Some(receiver()), vec![], 21000)
+                ])
+                .build()
+                .unwrap_err(),
+            TraceConfigError::BlockGasLimitExceeded {
+                txs_gas_limit: Word::from(42000),
+                block_gas_limit: Word::from(30000),
+            }
+        );
+        assert_eq!(
+            builder()
+                .block_constants(BlockConstants {
+                    number: U64::from(10),
+                    ..Default::default()
+                })
+                .history_hashes(vec![Word::one(); 3])
+                .build()
+                .unwrap_err(),
+            TraceConfigError::MissingHistoryHashes {
+                expected: 10,
+                given: 3
+            }
+        );
+    }
+
+    #[test]
+    fn intrinsic_gas_of_txs() {
+        let mut access_list_tx = tx(Some(receiver()), vec![], 0);
+        access_list_tx.access_list = Some(AccessList(vec![Default::default(); 2]));
+        access_list_tx.access_list.as_mut().unwrap().0[0].storage_keys = vec![Default::default()];
+        assert_eq!(
+            intrinsic_gas(&access_list_tx, false),
+            21000 + 2 * 2400 + 1900
+        );
+
+        // 33 bytes of init code are 2 words
+        let create_tx = tx(None, vec![1; 33], 0);
+        assert_eq!(intrinsic_gas(&create_tx, false), 53000 + 33 * 16);
+        assert_eq!(intrinsic_gas(&create_tx, true), 53000 + 33 * 16 + 2 * 2);
+    }
+}
@@ -0,0 +1,175 @@
+//! Loading of the accounts of a state dumped by `geth dump`, to trace against
+//! the contracts of a real chain.
+//!
+//! Both outputs of `geth dump` are supported: the JSON object of the whole
+//! state, and the iterative one (`--iterative`) with a JSON object per line.
+//! The storage of the accounts is loaded as a [`StorageSnapshot`], so that
+//! contracts with many slots stay cheap to pass to the tracer. Geth only
+//! knows the keys of the slots and the addresses of the accounts if it
+//! recorded their preimages (`--cache.preimages`): the accounts without an
+//! address are rejected.
+
+use eth_types::{
+    geth_types::{Account, StorageSnapshot},
+    Address, Bytes, Word, H256,
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs, io,
+    path::Path,
+};
+
+/// Error while loading a geth dump
+#[derive(Debug)]
+pub enum GethDumpError {
+    /// The dump can't be read
+    Io(io::Error),
+    /// The dump isn't the JSON of geth
+    Json(serde_json::Error),
+    /// An account of the dump can't be loaded
+    InvalidAccount(String),
+}
+
+impl Display for GethDumpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl StdError for GethDumpError {}
+
+/// Corresponds to `DumpAccount` in `go-ethereum/core/state/dump.go`
+#[derive(Deserialize)]
+struct DumpAccount {
+    /// Decimal balance
+    balance: String,
+    nonce: u64,
+    #[serde(default)]
+    code: Bytes,
+    /// Values are hex without prefix, trimmed of their leading zeros
+    #[serde(default)]
+    storage: HashMap<H256, String>,
+    /// Only given by the iterative dump
+    #[serde(default)]
+    address: Option<Address>,
+}
+
+/// Corresponds to `Dump` in `go-ethereum/core/state/dump.go`
+#[derive(Deserialize)]
+struct Dump {
+    accounts: HashMap<String, DumpAccount>,
+}
+
+impl DumpAccount {
+    fn into_account(self, address: Address) -> Result<Account, GethDumpError> {
+        let invalid = |what: &str| {
+            GethDumpError::InvalidAccount(format!("{} of account {:?}", what, address))
+        };
+        let balance = Word::from_dec_str(&self.balance).map_err(|_| invalid("balance"))?;
+        let slots = self
+            .storage
+            .into_iter()
+            .map(|(key, value)| {
+                let value = Word::from_str_radix(&value, 16).map_err(|_| invalid("storage"))?;
+                Ok((Word::from_big_endian(key.as_bytes()), value))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Account {
+            address,
+            nonce: self.nonce.into(),
+            balance,
+            code: self.code,
+            storage: HashMap::new(),
+            storage_snapshot: (!slots.is_empty()).then(|| StorageSnapshot::from_slots(slots)),
+        })
+    }
+}
+
+/// Parses the accounts of the output of `geth dump`, whole or iterative.
+pub fn parse_geth_dump(dump: &str) -> Result<Vec<Account>, GethDumpError> {
+    if let Ok(dump) = serde_json::from_str::<Dump>(dump) {
+        return dump
+            .accounts
+            .into_iter()
+            .map(|(address, account)| {
+                let address = address.parse().map_err(|_| {
+                    GethDumpError::InvalidAccount(format!("account without address {}", address))
+                })?;
+                account.into_account(address)
+            })
+            .collect();
+    }
+
+    // The iterative dump starts with a line of the state root.
+    dump.lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let account: DumpAccount = serde_json::from_str(line).map_err(GethDumpError::Json)?;
+            let address = account.address.ok_or_else(|| {
+                GethDumpError::InvalidAccount("account without address".to_string())
+            })?;
+            account.into_account(address)
+        })
+        .collect()
+}
+
+/// Loads the accounts of a file written by `geth dump`, whole or iterative.
+pub fn load_geth_dump(path: impl AsRef<Path>) -> Result<Vec<Account>, GethDumpError> {
+    parse_geth_dump(&fs::read_to_string(path).map_err(GethDumpError::Io)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x000000000000000000000000000000000000cafe";
+
+    fn check(accounts: Vec<Account>) {
+        assert_eq!(accounts.len(), 1);
+        let account = &accounts[0];
+        assert_eq!(account.address, ADDRESS.parse().unwrap());
+        assert_eq!(account.nonce, Word::one());
+        assert_eq!(account.balance, Word::from(1_000_000_000_000_000_000u64));
+        assert_eq!(account.code, Bytes::from(vec![0x60, 0x00]));
+        let mut slots = account.storage_snapshot.as_ref().unwrap().slots().unwrap();
+        slots.sort();
+        assert_eq!(
+            slots,
+            vec![
+                (Word::zero(), Word::from(0x2a)),
+                (Word::one(), Word::from(0x1234)),
+            ]
+        );
+    }
+
+    const ACCOUNT: &str = r#""balance": "1000000000000000000",
+        "nonce": 1,
+        "root": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "codeHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "code": "0x6000",
+        "storage": {
+          "0x0000000000000000000000000000000000000000000000000000000000000000": "2a",
+          "0x0000000000000000000000000000000000000000000000000000000000000001": "1234"
+        }"#;
+
+    #[test]
+    fn parse_whole_dump() {
+        let dump = format!(
+            r#"{{ "root": "0x00", "accounts": {{ "{}": {{ {} }} }} }}"#,
+            ADDRESS, ACCOUNT
+        );
+        check(parse_geth_dump(&dump).unwrap());
+    }
+
+    #[test]
+    fn parse_iterative_dump() {
+        let account = format!(r#"{{ {}, "address": "{}" }}"#, ACCOUNT, ADDRESS).replace('\n', "");
+        let dump = format!("{{\"root\": \"0x00\"}}\n{}\n", account);
+        check(parse_geth_dump(&dump).unwrap());
+    }
+}
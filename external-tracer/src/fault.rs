@@ -0,0 +1,72 @@
+//! Faults injected into traces, so that negative tests can check that the
+//! circuits reject a witness built from a corrupted trace.
+
+use eth_types::{GethExecTrace, Word};
+
+/// Error reported by geth when a step runs out of gas
+const OUT_OF_GAS: &str = "out of gas";
+
+/// What a [`TraceFault`] does to its step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceFaultKind {
+    /// The step fails with out of gas. The following steps of its call are
+    /// dropped, and the tx fails if the step is in the root call.
+    OutOfGas,
+    /// The stack value at `index`, counted from the top, is replaced by
+    /// `value` before the step is executed.
+    StackValue {
+        /// Index of the value from the top of the stack
+        index: usize,
+        /// Value written in the stack
+        value: Word,
+    },
+}
+
+/// A fault injected at one step of a trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceFault {
+    /// Index of the tx in the block
+    pub tx_index: usize,
+    /// Index of the step in the struct logs of the tx
+    pub step_index: usize,
+    /// The fault
+    pub kind: TraceFaultKind,
+}
+
+impl TraceFault {
+    /// Apply the fault to the traces of a block. Panics if the step does not
+    /// exist.
+    pub fn apply(&self, traces: &mut [GethExecTrace]) {
+        let trace = &mut traces[self.tx_index];
+        let step = &mut trace.struct_logs[self.step_index];
+        match &self.kind {
+            TraceFaultKind::OutOfGas => {
+                step.error = Some(OUT_OF_GAS.to_string());
+                let depth = step.depth;
+                let call_end = trace.struct_logs[self.step_index + 1..]
+                    .iter()
+                    .position(|step| step.depth < depth)
+                    .map_or(trace.struct_logs.len(), |offset| {
+                        self.step_index + 1 + offset
+                    });
+                trace.struct_logs.drain(self.step_index + 1..call_end);
+                if depth == 1 {
+                    trace.failed = true;
+                    trace.return_value.clear();
+                }
+            }
+            TraceFaultKind::StackValue { index, value } => {
+                let stack = &mut step.stack.0;
+                let len = stack.len();
+                stack[len - 1 - index] = *value;
+            }
+        }
+    }
+}
+
+/// Apply the faults, in order, to the traces of a block.
+pub fn inject_faults(traces: &mut [GethExecTrace], faults: &[TraceFault]) {
+    for fault in faults {
+        fault.apply(traces);
+    }
+}
@@ -1,11 +1,27 @@
 //! This module generates traces by connecting to an external tracer
 
 use eth_types::{
+    error::{TracingError, TracingErrorCode},
     geth_types::{Account, BlockConstants, Transaction},
-    Address, Error, GethExecTrace, Word,
+    Address, CallTrace, Error, GethExecTrace, GethExecTraceWithCalls, Word,
 };
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::{collections::HashMap, num::NonZeroUsize, thread};
+
+#[cfg(feature = "async")]
+pub mod asynchronous;
+mod builder;
+mod dump;
+mod fault;
+#[cfg(test)]
+mod revm_diff;
+
+pub use builder::{
+    default_history_hash, intrinsic_gas, TraceConfigBuilder, TraceConfigError, DEFAULT_COINBASE,
+    DEFAULT_DIFFICULTY,
+};
+pub use dump::{load_geth_dump, parse_geth_dump, GethDumpError};
+pub use fault::{inject_faults, TraceFault, TraceFaultKind};
 
 /// Configuration structure for `geth_utlis::trace`
 #[derive(Debug, Default, Clone, Serialize)]
@@ -25,6 +41,51 @@ pub struct TraceConfig {
     pub logger_config: LoggerConfig,
     /// chain config
     pub chain_config: Option<ChainConfig>,
+    /// tracer run for each transaction
+    pub tracer: Tracer,
+    /// config passed to the tracer, e.g. `{"onlyTopCall": true}` for the
+    /// call tracer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracer_config: Option<serde_json::Value>,
+    /// trace the txs failing validation as failed txs without steps, instead
+    /// of failing the trace. Only supported by the [`Tracer::StructLogger`].
+    pub include_invalid_txs: bool,
+    /// treat the txs with a zero gas price from the system sender as gas-free
+    /// system txs. geth already traces them without a base fee, so the flag
+    /// is only read by the circuit input builder.
+    #[serde(skip)]
+    pub gas_free_txs: bool,
+}
+
+/// Tracer used by geth to trace the transactions
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Tracer {
+    /// The struct logger, whose output is parsed by [`trace`]
+    #[default]
+    StructLogger,
+    /// The struct logger along with a logger of the call frames of the same
+    /// run, whose output is parsed by [`trace_with_calls`]
+    StructLoggerWithCalls,
+    /// The native call tracer, whose output is parsed by [`trace_calls`]
+    CallTracer,
+    /// The native prestate tracer
+    PrestateTracer,
+    /// A custom tracer, given by its name in geth's tracer directory or by
+    /// the code of a JS tracer. JS tracers are only available if geth-utils
+    /// registers geth's JS tracer package.
+    Custom(String),
+}
+
+impl Serialize for Tracer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::StructLogger => "structLogger",
+            Self::StructLoggerWithCalls => "structLoggerWithCalls",
+            Self::CallTracer => "callTracer",
+            Self::PrestateTracer => "prestateTracer",
+            Self::Custom(tracer) => tracer,
+        })
+    }
 }
 
 /// Configuration structure for `logger.Config`
@@ -67,6 +128,8 @@ impl LoggerConfig {
 pub struct ChainConfig {
     /// Shanghai switch time (nil = no fork, 0 = already on shanghai)
     pub shanghai_time: Option<u64>,
+    /// Prague switch time (nil = no fork, 0 = already on prague)
+    pub prague_time: Option<u64>,
     /// TerminalTotalDifficulty is the amount of total difficulty reached by
     /// the network that triggers the consensus upgrade.
     pub terminal_total_difficulty: Option<u64>,
@@ -81,23 +144,110 @@ impl ChainConfig {
     pub fn shanghai() -> Self {
         Self {
             shanghai_time: Some(0),
+            prague_time: None,
             terminal_total_difficulty: Some(0),
             terminal_total_difficulty_passed: true,
         }
     }
 }
 
-/// Creates a trace for the specified config
-pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
-    // Get the trace
-    let trace_string = geth_utils::trace(&serde_json::to_string(&config).unwrap()).map_err(
-        |error| match error {
-            geth_utils::Error::TracingError(error) => Error::TracingError(error),
-        },
-    )?;
+fn from_geth_utils_error(error: geth_utils::Error) -> Error {
+    let geth_utils::Error::TracingError(error) = error;
+    Error::TracingError(
+        serde_json::from_str(&error)
+            .unwrap_or_else(|_| TracingError::new(TracingErrorCode::Internal, error)),
+    )
+}
+
+fn geth_trace(config: &TraceConfig) -> Result<String, Error> {
+    let _span = tracing::info_span!(
+        "geth_trace",
+        txs = config.transactions.len(),
+        tracer = ?config.tracer
+    )
+    .entered();
+    let trace_string = geth_utils::trace(&serde_json::to_string(&config).unwrap())
+        .map_err(from_geth_utils_error)?;
 
     log::trace!("trace: {}", trace_string);
 
+    Ok(trace_string)
+}
+
+/// Creates a trace for the specified config, which must use the
+/// [`Tracer::StructLogger`]
+pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    // Get the trace
+    let trace_string = geth_trace(config)?;
+
     let trace = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
     Ok(trace)
 }
+
+/// Creates a call trace of each transaction with geth's call tracer, ignoring
+/// the tracer set in the config
+pub fn trace_calls(config: &TraceConfig) -> Result<Vec<CallTrace>, Error> {
+    let config = TraceConfig {
+        tracer: Tracer::CallTracer,
+        ..config.clone()
+    };
+    let trace_string = geth_trace(&config)?;
+
+    serde_json::from_str(&trace_string).map_err(Error::SerdeError)
+}
+
+/// Creates a trace of each transaction with the struct logger and the tree of
+/// its call frames in one run, ignoring the tracer set in the config. Unlike
+/// the frames of [`trace_calls`], these frames give the depth of each call and
+/// the refund counter when it returned.
+pub fn trace_with_calls(config: &TraceConfig) -> Result<Vec<GethExecTraceWithCalls>, Error> {
+    let config = TraceConfig {
+        tracer: Tracer::StructLoggerWithCalls,
+        ..config.clone()
+    };
+    let trace_string = geth_trace(&config)?;
+
+    serde_json::from_str(&trace_string).map_err(Error::SerdeError)
+}
+
+/// Returns the raw output of the tracer set in the config for each
+/// transaction
+pub fn trace_raw(config: &TraceConfig) -> Result<Vec<serde_json::Value>, Error> {
+    let trace_string = geth_trace(config)?;
+
+    serde_json::from_str(&trace_string).map_err(Error::SerdeError)
+}
+
+/// Output of a config of a batch traced by geth-utils
+#[derive(Deserialize)]
+struct BatchResult {
+    #[serde(default)]
+    result: Option<Vec<GethExecTrace>>,
+    #[serde(default)]
+    error: Option<TracingError>,
+}
+
+/// Creates the traces of several independent configs, which must use the
+/// [`Tracer::StructLogger`]. geth-utils runs up to one config per available
+/// core at once. A config failing doesn't fail the others, its error is
+/// returned in its place.
+pub fn trace_batch(
+    configs: &[TraceConfig],
+) -> Result<Vec<Result<Vec<GethExecTrace>, Error>>, Error> {
+    let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let _span = tracing::info_span!("geth_trace_batch", configs = configs.len(), workers).entered();
+    let trace_string = geth_utils::trace_batch(&serde_json::to_string(configs).unwrap(), workers)
+        .map_err(from_geth_utils_error)?;
+
+    let results: Vec<BatchResult> =
+        serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
+    Ok(results
+        .into_iter()
+        .map(|result| match (result.result, result.error) {
+            (Some(traces), None) => Ok(traces),
+            (_, error) => Err(Error::TracingError(error.unwrap_or_else(|| {
+                TracingError::new(TracingErrorCode::Internal, "batch result without output")
+            }))),
+        })
+        .collect())
+}
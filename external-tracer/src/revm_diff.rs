@@ -0,0 +1,590 @@
+//! Differential testing of the external tracer against revm.
+//!
+//! [`diff_with_revm`] runs the transactions of a [`TraceConfig`] both through
+//! geth (with the struct logger, the call tracer and the prestate tracer) and
+//! through revm, and reports every difference in the result, the gas used,
+//! the logs and the post-state of the transactions. The two EVMs are
+//! expected to agree; a divergence usually points to a bug in how the config
+//! is passed to geth-utils or in how its output is parsed.
+//!
+//! Some differences between the two setups are known and are either
+//! normalized or rejected by [`diff_with_revm`]:
+//! - geth-utils runs geth with `NoBaseFee`, so a tx can pay less than the base fee, which revm
+//!   rejects. Configs with such txs are not compared.
+//! - geth-utils sets `PREVRANDAO` to the difficulty of the block. revm reads the difficulty for
+//!   both opcodes, so they agree as long as the difficulty is used as randao.
+//! - revm 2 has no Shanghai spec, configs with a Shanghai chain config are not compared. Cancun is
+//!   supported by neither EVM.
+//! - geth's call tracer (v1.11) does not record where logs were emitted relative to sub calls, so
+//!   logs are compared as a sorted list.
+//! - Scroll builds of geth charge an L1 fee to the sender and disable some opcodes, neither of
+//!   which revm knows about.
+//! - Empty accounts and zero storage slots are dropped on both sides before comparing post-states,
+//!   as EIP-158 makes them equivalent to missing ones.
+
+use crate::{trace, trace_raw, LoggerConfig, TraceConfig, Tracer};
+use eth_types::{Address, Bytes, Error, ToBigEndian, Word, H256};
+use primitive_types::{H160 as RevmAddress, H256 as RevmHash, U256 as RevmWord};
+use revm::{AccountInfo, Bytecode, CreateScheme, InMemoryDB, Return, SpecId, TransactTo, EVM};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Outcome of a transaction, as seen by either EVM
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TxOutcome {
+    success: bool,
+    gas_used: u64,
+    /// Logs of the tx, sorted
+    logs: Vec<LogEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct LogEntry {
+    address: Address,
+    topics: Vec<H256>,
+    data: Vec<u8>,
+}
+
+/// Account of a post-state, without its zero storage slots
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct PostAccount {
+    nonce: u64,
+    balance: Word,
+    code: Vec<u8>,
+    storage: BTreeMap<Word, Word>,
+}
+
+impl PostAccount {
+    fn is_empty(&self) -> bool {
+        self.nonce == 0 && self.balance.is_zero() && self.code.is_empty() && self.storage.is_empty()
+    }
+}
+
+type PostState = BTreeMap<Address, PostAccount>;
+
+/// Outcome of all the transactions of a config
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Outcome {
+    txs: Vec<TxOutcome>,
+    state: PostState,
+}
+
+fn revm_address(address: Address) -> RevmAddress {
+    RevmAddress::from_slice(address.as_bytes())
+}
+
+fn revm_word(word: Word) -> RevmWord {
+    RevmWord::from_big_endian(&word.to_be_bytes())
+}
+
+fn word(word: RevmWord) -> Word {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Word::from_big_endian(&bytes)
+}
+
+/// Panics if the config uses a feature on which geth-utils and revm are known
+/// to diverge, see the module docs.
+fn assert_comparable(config: &TraceConfig) {
+    assert!(
+        config
+            .chain_config
+            .as_ref()
+            .and_then(|chain_config| chain_config.shanghai_time)
+            .is_none(),
+        "revm 2 can't run Shanghai configs"
+    );
+    for (i, tx) in config.transactions.iter().enumerate() {
+        assert!(
+            tx.gas_price >= config.block_constants.base_fee,
+            "tx {i} pays less than the base fee, which only geth-utils accepts"
+        );
+    }
+}
+
+fn run_revm(config: &TraceConfig) -> Outcome {
+    let mut db = InMemoryDB::default();
+    for (address, account) in config.accounts.iter() {
+        let address = revm_address(*address);
+        db.insert_account_info(
+            address,
+            AccountInfo::new(
+                revm_word(account.balance),
+                account.nonce.as_u64(),
+                Bytecode::new_raw(account.code.to_vec().into()),
+            ),
+        );
+        let storage = account.all_storage().expect("valid storage snapshot");
+        for (key, value) in storage {
+            db.insert_account_storage(address, revm_word(key), revm_word(value))
+                .expect("storage of an in-memory account");
+        }
+    }
+    // history_hashes ends with the hash of the parent block
+    let number = config.block_constants.number.as_u64();
+    for (i, hash) in config.history_hashes.iter().rev().enumerate() {
+        db.block_hashes.insert(
+            RevmWord::from(number - 1 - i as u64),
+            RevmHash::from_slice(&hash.to_be_bytes()),
+        );
+    }
+
+    let mut evm = EVM::new();
+    evm.database(db);
+    evm.env.cfg.chain_id = revm_word(config.chain_id);
+    evm.env.cfg.spec_id = SpecId::MERGE;
+    let block = &config.block_constants;
+    evm.env.block.number = RevmWord::from(number);
+    evm.env.block.coinbase = revm_address(block.coinbase);
+    evm.env.block.timestamp = revm_word(block.timestamp);
+    evm.env.block.difficulty = revm_word(block.difficulty);
+    evm.env.block.basefee = revm_word(block.base_fee);
+    evm.env.block.gas_limit = revm_word(block.gas_limit);
+
+    let mut txs = Vec::with_capacity(config.transactions.len());
+    for tx in config.transactions.iter() {
+        evm.env.tx.caller = revm_address(tx.from);
+        evm.env.tx.transact_to = match tx.to {
+            Some(to) => TransactTo::Call(revm_address(to)),
+            None => TransactTo::Create(CreateScheme::Create),
+        };
+        evm.env.tx.nonce = Some(tx.nonce.as_u64());
+        evm.env.tx.value = revm_word(tx.value);
+        evm.env.tx.data = tx.call_data.to_vec().into();
+        evm.env.tx.gas_limit = tx.gas_limit.as_u64();
+        // geth-utils treats every tx as a legacy one paying the gas price
+        evm.env.tx.gas_price = revm_word(tx.gas_price);
+        evm.env.tx.gas_priority_fee = None;
+        evm.env.tx.access_list = tx
+            .access_list
+            .iter()
+            .flat_map(|access_list| access_list.0.iter())
+            .map(|item| {
+                (
+                    revm_address(item.address),
+                    item.storage_keys
+                        .iter()
+                        .map(|key| RevmWord::from_big_endian(key.as_bytes()))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let result = evm.transact_commit();
+        let mut logs: Vec<_> = result
+            .logs
+            .iter()
+            .map(|log| LogEntry {
+                address: Address::from_slice(log.address.as_bytes()),
+                topics: log
+                    .topics
+                    .iter()
+                    .map(|topic| H256::from_slice(topic.as_bytes()))
+                    .collect(),
+                data: log.data.to_vec(),
+            })
+            .collect();
+        logs.sort();
+        txs.push(TxOutcome {
+            success: matches!(
+                result.exit_reason,
+                Return::Continue | Return::Stop | Return::Return | Return::SelfDestruct
+            ),
+            gas_used: result.gas_used,
+            logs,
+        });
+    }
+
+    let db = evm.db.expect("database set above");
+    let state = db
+        .accounts
+        .iter()
+        .map(|(address, account)| {
+            let code = account
+                .info
+                .code
+                .as_ref()
+                .map(|code| code.bytes()[..code.len()].to_vec())
+                .unwrap_or_default();
+            let storage = account
+                .storage
+                .iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(key, value)| (word(*key), word(*value)))
+                .collect();
+            (
+                Address::from_slice(address.as_bytes()),
+                PostAccount {
+                    nonce: account.info.nonce,
+                    balance: word(account.info.balance),
+                    code,
+                    storage,
+                },
+            )
+        })
+        .filter(|(_, account)| !account.is_empty())
+        .collect();
+
+    Outcome { txs, state }
+}
+
+/// Call frame of geth's call tracer, with only the fields needed to collect
+/// its logs
+#[derive(Deserialize)]
+struct CallFrame {
+    #[serde(default)]
+    logs: Vec<CallLog>,
+    #[serde(default)]
+    calls: Vec<CallFrame>,
+}
+
+#[derive(Deserialize)]
+struct CallLog {
+    address: Address,
+    topics: Vec<H256>,
+    data: Bytes,
+}
+
+impl CallFrame {
+    fn collect_logs(&self, logs: &mut Vec<LogEntry>) {
+        logs.extend(self.logs.iter().map(|log| LogEntry {
+            address: log.address,
+            topics: log.topics.clone(),
+            data: log.data.to_vec(),
+        }));
+        for call in self.calls.iter() {
+            call.collect_logs(logs);
+        }
+    }
+}
+
+/// Output of geth's prestate tracer in diff mode for one tx
+#[derive(Deserialize)]
+struct StateDiff {
+    pre: HashMap<Address, PrestateAccount>,
+    post: HashMap<Address, PrestateAccount>,
+}
+
+/// Account of the prestate tracer, whose fields are only set if they changed
+/// in the post-state of a diff
+#[derive(Deserialize)]
+struct PrestateAccount {
+    balance: Option<Word>,
+    nonce: Option<u64>,
+    code: Option<Bytes>,
+    #[serde(default)]
+    storage: HashMap<H256, H256>,
+}
+
+/// Apply the diff of a tx to `state`. Accounts only in `pre` were deleted,
+/// and slots only in `pre` of an account in `post` were cleared.
+fn apply_diff(state: &mut PostState, diff: StateDiff) {
+    for (address, pre) in diff.pre.iter() {
+        match diff.post.get(address) {
+            None => {
+                state.remove(address);
+            }
+            Some(post) => {
+                let account = state.entry(*address).or_default();
+                for key in pre.storage.keys() {
+                    if !post.storage.contains_key(key) {
+                        account
+                            .storage
+                            .remove(&Word::from_big_endian(key.as_bytes()));
+                    }
+                }
+            }
+        }
+    }
+    for (address, post) in diff.post {
+        let account = state.entry(address).or_default();
+        if let Some(balance) = post.balance {
+            account.balance = balance;
+        }
+        if let Some(nonce) = post.nonce {
+            account.nonce = nonce;
+        }
+        if let Some(code) = post.code {
+            account.code = code.to_vec();
+        }
+        for (key, value) in post.storage {
+            let key = Word::from_big_endian(key.as_bytes());
+            let value = Word::from_big_endian(value.as_bytes());
+            if value.is_zero() {
+                account.storage.remove(&key);
+            } else {
+                account.storage.insert(key, value);
+            }
+        }
+    }
+}
+
+fn run_geth(config: &TraceConfig) -> Result<Outcome, Error> {
+    let config = TraceConfig {
+        logger_config: LoggerConfig {
+            disable_stack: true,
+            disable_storage: true,
+            enable_return_data: false,
+            ..LoggerConfig::default()
+        },
+        tracer: Tracer::StructLogger,
+        tracer_config: None,
+        ..config.clone()
+    };
+    let traces = trace(&config)?;
+    let calls = trace_raw(&TraceConfig {
+        tracer: Tracer::CallTracer,
+        tracer_config: Some(serde_json::json!({ "withLog": true })),
+        ..config.clone()
+    })?;
+    let diffs = trace_raw(&TraceConfig {
+        tracer: Tracer::PrestateTracer,
+        tracer_config: Some(serde_json::json!({ "diffMode": true })),
+        ..config.clone()
+    })?;
+
+    let mut txs = Vec::with_capacity(traces.len());
+    for (trace, call) in traces.iter().zip(calls) {
+        let call: CallFrame = serde_json::from_value(call).map_err(Error::SerdeError)?;
+        let mut logs = Vec::new();
+        call.collect_logs(&mut logs);
+        logs.sort();
+        txs.push(TxOutcome {
+            success: !trace.failed,
+            gas_used: trace.gas.0,
+            logs,
+        });
+    }
+
+    let mut state: PostState = config
+        .accounts
+        .iter()
+        .map(|(address, account)| {
+            let storage = account
+                .storage
+                .iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(key, value)| (*key, *value))
+                .collect();
+            (
+                *address,
+                PostAccount {
+                    nonce: account.nonce.as_u64(),
+                    balance: account.balance,
+                    code: account.code.to_vec(),
+                    storage,
+                },
+            )
+        })
+        .collect();
+    for diff in diffs {
+        apply_diff(
+            &mut state,
+            serde_json::from_value(diff).map_err(Error::SerdeError)?,
+        );
+    }
+    state.retain(|_, account| !account.is_empty());
+
+    Ok(Outcome { txs, state })
+}
+
+/// Run the txs of `config` through geth and revm and return the differences
+/// between the two, empty if they agree.
+///
+/// Panics if the config relies on a known divergence between the two, see
+/// the module docs.
+fn diff_with_revm(config: &TraceConfig) -> Result<Vec<String>, Error> {
+    assert_comparable(config);
+    let geth = run_geth(config)?;
+    let revm = run_revm(config);
+
+    let mut diffs = Vec::new();
+    if geth.txs.len() != revm.txs.len() {
+        diffs.push(format!(
+            "geth ran {} txs, revm {}",
+            geth.txs.len(),
+            revm.txs.len()
+        ));
+    }
+    for (i, (geth, revm)) in geth.txs.iter().zip(revm.txs.iter()).enumerate() {
+        if geth.success != revm.success {
+            diffs.push(format!(
+                "tx {i}: success is {} in geth, {} in revm",
+                geth.success, revm.success
+            ));
+        }
+        if geth.gas_used != revm.gas_used {
+            diffs.push(format!(
+                "tx {i}: gas used is {} in geth, {} in revm",
+                geth.gas_used, revm.gas_used
+            ));
+        }
+        if geth.logs != revm.logs {
+            diffs.push(format!(
+                "tx {i}: logs are {:?} in geth, {:?} in revm",
+                geth.logs, revm.logs
+            ));
+        }
+    }
+    let addresses: BTreeSet<_> = geth.state.keys().chain(revm.state.keys()).collect();
+    for address in addresses {
+        let geth = geth.state.get(address);
+        let revm = revm.state.get(address);
+        if geth != revm {
+            diffs.push(format!(
+                "account {address:?} is {geth:?} in geth, {revm:?} in revm"
+            ));
+        }
+    }
+
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod revm_diff_tests {
+    use super::*;
+    use eth_types::{
+        address, bytecode,
+        geth_types::{Account, BlockConstants, Transaction},
+        U64,
+    };
+
+    const SENDER: &str = "0x000000000000000000000000000000000000cafe";
+    const CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+    const REVERTER: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn config(accounts: Vec<Account>, call_data: Vec<u8>) -> TraceConfig {
+        let sender = Account {
+            address: address!(SENDER),
+            balance: Word::from(10u64).pow(20.into()),
+            ..Default::default()
+        };
+        TraceConfig {
+            chain_id: Word::from(1337),
+            history_hashes: vec![Word::from(0x1234)],
+            block_constants: BlockConstants {
+                coinbase: address!("0x00000000000000000000000000000000000000c0"),
+                timestamp: Word::from(1_000_000),
+                number: U64::from(2),
+                difficulty: Word::from(0x20000),
+                gas_limit: Word::from(10_000_000),
+                base_fee: Word::from(7),
+                ..Default::default()
+            },
+            accounts: std::iter::once(sender)
+                .chain(accounts)
+                .map(|account| (account.address, account))
+                .collect(),
+            transactions: vec![Transaction {
+                from: address!(SENDER),
+                to: Some(address!(CONTRACT)),
+                nonce: Word::zero(),
+                gas_limit: Word::from(1_000_000),
+                value: Word::from(0x100),
+                gas_price: Word::from(10),
+                call_data: call_data.into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn assert_no_diff(config: &TraceConfig) {
+        let diffs = diff_with_revm(config).unwrap();
+        assert!(diffs.is_empty(), "{}", diffs.join("\n"));
+    }
+
+    #[test]
+    fn storage_logs_and_block_context() {
+        let code = bytecode! {
+            // overwrite one slot, clear another for a refund
+            .op_sstore(0x00, 0x2a)
+            .op_sstore(0x01, 0x00)
+            NUMBER
+            PUSH1(0x01)
+            SWAP1
+            SUB
+            BLOCKHASH
+            PUSH1(0x02)
+            SSTORE
+            DIFFICULTY
+            BASEFEE
+            ADD
+            CHAINID
+            ADD
+            PUSH1(0x00)
+            MSTORE
+            .op_log2(0x00, 0x20, 0x11, 0x22)
+            STOP
+        };
+        let contract = Account {
+            address: address!(CONTRACT),
+            code: code.into(),
+            storage: [(Word::one(), Word::from(5))].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_no_diff(&config(vec![contract], vec![]));
+    }
+
+    #[test]
+    fn nested_calls_creations_and_reverts() {
+        // logs and writes of the reverted call are dropped
+        let reverter = Account {
+            address: address!(REVERTER),
+            code: bytecode! {
+                .op_sstore(0x00, 0x01)
+                .op_log0(0x00, 0x00)
+                .op_revert(0x00, 0x00)
+            }
+            .into(),
+            ..Default::default()
+        };
+        // the init code deploys a contract made of the first byte of memory
+        let init_code = bytecode! {
+            .op_mstore8(0x00, 0xfe)
+            .op_return(0x00, 0x01)
+        }
+        .code();
+        let mut code = bytecode! {
+            .op_call(0x10000, address!(REVERTER), 0x10, 0x00, 0x00, 0x00, 0x00)
+            .op_log0(0x00, 0x00)
+        };
+        for (i, byte) in init_code.iter().enumerate() {
+            code.op_mstore8(i, *byte as u64);
+        }
+        code.append(&bytecode! {
+            .op_create(0x20, 0x00, init_code.len())
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        });
+        let contract = Account {
+            address: address!(CONTRACT),
+            code: code.into(),
+            ..Default::default()
+        };
+        assert_no_diff(&config(vec![contract, reverter], vec![]));
+    }
+
+    #[test]
+    fn failed_tx() {
+        let contract = Account {
+            address: address!(CONTRACT),
+            code: bytecode! {
+                .op_sstore(0x00, 0x01)
+                .op_jump(0x00)
+            }
+            .into(),
+            ..Default::default()
+        };
+        assert_no_diff(&config(vec![contract], vec![0xde, 0xad]));
+    }
+
+    #[test]
+    #[should_panic(expected = "pays less than the base fee")]
+    fn reject_tx_below_base_fee() {
+        let mut config = config(vec![], vec![]);
+        config.transactions[0].gas_price = Word::one();
+        assert_comparable(&config);
+    }
+}
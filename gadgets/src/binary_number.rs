@@ -9,7 +9,7 @@ use halo2_proofs::{
     plonk::{Advice, Any, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
-use std::{collections::BTreeSet, marker::PhantomData};
+use std::{collections::BTreeSet, iter::once, marker::PhantomData};
 use strum::IntoEnumIterator;
 
 /// Helper trait that implements functionality to represent a generic type as
@@ -116,7 +116,7 @@ pub struct BinaryNumberChip<F, T, const N: usize> {
     _marker: PhantomData<F>,
 }
 
-impl<F: Field, T: IntoEnumIterator, const N: usize> BinaryNumberChip<F, T, N>
+impl<F: Field, T, const N: usize> BinaryNumberChip<F, T, N>
 where
     T: AsBits<N>,
 {
@@ -128,8 +128,47 @@ where
         }
     }
 
-    /// Configure constraints for the binary number chip.
-    pub fn configure(
+    /// Configure constraints for the binary number chip, with the values in
+    /// `0..range`. The range doesn't need to be a power of two, the bit
+    /// patterns past it are disallowed by at most N constraints.
+    pub fn configure_with_range(
+        meta: &mut ConstraintSystem<F>,
+        selector: Column<Fixed>,
+        value: Option<Column<Any>>,
+        range: usize,
+    ) -> BinaryNumberConfig<T, N> {
+        assert!(
+            range > 0 && range <= 1 << N,
+            "range {} doesn't fit in {} bits",
+            range,
+            N
+        );
+        let config = Self::configure_bits(meta, selector, value);
+        Self::configure_range(meta, selector, &config, range);
+        config
+    }
+
+    /// Assign a value to the binary number chip. A generic type that implements
+    /// the AsBits trait can be provided for assignment.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: &T,
+    ) -> Result<(), Error> {
+        for (&bit, &column) in value.as_bits().iter().zip(&self.config.bits) {
+            region.assign_advice(
+                || format!("binary number {:?}", column),
+                column,
+                offset,
+                || Value::known(F::from(bit)),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Configure the bit columns, and their value if given.
+    fn configure_bits(
         meta: &mut ConstraintSystem<F>,
         selector: Column<Fixed>,
         value: Option<Column<Any>>,
@@ -159,8 +198,59 @@ where
             });
         }
 
+        config
+    }
+
+    /// Disallow the values from `range` on, by comparing the bits with those
+    /// of `range - 1` from the most significant one. A value is out of range
+    /// when it has a 1 at a 0 bit of `range - 1`, and the same higher bits.
+    /// Only the higher 1 bits need to be matched, a 1 at a higher 0 bit is
+    /// already disallowed by the constraint of that bit.
+    fn configure_range(
+        meta: &mut ConstraintSystem<F>,
+        selector: Column<Fixed>,
+        config: &BinaryNumberConfig<T, N>,
+        range: usize,
+    ) {
+        if range == 1 << N {
+            return;
+        }
+        let max_bits: [bool; N] = (range - 1).as_bits();
+        meta.create_gate("binary number value in range", |meta| {
+            let selector = meta.query_fixed(selector, Rotation::cur());
+            let bits = config
+                .bits
+                .map(|bit| meta.query_advice(bit, Rotation::cur()));
+            (0..N)
+                .filter(|&i| !max_bits[i])
+                .map(|i| {
+                    let higher_ones = (0..i).filter(|&j| max_bits[j]).map(|j| bits[j].clone());
+                    selector.clone() * and::expr(higher_ones.chain(once(bits[i].clone())))
+                })
+                .collect::<Vec<_>>()
+        });
+    }
+}
+
+impl<F: Field, T: IntoEnumIterator, const N: usize> BinaryNumberChip<F, T, N>
+where
+    T: AsBits<N>,
+{
+    /// Configure constraints for the binary number chip, with the values of
+    /// the variants of T.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        selector: Column<Fixed>,
+        value: Option<Column<Any>>,
+    ) -> BinaryNumberConfig<T, N> {
+        let config = Self::configure_bits(meta, selector, value);
+
         // Disallow bit patterns (if any) that don't correspond to a variant of T.
         let valid_values: BTreeSet<usize> = T::iter().map(|t| from_bits(&t.as_bits())).collect();
+        if valid_values.iter().copied().eq(0..valid_values.len()) {
+            Self::configure_range(meta, selector, &config, valid_values.len());
+            return config;
+        }
         let mut invalid_values = (0..1 << N).filter(|i| !valid_values.contains(i)).peekable();
         if invalid_values.peek().is_some() {
             meta.create_gate("binary number value in range", |meta| {
@@ -176,25 +266,6 @@ where
 
         config
     }
-
-    /// Assign a value to the binary number chip. A generic type that implements
-    /// the AsBits trait can be provided for assignment.
-    pub fn assign(
-        &self,
-        region: &mut Region<'_, F>,
-        offset: usize,
-        value: &T,
-    ) -> Result<(), Error> {
-        for (&bit, &column) in value.as_bits().iter().zip(&self.config.bits) {
-            region.assign_advice(
-                || format!("binary number {:?}", column),
-                column,
-                offset,
-                || Value::known(F::from(bit)),
-            )?;
-        }
-        Ok(())
-    }
 }
 
 /// Helper function to get a decimal representation given the bits.
@@ -202,3 +273,111 @@ pub fn from_bits(bits: &[bool]) -> usize {
     bits.iter()
         .fold(0, |result, &bit| bit as usize + 2 * result)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{BinaryNumberChip, BinaryNumberConfig};
+    use eth_types::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr as Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed},
+    };
+    use std::marker::PhantomData;
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        q_enable: Column<Fixed>,
+        value: Column<Advice>,
+        bits: BinaryNumberConfig<usize, 3>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F, const RANGE: usize> {
+        values: Vec<usize>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field, const RANGE: usize> Circuit<F> for TestCircuit<F, RANGE> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.fixed_column();
+            let value = meta.advice_column();
+            let bits =
+                BinaryNumberChip::configure_with_range(meta, q_enable, Some(value.into()), RANGE);
+
+            TestCircuitConfig {
+                q_enable,
+                value,
+                bits,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = BinaryNumberChip::construct(config.bits);
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        region.assign_fixed(
+                            || "q_enable",
+                            config.q_enable,
+                            offset,
+                            || Value::known(F::one()),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            offset,
+                            || Value::known(F::from(*value as u64)),
+                        )?;
+                        chip.assign(&mut region, offset, value)?;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify_range<const RANGE: usize>() {
+        for value in 0..8 {
+            let circuit = TestCircuit::<Fp, RANGE> {
+                values: vec![value],
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fp>::run(4, &circuit, vec![]).unwrap();
+            assert_eq!(
+                prover.verify().is_ok(),
+                value < RANGE,
+                "value {} in range {}",
+                value,
+                RANGE
+            );
+        }
+    }
+
+    #[test]
+    fn binary_number_with_range() {
+        verify_range::<1>();
+        verify_range::<2>();
+        verify_range::<3>();
+        verify_range::<4>();
+        verify_range::<5>();
+        verify_range::<6>();
+        verify_range::<7>();
+        verify_range::<8>();
+    }
+}
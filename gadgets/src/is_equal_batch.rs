@@ -0,0 +1,97 @@
+//! IsEqualBatch chip checks the equality of `N` pairs of expressions, with the
+//! inverses of their differences shared over the rows of a step as in the
+//! [`IsZeroBatchChip`].
+
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+};
+
+use super::is_zero_batch::{IsZeroBatchChip, IsZeroBatchConfig, IsZeroBatchInstruction};
+
+/// Instruction that the IsEqualBatch chip needs to implement.
+pub trait IsEqualBatchInstruction<F: Field, const N: usize> {
+    /// Assign the `N` pairs of lhs and rhs witnesses in the step starting at
+    /// `offset`.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: [Value<F>; N],
+        rhs: [Value<F>; N],
+    ) -> Result<(), Error>;
+}
+
+/// Config for the IsEqualBatch chip.
+#[derive(Clone, Debug)]
+pub struct IsEqualBatchConfig<F, const N: usize> {
+    /// Checks whether the differences are zero.
+    pub is_zero: IsZeroBatchConfig<F, N>,
+}
+
+impl<F: Field, const N: usize> IsEqualBatchConfig<F, N> {
+    /// Returns an expression that denotes whether the pair `idx` is equal.
+    pub fn expr(&self, idx: usize) -> Expression<F> {
+        self.is_zero.expr(idx)
+    }
+}
+
+/// Chip that checks the equality of `N` pairs of expressions.
+#[derive(Clone, Debug)]
+pub struct IsEqualBatchChip<F, const N: usize> {
+    config: IsEqualBatchConfig<F, N>,
+}
+
+impl<F: Field, const N: usize> IsEqualBatchChip<F, N> {
+    /// Configure the IsEqualBatch chip, with the inverses of the differences
+    /// in `value_inv` columns over `height` rows.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> [Expression<F>; N],
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> [Expression<F>; N],
+        value_inv: &[Column<Advice>],
+        height: usize,
+    ) -> IsEqualBatchConfig<F, N> {
+        let values = |meta: &mut VirtualCells<'_, F>| {
+            let mut rhs = rhs(meta).into_iter();
+            lhs(meta).map(|lhs| lhs - rhs.next().expect("there is a rhs per lhs"))
+        };
+        let is_zero = IsZeroBatchChip::configure(meta, q_enable, values, value_inv, height);
+
+        IsEqualBatchConfig { is_zero }
+    }
+
+    /// Construct an IsEqualBatch chip given a config.
+    pub fn construct(config: IsEqualBatchConfig<F, N>) -> Self {
+        Self { config }
+    }
+}
+
+impl<F: Field, const N: usize> IsEqualBatchInstruction<F, N> for IsEqualBatchChip<F, N> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: [Value<F>; N],
+        rhs: [Value<F>; N],
+    ) -> Result<(), Error> {
+        let mut rhs = rhs.into_iter();
+        let values = lhs.map(|lhs| lhs - rhs.next().expect("there is a rhs per lhs"));
+        IsZeroBatchChip::construct(self.config.is_zero.clone()).assign(region, offset, values)
+    }
+}
+
+impl<F: Field, const N: usize> Chip<F> for IsEqualBatchChip<F, N> {
+    type Config = IsEqualBatchConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
@@ -0,0 +1,301 @@
+//! IsZeroBatch chip checks whether each of `N` values is zero, like `N`
+//! [`IsZeroChip`](crate::is_zero::IsZeroChip)s, for circuits laid out in steps
+//! of `height` rows.
+//!
+//! The inverse of each value is witnessed in a cell of the step instead of a
+//! column of its own, so that the `N` checks only take `N / height` columns
+//! (rounded up):
+//!  - the inverse of the value `i` is in the column `i / height`, at the row `i % height` of the
+//!    step,
+//!  - the columns are given by the caller, so that chips of different steps can share them.
+
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+use std::array;
+
+use crate::util::Expr;
+
+/// Instruction that the IsZeroBatch chip needs to implement.
+pub trait IsZeroBatchInstruction<F: Field, const N: usize> {
+    /// Given the `values` to be checked if they are zero, witnesses their
+    /// inverses in the step starting at `offset`.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        values: [Value<F>; N],
+    ) -> Result<(), Error>;
+}
+
+/// Config for the IsZeroBatch chip.
+#[derive(Clone, Debug)]
+pub struct IsZeroBatchConfig<F, const N: usize> {
+    /// Column and row in the step of the inverse of each value.
+    pub value_inv: [(Column<Advice>, usize); N],
+    /// Expressions that are 1 if the value at the same index is zero, and 0
+    /// otherwise.
+    pub is_zero_expressions: [Expression<F>; N],
+}
+
+impl<F: Field, const N: usize> IsZeroBatchConfig<F, N> {
+    /// Returns the is_zero expression of the value `idx`.
+    pub fn expr(&self, idx: usize) -> Expression<F> {
+        self.is_zero_expressions[idx].clone()
+    }
+
+    /// Annotates columns of this gadget embedded within a circuit region.
+    pub fn annotate_columns_in_region(&self, region: &mut Region<F>, prefix: &str) {
+        let mut columns: Vec<_> = self.value_inv.iter().map(|(column, _)| *column).collect();
+        columns.dedup();
+        for (idx, column) in columns.into_iter().enumerate() {
+            region.name_column(
+                || format!("{}_GADGETS_IS_ZERO_BATCH_inverse_witness_{}", prefix, idx),
+                column,
+            );
+        }
+    }
+}
+
+/// Chip that checks whether each of `N` values is zero.
+#[derive(Clone, Debug)]
+pub struct IsZeroBatchChip<F, const N: usize> {
+    config: IsZeroBatchConfig<F, N>,
+}
+
+impl<F: Field, const N: usize> IsZeroBatchChip<F, N> {
+    /// Configure the IsZeroBatch chip, with `q_enable` enabled on the first
+    /// row of each step and the inverses in `value_inv` columns over `height`
+    /// rows.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl FnOnce(&mut VirtualCells<'_, F>) -> [Expression<F>; N],
+        value_inv: &[Column<Advice>],
+        height: usize,
+    ) -> IsZeroBatchConfig<F, N> {
+        assert!(
+            value_inv.len() * height >= N,
+            "{} columns of {} rows can't hold {} inverses",
+            value_inv.len(),
+            height,
+            N
+        );
+        let value_inv = array::from_fn(|idx| (value_inv[idx / height], idx % height));
+        // dummy initialization
+        let mut is_zero_expressions = array::from_fn(|_| 0.expr());
+
+        meta.create_gate("is_zero batch gate", |meta| {
+            let q_enable = q_enable(meta);
+            let mut inverses = value_inv
+                .map(|(column, row)| meta.query_advice(column, Rotation(row as i32)))
+                .into_iter();
+            let values = values(meta);
+
+            is_zero_expressions = values.clone().map(|value| {
+                1.expr() - value * inverses.next().expect("there is an inverse per value")
+            });
+
+            // as in the IsZero chip, for each value:
+            // 1. value == 0
+            // 2. if value != 0, require is_zero_expression == 0 => value_inv == value.invert()
+            values
+                .into_iter()
+                .zip(is_zero_expressions.clone())
+                .map(move |(value, is_zero)| q_enable.clone() * value * is_zero)
+        });
+
+        IsZeroBatchConfig {
+            value_inv,
+            is_zero_expressions,
+        }
+    }
+
+    /// Given an `IsZeroBatchConfig`, construct the chip.
+    pub fn construct(config: IsZeroBatchConfig<F, N>) -> Self {
+        Self { config }
+    }
+}
+
+impl<F: Field, const N: usize> IsZeroBatchInstruction<F, N> for IsZeroBatchChip<F, N> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        values: [Value<F>; N],
+    ) -> Result<(), Error> {
+        for ((column, row), value) in self.config.value_inv.into_iter().zip(values) {
+            // postpone the invert to prover which has batch_invert function to
+            // amortize among all the assignments.
+            let value_invert = value.into_field().invert();
+            region.assign_advice(
+                || "witness inverse of value",
+                column,
+                offset + row,
+                || value_invert,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: Field, const N: usize> Chip<F> for IsZeroBatchChip<F, N> {
+    type Config = IsZeroBatchConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IsZeroBatchChip, IsZeroBatchConfig, IsZeroBatchInstruction};
+
+    use eth_types::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr as Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+    use std::marker::PhantomData;
+
+    /// Values checked in each step
+    const N: usize = 5;
+    /// Rows of each step
+    const HEIGHT: usize = 2;
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        values: [Column<Advice>; N],
+        checks: [Column<Advice>; N],
+        is_zero: IsZeroBatchConfig<F, N>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<[u64; N]>,
+        // checks[i][j] is whether values[i][j] is zero
+        checks: Vec<[bool; N]>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let values = [(); N].map(|_| meta.advice_column());
+            let checks = [(); N].map(|_| meta.advice_column());
+            let value_inv = [(); 3].map(|_| meta.advice_column());
+
+            let is_zero = IsZeroBatchChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| values.map(|value| meta.query_advice(value, Rotation::cur())),
+                &value_inv,
+                HEIGHT,
+            );
+
+            meta.create_gate("check is_zero", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let checks = checks.map(|check| meta.query_advice(check, Rotation::cur()));
+
+                checks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, check)| q_enable.clone() * (is_zero.expr(idx) - check))
+                    .collect::<Vec<_>>()
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                values,
+                checks,
+                is_zero,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = IsZeroBatchChip::construct(config.is_zero.clone());
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (step, (values, checks)) in
+                        self.values.iter().zip(self.checks.iter()).enumerate()
+                    {
+                        let offset = step * HEIGHT;
+                        config.q_enable.enable(&mut region, offset)?;
+                        for idx in 0..N {
+                            region.assign_advice(
+                                || "value",
+                                config.values[idx],
+                                offset,
+                                || Value::known(F::from(values[idx])),
+                            )?;
+                            region.assign_advice(
+                                || "check",
+                                config.checks[idx],
+                                offset,
+                                || Value::known(F::from(checks[idx] as u64)),
+                            )?;
+                        }
+                        chip.assign(
+                            &mut region,
+                            offset,
+                            values.map(|value| Value::known(F::from(value))),
+                        )?;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(values: Vec<[u64; N]>, checks: Vec<[bool; N]>) -> bool {
+        let circuit = TestCircuit::<Fp> {
+            values,
+            checks,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::<Fp>::run(6, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn is_zero_batch_gadget() {
+        let values = vec![[0, 1, 0, 7, 0], [3, 0, 0, 0, 1], [0; N], [9, 8, 7, 6, 5]];
+        let checks: Vec<_> = values.iter().map(|values| values.map(|v| v == 0)).collect();
+        assert!(verify(values.clone(), checks.clone()));
+
+        for step in 0..values.len() {
+            for idx in 0..N {
+                let mut wrong = checks.clone();
+                wrong[step][idx] = !wrong[step][idx];
+                assert!(!verify(values.clone(), wrong), "{} {}", step, idx);
+            }
+        }
+    }
+}
@@ -17,11 +17,15 @@ pub mod comparator;
 pub mod comparison;
 pub mod evm_word;
 pub mod is_equal;
+pub mod is_equal_batch;
 pub mod is_zero;
+pub mod is_zero_batch;
 pub mod less_than;
 pub mod monotone;
 pub mod mul_add;
+pub mod mul_add_mod;
 pub mod util;
+pub mod word_comparison;
 
 use eth_types::Field;
 use halo2_proofs::{
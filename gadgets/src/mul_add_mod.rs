@@ -0,0 +1,593 @@
+//! Chip that implements instructions to check: a * b + c == r (mod n) where
+//! a, b, c, n and r are all 256-bit words, with the EVM convention that r == 0
+//! when n == 0. ADDMOD is checked with b == 1, and MULMOD with c == 0.
+//!
+//! The chip witnesses the 512-bit quotient k of a * b + c by n, and checks
+//! a * b + c == k * n + r over the integers, 128 bits at a time. The carry of
+//! each 128-bit chunk of the difference of the two sides can be negative, so it
+//! is witnessed in 9 bytes with an offset of 2^71. The remainder is checked to
+//! be less than n with a [`WordComparisonChip`].
+//!
+//! Note that the range of the limbs, halves and carry bytes isn't checked by
+//! this chip: the limbs must be checked to fit in 64 bits, the halves in 128
+//! bits and the carries in bytes by the circuit using it.
+//!
+//! The circuit layout is as follows:
+#[rustfmt::skip]
+// | q_step | col0     | col1     | col2     | col3     | col4     | col5     | col6     | col7     | col8     |
+// |--------|----------|----------|----------|----------|----------|----------|----------|----------|----------|
+// | 1      | a_limb0  | a_limb1  | a_limb2  | a_limb3  | b_limb0  | b_limb1  | b_limb2  | b_limb3  | -        |
+// | 0      | n_limb0  | n_limb1  | n_limb2  | n_limb3  | c_lo     | c_hi     | r_lo     | r_hi     | -        |
+// | 0      | k_limb0  | k_limb1  | k_limb2  | k_limb3  | k_limb4  | k_limb5  | k_limb6  | k_limb7  | -        |
+// | 0      | carry0_0 | carry0_1 | carry0_2 | carry0_3 | carry0_4 | carry0_5 | carry0_6 | carry0_7 | carry0_8 |
+// | 0      | carry1_0 | carry1_1 | carry1_2 | carry1_3 | carry1_4 | carry1_5 | carry1_6 | carry1_7 | carry1_8 |
+// | 0      | carry2_0 | carry2_1 | carry2_2 | carry2_3 | carry2_4 | carry2_5 | carry2_6 | carry2_7 | carry2_8 |
+// |--------|----------|----------|----------|----------|----------|----------|----------|----------|----------|
+
+use eth_types::{Field, ToLittleEndian, Word, U512};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+use std::ops::{Add, Mul};
+
+use crate::{
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+    util::{expr_from_bytes, not, pow_of_two, Expr},
+    word_comparison::{WordComparisonChip, WordComparisonConfig, WordComparisonInstruction},
+};
+
+/// Rows taken by each check of the chip.
+pub const MUL_ADD_MOD_ROWS: usize = 6;
+
+/// Bytes of each carry.
+const N_BYTES_CARRY: usize = 9;
+
+/// Offset of the witnessed carries, which are in (-2^71, 2^71).
+const CARRY_OFFSET_BITS: usize = 71;
+
+/// Sums `Σ_{i + j = m} lhs[i] * rhs[j]` of the products of the limbs of `lhs`
+/// and `rhs`, for each position m.
+fn limb_products<T>(lhs: &[T], rhs: &[T], zero: T) -> Vec<T>
+where
+    T: Clone + Add<Output = T> + Mul<Output = T>,
+{
+    let mut sums = vec![zero; lhs.len() + rhs.len() - 1];
+    for (i, lhs) in lhs.iter().enumerate() {
+        for (j, rhs) in rhs.iter().enumerate() {
+            sums[i + j] = sums[i + j].clone() + lhs.clone() * rhs.clone();
+        }
+    }
+    sums
+}
+
+/// Instruction that the MulAddMod chip needs to implement.
+pub trait MulAddModInstruction<F: FieldExt> {
+    /// Assign the `[a, b, c, n]` words of the check and the witness of
+    /// `a * b + c (mod n)`, which is returned.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        words: [Word; 4],
+    ) -> Result<Word, Error>;
+
+    /// Assign the check of `a + b (mod n)`, which is returned.
+    fn assign_add_mod(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: Word,
+        b: Word,
+        n: Word,
+    ) -> Result<Word, Error> {
+        self.assign(region, offset, [a, Word::one(), b, n])
+    }
+
+    /// Assign the check of `a * b (mod n)`, which is returned.
+    fn assign_mul_mod(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: Word,
+        b: Word,
+        n: Word,
+    ) -> Result<Word, Error> {
+        self.assign(region, offset, [a, b, Word::zero(), n])
+    }
+}
+
+/// Config for the MulAddMod chip.
+#[derive(Clone, Debug)]
+pub struct MulAddModConfig<F> {
+    /// Columns used over multiple rows to represent the schema described
+    /// above.
+    pub cols: [Column<Advice>; 9],
+    /// Whether n is zero.
+    pub n_is_zero: IsZeroConfig<F>,
+    /// Comparison of r with n.
+    pub r_lt_n: WordComparisonConfig<F>,
+}
+
+impl<F: Field> MulAddModConfig<F> {
+    fn limbs(&self, meta: &mut VirtualCells<'_, F>, first: usize, row: i32) -> [Expression<F>; 4] {
+        [0, 1, 2, 3].map(|i| meta.query_advice(self.cols[first + i], Rotation(row)))
+    }
+
+    fn lo_hi(&self, meta: &mut VirtualCells<'_, F>, first: usize, row: i32) -> [Expression<F>; 2] {
+        [0, 1].map(|i| meta.query_advice(self.cols[first + i], Rotation(row)))
+    }
+
+    /// 64-bit limbs representing `a` from the equation `a * b + c == r (mod
+    /// n)`.
+    pub fn a_limbs(&self, meta: &mut VirtualCells<'_, F>) -> [Expression<F>; 4] {
+        self.limbs(meta, 0, 0)
+    }
+
+    /// 64-bit limbs representing `b` from the equation `a * b + c == r (mod
+    /// n)`.
+    pub fn b_limbs(&self, meta: &mut VirtualCells<'_, F>) -> [Expression<F>; 4] {
+        self.limbs(meta, 4, 0)
+    }
+
+    /// 64-bit limbs representing `n` from the equation `a * b + c == r (mod
+    /// n)`.
+    pub fn n_limbs(&self, meta: &mut VirtualCells<'_, F>) -> [Expression<F>; 4] {
+        self.limbs(meta, 0, 1)
+    }
+
+    /// 128-bit lo-hi parts of `n` from the equation `a * b + c == r (mod n)`.
+    pub fn n_lo_hi(&self, meta: &mut VirtualCells<'_, F>) -> [Expression<F>; 2] {
+        let [n0, n1, n2, n3] = self.n_limbs(meta);
+        let shift = Expression::Constant(pow_of_two::<F>(64));
+        [n0 + n1 * shift.clone(), n2 + n3 * shift]
+    }
+
+    /// 128-bit lo-hi parts of `c` from the equation `a * b + c == r (mod n)`.
+    pub fn c_lo_hi(&self, meta: &mut VirtualCells<'_, F>) -> [Expression<F>; 2] {
+        self.lo_hi(meta, 4, 1)
+    }
+
+    /// 128-bit lo-hi parts of `r` from the equation `a * b + c == r (mod n)`.
+    pub fn r_lo_hi(&self, meta: &mut VirtualCells<'_, F>) -> [Expression<F>; 2] {
+        self.lo_hi(meta, 6, 1)
+    }
+
+    /// Annotates columns of this gadget embedded within a circuit region.
+    pub fn annotate_columns_in_region(&self, region: &mut Region<F>, prefix: &str) {
+        for (i, col) in self.cols.iter().enumerate() {
+            region.name_column(|| format!("{}_GADGET_MUL_ADD_MOD_col{}", prefix, i), *col);
+        }
+        self.n_is_zero
+            .annotate_columns_in_region(region, &format!("{}_GADGET_MUL_ADD_MOD", prefix));
+    }
+}
+
+/// Chip to constrain a * b + c == r (mod n).
+#[derive(Clone, Debug)]
+pub struct MulAddModChip<F> {
+    config: MulAddModConfig<F>,
+}
+
+impl<F: Field> MulAddModChip<F> {
+    /// Configure the MulAddMod chip.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F> + Clone,
+    ) -> MulAddModConfig<F> {
+        let cols = [(); 9].map(|_| meta.advice_column());
+        let n_limbs = move |meta: &mut VirtualCells<'_, F>| {
+            [0, 1, 2, 3].map(|i| meta.query_advice(cols[i], Rotation(1)))
+        };
+        let n_inv = meta.advice_column();
+        let n_is_zero = IsZeroChip::configure(
+            meta,
+            q_enable.clone(),
+            move |meta| {
+                n_limbs(meta)
+                    .into_iter()
+                    .fold(0.expr(), |acc, limb| acc + limb)
+            },
+            n_inv,
+        );
+        let r_lt_n = WordComparisonChip::configure(
+            meta,
+            q_enable.clone(),
+            move |meta| [6, 7].map(|i| meta.query_advice(cols[i], Rotation(1))),
+            move |meta| {
+                let [n0, n1, n2, n3] = n_limbs(meta);
+                let shift = Expression::Constant(pow_of_two::<F>(64));
+                [n0 + n1 * shift.clone(), n2 + n3 * shift]
+            },
+        );
+        let config = MulAddModConfig {
+            cols,
+            n_is_zero,
+            r_lt_n,
+        };
+
+        meta.create_gate("mul add mod gate", |meta| {
+            let q_enable = q_enable(meta);
+            let n_is_zero = config.n_is_zero.expr();
+            let r_lt_n = config.r_lt_n.is_lt(meta, None);
+
+            let a_limbs = config.a_limbs(meta);
+            let b_limbs = config.b_limbs(meta);
+            let n_limbs = config.n_limbs(meta);
+            let k_limbs: Vec<_> = cols[..8]
+                .iter()
+                .map(|col| meta.query_advice(*col, Rotation(2)))
+                .collect();
+            let [c_lo, c_hi] = config.c_lo_hi(meta);
+            let [r_lo, r_hi] = config.r_lo_hi(meta);
+            let carries = [3, 4, 5].map(|row| {
+                let bytes = cols.map(|col| meta.query_advice(col, Rotation(row)));
+                expr_from_bytes(&bytes) - Expression::Constant(pow_of_two(CARRY_OFFSET_BITS))
+            });
+
+            let t = limb_products(&a_limbs, &b_limbs, 0.expr());
+            let u = limb_products(&k_limbs, &n_limbs, 0.expr());
+            let shift = Expression::Constant(pow_of_two::<F>(64));
+            let chunk = |sums: &[Expression<F>], q: usize| {
+                sums.get(2 * q).cloned().unwrap_or_else(|| 0.expr())
+                    + sums.get(2 * q + 1).cloned().unwrap_or_else(|| 0.expr()) * shift.clone()
+            };
+
+            // a * b + c == k * n + r, 128 bits at a time
+            let mut constraints = vec![];
+            let mut carry_in = 0.expr();
+            for (q, (c, r)) in [c_lo, c_hi, 0.expr(), 0.expr()]
+                .into_iter()
+                .zip([r_lo.clone(), r_hi.clone(), 0.expr(), 0.expr()])
+                .enumerate()
+            {
+                let carry_out = carries.get(q).cloned().unwrap_or_else(|| 0.expr());
+                constraints.push(
+                    chunk(&t, q) + c - chunk(&u, q) - r + carry_in
+                        - carry_out.clone() * Expression::Constant(pow_of_two::<F>(128)),
+                );
+                carry_in = carry_out;
+            }
+            // k * n fits in 512 bits
+            constraints.extend(u[8..].iter().cloned());
+            // r < n
+            constraints.push(not::expr(r_lt_n));
+
+            let n_is_not_zero = not::expr(n_is_zero.clone());
+            constraints
+                .into_iter()
+                .map(move |constraint| n_is_not_zero.clone() * constraint)
+                // r == 0 when n == 0
+                .chain([n_is_zero.clone() * r_lo, n_is_zero * r_hi])
+                .map(move |constraint| q_enable.clone() * constraint)
+        });
+
+        config
+    }
+
+    /// Constructs a MulAddMod chip given its config.
+    pub fn construct(config: MulAddModConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// Assign the `[a, b, c, n]` words with the quotient `k` and the remainder
+    /// `r` of `a * b + c` by `n`, which don't have to be the right ones.
+    fn assign_witness(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [a, b, c, n]: [Word; 4],
+        k: U512,
+        r: Word,
+    ) -> Result<(), Error> {
+        let cols = self.config.cols;
+        let mut assign = |name: &str, col: usize, row: usize, value: F| {
+            region.assign_advice(
+                || format!("{} {}", name, offset + row),
+                cols[col],
+                offset + row,
+                || Value::known(value),
+            )
+        };
+        let halves = |word: Word| [word.low_u128(), (word >> 128).low_u128()];
+
+        for (i, (a, b)) in a.0.iter().zip(b.0.iter()).enumerate() {
+            assign("a limb", i, 0, F::from(*a))?;
+            assign("b limb", 4 + i, 0, F::from(*b))?;
+        }
+        for (i, n) in n.0.iter().enumerate() {
+            assign("n limb", i, 1, F::from(*n))?;
+        }
+        for (i, (c, r)) in halves(c).into_iter().zip(halves(r)).enumerate() {
+            assign("c half", 4 + i, 1, F::from_u128(c))?;
+            assign("r half", 6 + i, 1, F::from_u128(r))?;
+        }
+        for (i, k) in k.0.iter().enumerate() {
+            assign("k limb", i, 2, F::from(*k))?;
+        }
+        for row in 0..3 {
+            assign("unused col", 8, row, F::zero())?;
+        }
+
+        // carries of the chunks of a * b + c - (k * n + r), kept in unsigned
+        // integers by adding the offset of the carries to both of them
+        let limbs = |limbs: &[u64]| {
+            limbs
+                .iter()
+                .map(|limb| Word::from(*limb))
+                .collect::<Vec<_>>()
+        };
+        let t = limb_products(&limbs(&a.0), &limbs(&b.0), Word::zero());
+        let u = limb_products(&limbs(&k.0), &limbs(&n.0), Word::zero());
+        let chunk = |sums: &[Word], q: usize| {
+            sums.get(2 * q).copied().unwrap_or_default()
+                + (sums.get(2 * q + 1).copied().unwrap_or_default() << 64)
+        };
+        let half = |word: Word, q: usize| Word::from(halves(word).get(q).copied().unwrap_or(0));
+        let carry_offset = Word::one() << CARRY_OFFSET_BITS;
+        let mut carry_in = carry_offset;
+        for q in 0..3 {
+            let lhs = chunk(&t, q) + half(c, q) + carry_in + (carry_offset << 128);
+            let rhs = chunk(&u, q) + half(r, q) + carry_offset;
+            let carry_out = lhs.saturating_sub(rhs) >> 128;
+            for (i, byte) in carry_out.to_le_bytes()[..N_BYTES_CARRY].iter().enumerate() {
+                assign("carry byte", i, 3 + q, F::from(*byte as u64))?;
+            }
+            carry_in = carry_out;
+        }
+
+        IsZeroChip::construct(self.config.n_is_zero.clone()).assign(
+            region,
+            offset,
+            Value::known(n.0.iter().fold(F::zero(), |acc, limb| acc + F::from(*limb))),
+        )?;
+        WordComparisonChip::construct(self.config.r_lt_n.clone()).assign(region, offset, r, n)?;
+
+        Ok(())
+    }
+}
+
+impl<F: Field> MulAddModInstruction<F> for MulAddModChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [a, b, c, n]: [Word; 4],
+    ) -> Result<Word, Error> {
+        let (k, r) = if n.is_zero() {
+            (U512::zero(), Word::zero())
+        } else {
+            let (k, r) = (a.full_mul(b) + U512::from(c)).div_mod(U512::from(n));
+            (k, Word::try_from(r).expect("the remainder is less than n"))
+        };
+        self.assign_witness(region, offset, [a, b, c, n], k, r)?;
+
+        Ok(r)
+    }
+}
+
+impl<F: Field> Chip<F> for MulAddModChip<F> {
+    type Config = MulAddModConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MulAddModChip, MulAddModConfig, MulAddModInstruction, MUL_ADD_MOD_ROWS};
+    use eth_types::{Field, Word, U512};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr as Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+    use std::marker::PhantomData;
+
+    #[derive(Clone, Copy, Debug)]
+    enum Check {
+        AddMod(Word, Word, Word),
+        MulMod(Word, Word, Word),
+        /// `[a, b, c, n]` words with the quotient and remainder to witness
+        Witness([Word; 4], U512, Word),
+    }
+
+    impl Check {
+        /// Remainder expected from the chip.
+        fn expected(&self) -> Word {
+            let reduce = |value: U512, n: Word| {
+                if n.is_zero() {
+                    Word::zero()
+                } else {
+                    Word::try_from(value % U512::from(n)).unwrap()
+                }
+            };
+            match *self {
+                Check::AddMod(a, b, n) => reduce(U512::from(a) + U512::from(b), n),
+                Check::MulMod(a, b, n) => reduce(a.full_mul(b), n),
+                Check::Witness(_, _, r) => r,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        expected: [Column<Advice>; 2],
+        mul_add_mod: MulAddModConfig<F>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        checks: Vec<Check>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let expected = [(); 2].map(|_| meta.advice_column());
+            let mul_add_mod = MulAddModChip::configure(meta, |meta| meta.query_selector(q_enable));
+
+            meta.create_gate("check the remainder", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let expected = expected.map(|half| meta.query_advice(half, Rotation::cur()));
+                mul_add_mod
+                    .r_lo_hi(meta)
+                    .into_iter()
+                    .zip(expected)
+                    .map(move |(r, expected)| q_enable.clone() * (r - expected))
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                expected,
+                mul_add_mod,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = MulAddModChip::construct(config.mul_add_mod.clone());
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (i, check) in self.checks.iter().enumerate() {
+                        let offset = i * MUL_ADD_MOD_ROWS;
+                        config.q_enable.enable(&mut region, offset)?;
+                        match *check {
+                            Check::AddMod(a, b, n) => {
+                                chip.assign_add_mod(&mut region, offset, a, b, n)?;
+                            }
+                            Check::MulMod(a, b, n) => {
+                                chip.assign_mul_mod(&mut region, offset, a, b, n)?;
+                            }
+                            Check::Witness(words, k, r) => {
+                                chip.assign_witness(&mut region, offset, words, k, r)?;
+                            }
+                        }
+                        let expected = check.expected();
+                        for (column, half) in config
+                            .expected
+                            .into_iter()
+                            .zip([expected.low_u128(), (expected >> 128).low_u128()])
+                        {
+                            region.assign_advice(
+                                || "expected half",
+                                column,
+                                offset,
+                                || Value::known(F::from_u128(half)),
+                            )?;
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(checks: Vec<Check>) -> bool {
+        let circuit = TestCircuit::<Fp> {
+            checks,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::<Fp>::run(13, &circuit, vec![]).unwrap();
+        prover.verify_par().is_ok()
+    }
+
+    fn boundaries() -> Vec<Word> {
+        let one = Word::one();
+        vec![
+            Word::zero(),
+            one,
+            Word::from(2),
+            (one << 64) - 1,
+            one << 64,
+            (one << 128) - 1,
+            one << 128,
+            one << 255,
+            Word::MAX - 1,
+            Word::MAX,
+        ]
+    }
+
+    fn triples() -> Vec<(Word, Word, Word)> {
+        let boundaries = boundaries();
+        let mut triples = vec![];
+        for a in boundaries.iter() {
+            for b in boundaries.iter() {
+                for n in boundaries.iter() {
+                    triples.push((*a, *b, *n));
+                }
+            }
+        }
+        triples
+    }
+
+    #[test]
+    fn add_mod_at_word_boundaries() {
+        let checks = triples()
+            .into_iter()
+            .map(|(a, b, n)| Check::AddMod(a, b, n))
+            .collect();
+        assert!(verify(checks));
+    }
+
+    #[test]
+    fn mul_mod_at_word_boundaries() {
+        let checks = triples()
+            .into_iter()
+            .map(|(a, b, n)| Check::MulMod(a, b, n))
+            .collect();
+        assert!(verify(checks));
+    }
+
+    #[test]
+    fn mul_add_mod_wrong_witness() {
+        let (a, b, c, n) = (Word::MAX, Word::MAX - 1, Word::from(5), Word::from(7));
+        let (k, r) = (a.full_mul(b) + U512::from(c)).div_mod(U512::from(n));
+        let r = Word::try_from(r).unwrap();
+        let words = [a, b, c, n];
+        assert!(verify(vec![Check::Witness(words, k, r)]));
+
+        for (words, k, r) in [
+            // a remainder not less than n
+            (words, k - 1, r + n),
+            (words, k, r + 1),
+            (words, k + 1, r),
+            // k * n overflowing 512 bits to the right value in the field
+            (words, k + (U512::one() << 511), r),
+            // a remainder with n == 0
+            ([a, b, c, Word::zero()], U512::zero(), Word::one()),
+            (
+                [Word::from(5), Word::from(7), c, Word::zero()],
+                U512::zero(),
+                Word::from(40),
+            ),
+        ] {
+            assert!(!verify(vec![Check::Witness(words, k, r)]), "{:?}", words);
+        }
+    }
+}
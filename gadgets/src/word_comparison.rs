@@ -0,0 +1,396 @@
+//! WordComparison chip can be used to compare two 256-bit words LHS and RHS,
+//! each given as the expressions of its low and high 128-bit halves.
+//!
+//! The halves are compared with a [`ComparatorChip`] each, and the words are
+//! ordered by their high halves first:
+//! `lt = hi_lt + hi_eq * lo_lt` and `eq = hi_eq * lo_eq`.
+
+use eth_types::{Field, Word};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region},
+    plonk::{ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::{
+    comparator::{ComparatorChip, ComparatorConfig, ComparatorInstruction},
+    util::{not, select, split_u256},
+};
+
+/// Bytes of a half of a word
+const N_BYTES_HALF_WORD: usize = 16;
+
+/// Expression of the half `idx` of the `[lo, hi]` halves returned by `word`.
+fn half<F: Field>(
+    word: impl FnOnce(&mut VirtualCells<F>) -> [Expression<F>; 2] + Clone,
+    idx: usize,
+) -> impl FnOnce(&mut VirtualCells<F>) -> Expression<F> + Clone {
+    move |meta| {
+        let [lo, hi] = word(meta);
+        if idx == 0 {
+            lo
+        } else {
+            hi
+        }
+    }
+}
+
+/// Instruction that the WordComparison chip needs to implement.
+pub trait WordComparisonInstruction<F: FieldExt> {
+    /// Assign the lhs and rhs words to the WordComparison chip's region.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error>;
+}
+
+/// Config for the WordComparison chip.
+#[derive(Clone, Debug)]
+pub struct WordComparisonConfig<F> {
+    /// Comparison of the low halves.
+    pub lo: ComparatorConfig<F, N_BYTES_HALF_WORD>,
+    /// Comparison of the high halves.
+    pub hi: ComparatorConfig<F, N_BYTES_HALF_WORD>,
+}
+
+impl<F: Field> WordComparisonConfig<F> {
+    /// Returns an expression that denotes whether lhs < rhs.
+    pub fn is_lt(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        let (lo_lt, _) = self.lo.expr(meta, rotation);
+        let (hi_lt, hi_eq) = self.hi.expr(meta, rotation);
+        hi_lt + hi_eq * lo_lt
+    }
+
+    /// Returns an expression that denotes whether lhs == rhs.
+    pub fn is_eq(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        let (_, lo_eq) = self.lo.expr(meta, rotation);
+        let (_, hi_eq) = self.hi.expr(meta, rotation);
+        hi_eq * lo_eq
+    }
+
+    /// Returns an expression that denotes whether lhs <= rhs.
+    pub fn is_leq(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        self.is_lt(meta, rotation) + self.is_eq(meta, rotation)
+    }
+
+    /// Returns an expression that denotes whether lhs > rhs.
+    pub fn is_gt(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        not::expr(self.is_leq(meta, rotation))
+    }
+
+    /// Returns an expression that denotes whether lhs >= rhs.
+    pub fn is_geq(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        not::expr(self.is_lt(meta, rotation))
+    }
+
+    /// Returns the `[lo, hi]` halves of the smaller of the words `lhs` and
+    /// `rhs`, which must be the ones the chip was configured with.
+    pub fn min(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rotation: Option<Rotation>,
+        lhs: [Expression<F>; 2],
+        rhs: [Expression<F>; 2],
+    ) -> [Expression<F>; 2] {
+        let lt = self.is_lt(meta, rotation);
+        let ([lhs_lo, lhs_hi], [rhs_lo, rhs_hi]) = (lhs, rhs);
+        [
+            select::expr(lt.clone(), lhs_lo, rhs_lo),
+            select::expr(lt, lhs_hi, rhs_hi),
+        ]
+    }
+
+    /// Returns the `[lo, hi]` halves of the larger of the words `lhs` and
+    /// `rhs`, which must be the ones the chip was configured with.
+    pub fn max(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rotation: Option<Rotation>,
+        lhs: [Expression<F>; 2],
+        rhs: [Expression<F>; 2],
+    ) -> [Expression<F>; 2] {
+        let lt = self.is_lt(meta, rotation);
+        let ([lhs_lo, lhs_hi], [rhs_lo, rhs_hi]) = (lhs, rhs);
+        [
+            select::expr(lt.clone(), rhs_lo, lhs_lo),
+            select::expr(lt, rhs_hi, lhs_hi),
+        ]
+    }
+}
+
+/// Chip that compares two words given by their lo/hi halves.
+#[derive(Clone, Debug)]
+pub struct WordComparisonChip<F> {
+    config: WordComparisonConfig<F>,
+}
+
+impl<F: Field> WordComparisonChip<F> {
+    /// Configure the WordComparison chip, with `lhs` and `rhs` returning the
+    /// `[lo, hi]` halves of the words.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<F>) -> Expression<F> + Clone,
+        lhs: impl FnOnce(&mut VirtualCells<F>) -> [Expression<F>; 2] + Clone,
+        rhs: impl FnOnce(&mut VirtualCells<F>) -> [Expression<F>; 2] + Clone,
+    ) -> WordComparisonConfig<F> {
+        let lo = ComparatorChip::configure(
+            meta,
+            q_enable.clone(),
+            half(lhs.clone(), 0),
+            half(rhs.clone(), 0),
+        );
+        let hi = ComparatorChip::configure(meta, q_enable, half(lhs, 1), half(rhs, 1));
+
+        WordComparisonConfig { lo, hi }
+    }
+
+    /// Constructs a WordComparison chip given its config.
+    pub fn construct(config: WordComparisonConfig<F>) -> Self {
+        Self { config }
+    }
+}
+
+impl<F: Field> WordComparisonInstruction<F> for WordComparisonChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error> {
+        let (lhs_lo, lhs_hi) = split_u256(&lhs);
+        let (rhs_lo, rhs_hi) = split_u256(&rhs);
+        let half = |half: Word| F::from_u128(half.as_u128());
+
+        ComparatorChip::construct(self.config.lo.clone()).assign(
+            region,
+            offset,
+            half(lhs_lo),
+            half(rhs_lo),
+        )?;
+        ComparatorChip::construct(self.config.hi.clone()).assign(
+            region,
+            offset,
+            half(lhs_hi),
+            half(rhs_hi),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<F: Field> Chip<F> for WordComparisonChip<F> {
+    type Config = WordComparisonConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{WordComparisonChip, WordComparisonConfig, WordComparisonInstruction};
+    use crate::util::{split_u256, Expr};
+    use eth_types::{Field, Word};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr as Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector, VirtualCells},
+        poly::Rotation,
+    };
+    use std::{cmp::Ordering, marker::PhantomData};
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        lhs: [Column<Advice>; 2],
+        rhs: [Column<Advice>; 2],
+        is_lt: Column<Advice>,
+        is_eq: Column<Advice>,
+        min: [Column<Advice>; 2],
+        max: [Column<Advice>; 2],
+        comparison: WordComparisonConfig<F>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<(Word, Word)>,
+        // claimed ordering of each pair of values
+        orderings: Vec<Ordering>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let [lhs, rhs, min, max] = [(); 4].map(|_| [(); 2].map(|_| meta.advice_column()));
+            let (is_lt, is_eq) = (meta.advice_column(), meta.advice_column());
+
+            let comparison = WordComparisonChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| lhs.map(|half| meta.query_advice(half, Rotation::cur())),
+                |meta| rhs.map(|half| meta.query_advice(half, Rotation::cur())),
+            );
+
+            meta.create_gate("check the word comparisons", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let query = |meta: &mut VirtualCells<F>, word: [Column<Advice>; 2]| {
+                    word.map(|half| meta.query_advice(half, Rotation::cur()))
+                };
+                let is_lt = meta.query_advice(is_lt, Rotation::cur());
+                let is_eq = meta.query_advice(is_eq, Rotation::cur());
+                let (lhs, rhs) = (query(meta, lhs), query(meta, rhs));
+                let (min, max) = (query(meta, min), query(meta, max));
+
+                let mut constraints = vec![
+                    comparison.is_lt(meta, None) - is_lt.clone(),
+                    comparison.is_eq(meta, None) - is_eq.clone(),
+                    comparison.is_leq(meta, None) - (is_lt.clone() + is_eq.clone()),
+                    comparison.is_gt(meta, None) - (1.expr() - is_lt.clone() - is_eq),
+                    comparison.is_geq(meta, None) - (1.expr() - is_lt),
+                ];
+                let comparison_min = comparison.min(meta, None, lhs.clone(), rhs.clone());
+                let comparison_max = comparison.max(meta, None, lhs, rhs);
+                for (expected, actual) in min.into_iter().zip(comparison_min) {
+                    constraints.push(expected - actual);
+                }
+                for (expected, actual) in max.into_iter().zip(comparison_max) {
+                    constraints.push(expected - actual);
+                }
+
+                constraints
+                    .into_iter()
+                    .map(move |constraint| q_enable.clone() * constraint)
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                lhs,
+                rhs,
+                is_lt,
+                is_eq,
+                min,
+                max,
+                comparison,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = WordComparisonChip::construct(config.comparison.clone());
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (offset, ((lhs, rhs), ordering)) in
+                        self.values.iter().zip(self.orderings.iter()).enumerate()
+                    {
+                        config.q_enable.enable(&mut region, offset)?;
+                        let (min, max) = match ordering {
+                            Ordering::Less => (lhs, rhs),
+                            _ => (rhs, lhs),
+                        };
+                        for (columns, word) in [
+                            (config.lhs, lhs),
+                            (config.rhs, rhs),
+                            (config.min, min),
+                            (config.max, max),
+                        ] {
+                            let (lo, hi) = split_u256(word);
+                            for (column, half) in columns.into_iter().zip([lo, hi]) {
+                                region.assign_advice(
+                                    || "word half",
+                                    column,
+                                    offset,
+                                    || Value::known(F::from_u128(half.as_u128())),
+                                )?;
+                            }
+                        }
+                        for (column, check) in [
+                            (config.is_lt, *ordering == Ordering::Less),
+                            (config.is_eq, *ordering == Ordering::Equal),
+                        ] {
+                            region.assign_advice(
+                                || "check",
+                                column,
+                                offset,
+                                || Value::known(F::from(check as u64)),
+                            )?;
+                        }
+                        chip.assign(&mut region, offset, *lhs, *rhs)?;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(values: Vec<(Word, Word)>, orderings: Vec<Ordering>) -> bool {
+        let circuit = TestCircuit::<Fp> {
+            values,
+            orderings,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::<Fp>::run(6, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    fn values() -> Vec<(Word, Word)> {
+        let high = Word::one() << 128;
+        vec![
+            (Word::from(1), Word::from(2)),
+            (Word::from(2), Word::from(1)),
+            (Word::from(7), Word::from(7)),
+            // the high halves decide over the low ones
+            (high, high - 1),
+            (high - 1, high),
+            (high + 5, high + 5),
+            (Word::MAX - 1, Word::MAX),
+            (Word::MAX, Word::zero()),
+        ]
+    }
+
+    #[test]
+    fn word_comparison_ok() {
+        let values = values();
+        let orderings = values.iter().map(|(lhs, rhs)| lhs.cmp(rhs)).collect();
+        assert!(verify(values, orderings));
+    }
+
+    #[test]
+    fn word_comparison_wrong_ordering() {
+        let values = values();
+        for (idx, (lhs, rhs)) in values.iter().enumerate() {
+            for wrong in [Ordering::Less, Ordering::Equal, Ordering::Greater] {
+                if wrong == lhs.cmp(rhs) {
+                    continue;
+                }
+                let mut orderings: Vec<_> = values.iter().map(|(lhs, rhs)| lhs.cmp(rhs)).collect();
+                orderings[idx] = wrong;
+                assert!(!verify(values.clone(), orderings), "{:?} {:?}", lhs, rhs);
+            }
+        }
+    }
+}
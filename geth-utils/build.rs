@@ -28,8 +28,12 @@ fn main() {
     // Files the lib depends on that should recompile the lib
     let dep_files = vec![
         "./gethutil/asm.go",
+        "./gethutil/calls.go",
+        "./gethutil/errors.go",
+        "./gethutil/snapshot.go",
         "./gethutil/trace.go",
         "./gethutil/util.go",
+        "./lib/lib.go",
         "./go.mod",
     ];
     for file in dep_files {
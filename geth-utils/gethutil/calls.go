@@ -0,0 +1,153 @@
+package gethutil
+
+import (
+	"math/big"
+
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/common/hexutil"
+	"github.com/ethereum/go-ethereum/core/vm"
+)
+
+// CallFrame is a call made while applying a transaction, with the fields of
+// the frames of geth's native call tracer, plus its depth and the refund
+// counter when it returned.
+type CallFrame struct {
+	Type    string          `json:"type"`
+	From    common.Address  `json:"from"`
+	To      *common.Address `json:"to,omitempty"`
+	Value   *hexutil.Big    `json:"value,omitempty"`
+	Gas     hexutil.Uint64  `json:"gas"`
+	GasUsed hexutil.Uint64  `json:"gasUsed"`
+	Input   hexutil.Bytes   `json:"input"`
+	Output  hexutil.Bytes   `json:"output,omitempty"`
+	Error   string          `json:"error,omitempty"`
+	Depth   int             `json:"depth"`
+	Refund  hexutil.Uint64  `json:"refund"`
+	Calls   []*CallFrame    `json:"calls,omitempty"`
+}
+
+// CallFrameLogger records the tree of the call frames of a transaction.
+// Calls failing before they start, like the calls above the maximum depth or
+// transferring more than the caller's balance, have no frame.
+type CallFrameLogger struct {
+	env    *vm.EVM
+	frames []*CallFrame
+	root   *CallFrame
+}
+
+func (l *CallFrameLogger) enter(typ vm.OpCode, from, to common.Address, input []byte, gas uint64, value *big.Int) {
+	frame := &CallFrame{
+		Type:  typ.String(),
+		From:  from,
+		To:    &to,
+		Gas:   hexutil.Uint64(gas),
+		Input: common.CopyBytes(input),
+		Depth: len(l.frames) + 1,
+	}
+	if value != nil {
+		frame.Value = (*hexutil.Big)(new(big.Int).Set(value))
+	}
+	if len(l.frames) == 0 {
+		l.root = frame
+	} else {
+		parent := l.frames[len(l.frames)-1]
+		parent.Calls = append(parent.Calls, frame)
+	}
+	l.frames = append(l.frames, frame)
+}
+
+func (l *CallFrameLogger) exit(output []byte, gasUsed uint64, err error) {
+	frame := l.frames[len(l.frames)-1]
+	l.frames = l.frames[:len(l.frames)-1]
+	frame.GasUsed = hexutil.Uint64(gasUsed)
+	frame.Output = common.CopyBytes(output)
+	frame.Refund = hexutil.Uint64(l.env.StateDB.GetRefund())
+	if err != nil {
+		frame.Error = err.Error()
+	}
+}
+
+// Root returns the frame of the call of the transaction.
+func (l *CallFrameLogger) Root() *CallFrame { return l.root }
+
+func (l *CallFrameLogger) CaptureTxStart(gasLimit uint64) {}
+
+func (l *CallFrameLogger) CaptureTxEnd(restGas uint64) {}
+
+func (l *CallFrameLogger) CaptureStart(env *vm.EVM, from common.Address, to common.Address, create bool, input []byte, gas uint64, value *big.Int) {
+	l.env = env
+	typ := vm.CALL
+	if create {
+		typ = vm.CREATE
+	}
+	l.enter(typ, from, to, input, gas, value)
+}
+
+func (l *CallFrameLogger) CaptureEnd(output []byte, gasUsed uint64, err error) {
+	l.exit(output, gasUsed, err)
+}
+
+func (l *CallFrameLogger) CaptureEnter(typ vm.OpCode, from common.Address, to common.Address, input []byte, gas uint64, value *big.Int) {
+	l.enter(typ, from, to, input, gas, value)
+}
+
+func (l *CallFrameLogger) CaptureExit(output []byte, gasUsed uint64, err error) {
+	l.exit(output, gasUsed, err)
+}
+
+func (l *CallFrameLogger) CaptureState(pc uint64, op vm.OpCode, gas, cost uint64, scope *vm.ScopeContext, rData []byte, depth int, err error) {
+}
+
+func (l *CallFrameLogger) CaptureFault(pc uint64, op vm.OpCode, gas, cost uint64, scope *vm.ScopeContext, depth int, err error) {
+}
+
+// multiLogger forwards the events of the EVM to several loggers, in order.
+type multiLogger []vm.EVMLogger
+
+func (m multiLogger) CaptureTxStart(gasLimit uint64) {
+	for _, l := range m {
+		l.CaptureTxStart(gasLimit)
+	}
+}
+
+func (m multiLogger) CaptureTxEnd(restGas uint64) {
+	for _, l := range m {
+		l.CaptureTxEnd(restGas)
+	}
+}
+
+func (m multiLogger) CaptureStart(env *vm.EVM, from common.Address, to common.Address, create bool, input []byte, gas uint64, value *big.Int) {
+	for _, l := range m {
+		l.CaptureStart(env, from, to, create, input, gas, value)
+	}
+}
+
+func (m multiLogger) CaptureEnd(output []byte, gasUsed uint64, err error) {
+	for _, l := range m {
+		l.CaptureEnd(output, gasUsed, err)
+	}
+}
+
+func (m multiLogger) CaptureEnter(typ vm.OpCode, from common.Address, to common.Address, input []byte, gas uint64, value *big.Int) {
+	for _, l := range m {
+		l.CaptureEnter(typ, from, to, input, gas, value)
+	}
+}
+
+func (m multiLogger) CaptureExit(output []byte, gasUsed uint64, err error) {
+	for _, l := range m {
+		l.CaptureExit(output, gasUsed, err)
+	}
+}
+
+func (m multiLogger) CaptureState(pc uint64, op vm.OpCode, gas, cost uint64, scope *vm.ScopeContext, rData []byte, depth int, err error) {
+	for _, l := range m {
+		l.CaptureState(pc, op, gas, cost, scope, rData, depth, err)
+	}
+}
+
+func (m multiLogger) CaptureFault(pc uint64, op vm.OpCode, gas, cost uint64, scope *vm.ScopeContext, depth int, err error) {
+	for _, l := range m {
+		l.CaptureFault(pc, op, gas, cost, scope, depth, err)
+	}
+}
@@ -0,0 +1,101 @@
+package gethutil
+
+import (
+	"errors"
+	"fmt"
+
+	"github.com/ethereum/go-ethereum/core"
+)
+
+// Codes of the errors of the tracing, so that the callers can tell the
+// transactions failing their validation from a bug of the config or of the
+// environment.
+const (
+	CodeInvalidConfig     = "InvalidConfig"
+	CodeIntrinsicGas      = "IntrinsicGas"
+	CodeNonceTooLow       = "NonceTooLow"
+	CodeNonceTooHigh      = "NonceTooHigh"
+	CodeNonceMax          = "NonceMax"
+	CodeInsufficientFunds = "InsufficientFunds"
+	CodeInvalidTx         = "InvalidTx"
+	CodeEVMError          = "EVMError"
+	CodeTracer            = "Tracer"
+	CodeInternal          = "Internal"
+)
+
+// TraceError is an error of the tracing with its code, and the index of the
+// transaction that failed if the error is about a transaction.
+type TraceError struct {
+	Code    string `json:"code"`
+	TxIndex *int   `json:"txIndex,omitempty"`
+	Message string `json:"message"`
+}
+
+func (e *TraceError) Error() string { return e.Message }
+
+// NewTraceError returns an error that isn't about a transaction.
+func NewTraceError(code string, format string, args ...interface{}) *TraceError {
+	return &TraceError{Code: code, Message: fmt.Sprintf(format, args...)}
+}
+
+// AsTraceError returns err if it's a TraceError, or wraps it in an internal
+// error otherwise.
+func AsTraceError(err error) *TraceError {
+	var traceError *TraceError
+	if errors.As(err, &traceError) {
+		return traceError
+	}
+	return NewTraceError(CodeInternal, "%v", err)
+}
+
+// invalidTxErrors are the other errors of the validation of a transaction by
+// core.ApplyMessage.
+var invalidTxErrors = []error{
+	core.ErrGasLimitReached,
+	core.ErrGasUintOverflow,
+	core.ErrTxTypeNotSupported,
+	core.ErrTipAboveFeeCap,
+	core.ErrTipVeryHigh,
+	core.ErrFeeCapVeryHigh,
+	core.ErrFeeCapTooLow,
+	core.ErrSenderNoEOA,
+	core.ErrMaxInitCodeSizeExceeded,
+}
+
+// txError returns the error of the transaction i failing to apply, with
+// the code of the check it failed.
+func txError(i int, err error) *TraceError {
+	code := CodeEVMError
+	switch {
+	case errors.Is(err, core.ErrIntrinsicGas):
+		code = CodeIntrinsicGas
+	case errors.Is(err, core.ErrNonceTooLow):
+		code = CodeNonceTooLow
+	case errors.Is(err, core.ErrNonceTooHigh):
+		code = CodeNonceTooHigh
+	case errors.Is(err, core.ErrNonceMax):
+		code = CodeNonceMax
+	case errors.Is(err, core.ErrInsufficientFunds), errors.Is(err, core.ErrInsufficientFundsForTransfer):
+		code = CodeInsufficientFunds
+	default:
+		for _, invalidTxError := range invalidTxErrors {
+			if errors.Is(err, invalidTxError) {
+				code = CodeInvalidTx
+			}
+		}
+	}
+	return &TraceError{
+		Code:    code,
+		TxIndex: &i,
+		Message: fmt.Sprintf("Failed to apply config.Transactions[%d]: %v", i, err),
+	}
+}
+
+// tracerError returns the error of the tracer of the transaction i.
+func tracerError(i int, format string, err error) *TraceError {
+	return &TraceError{
+		Code:    CodeTracer,
+		TxIndex: &i,
+		Message: fmt.Sprintf(format, i, err),
+	}
+}
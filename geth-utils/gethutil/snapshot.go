@@ -0,0 +1,70 @@
+package gethutil
+
+import (
+	"bytes"
+	"fmt"
+
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/common/hexutil"
+	"github.com/ethereum/go-ethereum/rlp"
+)
+
+// Formats of a StorageSnapshot
+const (
+	// SnapshotPairs is the format of 32-byte keys and values in pairs sorted
+	// by key.
+	SnapshotPairs = "pairs"
+	// SnapshotRlp is the format of an RLP list of [key, value] lists, with the
+	// values trimmed of their leading zeros as in the storage trie.
+	SnapshotRlp = "rlp"
+)
+
+// StorageSnapshot is the storage of an account in a compact format, for the
+// accounts with too many slots for a map of hex strings.
+type StorageSnapshot struct {
+	Format string        `json:"format"`
+	Data   hexutil.Bytes `json:"data"`
+}
+
+// StorageSlot is a slot of a StorageSnapshot.
+type StorageSlot struct {
+	Key   common.Hash
+	Value common.Hash
+}
+
+// Slots decodes the slots of the snapshot.
+func (s *StorageSnapshot) Slots() ([]StorageSlot, error) {
+	switch s.Format {
+	case SnapshotPairs:
+		if len(s.Data)%64 != 0 {
+			return nil, fmt.Errorf("pairs of %d bytes aren't 64 bytes long", len(s.Data))
+		}
+		slots := make([]StorageSlot, len(s.Data)/64)
+		for i := range slots {
+			slots[i].Key = common.BytesToHash(s.Data[i*64 : i*64+32])
+			slots[i].Value = common.BytesToHash(s.Data[i*64+32 : i*64+64])
+			if i > 0 && bytes.Compare(slots[i-1].Key[:], slots[i].Key[:]) >= 0 {
+				return nil, fmt.Errorf("pairs aren't sorted by key at %d", i)
+			}
+		}
+		return slots, nil
+	case SnapshotRlp:
+		var entries []struct {
+			Key   []byte
+			Value []byte
+		}
+		if err := rlp.DecodeBytes(s.Data, &entries); err != nil {
+			return nil, err
+		}
+		slots := make([]StorageSlot, len(entries))
+		for i, entry := range entries {
+			if len(entry.Key) != 32 || len(entry.Value) > 32 {
+				return nil, fmt.Errorf("slot %d: key isn't 32 bytes or value is over 32 bytes", i)
+			}
+			slots[i].Key = common.BytesToHash(entry.Key)
+			slots[i].Value = common.BytesToHash(entry.Value)
+		}
+		return slots, nil
+	}
+	return nil, fmt.Errorf("unknown storage snapshot format %q", s.Format)
+}
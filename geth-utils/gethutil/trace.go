@@ -1,6 +1,7 @@
 package gethutil
 
 import (
+	"encoding/json"
 	"fmt"
 	"math/big"
 
@@ -11,7 +12,9 @@ import (
 	"github.com/ethereum/go-ethereum/core/state"
 	"github.com/ethereum/go-ethereum/core/types"
 	"github.com/ethereum/go-ethereum/core/vm"
+	"github.com/ethereum/go-ethereum/eth/tracers"
 	"github.com/ethereum/go-ethereum/eth/tracers/logger"
+	_ "github.com/ethereum/go-ethereum/eth/tracers/native"
 	"github.com/ethereum/go-ethereum/params"
 	"github.com/imdario/mergo"
 )
@@ -25,6 +28,16 @@ type ExecutionResult struct {
 	Failed      bool           `json:"failed"`
 	ReturnValue string         `json:"returnValue"`
 	StructLogs  []StructLogRes `json:"structLogs"`
+	Validation  *TxValidation  `json:"validation,omitempty"`
+}
+
+// TxValidation is how geth validated a transaction before running it: the
+// intrinsic gas and gas price it charged, and the error of the checks of the
+// nonce, balance and gas limit of the transaction if it failed them.
+type TxValidation struct {
+	IntrinsicGas      uint64       `json:"intrinsicGas"`
+	EffectiveGasPrice *hexutil.Big `json:"effectiveGasPrice"`
+	Error             string       `json:"error,omitempty"`
 }
 
 // StructLogRes stores a structured log emitted by the EVM while replaying a
@@ -96,6 +109,9 @@ type Account struct {
 	Balance *hexutil.Big                `json:"balance"`
 	Code    hexutil.Bytes               `json:"code"`
 	Storage map[common.Hash]common.Hash `json:"storage"`
+
+	// StorageSnapshot holds more slots, set after the ones of Storage.
+	StorageSnapshot *StorageSnapshot `json:"storage_snapshot"`
 }
 
 type Transaction struct {
@@ -124,11 +140,112 @@ type TraceConfig struct {
 	Transactions  []Transaction              `json:"transactions"`
 	LoggerConfig  *logger.Config             `json:"logger_config"`
 	ChainConfig   *params.ChainConfig        `json:"chain_config"`
+	// Tracer is the name of a tracer registered in geth's tracer directory,
+	// like "callTracer". Empty or StructLogger selects the struct logger.
+	Tracer       string          `json:"tracer"`
+	TracerConfig json.RawMessage `json:"tracer_config"`
+	// IncludeInvalidTxs makes the struct logger return a failed result
+	// without steps for the transactions failing validation, instead of
+	// failing the whole trace.
+	IncludeInvalidTxs bool `json:"include_invalid_txs"`
+}
+
+// StructLogger is the name of the default tracer, whose output is the
+// ExecutionResult.
+const StructLogger = "structLogger"
+
+// StructLoggerWithCalls is the name of the mode running the struct logger
+// along with a CallFrameLogger, whose output is the ExecutionResultWithCalls.
+const StructLoggerWithCalls = "structLoggerWithCalls"
+
+// ExecutionResultWithCalls groups the result of the struct logger with the
+// tree of the call frames of the same run of a transaction.
+type ExecutionResultWithCalls struct {
+	Trace *ExecutionResult `json:"trace"`
+	Call  *CallFrame       `json:"call"`
+}
+
+// IsStructLogger returns whether the config selects the struct logger.
+func (config *TraceConfig) IsStructLogger() bool {
+	return config.Tracer == "" || config.Tracer == StructLogger
 }
 
 func newUint64(val uint64) *uint64 { return &val }
 
+func newExecutionResult(result *core.ExecutionResult, validation *TxValidation, tracer *logger.StructLogger) *ExecutionResult {
+	return &ExecutionResult{
+		Gas:         result.UsedGas,
+		Failed:      result.Failed(),
+		ReturnValue: fmt.Sprintf("%x", result.ReturnData),
+		StructLogs:  FormatLogs(tracer.StructLogs()),
+		Validation:  validation,
+	}
+}
+
+// Trace runs the transactions with the struct logger.
 func Trace(config TraceConfig) ([]*ExecutionResult, error) {
+	executionResults := make([]*ExecutionResult, len(config.Transactions))
+	err := applyTransactions(config, func(i int) (vm.EVMLogger, func(*core.ExecutionResult, *TxValidation) error, error) {
+		tracer := logger.NewStructLogger(config.LoggerConfig)
+		return tracer, func(result *core.ExecutionResult, validation *TxValidation) error {
+			executionResults[i] = newExecutionResult(result, validation, tracer)
+			return nil
+		}, nil
+	})
+	if err != nil {
+		return nil, err
+	}
+
+	return executionResults, nil
+}
+
+// TraceWithCalls runs the transactions with the struct logger and a
+// CallFrameLogger.
+func TraceWithCalls(config TraceConfig) ([]*ExecutionResultWithCalls, error) {
+	results := make([]*ExecutionResultWithCalls, len(config.Transactions))
+	err := applyTransactions(config, func(i int) (vm.EVMLogger, func(*core.ExecutionResult, *TxValidation) error, error) {
+		structLogger := logger.NewStructLogger(config.LoggerConfig)
+		callLogger := &CallFrameLogger{}
+		return multiLogger{structLogger, callLogger}, func(result *core.ExecutionResult, validation *TxValidation) error {
+			results[i] = &ExecutionResultWithCalls{
+				Trace: newExecutionResult(result, validation, structLogger),
+				Call: callLogger.Root(),
+			}
+			return nil
+		}, nil
+	})
+	if err != nil {
+		return nil, err
+	}
+
+	return results, nil
+}
+
+// TraceWithTracer runs the transactions with config.Tracer and returns the
+// result of the tracer for each of them.
+func TraceWithTracer(config TraceConfig) ([]json.RawMessage, error) {
+	results := make([]json.RawMessage, len(config.Transactions))
+	err := applyTransactions(config, func(i int) (vm.EVMLogger, func(*core.ExecutionResult, *TxValidation) error, error) {
+		tracer, err := tracers.DefaultDirectory.New(config.Tracer, &tracers.Context{TxIndex: i}, config.TracerConfig)
+		if err != nil {
+			return nil, nil, err
+		}
+		return tracer, func(*core.ExecutionResult, *TxValidation) error {
+			results[i], err = tracer.GetResult()
+			return err
+		}, nil
+	})
+	if err != nil {
+		return nil, err
+	}
+
+	return results, nil
+}
+
+// applyTransactions applies the transactions of the config in order. For each
+// transaction, newTracer returns the tracer to run and a function collecting
+// its output and validation once the transaction is applied.
+func applyTransactions(config TraceConfig, newTracer func(i int) (vm.EVMLogger, func(*core.ExecutionResult, *TxValidation) error, error)) error {
 	chainConfig := params.ChainConfig{
 		ChainID:             toBigInt(config.ChainID),
 		HomesteadBlock:      big.NewInt(0),
@@ -186,7 +303,7 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		txsGasLimit += uint64(tx.GasLimit)
 	}
 	if txsGasLimit > blockGasLimit {
-		return nil, fmt.Errorf("txs total gas: %d Exceeds block gas limit: %d", txsGasLimit, blockGasLimit)
+		return NewTraceError(CodeInvalidConfig, "txs total gas: %d Exceeds block gas limit: %d", txsGasLimit, blockGasLimit)
 	}
 
 	// For opcode PREVRANDAO
@@ -223,28 +340,52 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		for key, value := range account.Storage {
 			stateDB.SetState(address, key, value)
 		}
+		if account.StorageSnapshot != nil {
+			slots, err := account.StorageSnapshot.Slots()
+			if err != nil {
+				return NewTraceError(CodeInvalidConfig, "Failed to load the storage snapshot of %v: %v", address, err)
+			}
+			for _, slot := range slots {
+				stateDB.SetState(address, slot.Key, slot.Value)
+			}
+		}
 	}
 	stateDB.Finalise(true)
 
+	rules := chainConfig.Rules(blockCtx.BlockNumber, blockCtx.Random != nil, blockCtx.Time)
+
 	// Run the transactions with tracing enabled.
-	executionResults := make([]*ExecutionResult, len(config.Transactions))
 	for i, message := range messages {
-		tracer := logger.NewStructLogger(config.LoggerConfig)
+		tracer, collect, err := newTracer(i)
+		if err != nil {
+			return tracerError(i, "Failed to create tracer for config.Transactions[%d]: %v", err)
+		}
 		evm := vm.NewEVM(blockCtx, core.NewEVMTxContext(&message), stateDB, &chainConfig, vm.Config{Debug: true, Tracer: tracer, NoBaseFee: true})
 
+		validation := &TxValidation{EffectiveGasPrice: (*hexutil.Big)(message.GasPrice)}
+		validation.IntrinsicGas, err = core.IntrinsicGas(message.Data, message.AccessList, message.To == nil, rules.IsHomestead, rules.IsIstanbul, rules.IsShanghai)
+		if err != nil {
+			validation.Error = err.Error()
+		}
+
+		// ApplyMessage only returns the errors of the validation, which may
+		// fail after the gas is bought, so its changes are reverted.
+		snapshot := stateDB.Snapshot()
 		result, err := core.ApplyMessage(evm, &message, new(core.GasPool).AddGas(message.GasLimit))
 		if err != nil {
-			return nil, fmt.Errorf("Failed to apply config.Transactions[%d]: %w", i, err)
+			if !config.IncludeInvalidTxs || !config.IsStructLogger() {
+				return txError(i, err)
+			}
+			stateDB.RevertToSnapshot(snapshot)
+			validation.Error = err.Error()
+			result = &core.ExecutionResult{Err: err}
 		}
 		stateDB.Finalise(true)
 
-		executionResults[i] = &ExecutionResult{
-			Gas:         result.UsedGas,
-			Failed:      result.Failed(),
-			ReturnValue: fmt.Sprintf("%x", result.ReturnData),
-			StructLogs:  FormatLogs(tracer.StructLogs()),
+		if err := collect(result, validation); err != nil {
+			return tracerError(i, "Failed to get the trace of config.Transactions[%d]: %v", err)
 		}
 	}
 
-	return executionResults, nil
+	return nil
 }
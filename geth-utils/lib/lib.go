@@ -6,29 +6,95 @@ package main
 import "C"
 import (
 	"encoding/json"
-	"fmt"
 	"main/gethutil"
+	"sync"
 	"unsafe"
 )
 
-// TODO: Add proper error handling.  For example, return an int, where 0 means
-// ok, and !=0 means error.
+// runTrace runs the tracer selected by the config.
+func runTrace(config gethutil.TraceConfig) (interface{}, error) {
+	if config.IsStructLogger() {
+		return gethutil.Trace(config)
+	} else if config.Tracer == gethutil.StructLoggerWithCalls {
+		return gethutil.TraceWithCalls(config)
+	}
+	return gethutil.TraceWithTracer(config)
+}
+
+// errorString returns the JSON of a TraceError. The output of a trace is an
+// array, so the caller tells an error by its output being an object.
+func errorString(err *gethutil.TraceError) *C.char {
+	bytes, _ := json.Marshal(err)
+	return C.CString(string(bytes))
+}
+
 //export CreateTrace
 func CreateTrace(configStr *C.char) *C.char {
 	var config gethutil.TraceConfig
 	err := json.Unmarshal([]byte(C.GoString(configStr)), &config)
 	if err != nil {
-		return C.CString(fmt.Sprintf("Failed to unmarshal config, err: %v", err))
+		return errorString(gethutil.NewTraceError(gethutil.CodeInvalidConfig, "Failed to unmarshal config, err: %v", err))
 	}
 
-	executionResults, err := gethutil.Trace(config)
+	results, err := runTrace(config)
 	if err != nil {
-		return C.CString(fmt.Sprintf("Failed to run Trace, err: %v", err))
+		return errorString(gethutil.AsTraceError(err))
+	}
+
+	bytes, err := json.MarshalIndent(results, "", "  ")
+	if err != nil {
+		return errorString(gethutil.NewTraceError(gethutil.CodeInternal, "Failed to marshal trace results, err: %v", err))
+	}
+
+	return C.CString(string(bytes))
+}
+
+// batchResult is the output of a config of a batch, or the error failing it.
+type batchResult struct {
+	Result interface{}          `json:"result,omitempty"`
+	Error  *gethutil.TraceError `json:"error,omitempty"`
+}
+
+// CreateTraces runs the tracers of an array of independent configs, with at
+// most `workers` of them running at once. A config failing doesn't fail the
+// others, its error is returned in place of its output.
+//export CreateTraces
+func CreateTraces(configsStr *C.char, workers C.int) *C.char {
+	var configs []gethutil.TraceConfig
+	err := json.Unmarshal([]byte(C.GoString(configsStr)), &configs)
+	if err != nil {
+		return errorString(gethutil.NewTraceError(gethutil.CodeInvalidConfig, "Failed to unmarshal configs, err: %v", err))
+	}
+	if workers < 1 {
+		workers = 1
+	}
+
+	results := make([]batchResult, len(configs))
+	jobs := make(chan int)
+	var wg sync.WaitGroup
+	for w := 0; w < int(workers); w++ {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for i := range jobs {
+				result, err := runTrace(configs[i])
+				if err != nil {
+					results[i].Error = gethutil.AsTraceError(err)
+				} else {
+					results[i].Result = result
+				}
+			}
+		}()
+	}
+	for i := range configs {
+		jobs <- i
 	}
+	close(jobs)
+	wg.Wait()
 
-	bytes, err := json.MarshalIndent(executionResults, "", "  ")
+	bytes, err := json.Marshal(results)
 	if err != nil {
-		return C.CString(fmt.Sprintf("Failed to marshal []ExecutionResult, err: %v", err))
+		return errorString(gethutil.NewTraceError(gethutil.CodeInternal, "Failed to marshal trace results, err: %v", err))
 	}
 
 	return C.CString(string(bytes))
@@ -3,21 +3,36 @@
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use std::{
     ffi::{CStr, CString},
-    os::raw::c_char,
+    os::raw::{c_char, c_int},
 };
 
 extern "C" {
     fn CreateTrace(str: *const c_char) -> *const c_char;
+    fn CreateTraces(str: *const c_char, workers: c_int) -> *const c_char;
     fn FreeString(str: *const c_char);
 }
 
 /// Creates the trace
 pub fn trace(config: &str) -> Result<String, Error> {
+    call_go(config, |c_config| unsafe { CreateTrace(c_config) })
+}
+
+/// Creates the traces of a JSON array of independent configs, running at
+/// most `workers` of them at once. The output is an array with the result or
+/// the error object of each config.
+pub fn trace_batch(configs: &str, workers: usize) -> Result<String, Error> {
+    let workers = workers.clamp(1, c_int::MAX as usize) as c_int;
+    call_go(configs, |c_configs| unsafe {
+        CreateTraces(c_configs, workers)
+    })
+}
+
+fn call_go(input: &str, f: impl FnOnce(*const c_char) -> *const c_char) -> Result<String, Error> {
     // Create a string we can pass into Go
-    let c_config = CString::new(config).expect("invalid config");
+    let c_input = CString::new(input).expect("invalid config");
 
     // Generate the trace externally
-    let result = unsafe { CreateTrace(c_config.as_ptr()) };
+    let result = f(c_input.as_ptr());
 
     // Convert the returned string to something we can use in Rust again.
     // Also make sure the returned data is copied to rust managed memory.
@@ -30,8 +45,8 @@ pub fn trace(config: &str) -> Result<String, Error> {
     // We can now free the returned string (memory managed by Go)
     unsafe { FreeString(c_result.as_ptr()) };
 
-    // Return the trace
-    match result.is_empty() || result.starts_with("Failed") {
+    // Return the trace, or the error given as an object
+    match result.is_empty() || result.starts_with('{') {
         true => Err(Error::TracingError(result)),
         false => Ok(result),
     }
@@ -40,7 +55,8 @@ pub fn trace(config: &str) -> Result<String, Error> {
 /// Error type for any geth-utils related failure.
 #[derive(Debug, Clone)]
 pub enum Error {
-    /// Error while tracing.
+    /// Error while tracing, in the JSON of geth-utils: its code, the index of
+    /// the tx that failed if any, and its message.
     TracingError(String),
 }
 
@@ -52,7 +68,7 @@ impl Display for Error {
 
 #[cfg(test)]
 mod test {
-    use crate::trace;
+    use crate::{trace, trace_batch, Error};
 
     #[test]
     fn valid_tx() {
@@ -150,7 +166,68 @@ mod test {
                 ]
             }"#,
         ] {
-            assert!(trace(config).is_err())
+            let Error::TracingError(error) = trace(config).unwrap_err();
+            assert!(error.contains(r#""txIndex":0"#));
         }
     }
+
+    #[test]
+    fn include_invalid_tx() {
+        // Insufficient gas for intrinsic usage
+        let config = r#"{
+            "block_constants": {
+                "gas_limit": "0xcf080"
+            },
+            "transactions": [
+                {
+                    "from": "0x00000000000000000000000000000000000000fe",
+                    "to": "0x00000000000000000000000000000000000000ff"
+                }
+            ],
+            "include_invalid_txs": true
+        }"#;
+        let trace = trace(config).unwrap();
+        assert!(trace.contains(r#""failed":true"#));
+        assert!(trace.contains(r#""structLogs":[]"#));
+        assert!(trace.contains(r#""intrinsicGas":21000"#));
+        assert!(trace.contains(r#""error":"intrinsic gas too low"#));
+    }
+
+    #[test]
+    fn batch_of_txs() {
+        let configs = r#"[
+            {
+                "block_constants": {
+                    "gas_limit": "0x52080"
+                },
+                "accounts": {
+                    "0x00000000000000000000000000000000000000fe": {
+                        "balance": "0x100000000000000000"
+                    }
+                },
+                "transactions": [
+                    {
+                        "from": "0x00000000000000000000000000000000000000fe",
+                        "to": "0x00000000000000000000000000000000000000ff",
+                        "gas_limit": "0x5208"
+                    }
+                ]
+            },
+            {
+                "block_constants": {
+                    "gas_limit": "0xcf080"
+                },
+                "transactions": [
+                    {
+                        "from": "0x00000000000000000000000000000000000000fe",
+                        "to": "0x00000000000000000000000000000000000000ff"
+                    }
+                ]
+            }
+        ]"#;
+        let traces = trace_batch(configs, 2).unwrap();
+        // The invalid tx of the second config doesn't fail the first one.
+        assert!(traces.starts_with(r#"[{"result":"#));
+        assert!(traces.contains(r#"},{"error":{"code":"IntrinsicGas","txIndex":0,"#));
+    }
 }
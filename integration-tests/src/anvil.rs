@@ -0,0 +1,258 @@
+//! Harness to check that contracts are provable by the zkEVM against a local
+//! Anvil node: deploy the contracts, send transactions, pull the traces and
+//! the state of the resulting block, and run the super circuit on it with the
+//! `MockProver`.
+//!
+//! Requires the `anvil` binary of foundry in `PATH`.
+
+use crate::{
+    integration_test_circuits::{
+        CIRCUITS_PARAMS, MAX_CALLDATA, MAX_INNER_BLOCKS, MAX_TXS, TEST_MOCK_RANDOMNESS,
+    },
+    CompiledContract,
+};
+use bus_mapping::{
+    circuit_input_builder::{get_state_accesses, AccessSet, BlockHead, CircuitInputBuilder},
+    rpc::GethClient,
+    state_db::{self, CodeDB, StateDB},
+};
+use eth_types::{BigEndianHash, ToWord, H256};
+use ethers::{
+    abi::Tokenize,
+    contract::{Contract, ContractFactory},
+    core::types::{transaction::eip2718::TypedTransaction, BlockId, BlockNumber},
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    solc::Solc,
+    utils::{keccak256, Anvil, AnvilInstance},
+};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use std::{path::Path, sync::Arc, time::Duration};
+use url::Url;
+use zkevm_circuits::super_circuit::SuperCircuit;
+
+/// Client signing the transactions with the first Anvil account.
+pub type AnvilClient = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+/// Super circuit run on the blocks of the harness.
+type AnvilSuperCircuit = SuperCircuit<Fr, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>;
+
+/// Compile the contract `name` of the solidity file at `path`.
+pub fn compile_contract(path: &Path, name: &str) -> CompiledContract {
+    let compiled = Solc::default()
+        .compile_source(path)
+        .unwrap_or_else(|_| panic!("solc compile error {:?}", path));
+    if !compiled.errors.is_empty() {
+        panic!("Errors compiling {:?}:\n{:#?}", path, compiled.errors)
+    }
+    let path = path.to_str().expect("path is not str");
+    let contract = compiled.get(path, name).expect("contract not found");
+    CompiledContract {
+        path: path.to_string(),
+        name: name.to_string(),
+        abi: contract.abi.expect("no abi found").clone(),
+        bin: contract
+            .bin
+            .expect("no bin found")
+            .clone()
+            .into_bytes()
+            .expect("bin"),
+        bin_runtime: contract
+            .bin_runtime
+            .expect("no bin_runtime found")
+            .clone()
+            .into_bytes()
+            .expect("bin_runtime"),
+    }
+}
+
+/// A local Anvil node, killed when the harness is dropped.
+pub struct AnvilHarness {
+    anvil: AnvilInstance,
+    client: Arc<AnvilClient>,
+}
+
+impl AnvilHarness {
+    /// Spawn an Anvil node. Blocks are only mined by
+    /// [`AnvilHarness::mine_block`], so that a block can hold several
+    /// transactions.
+    pub async fn new() -> Self {
+        let anvil = Anvil::new().arg("--no-mining").spawn();
+        let provider = Provider::<Http>::try_from(anvil.endpoint())
+            .expect("invalid anvil endpoint")
+            .interval(Duration::from_millis(100));
+        let wallet = LocalWallet::from(anvil.keys()[0].clone()).with_chain_id(anvil.chain_id());
+        Self {
+            client: Arc::new(SignerMiddleware::new(provider, wallet)),
+            anvil,
+        }
+    }
+
+    /// Client to send transactions to the node.
+    pub fn client(&self) -> Arc<AnvilClient> {
+        self.client.clone()
+    }
+
+    /// Deploy a contract in its own block.
+    pub async fn deploy<T: Tokenize>(
+        &self,
+        compiled: &CompiledContract,
+        args: T,
+    ) -> Contract<AnvilClient> {
+        log::info!("Deploying {}...", compiled.name);
+        let factory =
+            ContractFactory::new(compiled.abi.clone(), compiled.bin.clone(), self.client());
+        let deployer = factory.deploy(args).expect("cannot deploy");
+        let tx = self
+            .client
+            .send_transaction(deployer.tx, None)
+            .await
+            .expect("cannot send deploy tx");
+        self.mine_block().await;
+        let receipt = tx
+            .await
+            .expect("cannot confirm deploy")
+            .expect("deploy tx not mined");
+        let address = receipt.contract_address.expect("no contract address");
+        Contract::new(address, compiled.abi.clone(), self.client())
+    }
+
+    /// Send the transactions and mine them in a single block, returning its
+    /// number.
+    pub async fn send_block(&self, txs: Vec<TypedTransaction>) -> u64 {
+        // The nonce of the pending txs is not taken into account when filling
+        // the txs, so set it here.
+        let mut nonce = self
+            .client
+            .get_transaction_count(self.client.address(), None)
+            .await
+            .expect("cannot get nonce");
+        let mut pending = Vec::new();
+        for mut tx in txs {
+            if tx.nonce().is_none() {
+                tx.set_nonce(nonce);
+                nonce += 1.into();
+            }
+            pending.push(
+                self.client
+                    .send_transaction(tx, None)
+                    .await
+                    .expect("cannot send tx"),
+            );
+        }
+        let block_num = self.mine_block().await;
+        for tx in pending {
+            let receipt = tx.await.expect("cannot confirm tx").expect("tx not mined");
+            assert_eq!(
+                receipt.block_number.map(|num| num.as_u64()),
+                Some(block_num),
+                "tx {:?} not mined in block {}",
+                receipt.transaction_hash,
+                block_num
+            );
+        }
+        block_num
+    }
+
+    /// Mine a block with the pending transactions and return its number.
+    pub async fn mine_block(&self) -> u64 {
+        let provider = self.client.provider();
+        let _: String = provider
+            .request("evm_mine", ())
+            .await
+            .expect("cannot mine block");
+        provider
+            .get_block_number()
+            .await
+            .expect("cannot get block number")
+            .as_u64()
+    }
+
+    /// Generate the circuit inputs of block `block_num` from its traces and
+    /// the state at the previous block.
+    pub async fn circuit_input_builder(&self, block_num: u64) -> CircuitInputBuilder {
+        let cli = GethClient::new(Http::new(
+            Url::parse(&self.anvil.endpoint()).expect("invalid anvil endpoint"),
+        ));
+        let eth_block = cli
+            .get_block_by_number(block_num.into())
+            .await
+            .expect("cannot get block");
+        // Anvil does not implement `debug_traceBlockByNumber`.
+        let mut geth_traces = Vec::new();
+        for tx in &eth_block.transactions {
+            geth_traces.extend(
+                cli.trace_tx_by_hash(tx.hash)
+                    .await
+                    .expect("cannot trace tx"),
+            );
+        }
+
+        // Anvil does not implement `eth_getProof` either, so read the
+        // accessed state directly.
+        let access_set: AccessSet = get_state_accesses(&eth_block, &geth_traces)
+            .expect("state accesses")
+            .into();
+        let provider = self.client.provider();
+        let parent = Some(BlockId::Number(BlockNumber::Number((block_num - 1).into())));
+        let mut sdb = StateDB::new();
+        let mut code_db = CodeDB::new();
+        for (address, keys) in access_set.state {
+            let mut storage = std::collections::HashMap::new();
+            for key in keys {
+                let value = provider
+                    .get_storage_at(address, H256::from_uint(&key), parent)
+                    .await
+                    .expect("cannot get storage");
+                storage.insert(key, value.to_word());
+            }
+            let code = provider
+                .get_code(address, parent)
+                .await
+                .expect("cannot get code")
+                .to_vec();
+            let account = state_db::Account {
+                nonce: provider
+                    .get_transaction_count(address, parent)
+                    .await
+                    .expect("cannot get nonce"),
+                balance: provider
+                    .get_balance(address, parent)
+                    .await
+                    .expect("cannot get balance"),
+                storage,
+                keccak_code_hash: H256(keccak256(&code)),
+                code_size: code.len().to_word(),
+                code_hash: code_db.insert(code),
+            };
+            sdb.set_account(&address, account);
+        }
+
+        let head = BlockHead::new(self.anvil.chain_id().into(), Vec::new(), &eth_block)
+            .expect("cannot build block head");
+        let mut builder =
+            CircuitInputBuilder::new_from_headers(CIRCUITS_PARAMS, sdb, code_db, &[head]);
+        builder
+            .handle_block(&eth_block, &geth_traces)
+            .expect("cannot handle block");
+        builder
+    }
+
+    /// Check with the `MockProver` that the super circuit accepts block
+    /// `block_num`.
+    pub async fn check_block(&self, block_num: u64) {
+        let builder = self.circuit_input_builder(block_num).await;
+        let (k, circuit, instance) = AnvilSuperCircuit::build_from_circuit_input_builder(&builder)
+            .expect("cannot build super circuit");
+        log::info!(
+            "checking block #{} with the super circuit, k = {}",
+            block_num,
+            k
+        );
+        let prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+        prover
+            .verify_par()
+            .expect("mock prover verification failed");
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{get_client, GenDataOutput};
+use crate::{get_client, GenDataOutput, PROVER_SEED};
 use bus_mapping::{
-    circuit_input_builder::{BuilderClient, CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{BuilderClient, CircuitInputBuilder, CircuitProfile, CircuitsParams},
     mock::BlockData,
 };
 use eth_types::geth_types::GethData;
@@ -17,13 +17,13 @@ use halo2_proofs::{
         },
     },
     transcript::{
-        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        Blake2bRead, Blake2bWrite, Challenge255, Transcript, TranscriptReadBuffer,
+        TranscriptWriterBuffer,
     },
 };
 use lazy_static::lazy_static;
 use mock::TestContext;
 use rand_chacha::rand_core::SeedableRng;
-use rand_core::RngCore;
 use rand_xorshift::XorShiftRng;
 use std::{collections::HashMap, marker::PhantomData, sync::Mutex};
 use tokio::sync::Mutex as TokioMutex;
@@ -41,12 +41,12 @@ use zkevm_circuits::{
 };
 
 /// TEST_MOCK_RANDOMNESS
-const TEST_MOCK_RANDOMNESS: u64 = 0x100;
+pub(crate) const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
 /// MAX_TXS
-const MAX_TXS: usize = 4;
+pub(crate) const MAX_TXS: usize = 4;
 /// MAX_CALLDATA
-const MAX_CALLDATA: usize = 512;
+pub(crate) const MAX_CALLDATA: usize = 512;
 /// MAX_RLP_ROWS
 const MAX_RLP_ROWS: usize = 1000;
 /// MAX_RWS
@@ -64,7 +64,7 @@ const MAX_EXP_STEPS: usize = 1000;
 
 const MAX_KECCAK_ROWS: usize = 15000;
 
-const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
+pub(crate) const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_rws: MAX_RWS,
     max_txs: MAX_TXS,
     max_calldata: MAX_CALLDATA,
@@ -75,6 +75,10 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_evm_rows: MAX_EVM_ROWS,
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
+    keccak_rows_per_round: 0,
+    max_ecrecover_sigs: 0,
+    max_log_items: 0,
+    profile: CircuitProfile::Full,
     max_rlp_rows: MAX_RLP_ROWS,
 };
 
@@ -90,10 +94,20 @@ const EXP_CIRCUIT_DEGREE: u32 = 16;
 lazy_static! {
     /// Data generation.
     static ref GEN_DATA: GenDataOutput = GenDataOutput::load();
-    static ref RNG: XorShiftRng = XorShiftRng::from_seed([
-        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
-        0xe5,
-    ]);
+}
+
+/// Rng used to generate the params and the proofs from a seed
+fn seeded_rng(seed: u64) -> XorShiftRng {
+    XorShiftRng::seed_from_u64(seed)
+}
+
+/// A proof with the seed it was generated with. The seed is absorbed in the
+/// transcript before the proof, so the verifier needs it too.
+pub struct TestProof {
+    /// Seed of the rng used by the prover
+    pub seed: u64,
+    /// Proof bytes
+    pub proof: Vec<u8>,
 }
 
 lazy_static! {
@@ -126,7 +140,7 @@ lazy_static! {
     TokioMutex::new(IntegrationTest::new("Keccak", KECCAK_CIRCUIT_DEGREE));
 
     /// Integration test for Copy circuit
-    pub static ref SUPER_CIRCUIT_TEST: TokioMutex<IntegrationTest<SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>>> =
+    pub static ref SUPER_CIRCUIT_TEST: TokioMutex<IntegrationTest<SuperCircuit::<Fr, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>>> =
     TokioMutex::new(IntegrationTest::new("Super", SUPER_CIRCUIT_DEGREE));
 
      /// Integration test for Exp circuit
@@ -173,19 +187,22 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
     }
 
     fn test_actual(&self, circuit: C, instance: Vec<Vec<Fr>>, proving_key: ProvingKey<G1Affine>) {
-        fn test_gen_proof<C: Circuit<Fr>, R: RngCore>(
-            rng: R,
+        fn test_gen_proof<C: Circuit<Fr>>(
+            seed: u64,
             circuit: C,
             general_params: &ParamsKZG<Bn256>,
             proving_key: &ProvingKey<G1Affine>,
             mut transcript: Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
             instances: &[&[Fr]],
-        ) -> Vec<u8> {
+        ) -> TestProof {
+            transcript
+                .common_scalar(Fr::from(seed))
+                .expect("absorbing the seed should not fail");
             create_proof::<
                 KZGCommitmentScheme<Bn256>,
                 ProverSHPLONK<'_, Bn256>,
                 Challenge255<G1Affine>,
-                R,
+                XorShiftRng,
                 Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
                 C,
             >(
@@ -193,22 +210,29 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
                 proving_key,
                 &[circuit],
                 &[instances],
-                rng,
+                seeded_rng(seed),
                 &mut transcript,
             )
             .expect("proof generation should not fail");
 
-            transcript.finalize()
+            TestProof {
+                seed,
+                proof: transcript.finalize(),
+            }
         }
 
         fn test_verify(
             general_params: &ParamsKZG<Bn256>,
             verifier_params: &ParamsKZG<Bn256>,
             verifying_key: &VerifyingKey<G1Affine>,
-            proof: &[u8],
+            proof: &TestProof,
             instances: &[&[Fr]],
         ) {
-            let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+            let mut verifier_transcript =
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.proof.as_slice());
+            verifier_transcript
+                .common_scalar(Fr::from(proof.seed))
+                .expect("absorbing the seed should not fail");
             let strategy = SingleStrategy::new(general_params);
 
             verify_proof::<
@@ -235,8 +259,13 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
         // change instace to slice
         let instance: Vec<&[Fr]> = instance.iter().map(|v| v.as_slice()).collect();
 
+        log::info!(
+            "proving {} circuit with PROVER_SEED={}",
+            self.name,
+            *PROVER_SEED
+        );
         let proof = test_gen_proof(
-            RNG.clone(),
+            *PROVER_SEED,
             circuit,
             &general_params,
             &proving_key,
@@ -325,7 +354,7 @@ fn get_general_params(degree: u32) -> ParamsKZG<Bn256> {
     match map.get(&degree) {
         Some(params) => params.clone(),
         None => {
-            let params = ParamsKZG::<Bn256>::setup(degree, RNG.clone());
+            let params = ParamsKZG::<Bn256>::setup(degree, seeded_rng(*PROVER_SEED));
             map.insert(degree, params.clone());
             params
         }
@@ -72,6 +72,13 @@ lazy_static! {
         Err(VarError::NotPresent) => "super".to_string(),
         Err(e) => panic!("Error in CIRCUIT env var: {:?}", e),
     };
+    /// Seed of the rng used to generate the params and the proofs, so that a
+    /// proof or a failure can be reproduced bit-for-bit on another machine.
+    pub static ref PROVER_SEED: u64 =  match env::var("PROVER_SEED") {
+        Ok(val) => str::parse::<u64>(&val).unwrap(),
+        Err(VarError::NotPresent) => 0x5962be5d763d318d,
+        Err(e) => panic!("Error in PROVER_SEED env var: {:?}", e),
+    };
 
 }
 
@@ -164,5 +171,7 @@ pub struct CompiledContract {
     pub bin_runtime: Bytes,
 }
 
+/// Harness to prove blocks of a local Anvil node.
+pub mod anvil;
 /// Common code for integration tests of circuits.
 pub mod integration_test_circuits;
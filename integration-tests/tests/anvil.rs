@@ -0,0 +1,35 @@
+#![cfg(feature = "anvil")]
+
+use ethers::core::types::U256;
+use integration_tests::{
+    anvil::{compile_contract, AnvilHarness},
+    log_init, CONTRACTS_PATH,
+};
+use std::path::Path;
+
+#[tokio::test]
+async fn test_anvil_greeter() {
+    log_init();
+    let harness = AnvilHarness::new().await;
+    let compiled = compile_contract(
+        &Path::new(CONTRACTS_PATH).join("greeter/Greeter.sol"),
+        "Greeter",
+    );
+    let greeter = harness.deploy(&compiled, U256::from(42)).await;
+
+    let set_value = greeter
+        .method::<_, ()>("set_value", U256::from(7))
+        .expect("cannot construct set_value call")
+        .legacy()
+        .gas(100_000)
+        .tx;
+    let retrieve = greeter
+        .method::<_, U256>("retrieve", ())
+        .expect("cannot construct retrieve call")
+        .legacy()
+        .gas(100_000)
+        .tx;
+    let block_num = harness.send_block(vec![set_value, retrieve]).await;
+
+    harness.check_block(block_num).await;
+}
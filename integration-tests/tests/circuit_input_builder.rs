@@ -1,7 +1,7 @@
 #![cfg(feature = "circuit_input_builder")]
 
 use bus_mapping::circuit_input_builder::{
-    build_state_code_db, get_state_accesses, BuilderClient, CircuitsParams,
+    build_state_code_db, get_state_accesses, BuilderClient, CircuitProfile, CircuitsParams,
 };
 use integration_tests::{get_client, log_init, GenDataOutput};
 use lazy_static::lazy_static;
@@ -26,6 +26,10 @@ async fn test_circuit_input_builder_block(block_num: u64) {
             max_evm_rows: 0,
             max_exp_steps: 1000,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_rlp_rows: 4200,
         },
     )
@@ -1,5 +1,5 @@
 use bus_mapping::{
-    circuit_input_builder::{keccak_inputs, BuilderClient, CircuitsParams},
+    circuit_input_builder::{keccak_inputs, BuilderClient, CircuitProfile, CircuitsParams},
     Error::JSONRpcError,
 };
 use halo2_proofs::{
@@ -11,7 +11,7 @@ use halo2_proofs::{
 use integration_tests::{get_client, log_init, CIRCUIT, END_BLOCK, START_BLOCK, TX_ID};
 use zkevm_circuits::{
     evm_circuit::{witness::block_convert, EvmCircuit},
-    keccak_circuit::keccak_packed_multi::multi_keccak,
+    keccak_circuit::keccak_packed_multi::{get_num_rows_per_round, multi_keccak},
     rlp_circuit_fsm::RlpCircuit,
     state_circuit::StateCircuit,
     super_circuit::SuperCircuit,
@@ -30,6 +30,10 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_bytecode: 30000,
     max_mpt_rows: 30000,
     max_keccak_rows: 0,
+    keccak_rows_per_round: 0,
+    max_ecrecover_sigs: 0,
+    max_log_items: 0,
+    profile: CircuitProfile::Full,
     max_exp_steps: 1000,
     max_evm_rows: 0,
     max_rlp_rows: 33000,
@@ -53,6 +57,10 @@ async fn test_mock_prove_tx() {
         max_inner_blocks: 64,
         max_bytecode: 40000,
         max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
         max_exp_steps: 5000,
         max_evm_rows: 0,
         max_rlp_rows: 42000,
@@ -93,7 +101,7 @@ fn test_witness_block(block: &witness::Block<Fr>) -> Vec<VerifyFailure> {
     } else if *CIRCUIT == "state" {
         test_with::<StateCircuit<Fr>>(block)
     } else if *CIRCUIT == "super" {
-        test_with::<SuperCircuit<Fr, 350, 2_000_000, 64, 0x1000>>(block)
+        test_with::<SuperCircuit<Fr, 64, 0x1000>>(block)
     } else {
         unimplemented!()
     };
@@ -120,6 +128,10 @@ async fn test_circuit_all_block() {
             max_bytecode: 3_000_000,
             max_mpt_rows: 2_000_000,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_exp_steps: 100_000,
             max_evm_rows: 0,
             max_rlp_rows: 2_070_000,
@@ -179,9 +191,14 @@ async fn test_print_circuits_size() {
             Value::known(block.randomness),
             Value::known(block.randomness),
         );
-        let keccak_rows = multi_keccak(&keccak_inputs, challenges, None)
-            .unwrap()
-            .len();
+        let keccak_rows = multi_keccak(
+            &keccak_inputs,
+            challenges,
+            None,
+            get_num_rows_per_round(block.circuits_params.keccak_rows_per_round),
+        )
+        .unwrap()
+        .len();
         log::info!(
             "block number: {}, evm row {}, keccak row {}",
             block_num,
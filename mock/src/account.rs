@@ -28,6 +28,7 @@ impl From<MockAccount> for Account {
             balance: mock.balance,
             code: mock.code,
             storage: mock.storage,
+            storage_snapshot: None,
         }
     }
 }
@@ -0,0 +1,135 @@
+//! Generator of a tx running a tree of calls, to check the reversion of the
+//! writes of nested calls.
+//!
+//! The contract of each call of the tree writes to its storage, calls its
+//! children in order, writes to its storage again, and then stops or reverts.
+//! A reverted call reverts the writes of its successful callees along with its
+//! own, while the writes of a failed callee are already reverted when it
+//! returns, so mixing both at several depths covers the ways the reversible
+//! writes of a tx interleave.
+
+use crate::{eth, TestContext, MOCK_ACCOUNTS};
+use eth_types::{bytecode, evm_types::OpcodeId, Address, Bytecode, Error, ToWord, Word};
+
+/// Gas limit of the tx of [`call_tree`]
+pub const CALL_TREE_GAS: u64 = 1_000_000;
+
+/// Call of a tree run by [`call_tree`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallNode {
+    /// Whether the call ends with `REVERT` instead of `STOP`
+    pub reverts: bool,
+    /// Value transferred by the call
+    pub value: u64,
+    /// Calls made by this one, in order
+    pub children: Vec<CallNode>,
+}
+
+impl CallNode {
+    /// Call stopping after its children
+    pub fn stop(children: Vec<CallNode>) -> Self {
+        Self {
+            reverts: false,
+            value: 0,
+            children,
+        }
+    }
+
+    /// Call reverting after its children
+    pub fn revert(children: Vec<CallNode>) -> Self {
+        Self {
+            reverts: true,
+            value: 0,
+            children,
+        }
+    }
+
+    /// Sets the value transferred by the call, ignored for the root call
+    pub fn value(mut self, value: u64) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Number of calls of the tree
+    pub fn num_calls(&self) -> usize {
+        1 + self.children.iter().map(CallNode::num_calls).sum::<usize>()
+    }
+
+    /// Pushes the address and the code of the contract of each call of the
+    /// tree, in pre-order.
+    fn contracts(&self, contracts: &mut Vec<(Address, Bytecode)>) {
+        let index = contracts.len();
+        contracts.push((call_tree_address(index), Bytecode::default()));
+
+        let mut code = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+        };
+        for child in &self.children {
+            let child_address = call_tree_address(contracts.len());
+            child.contracts(contracts);
+            code.append(&bytecode! {
+                PUSH1(0) // retLength
+                PUSH1(0) // retOffset
+                PUSH1(0) // argsLength
+                PUSH1(0) // argsOffset
+                PUSH32(Word::from(child.value)) // value
+                PUSH20(child_address.to_word()) // addr
+                GAS // gas
+                CALL
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH1(2)
+            PUSH1(1)
+            SSTORE
+            PUSH1(0)
+            PUSH1(0)
+        });
+        code.write_op(if self.reverts {
+            OpcodeId::REVERT
+        } else {
+            OpcodeId::STOP
+        });
+        contracts[index].1 = code;
+    }
+}
+
+/// Address of the contract of the call at `index` in the pre-order of the
+/// tree, the root being at 0.
+pub fn call_tree_address(index: usize) -> Address {
+    Address::from_low_u64_be(0x1000 + index as u64)
+}
+
+/// Returns a context of a tx running the calls of `tree`, with an account per
+/// call followed by the account sending the tx. `NACC` must be the
+/// [`CallNode::num_calls`] of the tree plus one.
+pub fn call_tree<const NACC: usize>(tree: &CallNode) -> Result<TestContext<NACC, 1>, Error> {
+    assert_eq!(
+        NACC,
+        tree.num_calls() + 1,
+        "one account per call and the sender"
+    );
+    let mut contracts = Vec::with_capacity(tree.num_calls());
+    tree.contracts(&mut contracts);
+
+    TestContext::new(
+        None,
+        |mut accs| {
+            let (sender, calls) = accs.split_last_mut().unwrap();
+            for (acc, (address, code)) in calls.iter_mut().zip(contracts) {
+                acc.address(address).balance(eth(1)).code(code);
+            }
+            sender.address(MOCK_ACCOUNTS[0]).balance(eth(10));
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[NACC - 1].address)
+                .to(accs[0].address)
+                .gas(Word::from(CALL_TREE_GAS));
+        },
+        |block, _txs| block.number(0xcafeu64),
+    )
+}
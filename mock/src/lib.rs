@@ -7,13 +7,19 @@ use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 mod account;
 mod block;
+pub mod call_tree;
+pub mod stress;
 pub mod test_ctx;
 mod transaction;
+mod user_operation;
 
 pub(crate) use account::MockAccount;
 pub(crate) use block::MockBlock;
 pub use test_ctx::TestContext;
 pub use transaction::{AddrOrWallet, MockTransaction, CORRECT_MOCK_TXS};
+pub use user_operation::{
+    bundle_call_data, generate_entry_point_bytecode, MockUserOperation, USER_OPERATION_EVENT_TOPIC,
+};
 
 /// Mock block gas limit
 pub const MOCK_BLOCK_GAS_LIMIT: u64 = 10_000_000_000_000_000;
@@ -0,0 +1,175 @@
+//! Generators of the extreme cases of the EVM, to check that the parameters
+//! of the circuits are sized for them and that their error paths are handled.
+//!
+//! Each generator returns a [`TestContext`] of a single tx calling the
+//! generated contract, with the accounts of
+//! [`TestContext::simple_ctx_with_bytecode`], so that the tests and the
+//! benchmarks can use them as is.
+
+use crate::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+use eth_types::{
+    bytecode,
+    evm_types::{gas_utils::memory_expansion_gas_cost, GasCost},
+    Bytecode, Bytes, Error, ToBigEndian, Word,
+};
+
+/// Maximum depth of the calls: the call of the tx is at depth 1, and a call
+/// made at a depth above this one fails.
+pub const MAX_CALL_DEPTH: usize = 1024;
+
+/// Gas limit of the tx of [`call_chain`], enough for the 63/64 of the gas
+/// given to each call to reach [`MAX_CALL_DEPTH`].
+pub const CALL_CHAIN_GAS: u64 = 1_000_000_000_000;
+
+/// Gas limit of a block of mainnet, to size the memory expansion and the log
+/// data of a tx filling a block.
+pub const MAINNET_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Bytecode of a contract calling itself, until the number of frames given as
+/// its calldata is reached.
+pub fn call_chain_bytecode() -> Bytecode {
+    // Calls itself with calldata - 1, with all its gas.
+    let recurse = bytecode! {
+        PUSH1(1)
+        SWAP1
+        SUB
+        PUSH1(0)
+        MSTORE
+        PUSH1(0) // retLength
+        PUSH1(0) // retOffset
+        PUSH1(0x20) // argsLength
+        PUSH1(0) // argsOffset
+        PUSH1(0) // value
+        ADDRESS
+        GAS
+        CALL
+        POP
+    };
+    let mut code = bytecode! {
+        PUSH1(0)
+        CALLDATALOAD
+        PUSH1(1)
+        DUP2
+        GT
+        ISZERO
+    };
+    // Skips the call when the calldata is 1 or less.
+    let end = code.code().len() + 4 + recurse.code().len();
+    code.append(&bytecode! {
+        PUSH2(end)
+        JUMPI
+    });
+    code.append(&recurse);
+    code.append(&bytecode! {
+        JUMPDEST
+        STOP
+    });
+    code
+}
+
+/// Returns a context of a chain of `frames` nested calls, the call of the tx
+/// included. A chain of more than [`MAX_CALL_DEPTH`] + 1 frames stops at the
+/// maximum depth, where the call fails with a depth error.
+pub fn call_chain(frames: usize) -> Result<TestContext<2, 1>, Error> {
+    stress_tx(
+        call_chain_bytecode(),
+        CALL_CHAIN_GAS,
+        Word::from(frames).to_be_bytes().to_vec().into(),
+    )
+}
+
+/// Returns a context of the deepest chain of calls, where the last call fails
+/// with a depth error.
+pub fn max_depth_call_chain() -> Result<TestContext<2, 1>, Error> {
+    call_chain(MAX_CALL_DEPTH + 2)
+}
+
+/// Returns the largest `n` up to `max` for which `gas_cost(n)` is at most
+/// `gas`, given that `gas_cost` increases.
+fn max_affordable(gas: u64, max: u64, gas_cost: impl Fn(u64) -> u64) -> u64 {
+    let (mut low, mut high) = (0, max);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if gas_cost(mid) <= gas {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// Bytecode expanding the memory to `memory_word_size` words with a single
+/// `MSTORE8`.
+pub fn memory_expansion_bytecode(memory_word_size: u64) -> Bytecode {
+    bytecode! {
+        PUSH1(0xff)
+        PUSH32(Word::from(memory_word_size * 32).saturating_sub(Word::one()))
+        MSTORE8
+        STOP
+    }
+}
+
+/// Returns the largest memory, in words, that a tx with `gas_limit` can expand
+/// with [`memory_expansion_bytecode`].
+pub fn max_memory_word_size(gas_limit: u64) -> u64 {
+    let gas = gas_limit.saturating_sub(GasCost::TX.as_u64() + 3 * GasCost::FASTEST.as_u64());
+    max_affordable(gas, u32::MAX as u64, |memory_word_size| {
+        memory_expansion_gas_cost(0, memory_word_size)
+    })
+}
+
+/// Returns a context of a tx with `gas_limit` expanding the memory as much as
+/// it can, or a word more than it can if `out_of_gas`.
+pub fn memory_expansion(gas_limit: u64, out_of_gas: bool) -> Result<TestContext<2, 1>, Error> {
+    let memory_word_size = max_memory_word_size(gas_limit) + u64::from(out_of_gas);
+    stress_tx(
+        memory_expansion_bytecode(memory_word_size),
+        gas_limit,
+        Bytes::default(),
+    )
+}
+
+/// Bytecode logging `size` bytes of memory with a `LOG0`.
+pub fn log_bytecode(size: u64) -> Bytecode {
+    bytecode! {
+        PUSH32(size)
+        PUSH1(0)
+        LOG0
+        STOP
+    }
+}
+
+/// Returns the largest data, in bytes, that a tx with `gas_limit` can log with
+/// [`log_bytecode`].
+pub fn max_log_size(gas_limit: u64) -> u64 {
+    let gas = gas_limit.saturating_sub(
+        GasCost::TX.as_u64() + 2 * GasCost::FASTEST.as_u64() + GasCost::LOG.as_u64(),
+    );
+    max_affordable(gas, u32::MAX as u64, |size| {
+        // 8 gas per byte of data
+        8 * size + memory_expansion_gas_cost(0, (size + 31) / 32)
+    })
+}
+
+/// Returns a context of a tx with `gas_limit` logging as much data as it can,
+/// or a byte more than it can if `out_of_gas`.
+pub fn huge_log(gas_limit: u64, out_of_gas: bool) -> Result<TestContext<2, 1>, Error> {
+    let size = max_log_size(gas_limit) + u64::from(out_of_gas);
+    stress_tx(log_bytecode(size), gas_limit, Bytes::default())
+}
+
+fn stress_tx(code: Bytecode, gas_limit: u64, input: Bytes) -> Result<TestContext<2, 1>, Error> {
+    TestContext::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .input(input)
+                .gas(Word::from(gas_limit));
+        },
+        |block, _txs| block.number(0xcafeu64),
+    )
+}
@@ -3,13 +3,14 @@
 use crate::{eth, MockAccount, MockBlock, MockTransaction};
 use eth_types::{
     geth_types::{Account, BlockConstants, GethData},
-    BigEndianHash, Block, Bytecode, Error, GethExecTrace, Transaction, Word, H256,
+    BigEndianHash, Block, Bytecode, CallTrace, Error, GethExecTrace, GethExecTraceWithCalls,
+    Transaction, Word, H256,
 };
-use external_tracer::{trace, TraceConfig};
+use external_tracer::{trace, trace_with_calls, TraceConfig};
 use helpers::*;
 use itertools::Itertools;
 
-pub use external_tracer::LoggerConfig;
+pub use external_tracer::{LoggerConfig, TraceFault, TraceFaultKind};
 
 /// TestContext is a type that contains all the information from a block
 /// required to build the circuit inputs.
@@ -216,6 +217,26 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
         )
     }
 
+    /// Injects the faults into the traces, to build negative tests where the
+    /// circuits must reject the witness generated from the corrupted traces.
+    pub fn with_faults(mut self, faults: &[TraceFault]) -> Self {
+        external_tracer::inject_faults(&mut self.geth_traces, faults);
+        self
+    }
+
+    /// Traces the txs again along with their call frames, which give the
+    /// calls of each tx as geth sees them.
+    pub fn call_traces(&self) -> Result<Vec<CallTrace>, Error> {
+        let traces = gen_geth_traces_with_calls(
+            self.chain_id,
+            self.eth_block.clone(),
+            self.accounts.to_vec(),
+            Some(self.history_hashes.clone()),
+            LoggerConfig::default(),
+        )?;
+        Ok(traces.into_iter().map(|trace| trace.call).collect())
+    }
+
     /// Returns a simple TestContext setup with a single tx executing the
     /// bytecode passed as parameters. The balances of the 2 accounts and
     /// addresses are the ones used in [`TestContext::
@@ -231,16 +252,14 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
     }
 }
 
-/// Generates execution traces for the transactions included in the provided
-/// Block
-pub fn gen_geth_traces(
+fn trace_config(
     chain_id: Word,
     block: Block<Transaction>,
     accounts: Vec<Account>,
     history_hashes: Option<Vec<Word>>,
     logger_config: LoggerConfig,
-) -> Result<Vec<GethExecTrace>, Error> {
-    let trace_config = TraceConfig {
+) -> Result<TraceConfig, Error> {
+    Ok(TraceConfig {
         chain_id,
         history_hashes: history_hashes.unwrap_or_default(),
         block_constants: BlockConstants::try_from(&block)?,
@@ -258,11 +277,37 @@ pub fn gen_geth_traces(
         chain_config: Some(external_tracer::ChainConfig::shanghai()),
         #[cfg(not(feature = "shanghai"))]
         chain_config: None,
-    };
+        ..Default::default()
+    })
+}
+
+/// Generates execution traces for the transactions included in the provided
+/// Block
+pub fn gen_geth_traces(
+    chain_id: Word,
+    block: Block<Transaction>,
+    accounts: Vec<Account>,
+    history_hashes: Option<Vec<Word>>,
+    logger_config: LoggerConfig,
+) -> Result<Vec<GethExecTrace>, Error> {
+    let trace_config = trace_config(chain_id, block, accounts, history_hashes, logger_config)?;
     let traces = trace(&trace_config)?;
     Ok(traces)
 }
 
+/// Generates execution traces for the transactions included in the provided
+/// Block, with the call frames of each transaction
+pub fn gen_geth_traces_with_calls(
+    chain_id: Word,
+    block: Block<Transaction>,
+    accounts: Vec<Account>,
+    history_hashes: Option<Vec<Word>>,
+    logger_config: LoggerConfig,
+) -> Result<Vec<GethExecTraceWithCalls>, Error> {
+    let trace_config = trace_config(chain_id, block, accounts, history_hashes, logger_config)?;
+    trace_with_calls(&trace_config)
+}
+
 /// Collection of helper functions which contribute to specific rutines on the
 /// builder pattern used to construct [`TestContext`]s.
 pub mod helpers {
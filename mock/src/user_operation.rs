@@ -0,0 +1,67 @@
+//! Mock EIP-4337 style bundles: a bundler tx calling an EntryPoint contract
+//! that executes a list of user operations.
+
+use eth_types::{bytecode, bytecode::Bytecode, Address, Word};
+use ethers_core::utils::keccak256;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// First topic of the log emitted by the mock EntryPoint after each user
+    /// operation, with the index of the operation as second topic and
+    /// `[success, gas_used]` as data.
+    pub static ref USER_OPERATION_EVENT_TOPIC: Word =
+        Word::from_big_endian(&keccak256("UserOperationEvent(uint256,bool,uint256)"));
+}
+
+/// A user operation executed by the mock EntryPoint: a call to `sender` with
+/// `call_data`, given at most `call_gas_limit` gas.
+#[derive(Clone, Debug, Default)]
+pub struct MockUserOperation {
+    /// The account executing the operation
+    pub sender: Address,
+    /// Gas given to the call to the sender
+    pub call_gas_limit: u64,
+    /// Call data passed to the sender
+    pub call_data: Vec<u8>,
+}
+
+/// Call data of a bundler tx executing `user_ops` with the EntryPoint of
+/// [`generate_entry_point_bytecode`]: the concatenation of their call data.
+pub fn bundle_call_data(user_ops: &[MockUserOperation]) -> Vec<u8> {
+    user_ops
+        .iter()
+        .flat_map(|user_op| user_op.call_data.iter().copied())
+        .collect()
+}
+
+/// Generate the code of a mock EntryPoint executing `user_ops` in order,
+/// given the call data of [`bundle_call_data`].
+///
+/// For each operation, it copies the operation's call data to memory, calls
+/// the sender and emits a [`USER_OPERATION_EVENT_TOPIC`] log. The gas
+/// reported in the log is the difference between the results of the `GAS`
+/// opcodes around the call, so it can be checked against the trace.
+pub fn generate_entry_point_bytecode(user_ops: &[MockUserOperation]) -> Bytecode {
+    let mut code = Bytecode::default();
+    let mut call_data_offset = 0;
+    for (index, user_op) in user_ops.iter().enumerate() {
+        let call_data_length = user_op.call_data.len();
+        code.append(&bytecode! {
+            .op_calldatacopy(0, call_data_offset, call_data_length)
+            GAS
+            .op_call(user_op.call_gas_limit, user_op.sender, 0, 0, call_data_length, 0, 0)
+            GAS // gas_before, success, gas_after
+            SWAP1
+            PUSH1(0x00)
+            MSTORE // gas_before, gas_after
+            SWAP1
+            SUB
+            PUSH1(0x20)
+            MSTORE
+            .op_log2(0, 0x40, *USER_OPERATION_EVENT_TOPIC, index)
+        });
+        call_data_offset += call_data_length;
+    }
+    code.op_stop();
+    code
+}
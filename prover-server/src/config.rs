@@ -0,0 +1,59 @@
+//! Service configuration, read from the environment.
+
+use std::{
+    env::{self, VarError},
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+};
+use url::Url;
+
+/// Configuration of the prover service.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Address the HTTP API listens on (`PROVER_LISTEN_ADDR`)
+    pub listen_addr: SocketAddr,
+    /// Endpoint of the geth node the blocks are fetched from
+    /// (`PROVER_RPC_URL`)
+    pub rpc_url: Url,
+    /// Path of the KZG params (`PROVER_PARAMS_PATH`). When unset the params
+    /// are generated from `seed`, which is only suitable for testing.
+    pub params_path: Option<PathBuf>,
+    /// Seed of the rng used for the params generation and the proofs
+    /// (`PROVER_SEED`)
+    pub seed: u64,
+    /// Degree of the super circuit (`PROVER_DEGREE`)
+    pub degree: u32,
+    /// Degree of the aggregation circuit (`PROVER_AGG_DEGREE`). When unset the
+    /// super circuit proof is served without aggregation.
+    pub agg_degree: Option<u32>,
+}
+
+fn env_var<T: FromStr>(name: &str) -> Option<T> {
+    match env::var(name) {
+        Ok(value) => Some(
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid value of {}: {}", name, value)),
+        ),
+        Err(VarError::NotPresent) => None,
+        Err(e) => panic!("Error in {}: {:?}", name, e),
+    }
+}
+
+impl Config {
+    /// Read the configuration from the environment, using the defaults for
+    /// unset variables.
+    pub fn from_env() -> Self {
+        Self {
+            listen_addr: env_var("PROVER_LISTEN_ADDR")
+                .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 8080))),
+            rpc_url: env_var("PROVER_RPC_URL")
+                .unwrap_or_else(|| Url::parse("http://localhost:8545").unwrap()),
+            params_path: env_var("PROVER_PARAMS_PATH"),
+            seed: env_var("PROVER_SEED").unwrap_or(0x5962be5d763d318d),
+            degree: env_var("PROVER_DEGREE").unwrap_or(20),
+            agg_degree: env_var("PROVER_AGG_DEGREE"),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Queue of proving jobs. Jobs are proven one at a time, in submission order,
+//! by a worker task owning the [`Prover`].
+
+use crate::prover::{build_witness, BlockSource, Prover};
+use bus_mapping::circuit_input_builder::BuilderClient;
+use ethers_providers::Http;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Formatter, Result as FmtResult},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+};
+use tokio::{sync::mpsc, task};
+use zkevm_circuits::proof_envelope::ProofEnvelope;
+
+/// Status of a proving job.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for the previous jobs to finish
+    Queued,
+    /// Generating the witness of the block
+    Witness,
+    /// Proving the block
+    Proving,
+    /// The proof is ready
+    Done,
+    /// The job failed
+    Failed {
+        /// Reason of the failure
+        error: String,
+    },
+}
+
+/// Maximum number of jobs waiting to be proven
+pub const MAX_QUEUED_JOBS: usize = 64;
+/// Maximum number of finished jobs whose result is kept
+pub const MAX_FINISHED_JOBS: usize = 256;
+
+/// Error while submitting a job.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SubmitError {
+    /// There are already [`MAX_QUEUED_JOBS`] jobs waiting
+    QueueFull,
+    /// The prover worker has stopped
+    WorkerGone,
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::QueueFull => write!(f, "the job queue is full"),
+            Self::WorkerGone => write!(f, "the prover worker has stopped"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+#[derive(Default)]
+struct Jobs {
+    next_id: u64,
+    status: HashMap<u64, JobStatus>,
+    proofs: HashMap<u64, ProofEnvelope>,
+    /// Finished jobs, oldest first
+    finished: VecDeque<u64>,
+}
+
+impl Jobs {
+    /// Record the final status of the job, and forget the oldest finished
+    /// jobs beyond [`MAX_FINISHED_JOBS`].
+    fn finish(&mut self, id: u64, status: JobStatus, proof: Option<ProofEnvelope>) {
+        self.status.insert(id, status);
+        if let Some(proof) = proof {
+            self.proofs.insert(id, proof);
+        }
+        self.finished.push_back(id);
+        while self.finished.len() > MAX_FINISHED_JOBS {
+            let evicted = self.finished.pop_front().unwrap();
+            self.status.remove(&evicted);
+            self.proofs.remove(&evicted);
+        }
+    }
+}
+
+/// Handle to the job queue, shared by the API handlers.
+#[derive(Clone)]
+pub struct JobQueue {
+    jobs: Arc<Mutex<Jobs>>,
+    sender: mpsc::Sender<(u64, BlockSource)>,
+}
+
+impl JobQueue {
+    /// Start the worker proving the submitted jobs.
+    pub fn start(client: BuilderClient<Http>, prover: Prover) -> Self {
+        let (sender, receiver) = mpsc::channel(MAX_QUEUED_JOBS);
+        let queue = Self {
+            jobs: Default::default(),
+            sender,
+        };
+        tokio::spawn(queue.clone().run(receiver, client, prover));
+        queue
+    }
+
+    /// Queue the block for proving and return the id of the job.
+    pub fn submit(&self, source: BlockSource) -> Result<u64, SubmitError> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let id = jobs.next_id;
+        self.sender.try_send((id, source)).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => SubmitError::QueueFull,
+            mpsc::error::TrySendError::Closed(_) => SubmitError::WorkerGone,
+        })?;
+        jobs.next_id += 1;
+        jobs.status.insert(id, JobStatus::Queued);
+        Ok(id)
+    }
+
+    /// Status of the job, `None` if there is no such job.
+    pub fn status(&self, id: u64) -> Option<JobStatus> {
+        self.jobs.lock().unwrap().status.get(&id).cloned()
+    }
+
+    /// Proof of the job, `None` if it is not done.
+    pub fn proof(&self, id: u64) -> Option<ProofEnvelope> {
+        self.jobs.lock().unwrap().proofs.get(&id).cloned()
+    }
+
+    fn set_status(&self, id: u64, status: JobStatus) {
+        self.jobs.lock().unwrap().status.insert(id, status);
+    }
+
+    fn fail(&self, id: u64, error: String) {
+        log::error!("job {}: {}", id, error);
+        self.jobs
+            .lock()
+            .unwrap()
+            .finish(id, JobStatus::Failed { error }, None);
+    }
+
+    async fn run(
+        self,
+        mut receiver: mpsc::Receiver<(u64, BlockSource)>,
+        client: BuilderClient<Http>,
+        mut prover: Prover,
+    ) {
+        while let Some((id, source)) = receiver.recv().await {
+            self.set_status(id, JobStatus::Witness);
+            let builder = match build_witness(&client, source).await {
+                Ok(builder) => builder,
+                Err(e) => {
+                    self.fail(id, e.to_string());
+                    continue;
+                }
+            };
+
+            self.set_status(id, JobStatus::Proving);
+            log::info!("job {}: proving", id);
+            // Proving is CPU bound, keep it off the async runtime. A panic of
+            // the prover fails the job, the proving keys are left untouched
+            // by a failed proof so the prover is kept for the next jobs.
+            let (returned, result) = task::spawn_blocking(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| prover.prove(&builder)));
+                (prover, result)
+            })
+            .await
+            .expect("the proving task is never cancelled");
+            prover = returned;
+
+            match result {
+                Ok(Ok(proof)) => {
+                    log::info!("job {}: done", id);
+                    self.jobs
+                        .lock()
+                        .unwrap()
+                        .finish(id, JobStatus::Done, Some(proof));
+                }
+                Ok(Err(e)) => self.fail(id, e.to_string()),
+                Err(panic) => {
+                    let reason = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    self.fail(id, format!("the prover panicked: {}", reason));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_status_json() {
+        assert_eq!(
+            serde_json::to_value(JobStatus::Proving).unwrap(),
+            serde_json::json!({ "status": "proving" })
+        );
+        assert_eq!(
+            serde_json::to_value(JobStatus::Failed {
+                error: "no block".to_string()
+            })
+            .unwrap(),
+            serde_json::json!({ "status": "failed", "error": "no block" })
+        );
+    }
+
+    #[test]
+    fn finished_jobs_evicted() {
+        let mut jobs = Jobs::default();
+        for id in 0..MAX_FINISHED_JOBS as u64 + 2 {
+            jobs.status.insert(id, JobStatus::Queued);
+            jobs.finish(id, JobStatus::Done, None);
+        }
+        assert_eq!(jobs.status.len(), MAX_FINISHED_JOBS);
+        assert_eq!(jobs.status.get(&0), None);
+        assert_eq!(jobs.status.get(&1), None);
+        assert_eq!(jobs.status.get(&2), Some(&JobStatus::Done));
+    }
+
+    #[test]
+    fn submit_errors() {
+        let (sender, receiver) = mpsc::channel(1);
+        let queue = JobQueue {
+            jobs: Default::default(),
+            sender,
+        };
+        let source = || BlockSource::Number { block_number: 1 };
+        assert_eq!(queue.submit(source()), Ok(0));
+        assert_eq!(queue.submit(source()), Err(SubmitError::QueueFull));
+        drop(receiver);
+        assert_eq!(queue.submit(source()), Err(SubmitError::WorkerGone));
+        assert_eq!(queue.status(1), None);
+    }
+
+    #[test]
+    fn block_source_json() {
+        let source: BlockSource = serde_json::from_str(r#"{ "block_number": 42 }"#).unwrap();
+        assert!(matches!(source, BlockSource::Number { block_number: 42 }));
+    }
+}
@@ -0,0 +1,15 @@
+//! Prover service: builds the witness of a block, proves it with the super
+//! circuit, optionally aggregates the proof, and serves the result over an
+//! HTTP API.
+
+#![deny(rustdoc::broken_intra_doc_links)]
+#![deny(missing_docs)]
+
+pub mod config;
+pub mod jobs;
+pub mod prover;
+pub mod server;
+
+pub use config::Config;
+pub use jobs::{JobQueue, JobStatus, SubmitError};
+pub use prover::{BlockSource, Prover, ProverError};
@@ -0,0 +1,27 @@
+use bus_mapping::{circuit_input_builder::BuilderClient, rpc::GethClient};
+use env_logger::Env;
+use ethers_providers::Http;
+use prover_server::{prover::CIRCUITS_PARAMS, server, Config, JobQueue, Prover};
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let config = Config::from_env();
+    log::info!("{:?}", config);
+
+    let client = BuilderClient::new(
+        GethClient::new(Http::new(config.rpc_url.clone())),
+        CIRCUITS_PARAMS,
+    )
+    .await
+    .expect("cannot connect to the RPC node");
+    let prover = Prover::new(&config).expect("cannot load the params");
+    log::info!("circuit version: {:?}", prover.circuit_version());
+
+    let queue = JobQueue::start(client, prover);
+    log::info!("listening on {}", config.listen_addr);
+    server::serve(config.listen_addr, queue)
+        .await
+        .expect("server failed");
+}
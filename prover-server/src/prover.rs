@@ -0,0 +1,306 @@
+//! Witness generation and proving of a block with the super circuit, followed
+//! by the optional aggregation of the super circuit proof.
+
+use crate::config::Config;
+use bus_mapping::{
+    circuit_input_builder::{
+        BlockHead, BuilderClient, CircuitInputBuilder, CircuitProfile, CircuitsParams,
+    },
+    state_db::{CodeDB, StateDB},
+    Error,
+};
+use eth_types::{Address, Block, GethExecTrace, Transaction, Word, H256};
+use ethers_core::utils::keccak256;
+use ethers_providers::Http;
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::ProvingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use serde::Deserialize;
+use snark_verifier_sdk::{
+    gen_pk,
+    halo2::{aggregation::AggregationCircuit, gen_snark_shplonk},
+};
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::File,
+    io::{self, BufReader},
+};
+use zkevm_circuits::{
+    proof_envelope::ProofEnvelope, super_circuit::SuperCircuit, util::vk_fingerprint,
+};
+
+/// Maximum number of txs per block
+pub const MAX_TXS: usize = 32;
+/// Maximum number of calldata bytes per block
+pub const MAX_CALLDATA: usize = 131072;
+/// Maximum number of blocks per proof
+pub const MAX_INNER_BLOCKS: usize = 1;
+/// Randomness used by the super circuit
+const MOCK_RANDOMNESS: u64 = 0x100;
+
+/// Parameters of the super circuit proven by the service
+pub const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
+    max_rws: 500000,
+    max_txs: MAX_TXS,
+    max_calldata: MAX_CALLDATA,
+    max_rlp_rows: 200000,
+    max_copy_rows: 500000,
+    max_inner_blocks: MAX_INNER_BLOCKS,
+    max_exp_steps: 10000,
+    max_bytecode: 200000,
+    max_evm_rows: 500000,
+    max_mpt_rows: 500000,
+    max_keccak_rows: 0,
+    keccak_rows_per_round: 0,
+    max_ecrecover_sigs: 0,
+    max_log_items: 0,
+    profile: CircuitProfile::Full,
+};
+
+/// Super circuit proven by the service
+pub type ProverCircuit = SuperCircuit<Fr, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>;
+
+/// Block to prove.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BlockSource {
+    /// Block fetched from the RPC node by number
+    Number {
+        /// Number of the block
+        block_number: u64,
+    },
+    /// Block and execution traces given by the caller. The state accessed by
+    /// the block is still fetched from the RPC node.
+    Block {
+        /// Block with its transactions
+        block: Block<Transaction>,
+        /// Execution trace of each transaction
+        traces: Vec<GethExecTrace>,
+    },
+}
+
+/// Error while proving a block.
+#[derive(Debug)]
+pub enum ProverError {
+    /// The witness of the block could not be generated
+    Witness(Error),
+    /// The block does not fit in the super circuit
+    CircuitTooLarge {
+        /// Degree needed by the block
+        required: u32,
+        /// Degree of the super circuit
+        available: u32,
+    },
+}
+
+impl Display for ProverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Witness(e) => write!(f, "witness generation failed: {}", e),
+            Self::CircuitTooLarge {
+                required,
+                available,
+            } => write!(
+                f,
+                "block needs a circuit of degree {}, the service proves degree {}",
+                required, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl From<Error> for ProverError {
+    fn from(e: Error) -> Self {
+        Self::Witness(e)
+    }
+}
+
+/// Generate the circuit inputs of the block.
+pub async fn build_witness(
+    client: &BuilderClient<Http>,
+    source: BlockSource,
+) -> Result<CircuitInputBuilder, ProverError> {
+    match source {
+        BlockSource::Number { block_number } => Ok(client.gen_inputs(block_number).await?.0),
+        BlockSource::Block { block, traces } => {
+            let block_number = block
+                .number
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+                .as_u64();
+            let access_set = BuilderClient::<Http>::get_state_accesses(&block, &traces)?;
+            let (proofs, codes) = client.get_state(block_number, access_set.into()).await?;
+            let (state_db, code_db) = BuilderClient::<Http>::build_state_code_db(proofs, codes);
+            Ok(client.gen_inputs_from_state(
+                state_db,
+                code_db,
+                &block,
+                &traces,
+                Vec::new(),
+                Word::zero(),
+            )?)
+        }
+    }
+}
+
+/// Aggregation of the super circuit proofs.
+struct Aggregation {
+    params: ParamsKZG<Bn256>,
+    key: ProvingKey<G1Affine>,
+}
+
+/// Proves blocks with the super circuit. The proving keys are generated at
+/// startup from the circuit of an empty block, since the fixed columns of the
+/// circuit only depend on [`CIRCUITS_PARAMS`].
+pub struct Prover {
+    degree: u32,
+    params: ParamsKZG<Bn256>,
+    key: ProvingKey<G1Affine>,
+    aggregation: Option<Aggregation>,
+    circuit_version: H256,
+    rng: XorShiftRng,
+}
+
+fn read_params(path: &std::path::Path) -> io::Result<ParamsKZG<Bn256>> {
+    ParamsKZG::read(&mut BufReader::new(File::open(path)?))
+}
+
+/// Circuit inputs of an empty block, used to generate the proving keys.
+fn empty_block() -> Result<CircuitInputBuilder, Error> {
+    let eth_block = Block::<Transaction> {
+        author: Some(Address::zero()),
+        number: Some(0.into()),
+        base_fee_per_gas: Some(Word::zero()),
+        ..Default::default()
+    };
+    let head = BlockHead::new(Word::one(), Vec::new(), &eth_block)?;
+    let mut builder = CircuitInputBuilder::new_from_headers(
+        CIRCUITS_PARAMS,
+        StateDB::new(),
+        CodeDB::new(),
+        &[head],
+    );
+    builder.handle_block(&eth_block, &[])?;
+    Ok(builder)
+}
+
+impl Prover {
+    /// Load or generate the params of the configured circuits.
+    pub fn new(config: &Config) -> io::Result<Self> {
+        let max_degree = config
+            .agg_degree
+            .unwrap_or(config.degree)
+            .max(config.degree);
+        let mut rng = XorShiftRng::seed_from_u64(config.seed);
+        let agg_params = match &config.params_path {
+            Some(path) => {
+                let params = read_params(path)?;
+                if params.k() < max_degree {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("params of degree {} < {}", params.k(), max_degree),
+                    ));
+                }
+                params
+            }
+            None => {
+                log::warn!("generating params from PROVER_SEED, do not use them in production");
+                ParamsKZG::setup(max_degree, &mut rng)
+            }
+        };
+        let mut params = agg_params.clone();
+        params.downsize(config.degree);
+
+        let (k, circuit, _) = empty_block()
+            .and_then(|builder| ProverCircuit::build_from_circuit_input_builder(&builder))
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        if k > config.degree {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "an empty block needs a circuit of degree {} > {}",
+                    k, config.degree
+                ),
+            ));
+        }
+        log::info!("generating the super circuit proving key");
+        let key = gen_pk(&params, &circuit, None);
+        let mut circuit_version = vk_fingerprint(key.get_vk(), &CIRCUITS_PARAMS);
+
+        let aggregation = config.agg_degree.map(|agg_degree| {
+            let mut agg_params = agg_params;
+            agg_params.downsize(agg_degree);
+            // The aggregation circuit needs a snark to be built, its fixed
+            // columns only depend on the super circuit verifying key.
+            let snark = gen_snark_shplonk(&params, &key, circuit, &mut rng, None);
+            let agg_circuit = AggregationCircuit::new(&agg_params, [snark], &mut rng);
+            log::info!("generating the aggregation circuit proving key");
+            let agg_key = gen_pk(&agg_params, &agg_circuit, None);
+            let agg_version = vk_fingerprint(agg_key.get_vk(), &CIRCUITS_PARAMS);
+            circuit_version = H256(keccak256(
+                [circuit_version.as_bytes(), agg_version.as_bytes()].concat(),
+            ));
+            Aggregation {
+                params: agg_params,
+                key: agg_key,
+            }
+        });
+
+        Ok(Self {
+            degree: config.degree,
+            params,
+            key,
+            aggregation,
+            circuit_version,
+            rng,
+        })
+    }
+
+    /// Identifier of the proven circuit, put in the proof envelopes.
+    pub fn circuit_version(&self) -> H256 {
+        self.circuit_version
+    }
+
+    /// Prove the block, and aggregate the proof if aggregation is enabled.
+    pub fn prove(&mut self, builder: &CircuitInputBuilder) -> Result<ProofEnvelope, ProverError> {
+        let (k, circuit, _) = ProverCircuit::build_from_circuit_input_builder(builder)?;
+        if k > self.degree {
+            return Err(ProverError::CircuitTooLarge {
+                required: k,
+                available: self.degree,
+            });
+        }
+
+        let mut snark = gen_snark_shplonk(&self.params, &self.key, circuit, &mut self.rng, None);
+
+        if let Some(aggregation) = &self.aggregation {
+            let agg_circuit = AggregationCircuit::new(&aggregation.params, [snark], &mut self.rng);
+            snark = gen_snark_shplonk(
+                &aggregation.params,
+                &aggregation.key,
+                agg_circuit,
+                &mut self.rng,
+                None,
+            );
+        }
+
+        let headers = &builder.block.headers;
+        let block_range = (
+            headers.keys().next().copied().unwrap_or_default(),
+            headers.keys().next_back().copied().unwrap_or_default(),
+        );
+        Ok(ProofEnvelope::new(
+            self.circuit_version,
+            CIRCUITS_PARAMS,
+            builder.block.chain_id.as_u64(),
+            block_range,
+            &snark.instances,
+            snark.proof,
+        ))
+    }
+}
@@ -0,0 +1,89 @@
+//! HTTP API of the prover service.
+//!
+//! - `POST /v1/proofs` with a [`BlockSource`] body queues a block for proving and returns the job
+//!   id, or 503 if the queue is full or the prover worker has stopped.
+//! - `GET /v1/proofs/{id}` returns the [`JobStatus`] of the job.
+//! - `GET /v1/proofs/{id}/proof` returns the proof envelope once the job is done.
+//! - `GET /health` returns 200 while the service is up.
+
+use crate::{jobs::JobQueue, prover::BlockSource, JobStatus};
+use hyper::{
+    body,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{convert::Infallible, net::SocketAddr};
+
+/// Serve the API on `addr` until the server fails.
+pub async fn serve(addr: SocketAddr, queue: JobQueue) -> hyper::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let queue = queue.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let queue = queue.clone();
+                async move { Ok::<_, Infallible>(handle(&queue, request).await) }
+            }))
+        }
+    });
+    Server::bind(&addr).serve(make_service).await
+}
+
+fn json_response(status: StatusCode, value: &impl Serialize) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(value).unwrap()))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, error: impl ToString) -> Response<Body> {
+    json_response(status, &json!({ "error": error.to_string() }))
+}
+
+fn proof_response(queue: &JobQueue, id: u64) -> Response<Body> {
+    match (queue.status(id), queue.proof(id)) {
+        (_, Some(proof)) => json_response(StatusCode::OK, &proof),
+        (Some(JobStatus::Failed { error }), _) => {
+            error_response(StatusCode::UNPROCESSABLE_ENTITY, error)
+        }
+        (Some(_), None) => error_response(StatusCode::CONFLICT, "proof not ready"),
+        (None, None) => error_response(StatusCode::NOT_FOUND, "unknown job"),
+    }
+}
+
+async fn handle(queue: &JobQueue, request: Request<Body>) -> Response<Body> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+    match (&method, segments.as_slice()) {
+        (&Method::GET, ["health"]) => json_response(StatusCode::OK, &json!({ "status": "ok" })),
+        (&Method::POST, ["v1", "proofs"]) => {
+            let bytes = match body::to_bytes(request.into_body()).await {
+                Ok(bytes) => bytes,
+                Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+            };
+            match serde_json::from_slice::<BlockSource>(&bytes) {
+                Ok(source) => match queue.submit(source) {
+                    Ok(id) => json_response(StatusCode::ACCEPTED, &json!({ "id": id })),
+                    Err(e) => error_response(StatusCode::SERVICE_UNAVAILABLE, e),
+                },
+                Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+            }
+        }
+        (&Method::GET, ["v1", "proofs", id]) => {
+            match id.parse().ok().and_then(|id| queue.status(id)) {
+                Some(status) => json_response(StatusCode::OK, &status),
+                None => error_response(StatusCode::NOT_FOUND, "unknown job"),
+            }
+        }
+        (&Method::GET, ["v1", "proofs", id, "proof"]) => match id.parse() {
+            Ok(id) => proof_response(queue, id),
+            Err(_) => error_response(StatusCode::NOT_FOUND, "unknown job"),
+        },
+        _ => error_response(StatusCode::NOT_FOUND, "not found"),
+    }
+}
@@ -1,7 +1,7 @@
 use super::{AccountMatch, StateTest, StateTestResult};
 use crate::config::TestSuite;
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{CircuitInputBuilder, CircuitProfile, CircuitsParams},
     mock::BlockData,
 };
 use eth_types::{geth_types, geth_types::TxType, Address, Bytes, GethExecTrace, U256, U64};
@@ -175,6 +175,7 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 difficulty: st.env.current_difficulty,
                 gas_limit: U256::from(st.env.current_gas_limit),
                 base_fee: st.env.current_base_fee,
+                ..Default::default()
             },
 
             transactions: vec![geth_types::Transaction {
@@ -205,6 +206,7 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
             chain_config: Some(external_tracer::ChainConfig::shanghai()),
             #[cfg(not(feature = "shanghai"))]
             chain_config: None,
+            ..Default::default()
         },
         st.result,
     )
@@ -238,10 +240,12 @@ pub fn run_test(
                 found: "no error".into(),
             })
         }
-        (Err(_), true) => return Ok(()),
-        (Err(err), false) => {
+        // Only a tx failing its validation is the expected exception, other
+        // errors of the tracer are bugs of the test setup.
+        (Err(eth_types::Error::TracingError(err)), true) if err.is_tx_failure() => return Ok(()),
+        (Err(err), _) => {
             return Err(StateTestError::Exception {
-                expected: false,
+                expected: st.exception,
                 found: err.to_string(),
             })
         }
@@ -330,6 +334,10 @@ pub fn run_test(
             max_evm_rows: 0,
             max_exp_steps: 5000,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_inner_blocks: 64,
             max_rlp_rows: 6000,
         };
@@ -358,12 +366,15 @@ pub fn run_test(
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
             max_inner_blocks: 64,
             max_rlp_rows: 512,
         };
         let (k, circuit, instance, _builder) =
-            SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, 64, 0x100>::build(geth_data, circuits_params)
-                .unwrap();
+            SuperCircuit::<Fr, 64, 0x100>::build(geth_data, circuits_params).unwrap();
         builder = _builder;
 
         let prover = MockProver::run(k, &circuit, instance).unwrap();
@@ -242,6 +242,7 @@ impl<'a> JsonStateTestBuilder<'a> {
                 nonce: parse::parse_u256(&acc.nonce)?,
                 code: parse::parse_code(self.compiler, &acc.code)?,
                 storage,
+                storage_snapshot: None,
             };
             accounts.insert(address, account);
         }
@@ -422,6 +423,7 @@ mod test {
                     balance: U256::from(1000000000000000000u64),
                     code: Bytes::from(hex::decode("600160010160005500")?),
                     storage: HashMap::new(),
+                    storage_snapshot: None,
                 },
             )]),
             result: HashMap::from([(
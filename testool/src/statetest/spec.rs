@@ -32,6 +32,7 @@ impl TryInto<Account> for AccountMatch {
             code: self.code.context("code")?,
             nonce: self.nonce.context("nonce")?,
             storage: self.storage,
+            storage_snapshot: None,
         })
     }
 }
@@ -210,6 +211,7 @@ impl StateTest {
                 balance: U256::from(10).pow(18.into()),
                 code: Bytes::default(),
                 storage: HashMap::new(),
+                storage_snapshot: None,
             },
         );
 
@@ -247,6 +249,7 @@ impl StateTest {
                     code: Bytes::from(code.code()),
                     balance,
                     storage,
+                    storage_snapshot: None,
                 },
             );
         }
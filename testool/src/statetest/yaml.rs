@@ -638,6 +638,7 @@ arith:
                         nonce: U256::zero(),
 
                         storage: HashMap::from([(U256::zero(), U256::one())]),
+                        storage_snapshot: None,
                     },
                 ),
                 (
@@ -649,6 +650,7 @@ arith:
                         nonce: U256::zero(),
 
                         storage: HashMap::new(),
+                        storage_snapshot: None,
                     },
                 ),
             ]),
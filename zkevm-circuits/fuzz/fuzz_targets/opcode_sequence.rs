@@ -0,0 +1,28 @@
+//! Execute a sequence of opcodes with random operands and state, so that
+//! opcodes see the memory, storage, logs and return data left by the
+//! previous ones, and check the witness against the circuits.
+//!
+//! Run with `cargo fuzz run opcode_sequence` from `zkevm-circuits`.
+
+#![no_main]
+
+use libfuzzer_sys::{
+    arbitrary::{Result, Unstructured},
+    fuzz_target,
+};
+use zkevm_circuits_fuzz::{arbitrary_code, arbitrary_ctx, run_circuits};
+
+/// Maximum number of opcodes of the code, keeping the circuits small.
+const MAX_OPCODES: usize = 16;
+
+fn run(u: &mut Unstructured) -> Result<()> {
+    let code = arbitrary_code(u, MAX_OPCODES)?;
+    if let Some(ctx) = arbitrary_ctx(u, code)? {
+        run_circuits(ctx);
+    }
+    Ok(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = run(&mut Unstructured::new(data));
+});
@@ -0,0 +1,27 @@
+//! Execute a single opcode with random operands and state, and check its
+//! witness against the circuits.
+//!
+//! Run with `cargo fuzz run single_opcode` from `zkevm-circuits`.
+
+#![no_main]
+
+use eth_types::{bytecode::Bytecode, evm_types::OpcodeId};
+use libfuzzer_sys::{
+    arbitrary::{Result, Unstructured},
+    fuzz_target,
+};
+use zkevm_circuits_fuzz::{append_opcode, arbitrary_ctx, run_circuits, OPCODES};
+
+fn run(u: &mut Unstructured) -> Result<()> {
+    let mut code = Bytecode::default();
+    append_opcode(u, &mut code, *u.choose(OPCODES)?)?;
+    code.write_op(OpcodeId::STOP);
+    if let Some(ctx) = arbitrary_ctx(u, code)? {
+        run_circuits(ctx);
+    }
+    Ok(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = run(&mut Unstructured::new(data));
+});
@@ -0,0 +1,155 @@
+//! Generation of random but well formed test cases for the fuzz targets of
+//! the EVM circuit: bytecode pushing the operands of each opcode before it,
+//! and the state of the called contract, traced by the external tracer.
+//!
+//! Each test case is run through [`CircuitTestBuilder`], so a divergence
+//! between bus-mapping and the circuits fails a constraint and panics.
+
+use eth_types::{bytecode::Bytecode, evm_types::OpcodeId, Bytes, Word};
+use libfuzzer_sys::arbitrary::{Result, Unstructured};
+use mock::{eth, test_ctx::TestContext, MOCK_ACCOUNTS};
+use zkevm_circuits::test_util::CircuitTestBuilder;
+
+/// Opcodes the generated code is made of. Calls, creates and jumps are left
+/// out, as random operands mostly make them fail early.
+pub const OPCODES: &[OpcodeId] = &[
+    OpcodeId::ADD,
+    OpcodeId::MUL,
+    OpcodeId::SUB,
+    OpcodeId::DIV,
+    OpcodeId::SDIV,
+    OpcodeId::MOD,
+    OpcodeId::SMOD,
+    OpcodeId::ADDMOD,
+    OpcodeId::MULMOD,
+    OpcodeId::EXP,
+    OpcodeId::SIGNEXTEND,
+    OpcodeId::LT,
+    OpcodeId::GT,
+    OpcodeId::SLT,
+    OpcodeId::SGT,
+    OpcodeId::EQ,
+    OpcodeId::ISZERO,
+    OpcodeId::AND,
+    OpcodeId::OR,
+    OpcodeId::XOR,
+    OpcodeId::NOT,
+    OpcodeId::BYTE,
+    OpcodeId::SHL,
+    OpcodeId::SHR,
+    OpcodeId::SAR,
+    OpcodeId::SHA3,
+    OpcodeId::ADDRESS,
+    OpcodeId::BALANCE,
+    OpcodeId::ORIGIN,
+    OpcodeId::CALLER,
+    OpcodeId::CALLVALUE,
+    OpcodeId::CALLDATALOAD,
+    OpcodeId::CALLDATASIZE,
+    OpcodeId::CALLDATACOPY,
+    OpcodeId::CODESIZE,
+    OpcodeId::CODECOPY,
+    OpcodeId::GASPRICE,
+    OpcodeId::EXTCODESIZE,
+    OpcodeId::EXTCODECOPY,
+    OpcodeId::RETURNDATASIZE,
+    OpcodeId::RETURNDATACOPY,
+    OpcodeId::EXTCODEHASH,
+    OpcodeId::BLOCKHASH,
+    OpcodeId::COINBASE,
+    OpcodeId::TIMESTAMP,
+    OpcodeId::NUMBER,
+    OpcodeId::DIFFICULTY,
+    OpcodeId::GASLIMIT,
+    OpcodeId::CHAINID,
+    OpcodeId::SELFBALANCE,
+    OpcodeId::BASEFEE,
+    OpcodeId::POP,
+    OpcodeId::MLOAD,
+    OpcodeId::MSTORE,
+    OpcodeId::MSTORE8,
+    OpcodeId::SLOAD,
+    OpcodeId::SSTORE,
+    OpcodeId::PC,
+    OpcodeId::MSIZE,
+    OpcodeId::GAS,
+    OpcodeId::LOG0,
+    OpcodeId::LOG1,
+    OpcodeId::LOG2,
+    OpcodeId::LOG3,
+    OpcodeId::LOG4,
+    OpcodeId::RETURN,
+    OpcodeId::REVERT,
+];
+
+/// An operand, mostly small to stay within the memory and gas limits, and
+/// sometimes around the limits of the circuits' range checks.
+pub fn arbitrary_operand(u: &mut Unstructured) -> Result<Word> {
+    Ok(match u.int_in_range(0..=7)? {
+        0 => Word::from_big_endian(&u.arbitrary::<[u8; 32]>()?),
+        1 => Word::from(u.arbitrary::<u64>()?),
+        2 => {
+            let power_of_two = Word::one() << u.int_in_range(0..=255usize)?;
+            power_of_two - Word::from(u.int_in_range(0..=1u64)?)
+        }
+        _ => Word::from(u.int_in_range(0..=0x100u64)?),
+    })
+}
+
+/// Append `opcode` to `code`, after pushing random operands for it.
+pub fn append_opcode(u: &mut Unstructured, code: &mut Bytecode, opcode: OpcodeId) -> Result<()> {
+    let (pops, _) = opcode.stack_pops_pushes();
+    for _ in 0..pops {
+        code.push(32, arbitrary_operand(u)?);
+    }
+    code.write_op(opcode);
+    Ok(())
+}
+
+/// Code executing up to `max_opcodes` opcodes of [`OPCODES`], then STOP.
+pub fn arbitrary_code(u: &mut Unstructured, max_opcodes: usize) -> Result<Bytecode> {
+    let mut code = Bytecode::default();
+    for _ in 0..u.int_in_range(1..=max_opcodes)? {
+        append_opcode(u, &mut code, *u.choose(OPCODES)?)?;
+    }
+    code.write_op(OpcodeId::STOP);
+    Ok(code)
+}
+
+/// A tx with random value and call data calling a contract with `code`, some
+/// storage and balance, traced by the external tracer. `None` when the
+/// tracer rejects the block.
+pub fn arbitrary_ctx(u: &mut Unstructured, code: Bytecode) -> Result<Option<TestContext<2, 1>>> {
+    let balance = Word::from(u.arbitrary::<u32>()?);
+    let storage = (0..u.int_in_range(0..=4)?)
+        .map(|_| Ok((Word::from(u.int_in_range(0..=8u64)?), arbitrary_operand(u)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let value = Word::from(u.arbitrary::<u16>()?);
+    let call_data = Bytes::from(u.bytes(u.int_in_range(0..=96)?)?.to_vec());
+
+    Ok(TestContext::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(MOCK_ACCOUNTS[0])
+                .balance(balance)
+                .code(code)
+                .storage(storage.into_iter());
+            accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .value(value)
+                .input(call_data);
+        },
+        |block, _txs| block.number(0xcafeu64),
+    )
+    .ok())
+}
+
+/// Run the EVM and state circuits on the witness of the tx of `ctx`.
+pub fn run_circuits(ctx: TestContext<2, 1>) {
+    CircuitTestBuilder::new_from_test_ctx(ctx).run();
+}
@@ -1,6 +1,6 @@
-use crate::{table::BytecodeFieldTag, util::get_push_size};
+use crate::table::BytecodeFieldTag;
 use bus_mapping::state_db::CodeDB;
-use eth_types::{Field, ToWord, Word, U256};
+use eth_types::{evm_types::CodeAnalysis, Field, ToWord, Word, U256};
 use std::vec;
 
 /// Public data for the bytecode
@@ -35,17 +35,9 @@ pub fn unroll_with_codehash<F: Field>(code_hash: U256, bytes: Vec<u8>) -> Unroll
         is_code: F::zero(),
         value: F::from(bytes.len() as u64),
     }];
-    // Run over all the bytes
-    let mut push_rindex = 0;
-    for (index, byte) in bytes.iter().enumerate() {
-        // Track which byte is an opcode and which is push data
-        let is_code = push_rindex == 0;
-        push_rindex = if is_code {
-            get_push_size(*byte)
-        } else {
-            push_rindex - 1
-        };
-
+    // Track which byte is an opcode and which is push data
+    let analysis = CodeAnalysis::new(&bytes);
+    for (index, (byte, is_code)) in bytes.iter().zip(analysis.is_code_iter()).enumerate() {
         rows.push(BytecodeRow::<F> {
             code_hash,
             tag: F::from(BytecodeFieldTag::Byte as u64),
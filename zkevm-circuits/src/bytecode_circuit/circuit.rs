@@ -4,12 +4,16 @@ use crate::{
         constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
         not, or, rlc, select,
     },
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{BytecodeFieldTag, BytecodeTable, KeccakTable, LookupTable},
-    util::{get_push_size, Challenges, Expr, SubCircuit, SubCircuitConfig},
+    util::{get_push_size, word, Challenges, Expr, SubCircuit, SubCircuitConfig},
     witness,
 };
 use bus_mapping::{state_db::EMPTY_CODE_HASH_LE, util::POSEIDON_CODE_HASH_ZERO};
-use eth_types::{Field, ToLittleEndian, ToScalar, ToWord};
+use eth_types::{Field, ToScalar, ToWord};
 use gadgets::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
@@ -47,6 +51,9 @@ pub struct BytecodeCircuitConfig<F> {
     q_enable: Column<Fixed>,
     q_first: Column<Fixed>,
     q_last: Column<Fixed>,
+    /// Set on the first row of every page but the first one.  It only
+    /// depends on the circuit size and the page size.  See [`BytecodePage`].
+    q_page_first: Column<Fixed>,
     bytecode_table: BytecodeTable,
     push_data_left: Column<Advice>,
     value_rlc: Column<Advice>,
@@ -86,6 +93,7 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
         let q_enable = bytecode_table.q_enable;
         let q_first = meta.fixed_column();
         let q_last = meta.fixed_column();
+        let q_page_first = meta.fixed_column();
         let value = bytecode_table.value;
         let push_data_left = meta.advice_column();
         let value_rlc = meta.advice_column_in(SecondPhase);
@@ -392,6 +400,71 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
             ]))
         });
 
+        // When q_page_first && cur.tag == Byte ->
+        // assert cur.hash == prev.hash
+        // assert cur.length == prev.length
+        // if prev.tag == Byte:
+        //     assert cur.index == prev.index + 1
+        //     assert cur.value_rlc == prev.value_rlc * randomness + cur.value
+        //     if prev.is_code:
+        //         assert cur.push_data_left == prev.push_data_size
+        //     else:
+        //         assert cur.push_data_left == prev.push_data_left - 1
+        // else:
+        //     assert cur.index == 0
+        //     assert cur.value_rlc == cur.value
+        //     assert cur.push_data_left == 0
+        meta.create_gate("Page continuity", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let prev_is_byte = meta.query_advice(bytecode_table.tag, Rotation::prev());
+
+            cb.require_equal(
+                "cur.hash == prev.hash",
+                meta.query_advice(bytecode_table.code_hash, Rotation::cur()),
+                meta.query_advice(bytecode_table.code_hash, Rotation::prev()),
+            );
+
+            cb.require_equal(
+                "cur.length == prev.length",
+                meta.query_advice(length, Rotation::cur()),
+                meta.query_advice(length, Rotation::prev()),
+            );
+
+            cb.require_equal(
+                "cur.index == prev.tag * (prev.index + 1)",
+                meta.query_advice(bytecode_table.index, Rotation::cur()),
+                prev_is_byte.clone()
+                    * (meta.query_advice(bytecode_table.index, Rotation::prev()) + 1.expr()),
+            );
+
+            cb.require_equal(
+                "cur.value_rlc == prev.tag * prev.value_rlc * randomness + cur.value",
+                meta.query_advice(value_rlc, Rotation::cur()),
+                prev_is_byte.clone()
+                    * meta.query_advice(value_rlc, Rotation::prev())
+                    * challenges.keccak_input()
+                    + meta.query_advice(value, Rotation::cur()),
+            );
+
+            cb.require_equal(
+                "cur.push_data_left == prev.tag * (prev.is_code ? prev.push_data_size : prev.push_data_left - 1)",
+                meta.query_advice(push_data_left, Rotation::cur()),
+                prev_is_byte
+                    * select::expr(
+                        meta.query_advice(bytecode_table.is_code, Rotation::prev()),
+                        meta.query_advice(push_data_size, Rotation::prev()),
+                        meta.query_advice(push_data_left, Rotation::prev()) - 1.expr(),
+                    ),
+            );
+
+            cb.gate(and::expr(vec![
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_fixed(q_page_first, Rotation::cur()),
+                is_byte(meta),
+            ]))
+        });
+
         // When is_byte_to_header ->
         // assert cur.index + 1 == cur.length
         // assert keccak256_table_lookup(cur.hash, cur.length, cur.value_rlc)
@@ -444,6 +517,7 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
             q_enable,
             q_first,
             q_last,
+            q_page_first,
             bytecode_table,
             push_data_left,
             value_rlc,
@@ -479,6 +553,24 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         overwrite: &UnrolledBytecode<F>,
         challenges: &Challenges<Value<F>>,
         fail_fast: bool,
+    ) -> Result<(), Error> {
+        self.assign_paged_internal(
+            layouter, size, None, witness, overwrite, challenges, fail_fast,
+        )
+    }
+
+    /// Assign the bytecodes to the circuit rows split into pages of
+    /// `page_rows` rows, or into a single page if `page_rows` is `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign_paged_internal(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        size: usize,
+        page_rows: Option<usize>,
+        witness: &[UnrolledBytecode<F>],
+        overwrite: &UnrolledBytecode<F>,
+        challenges: &Challenges<Value<F>>,
+        fail_fast: bool,
     ) -> Result<(), Error> {
         let push_data_left_is_zero_chip =
             IsZeroChip::construct(self.push_data_left_is_zero.clone());
@@ -538,6 +630,21 @@ impl<F: Field> BytecodeCircuitConfig<F> {
                     )?;
                 }
 
+                // The page boundaries only depend on the circuit size
+                if let Some(page_rows) = page_rows {
+                    for page in BytecodePage::paginate(last_row_offset + 1, page_rows)
+                        .into_iter()
+                        .skip(1)
+                    {
+                        region.assign_fixed(
+                            || format!("assign q_page_first {}", page.start),
+                            self.q_page_first,
+                            page.start,
+                            || Value::known(F::one()),
+                        )?;
+                    }
+                }
+
                 // Padding
                 for idx in offset..=last_row_offset {
                     self.set_padding_row(
@@ -593,7 +700,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
 
             let code_hash = challenges
                 .evm_word()
-                .map(|challenge| rlc::value(&row.code_hash.to_le_bytes(), challenge));
+                .map(|challenge| word::rlc(&row.code_hash, challenge));
             for (name, column, value) in [
                 ("code_hash", self.bytecode_table.code_hash, code_hash),
                 ("value_rlc", self.value_rlc, value_rlc),
@@ -635,7 +742,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
             if cfg!(feature = "poseidon-codehash") {
                 bytecode.rows[0].code_hash.to_scalar().unwrap()
             } else {
-                rlc::value(&bytecode.rows[0].code_hash.to_le_bytes(), challenge)
+                word::rlc(&bytecode.rows[0].code_hash, challenge)
             }
         });
 
@@ -853,6 +960,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         region.name_column(|| "BYTECODE_q_enable", self.q_enable);
         region.name_column(|| "BYTECODE_q_first", self.q_first);
         region.name_column(|| "BYTECODE_q_last", self.q_last);
+        region.name_column(|| "BYTECODE_q_page_first", self.q_page_first);
         region.name_column(|| "BYTECODE_length", self.length);
         region.name_column(|| "BYTECODE_push_data_left", self.push_data_left);
         region.name_column(|| "BYTECODE_push_data_size", self.push_data_size);
@@ -895,6 +1003,42 @@ impl<F: Field> BytecodeCircuitConfig<F> {
     }
 }
 
+/// A window of consecutive rows of the bytecode circuit.
+///
+/// The pages only depend on the circuit size and the page size, so a bytecode
+/// may start on a page and end on a later one.  All the row checks apply on
+/// every page, and the code hash, length, index, push data and value RLC of a
+/// bytecode are carried from the last row of a page to the first row of the
+/// next one by the page continuity gate.  The keccak lookup of a bytecode is
+/// done on its last row, whichever page it ends on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BytecodePage {
+    /// Offset of the first row of the page.
+    pub start: usize,
+    /// Number of rows in the page.
+    pub len: usize,
+}
+
+impl BytecodePage {
+    /// Offset of the row following the page.
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    /// Split `rows` circuit rows into pages of `page_rows` rows, the last
+    /// page holding the remaining rows.
+    pub fn paginate(rows: usize, page_rows: usize) -> Vec<Self> {
+        assert!(page_rows > 0, "a bytecode page needs at least 1 row");
+        (0..rows)
+            .step_by(page_rows)
+            .map(|start| Self {
+                start,
+                len: page_rows.min(rows - start),
+            })
+            .collect()
+    }
+}
+
 /// BytecodeCircuit
 #[derive(Clone, Default, Debug)]
 pub struct BytecodeCircuit<F: Field> {
@@ -904,6 +1048,9 @@ pub struct BytecodeCircuit<F: Field> {
     pub size: usize,
     /// Overwrite
     pub overwrite: UnrolledBytecode<F>,
+    /// Number of rows of the pages the circuit rows are split into, a single
+    /// page if `None`.  Not supported with the `poseidon-codehash` feature.
+    pub page_rows: Option<usize>,
 }
 
 impl<F: Field> BytecodeCircuit<F> {
@@ -913,6 +1060,16 @@ impl<F: Field> BytecodeCircuit<F> {
             bytecodes,
             size,
             overwrite: Default::default(),
+            page_rows: None,
+        }
+    }
+
+    /// Creates a bytecode circuit with its rows split into pages of
+    /// `page_rows` rows
+    pub fn new_paged(bytecodes: Vec<UnrolledBytecode<F>>, size: usize, page_rows: usize) -> Self {
+        BytecodeCircuit {
+            page_rows: Some(page_rows),
+            ..Self::new(bytecodes, size)
         }
     }
 
@@ -967,13 +1124,72 @@ impl<F: Field> SubCircuit<F> for BytecodeCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         config.load_aux_tables(layouter)?;
-        config.assign_internal(
-            layouter,
-            self.size,
-            &self.bytecodes,
-            &self.overwrite,
-            challenges,
-            true,
-        )
+        #[cfg(not(feature = "poseidon-codehash"))]
+        {
+            config.assign_paged_internal(
+                layouter,
+                self.size,
+                self.page_rows,
+                &self.bytecodes,
+                &self.overwrite,
+                challenges,
+                true,
+            )
+        }
+        #[cfg(feature = "poseidon-codehash")]
+        {
+            debug_assert!(
+                self.page_rows.is_none(),
+                "paged bytecode circuit needs keccak code hash"
+            );
+            config.assign_internal(
+                layouter,
+                self.size,
+                &self.bytecodes,
+                &self.overwrite,
+                challenges,
+                true,
+            )
+        }
+    }
+}
+
+impl<F: Field> SuperSubCircuit<F> for BytecodeCircuit<F> {
+    const NAME: &'static str = "bytecode circuit";
+    #[cfg(not(feature = "poseidon-codehash"))]
+    const TABLES: &'static [(SharedTable, u32)] =
+        &[(SharedTable::Bytecode, 1), (SharedTable::Keccak, 1)];
+    #[cfg(feature = "poseidon-codehash")]
+    const TABLES: &'static [(SharedTable, u32)] = &[
+        (SharedTable::Bytecode, 1),
+        (SharedTable::Keccak, 1),
+        (SharedTable::Poseidon, 1),
+    ];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Bytecode];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        let base_args = BytecodeCircuitConfigArgs {
+            bytecode_table: tables.bytecode().clone(),
+            keccak_table: tables.keccak().clone(),
+            challenges: args.challenges.clone(),
+        };
+        #[cfg(not(feature = "poseidon-codehash"))]
+        {
+            BytecodeCircuitConfig::new(meta, base_args)
+        }
+        #[cfg(feature = "poseidon-codehash")]
+        {
+            ToHashBlockCircuitConfig::new(
+                meta,
+                to_poseidon_hash::ToHashBlockBytecodeCircuitConfigArgs {
+                    base_args,
+                    poseidon_table: *tables.poseidon(),
+                },
+            )
+        }
     }
 }
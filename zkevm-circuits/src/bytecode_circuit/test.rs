@@ -6,8 +6,14 @@ use crate::{
 };
 use bus_mapping::{evm::OpcodeId, state_db::CodeDB};
 use eth_types::{Bytecode, Field, ToWord, Word};
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use halo2_proofs::{
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::keygen_vk,
+    poly::kzg::commitment::ParamsKZG,
+};
 use log::error;
+use rand::SeedableRng;
 
 #[test]
 fn bytecode_circuit_unusable_rows() {
@@ -184,6 +190,63 @@ fn bytecode_push() {
     );
 }
 
+/// Tests bytecodes spanning several pages of the circuit rows
+#[cfg(not(feature = "poseidon-codehash"))]
+#[test]
+fn bytecode_paged() {
+    let k = 9;
+    let bytecodes: Vec<UnrolledBytecode<Fr>> = vec![
+        unroll(vec![7u8; 150]),
+        unroll(vec![]),
+        unroll(vec![OpcodeId::PUSH32.as_u8(); 100]),
+    ];
+    let circuit = BytecodeCircuit::<Fr>::new_paged(bytecodes, 2usize.pow(k), 64);
+    let prover = MockProver::<Fr>::run(k, &circuit, Vec::new()).unwrap();
+    assert_eq!(prover.verify_par(), Ok(()));
+}
+
+/// Tests a bytecode truncated at a page boundary
+#[cfg(not(feature = "poseidon-codehash"))]
+#[test]
+fn bytecode_paged_truncated() {
+    let k = 9;
+    let mut truncated = unroll(vec![7u8; 100]);
+    // The header and the first 63 bytes fill the first page
+    truncated.rows.truncate(64);
+    let bytecodes = vec![truncated, unroll(vec![OpcodeId::ADD.as_u8()])];
+    let circuit = BytecodeCircuit::<Fr>::new_paged(bytecodes, 2usize.pow(k), 64);
+    let prover = MockProver::<Fr>::run(k, &circuit, Vec::new()).unwrap();
+    assert!(prover.verify_par().is_err());
+}
+
+/// Tests that the page boundaries don't depend on where the bytecodes end
+#[cfg(not(feature = "poseidon-codehash"))]
+#[test]
+fn bytecode_paged_vk_independent_of_bytecodes() {
+    let k = 9;
+    let params = ParamsKZG::<Bn256>::setup(k, rand_chacha::ChaCha20Rng::seed_from_u64(2));
+
+    // The same number of bytecodes, so the dev keccak table has the same rows
+    let split = BytecodeCircuit::<Fr>::new_paged(
+        vec![unroll(vec![7u8; 100]), unroll(vec![7u8; 10])],
+        2usize.pow(k),
+        64,
+    );
+    let unsplit = BytecodeCircuit::<Fr>::new_paged(
+        vec![unroll(vec![7u8; 10]), unroll(vec![7u8; 20])],
+        2usize.pow(k),
+        64,
+    );
+
+    let vk_split = keygen_vk(&params, &split).unwrap();
+    let vk_unsplit = keygen_vk(&params, &unsplit).unwrap();
+    assert_eq!(vk_split.fixed_commitments(), vk_unsplit.fixed_commitments());
+    assert_eq!(
+        vk_split.permutation().commitments(),
+        vk_unsplit.permutation().commitments()
+    );
+}
+
 /// Test invalid code_hash data
 /// There is only one case where this test should be disabled:
 ///   "poseidon-codehash" enabled, but "poseidon-codehash-lookup" disabled.
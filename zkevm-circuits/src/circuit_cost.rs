@@ -0,0 +1,166 @@
+//! Cost of each part of a circuit, measured on its `ConstraintSystem` after
+//! each part is configured, to prioritize the layout optimizations.
+//!
+//! The proving cost of a part is estimated by the polynomials the prover
+//! commits to for it, each of which costs an MSM and FFTs of the size of the
+//! circuit:
+//! - one per advice column,
+//! - three per lookup: the permuted input, the permuted table and the grand product,
+//! - its share of the grand products of the permutation argument, each of which covers `degree - 2`
+//!   columns.
+//!
+//! Fixed columns and selectors are committed to at keygen, and only add to
+//! the evaluation of the quotient polynomial, as the gates do.
+
+use eth_types::Field;
+use halo2_proofs::plonk::{ConstraintSystem, Expression};
+use std::fmt;
+
+/// Size of a `ConstraintSystem` at some point of its configuration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    advice_columns: usize,
+    fixed_columns: usize,
+    selectors: usize,
+    gates: usize,
+    lookups: usize,
+    permutation_columns: usize,
+}
+
+impl Checkpoint {
+    /// Current size of the constraint system.
+    pub fn new<F: Field>(meta: &ConstraintSystem<F>) -> Self {
+        Self {
+            advice_columns: meta.num_advice_columns,
+            fixed_columns: meta.num_fixed_columns,
+            selectors: meta.num_selectors,
+            gates: meta.gates().len(),
+            lookups: meta.lookups.len(),
+            permutation_columns: meta.permutation.columns.len(),
+        }
+    }
+}
+
+/// Columns, constraints and estimated proving cost of a part of a circuit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartCost {
+    /// Name of the part
+    pub name: &'static str,
+    /// Advice columns
+    pub advice_columns: usize,
+    /// Fixed columns, without the selectors
+    pub fixed_columns: usize,
+    /// Selectors, which are compressed into fixed columns at keygen
+    pub selectors: usize,
+    /// Polynomial constraints of the gates
+    pub constraints: usize,
+    /// Lookup arguments
+    pub lookups: usize,
+    /// Columns in the permutation argument
+    pub permutation_columns: usize,
+    /// Highest degree of the gates
+    pub gate_degree: usize,
+    /// Highest degree of the lookup arguments
+    pub lookup_degree: usize,
+    /// Polynomials committed to by the prover
+    pub committed_polys: f64,
+}
+
+/// Cost of each part of a circuit, in the order they are configured.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostReport {
+    /// Degree of the whole circuit
+    pub degree: usize,
+    /// Cost of each part
+    pub parts: Vec<PartCost>,
+}
+
+impl CostReport {
+    /// Cost of the parts delimited by `checkpoints`, each taken after the
+    /// part it names is configured, and preceded by `start`, taken before the
+    /// first part.
+    pub fn new<F: Field>(
+        meta: &ConstraintSystem<F>,
+        start: Checkpoint,
+        checkpoints: &[(&'static str, Checkpoint)],
+    ) -> Self {
+        let degree = meta.degree();
+        let gates = meta.gates();
+        let mut prev = start;
+        let parts = checkpoints
+            .iter()
+            .map(|(name, end)| {
+                let gate_degree = gates[prev.gates..end.gates]
+                    .iter()
+                    .flat_map(|gate| gate.polynomials())
+                    .map(|poly| poly.degree())
+                    .max()
+                    .unwrap_or_default();
+                // z(wX) (a'(X) + beta) (s'(X) + gamma) - z(X) (a(X) + beta) (s(X) + gamma),
+                // enabled on the usable rows
+                let lookup_degree = meta.lookups[prev.lookups..end.lookups]
+                    .iter()
+                    .map(|lookup| {
+                        let max_degree = |exprs: &[Expression<F>]| {
+                            exprs.iter().map(Expression::degree).max().unwrap_or(1)
+                        };
+                        2 + max_degree(lookup.input_expressions())
+                            + max_degree(lookup.table_expressions())
+                    })
+                    .max()
+                    .unwrap_or_default();
+                let part = PartCost {
+                    name,
+                    advice_columns: end.advice_columns - prev.advice_columns,
+                    fixed_columns: end.fixed_columns - prev.fixed_columns,
+                    selectors: end.selectors - prev.selectors,
+                    constraints: gates[prev.gates..end.gates]
+                        .iter()
+                        .map(|gate| gate.polynomials().len())
+                        .sum(),
+                    lookups: end.lookups - prev.lookups,
+                    permutation_columns: end.permutation_columns - prev.permutation_columns,
+                    gate_degree,
+                    lookup_degree,
+                    committed_polys: (end.advice_columns - prev.advice_columns) as f64
+                        + 3.0 * (end.lookups - prev.lookups) as f64
+                        + (end.permutation_columns - prev.permutation_columns) as f64
+                            / degree.saturating_sub(2).max(1) as f64,
+                };
+                prev = *end;
+                part
+            })
+            .collect();
+        Self { degree, parts }
+    }
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: f64 = self.parts.iter().map(|part| part.committed_polys).sum();
+        writeln!(f, "circuit degree: {}", self.degree)?;
+        writeln!(
+            f,
+            "| part | advice | fixed | selectors | constraints | lookups | permutation | gate degree | lookup degree | committed polys | cost share |"
+        )?;
+        writeln!(f, "|---|---|---|---|---|---|---|---|---|---|---|")?;
+        for part in &self.parts {
+            writeln!(
+                f,
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {:.1} | {:.1}% |",
+                part.name,
+                part.advice_columns,
+                part.fixed_columns,
+                part.selectors,
+                part.constraints,
+                part.lookups,
+                part.permutation_columns,
+                part.gate_degree,
+                part.lookup_degree,
+                part.committed_polys,
+                100.0 * part.committed_polys / total.max(1.0)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -36,6 +36,10 @@ use halo2_proofs::plonk::SecondPhase;
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{
         BytecodeFieldTag, BytecodeTable, CopyTable, LookupTable, RwTable, RwTableTag,
         TxContextFieldTag, TxTable,
@@ -199,6 +203,10 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                     CopyDataType::Precompile(PrecompileCalls::Blake2F),
                     Rotation::cur(),
                 )(meta),
+                tag.value_equals(
+                    CopyDataType::Precompile(PrecompileCalls::P256Verify),
+                    Rotation::cur(),
+                )(meta),
             ]);
             vec![
                 enabled.expr() * (is_precompile - precompiles),
@@ -670,7 +678,7 @@ impl<F: Field> CopyCircuitConfig<F> {
         max_copy_rows: usize,
         challenges: Challenges<Value<F>>,
     ) -> Result<(), Error> {
-        let copy_rows_needed = copy_events.iter().map(|c| c.bytes.len() * 2).sum::<usize>();
+        let copy_rows_needed = copy_events.iter().map(CopyEvent::num_rows).sum::<usize>();
 
         // The `+ 2` is used to take into account the two extra empty copy rows needed
         // to satisfy the query at `Rotation(2)` performed inside of the
@@ -963,7 +971,7 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
             block
                 .copy_events
                 .iter()
-                .map(|c| c.bytes.len() * 2)
+                .map(CopyEvent::num_rows)
                 .sum::<usize>()
                 + 2,
             block.circuits_params.max_copy_rows,
@@ -981,6 +989,39 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
     }
 }
 
+impl<F: Field> SuperSubCircuit<F> for CopyCircuit<F> {
+    const NAME: &'static str = "copy circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[
+        (SharedTable::Tx, 1),
+        (SharedTable::Rw, 1),
+        (SharedTable::Bytecode, 1),
+        (SharedTable::Copy, 1),
+    ];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Copy];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        CopyCircuitConfig::new(
+            meta,
+            CopyCircuitConfigArgs {
+                tx_table: tables.tx().clone(),
+                rw_table: *tables.rw(),
+                bytecode_table: tables.bytecode().clone(),
+                copy_table: *tables.copy(),
+                q_enable: tables.q_copy(),
+                challenges: args.challenges.clone(),
+            },
+        )
+    }
+
+    fn from_block(block: &witness::Block<F>) -> Self {
+        Self::new_from_block_no_external(block)
+    }
+}
+
 #[cfg(test)]
 mod copy_circuit_stats {
     use crate::{
@@ -1016,7 +1057,7 @@ mod copy_circuit_stats {
                 block
                     .copy_events
                     .iter()
-                    .map(|c| c.bytes.len() * 2)
+                    .map(|c| c.num_rows())
                     .sum::<usize>()
             },
         );
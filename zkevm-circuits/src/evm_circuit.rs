@@ -20,10 +20,19 @@ pub use self::EvmCircuit as TestEvmCircuit;
 pub use crate::witness;
 use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
+    state_circuit::StateCircuit,
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SubCircuitRegistry, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{
-        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable, TxTable,
+        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LogsBloomTable, LookupTable,
+        P256VerifyTable, ReceiptTable, RwTable, SigTable, TxTable,
+    },
+    util::{
+        rw_fingerprint::{RwFingerprintConfig, RwFingerprints},
+        SubCircuit, SubCircuitConfig,
     },
-    util::{SubCircuit, SubCircuitConfig},
 };
 use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
@@ -31,7 +40,7 @@ use execution::ExecutionConfig;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
 use table::FixedTableTag;
-use witness::Block;
+use witness::{Block, Rw, RwMap};
 
 /// EvmCircuitConfig implements verification of execution trace of a block.
 #[derive(Clone, Debug)]
@@ -47,6 +56,11 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    receipt_table: ReceiptTable,
+    sig_table: SigTable,
+    p256_verify_table: P256VerifyTable,
+    logs_bloom_table: LogsBloomTable,
+    rw_fingerprint: RwFingerprintConfig,
 }
 
 /// Circuit configuration arguments
@@ -55,7 +69,7 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub challenges: crate::util::Challenges<Expression<F>>,
     /// TxTable
     pub tx_table: TxTable,
-    /// RwTable
+    /// RwTable, holding the rws in chronological order
     pub rw_table: RwTable,
     /// BytecodeTable
     pub bytecode_table: BytecodeTable,
@@ -67,6 +81,14 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// ExpTable
     pub exp_table: ExpTable,
+    /// ReceiptTable
+    pub receipt_table: ReceiptTable,
+    /// SigTable
+    pub sig_table: SigTable,
+    /// P256VerifyTable
+    pub p256_verify_table: P256VerifyTable,
+    /// LogsBloomTable
+    pub logs_bloom_table: LogsBloomTable,
 }
 
 /// Circuit exported cells after synthesis, used for subcircuit
@@ -92,10 +114,15 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            receipt_table,
+            sig_table,
+            p256_verify_table,
+            logs_bloom_table,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
         let byte_table = [(); 1].map(|_| meta.fixed_column());
+        let rw_fingerprint = RwFingerprintConfig::configure(meta, &rw_table, &challenges);
         let execution = Box::new(ExecutionConfig::configure(
             meta,
             challenges,
@@ -108,6 +135,10 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &exp_table,
+            &receipt_table,
+            &sig_table,
+            &p256_verify_table,
+            &logs_bloom_table,
         ));
 
         meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
@@ -121,6 +152,10 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         copy_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
         exp_table.annotate_columns(meta);
+        receipt_table.annotate_columns(meta);
+        sig_table.annotate_columns(meta);
+        p256_verify_table.annotate_columns(meta);
+        logs_bloom_table.annotate_columns(meta);
 
         Self {
             fixed_table,
@@ -133,6 +168,11 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            receipt_table,
+            sig_table,
+            p256_verify_table,
+            logs_bloom_table,
+            rw_fingerprint,
         }
     }
 }
@@ -179,6 +219,38 @@ impl<F: Field> EvmCircuitConfig<F> {
             },
         )
     }
+
+    /// Load the rw table with the rws in chronological order, along with
+    /// their fingerprint. The rws of the previous chunks are not supported
+    /// yet, so the fingerprint starts at 1.
+    pub fn load_rw_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rws: &[Rw],
+        n_rows: usize,
+        challenges: &crate::util::Challenges<Value<F>>,
+    ) -> Result<RwFingerprints<Assigned<F>>, Error> {
+        layouter.assign_region(
+            || "rw table",
+            |mut region| {
+                self.rw_table
+                    .load_with_region(&mut region, rws, n_rows, challenges.evm_word())?;
+                let (rows, _) = RwMap::table_assignments_prepad(rws, n_rows);
+                let rows = rows
+                    .iter()
+                    .map(|row| row.table_assignment(challenges.evm_word()))
+                    .collect::<Vec<_>>();
+                let fingerprints = self.rw_fingerprint.assign(
+                    &mut region,
+                    &rows,
+                    Value::known(F::one()),
+                    challenges,
+                )?;
+                region.constrain_constant(fingerprints.prev.0, F::one())?;
+                Ok(fingerprints)
+            },
+        )
+    }
 }
 
 /// Tx Circuit for verifying transaction signatures
@@ -188,6 +260,7 @@ pub struct EvmCircuit<F: Field> {
     pub block: Option<Block<F>>,
     fixed_table_tags: Vec<FixedTableTag>,
     pub(crate) exports: std::cell::RefCell<Option<EvmCircuitExports<Assigned<F>>>>,
+    pub(crate) rw_fingerprints: std::cell::RefCell<Option<RwFingerprints<Assigned<F>>>>,
 }
 
 impl<F: Field> EvmCircuit<F> {
@@ -297,12 +370,84 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
 
         config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
         config.load_byte_table(layouter)?;
+        let rw_fingerprints = config.load_rw_table(
+            layouter,
+            &block.rws.table_assignments_chronological(),
+            block.circuits_params.max_rws,
+            challenges,
+        )?;
+        self.rw_fingerprints.borrow_mut().replace(rw_fingerprints);
         let export = config.execution.assign_block(layouter, block, challenges)?;
         self.exports.borrow_mut().replace(export);
         Ok(())
     }
 }
 
+impl<F: Field> SuperSubCircuit<F> for EvmCircuit<F> {
+    const NAME: &'static str = "evm circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[
+        (SharedTable::Tx, 1),
+        (SharedTable::ChronologicalRw, 1),
+        (SharedTable::Bytecode, 1),
+        (SharedTable::Block, 1),
+        (SharedTable::Copy, 1),
+        (SharedTable::Keccak, 1),
+        (SharedTable::Exp, 1),
+        (SharedTable::Receipt, 1),
+        (SharedTable::Sig, 1),
+        (SharedTable::P256Verify, 1),
+        (SharedTable::LogsBloom, 1),
+    ];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::ChronologicalRw];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        EvmCircuitConfig::new(
+            meta,
+            EvmCircuitConfigArgs {
+                challenges: args.challenges.clone(),
+                tx_table: tables.tx().clone(),
+                rw_table: *tables.chronological_rw(),
+                bytecode_table: tables.bytecode().clone(),
+                block_table: tables.block().clone(),
+                copy_table: *tables.copy(),
+                keccak_table: tables.keccak().clone(),
+                exp_table: *tables.exp(),
+                receipt_table: tables.receipt().clone(),
+                sig_table: tables.sig().clone(),
+                p256_verify_table: tables.p256_verify().clone(),
+                logs_bloom_table: tables.logs_bloom().clone(),
+            },
+        )
+    }
+
+    /// Constrain the fingerprints of the rw tables of the EVM and state
+    /// circuits to be equal, so that both tables hold the same rws.
+    fn connect<R: SubCircuitRegistry<F>>(
+        &self,
+        circuits: &R,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let state_circuit = circuits.find::<StateCircuit<F>>().ok_or(Error::Synthesis)?;
+        let evm = self.rw_fingerprints.borrow().clone();
+        let state = state_circuit.rw_fingerprints.borrow().clone();
+        let (evm, state) = match (evm, state) {
+            (Some(evm), Some(state)) => (evm, state),
+            _ => return Err(Error::Synthesis),
+        };
+        layouter.assign_region(
+            || "rw fingerprints connecting region",
+            |mut region| {
+                region.constrain_equal(evm.prev.0, state.prev.0)?;
+                region.constrain_equal(evm.next.0, state.next.0)
+            },
+        )
+    }
+}
+
 /// create fixed_table_tags needed given witness block
 pub(crate) fn detect_fixed_table_tags<F: Field>(block: &Block<F>) -> Vec<FixedTableTag> {
     let need_bitwise_lookup = block.txs.iter().any(|tx| {
@@ -409,6 +554,10 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let keccak_table = KeccakTable::construct(meta);
         let exp_table = ExpTable::construct(meta);
+        let receipt_table = ReceiptTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
+        let p256_verify_table = P256VerifyTable::construct(meta);
+        let logs_bloom_table = LogsBloomTable::construct(meta);
         (
             EvmCircuitConfig::new(
                 meta,
@@ -421,6 +570,10 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     copy_table,
                     keccak_table,
                     exp_table,
+                    receipt_table,
+                    sig_table,
+                    p256_verify_table,
+                    logs_bloom_table,
                 },
             ),
             challenges,
@@ -446,12 +599,6 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             &challenges,
         )?;
         block.rws.check_rw_counter_sanity();
-        config.rw_table.load(
-            &mut layouter,
-            &block.rws.table_assignments(),
-            block.circuits_params.max_rws,
-            challenges.evm_word(),
-        )?;
         config
             .bytecode_table
             .dev_load(&mut layouter, block.bytecodes.values(), &challenges)?;
@@ -465,6 +612,23 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .keccak_table
             .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
         config.exp_table.dev_load(&mut layouter, block)?;
+        config.receipt_table.load(
+            &mut layouter,
+            &block.txs,
+            block.circuits_params.max_txs,
+            &challenges,
+        )?;
+        config
+            .sig_table
+            .dev_load(&mut layouter, &block.ecrecover_sign_datas(), &challenges)?;
+        config.p256_verify_table.dev_load(
+            &mut layouter,
+            &block.p256_verify_inputs(),
+            &challenges,
+        )?;
+        config
+            .logs_bloom_table
+            .dev_load(&mut layouter, &block.txs, &challenges)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -646,7 +810,17 @@ mod evm_circuit_stats {
             keccak_table,
             LOOKUP_CONFIG[6].1,
             exp_table,
-            LOOKUP_CONFIG[7].1
+            LOOKUP_CONFIG[7].1,
+            receipt_table,
+            LOOKUP_CONFIG[8].1,
+            sig_table,
+            LOOKUP_CONFIG[9].1,
+            p256_verify_table,
+            LOOKUP_CONFIG[10].1,
+            precompile_rlc,
+            LOOKUP_CONFIG[11].1,
+            logs_bloom_table,
+            LOOKUP_CONFIG[12].1
         );
     }
 
@@ -20,11 +20,11 @@ use crate::{
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
-    table::{LookupTable, RwTableTag, TxReceiptFieldTag},
-    util::{query_expression, Challenges, Expr},
+    table::{CopyTable, LookupTable, RwTableTag, TxReceiptFieldTag},
+    util::{query_expression, word, Challenges, Expr},
 };
 use bus_mapping::util::read_env_var;
-use eth_types::{Field, ToLittleEndian};
+use eth_types::Field;
 use gadgets::util::not;
 use halo2_proofs::{
     arithmetic::FieldExt,
@@ -61,6 +61,8 @@ mod address;
 mod balance;
 mod begin_tx;
 mod bitwise;
+mod blobbasefee;
+mod blobhash;
 mod block_ctx;
 mod blockhash;
 mod byte;
@@ -136,6 +138,8 @@ mod sload;
 mod sstore;
 mod stop;
 mod swap;
+mod tload;
+mod tstore;
 
 use self::{logs::LogGadget, precompiles::BasePrecompileGadget, sha3::Sha3Gadget};
 use add_sub::AddSubGadget;
@@ -144,6 +148,8 @@ use address::AddressGadget;
 use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
+use blobbasefee::BlobBaseFeeGadget;
+use blobhash::BlobHashGadget;
 use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
 use blockhash::BlockHashGadget;
 use byte::ByteGadget;
@@ -217,6 +223,8 @@ use sload::SloadGadget;
 use sstore::SstoreGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
+use tload::TloadGadget;
+use tstore::TstoreGadget;
 
 pub(crate) trait ExecutionGadget<F: FieldExt> {
     const NAME: &'static str;
@@ -269,6 +277,8 @@ pub(crate) struct ExecutionConfig<F> {
     address_gadget: Box<AddressGadget<F>>,
     balance_gadget: Box<BalanceGadget<F>>,
     bitwise_gadget: Box<BitwiseGadget<F>>,
+    blob_base_fee_gadget: Box<BlobBaseFeeGadget<F>>,
+    blob_hash_gadget: Box<BlobHashGadget<F>>,
     byte_gadget: Box<ByteGadget<F>>,
     call_op_gadget: Box<CallOpGadget<F>>,
     call_value_gadget: Box<CallValueGadget<F>>,
@@ -319,6 +329,8 @@ pub(crate) struct ExecutionConfig<F> {
     sstore_gadget: Box<SstoreGadget<F>>,
     stop_gadget: Box<StopGadget<F>>,
     swap_gadget: Box<SwapGadget<F>>,
+    tload_gadget: Box<TloadGadget<F>>,
+    tstore_gadget: Box<TstoreGadget<F>>,
     blockhash_gadget: Box<BlockHashGadget<F>>,
     block_ctx_u64_gadget: Box<BlockCtxU64Gadget<F>>,
     block_ctx_u160_gadget: Box<BlockCtxU160Gadget<F>>,
@@ -360,6 +372,8 @@ pub(crate) struct ExecutionConfig<F> {
     precompile_bn128pairing_gadget:
         Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBn256Pairing }>>,
     precompile_blake2f_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBlake2f }>>,
+    precompile_p256verify_gadget:
+        Box<BasePrecompileGadget<F, { ExecutionState::PrecompileP256Verify }>>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
@@ -374,9 +388,13 @@ impl<F: Field> ExecutionConfig<F> {
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
-        copy_table: &dyn LookupTable<F>,
+        copy_table: &CopyTable,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        receipt_table: &dyn LookupTable<F>,
+        sig_table: &dyn LookupTable<F>,
+        p256_verify_table: &dyn LookupTable<F>,
+        logs_bloom_table: &dyn LookupTable<F>,
     ) -> Self {
         let mut instrument = Instrument::default();
         let q_usable = meta.complex_selector();
@@ -544,6 +562,8 @@ impl<F: Field> ExecutionConfig<F> {
             add_sub_gadget: configure_gadget!(),
             addmod_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
+            blob_base_fee_gadget: configure_gadget!(),
+            blob_hash_gadget: configure_gadget!(),
             byte_gadget: configure_gadget!(),
             call_op_gadget: configure_gadget!(),
             call_value_gadget: configure_gadget!(),
@@ -597,6 +617,8 @@ impl<F: Field> ExecutionConfig<F> {
             sstore_gadget: configure_gadget!(),
             stop_gadget: configure_gadget!(),
             swap_gadget: configure_gadget!(),
+            tload_gadget: configure_gadget!(),
+            tstore_gadget: configure_gadget!(),
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
@@ -632,6 +654,7 @@ impl<F: Field> ExecutionConfig<F> {
             precompile_bn128mul_gadget: configure_gadget!(),
             precompile_bn128pairing_gadget: configure_gadget!(),
             precompile_blake2f_gadget: configure_gadget!(),
+            precompile_p256verify_gadget: configure_gadget!(),
             // step and presets
             step: step_curr,
             height_map,
@@ -650,6 +673,10 @@ impl<F: Field> ExecutionConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            receipt_table,
+            sig_table,
+            p256_verify_table,
+            logs_bloom_table,
             &challenges,
             &cell_manager,
         );
@@ -902,9 +929,13 @@ impl<F: Field> ExecutionConfig<F> {
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
         block_table: &dyn LookupTable<F>,
-        copy_table: &dyn LookupTable<F>,
+        copy_table: &CopyTable,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        receipt_table: &dyn LookupTable<F>,
+        sig_table: &dyn LookupTable<F>,
+        p256_verify_table: &dyn LookupTable<F>,
+        logs_bloom_table: &dyn LookupTable<F>,
         challenges: &Challenges<Expression<F>>,
         cell_manager: &CellManager<F>,
     ) {
@@ -913,16 +944,20 @@ impl<F: Field> ExecutionConfig<F> {
                 let name = format!("{:?}", table);
                 meta.lookup_any(Box::leak(name.into_boxed_str()), |meta| {
                     let table_expressions = match table {
-                        Table::Fixed => fixed_table,
-                        Table::Tx => tx_table,
-                        Table::Rw => rw_table,
-                        Table::Bytecode => bytecode_table,
-                        Table::Block => block_table,
-                        Table::Copy => copy_table,
-                        Table::Keccak => keccak_table,
-                        Table::Exp => exp_table,
-                    }
-                    .table_exprs(meta);
+                        Table::Fixed => fixed_table.table_exprs(meta),
+                        Table::Tx => tx_table.table_exprs(meta),
+                        Table::Rw => rw_table.table_exprs(meta),
+                        Table::Bytecode => bytecode_table.table_exprs(meta),
+                        Table::Block => block_table.table_exprs(meta),
+                        Table::Copy => copy_table.table_exprs(meta),
+                        Table::Keccak => keccak_table.table_exprs(meta),
+                        Table::Exp => exp_table.table_exprs(meta),
+                        Table::Receipt => receipt_table.table_exprs(meta),
+                        Table::Sig => sig_table.table_exprs(meta),
+                        Table::P256Verify => p256_verify_table.table_exprs(meta),
+                        Table::PrecompileRlc => copy_table.precompile_rlc_table_exprs(meta),
+                        Table::LogsBloom => logs_bloom_table.table_exprs(meta),
+                    };
                     vec![(
                         column.expr(),
                         rlc::expr(&table_expressions, challenges.lookup_input()),
@@ -1203,7 +1238,7 @@ impl<F: Field> ExecutionConfig<F> {
 
         let withdraw_root_rlc = challenges
             .evm_word()
-            .map(|r| rlc::value(&block.withdraw_root.to_le_bytes(), r));
+            .map(|r| word::rlc(&block.withdraw_root, r));
 
         Ok(EvmCircuitExports {
             withdraw_root: (final_withdraw_root_cell, withdraw_root_rlc.into()),
@@ -1378,6 +1413,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::CALLER => assign_exec_step!(self.caller_gadget),
             ExecutionState::CALLVALUE => assign_exec_step!(self.call_value_gadget),
             ExecutionState::CHAINID => assign_exec_step!(self.chainid_gadget),
+            ExecutionState::BLOBHASH => assign_exec_step!(self.blob_hash_gadget),
+            ExecutionState::BLOBBASEFEE => assign_exec_step!(self.blob_base_fee_gadget),
             ExecutionState::CODECOPY => assign_exec_step!(self.codecopy_gadget),
             ExecutionState::CODESIZE => assign_exec_step!(self.codesize_gadget),
             ExecutionState::CMP => assign_exec_step!(self.comparator_gadget),
@@ -1426,6 +1463,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::SIGNEXTEND => assign_exec_step!(self.signextend_gadget),
             ExecutionState::SLOAD => assign_exec_step!(self.sload_gadget),
             ExecutionState::SSTORE => assign_exec_step!(self.sstore_gadget),
+            ExecutionState::TLOAD => assign_exec_step!(self.tload_gadget),
+            ExecutionState::TSTORE => assign_exec_step!(self.tstore_gadget),
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
             // dummy errors
@@ -1517,6 +1556,9 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::PrecompileBlake2f => {
                 assign_exec_step!(self.precompile_blake2f_gadget)
             }
+            ExecutionState::PrecompileP256Verify => {
+                assign_exec_step!(self.precompile_p256verify_gadget)
+            }
         }
 
         // Fill in the witness values for stored expressions
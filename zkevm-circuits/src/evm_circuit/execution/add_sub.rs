@@ -107,7 +107,10 @@ mod test {
     use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
     use eth_types::{bytecode, evm_types::OpcodeId, Word};
 
-    use mock::TestContext;
+    use mock::{
+        test_ctx::{TraceFault, TraceFaultKind},
+        TestContext,
+    };
 
     fn test_ok(opcode: OpcodeId, a: Word, b: Word) {
         let bytecode = bytecode! {
@@ -129,6 +132,36 @@ mod test {
         test_ok(OpcodeId::SUB, 0x090705.into(), 0x060504.into());
     }
 
+    #[test]
+    fn add_gadget_wrong_result() {
+        let (a, b) = (Word::from(0x030201), Word::from(0x060504));
+        let bytecode = bytecode! {
+            PUSH32(a)
+            PUSH32(b)
+            ADD
+            STOP
+        };
+        // Corrupt the result of ADD, found on top of the stack of STOP.
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .with_faults(&[TraceFault {
+                tx_index: 0,
+                step_index: 3,
+                kind: TraceFaultKind::StackValue {
+                    index: 0,
+                    value: a + b + 1,
+                },
+            }]);
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+                assert!(prover
+                    .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                    .is_err())
+            }))
+            .run();
+    }
+
     #[test]
     fn add_gadget_rand() {
         let a = rand_word();
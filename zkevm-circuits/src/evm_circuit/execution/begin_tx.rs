@@ -1,3 +1,5 @@
+#[cfg(feature = "prague")]
+use crate::evm_circuit::util::common_gadget::AuthorizationListGadget;
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
@@ -12,18 +14,21 @@ use crate::{
             },
             from_bytes, is_precompiled,
             math_gadget::{
-                ConstantDivisionGadget, ContractCreateGadget, IsEqualGadget, IsZeroGadget,
-                LtGadget, MulWordByU64Gadget, RangeCheckGadget,
+                AddWordsGadget, ConstantDivisionGadget, ContractCreateGadget, IsEqualGadget,
+                IsZeroGadget, LtGadget, LtWordGadget, MulWordByU64Gadget, RangeCheckGadget,
             },
-            CachedRegion, Cell, StepRws, Word,
+            pow_of_two_expr, CachedRegion, Cell, StepRws, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::{
         AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxFieldTag as TxContextFieldTag,
     },
+    util::word::WordLoHi,
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar};
+#[cfg(feature = "rip7212")]
+use bus_mapping::precompile::PrecompileCalls;
+use eth_types::{geth_types::TxType, Address, Field, ToLittleEndian, ToScalar};
 use ethers_core::utils::{get_contract_address, keccak256, rlp::RlpStream};
 use gadgets::util::{expr_from_bytes, not, or, Expr};
 use halo2_proofs::{circuit::Value, plonk::Error};
@@ -41,10 +46,15 @@ use gadgets::util::select;
 pub(crate) struct BeginTxGadget<F> {
     tx_id: Cell<F>,
     tx_nonce: Cell<F>,
+    caller_nonce: Cell<F>,
+    is_nonce_match: IsEqualGadget<F>,
+    // EIP-2681: the nonce can't be increased past 2^64 - 1
+    is_nonce_below_max: LtGadget<F, N_BYTES_U64>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
     mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
     tx_fee: Word<F>,
+    tx_fee_carry_lo: Cell<F>,
     tx_caller_address: Cell<F>,
     tx_caller_address_is_zero: IsZeroGadget<F>,
     tx_callee_address: Cell<F>,
@@ -60,10 +70,17 @@ pub(crate) struct BeginTxGadget<F> {
     reversion_info: ReversionInfo<F>,
     intrinsic_gas_cost: Cell<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    // EIP-7623: the gas of a valid tx of a Prague block covers the floor cost
+    // of its call data.
+    tx_is_prague: Cell<F>,
+    tx_data_tokens: Cell<F>,
+    sufficient_gas_for_floor: RangeCheckGadget<F, N_BYTES_GAS>,
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
     phase2_code_hash: Cell<F>,
     is_empty_code_hash: IsEqualGadget<F>,
     is_precompile_lt: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    #[cfg(feature = "rip7212")]
+    is_p256_verify: IsEqualGadget<F>,
     /// Keccak256(RLP([tx_caller_address, tx_nonce]))
     caller_nonce_hash_bytes: [Cell<F>; N_BYTES_WORD],
     /// RLP gadget for CREATE address.
@@ -76,7 +93,21 @@ pub(crate) struct BeginTxGadget<F> {
     // coinbase, and may be duplicate.
     // <https://github.com/ethereum/go-ethereum/blob/604e215d1bb070dff98fb76aa965064c74e3633f/core/state/statedb.go#LL1119C9-L1119C9>
     is_coinbase_warm: Cell<F>,
+    tx_type: Cell<F>,
+    is_l1_msg: IsEqualGadget<F>,
     tx_l1_fee: TxL1FeeGadget<F>,
+    // An invalid tx fails the nonce or the balance check, and is included as
+    // a no-op.
+    tx_is_invalid: Cell<F>,
+    caller_balance: Word<F>,
+    tx_total_cost: AddWordsGadget<F, 2, false>,
+    is_balance_insufficient: LtWordGadget<F>,
+    // EIP-7702: the authorizations of a set-code tx are applied after the
+    // access list is warmed up.
+    #[cfg(feature = "prague")]
+    tx_authorization_list_length: Cell<F>,
+    #[cfg(feature = "prague")]
+    authorization_list: AuthorizationListGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -103,7 +134,19 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             ]
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
 
-        let tx_l1_fee = TxL1FeeGadget::construct(cb, tx_id.expr(), tx_data_gas_cost.expr());
+        let tx_type = cb.tx_context(tx_id.expr(), TxContextFieldTag::TxType, None);
+        let is_l1_msg =
+            IsEqualGadget::construct(cb, tx_type.expr(), u64::from(TxType::L1Msg).expr());
+        let tx_is_invalid = cb.tx_context(tx_id.expr(), TxContextFieldTag::IsInvalid, None);
+        let is_valid = not::expr(tx_is_invalid.expr());
+
+        let tx_l1_fee = TxL1FeeGadget::construct(
+            cb,
+            tx_id.expr(),
+            call_id.expr(),
+            tx_data_gas_cost.expr(),
+            is_l1_msg.expr(),
+        );
 
         cb.call_context_lookup(
             1.expr(),
@@ -152,12 +195,28 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         });
 
         // Increase caller's nonce.
-        // (tx caller's nonce always increases even when tx ends with error)
+        // (tx caller's nonce always increases even when tx ends with error,
+        // but is left unchanged by an invalid tx)
+        let caller_nonce = cb.query_cell();
+        let is_nonce_match = IsEqualGadget::construct(cb, caller_nonce.expr(), tx_nonce.expr());
+        let is_nonce_below_max = LtGadget::construct(cb, caller_nonce.expr(), u64::MAX.expr());
+        cb.condition(is_valid.clone(), |cb| {
+            cb.require_equal(
+                "tx nonce == caller nonce",
+                is_nonce_match.expr(),
+                true.expr(),
+            );
+            cb.require_equal(
+                "caller nonce < 2^64 - 1",
+                is_nonce_below_max.expr(),
+                true.expr(),
+            );
+        });
         cb.account_write(
             tx_caller_address.expr(),
             AccountFieldTag::Nonce,
-            tx_nonce.expr() + 1.expr(),
-            tx_nonce.expr(),
+            caller_nonce.expr() + is_valid.clone(),
+            caller_nonce.expr(),
             None,
         ); // rwc_delta += 1
 
@@ -168,18 +227,37 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             MulWordByU64Gadget::construct(cb, tx_gas_price.clone(), tx_gas.expr());
         let tx_fee = cb.query_word_rlc();
 
+        // The 64-bit L1 fee carries at most 1 into the high 128 bits, and the
+        // sum must not overflow 256 bits.
+        let tx_fee_carry_lo = cb.query_bool();
         cb.require_equal(
-            "tx_fee == l1_fee + l2_fee",
+            "tx_fee_lo + carry_lo ⋅ 2^128 == l1_fee + l2_fee_lo",
+            from_bytes::expr(&tx_fee.cells[..16]) + tx_fee_carry_lo.expr() * pow_of_two_expr(128),
             from_bytes::expr(&tx_l1_fee.tx_l1_fee().cells[..])
                 + from_bytes::expr(&mul_gas_fee_by_gas.product().cells[..16]),
-            from_bytes::expr(&tx_fee.cells[..16]),
+        );
+        cb.require_equal(
+            "tx_fee_hi == l2_fee_hi + carry_lo",
+            from_bytes::expr(&tx_fee.cells[16..]),
+            from_bytes::expr(&mul_gas_fee_by_gas.product().cells[16..]) + tx_fee_carry_lo.expr(),
         );
 
-        // a valid precompile address is: 1 <= addr <= 9 (addr != 0 && addr < 0xA)
+        // a valid precompile address is: 1 <= addr <= 9 (addr != 0 && addr < 0xA),
+        // or 0x100 when RIP-7212 is enabled.
         let is_precompile_lt = LtGadget::construct(cb, tx_callee_address.expr(), 0xA.expr());
+        #[cfg(feature = "rip7212")]
+        let is_p256_verify = IsEqualGadget::construct(
+            cb,
+            tx_callee_address.expr(),
+            u64::from(PrecompileCalls::P256Verify).expr(),
+        );
+        #[cfg(feature = "rip7212")]
+        let is_precompile_address = or::expr([is_precompile_lt.expr(), is_p256_verify.expr()]);
+        #[cfg(not(feature = "rip7212"))]
+        let is_precompile_address = is_precompile_lt.expr();
         let is_precompile = and::expr([
             not::expr(tx_callee_address_is_zero.expr()),
-            is_precompile_lt.expr(),
+            is_precompile_address,
         ]);
 
         let tx_call_data_word_length =
@@ -189,9 +267,16 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         // Use intrinsic gas
         // TODO2: contrain calling precompile directly
 
+        #[cfg(feature = "prague")]
+        let tx_authorization_list_length = cb.tx_context(
+            tx_id.expr(),
+            TxContextFieldTag::AuthorizationListLength,
+            None,
+        );
+
         let intrinsic_gas_cost = cb.query_cell();
         #[cfg(feature = "reject-eip2718")]
-        cb.condition(not::expr(is_precompile.expr()), |cb| {
+        cb.condition(not::expr(is_precompile.expr()) * is_valid.clone(), |cb| {
             // Calculate gas cost of init code only for EIP-3860 of Shanghai.
             #[cfg(feature = "shanghai")]
             let init_code_gas_cost = select::expr(
@@ -203,6 +288,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             #[cfg(not(feature = "shanghai"))]
             let init_code_gas_cost = 0.expr();
 
+            // Calculate gas cost of the authorizations of a set-code tx of
+            // EIP-7702.
+            #[cfg(feature = "prague")]
+            let authorization_gas_cost = tx_authorization_list_length.expr()
+                * eth_types::eip7702::PER_EMPTY_ACCOUNT_COST.expr();
+            #[cfg(not(feature = "prague"))]
+            let authorization_gas_cost = 0.expr();
+
             cb.require_equal(
                 "calculate intrinsic gas cost",
                 intrinsic_gas_cost.expr(),
@@ -211,12 +304,30 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     eth_types::evm_types::GasCost::CREATION_TX.expr(),
                     eth_types::evm_types::GasCost::TX.expr(),
                 ) + tx_call_data_gas_cost.expr()
-                    + init_code_gas_cost,
+                    + init_code_gas_cost
+                    + authorization_gas_cost,
             )
         });
         // Check gas_left is sufficient
         let gas_left = tx_gas.expr() - intrinsic_gas_cost.expr();
         let sufficient_gas_left = RangeCheckGadget::construct(cb, gas_left.clone());
+        let tx_is_prague = cb.tx_context(tx_id.expr(), TxContextFieldTag::IsPrague, None);
+        let (tx_data_tokens, sufficient_gas_for_floor) = {
+            let tx_data_tokens = cb.query_cell();
+            cb.require_equal(
+                "call data gas cost == 4 * call data tokens",
+                tx_call_data_gas_cost.expr(),
+                4.expr() * tx_data_tokens.expr(),
+            );
+            let floor = eth_types::evm_types::GasCost::TX.expr()
+                + eth_types::evm_types::gas_utils::TOTAL_COST_FLOOR_PER_TOKEN.expr()
+                    * tx_data_tokens.expr();
+            let sufficient_gas_for_floor = RangeCheckGadget::construct(
+                cb,
+                tx_gas.expr() - floor * tx_is_prague.expr() * is_valid.clone(),
+            );
+            (tx_data_tokens, sufficient_gas_for_floor)
+        };
 
         // Prepare access list of caller and callee
         cb.account_access_list_write(
@@ -255,6 +366,20 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         ); // rwc_delta += 1
 
+        // Apply the authorizations of a set-code tx.
+        #[cfg(feature = "prague")]
+        let authorization_list = AuthorizationListGadget::construct(
+            cb,
+            tx_id.expr(),
+            call_id.expr(),
+            tx_authorization_list_length.expr(),
+            is_valid.clone(),
+        );
+        #[cfg(feature = "prague")]
+        let authorization_rw_delta = authorization_list.rw_delta();
+        #[cfg(not(feature = "prague"))]
+        let authorization_rw_delta = 0.expr();
+
         // Read code_hash of callee
         let phase2_code_hash = cb.query_cell_phase2();
         let is_empty_code_hash =
@@ -269,6 +394,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             and::expr([
                 not::expr(tx_is_create.expr()),
                 not::expr(is_precompile.expr()),
+                is_valid.clone(),
             ]),
             |cb| {
                 cb.account_read(
@@ -279,17 +405,39 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             },
         );
 
+        // An invalid tx reads the caller's balance instead, to show that it
+        // can't pay for the fee and the value.
+        let caller_balance = cb.query_word_rlc();
+        cb.condition(tx_is_invalid.expr(), |cb| {
+            cb.account_read(
+                tx_caller_address.expr(),
+                AccountFieldTag::Balance,
+                caller_balance.expr(),
+            ); // rwc_delta += 1
+        });
+        let tx_total_cost = cb.query_word_rlc();
+        let tx_total_cost =
+            AddWordsGadget::construct(cb, [tx_fee.clone(), tx_value.clone()], tx_total_cost);
+        let is_balance_insufficient =
+            LtWordGadget::construct(cb, &caller_balance, tx_total_cost.sum());
+
         // Transfer value from caller to callee, creating account if necessary.
-        let transfer_with_gas_fee = TransferWithGasFeeGadget::construct(
-            cb,
-            tx_caller_address.expr(),
-            call_callee_address.expr(),
-            or::expr([not::expr(callee_not_exists.expr()), is_precompile.expr()]),
-            tx_is_create.expr(),
-            tx_value.clone(),
-            tx_fee.clone(),
-            &mut reversion_info,
-        );
+        // The value of an L1 message tx is transferred like the one of any tx
+        // rather than minted, as the deposit txs of OP-stack chains do: on
+        // this chain the sender of an L1 message holds the value already, so
+        // minting is out of scope.
+        let transfer_with_gas_fee = cb.condition(is_valid.clone(), |cb| {
+            TransferWithGasFeeGadget::construct(
+                cb,
+                tx_caller_address.expr(),
+                call_callee_address.expr(),
+                or::expr([not::expr(callee_not_exists.expr()), is_precompile.expr()]),
+                tx_is_create.expr(),
+                tx_value.clone(),
+                tx_fee.clone(),
+                &mut reversion_info,
+            )
+        });
 
         let caller_nonce_hash_bytes = array_init::array_init(|_| cb.query_byte());
         let create = ContractCreateGadget::construct(cb);
@@ -319,7 +467,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         });
 
         // 1. Handle contract creation transaction.
-        cb.condition(tx_is_create.expr(), |cb| {
+        cb.condition(tx_is_create.expr() * is_valid.clone(), |cb| {
             let output_rlc = cb.word_rlc::<N_BYTES_WORD>(
                 caller_nonce_hash_bytes
                     .iter()
@@ -375,6 +523,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write TxAccessListAccount (Caller)
                 //   - Write TxAccessListAccount (Callee)
                 //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                //   - an AuthorizationListGadget only for Prague
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
                 //   - Write CallContext Depth
@@ -394,7 +543,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     21.expr()
                         + tx_l1_fee.rw_delta()
                         + transfer_with_gas_fee.rw_delta()
-                        + SHANGHAI_RW_DELTA.expr(),
+                        + SHANGHAI_RW_DELTA.expr()
+                        + authorization_rw_delta.clone(),
                 ),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
@@ -411,7 +561,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         });
 
         // 2. Handle call to precompiled contracts.
-        cb.condition(is_precompile.expr(), |cb| {
+        cb.condition(is_precompile.expr() * is_valid.clone(), |cb| {
             cb.require_equal(
                 "precompile should be zero code hash",
                 // FIXME: see in opcodes.rs gen_begin_tx_ops
@@ -441,12 +591,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write TxAccessListAccount (Callee)
                 //   - Write TxAccessListAccount (Coinbase) only for Shanghai
                 //   - a TxL1FeeGadget
+                //   - an AuthorizationListGadget only for Prague
                 //   - a TransferWithGasFeeGadget
                 rw_counter: Delta(
                     7.expr()
                         + tx_l1_fee.rw_delta()
                         + transfer_with_gas_fee.rw_delta()
                         + SHANGHAI_RW_DELTA.expr()
+                        + authorization_rw_delta.clone()
                         // TRICKY:
                         // Process the reversion only for Precompile in begin TX. Since no
                         // associated opcodes could process reversion afterwards
@@ -467,6 +619,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 not::expr(tx_is_create.expr()),
                 no_callee_code.expr(),
                 not::expr(is_precompile.expr()),
+                is_valid.clone(),
             ]),
             |cb| {
                 cb.require_equal(
@@ -492,12 +645,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Coinbase) only for Shanghai
                     //   - Read Account CodeHash
                     //   - a TxL1FeeGadget
+                    //   - an AuthorizationListGadget only for Prague
                     //   - a TransferWithGasFeeGadget
                     rw_counter: Delta(
                         8.expr()
                             + tx_l1_fee.rw_delta()
                             + transfer_with_gas_fee.rw_delta()
-                            + SHANGHAI_RW_DELTA.expr(),
+                            + SHANGHAI_RW_DELTA.expr()
+                            + authorization_rw_delta.clone(),
                     ),
                     call_id: To(call_id.expr()),
                     ..StepStateTransition::any()
@@ -507,7 +662,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
 
         // 4. Call to account with non-empty code.
         cb.condition(
-            and::expr([not::expr(tx_is_create.expr()), not::expr(no_callee_code)]),
+            and::expr([
+                not::expr(tx_is_create.expr()),
+                not::expr(no_callee_code),
+                is_valid.clone(),
+            ]),
             |cb| {
                 // Setup first call's context.
                 for (field_tag, value) in [
@@ -545,6 +704,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
                     //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                    //   - an AuthorizationListGadget only for Prague
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
                     //   - Write CallContext Depth
@@ -564,7 +724,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                         21.expr()
                             + tx_l1_fee.rw_delta()
                             + transfer_with_gas_fee.rw_delta()
-                            + SHANGHAI_RW_DELTA.expr(),
+                            + SHANGHAI_RW_DELTA.expr()
+                            + authorization_rw_delta,
                     ),
                     call_id: To(call_id.expr()),
                     is_root: To(true.expr()),
@@ -578,13 +739,57 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             },
         );
 
+        // 5. Invalid tx, included as a no-op.
+        cb.condition(tx_is_invalid.expr(), |cb| {
+            let is_insufficient = or::expr([
+                tx_total_cost.carry().as_ref().unwrap().expr(),
+                is_balance_insufficient.expr(),
+            ]);
+            cb.require_zero(
+                "Invalid tx fails the nonce or the balance check",
+                is_nonce_match.expr() * is_nonce_below_max.expr() * not::expr(is_insufficient),
+            );
+            cb.require_zero(
+                "Invalid tx is not persistent",
+                reversion_info.is_persistent(),
+            );
+            cb.require_zero("Invalid tx uses no gas", intrinsic_gas_cost.expr());
+            cb.require_equal(
+                "Go to EndTx when Tx is invalid",
+                cb.next.execution_state_selector([ExecutionState::EndTx]),
+                1.expr(),
+            );
+
+            cb.require_step_state_transition(StepStateTransition {
+                // 8 + TxL1FeeGadget associated reads or writes:
+                //   - Write CallContext TxId
+                //   - Write CallContext RwCounterEndOfReversion
+                //   - Write CallContext IsPersistent
+                //   - Write CallContext IsSuccess
+                //   - Write Account (Caller) Nonce
+                //   - Write TxAccessListAccount (Caller)
+                //   - Write TxAccessListAccount (Callee)
+                //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                //   - Read Account (Caller) Balance
+                //   - a TxL1FeeGadget
+                rw_counter: Delta(8.expr() + tx_l1_fee.rw_delta() + SHANGHAI_RW_DELTA.expr()),
+                call_id: To(call_id.expr()),
+                gas_left: To(tx_gas.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
         Self {
             tx_id,
             tx_nonce,
+            caller_nonce,
+            is_nonce_match,
+            is_nonce_below_max,
             tx_gas,
             tx_gas_price,
             mul_gas_fee_by_gas,
             tx_fee,
+            tx_fee_carry_lo,
             tx_caller_address,
             tx_caller_address_is_zero,
             tx_callee_address,
@@ -598,18 +803,33 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_data_gas_cost,
             reversion_info,
             sufficient_gas_left,
+            tx_is_prague,
+            tx_data_tokens,
+            sufficient_gas_for_floor,
             transfer_with_gas_fee,
             phase2_code_hash,
             intrinsic_gas_cost,
             is_empty_code_hash,
             is_precompile_lt,
+            #[cfg(feature = "rip7212")]
+            is_p256_verify,
             caller_nonce_hash_bytes,
             create,
             callee_not_exists,
             is_caller_callee_equal,
             coinbase,
             is_coinbase_warm,
+            tx_type,
+            is_l1_msg,
             tx_l1_fee,
+            tx_is_invalid,
+            caller_balance,
+            tx_total_cost,
+            is_balance_insufficient,
+            #[cfg(feature = "prague")]
+            tx_authorization_list_length,
+            #[cfg(feature = "prague")]
+            authorization_list,
         }
     }
 
@@ -625,35 +845,68 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let zero = eth_types::Word::zero();
 
         let mut rws = StepRws::new(block, step);
-        rws.offset_add(10);
+        rws.offset_add(4 + TxL1FeeGadget::<F>::RW_DELTA);
+        let caller_nonce = rws.next().account_nonce_pair().1;
+        rws.offset_add(7 + TxL1FeeGadget::<F>::RW_DELTA);
 
         #[cfg(feature = "shanghai")]
         let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
         #[cfg(not(feature = "shanghai"))]
         let is_coinbase_warm = false;
 
-        let mut callee_code_hash = zero;
-        if !tx.is_create && !is_precompiled(&tx.callee_address.unwrap_or_default()) {
-            callee_code_hash = rws.next().account_codehash_pair().1;
-        }
-        let callee_exists = is_precompiled(&tx.callee_address.unwrap_or_default())
-            || (!tx.is_create && !callee_code_hash.is_zero());
-        let caller_balance_sub_fee_pair = rws.next().account_balance_pair();
-        let must_create = tx.is_create;
-        if (!callee_exists && !tx.value.is_zero()) || must_create {
-            callee_code_hash = rws.next().account_codehash_pair().1;
+        #[cfg(feature = "prague")]
+        {
+            self.tx_authorization_list_length.assign(
+                region,
+                offset,
+                Value::known(F::from(tx.authorization_list.len() as u64)),
+            )?;
+            self.authorization_list
+                .assign(region, offset, block, tx, &mut rws)?;
         }
+
+        let mut callee_code_hash = zero;
+        let mut caller_balance = zero;
+        let mut caller_balance_sub_fee_pair = (zero, zero);
         let mut caller_balance_sub_value_pair = (zero, zero);
         let mut callee_balance_pair = (zero, zero);
-        if !tx.value.is_zero() {
-            caller_balance_sub_value_pair = rws.next().account_balance_pair();
-            callee_balance_pair = rws.next().account_balance_pair();
-        };
+        if tx.is_invalid {
+            caller_balance = rws.next().account_balance_pair().1;
+        } else {
+            if !tx.is_create && !is_precompiled(&tx.callee_address.unwrap_or_default()) {
+                callee_code_hash = rws.next().account_codehash_pair().1;
+            }
+            let callee_exists = is_precompiled(&tx.callee_address.unwrap_or_default())
+                || (!tx.is_create && !callee_code_hash.is_zero());
+            caller_balance_sub_fee_pair = rws.next().account_balance_pair();
+            let must_create = tx.is_create;
+            if (!callee_exists && !tx.value.is_zero()) || must_create {
+                callee_code_hash = rws.next().account_codehash_pair().1;
+            }
+            if !tx.value.is_zero() {
+                caller_balance_sub_value_pair = rws.next().account_balance_pair();
+                callee_balance_pair = rws.next().account_balance_pair();
+            };
+        }
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
         self.tx_nonce
             .assign(region, offset, Value::known(F::from(tx.nonce)))?;
+        self.caller_nonce
+            .assign(region, offset, Value::known(F::from(caller_nonce.as_u64())))?;
+        self.is_nonce_match.assign(
+            region,
+            offset,
+            F::from(caller_nonce.as_u64()),
+            F::from(tx.nonce),
+        )?;
+        self.is_nonce_below_max.assign(
+            region,
+            offset,
+            F::from(caller_nonce.as_u64()),
+            F::from(u64::MAX),
+        )?;
         self.tx_gas
             .assign(region, offset, Value::known(F::from(tx.gas)))?;
         self.tx_gas_price
@@ -686,6 +939,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, callee_address)?;
         self.is_precompile_lt
             .assign(region, offset, callee_address, F::from(0xA))?;
+        #[cfg(feature = "rip7212")]
+        self.is_p256_verify.assign(
+            region,
+            offset,
+            callee_address,
+            F::from(u64::from(PrecompileCalls::P256Verify)),
+        )?;
         self.call_callee_address.assign(
             region,
             offset,
@@ -730,9 +990,40 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, Value::known(F::from(step.gas_cost)))?;
         self.sufficient_gas_left
             .assign(region, offset, F::from(tx.gas - step.gas_cost))?;
-        let tx_fee = caller_balance_sub_fee_pair.1 - caller_balance_sub_fee_pair.0;
+        self.tx_is_prague
+            .assign(region, offset, Value::known(F::from(tx.is_prague as u64)))?;
+        let tx_data_tokens = tx.call_data_gas_cost / 4;
+        self.tx_data_tokens
+            .assign(region, offset, Value::known(F::from(tx_data_tokens)))?;
+        let floor = if tx.is_prague && !tx.is_invalid {
+            eth_types::evm_types::GasCost::TX.as_u64()
+                + eth_types::evm_types::gas_utils::TOTAL_COST_FLOOR_PER_TOKEN * tx_data_tokens
+        } else {
+            0
+        };
+        self.sufficient_gas_for_floor
+            .assign(region, offset, F::from(tx.gas - floor))?;
+        let is_l1_msg = tx.tx_type.is_l1_msg();
+        let tx_l1_fee = if is_l1_msg {
+            0
+        } else {
+            tx.l1_fee.tx_l1_fee(tx.tx_data_gas_cost).0
+        };
+        let tx_l2_fee = tx.gas_price * tx.gas;
+        // An invalid tx pays nothing, so its fee is not in the balance updates.
+        let tx_fee = if tx.is_invalid {
+            tx_l2_fee + tx_l1_fee
+        } else {
+            caller_balance_sub_fee_pair.1 - caller_balance_sub_fee_pair.0
+        };
         self.tx_fee
             .assign(region, offset, Some(tx_fee.to_le_bytes()))?;
+        let tx_fee_carry_lo = (WordLoHi::from(tx_l2_fee).lo + tx_l1_fee) >> 128;
+        self.tx_fee_carry_lo.assign(
+            region,
+            offset,
+            Value::known(F::from(!tx_fee_carry_lo.is_zero() as u64)),
+        )?;
         log::info!(
             "tx_fee assigned {:?}, gas price {:?}, gas {}",
             tx_fee,
@@ -748,6 +1039,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx.value,
             tx_fee,
         )?;
+        self.tx_is_invalid
+            .assign(region, offset, Value::known(F::from(tx.is_invalid as u64)))?;
+        self.caller_balance
+            .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
+        let (tx_total_cost, _) = tx_fee.overflowing_add(tx.value);
+        self.tx_total_cost
+            .assign(region, offset, [tx_fee, tx.value], tx_total_cost)?;
+        self.is_balance_insufficient
+            .assign(region, offset, caller_balance, tx_total_cost)?;
         self.phase2_code_hash
             .assign(region, offset, region.code_hash(callee_code_hash))?;
         let untrimmed_contract_addr = {
@@ -814,8 +1114,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         self.is_coinbase_warm
             .assign(region, offset, Value::known(F::from(is_coinbase_warm)))?;
 
-        let tx_l1_fee = tx.l1_fee.tx_l1_fee(tx.tx_data_gas_cost).0;
-        let tx_l2_fee = tx.gas_price * tx.gas;
+        let tx_type = F::from(u64::from(tx.tx_type));
+        self.tx_type.assign(region, offset, Value::known(tx_type))?;
+        self.is_l1_msg
+            .assign(region, offset, tx_type, F::from(u64::from(TxType::L1Msg)))?;
+
         if tx_fee != tx_l2_fee + tx_l1_fee {
             log::error!(
                 "begin_tx assign: tx_fee ({}) != tx_l1_fee ({}) + tx_l2_fee ({})",
@@ -831,6 +1134,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx.l1_fee,
             tx.l1_fee_committed,
             tx.tx_data_gas_cost,
+            is_l1_msg,
         )
     }
 }
@@ -839,12 +1143,19 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
 mod test {
     use std::vec;
 
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
-    use bus_mapping::evm::OpcodeId;
-    use eth_types::{self, address, bytecode, evm_types::GasCost, word, Bytecode, Word};
+    use crate::{
+        evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder, witness::block_convert,
+    };
+    use bus_mapping::{evm::OpcodeId, mock::BlockData};
+    use eth_types::{
+        self, address, bytecode, evm_types::GasCost, geth_types::GethData, word, Bytecode, Word,
+    };
     use ethers_core::types::Bytes;
+    use halo2_proofs::halo2curves::bn256::Fr;
 
-    use mock::{eth, gwei, MockTransaction, TestContext, MOCK_ACCOUNTS};
+    use mock::{
+        eth, gwei, test_ctx::helpers::tx_from_1_to_0, MockTransaction, TestContext, MOCK_ACCOUNTS,
+    };
 
     fn gas(call_data: &[u8]) -> Word {
         Word::from(
@@ -1126,6 +1437,29 @@ mod test {
         begin_tx_deploy(0xfffffffffffffffeu64);
     }
 
+    #[test]
+    fn begin_tx_deploy_empty_init_code() {
+        // Nothing is executed, so BeginTx is followed by EndTx and the
+        // contract is created without code.
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .gas_price(gwei(2))
+                    .gas(Word::from(0x10000))
+                    .value(eth(2));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn begin_tx_precompile() {
         let ctx = TestContext::<1, 1>::new(
@@ -1166,4 +1500,130 @@ mod test {
 
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
+
+    // The tx is made invalid after tracing, as the tracer would reject it, and
+    // included with the failed trace without steps that a sequencer produces.
+    fn test_invalid_tx(make_invalid: impl FnOnce(&mut GethData)) {
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        make_invalid(&mut block);
+        block.geth_traces[0].failed = true;
+        block.geth_traces[0].struct_logs.clear();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.include_invalid_txs = true;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert!(builder.block.txs[0].is_invalid);
+
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn begin_tx_invalid_nonce() {
+        test_invalid_tx(|block| block.eth_block.transactions[0].nonce += Word::one());
+    }
+
+    #[test]
+    fn begin_tx_invalid_nonce_max() {
+        test_invalid_tx(|block| {
+            block.accounts[1].nonce = u64::MAX.into();
+            block.eth_block.transactions[0].nonce = u64::MAX.into();
+        });
+    }
+
+    #[test]
+    fn begin_tx_invalid_insufficient_balance() {
+        test_invalid_tx(|block| block.eth_block.transactions[0].value = eth(20));
+    }
+
+    #[test]
+    fn begin_tx_fee_above_128_bits() {
+        // The fee carries from the low into the high 128 bits.
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::one() << 200);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas_price(Word::one() << 120);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[cfg(feature = "prague")]
+    #[test]
+    fn begin_tx_set_code() {
+        use bus_mapping::state_db::CodeDB;
+        use eth_types::{
+            eip7702::{Authorization, PER_EMPTY_ACCOUNT_COST, SET_CODE_TX_TYPE},
+            evm_types::Gas,
+            Address,
+        };
+        use ethers_signers::Signer;
+        use mock::{MOCK_CHAIN_ID, MOCK_WALLETS};
+
+        // The authority delegates to an account, then clears its code by
+        // delegating to the zero address.
+        let authority = &MOCK_WALLETS[2];
+        let authorizations = vec![
+            Authorization::new(*MOCK_CHAIN_ID, MOCK_ACCOUNTS[3], 0, authority),
+            Authorization::new(*MOCK_CHAIN_ID, Address::zero(), 1, authority),
+        ];
+        let mut block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(authority.address()).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let tx = &mut block.eth_block.transactions[0];
+        tx.transaction_type = Some(SET_CODE_TX_TYPE.into());
+        tx.other.insert(
+            "authorizationList".into(),
+            serde_json::to_value(&authorizations).unwrap(),
+        );
+        // A tx to an account without code only uses its intrinsic gas.
+        block.geth_traces[0].gas =
+            Gas(GasCost::TX.as_u64() + authorizations.len() as u64 * PER_EMPTY_ACCOUNT_COST);
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let account = builder.sdb.get_account(&authority.address()).1;
+        assert_eq!(account.nonce, Word::from(2));
+        assert_eq!(account.code_hash, CodeDB::empty_code_hash());
+
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        CircuitTestBuilder::<3, 1>::new_from_block(block).run();
+    }
 }
@@ -256,7 +256,11 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::test::{rand_bytes, rand_range},
+        table::RwTableTag,
+        test_util::{CircuitTestBuilder, WitnessMutation},
+    };
     use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::{bytecode, Word};
     use mock::{
@@ -265,12 +269,12 @@ mod test {
         MockCallBytecodeParams,
     };
 
-    fn test_root_ok(
+    fn root_ctx(
         call_data_length: usize,
         length: usize,
         data_offset: Word,
         memory_offset: Word,
-    ) {
+    ) -> TestContext<2, 1> {
         let bytecode = bytecode! {
             PUSH32(length)
             PUSH32(data_offset)
@@ -282,7 +286,7 @@ mod test {
         let call_data = rand_bytes(call_data_length);
 
         // Get the execution steps from the external tracer
-        let ctx = TestContext::<2, 1>::new(
+        TestContext::<2, 1>::new(
             None,
             account_0_code_account_1_no_code(bytecode),
             |mut txs, accs| {
@@ -293,14 +297,26 @@ mod test {
             },
             |block, _tx| block.number(0xcafeu64),
         )
-        .unwrap();
+        .unwrap()
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx)
-            .params(CircuitsParams {
-                max_calldata: 600,
-                ..CircuitsParams::default()
-            })
-            .run();
+    fn test_root_ok(
+        call_data_length: usize,
+        length: usize,
+        data_offset: Word,
+        memory_offset: Word,
+    ) {
+        CircuitTestBuilder::new_from_test_ctx(root_ctx(
+            call_data_length,
+            length,
+            data_offset,
+            memory_offset,
+        ))
+        .params(CircuitsParams {
+            max_calldata: 600,
+            ..CircuitsParams::default()
+        })
+        .run();
     }
 
     fn test_internal_ok(
@@ -375,9 +391,70 @@ mod test {
         test_internal_ok(0x40, 0x40, 10, Word::MAX, 0xA0.into());
     }
 
+    #[test]
+    fn calldatacopy_gadget_data_offset_boundary() {
+        // A random offset in [2^64, 2^128).
+        let overflow_offset =
+            (Word::from(rand_range(1..=u64::MAX)) << 64) + rand_range(0..=u64::MAX);
+        for data_offset in [Word::from(u64::MAX), Word::one() << 64, overflow_offset] {
+            test_root_ok(0x40, 10, data_offset, 0x40.into());
+            test_internal_ok(0x40, 0x40, 10, data_offset, 0xA0.into());
+        }
+
+        // Data offsets around the end of call data.
+        for data_offset in [0x3f, 0x40, 0x41, rand_range(0x41..=u64::MAX)] {
+            test_root_ok(0x40, 10, data_offset.into(), 0x40.into());
+            test_internal_ok(0x40, 0x40, 10, data_offset.into(), 0xA0.into());
+        }
+    }
+
     #[test]
     fn calldatacopy_gadget_overflow_memory_offset_and_zero_length() {
         test_root_ok(0x40, 0, 0x40.into(), Word::MAX);
         test_internal_ok(0x40, 0x40, 0, 0x10.into(), Word::MAX);
     }
+
+    #[test]
+    fn calldatacopy_gadget_mutations_rejected() {
+        CircuitTestBuilder::new_from_test_ctx(root_ctx(0x40, 0x20, 0x00.into(), 0x40.into()))
+            .params(CircuitsParams {
+                max_calldata: 600,
+                ..CircuitsParams::default()
+            })
+            .run_with_mutations(&[
+                // The length pushed by the first PUSH32
+                (
+                    WitnessMutation::RwValue {
+                        tag: RwTableTag::Stack,
+                        index: 0,
+                    },
+                    "Rw",
+                ),
+                // The first byte written to memory by CALLDATACOPY
+                (
+                    WitnessMutation::RwValue {
+                        tag: RwTableTag::Memory,
+                        index: 0,
+                    },
+                    "Memory lookup",
+                ),
+                // The copy table row looked up by CALLDATACOPY counts the
+                // skipped byte
+                (
+                    WitnessMutation::SkipCopyStep {
+                        event_index: 0,
+                        byte_index: 0,
+                    },
+                    "Copy",
+                ),
+                // BeginTx, PUSH32 * 3, CALLDATACOPY
+                (
+                    WitnessMutation::GasLeft {
+                        tx_index: 0,
+                        step_index: 4,
+                    },
+                    "constraint of gas_left",
+                ),
+            ]);
+    }
 }
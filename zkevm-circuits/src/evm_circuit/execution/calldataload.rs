@@ -275,7 +275,10 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::test::{rand_bytes, rand_range},
+        test_util::CircuitTestBuilder,
+    };
     use eth_types::{bytecode, Word};
     use mock::{generate_mock_call_bytecode, MockCallBytecodeParams, TestContext};
 
@@ -349,4 +352,26 @@ mod test {
         test_root_ok(Word::MAX);
         test_internal_ok(0x1010, 0xff, Word::MAX);
     }
+
+    #[test]
+    fn calldataload_gadget_offset_boundary() {
+        // A random offset in [2^64, 2^128).
+        let overflow_offset =
+            (Word::from(rand_range(1..=u64::MAX)) << 64) + rand_range(0..=u64::MAX);
+        for offset in [Word::from(u64::MAX), Word::one() << 64, overflow_offset] {
+            test_root_ok(offset);
+            test_internal_ok(0x20, 0x10, offset);
+        }
+
+        // Offsets around the end of call data.
+        let call_data_length = rand_range(0x20..0x40);
+        for offset in [call_data_length - 1, call_data_length, call_data_length + 1] {
+            test_internal_ok(call_data_length, 0x10, offset.into());
+        }
+        test_internal_ok(
+            call_data_length,
+            0x10,
+            rand_range(call_data_length..=u64::MAX as usize).into(),
+        );
+    }
 }
@@ -1,20 +1,20 @@
+#[cfg(feature = "rip7212")]
+use crate::evm_circuit::util::{math_gadget::IsEqualGadget, or};
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_U64},
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_U64},
         step::ExecutionState,
         util::{
             and,
-            common_gadget::{CommonCallGadget, TransferGadget},
+            common_gadget::{CallGasGadget, CommonCallGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{
-                ConstantDivisionGadget, IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget,
-            },
+            math_gadget::{IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget},
             memory_gadget::{CommonMemoryAddressGadget, MemoryAddressGadget},
-            not, or,
+            not,
             precompile_gadget::PrecompileGadget,
             rlc, select, CachedRegion, Cell, Word,
         },
@@ -23,10 +23,12 @@ use crate::{
     table::{AccountFieldTag, CallContextFieldTag},
     util::Expr,
 };
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, precompile::is_precompiled};
-use eth_types::{
-    evm_types::GAS_STIPEND_CALL_WITH_VALUE, Field, ToAddress, ToLittleEndian, ToScalar, U256,
+use bus_mapping::{
+    circuit_input_builder::CopyDataType,
+    evm::OpcodeId,
+    precompile::{is_precompiled, PrecompileCalls},
 };
+use eth_types::{Field, ToAddress, ToLittleEndian, ToScalar, U256};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget for call related opcodes. It supports `OpcodeId::CALL`,
@@ -57,13 +59,14 @@ pub(crate) struct CallOpGadget<F> {
     // check if insufficient balance case
     is_insufficient_balance: LtWordGadget<F>,
     is_depth_ok: LtGadget<F, N_BYTES_U64>,
-    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
-    capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    call_gas: CallGasGadget<F>,
     // FIXME: free cells, only used in empty codehash (empty account and precompiles)
     step_gas_cost: Cell<F>,
     // to handle precompile calls
     is_code_address_zero: IsZeroGadget<F>,
     is_precompile_lt: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    #[cfg(feature = "rip7212")]
+    is_p256_verify: IsEqualGadget<F>,
     precompile_gadget: PrecompileGadget<F>,
     precompile_return_length: Cell<F>,
     precompile_return_length_zero: IsZeroGadget<F>,
@@ -192,14 +195,34 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
 
         // whether the call is to a precompiled contract.
-        // precompile contracts are stored from address 0x01 to 0x09.
+        // precompile contracts are stored from address 0x01 to 0x09, and at
+        // 0x100 when RIP-7212 is enabled.
         let is_code_address_zero = IsZeroGadget::construct(cb, call_gadget.callee_address_expr());
         let is_precompile_lt =
             LtGadget::construct(cb, call_gadget.callee_address_expr(), 0x0A.expr());
+        #[cfg(feature = "rip7212")]
+        let is_p256_verify = IsEqualGadget::construct(
+            cb,
+            call_gadget.callee_address_expr(),
+            u64::from(PrecompileCalls::P256Verify).expr(),
+        );
+        #[cfg(feature = "rip7212")]
+        let is_precompile_address = or::expr([is_precompile_lt.expr(), is_p256_verify.expr()]);
+        #[cfg(not(feature = "rip7212"))]
+        let is_precompile_address = is_precompile_lt.expr();
         let is_precompile = and::expr([
             not::expr(is_code_address_zero.expr()),
-            is_precompile_lt.expr(),
+            is_precompile_address,
         ]);
+        // The copy data type of a precompile is 5 + its address, except for
+        // P256VERIFY whose address isn't contiguous with the others.
+        let precompile_copy_type = 5.expr() + call_gadget.callee_address_expr(); // refer u64::from(CopyDataType)
+        #[cfg(feature = "rip7212")]
+        let precompile_copy_type = select::expr(
+            is_p256_verify.expr(),
+            CopyDataType::Precompile(PrecompileCalls::P256Verify).expr(),
+            precompile_copy_type,
+        );
         let precompile_return_length = cb.query_cell();
         let precompile_return_length_zero =
             IsZeroGadget::construct(cb, precompile_return_length.expr());
@@ -263,16 +286,14 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         // Only CALL opcode could invoke transfer to make empty account into non-empty.
         let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr(), is_call.expr());
         // Apply EIP 150
-        let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
-        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
-        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
-        let capped_callee_gas_left =
-            MinMaxGadget::construct(cb, call_gadget.gas_expr(), all_but_one_64th_gas.clone());
-        let callee_gas_left = select::expr(
+        let call_gas = CallGasGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr() - gas_cost.clone(),
+            call_gadget.gas_expr(),
             call_gadget.gas_is_u64.expr(),
-            capped_callee_gas_left.min(),
-            all_but_one_64th_gas,
+            call_gadget.has_value.clone(),
         );
+        let callee_gas_left = call_gas.callee_gas_left();
 
         let stack_pointer_delta =
             select::expr(is_call.expr() + is_callcode.expr(), 6.expr(), 5.expr());
@@ -373,7 +394,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             cb.curr.state.call_id.expr(),
                             CopyDataType::Memory.expr(),
                             callee_call_id.expr(),
-                            5.expr() + call_gadget.callee_address_expr(), // refer u64::from(CopyDataType)
+                            precompile_copy_type.expr(),
                             call_gadget.cd_address.offset(),
                             call_gadget.cd_address.address(),
                             0.expr(),
@@ -398,7 +419,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             let output_bytes_rlc = cb.query_cell_phase2();
                             cb.copy_table_lookup(
                                 callee_call_id.expr(),
-                                5.expr() + call_gadget.callee_address_expr(), // refer u64::from(CopyDataType)
+                                precompile_copy_type.expr(),
                                 callee_call_id.expr(),
                                 CopyDataType::Memory.expr(),
                                 0.expr(),
@@ -428,7 +449,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             let return_bytes_rlc = cb.query_cell_phase2();
                             cb.copy_table_lookup(
                                 callee_call_id.expr(),
-                                5.expr() + call_gadget.callee_address_expr(), // refer u64::from(CopyDataType)
+                                precompile_copy_type.expr(),
                                 cb.curr.state.call_id.expr(),
                                 CopyDataType::Memory.expr(),
                                 0.expr(),
@@ -475,7 +496,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             call_gadget.cd_address.offset(),
                             call_gadget.cd_address.length(),
                             call_gadget.rd_address.offset(),
-                            call_gadget.rd_address.length(),
+                            call_gadget.rd_address.has_length(),
                             precompile_return_length.expr(),
                             input_bytes_rlc.expr(),
                             output_bytes_rlc.expr(),
@@ -560,10 +581,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 rw_counter: Delta(22.expr()),
                 program_counter: Delta(1.expr()),
                 stack_pointer: Delta(stack_pointer_delta.expr()),
-                gas_left: Delta(
-                    call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr()
-                        - gas_cost.clone(),
-                ),
+                gas_left: Delta(call_gas.stipend() - gas_cost.clone()),
                 memory_word_size: To(memory_expansion.next_memory_word_size()),
                 reversible_write_counter: Delta(1.expr()),
                 ..StepStateTransition::default()
@@ -652,8 +670,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 }
 
                 // Give gas stipend if value is not zero
-                let callee_gas_left = callee_gas_left
-                    + call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr();
+                let callee_gas_left = call_gas.callee_gas_left_with_stipend();
 
                 // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
                 // not zero, two account write for `transfer` call (+2).
@@ -708,12 +725,13 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             caller_balance_word,
             is_insufficient_balance,
             is_depth_ok,
-            one_64th_gas,
-            capped_callee_gas_left,
+            call_gas,
             step_gas_cost,
             // precompile related fields.
             is_code_address_zero,
             is_precompile_lt,
+            #[cfg(feature = "rip7212")]
+            is_p256_verify,
             precompile_gadget,
             precompile_return_length,
             precompile_return_length_zero,
@@ -924,14 +942,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let gas_available = step.gas_left - gas_cost;
         self.step_gas_cost
             .assign(region, offset, Value::known(F::from(step.gas_cost)))?;
-        self.one_64th_gas
-            .assign(region, offset, gas_available.into())?;
-        self.capped_callee_gas_left.assign(
-            region,
-            offset,
-            F::from(gas.low_u64()),
-            F::from(gas_available - gas_available / 64),
-        )?;
+        self.call_gas.assign(region, offset, gas_available, gas)?;
 
         // precompile related assignment.
         let code_address: F = callee_address.to_address().to_scalar().unwrap();
@@ -939,13 +950,13 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             .assign(region, offset, code_address)?;
         self.is_precompile_lt
             .assign(region, offset, code_address, 0x0Au64.into())?;
-        if is_precompiled(&callee_address.to_address()) {
-            self.precompile_gadget.assign(
-                region,
-                offset,
-                callee_address.to_address().0[19].into(),
-            )?;
-        }
+        #[cfg(feature = "rip7212")]
+        self.is_p256_verify.assign(
+            region,
+            offset,
+            code_address,
+            F::from(u64::from(PrecompileCalls::P256Verify)),
+        )?;
         let precompile_return_length = if is_precompiled(&callee_address.to_address()) {
             let value_rw = block.rws[step.rw_indices[32 + rw_offset]];
             assert_eq!(
@@ -1015,6 +1026,15 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     .challenges()
                     .keccak_input()
                     .map(|randomness| rlc::value(return_bytes.iter().rev(), randomness));
+                let precompile = PrecompileCalls::try_from(callee_address.to_address())
+                    .map_err(|_| Error::Synthesis)?;
+                self.precompile_gadget.assign(
+                    region,
+                    offset,
+                    precompile,
+                    &input_bytes,
+                    precompile_return_length.as_u64(),
+                )?;
                 (input_bytes_rlc, output_bytes_rlc, return_bytes_rlc)
             } else {
                 (
@@ -1038,14 +1058,18 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::test_util::CircuitTestBuilder;
+    use crate::test_util::{CircuitTestBuilder, WitnessMutation};
     use bus_mapping::{circuit_input_builder::CircuitsParams, evm::PrecompileCallArgs};
     use eth_types::{
-        address, bytecode, evm_types::OpcodeId, geth_types::Account, word, Address, ToWord, Word,
+        address, bytecode,
+        evm_types::{gas_utils::all_but_one_64th_gas, GasCost, OpcodeId},
+        geth_types::Account,
+        word, Address, ToWord, Word,
     };
 
     use itertools::Itertools;
     use mock::{
+        call_tree::{call_tree, CallNode},
         test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
         TestContext,
     };
@@ -1311,6 +1335,105 @@ mod test {
         );
     }
 
+    /// Runs a CALL whose gas argument is `gas_delta` away from all but one
+    /// 64th of the gas available after its cost, which is `64 * 1000 +
+    /// remainder`.
+    fn test_call_gas(remainder: u64, gas_delta: i64, value: u64) {
+        let gas_available = 64 * 1000 + remainder;
+        let gas = Word::from((all_but_one_64th_gas(gas_available) as i64 + gas_delta) as u64);
+        test_call_gas_argument(gas_available, gas, value);
+    }
+
+    fn test_call_gas_argument(gas_available: u64, gas: Word, value: u64) {
+        let code = bytecode! {
+            .op_call(gas, Address::repeat_byte(0xff), value, 0, 0, 0, 0)
+            STOP
+        };
+        // 7 pushes, and the call to a cold account, with value to an
+        // existing account.
+        let gas_cost = 7 * GasCost::FASTEST.as_u64()
+            + GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            + if value == 0 {
+                0
+            } else {
+                GasCost::CALL_WITH_VALUE.as_u64()
+            };
+        let callee = callee(bytecode! { STOP });
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(10u64.pow(19)));
+                accs[1]
+                    .address(Address::repeat_byte(0xfe))
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(code);
+                accs[2]
+                    .address(callee.address)
+                    .code(callee.code)
+                    .nonce(callee.nonce)
+                    .balance(callee.balance);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(Word::from(GasCost::TX.as_u64() + gas_cost + gas_available));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn callop_gas_one_64th_boundaries() {
+        for (remainder, value) in [0, 1, 63].into_iter().cartesian_product([0, 1]) {
+            for gas_delta in [-1, 0, 1] {
+                test_call_gas(remainder, gas_delta, value);
+            }
+        }
+        // a gas argument above 64 bits gets all but one 64th too
+        test_call_gas_argument(64 * 1000 + 63, Word::from(u64::MAX) + 1, 1);
+    }
+
+    #[test]
+    fn callop_access_list_reverted() {
+        let address = Address::repeat_byte(0xaa);
+        // warms the address and the slot 5 of the storage it runs with, and
+        // reverts
+        let callee_code = bytecode! {
+            .op_balance(address)
+            POP
+            .op_sload(5)
+            POP
+            .op_revert(0, 0)
+        };
+        // both are cold again after the callee reverted, called or delegated
+        let caller_code = bytecode! {
+            .op_call(0x10000, Address::repeat_byte(0xff), 0, 0, 0, 0, 0)
+            POP
+            .op_delegatecall(0x10000, Address::repeat_byte(0xff), 0, 0, 0, 0)
+            POP
+            .op_balance(address)
+            POP
+            .op_sload(5)
+            POP
+            STOP
+        };
+        test_ok(
+            Account {
+                address: Address::repeat_byte(0xfe),
+                balance: Word::from(10).pow(20.into()),
+                code: caller_code.into(),
+                ..Default::default()
+            },
+            callee(callee_code),
+        );
+    }
+
     fn test_ok(caller: Account, callee: Account) {
         let ctx = TestContext::<3, 1>::new(
             None,
@@ -1349,6 +1472,107 @@ mod test {
             .run();
     }
 
+    fn test_call_tree<const NACC: usize>(tree: CallNode) {
+        CircuitTestBuilder::new_from_test_ctx(call_tree::<NACC>(&tree).unwrap())
+            .params(CircuitsParams {
+                max_rws: 2000,
+                ..Default::default()
+            })
+            .run();
+    }
+
+    #[test]
+    fn callop_reverted_call_tree() {
+        // Reverted calls under successful ones under reverted ones, with
+        // successful siblings before and after them.
+        test_call_tree::<11>(CallNode::revert(vec![
+            CallNode::stop(vec![CallNode::revert(vec![]), CallNode::stop(vec![])]),
+            CallNode::revert(vec![CallNode::stop(vec![CallNode::stop(vec![])])]),
+            CallNode::stop(vec![CallNode::revert(vec![CallNode::stop(vec![])])]),
+        ]));
+    }
+
+    #[test]
+    fn callop_alternating_call_chain() {
+        test_call_tree::<7>(CallNode::stop(vec![CallNode::revert(vec![
+            CallNode::stop(vec![CallNode::revert(vec![CallNode::stop(vec![
+                CallNode::revert(vec![]),
+            ])])]),
+        ])]));
+    }
+
+    #[test]
+    fn callop_reverted_call_tree_with_value() {
+        test_call_tree::<7>(CallNode::revert(vec![
+            CallNode::stop(vec![]).value(1),
+            CallNode::revert(vec![CallNode::stop(vec![]).value(2)]).value(3),
+            CallNode::stop(vec![CallNode::revert(vec![]).value(4)]).value(5),
+        ]));
+    }
+
+    #[test]
+    fn callop_callcode_delegatecall_chains() {
+        // The leaf stores the CALLER, CALLVALUE and ADDRESS it sees, in the
+        // storage of the root, which both calls of the chain keep.
+        let [root, middle, leaf] = [0xa1u64, 0xa2, 0xa3].map(Address::from_low_u64_be);
+        let leaf_code = bytecode! {
+            CALLER
+            PUSH1(0)
+            SSTORE
+            CALLVALUE
+            PUSH1(1)
+            SSTORE
+            ADDRESS
+            PUSH1(2)
+            SSTORE
+            STOP
+        };
+        let call = |op: OpcodeId, address: Address, value: u64| {
+            let mut code = bytecode::Bytecode::default();
+            match op {
+                OpcodeId::CALLCODE => code.op_callcode(0x80000, address, value, 0, 0, 0, 0),
+                _ => code.op_delegatecall(0x80000, address, 0, 0, 0, 0),
+            };
+            code.op_pop().op_stop();
+            code
+        };
+        for (first, second) in [OpcodeId::CALLCODE, OpcodeId::DELEGATECALL]
+            .into_iter()
+            .cartesian_product([OpcodeId::CALLCODE, OpcodeId::DELEGATECALL])
+        {
+            let ctx = TestContext::<4, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(address!("0x000000000000000000000000000000000000cafe"))
+                        .balance(Word::from(10u64.pow(19)));
+                    accs[1]
+                        .address(root)
+                        .balance(Word::from(10u64.pow(18)))
+                        .code(call(first, middle, 2));
+                    accs[2].address(middle).code(call(second, leaf, 3));
+                    accs[3].address(leaf).code(leaf_code.clone());
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .to(accs[1].address)
+                        .gas(500000.into())
+                        .value(7.into());
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap();
+
+            CircuitTestBuilder::new_from_test_ctx(ctx)
+                .params(CircuitsParams {
+                    max_rws: 2000,
+                    ..Default::default()
+                })
+                .run();
+        }
+    }
+
     fn test_recursive(opcode: &OpcodeId) {
         let is_call_or_callcode = opcode == &OpcodeId::CALL || opcode == &OpcodeId::CALLCODE;
         let mut caller_bytecode = bytecode! {
@@ -1763,4 +1987,103 @@ mod test {
                 .run();
         }
     }
+
+    #[test]
+    fn callop_ecrecover_forged_address() {
+        let ecrecover = PrecompileCallArgs {
+            name: "ecRecover",
+            setup_code: bytecode! {
+                PUSH32(word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3")) // hash
+                PUSH1(0x0)
+                MSTORE
+                PUSH1(28) // v
+                PUSH1(0x20)
+                MSTORE
+                PUSH32(word!("0x9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608")) // r
+                PUSH1(0x40)
+                MSTORE
+                PUSH32(word!("0x4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada")) // s
+                PUSH1(0x60)
+                MSTORE
+            },
+            ret_size: Word::from(0x20),
+            ret_offset: Word::from(0x80),
+            call_data_length: Word::from(0x80),
+            address: Word::from(0x1),
+            ..Default::default()
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(ecrecover.with_call_op(OpcodeId::STATICCALL)),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        // The address returned by the call must be the one of the public key
+        // verifying the signature.
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(CircuitsParams {
+                max_rws: ecrecover.max_rws,
+                ..Default::default()
+            })
+            .run_with_mutations(&[(WitnessMutation::EcrecoverPubKey { event_index: 0 }, "Sig")]);
+    }
+
+    #[cfg(feature = "rip7212")]
+    #[ignore = "the geth tracing the tests has no RIP-7212 precompile at 0x100"]
+    #[test]
+    fn callop_p256_verify() {
+        let p256_verify = |s: Word| PrecompileCallArgs {
+            name: "P256VERIFY",
+            setup_code: bytecode! {
+                PUSH32(word!("0x9749ebd9e8d86c4c6e4fd13a95921bd0d6f93022fa55e21638beff533fcf1731")) // hash
+                PUSH1(0x0)
+                MSTORE
+                PUSH32(word!("0x4f347d910f71da7872ab141e818c06064461212c68014cf3cf643887497eff8b")) // r
+                PUSH1(0x20)
+                MSTORE
+                PUSH32(s) // s
+                PUSH1(0x40)
+                MSTORE
+                PUSH32(word!("0xb7e08afdfe94bad3f1dc8c734798ba1c62b3a0ad1e9ea2a38201cd0889bc7a19")) // x
+                PUSH1(0x60)
+                MSTORE
+                PUSH32(word!("0x3603f747959dbf7a4bb226e41928729063adc7ae43529e61b563bbc606cc5e09")) // y
+                PUSH1(0x80)
+                MSTORE
+            },
+            ret_size: Word::from(0x20),
+            ret_offset: Word::from(0xA0),
+            call_data_length: Word::from(0xA0),
+            address: PrecompileCalls::P256Verify.address().to_word(),
+            ..Default::default()
+        };
+        let valid_s = word!("0xc3e3014ce615311f86036a3b2d46499f0ef4e75e18bea5b185ab6240804fd7d6");
+
+        for (s, is_valid) in [(valid_s, true), (valid_s + 1, false)] {
+            let test_call = p256_verify(s);
+            let ctx = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(test_call.with_call_op(OpcodeId::STATICCALL)),
+                tx_from_1_to_0,
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap();
+            let builder = CircuitTestBuilder::new_from_test_ctx(ctx).params(CircuitsParams {
+                max_rws: test_call.max_rws,
+                ..Default::default()
+            });
+            if is_valid {
+                // The call must not be proved valid without its signature
+                // verified.
+                builder.run_with_mutations(&[(
+                    WitnessMutation::DropPrecompileEvent { event_index: 0 },
+                    "P256Verify",
+                )]);
+            } else {
+                builder.run();
+            }
+        }
+    }
 }
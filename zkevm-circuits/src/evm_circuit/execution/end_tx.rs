@@ -18,14 +18,25 @@ use crate::{
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::{
-        BlockContextFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag, TxReceiptFieldTag,
+        BlockContextFieldTag, CallContextFieldTag, ReceiptFieldTag, RwTableTag, TxContextFieldTag,
+        TxReceiptFieldTag,
     },
     util::Expr,
 };
-use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field, ToLittleEndian, ToScalar};
+use eth_types::{
+    evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field, ToLittleEndian, ToScalar, U256,
+};
+use gadgets::util::not;
 use halo2_proofs::{circuit::Value, plonk::Error};
 use strum::EnumCount;
 
+// With the `scroll` feature, the L1 fee written to the call context by
+// BeginTx is read back to credit it to the coinbase.
+#[cfg(feature = "scroll")]
+const L1_FEE_RW_DELTA: u8 = 1;
+#[cfg(not(feature = "scroll"))]
+const L1_FEE_RW_DELTA: u8 = 0;
+
 #[derive(Clone, Debug)]
 pub(crate) struct EndTxGadget<F> {
     tx_id: Cell<F>,
@@ -33,9 +44,15 @@ pub(crate) struct EndTxGadget<F> {
     max_refund: ConstantDivisionGadget<F, N_BYTES_GAS>,
     refund: Cell<F>,
     effective_refund: MinMaxGadget<F, N_BYTES_GAS>,
+    tx_data_tokens: Cell<F>,
+    gas_paid_with_floor: MinMaxGadget<F, N_BYTES_GAS>,
+    gas_used_with_floor: MinMaxGadget<F, N_BYTES_GAS>,
     effective_fee: Word<F>,
     mul_gas_price_by_refund: MulWordByU64Gadget<F>,
     tx_caller_address: Cell<F>,
+    tx_is_system: Cell<F>,
+    tx_is_invalid: Cell<F>,
+    tx_is_prague: Cell<F>,
     gas_fee_refund: UpdateBalanceGadget<F, 2, true>,
     sub_gas_price_by_base_fee: AddWordsGadget<F, 2, true>,
     mul_effective_tip_by_gas_used: MulWordByU64Gadget<F>,
@@ -44,6 +61,11 @@ pub(crate) struct EndTxGadget<F> {
     current_cumulative_gas_used: Cell<F>,
     is_first_tx: IsEqualGadget<F>,
     is_persistent: Cell<F>,
+    revert_data_hash: Cell<F>,
+    #[cfg(feature = "scroll")]
+    tx_l1_fee: Cell<F>,
+    #[cfg(feature = "scroll")]
+    effective_fee_carry_lo: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
@@ -54,10 +76,17 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let is_persistent = cb.call_context(None, CallContextFieldTag::IsPersistent);
+        #[cfg(feature = "scroll")]
+        let tx_l1_fee = cb.call_context(None, CallContextFieldTag::L1Fee);
 
-        let [tx_gas, tx_caller_address] =
-            [TxContextFieldTag::Gas, TxContextFieldTag::CallerAddress]
-                .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+        let [tx_gas, tx_caller_address, tx_is_system, tx_is_invalid, tx_is_prague] = [
+            TxContextFieldTag::Gas,
+            TxContextFieldTag::CallerAddress,
+            TxContextFieldTag::IsSystem,
+            TxContextFieldTag::IsInvalid,
+            TxContextFieldTag::IsPrague,
+        ]
+        .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
         let tx_gas_price = cb.tx_context_as_word(tx_id.expr(), TxContextFieldTag::GasPrice, None);
 
         // Calculate effective gas to refund
@@ -70,12 +99,44 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         let refund = cb.query_cell();
         cb.tx_refund_read(tx_id.expr(), refund.expr());
         let effective_refund = MinMaxGadget::construct(cb, max_refund.quotient(), refund.expr());
+        let gas_paid = gas_used.clone() - effective_refund.min();
+
+        // A valid tx of a Prague block uses at least the floor cost of its
+        // call data of EIP-7623, after the refund. The call data gas cost of
+        // the tx table is 4 per token of the call data.
+        let (tx_data_tokens, gas_paid_with_floor, gas_used_with_floor, gas_paid, gas_used) = {
+            use eth_types::evm_types::{gas_utils::TOTAL_COST_FLOOR_PER_TOKEN, GasCost};
 
-        // Add effective_refund * tx_gas_price back to caller's balance
+            let tx_call_data_gas_cost =
+                cb.tx_context(tx_id.expr(), TxContextFieldTag::CallDataGasCost, None);
+            let tx_data_tokens = cb.query_cell();
+            cb.require_equal(
+                "call data gas cost == 4 * call data tokens",
+                tx_call_data_gas_cost.expr(),
+                4.expr() * tx_data_tokens.expr(),
+            );
+            let floor = (GasCost::TX.expr()
+                + TOTAL_COST_FLOOR_PER_TOKEN.expr() * tx_data_tokens.expr())
+                * tx_is_prague.expr()
+                * not::expr(tx_is_invalid.expr());
+            let gas_paid_with_floor = MinMaxGadget::construct(cb, gas_paid, floor.clone());
+            let gas_used_with_floor = MinMaxGadget::construct(cb, gas_used, floor);
+            let (gas_paid, gas_used) = (gas_paid_with_floor.max(), gas_used_with_floor.max());
+            (
+                tx_data_tokens,
+                gas_paid_with_floor,
+                gas_used_with_floor,
+                gas_paid,
+                gas_used,
+            )
+        };
+
+        // Add (tx_gas - gas_paid) * tx_gas_price back to caller's balance,
+        // unless the tx is invalid and paid nothing in BeginTx
         let mul_gas_price_by_refund = MulWordByU64Gadget::construct(
             cb,
             tx_gas_price.clone(),
-            effective_refund.min() + cb.curr.state.gas_left.expr(),
+            (tx_gas.expr() - gas_paid.clone()) * not::expr(tx_is_invalid.expr()),
         );
         let gas_fee_refund = UpdateBalanceGadget::construct(
             cb,
@@ -84,7 +145,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             None,
         );
 
-        // Add gas_used * effective_tip to coinbase's balance
+        // Add gas_paid * effective_tip to coinbase's balance
         let coinbase = cb.query_cell();
         let base_fee = cb.query_word_rlc();
         for (tag, value) in [
@@ -94,16 +155,39 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             cb.block_lookup(tag.expr(), cb.curr.state.block_number.expr(), value);
         }
         let effective_tip = cb.query_word_rlc();
-        let sub_gas_price_by_base_fee =
-            AddWordsGadget::construct(cb, [effective_tip.clone(), base_fee], tx_gas_price);
-        let mul_effective_tip_by_gas_used = MulWordByU64Gadget::construct(
-            cb,
-            effective_tip,
-            gas_used.clone() - effective_refund.min(),
-        );
+        // System txs have a zero gas price (checked in the tx circuit) and are
+        // exempt from the base fee, so they pay no tip.
+        let sub_gas_price_by_base_fee = cb.condition(not::expr(tx_is_system.expr()), |cb| {
+            AddWordsGadget::construct(cb, [effective_tip.clone(), base_fee], tx_gas_price)
+        });
+        cb.condition(tx_is_system.expr(), |cb| {
+            cb.require_zero("effective_tip == 0 for system tx", effective_tip.expr());
+        });
+        let mul_effective_tip_by_gas_used =
+            MulWordByU64Gadget::construct(cb, effective_tip, gas_paid);
 
         let effective_fee = cb.query_word_rlc();
-        // TODO: contraint l1 fee
+        #[cfg(feature = "scroll")]
+        let effective_fee_carry_lo = {
+            use crate::evm_circuit::util::{from_bytes, pow_of_two_expr};
+            // The 64-bit L1 fee carries at most 1 into the high 128 bits. An
+            // invalid tx paid no L1 fee.
+            let carry_lo = cb.query_bool();
+            cb.require_equal(
+                "tx_fee_lo + carry_lo ⋅ 2^128 == l1_fee + l2_fee_lo",
+                from_bytes::expr(&effective_fee.cells[..16])
+                    + carry_lo.expr() * pow_of_two_expr(128),
+                tx_l1_fee.expr() * not::expr(tx_is_invalid.expr())
+                    + from_bytes::expr(&mul_effective_tip_by_gas_used.product().cells[..16]),
+            );
+            cb.require_equal(
+                "tx_fee_hi == l2_fee_hi + carry_lo",
+                from_bytes::expr(&effective_fee.cells[16..]),
+                from_bytes::expr(&mul_effective_tip_by_gas_used.product().cells[16..])
+                    + carry_lo.expr(),
+            );
+            carry_lo
+        };
         #[cfg(not(feature = "scroll"))]
         cb.require_equal(
             "tx_fee == l1_fee + l2_fee, l1_fee == 0",
@@ -127,6 +211,12 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             TxReceiptFieldTag::LogLength,
             cb.curr.state.log_id.expr(),
         );
+        cb.receipt_table_lookup(tx_id.expr(), ReceiptFieldTag::Status, is_persistent.expr());
+        cb.receipt_table_lookup(
+            tx_id.expr(),
+            ReceiptFieldTag::LogLength,
+            cb.curr.state.log_id.expr(),
+        );
 
         let is_first_tx = IsEqualGadget::construct(cb, tx_id.expr(), 1.expr());
 
@@ -147,11 +237,32 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             );
         });
 
+        let cumulative_gas_used = gas_used + current_cumulative_gas_used.expr();
         cb.tx_receipt_lookup(
             1.expr(),
             tx_id.expr(),
             TxReceiptFieldTag::CumulativeGasUsed,
-            gas_used + current_cumulative_gas_used.expr(),
+            cumulative_gas_used.clone(),
+        );
+        cb.receipt_table_lookup(
+            tx_id.expr(),
+            ReceiptFieldTag::CumulativeGasUsed,
+            cumulative_gas_used,
+        );
+
+        // Written by the REVERT of the root call, so it's zero when the tx
+        // didn't end with a REVERT.
+        let revert_data_hash = cb.query_cell_phase2();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::RevertDataHash,
+            revert_data_hash.expr(),
+        );
+        cb.receipt_table_lookup(
+            tx_id.expr(),
+            ReceiptFieldTag::RevertDataHash,
+            revert_data_hash.expr(),
         );
 
         cb.condition(
@@ -165,7 +276,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
                 );
 
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(10.expr() - is_first_tx.expr()),
+                    rw_counter: Delta(11.expr() - is_first_tx.expr() + L1_FEE_RW_DELTA.expr()),
                     ..StepStateTransition::any()
                 });
             },
@@ -175,7 +286,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             cb.next.execution_state_selector([ExecutionState::EndBlock]),
             |cb| {
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(9.expr() - is_first_tx.expr()),
+                    rw_counter: Delta(10.expr() - is_first_tx.expr() + L1_FEE_RW_DELTA.expr()),
                     // We propagate call_id so that EndBlock can get the last tx_id
                     // in order to count processed txs.
                     call_id: Same,
@@ -190,9 +301,15 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             max_refund,
             refund,
             effective_refund,
+            tx_data_tokens,
+            gas_paid_with_floor,
+            gas_used_with_floor,
             effective_fee,
             mul_gas_price_by_refund,
             tx_caller_address,
+            tx_is_system,
+            tx_is_invalid,
+            tx_is_prague,
             gas_fee_refund,
             sub_gas_price_by_base_fee,
             mul_effective_tip_by_gas_used,
@@ -201,6 +318,11 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             current_cumulative_gas_used,
             is_first_tx,
             is_persistent,
+            revert_data_hash,
+            #[cfg(feature = "scroll")]
+            tx_l1_fee,
+            #[cfg(feature = "scroll")]
+            effective_fee_carry_lo,
         }
     }
 
@@ -214,9 +336,13 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         let gas_used = tx.gas - step.gas_left;
-        let (refund, _) = block.rws[step.rw_indices[2]].tx_refund_value_pair();
-        let [(caller_balance, caller_balance_prev), (coinbase_balance, coinbase_balance_prev)] =
-            [step.rw_indices[3], step.rw_indices[4]].map(|idx| block.rws[idx].account_value_pair());
+        let rw_offset = L1_FEE_RW_DELTA as usize;
+        let (refund, _) = block.rws[step.rw_indices[2 + rw_offset]].tx_refund_value_pair();
+        let [(caller_balance, caller_balance_prev), (coinbase_balance, coinbase_balance_prev)] = [
+            step.rw_indices[3 + rw_offset],
+            step.rw_indices[4 + rw_offset],
+        ]
+        .map(|idx| block.rws[idx].account_value_pair());
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
@@ -232,12 +358,28 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             F::from(refund),
         )?;
         let effective_refund = refund.min(max_refund as u64);
-        let gas_fee_refund = tx.gas_price * (effective_refund + step.gas_left);
+        let gas_paid = gas_used - effective_refund;
+        let tx_data_tokens = tx.call_data_gas_cost / 4;
+        self.tx_data_tokens
+            .assign(region, offset, Value::known(F::from(tx_data_tokens)))?;
+        let floor = if tx.is_prague && !tx.is_invalid {
+            eth_types::evm_types::GasCost::TX.as_u64()
+                + eth_types::evm_types::gas_utils::TOTAL_COST_FLOOR_PER_TOKEN * tx_data_tokens
+        } else {
+            0
+        };
+        self.gas_paid_with_floor
+            .assign(region, offset, F::from(gas_paid), F::from(floor))?;
+        self.gas_used_with_floor
+            .assign(region, offset, F::from(gas_used), F::from(floor))?;
+        let gas_paid = gas_paid.max(floor);
+        let gas_to_refund = if tx.is_invalid { 0 } else { tx.gas - gas_paid };
+        let gas_fee_refund = tx.gas_price * gas_to_refund;
         self.mul_gas_price_by_refund.assign(
             region,
             offset,
             tx.gas_price,
-            effective_refund + step.gas_left,
+            gas_to_refund,
             gas_fee_refund,
         )?;
         self.tx_caller_address.assign(
@@ -257,19 +399,29 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             caller_balance,
         )?;
         let context = &block.context.ctxs[&tx.block_number];
-        let effective_tip = tx.gas_price - context.base_fee;
+        self.tx_is_system
+            .assign(region, offset, Value::known(F::from(tx.is_system as u64)))?;
+        self.tx_is_invalid
+            .assign(region, offset, Value::known(F::from(tx.is_invalid as u64)))?;
+        self.tx_is_prague
+            .assign(region, offset, Value::known(F::from(tx.is_prague as u64)))?;
+        let effective_tip = if tx.is_system {
+            eth_types::Word::zero()
+        } else {
+            tx.gas_price - context.base_fee
+        };
         self.sub_gas_price_by_base_fee.assign(
             region,
             offset,
             [effective_tip, context.base_fee],
             tx.gas_price,
         )?;
-        let coinbase_reward = effective_tip * (gas_used - effective_refund);
+        let coinbase_reward = effective_tip * gas_paid;
         self.mul_effective_tip_by_gas_used.assign(
             region,
             offset,
             effective_tip,
-            gas_used - effective_refund,
+            gas_paid,
             coinbase_reward,
         )?;
         self.coinbase.assign(
@@ -317,6 +469,39 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             offset,
             Value::known(F::from(call.is_persistent as u64)),
         )?;
+        self.revert_data_hash.assign(
+            region,
+            offset,
+            region.word_rlc(U256::from_big_endian(
+                tx.receipt.revert_data_hash.as_bytes(),
+            )),
+        )?;
+        #[cfg(feature = "scroll")]
+        self.tx_l1_fee.assign(
+            region,
+            offset,
+            Value::known(
+                block.rws[step.rw_indices[2]]
+                    .call_context_value()
+                    .to_scalar()
+                    .unwrap(),
+            ),
+        )?;
+        #[cfg(feature = "scroll")]
+        {
+            use crate::util::word::WordLoHi;
+            let tx_l1_fee = if tx.is_invalid {
+                eth_types::Word::zero()
+            } else {
+                block.rws[step.rw_indices[2]].call_context_value()
+            };
+            let carry_lo = (WordLoHi::from(effective_fee - tx_l1_fee).lo + tx_l1_fee) >> 128;
+            self.effective_fee_carry_lo.assign(
+                region,
+                offset,
+                Value::known(F::from(carry_lo.as_u64())),
+            )?;
+        }
 
         Ok(())
     }
@@ -324,9 +509,10 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::{self, bytecode};
+    use crate::{test_util::CircuitTestBuilder, witness::block_convert};
+    use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
+    use eth_types::{self, bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
 
     use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
@@ -380,4 +566,78 @@ mod test {
             .unwrap(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_receipts_with_logs() {
+        // A tx emitting a log followed by a reverted one, whose log is dropped
+        let code = bytecode! {
+            PUSH1(0x20) // topic
+            PUSH1(0x20) // length
+            PUSH1(0x00) // offset
+            LOG1
+            CALLVALUE
+            PUSH1(0x0c)
+            JUMPI
+            STOP
+            JUMPDEST
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        };
+        test_ok(
+            TestContext::<2, 2>::new(
+                None,
+                account_0_code_account_1_no_code(code),
+                |mut txs, accs| {
+                    txs[0].to(accs[0].address).from(accs[1].address);
+                    txs[1]
+                        .to(accs[0].address)
+                        .from(accs[1].address)
+                        .value(eth(1));
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap(),
+        );
+    }
+
+    // The 37000 gas used by the call data of 1000 non-zero bytes is below its
+    // floor cost of 21000 + 10 * 4000, which the tx pays if its block is on
+    // Prague.
+    fn test_call_data_floor(prague_time: Option<u64>, gas_used: u64) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(vec![0xff; 1000].into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.prague_time = prague_time;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert_eq!(builder.block.txs[0].receipt.cumulative_gas_used, gas_used);
+
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+    }
+
+    #[test]
+    fn end_tx_gadget_call_data_floor() {
+        test_call_data_floor(Some(0), 61000);
+    }
+
+    #[test]
+    fn end_tx_gadget_call_data_floor_before_prague() {
+        test_call_data_floor(Some(u64::MAX), 37000);
+        test_call_data_floor(None, 37000);
+    }
 }
@@ -15,12 +15,13 @@ use crate::{
     util::Expr,
 };
 
-use eth_types::{evm_types::GasCost, Field};
+use eth_types::{
+    evm_types::{GasCost, MAX_CODE_SIZE},
+    Field,
+};
 
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-const MAXCODESIZE: u64 = 0x6000u64;
-
 /// Gadget for code store oog and max code size exceed
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorCodeStoreGadget<F> {
@@ -58,9 +59,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorCodeStoreGadget<F> {
             GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length(),
         );
 
-        // constrain code size > MAXCODESIZE
+        // constrain code size > MAX_CODE_SIZE, a code of exactly MAX_CODE_SIZE
+        // bytes being valid
         let max_code_size_exceed =
-            LtGadget::construct(cb, MAXCODESIZE.expr(), memory_address.length());
+            LtGadget::construct(cb, MAX_CODE_SIZE.expr(), memory_address.length());
 
         // check must be one of CodeStoreOutOfGas or MaxCodeSizeExceeded
         cb.require_in_set(
@@ -113,7 +115,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorCodeStoreGadget<F> {
         self.max_code_size_exceed.assign(
             region,
             offset,
-            F::from(MAXCODESIZE),
+            F::from(MAX_CODE_SIZE),
             F::from(length.as_u64()),
         )?;
 
@@ -129,7 +131,7 @@ mod test {
     use eth_types::{
         address,
         bytecode,
-        evm_types::OpcodeId,
+        evm_types::{OpcodeId, MAX_CODE_SIZE},
         geth_types::Account,
         Address,
         Bytecode,
@@ -147,8 +149,6 @@ mod test {
         static ref CALLER_ADDRESS: Address = address!("0x00bbccddee000000000000000000000000002400");
     }
 
-    const MAXCODESIZE: u64 = 0x6000u64;
-
     fn run_test_circuits(ctx: TestContext<2, 1>) {
         CircuitTestBuilder::new_from_test_ctx(ctx)
             .params(CircuitsParams {
@@ -159,15 +159,24 @@ mod test {
     }
 
     fn initialization_bytecode(is_oog: bool) -> Bytecode {
+        if is_oog {
+            initialization_bytecode_returning(0, 5)
+        } else {
+            initialization_bytecode_returning(MAX_CODE_SIZE + 1, MAX_CODE_SIZE + 1)
+        }
+    }
+
+    /// Initialization code storing at `mstore_offset` and returning `length`
+    /// bytes of memory as the code to deploy.
+    fn initialization_bytecode_returning(mstore_offset: u64, length: u64) -> Bytecode {
         let memory_bytes = [0x60; 10];
         let memory_value = Word::from_big_endian(&memory_bytes);
-        let code_len = if is_oog { 0 } else { MAXCODESIZE + 1 };
 
         let mut code = bytecode! {
             PUSH10(memory_value)
-            PUSH32(code_len)
+            PUSH32(mstore_offset)
             MSTORE
-            PUSH2(if is_oog { 5 } else { code_len}) // length to copy
+            PUSH2(length) // length to copy
             PUSH2(32u64 - u64::try_from(memory_bytes.len()).unwrap()) // offset
             //PUSH2(0x00) // offset
 
@@ -272,6 +281,25 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_create_max_code_size_code_store_oog() {
+        // A code of exactly MAX_CODE_SIZE bytes doesn't exceed the limit, so
+        // the error is CodeStoreOutOfGas.
+        for is_create2 in [false, true] {
+            let initialization_code =
+                initialization_bytecode_returning(MAX_CODE_SIZE, MAX_CODE_SIZE);
+            let root_code = creator_bytecode(initialization_code, is_create2);
+            let caller = Account {
+                address: *CALLER_ADDRESS,
+                code: root_code.into(),
+                nonce: Word::one(),
+                balance: eth(10),
+                ..Default::default()
+            };
+            run_test_circuits(test_context(caller, false));
+        }
+    }
+
     #[test]
     fn tx_deploy_code_store_oog() {
         let code = initialization_bytecode(true);
@@ -317,4 +345,27 @@ mod test {
 
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
+
+    #[test]
+    fn tx_deploy_max_code_size_code_store_oog() {
+        let code = initialization_bytecode_returning(MAX_CODE_SIZE, MAX_CODE_SIZE);
+
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(20));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .gas(58000u64.into())
+                    .value(eth(2))
+                    .input(code.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
 }
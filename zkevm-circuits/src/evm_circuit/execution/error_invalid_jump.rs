@@ -242,6 +242,49 @@ mod test {
         .run();
     }
 
+    #[test]
+    fn invalid_jump_in_push_data() {
+        // jumps to a 0x5b in the data of a PUSH, which isn't a JUMPDEST
+        for opcode in [OpcodeId::JUMP, OpcodeId::JUMPI] {
+            let bytecode = bytecode! {
+                PUSH1(1) // condition if JUMPI
+                PUSH1(0x08)
+                .write_op(opcode)
+                PUSH1(0x00)
+                PUSH2(0x5b5b)
+                JUMPDEST
+                STOP
+            };
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+
+    #[test]
+    fn invalid_jump_dest_above_u64() {
+        // the low 64 bits of the destinations are the JUMPDEST, and their low
+        // bytes those of a program counter
+        for high in [Word::one() << 64, Word::one() << 255, Word::MAX << 64] {
+            for opcode in [OpcodeId::JUMP, OpcodeId::JUMPI] {
+                let bytecode = bytecode! {
+                    PUSH1(1) // condition if JUMPI
+                    PUSH32(high + Word::from(0x24))
+                    .write_op(opcode)
+                    JUMPDEST
+                    STOP
+                };
+
+                CircuitTestBuilder::new_from_test_ctx(
+                    TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+                )
+                .run();
+            }
+        }
+    }
+
     // internal call test
     struct Stack {
         gas: u64,
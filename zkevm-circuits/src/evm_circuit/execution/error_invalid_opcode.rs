@@ -120,6 +120,15 @@ mod test {
         test_internal_ok(0x20, 0x00, &[push0]);
     }
 
+    #[cfg(not(feature = "cancun"))]
+    #[test]
+    fn invalid_opcode_blob_opcodes_for_not_cancun() {
+        let (blobhash, blobbasefee) = (0x49, 0x4a);
+        test_root_ok(&[blobhash]);
+        test_root_ok(&[blobbasefee]);
+        test_internal_ok(0x20, 0x00, &[blobbasefee]);
+    }
+
     fn test_root_ok(invalid_code: &[u8]) {
         let mut code = Bytecode::default();
         invalid_code.iter().for_each(|b| {
@@ -246,7 +246,9 @@ mod tests {
     };
     use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::{
-        bytecode, evm_types::gas_utils::memory_copier_gas_cost, Bytecode, ToWord, U256,
+        bytecode,
+        evm_types::gas_utils::{memory_copier_gas_cost, memory_word_size},
+        Bytecode, ToWord, U256,
     };
     use itertools::Itertools;
     use mock::{
@@ -323,7 +325,7 @@ mod tests {
             };
 
             let gas_cost = gas_cost.unwrap_or_else(|| {
-                let memory_word_size = (dst_offset + copy_size + 31) / 32;
+                let memory_word_size = memory_word_size(dst_offset, copy_size);
 
                 OpcodeId::PUSH32.constant_gas_cost().0 * 3
                     + opcode.constant_gas_cost().0
@@ -360,7 +362,7 @@ mod tests {
             }
 
             let gas_cost = gas_cost.unwrap_or_else(|| {
-                let memory_word_size = (dst_offset + copy_size + 31) / 32;
+                let memory_word_size = memory_word_size(dst_offset, copy_size);
 
                 let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 4
                     + GasCost::COLD_ACCOUNT_ACCESS.0
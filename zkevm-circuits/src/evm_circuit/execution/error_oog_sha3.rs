@@ -140,7 +140,9 @@ mod tests {
     use super::*;
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use eth_types::{
-        bytecode, evm_types::gas_utils::memory_copier_gas_cost, Bytecode, ToWord, U256,
+        bytecode,
+        evm_types::gas_utils::{memory_copier_gas_cost, memory_word_size},
+        Bytecode, ToWord, U256,
     };
     use mock::{
         eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
@@ -212,7 +214,7 @@ mod tests {
     }
 
     fn dynamic_gas_cost(memory_offset: u64, memory_size: u64) -> u64 {
-        let memory_word_size = (memory_offset + memory_size + 31) / 32;
+        let memory_word_size = memory_word_size(memory_offset, memory_size);
 
         memory_copier_gas_cost(
             0,
@@ -44,11 +44,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
         // max_degree. otherwise need to do fixed lookup for these opcodes
         // checking.
         cb.require_in_set(
-            "ErrorWriteProtection only happens in [CALL, SSTORE, CREATE, CREATE2, SELFDESTRUCT, LOG0..4 ]",
+            "ErrorWriteProtection only happens in [CALL, SSTORE, TSTORE, CREATE, CREATE2, SELFDESTRUCT, LOG0..4 ]",
             opcode.expr(),
             vec![
                 OpcodeId::CALL.expr(),
                 OpcodeId::SSTORE.expr(),
+                OpcodeId::TSTORE.expr(),
                 OpcodeId::CREATE.expr(),
                 OpcodeId::CREATE2.expr(),
                 OpcodeId::SELFDESTRUCT.expr(),
@@ -66,8 +67,8 @@ impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
             cb.stack_pop(gas_word.expr());
             cb.stack_pop(code_address_word.expr());
             cb.stack_pop(value.expr());
-            //cb.require_zero("value of call is not zero",
-            // is_value_zero.expr());
+            // a CALL without value doesn't write, so is allowed in a static call
+            cb.require_zero("value of call is not zero", is_value_zero.expr());
         });
 
         // current call context is readonly
@@ -219,54 +220,71 @@ mod test {
 
     #[test]
     fn test_write_protection() {
-        // test sstore with write protection error
-        test_internal_write_protection(false);
-        // test call with write protection error
-        test_internal_write_protection(true);
+        for opcode in [
+            OpcodeId::SSTORE,
+            OpcodeId::CALL,
+            OpcodeId::CREATE,
+            OpcodeId::CREATE2,
+            OpcodeId::SELFDESTRUCT,
+            OpcodeId::LOG0,
+            OpcodeId::LOG1,
+            OpcodeId::LOG2,
+            OpcodeId::LOG3,
+            OpcodeId::LOG4,
+        ] {
+            test_internal_write_protection(opcode);
+        }
     }
 
     // ErrorWriteProtection error happen in internal call
-    fn test_internal_write_protection(is_call: bool) {
-        let mut caller_bytecode = bytecode! {
+    fn test_internal_write_protection(opcode: OpcodeId) {
+        let caller_bytecode = bytecode! {
             PUSH1(0)
             PUSH1(0)
             PUSH1(0)
             PUSH1(0)
-            //PUSH1(100) //skip value for static call
-        };
-
-        caller_bytecode.append(&bytecode! {
             PUSH32(Address::repeat_byte(0xff).to_word())
             PUSH2(40000) // gas
-            STATICCALL //CALL
+            STATICCALL
             STOP
-        });
-
-        let mut callee_bytecode = bytecode! {
-            PUSH1(42)
-            PUSH1(0x02)
         };
 
-        if is_call {
-            callee_bytecode.append(&bytecode! {
+        let mut callee_bytecode = match opcode {
+            OpcodeId::SSTORE => bytecode! {
+                PUSH1(42)
+                PUSH1(0x02)
+            },
+            OpcodeId::CALL => bytecode! {
                 PUSH1(0)
                 PUSH1(0)
                 PUSH1(10)
                 PUSH1(200)  // non zero value
                 PUSH20(Address::repeat_byte(0xff).to_word())
                 PUSH2(10000)  // gas
-                //this call got error: ErrorWriteProtection
-                CALL
-                RETURN
-                STOP
-            });
-        } else {
-            callee_bytecode.append(&bytecode! {
-                // this SSTORE got error: ErrorWriteProtection
-                SSTORE
-                STOP
-            });
-        }
+            },
+            OpcodeId::SELFDESTRUCT => bytecode! {
+                PUSH20(Address::repeat_byte(0xfe).to_word())
+            },
+            // value, offset, length and salt of CREATE2, offset, length and
+            // topics of LOGs
+            _ => {
+                let num_args = match opcode {
+                    OpcodeId::CREATE => 3,
+                    OpcodeId::CREATE2 => 4,
+                    _ => 2 + opcode.postfix().unwrap() as usize,
+                };
+                let mut code = Bytecode::default();
+                for _ in 0..num_args {
+                    code.push(1, Word::zero());
+                }
+                code
+            }
+        };
+        callee_bytecode.append(&bytecode! {
+            // this opcode got error: ErrorWriteProtection
+            .write_op(opcode)
+            STOP
+        });
 
         test_ok(
             Account {
@@ -1,20 +1,23 @@
 use bus_mapping::evm::OpcodeId;
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
-use gadgets::util::{and, not, split_u256, sum, Expr};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian, U256};
+use gadgets::util::{and, not, sum, Expr};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-use crate::evm_circuit::{
-    step::ExecutionState,
-    util::{
-        common_gadget::SameContextGadget,
-        constraint_builder::{
-            ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition, Transition,
+use crate::{
+    evm_circuit::{
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition, Transition,
+            },
+            from_bytes,
+            math_gadget::{ByteSizeGadget, IsEqualGadget, IsZeroGadget},
+            CachedRegion, Cell, Word,
         },
-        from_bytes,
-        math_gadget::{ByteSizeGadget, IsEqualGadget, IsZeroGadget},
-        CachedRegion, Cell, Word,
+        witness::{Block, Call, ExecStep, Transaction},
     },
-    witness::{Block, Call, ExecStep, Transaction},
+    util::word::WordLoHi,
 };
 
 use super::ExecutionGadget;
@@ -236,19 +239,13 @@ impl<F: Field> ExecutionGadget<F> for ExponentiationGadget<F> {
         self.exponentiation
             .assign(region, offset, Some(exponentiation.to_le_bytes()))?;
 
-        let (exponent_lo, exponent_hi) = split_u256(&exponent);
-        let exponent_lo_scalar = exponent_lo
-            .to_scalar()
-            .expect("exponent lo should fit into scalar");
-        let exponent_hi_scalar = exponent_hi
-            .to_scalar()
-            .expect("exponent hi should fit into scalar");
+        let exponent_lo_hi = WordLoHi::<F>::from_word(exponent);
         self.exponent_lo_is_zero
-            .assign(region, offset, exponent_lo_scalar)?;
+            .assign(region, offset, exponent_lo_hi.lo)?;
         self.exponent_hi_is_zero
-            .assign(region, offset, exponent_hi_scalar)?;
+            .assign(region, offset, exponent_lo_hi.hi)?;
         self.exponent_lo_is_one
-            .assign(region, offset, exponent_lo_scalar, F::one())?;
+            .assign(region, offset, exponent_lo_hi.lo, F::one())?;
 
         let (base_sq, _) = base.overflowing_mul(base);
         self.zero_rlc
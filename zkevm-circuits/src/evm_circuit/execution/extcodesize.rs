@@ -62,13 +62,13 @@ impl<F: Field> ExecutionGadget<F> for ExtcodesizeGadget<F> {
 
         let code_size = cb.query_word_rlc();
         cb.condition(exists.expr(), |cb| {
-            #[cfg(feature = "scroll")]
+            #[cfg(feature = "poseidon-codehash")]
             cb.account_read(
                 address.expr(),
                 AccountFieldTag::CodeSize,
                 from_bytes::expr(&code_size.cells),
             );
-            #[cfg(not(feature = "scroll"))]
+            #[cfg(not(feature = "poseidon-codehash"))]
             cb.bytecode_length(code_hash.expr(), from_bytes::expr(&code_size.cells));
         });
 
@@ -85,7 +85,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodesizeGadget<F> {
         );
 
         let rw_counter_delta = 7.expr();
-        #[cfg(feature = "scroll")]
+        #[cfg(feature = "poseidon-codehash")]
         let rw_counter_delta = rw_counter_delta + exists;
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(rw_counter_delta),
@@ -147,7 +147,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodesizeGadget<F> {
             .assign_value(region, offset, region.code_hash(code_hash))?;
 
         let rw_offset = 6;
-        #[cfg(feature = "scroll")]
+        #[cfg(feature = "poseidon-codehash")]
         let rw_offset = if code_hash.is_zero() {
             rw_offset
         } else {
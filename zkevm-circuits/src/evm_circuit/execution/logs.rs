@@ -70,6 +70,8 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         let is_persistent = cb.call_context(None, CallContextFieldTag::IsPersistent);
         cb.require_boolean("is_persistent is bool", is_persistent.expr());
 
+        // the address and topics of the persistent logs are set in the logs
+        // bloom of the block
         cb.condition(is_persistent.expr(), |cb| {
             cb.tx_log_lookup(
                 tx_id.expr(),
@@ -78,6 +80,13 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
                 0.expr(),
                 contract_address.expr(),
             );
+            cb.logs_bloom_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::Address,
+                0.expr(),
+                contract_address.expr(),
+            );
         });
 
         // constrain topics in logs
@@ -95,6 +104,13 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
                     idx.expr(),
                     topic.expr(),
                 );
+                cb.logs_bloom_lookup(
+                    tx_id.expr(),
+                    cb.curr.state.log_id.expr() + 1.expr(),
+                    TxLogFieldTag::Topic,
+                    idx.expr(),
+                    topic.expr(),
+                );
             });
         }
 
@@ -280,8 +296,8 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{evm_types::OpcodeId, Bytecode, Word};
-    use mock::TestContext;
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode, ToWord, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
     use rand::Rng;
 
     #[test]
@@ -375,6 +391,57 @@ mod test {
         );
     }
 
+    #[test]
+    fn log_gadget_in_reverted_sub_call() {
+        // The callee emits a log and reverts, between two logs of the caller,
+        // so the caller's logs have consecutive log ids.
+        let code_callee = bytecode! {
+            PUSH1(0xA0) // topic
+            PUSH1(0x20) // msize
+            PUSH1(0x00) // mstart
+            LOG1
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        };
+        let code_caller = bytecode! {
+            PUSH1(0x20) // msize
+            PUSH1(0x00) // mstart
+            LOG0
+            PUSH1(0x00) // retLength
+            PUSH1(0x00) // retOffset
+            PUSH1(0x00) // argsLength
+            PUSH1(0x00) // argsOffset
+            PUSH1(0x00) // value
+            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
+            PUSH32(0x1_0000) // gas
+            CALL
+            POP
+            PUSH1(0x10) // msize
+            PUSH1(0x00) // mstart
+            LOG0
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_caller);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_callee);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 60));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[derive(Clone, Copy)]
     struct Stack {
         mstart: Word,
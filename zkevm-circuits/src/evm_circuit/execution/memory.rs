@@ -177,7 +177,10 @@ mod test {
     use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
     use eth_types::{
         bytecode,
-        evm_types::{GasCost, OpcodeId},
+        evm_types::{
+            gas_utils::{memory_expansion_gas_cost, memory_word_size},
+            GasCost, OpcodeId,
+        },
         Word,
     };
     use mock::test_ctx::{helpers::*, TestContext};
@@ -241,16 +244,14 @@ mod test {
     #[test]
     fn memory_gadget_rand() {
         let calc_gas_cost = |opcode, memory_address: Word| {
-            let memory_address = memory_address.as_u64()
-                + match opcode {
-                    OpcodeId::MSTORE | OpcodeId::MLOAD => 32,
-                    OpcodeId::MSTORE8 => 1,
-                    _ => 0,
-                }
-                + 31;
-            let memory_size = memory_address / 32;
+            let length = match opcode {
+                OpcodeId::MSTORE | OpcodeId::MLOAD => 32,
+                OpcodeId::MSTORE8 => 1,
+                _ => 0,
+            };
+            let memory_size = memory_word_size(memory_address.as_u64(), length);
 
-            GasCost::FASTEST.as_u64() + 3 * memory_size + memory_size * memory_size / 512
+            GasCost::FASTEST.as_u64() + memory_expansion_gas_cost(0, memory_size)
         };
 
         for opcode in [OpcodeId::MSTORE, OpcodeId::MLOAD, OpcodeId::MSTORE8] {
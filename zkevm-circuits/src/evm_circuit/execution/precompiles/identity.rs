@@ -1,5 +1,9 @@
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, NumberOrHash},
+    precompile::PrecompileCalls,
+};
 use eth_types::{Field, ToScalar};
-use gadgets::util::Expr;
+use gadgets::util::{not, Expr};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 use crate::{
@@ -8,10 +12,10 @@ use crate::{
         step::ExecutionState,
         util::{
             common_gadget::RestoreContextGadget, constraint_builder::EVMConstraintBuilder,
-            CachedRegion, Cell,
+            math_gadget::IsZeroGadget, rlc, CachedRegion, Cell,
         },
     },
-    table::CallContextFieldTag,
+    table::{CallContextFieldTag, PrecompileIo},
     witness::{Block, Call, ExecStep, Transaction},
 };
 
@@ -24,6 +28,9 @@ pub struct IdentityGadget<F> {
     call_data_length: Cell<F>,
     return_data_offset: Cell<F>,
     return_data_length: Cell<F>,
+    output_length: Cell<F>,
+    is_call_data_length_zero: IsZeroGadget<F>,
+    input_rlc: Cell<F>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -51,12 +58,47 @@ impl<F: Field> ExecutionGadget<F> for IdentityGadget<F> {
             cb.execution_state().precompile_base_gas_cost().expr(),
         );
 
+        // The output of the precompile becomes the return data of the caller.
+        let output_length = cb.query_cell();
+
+        // The input copied from the memory of the caller is returned as is:
+        // the input and output copy events have the same length and RLC.
+        let is_call_data_length_zero = IsZeroGadget::construct(cb, call_data_length.expr());
+        let input_rlc = cb.query_cell_phase2();
+        cb.condition(not::expr(is_call_data_length_zero.expr()), |cb| {
+            cb.precompile_rlc_lookup(
+                PrecompileIo::Input,
+                PrecompileCalls::Identity,
+                caller_id.expr(),
+                cb.curr.state.call_id.expr(),
+                call_data_length.expr(),
+                input_rlc.expr(),
+            );
+        });
+        cb.condition(is_success.expr(), |cb| {
+            cb.require_equal(
+                "identity output length == call data length",
+                output_length.expr(),
+                call_data_length.expr(),
+            );
+            cb.condition(not::expr(is_call_data_length_zero.expr()), |cb| {
+                cb.precompile_rlc_lookup(
+                    PrecompileIo::Output,
+                    PrecompileCalls::Identity,
+                    caller_id.expr(),
+                    cb.curr.state.call_id.expr(),
+                    call_data_length.expr(),
+                    input_rlc.expr(),
+                );
+            });
+        });
+
         let restore_context = RestoreContextGadget::construct(
             cb,
             is_success.expr(),
             0.expr(),
             0.expr(),
-            0.expr(),
+            output_length.expr(),
             0.expr(),
             0.expr(),
         );
@@ -69,6 +111,9 @@ impl<F: Field> ExecutionGadget<F> for IdentityGadget<F> {
             call_data_length,
             return_data_offset,
             return_data_length,
+            output_length,
+            is_call_data_length_zero,
+            input_rlc,
             restore_context,
         }
     }
@@ -115,6 +160,37 @@ impl<F: Field> ExecutionGadget<F> for IdentityGadget<F> {
             Value::known(F::from(call.return_data_length)),
         )?;
 
+        // The last of the 3 writes following the 7 call context reads and the
+        // 9 caller reads of the restore context.
+        let output_length = block.rws[step.rw_indices[18]].call_context_value();
+        self.output_length.assign(
+            region,
+            offset,
+            Value::known(output_length.to_scalar().unwrap()),
+        )?;
+
+        self.is_call_data_length_zero
+            .assign(region, offset, F::from(call.call_data_length))?;
+        // The bytes of the input copy event, whose RLC is looked up.
+        let input_bytes: Vec<u8> = block
+            .copy_events
+            .iter()
+            .find(|event| {
+                event.dst_type == CopyDataType::Precompile(PrecompileCalls::Identity)
+                    && event.dst_id == NumberOrHash::Number(call.call_id)
+            })
+            .map_or_else(Vec::new, |event| {
+                event.bytes.iter().map(|&(byte, _)| byte).collect()
+            });
+        self.input_rlc.assign(
+            region,
+            offset,
+            region
+                .challenges()
+                .keccak_input()
+                .map(|randomness| rlc::value(input_bytes.iter().rev(), randomness)),
+        )?;
+
         self.restore_context
             .assign(region, offset, block, call, step, 7)
     }
@@ -130,7 +206,7 @@ mod test {
     use itertools::Itertools;
     use mock::TestContext;
 
-    use crate::test_util::CircuitTestBuilder;
+    use crate::test_util::{CircuitTestBuilder, WitnessMutation};
 
     lazy_static::lazy_static! {
         static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
@@ -207,4 +283,21 @@ mod test {
             .run();
         }
     }
+
+    #[test]
+    fn precompile_identity_output_differs_from_input() {
+        // The copy events of the call are the input, the output and the return
+        // data, drop a byte of the output.
+        let bytecode = TEST_VECTOR[1].with_call_op(OpcodeId::CALL);
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .run_with_mutations(&[(
+            WitnessMutation::SkipCopyStep {
+                event_index: 1,
+                byte_index: 0,
+            },
+            "PrecompileRlc",
+        )]);
+    }
 }
@@ -27,6 +27,7 @@ pub struct BasePrecompileGadget<F, const S: ExecutionState> {
     call_data_length: Cell<F>,
     return_data_offset: Cell<F>,
     return_data_length: Cell<F>,
+    output_length: Cell<F>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -54,12 +55,14 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
             cb.execution_state().precompile_base_gas_cost().expr(),
         );
 
+        // The output of the precompile becomes the return data of the caller.
+        let output_length = cb.query_cell();
         let restore_context = RestoreContextGadget::construct(
             cb,
             is_success.expr(),
             0.expr(),
             0.expr(),
-            0.expr(),
+            output_length.expr(),
             0.expr(),
             0.expr(),
         );
@@ -72,6 +75,7 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
             call_data_length,
             return_data_offset,
             return_data_length,
+            output_length,
             restore_context,
         }
     }
@@ -118,6 +122,15 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
             Value::known(F::from(call.return_data_length)),
         )?;
 
+        // The last of the 3 writes following the 7 call context reads and the
+        // 9 caller reads of the restore context.
+        let output_length = block.rws[step.rw_indices[18]].call_context_value();
+        self.output_length.assign(
+            region,
+            offset,
+            Value::known(output_length.to_scalar().unwrap()),
+        )?;
+
         self.restore_context
             .assign(region, offset, block, call, step, 7)
     }
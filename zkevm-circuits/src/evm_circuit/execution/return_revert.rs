@@ -42,6 +42,9 @@ pub(crate) struct ReturnRevertGadget<F> {
     return_data_offset: Cell<F>,
     return_data_length: Cell<F>,
 
+    revert_data_rlc: Cell<F>,
+    revert_data_hash: Cell<F>,
+
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     keccak_code_hash: Cell<F>,
     code_hash: Cell<F>,
@@ -143,7 +146,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
 
             // keccak hash of code.
             let keccak_code_hash = cb.query_cell_phase2();
-            #[cfg(feature = "scroll")]
+            #[cfg(feature = "poseidon-codehash")]
             cb.account_write(
                 address.expr(),
                 AccountFieldTag::KeccakCodeHash,
@@ -162,7 +165,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             // code size.
             let code_size = cb.query_cell_phase2();
             cb.require_equal("range == code size", range.length(), code_size.expr());
-            #[cfg(feature = "scroll")]
+            #[cfg(feature = "poseidon-codehash")]
             cb.account_write(
                 address.expr(),
                 AccountFieldTag::CodeSize,
@@ -182,6 +185,51 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             )
         });
 
+        // The hash of the data a root call reverted with is written to the call
+        // context, where EndTx reads it for the receipt of the tx.
+        let (revert_data_rlc, revert_data_hash) =
+            cb.condition(is_root.expr() * not::expr(is_success.expr()), |cb| {
+                let revert_data_rlc = cb.query_cell_phase2();
+                let revert_data_hash = cb.query_cell_phase2();
+                cb.require_equal(
+                    "increase rw counter once for each memory byte hashed",
+                    copy_rw_increase.expr(),
+                    range.length(),
+                );
+                cb.condition(range.has_length(), |cb| {
+                    cb.copy_table_lookup(
+                        cb.curr.state.call_id.expr(),
+                        CopyDataType::Memory.expr(),
+                        cb.curr.state.call_id.expr(),
+                        CopyDataType::RlcAcc.expr(),
+                        range.offset(),
+                        range.address(),
+                        0.expr(),
+                        range.length(),
+                        revert_data_rlc.expr(),
+                        copy_rw_increase.expr(),
+                    );
+                });
+                cb.condition(not::expr(range.has_length()), |cb| {
+                    cb.require_zero(
+                        "revert_data_rlc == 0 for length = 0",
+                        revert_data_rlc.expr(),
+                    );
+                });
+                cb.keccak_table_lookup(
+                    revert_data_rlc.expr(),
+                    range.length(),
+                    revert_data_hash.expr(),
+                );
+                cb.call_context_lookup(
+                    true.expr(),
+                    None,
+                    CallContextFieldTag::RevertDataHash,
+                    revert_data_hash.expr(),
+                );
+                (revert_data_rlc, revert_data_hash)
+            });
+
         // Case B in the specs.
         cb.condition(is_root.expr(), |cb| {
             cb.require_next_state(ExecutionState::EndTx);
@@ -207,9 +255,9 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         });
 
         // Case C in the specs.
-        #[cfg(feature = "scroll")]
+        #[cfg(feature = "poseidon-codehash")]
         let contract_deployment_rw_num = 3; // dual code hash + code size
-        #[cfg(not(feature = "scroll"))]
+        #[cfg(not(feature = "poseidon-codehash"))]
         let contract_deployment_rw_num = 1;
         let restore_context = cb.condition(not::expr(is_root.expr()), |cb| {
             RestoreContextGadget::construct(
@@ -262,9 +310,9 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             },
         );
 
-        // Without this, copy_rw_increase would be unconstrained for non-create root
-        // calls.
-        cb.condition(not::expr(is_create) * is_root, |cb| {
+        // Without this, copy_rw_increase would be unconstrained for successful
+        // non-create root calls.
+        cb.condition(not::expr(is_create) * is_root * is_success.expr(), |cb| {
             cb.require_zero(
                 "rw counter is 0 if there is no copy event",
                 copy_rw_increase.expr(),
@@ -282,6 +330,8 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             return_data_offset,
             return_data_length,
             restore_context,
+            revert_data_rlc,
+            revert_data_hash,
             memory_expansion,
             code_hash,
             keccak_code_hash,
@@ -363,13 +413,30 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
                 .assign(region, offset, Value::known(F::from(values.len() as u64)))?;
         }
 
-        let copy_rw_increase = if call.is_create && call.is_success {
-            length.as_u64()
-        } else if !call.is_root {
-            2 * std::cmp::min(call.return_data_length, length.as_u64())
-        } else {
-            0
-        };
+        if call.is_root && !call.is_success {
+            let values: Vec<_> = (3..3 + length.as_usize())
+                .map(|i| block.rws[step.rw_indices[i]].memory_value())
+                .collect();
+            self.revert_data_rlc.assign(
+                region,
+                offset,
+                region.keccak_rlc(&values.iter().rev().cloned().collect::<Vec<u8>>()),
+            )?;
+            self.revert_data_hash.assign(
+                region,
+                offset,
+                region.word_rlc(U256::from_big_endian(&keccak256(&values))),
+            )?;
+        }
+
+        let copy_rw_increase =
+            if (call.is_create && call.is_success) || (call.is_root && !call.is_success) {
+                length.as_u64()
+            } else if !call.is_root {
+                2 * std::cmp::min(call.return_data_length, length.as_u64())
+            } else {
+                0
+            };
         self.copy_rw_increase
             .assign(region, offset, Value::known(F::from(copy_rw_increase)))?;
         self.copy_rw_increase_is_zero
@@ -380,7 +447,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             let mut rw_counter_offset = 3;
             if is_contract_deployment {
                 rw_counter_offset += 5 + length.as_u64();
-                #[cfg(feature = "scroll")]
+                #[cfg(feature = "poseidon-codehash")]
                 {
                     rw_counter_offset += 2;
                 }
@@ -420,10 +487,11 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::test_util::{CircuitTestBuilder, WitnessMutation};
+    use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::{
         address, bytecode,
-        evm_types::OpcodeId,
+        evm_types::{OpcodeId, MAX_CODE_SIZE},
         geth_types::{Account, GethData},
         Address, Bytecode, ToWord, Word, U256,
     };
@@ -480,6 +548,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_revert_root_data_hash_mutation_rejected() {
+        let code = callee_bytecode(false, 0, 10);
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .run_with_mutations(&[(WitnessMutation::RevertDataHash { tx_index: 0 }, "Rw")]);
+    }
+
     #[test]
     fn test_return_nonroot_noncreate() {
         let test_parameters = [
@@ -554,6 +631,36 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_return_root_create_max_code_size() {
+        let tx_input = callee_bytecode(true, 0, MAX_CODE_SIZE).code();
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .gas(6_000_000u64.into())
+                    .input(tx_input.into());
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        // The deployed code is read byte by byte from memory, and written to
+        // the bytecode table through the copy circuit.
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(CircuitsParams {
+                max_rws: MAX_CODE_SIZE as usize + 256,
+                max_copy_rows: 2 * MAX_CODE_SIZE as usize + 256,
+                max_bytecode: MAX_CODE_SIZE as usize + 256,
+                ..Default::default()
+            })
+            .run();
+    }
+
     #[test]
     fn test_return_nonroot_create() {
         let test_parameters = [(0, 0), (0, 10), (300, 20), (1000, 0)];
@@ -20,7 +20,10 @@ use crate::{
     util::Expr,
 };
 
-use eth_types::{evm_types::GasCost, Field, ToScalar};
+use eth_types::{
+    evm_types::{gas_utils::sstore_refund, GasCost},
+    Field, ToScalar,
+};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -373,67 +376,13 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             region.word_rlc(value_prev),
         )?;
         debug_assert_eq!(
-            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
-            tx_refund
+            sstore_refund(tx_refund_old, original_value, value_prev, value),
+            Some(tx_refund)
         );
         Ok(())
     }
 }
 
-fn calc_expected_tx_refund(
-    tx_refund_old: u64,
-    value: eth_types::Word,
-    value_prev: eth_types::Word,
-    original_value: eth_types::Word,
-) -> u64 {
-    // Same clause tags(like "delete slot (2.1.2b)") used as [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27)
-    // Control flow of this function try to follow `makeGasSStoreFunc` for better
-    // understanding and comparison.
-
-    let mut tx_refund_new = tx_refund_old;
-
-    // The "clearing slot refund" and "resetting value refund" are ADDED together,
-    // they are NOT MUTUALLY EXCLUSIVE.
-    // Search "Apply both of the following clauses" in EIP-2200 for more details.
-    // There can be five total kinds of refund:
-    // 1. -SSTORE_CLEARS_SCHEDULE
-    // 2. SSTORE_CLEARS_SCHEDULE
-    // 3. SSTORE_SET - WARM_ACCESS
-    // 4. SSTORE_RESET - WARM_ACCESS
-    // 5. -SSTORE_CLEARS_SCHEDULE + SSTORE_RESET - WARM_ACCESS
-    // The last case can happen if (original_value, prev_value, value) be (v,0,v)
-    // where v != 0,
-    // then both "clearing slot refund" and "resetting value refund" are non zero.
-
-    if value_prev != value {
-        // refund related to clearing slot
-        // "delete slot (2.1.2b)" can be safely merged in "delete slot (2.2.1.2)"
-        if !original_value.is_zero() {
-            if value_prev.is_zero() {
-                // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
-            }
-            if value.is_zero() {
-                // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
-            }
-        }
-
-        // refund related to resetting value
-        if original_value == value {
-            if original_value.is_zero() {
-                // reset to original inexistent slot (2.2.2.1)
-                tx_refund_new += GasCost::SSTORE_SET.as_u64() - GasCost::WARM_ACCESS.as_u64();
-            } else {
-                // reset to original existing slot (2.2.2.2)
-                tx_refund_new += GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64();
-            }
-        }
-    }
-
-    tx_refund_new
-}
-
 #[cfg(test)]
 mod test {
 
@@ -7,7 +7,7 @@ use halo2_proofs::{
 use std::collections::HashMap;
 
 // Step dimension
-pub(crate) const STEP_WIDTH: usize = 140;
+pub(crate) const STEP_WIDTH: usize = 144;
 /// Step height
 pub const MAX_STEP_HEIGHT: usize = 21;
 /// The height of the state of a step, used by gates that connect two
@@ -37,7 +37,12 @@ pub(crate) const EVM_LOOKUP_COLS: usize = FIXED_TABLE_LOOKUPS
     + BLOCK_TABLE_LOOKUPS
     + COPY_TABLE_LOOKUPS
     + KECCAK_TABLE_LOOKUPS
-    + EXP_TABLE_LOOKUPS;
+    + EXP_TABLE_LOOKUPS
+    + RECEIPT_TABLE_LOOKUPS
+    + SIG_TABLE_LOOKUPS
+    + P256_VERIFY_TABLE_LOOKUPS
+    + PRECOMPILE_RLC_LOOKUPS
+    + LOGS_BLOOM_TABLE_LOOKUPS;
 
 /// Lookups done per row.
 pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
@@ -49,6 +54,11 @@ pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Copy, COPY_TABLE_LOOKUPS),
     (Table::Keccak, KECCAK_TABLE_LOOKUPS),
     (Table::Exp, EXP_TABLE_LOOKUPS),
+    (Table::Receipt, RECEIPT_TABLE_LOOKUPS),
+    (Table::Sig, SIG_TABLE_LOOKUPS),
+    (Table::P256Verify, P256_VERIFY_TABLE_LOOKUPS),
+    (Table::PrecompileRlc, PRECOMPILE_RLC_LOOKUPS),
+    (Table::LogsBloom, LOGS_BLOOM_TABLE_LOOKUPS),
 ];
 
 /// Fixed Table lookups done in EVMCircuit
@@ -75,6 +85,22 @@ pub const KECCAK_TABLE_LOOKUPS: usize = 1;
 /// Exp Table lookups done in EVMCircuit
 pub const EXP_TABLE_LOOKUPS: usize = 1;
 
+/// Receipt Table lookups done in EVMCircuit
+pub const RECEIPT_TABLE_LOOKUPS: usize = 1;
+
+/// Sig Table lookups done in EVMCircuit
+pub const SIG_TABLE_LOOKUPS: usize = 1;
+
+/// P256 Verify Table lookups done in EVMCircuit
+pub const P256_VERIFY_TABLE_LOOKUPS: usize = 1;
+
+/// Copy Table lookups of the RLC of precompile inputs and outputs done in
+/// EVMCircuit
+pub const PRECOMPILE_RLC_LOOKUPS: usize = 1;
+
+/// Logs Bloom Table lookups done in EVMCircuit
+pub const LOGS_BLOOM_TABLE_LOOKUPS: usize = 1;
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
@@ -2,6 +2,7 @@ pub use crate::table::TxContextFieldTag;
 use crate::{
     evm_circuit::step::{ExecutionState, ResponsibleOp},
     impl_expr,
+    table::PrecompileIo,
 };
 use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
 use eth_types::Field;
@@ -143,6 +144,11 @@ pub(crate) enum Table {
     Copy,
     Keccak,
     Exp,
+    Receipt,
+    Sig,
+    P256Verify,
+    PrecompileRlc,
+    LogsBloom,
 }
 
 #[derive(Clone, Debug)]
@@ -292,6 +298,71 @@ pub(crate) enum Lookup<F> {
         exponent_lo_hi: [Expression<F>; 2],
         exponentiation_lo_hi: [Expression<F>; 2],
     },
+    /// Lookup to receipt table, which contains the receipt fields of each tx.
+    Receipt {
+        /// Id of the tx.
+        tx_id: Expression<F>,
+        /// Tag to specify which field to read.
+        field_tag: Expression<F>,
+        /// Value of the field.
+        value: Expression<F>,
+    },
+    /// Lookup to sig table, which contains the signatures verified by the
+    /// sig circuit.
+    SigTable {
+        /// RLC of the message hash
+        msg_hash_rlc: Expression<F>,
+        /// RLC of `r`
+        sig_r_rlc: Expression<F>,
+        /// RLC of `s`
+        sig_s_rlc: Expression<F>,
+        /// RLC of the recovered address
+        recovered_addr_rlc: Expression<F>,
+    },
+    /// Lookup to P256 verify table, which contains the P256VERIFY calls with
+    /// a valid signature.
+    P256VerifyTable {
+        /// RLC of the message hash
+        msg_hash_rlc: Expression<F>,
+        /// RLC of `r`
+        sig_r_rlc: Expression<F>,
+        /// RLC of `s`
+        sig_s_rlc: Expression<F>,
+        /// RLC of the `x` coordinate of the public key
+        pk_x_rlc: Expression<F>,
+        /// RLC of the `y` coordinate of the public key
+        pk_y_rlc: Expression<F>,
+    },
+    /// Lookup to copy table, restricted to the RLC of the bytes a precompile
+    /// received or returned.
+    PrecompileRlc {
+        /// Whether the input or the output of the precompile is looked up.
+        io: PrecompileIo,
+        /// The precompile called.
+        precompile: PrecompileCalls,
+        /// The ID of the caller of the precompile.
+        caller_id: Expression<F>,
+        /// The ID of the precompile call.
+        call_id: Expression<F>,
+        /// The number of bytes of the input or output.
+        length: Expression<F>,
+        /// The RLC of the bytes of the input or output.
+        rlc: Expression<F>,
+    },
+    /// Lookup to logs bloom table, which contains the addresses and topics
+    /// of the logs set in the logs bloom of their block.
+    LogsBloom {
+        /// Id of the tx.
+        tx_id: Expression<F>,
+        /// Id of the log in the tx.
+        log_id: Expression<F>,
+        /// Tag to specify whether the address or a topic is looked up.
+        field_tag: Expression<F>,
+        /// Index of the topic, 0 for the address.
+        index: Expression<F>,
+        /// Address, or RLC of the topic.
+        value: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -311,6 +382,11 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::ExpTable { .. } => Table::Exp,
+            Self::Receipt { .. } => Table::Receipt,
+            Self::SigTable { .. } => Table::Sig,
+            Self::P256VerifyTable { .. } => Table::P256Verify,
+            Self::PrecompileRlc { .. } => Table::PrecompileRlc,
+            Self::LogsBloom { .. } => Table::LogsBloom,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -433,6 +509,70 @@ impl<F: Field> Lookup<F> {
                 exponentiation_lo_hi[0].clone(),
                 exponentiation_lo_hi[1].clone(),
             ],
+            Self::Receipt {
+                tx_id,
+                field_tag,
+                value,
+            } => vec![
+                1.expr(), // q_enable
+                tx_id.clone(),
+                field_tag.clone(),
+                value.clone(),
+            ],
+            Self::SigTable {
+                msg_hash_rlc,
+                sig_r_rlc,
+                sig_s_rlc,
+                recovered_addr_rlc,
+            } => vec![
+                1.expr(), // q_enable
+                msg_hash_rlc.clone(),
+                sig_r_rlc.clone(),
+                sig_s_rlc.clone(),
+                recovered_addr_rlc.clone(),
+            ],
+            Self::P256VerifyTable {
+                msg_hash_rlc,
+                sig_r_rlc,
+                sig_s_rlc,
+                pk_x_rlc,
+                pk_y_rlc,
+            } => vec![
+                1.expr(), // q_enable
+                msg_hash_rlc.clone(),
+                sig_r_rlc.clone(),
+                sig_s_rlc.clone(),
+                pk_x_rlc.clone(),
+                pk_y_rlc.clone(),
+            ],
+            Self::PrecompileRlc {
+                io,
+                precompile,
+                caller_id,
+                call_id,
+                length,
+                rlc,
+            } => io.lookup_input_exprs(
+                *precompile,
+                caller_id.clone(),
+                call_id.clone(),
+                length.clone(),
+                rlc.clone(),
+            ),
+            Self::LogsBloom {
+                tx_id,
+                log_id,
+                field_tag,
+                index,
+                value,
+            } => vec![
+                1.expr(), // q_enable
+                tx_id.clone(),
+                log_id.clone(),
+                field_tag.clone(),
+                index.clone(),
+                value.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
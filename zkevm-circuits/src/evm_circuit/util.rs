@@ -7,11 +7,11 @@ use crate::{
         table::Table,
     },
     table::RwTableTag,
-    util::{query_expression, Challenges, Expr},
+    util::{query_expression, word, Challenges, Expr},
     witness::{Block, ExecStep, Rw, RwMap},
 };
-use bus_mapping::state_db::CodeDB;
-use eth_types::{Address, ToLittleEndian, ToWord, U256};
+use bus_mapping::{precompile::PrecompileCalls, state_db::CodeDB};
+use eth_types::{Address, ToWord, U256};
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{AssignedCell, Region, Value},
@@ -193,18 +193,16 @@ impl<'r, 'b, F: FieldExt> CachedRegion<'r, 'b, F> {
     }
 
     pub fn word_rlc(&self, n: U256) -> Value<F> {
-        self.challenges
-            .evm_word()
-            .map(|r| rlc::value(&n.to_le_bytes(), r))
+        word::rlc_value(&n, self.challenges.evm_word())
     }
 
     pub fn code_hash(&self, n: U256) -> Value<F> {
         self.challenges.evm_word().map(|r| {
             if cfg!(feature = "poseidon-codehash") {
                 // only FieldExt is not enough for ToScalar trait so we have to make workaround
-                rlc::value(&n.to_le_bytes(), F::from(256u64))
+                word::rlc(&n, F::from(256u64))
             } else {
-                rlc::value(&n.to_le_bytes(), r)
+                word::rlc(&n, r)
             }
         })
     }
@@ -580,7 +578,7 @@ pub(crate) mod from_bytes {
 pub(crate) mod rlc {
     use std::ops::{Add, Mul};
 
-    use crate::util::Expr;
+    use crate::util::{fast_mock, Expr};
     use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
 
     pub(crate) fn expr<F: FieldExt, E: Expr<F>>(expressions: &[E], randomness: E) -> Expression<F> {
@@ -596,15 +594,8 @@ pub(crate) mod rlc {
         I: IntoIterator<Item = &'a u8>,
         <I as IntoIterator>::IntoIter: DoubleEndedIterator,
     {
-        let values = values
-            .into_iter()
-            .map(|v| F::from(*v as u64))
-            .collect::<Vec<F>>();
-        if !values.is_empty() {
-            generic(values, randomness)
-        } else {
-            F::zero()
-        }
+        let values = values.into_iter().copied().collect::<Vec<u8>>();
+        fast_mock::rlc(&values, randomness)
     }
 
     fn generic<V, I>(values: I, randomness: V) -> V
@@ -630,24 +621,6 @@ pub(crate) fn pow_of_two_expr<F: FieldExt>(by: usize) -> Expression<F> {
     Expression::Constant(pow_of_two(by))
 }
 
-/// Returns tuple consists of low and high part of U256
-pub(crate) fn split_u256(value: &U256) -> (U256, U256) {
-    (
-        U256([value.0[0], value.0[1], 0, 0]),
-        U256([value.0[2], value.0[3], 0, 0]),
-    )
-}
-
-/// Split a U256 value into 4 64-bit limbs stored in U256 values.
-pub(crate) fn split_u256_limb64(value: &U256) -> [U256; 4] {
-    [
-        U256([value.0[0], 0, 0, 0]),
-        U256([value.0[1], 0, 0, 0]),
-        U256([value.0[2], 0, 0, 0]),
-        U256([value.0[3], 0, 0, 0]),
-    ]
-}
-
 /// Transposes an `Value` of a [`Result`] into a [`Result`] of an `Value`.
 pub(crate) fn transpose_val_ret<F, E>(value: Value<Result<F, E>>) -> Result<Value<F>, E> {
     let mut ret = Ok(Value::unknown());
@@ -658,7 +631,8 @@ pub(crate) fn transpose_val_ret<F, E>(value: Value<Result<F, E>>) -> Result<Valu
 }
 
 pub(crate) fn is_precompiled(address: &Address) -> bool {
-    address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+    (address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19]))
+        || (cfg!(feature = "rip7212") && *address == PrecompileCalls::P256Verify.into())
 }
 
 /// Helper struct to read rw operations from a step sequentially.
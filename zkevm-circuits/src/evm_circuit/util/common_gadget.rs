@@ -1,7 +1,7 @@
 use super::{
     constraint_builder::ConstrainBuilderCommon,
     from_bytes,
-    math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+    math_gadget::{ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
     memory_gadget::{CommonMemoryAddressGadget, MemoryExpansionGadget},
     CachedRegion,
 };
@@ -23,15 +23,22 @@ use crate::{
     util::Expr,
     witness::{Block, Call, ExecStep},
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use eth_types::{
+    evm_types::{gas_utils::all_but_one_64th_gas, GasCost, GAS_STIPEND_CALL_WITH_VALUE},
+    Field, ToLittleEndian, ToScalar, U256,
+};
 use gadgets::util::{select, sum};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
 };
 
+#[cfg(feature = "prague")]
+mod authorization_list;
 mod tx_l1_fee;
 
+#[cfg(feature = "prague")]
+pub(crate) use authorization_list::AuthorizationListGadget;
 pub(crate) use tx_l1_fee::TxL1FeeGadget;
 
 /// Construction of execution state that stays in the same call context, which
@@ -848,6 +855,77 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
     }
 }
 
+/// Gas a CALL, CALLCODE, DELEGATECALL or STATICCALL starts its callee with:
+/// all but one 64th of the gas available after the cost of the call (EIP 150),
+/// capped by the gas argument if it fits in 64 bits, plus the stipend of a
+/// call with value.
+#[derive(Clone, Debug)]
+pub(crate) struct CallGasGadget<F> {
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    callee_gas_left: Expression<F>,
+    stipend: Expression<F>,
+}
+
+impl<F: Field> CallGasGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        gas_available: Expression<F>,
+        gas: Expression<F>,
+        gas_is_u64: Expression<F>,
+        has_value: Expression<F>,
+    ) -> Self {
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
+        let capped_callee_gas_left = MinMaxGadget::construct(cb, gas, all_but_one_64th_gas.clone());
+        let callee_gas_left = select::expr(
+            gas_is_u64,
+            capped_callee_gas_left.min(),
+            all_but_one_64th_gas,
+        );
+
+        Self {
+            one_64th_gas,
+            capped_callee_gas_left,
+            callee_gas_left,
+            stipend: has_value * GAS_STIPEND_CALL_WITH_VALUE.expr(),
+        }
+    }
+
+    /// Gas given to the callee out of the gas of the caller
+    pub(crate) fn callee_gas_left(&self) -> Expression<F> {
+        self.callee_gas_left.clone()
+    }
+
+    /// Stipend given to the callee on top of the gas of the caller
+    pub(crate) fn stipend(&self) -> Expression<F> {
+        self.stipend.clone()
+    }
+
+    /// Gas the callee starts with
+    pub(crate) fn callee_gas_left_with_stipend(&self) -> Expression<F> {
+        self.callee_gas_left() + self.stipend()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        gas_available: u64,
+        gas: U256,
+    ) -> Result<(), Error> {
+        self.one_64th_gas
+            .assign(region, offset, gas_available.into())?;
+        self.capped_callee_gas_left.assign(
+            region,
+            offset,
+            F::from(gas.low_u64()),
+            F::from(all_but_one_64th_gas(gas_available)),
+        )?;
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct SloadGasGadget<F> {
     is_warm: Expression<F>,
@@ -0,0 +1,564 @@
+use super::{CachedRegion, Cell};
+use crate::{
+    evm_circuit::{
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        util::{
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            not, StepRws,
+        },
+    },
+    table::{AccountFieldTag, AuthorizationField, TxFieldTag},
+    util::Expr,
+    witness::{Block, Transaction},
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, state_db::CodeDB};
+use eth_types::{
+    eip7702::{
+        DELEGATION_DESIGNATOR_LEN, DELEGATION_DESIGNATOR_PREFIX, MAX_AUTHORIZATIONS_PER_TX,
+        PER_AUTH_BASE_COST, PER_EMPTY_ACCOUNT_COST,
+    },
+    Field, ToLittleEndian, ToScalar, ToWord, U256,
+};
+use gadgets::util::{select, sum};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+/// Authorization list gadget of an EIP-7702 set-code tx, which applies its
+/// authorizations in BeginTx, after the access list is warmed up.
+///
+/// An authorization whose authority is zero in the tx table isn't valid on
+/// the chain and is skipped. Otherwise its authority is warmed up, and its
+/// code hash and, if the authority exists or the authorization applies, its
+/// nonce are written. The authorization applies when the authority has no
+/// code other than a delegation designator and has the nonce of the
+/// authorization: its code is set to the designator of the address, whose
+/// bytes are looked up in the copy table, or is cleared for the zero address,
+/// and its nonce is increased. The refund of the authorities which existed is
+/// written last. None of these writes is reversible.
+#[derive(Clone, Debug)]
+pub(crate) struct AuthorizationListGadget<F> {
+    authorizations: [AuthorizationGadget<F>; MAX_AUTHORIZATIONS_PER_TX],
+    rw_delta: Cell<F>,
+}
+
+#[derive(Clone, Debug)]
+struct AuthorizationGadget<F> {
+    is_active: Cell<F>,
+    authority: Cell<F>,
+    authority_is_zero: IsZeroGadget<F>,
+    is_checked: Cell<F>,
+    is_warm_prev: Cell<F>,
+    address: Cell<F>,
+    address_bytes: [Cell<F>; N_BYTES_ACCOUNT_ADDRESS],
+    address_is_zero: IsZeroGadget<F>,
+    nonce: Cell<F>,
+    prev_code_hash: Cell<F>,
+    prev_not_exists: IsZeroGadget<F>,
+    is_prev_empty_code: IsEqualGadget<F>,
+    // The authority has code, which is either a delegation designator or
+    // another code.
+    has_prev_code: Cell<F>,
+    prev_code_size: Cell<F>,
+    is_designator_size: IsEqualGadget<F>,
+    prev_code_prefix: [Cell<F>; 3],
+    prev_code_prefix_is_code: [Cell<F>; 3],
+    is_designator_prefix: IsEqualGadget<F>,
+    is_designator: Cell<F>,
+    prev_nonce: Cell<F>,
+    is_nonce_match: IsEqualGadget<F>,
+    is_applied: Cell<F>,
+    code_hash: Cell<F>,
+    #[cfg(feature = "poseidon-codehash")]
+    keccak_code_hash: Cell<F>,
+    #[cfg(feature = "poseidon-codehash")]
+    prev_keccak_code_hash: Cell<F>,
+}
+
+impl<F: Field> AuthorizationListGadget<F> {
+    /// Construct the gadget for the tx `tx_id` with an authorization list of
+    /// `length`, whose root call is `call_id`.
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        tx_id: Expression<F>,
+        call_id: Expression<F>,
+        length: Expression<F>,
+        is_valid: Expression<F>,
+    ) -> Self {
+        let rw_counter_offset = cb.rw_counter_offset();
+
+        let authorizations: [AuthorizationGadget<F>; MAX_AUTHORIZATIONS_PER_TX] =
+            array_init::array_init(|i| {
+                AuthorizationGadget::construct(cb, tx_id.expr(), call_id.expr(), i)
+            });
+
+        // The authorizations of the list are the first ones, and an invalid
+        // tx applies none of them.
+        for pair in authorizations.windows(2) {
+            cb.require_zero(
+                "authorization is active only after an active one",
+                pair[1].is_active.expr() * not::expr(pair[0].is_active.expr()),
+            );
+        }
+        cb.require_equal(
+            "number of active authorizations == authorization list length",
+            sum::expr(authorizations.iter().map(|auth| auth.is_active.expr())),
+            length * is_valid,
+        );
+
+        let refund = sum::expr(
+            authorizations
+                .iter()
+                .map(|auth| auth.is_applied.expr() * not::expr(auth.prev_not_exists.expr())),
+        ) * (PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST).expr();
+        cb.condition(authorizations[0].is_active.expr(), |cb| {
+            cb.tx_refund_write(tx_id.expr(), refund, 0.expr(), None);
+        });
+
+        let rw_delta = cb.query_cell();
+        cb.require_equal(
+            "rw_delta == number of rw operations of the authorization list",
+            rw_delta.expr(),
+            cb.rw_counter_offset() - rw_counter_offset,
+        );
+
+        Self {
+            authorizations,
+            rw_delta,
+        }
+    }
+
+    pub(crate) fn rw_delta(&self) -> Expression<F> {
+        self.rw_delta.expr()
+    }
+
+    /// Assign the gadget from the rw operations of the authorization list,
+    /// which `rws` is at the start of.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        rws: &mut StepRws,
+    ) -> Result<(), Error> {
+        let length = tx.authorization_list.len();
+
+        let mut rw_delta = 0;
+        for (i, (authorization, fields)) in self
+            .authorizations
+            .iter()
+            .zip(tx.authorization_fields().chunks(3))
+            .enumerate()
+        {
+            let [authority, address, nonce] = [
+                AuthorizationField::Authority,
+                AuthorizationField::Address,
+                AuthorizationField::Nonce,
+            ]
+            .map(|field| fields[field as usize].1);
+            let is_active = !tx.is_invalid && i < length;
+            rw_delta += authorization.assign(
+                region,
+                offset,
+                block,
+                rws,
+                is_active,
+                authority,
+                address,
+                nonce.as_u64(),
+            )?;
+        }
+        if !tx.is_invalid && length > 0 {
+            rws.next().tx_refund_value_pair();
+            rw_delta += 1;
+        }
+        self.rw_delta
+            .assign(region, offset, Value::known(F::from(rw_delta)))?;
+
+        Ok(())
+    }
+}
+
+impl<F: Field> AuthorizationGadget<F> {
+    fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        tx_id: Expression<F>,
+        call_id: Expression<F>,
+        i: usize,
+    ) -> Self {
+        let [authority, address, nonce] = [
+            AuthorizationField::Authority,
+            AuthorizationField::Address,
+            AuthorizationField::Nonce,
+        ]
+        .map(|field| {
+            cb.tx_context(
+                tx_id.expr(),
+                TxFieldTag::Authorization,
+                Some(field.index(i).expr()),
+            )
+        });
+        let is_active = cb.query_bool();
+        let authority_is_zero = IsZeroGadget::construct(cb, authority.expr());
+        let is_checked = cb.query_bool();
+        cb.require_equal(
+            "authorization is checked if its authority is valid on the chain",
+            is_checked.expr(),
+            is_active.expr() * not::expr(authority_is_zero.expr()),
+        );
+
+        let address_bytes = cb.query_bytes();
+        cb.require_equal(
+            "address bytes of the authorization",
+            from_bytes::expr(&address_bytes),
+            address.expr(),
+        );
+        let address_is_zero = IsZeroGadget::construct(cb, address.expr());
+
+        // The code of the authority before the authorization, which a
+        // non-existing account has as 0.
+        let prev_code_hash = cb.query_cell_phase2();
+        let prev_not_exists = IsZeroGadget::construct(cb, prev_code_hash.expr());
+        let is_prev_empty_code =
+            IsEqualGadget::construct(cb, prev_code_hash.expr(), cb.empty_code_hash_rlc());
+        let has_prev_code = cb.query_bool();
+        cb.require_equal(
+            "authority has code if it exists with a non-empty code hash",
+            has_prev_code.expr(),
+            not::expr(prev_not_exists.expr()) * not::expr(is_prev_empty_code.expr()),
+        );
+        let prev_code_size = cb.query_cell();
+        cb.condition(has_prev_code.expr(), |cb| {
+            cb.bytecode_length(prev_code_hash.expr(), prev_code_size.expr());
+        });
+        let is_designator_size =
+            IsEqualGadget::construct(cb, prev_code_size.expr(), DELEGATION_DESIGNATOR_LEN.expr());
+        let prev_code_prefix: [Cell<F>; 3] = cb.query_bytes();
+        let prev_code_prefix_is_code: [Cell<F>; 3] = array_init::array_init(|_| cb.query_cell());
+        cb.condition(has_prev_code.expr() * is_designator_size.expr(), |cb| {
+            for (index, (byte, is_code)) in prev_code_prefix
+                .iter()
+                .zip(prev_code_prefix_is_code.iter())
+                .enumerate()
+            {
+                cb.bytecode_lookup(
+                    prev_code_hash.expr(),
+                    index.expr(),
+                    is_code.expr(),
+                    byte.expr(),
+                );
+            }
+        });
+        let is_designator_prefix = IsEqualGadget::construct(
+            cb,
+            prev_code_prefix
+                .iter()
+                .fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()),
+            DELEGATION_DESIGNATOR_PREFIX
+                .iter()
+                .fold(0u64, |acc, byte| acc * 256 + *byte as u64)
+                .expr(),
+        );
+        let is_designator = cb.query_bool();
+        cb.require_equal(
+            "code of the authority is a delegation designator",
+            is_designator.expr(),
+            has_prev_code.expr() * is_designator_size.expr() * is_designator_prefix.expr(),
+        );
+
+        // A non-existing authority has nonce 0, which isn't written unless
+        // the authorization applies.
+        let prev_nonce = cb.query_cell();
+        cb.condition(is_checked.expr() * prev_not_exists.expr(), |cb| {
+            cb.require_zero("non-existing authority has nonce 0", prev_nonce.expr());
+        });
+        let is_nonce_match = IsEqualGadget::construct(cb, prev_nonce.expr(), nonce.expr());
+        let is_applied = cb.query_bool();
+        cb.require_equal(
+            "authorization applies to an authority without code and with its nonce",
+            is_applied.expr(),
+            is_checked.expr()
+                * not::expr(has_prev_code.expr() - is_designator.expr())
+                * is_nonce_match.expr(),
+        );
+
+        let code_hash = cb.query_cell_phase2();
+        #[cfg(feature = "poseidon-codehash")]
+        let keccak_code_hash = cb.query_cell_phase2();
+        #[cfg(feature = "poseidon-codehash")]
+        let prev_keccak_code_hash = cb.query_cell_phase2();
+        let is_warm_prev = cb.query_bool();
+        cb.condition(is_checked.expr(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                authority.expr(),
+                1.expr(),
+                is_warm_prev.expr(),
+                None,
+            );
+        });
+        cb.condition(is_applied.expr(), |cb| {
+            // The designator is copied from the bytecode table, and the
+            // accumulated bytes, in the order of the copy table, are the
+            // little-endian bytes of the address then the reversed prefix.
+            cb.condition(not::expr(address_is_zero.expr()), |cb| {
+                let mut bytes = address_bytes.iter().map(Expr::expr).collect::<Vec<_>>();
+                bytes.extend(DELEGATION_DESIGNATOR_PREFIX.iter().rev().map(Expr::expr));
+                cb.copy_table_lookup(
+                    code_hash.expr(),
+                    CopyDataType::Bytecode.expr(),
+                    call_id.expr(),
+                    CopyDataType::RlcAcc.expr(),
+                    0.expr(),
+                    DELEGATION_DESIGNATOR_LEN.expr(),
+                    0.expr(),
+                    DELEGATION_DESIGNATOR_LEN.expr(),
+                    cb.keccak_rlc::<DELEGATION_DESIGNATOR_LEN>(bytes.try_into().unwrap()),
+                    0.expr(),
+                );
+            });
+            cb.condition(address_is_zero.expr(), |cb| {
+                cb.require_equal(
+                    "delegation to the zero address clears the code",
+                    code_hash.expr(),
+                    cb.empty_code_hash_rlc(),
+                );
+            });
+            #[cfg(feature = "poseidon-codehash")]
+            cb.account_write(
+                authority.expr(),
+                AccountFieldTag::KeccakCodeHash,
+                keccak_code_hash.expr(),
+                prev_keccak_code_hash.expr() * not::expr(prev_not_exists.expr()),
+                None,
+            );
+        });
+        cb.condition(is_checked.expr(), |cb| {
+            cb.account_write(
+                authority.expr(),
+                AccountFieldTag::CodeHash,
+                select::expr(is_applied.expr(), code_hash.expr(), prev_code_hash.expr()),
+                prev_code_hash.expr(),
+                None,
+            );
+        });
+        #[cfg(feature = "poseidon-codehash")]
+        cb.condition(is_applied.expr(), |cb| {
+            cb.account_write(
+                authority.expr(),
+                AccountFieldTag::CodeSize,
+                not::expr(address_is_zero.expr()) * DELEGATION_DESIGNATOR_LEN.expr(),
+                has_prev_code.expr() * prev_code_size.expr(),
+                None,
+            );
+        });
+        cb.condition(
+            is_checked.expr()
+                * (is_applied.expr()
+                    + not::expr(is_applied.expr()) * not::expr(prev_not_exists.expr())),
+            |cb| {
+                cb.account_write(
+                    authority.expr(),
+                    AccountFieldTag::Nonce,
+                    prev_nonce.expr() + is_applied.expr(),
+                    prev_nonce.expr(),
+                    None,
+                );
+            },
+        );
+
+        Self {
+            is_active,
+            authority,
+            authority_is_zero,
+            is_checked,
+            is_warm_prev,
+            address,
+            address_bytes,
+            address_is_zero,
+            nonce,
+            prev_code_hash,
+            prev_not_exists,
+            is_prev_empty_code,
+            has_prev_code,
+            prev_code_size,
+            is_designator_size,
+            prev_code_prefix,
+            prev_code_prefix_is_code,
+            is_designator_prefix,
+            is_designator,
+            prev_nonce,
+            is_nonce_match,
+            is_applied,
+            code_hash,
+            #[cfg(feature = "poseidon-codehash")]
+            keccak_code_hash,
+            #[cfg(feature = "poseidon-codehash")]
+            prev_keccak_code_hash,
+        }
+    }
+
+    /// Assign the authorization and return the number of its rw operations.
+    #[allow(clippy::too_many_arguments)]
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        rws: &mut StepRws,
+        is_active: bool,
+        authority: U256,
+        address: U256,
+        nonce: u64,
+    ) -> Result<u64, Error> {
+        let is_checked = is_active && !authority.is_zero();
+        let mut rw_delta = 0;
+        let mut is_warm_prev = false;
+        let mut code_hash = U256::zero();
+        let mut prev_code_hash = U256::zero();
+        #[cfg(feature = "poseidon-codehash")]
+        let mut keccak_code_hash_pair = (U256::zero(), U256::zero());
+        let mut nonce_pair = (U256::zero(), U256::zero());
+        if is_checked {
+            is_warm_prev = rws.next().tx_access_list_value_pair().1;
+            let mut rw = rws.next();
+            // Only an applied authorization writes the keccak code hash.
+            #[cfg(feature = "poseidon-codehash")]
+            let is_applied = rw.field_tag() == Some(AccountFieldTag::KeccakCodeHash as u64);
+            #[cfg(feature = "poseidon-codehash")]
+            if is_applied {
+                keccak_code_hash_pair = rw.account_value_pair();
+                rw = rws.next();
+                rw_delta += 1;
+            }
+            (code_hash, prev_code_hash) = rw.account_codehash_pair();
+            #[cfg(feature = "poseidon-codehash")]
+            if is_applied {
+                rws.next().account_value_pair();
+                rw_delta += 1;
+            }
+            // The nonce is written if the authority exists, or if the
+            // authorization applies to the non-existing authority.
+            if !prev_code_hash.is_zero() || nonce == 0 {
+                nonce_pair = rws.next().account_nonce_pair();
+                rw_delta += 1;
+            }
+            rw_delta += 2;
+        }
+        let is_applied = nonce_pair.0 != nonce_pair.1;
+        let prev_nonce = nonce_pair.1;
+
+        let (prev_code_size, prev_code_prefix) =
+            if prev_code_hash.is_zero() || prev_code_hash == CodeDB::empty_code_hash().to_word() {
+                (0, [[0u8; 2]; 3])
+            } else {
+                let code = block
+                    .bytecodes
+                    .get(&prev_code_hash)
+                    .expect("could not find the code of the authority");
+                let prefix = if code.bytes.len() == DELEGATION_DESIGNATOR_LEN {
+                    array_init::array_init(|index| code.get(index))
+                } else {
+                    [[0u8; 2]; 3]
+                };
+                (code.bytes.len() as u64, prefix)
+            };
+        let has_prev_code = prev_code_size > 0;
+        let prefix = prev_code_prefix
+            .iter()
+            .fold(0u64, |acc, [byte, _]| acc * 256 + *byte as u64);
+        let designator_prefix = DELEGATION_DESIGNATOR_PREFIX
+            .iter()
+            .fold(0u64, |acc, byte| acc * 256 + *byte as u64);
+        let is_designator = has_prev_code
+            && prev_code_size == DELEGATION_DESIGNATOR_LEN as u64
+            && prefix == designator_prefix;
+
+        let [authority_scalar, address_scalar] = [authority, address].map(|value| {
+            value
+                .to_scalar()
+                .expect("unexpected Address -> Scalar conversion failure")
+        });
+        for (cell, value) in [
+            (&self.is_active, F::from(is_active as u64)),
+            (&self.authority, authority_scalar),
+            (&self.is_checked, F::from(is_checked as u64)),
+            (&self.is_warm_prev, F::from(is_warm_prev as u64)),
+            (&self.address, address_scalar),
+            (&self.nonce, F::from(nonce)),
+            (&self.has_prev_code, F::from(has_prev_code as u64)),
+            (&self.prev_code_size, F::from(prev_code_size)),
+            (&self.is_designator, F::from(is_designator as u64)),
+            (&self.prev_nonce, F::from(prev_nonce.as_u64())),
+            (&self.is_applied, F::from(is_applied as u64)),
+        ] {
+            cell.assign(region, offset, Value::known(value))?;
+        }
+        self.authority_is_zero
+            .assign(region, offset, authority_scalar)?;
+        for (cell, byte) in self.address_bytes.iter().zip(address.to_le_bytes()) {
+            cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+        }
+        self.address_is_zero
+            .assign(region, offset, address_scalar)?;
+
+        self.prev_code_hash
+            .assign(region, offset, region.code_hash(prev_code_hash))?;
+        self.prev_not_exists
+            .assign_value(region, offset, region.code_hash(prev_code_hash))?;
+        self.is_prev_empty_code.assign_value(
+            region,
+            offset,
+            region.code_hash(prev_code_hash),
+            region.empty_code_hash_rlc(),
+        )?;
+        self.is_designator_size.assign(
+            region,
+            offset,
+            F::from(prev_code_size),
+            F::from(DELEGATION_DESIGNATOR_LEN as u64),
+        )?;
+        for ((byte_cell, is_code_cell), [byte, is_code]) in self
+            .prev_code_prefix
+            .iter()
+            .zip(self.prev_code_prefix_is_code.iter())
+            .zip(prev_code_prefix)
+        {
+            byte_cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+            is_code_cell.assign(region, offset, Value::known(F::from(is_code as u64)))?;
+        }
+        self.is_designator_prefix.assign(
+            region,
+            offset,
+            F::from(prefix),
+            F::from(designator_prefix),
+        )?;
+        self.is_nonce_match
+            .assign(region, offset, F::from(prev_nonce.as_u64()), F::from(nonce))?;
+
+        self.code_hash.assign(
+            region,
+            offset,
+            region.code_hash(if is_applied { code_hash } else { U256::zero() }),
+        )?;
+        #[cfg(feature = "poseidon-codehash")]
+        {
+            self.keccak_code_hash.assign(
+                region,
+                offset,
+                region.word_rlc(keccak_code_hash_pair.0),
+            )?;
+            self.prev_keccak_code_hash.assign(
+                region,
+                offset,
+                region.word_rlc(keccak_code_hash_pair.1),
+            )?;
+        }
+
+        Ok(rw_delta)
+    }
+}
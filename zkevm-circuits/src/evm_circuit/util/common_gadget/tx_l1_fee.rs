@@ -4,7 +4,7 @@ use crate::{
         param::N_BYTES_U64,
         util::{
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-            from_bytes, U64Word,
+            from_bytes, not, U64Word,
         },
     },
     util::Expr,
@@ -38,12 +38,25 @@ pub(crate) struct TxL1FeeGadget<F> {
 }
 
 impl<F: Field> TxL1FeeGadget<F> {
+    /// Number of rw operations of the gadget:
+    /// - L1 base fee Read
+    /// - L1 fee overhead Read
+    /// - L1 fee scalar Read
+    /// - CallContext L1Fee Write (scroll only)
+    #[cfg(feature = "scroll")]
+    pub(crate) const RW_DELTA: usize = 4;
+    #[cfg(not(feature = "scroll"))]
+    pub(crate) const RW_DELTA: usize = 3;
+
+    #[cfg_attr(not(feature = "scroll"), allow(unused_variables))]
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
         tx_id: Expression<F>,
+        call_id: Expression<F>,
         tx_data_gas_cost: Expression<F>,
+        is_l1_msg: Expression<F>,
     ) -> Self {
-        let this = Self::raw_construct(cb, tx_data_gas_cost);
+        let this = Self::raw_construct(cb, tx_data_gas_cost, is_l1_msg);
 
         let l1_fee_address = Expression::Constant(l1_gas_price_oracle::ADDRESS.to_scalar().expect(
             "Unexpected address of l2 gasprice oracle contract -> Scalar conversion failure",
@@ -83,6 +96,15 @@ impl<F: Field> TxL1FeeGadget<F> {
             this.fee_scalar_committed.expr(),
         );
 
+        // Keep the fee in the call context for EndTx to credit the coinbase.
+        #[cfg(feature = "scroll")]
+        cb.call_context_lookup(
+            1.expr(),
+            Some(call_id),
+            crate::table::CallContextFieldTag::L1Fee,
+            from_bytes::expr(&this.tx_l1_fee_word.cells[..N_BYTES_U64]),
+        );
+
         this
     }
 
@@ -93,8 +115,13 @@ impl<F: Field> TxL1FeeGadget<F> {
         l1_fee: TxL1Fee,
         l1_fee_committed: TxL1Fee,
         tx_data_gas_cost: u64,
+        is_l1_msg: bool,
     ) -> Result<(), Error> {
-        let (tx_l1_fee, remainder) = l1_fee.tx_l1_fee(tx_data_gas_cost);
+        let (tx_l1_fee, remainder) = if is_l1_msg {
+            (0, 0)
+        } else {
+            l1_fee.tx_l1_fee(tx_data_gas_cost)
+        };
         self.tx_l1_fee_word
             .assign(region, offset, Some(tx_l1_fee.to_le_bytes()))?;
         self.remainder_word
@@ -125,17 +152,18 @@ impl<F: Field> TxL1FeeGadget<F> {
     }
 
     pub(crate) fn rw_delta(&self) -> Expression<F> {
-        // L1 base fee Read
-        // L1 fee overhead Read
-        // L1 fee scalar Read
-        3.expr()
+        Self::RW_DELTA.expr()
     }
 
     pub(crate) fn tx_l1_fee(&self) -> &U64Word<F> {
         &self.tx_l1_fee_word
     }
 
-    fn raw_construct(cb: &mut EVMConstraintBuilder<F>, tx_data_gas_cost: Expression<F>) -> Self {
+    fn raw_construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        tx_data_gas_cost: Expression<F>,
+        is_l1_msg: Expression<F>,
+    ) -> Self {
         let tx_l1_fee_word = cb.query_word_rlc();
         let remainder_word = cb.query_word_rlc();
 
@@ -154,11 +182,17 @@ impl<F: Field> TxL1FeeGadget<F> {
 
         // <https://github.com/scroll-tech/go-ethereum/blob/49192260a177f1b63fc5ea3b872fb904f396260c/rollup/fees/rollup_fee.go#L118>
         let tx_l1_gas = tx_data_gas_cost + 1088.expr() + fee_overhead;
-        cb.require_equal(
-            "fee_scalar * base_fee * tx_l1_gas == tx_l1_fee * 10e9 + remainder",
-            fee_scalar * base_fee * tx_l1_gas,
-            tx_l1_fee * TX_L1_FEE_PRECISION.expr() + remainder,
-        );
+        cb.condition(not::expr(is_l1_msg.expr()), |cb| {
+            cb.require_equal(
+                "fee_scalar * base_fee * tx_l1_gas == tx_l1_fee * 10e9 + remainder",
+                fee_scalar * base_fee * tx_l1_gas,
+                tx_l1_fee.expr() * TX_L1_FEE_PRECISION.expr() + remainder,
+            );
+        });
+        // L1 message txs are already paid for on L1.
+        cb.condition(is_l1_msg, |cb| {
+            cb.require_zero("tx_l1_fee == 0 for L1 message tx", tx_l1_fee);
+        });
 
         let base_fee_committed = cb.query_cell_phase2();
         let fee_overhead_committed = cb.query_cell_phase2();
@@ -236,7 +270,7 @@ mod tests {
             let tx_data_gas_cost = cb.query_cell();
             let expected_tx_l1_fee = cb.query_cell();
 
-            let gadget = TxL1FeeGadget::<F>::raw_construct(cb, tx_data_gas_cost.expr());
+            let gadget = TxL1FeeGadget::<F>::raw_construct(cb, tx_data_gas_cost.expr(), 0.expr());
 
             cb.require_equal(
                 "tx_l1_fee must be correct",
@@ -269,6 +303,7 @@ mod tests {
                 l1_fee,
                 TxL1Fee::default(),
                 tx_data_gas_cost.as_u64(),
+                false,
             )?;
             self.tx_data_gas_cost.assign(
                 region,
@@ -6,12 +6,13 @@ use crate::{
         util::{Cell, RandomLinearCombination, Word},
     },
     table::{
-        AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag,
-        TxLogFieldTag, TxReceiptFieldTag,
+        AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, PrecompileIo, ReceiptFieldTag,
+        RwTableTag, TxContextFieldTag, TxLogFieldTag, TxReceiptFieldTag,
     },
     util::{build_tx_log_expression, Challenges, Expr},
 };
 use bus_mapping::{
+    precompile::PrecompileCalls,
     state_db::EMPTY_CODE_HASH_LE,
     util::{KECCAK_CODE_HASH_ZERO, POSEIDON_CODE_HASH_ZERO},
 };
@@ -1067,6 +1068,58 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Transient Storage
+
+    pub(crate) fn transient_storage_read(
+        &mut self,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        tx_id: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "transient_storage_read",
+            false.expr(),
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn transient_storage_write(
+        &mut self,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        tx_id: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TransientStorage write",
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
     // Call context
 
     pub(crate) fn call_context(
@@ -1326,6 +1379,31 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         self.rw_counter_offset = self.rw_counter_offset.clone() + self.condition_expr() * rwc_inc;
     }
 
+    /// Lookup the RLC of the `length` bytes `precompile`, called as `call_id`
+    /// by `caller_id`, received as input or returned as output, from the first
+    /// row of its copy event.
+    pub(crate) fn precompile_rlc_lookup(
+        &mut self,
+        io: PrecompileIo,
+        precompile: PrecompileCalls,
+        caller_id: Expression<F>,
+        call_id: Expression<F>,
+        length: Expression<F>,
+        rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "precompile rlc lookup",
+            Lookup::PrecompileRlc {
+                io,
+                precompile,
+                caller_id,
+                call_id,
+                length,
+                rlc,
+            },
+        );
+    }
+
     // Exponentiation Table
 
     #[allow(clippy::too_many_arguments)]
@@ -1349,6 +1427,88 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Receipt Table
+
+    pub(crate) fn receipt_table_lookup(
+        &mut self,
+        tx_id: Expression<F>,
+        tag: ReceiptFieldTag,
+        value: Expression<F>,
+    ) {
+        self.add_lookup(
+            "receipt lookup",
+            Lookup::Receipt {
+                tx_id,
+                field_tag: tag.expr(),
+                value,
+            },
+        );
+    }
+
+    // Logs Bloom Table
+
+    pub(crate) fn logs_bloom_lookup(
+        &mut self,
+        tx_id: Expression<F>,
+        log_id: Expression<F>,
+        field_tag: TxLogFieldTag,
+        index: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.add_lookup(
+            "logs bloom lookup",
+            Lookup::LogsBloom {
+                tx_id,
+                log_id,
+                field_tag: field_tag.expr(),
+                index,
+                value,
+            },
+        );
+    }
+
+    // Sig Table
+
+    pub(crate) fn sig_table_lookup(
+        &mut self,
+        msg_hash_rlc: Expression<F>,
+        sig_r_rlc: Expression<F>,
+        sig_s_rlc: Expression<F>,
+        recovered_addr_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "sig table lookup",
+            Lookup::SigTable {
+                msg_hash_rlc,
+                sig_r_rlc,
+                sig_s_rlc,
+                recovered_addr_rlc,
+            },
+        );
+    }
+
+    // P256 Verify Table
+
+    pub(crate) fn p256_verify_table_lookup(
+        &mut self,
+        msg_hash_rlc: Expression<F>,
+        sig_r_rlc: Expression<F>,
+        sig_s_rlc: Expression<F>,
+        pk_x_rlc: Expression<F>,
+        pk_y_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "p256 verify table lookup",
+            Lookup::P256VerifyTable {
+                msg_hash_rlc,
+                sig_r_rlc,
+                sig_s_rlc,
+                pk_x_rlc,
+                pk_y_rlc,
+            },
+        );
+    }
+
     // Keccak Table
 
     pub(crate) fn keccak_table_lookup(
@@ -103,6 +103,21 @@ impl Instrument {
                     CellType::Lookup(Table::Exp) => {
                         report.exp_table = data_entry;
                     }
+                    CellType::Lookup(Table::Receipt) => {
+                        report.receipt_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Sig) => {
+                        report.sig_table = data_entry;
+                    }
+                    CellType::Lookup(Table::P256Verify) => {
+                        report.p256_verify_table = data_entry;
+                    }
+                    CellType::Lookup(Table::PrecompileRlc) => {
+                        report.precompile_rlc = data_entry;
+                    }
+                    CellType::Lookup(Table::LogsBloom) => {
+                        report.logs_bloom_table = data_entry;
+                    }
                 }
             }
             report_collection.push(report);
@@ -129,6 +144,11 @@ pub(crate) struct ExecStateReport {
     pub(crate) copy_table: StateReportRow,
     pub(crate) keccak_table: StateReportRow,
     pub(crate) exp_table: StateReportRow,
+    pub(crate) receipt_table: StateReportRow,
+    pub(crate) sig_table: StateReportRow,
+    pub(crate) p256_verify_table: StateReportRow,
+    pub(crate) precompile_rlc: StateReportRow,
+    pub(crate) logs_bloom_table: StateReportRow,
 }
 
 impl From<ExecutionState> for ExecStateReport {
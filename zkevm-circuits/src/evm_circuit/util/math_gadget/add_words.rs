@@ -2,9 +2,9 @@ use crate::{
     evm_circuit::util::{
         self,
         constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-        from_bytes, pow_of_two_expr, split_u256, sum, CachedRegion, Cell,
+        from_bytes, pow_of_two_expr, sum, CachedRegion, Cell,
     },
-    util::Expr,
+    util::{word::WordLoHi, Expr},
 };
 use eth_types::{Field, ToLittleEndian, ToScalar, Word};
 use halo2_proofs::{circuit::Value, plonk::Error};
@@ -96,8 +96,11 @@ impl<F: Field, const N_ADDENDS: usize, const CHECK_OVERFLOW: bool>
         }
         self.sum.assign(region, offset, Some(sum.to_le_bytes()))?;
 
-        let (addends_lo, addends_hi): (Vec<_>, Vec<_>) = addends.iter().map(split_u256).unzip();
-        let (sum_lo, sum_hi) = split_u256(&sum);
+        let (addends_lo, addends_hi): (Vec<_>, Vec<_>) = addends
+            .iter()
+            .map(|addend| WordLoHi::from(*addend).to_lo_hi())
+            .unzip();
+        let (sum_lo, sum_hi) = WordLoHi::from(sum).to_lo_hi();
 
         let sum_of_addends_lo = addends_lo
             .into_iter()
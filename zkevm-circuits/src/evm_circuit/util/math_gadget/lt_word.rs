@@ -1,6 +1,8 @@
-use crate::evm_circuit::util::{
-    self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, split_u256,
-    CachedRegion,
+use crate::{
+    evm_circuit::util::{
+        self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, CachedRegion,
+    },
+    util::word::WordLoHi,
 };
 use eth_types::{Field, Word};
 use halo2_proofs::plonk::{Error, Expression};
@@ -47,20 +49,10 @@ impl<F: Field> LtWordGadget<F> {
         lhs: Word,
         rhs: Word,
     ) -> Result<(), Error> {
-        let (lhs_lo, lhs_hi) = split_u256(&lhs);
-        let (rhs_lo, rhs_hi) = split_u256(&rhs);
-        self.comparison_hi.assign(
-            region,
-            offset,
-            F::from_u128(lhs_hi.as_u128()),
-            F::from_u128(rhs_hi.as_u128()),
-        )?;
-        self.lt_lo.assign(
-            region,
-            offset,
-            F::from_u128(lhs_lo.as_u128()),
-            F::from_u128(rhs_lo.as_u128()),
-        )?;
+        let lhs = WordLoHi::<F>::from_word(lhs);
+        let rhs = WordLoHi::<F>::from_word(rhs);
+        self.comparison_hi.assign(region, offset, lhs.hi, rhs.hi)?;
+        self.lt_lo.assign(region, offset, lhs.lo, rhs.lo)?;
         Ok(())
     }
 }
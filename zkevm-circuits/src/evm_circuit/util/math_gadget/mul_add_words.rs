@@ -2,9 +2,12 @@ use crate::{
     evm_circuit::util::{
         self,
         constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-        from_bytes, pow_of_two_expr, split_u256, split_u256_limb64, CachedRegion, Cell,
+        from_bytes, pow_of_two_expr, CachedRegion, Cell,
+    },
+    util::{
+        word::{self, WordLoHi},
+        Expr,
     },
-    util::Expr,
 };
 use eth_types::{Field, ToLittleEndian, Word};
 use halo2_proofs::{
@@ -112,10 +115,10 @@ impl<F: Field> MulAddWordsGadget<F> {
     ) -> Result<(), Error> {
         let (a, b, c, d) = (words[0], words[1], words[2], words[3]);
 
-        let a_limbs = split_u256_limb64(&a);
-        let b_limbs = split_u256_limb64(&b);
-        let (c_lo, c_hi) = split_u256(&c);
-        let (d_lo, d_hi) = split_u256(&d);
+        let a_limbs = word::limbs64(a);
+        let b_limbs = word::limbs64(b);
+        let (c_lo, c_hi) = WordLoHi::from(c).to_lo_hi();
+        let (d_lo, d_hi) = WordLoHi::from(d).to_lo_hi();
 
         let t0 = a_limbs[0] * b_limbs[0];
         let t1 = a_limbs[0] * b_limbs[1] + a_limbs[1] * b_limbs[0];
@@ -2,9 +2,12 @@ use crate::{
     evm_circuit::util::{
         self,
         constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-        from_bytes, pow_of_two_expr, split_u256, split_u256_limb64, CachedRegion, Cell,
+        from_bytes, pow_of_two_expr, CachedRegion, Cell,
+    },
+    util::{
+        word::{self, WordLoHi},
+        Expr,
     },
-    util::Expr,
 };
 use eth_types::{Field, ToLittleEndian, Word};
 use halo2_proofs::{circuit::Value, plonk::Error};
@@ -144,10 +147,10 @@ impl<F: Field> MulAddWords512Gadget<F> {
     ) -> Result<(), Error> {
         let (a, b, d, e) = (words[0], words[1], words[2], words[3]);
 
-        let a_limbs = split_u256_limb64(&a);
-        let b_limbs = split_u256_limb64(&b);
-        let (d_lo, _d_hi) = split_u256(&d);
-        let (e_lo, e_hi) = split_u256(&e);
+        let a_limbs = word::limbs64(a);
+        let b_limbs = word::limbs64(b);
+        let (d_lo, _d_hi) = WordLoHi::from(d).to_lo_hi();
+        let (e_lo, e_hi) = WordLoHi::from(e).to_lo_hi();
 
         let t0 = a_limbs[0] * b_limbs[0];
         let t1 = a_limbs[0] * b_limbs[1] + a_limbs[1] * b_limbs[0];
@@ -161,7 +164,7 @@ impl<F: Field> MulAddWords512Gadget<F> {
         let t5 = a_limbs[2] * b_limbs[3] + a_limbs[3] * b_limbs[2];
 
         let (carry_0, carry_1) = if let Some(c) = addend {
-            let (c_lo, c_hi) = split_u256(&c);
+            let (c_lo, c_hi) = WordLoHi::from(c).to_lo_hi();
             let carry_0 = ((t0 + (t1 << 64) + c_lo).saturating_sub(e_lo)) >> 128;
             let carry_1 = ((t2 + (t3 << 64) + c_hi + carry_0).saturating_sub(e_hi)) >> 128;
             (carry_0, carry_1)
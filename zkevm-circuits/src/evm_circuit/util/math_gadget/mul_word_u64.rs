@@ -2,9 +2,9 @@ use crate::{
     evm_circuit::util::{
         self,
         constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-        from_bytes, pow_of_two_expr, split_u256, CachedRegion,
+        from_bytes, pow_of_two_expr, CachedRegion,
     },
-    util::Expr,
+    util::{word::WordLoHi, Expr},
 };
 use eth_types::{Field, ToLittleEndian, Word};
 use halo2_proofs::{
@@ -69,8 +69,8 @@ impl<F: Field> MulWordByU64Gadget<F> {
         self.product
             .assign(region, offset, Some(product.to_le_bytes()))?;
 
-        let (multiplicand_lo, _) = split_u256(&multiplicand);
-        let (product_lo, _) = split_u256(&product);
+        let multiplicand_lo = WordLoHi::from(multiplicand).lo;
+        let product_lo = WordLoHi::from(product).lo;
 
         let carry_lo = (multiplicand_lo * multiplier - product_lo) >> 128;
         for (cell, byte) in self.carry_lo.iter().zip(
@@ -17,7 +17,7 @@ use crate::{
 };
 use array_init::array_init;
 use eth_types::{
-    evm_types::{GasCost, MAX_EXPANDED_MEMORY_ADDRESS},
+    evm_types::{gas_utils::memory_expansion_gas_cost, GasCost, MAX_EXPANDED_MEMORY_ADDRESS},
     Field, ToLittleEndian, U256,
 };
 use halo2_proofs::{
@@ -518,25 +518,23 @@ impl<F: Field, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize>
             next_memory_word_size = max.get_lower_128() as u64;
         }
 
-        // Calculate the quad gas cost for the memory size
-        let (curr_quad_memory_cost, _) = self.curr_quad_memory_cost.assign(
+        // Assign the quad gas cost for the memory size
+        self.curr_quad_memory_cost.assign(
             region,
             offset,
             (curr_memory_word_size as u128) * (curr_memory_word_size as u128),
         )?;
-        let (next_quad_memory_cost, _) = self.next_quad_memory_cost.assign(
+        self.next_quad_memory_cost.assign(
             region,
             offset,
             (next_memory_word_size as u128) * (next_memory_word_size as u128),
         )?;
 
-        // Calculate the gas cost for the expansian
-        let memory_cost = GasCost::MEMORY_EXPANSION_LINEAR_COEFF.as_u64()
-            * (next_memory_word_size - curr_memory_word_size)
-            + (next_quad_memory_cost - curr_quad_memory_cost) as u64;
-
         // Return the new memory size and the memory expansion gas cost
-        Ok((next_memory_word_size, memory_cost))
+        Ok((
+            next_memory_word_size,
+            memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size),
+        ))
     }
 }
 
@@ -1,19 +1,26 @@
 use bus_mapping::precompile::PrecompileCalls;
 use eth_types::Field;
-use gadgets::util::Expr;
-use halo2_proofs::plonk::Expression;
+use gadgets::util::{and, Expr};
+use halo2_proofs::{circuit::Value, plonk::Expression};
 
 use crate::evm_circuit::step::ExecutionState;
 
 use super::{
     constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
     math_gadget::BinaryNumberGadget,
-    CachedRegion,
+    rlc, CachedRegion, Cell,
 };
 
 #[derive(Clone, Debug)]
 pub struct PrecompileGadget<F> {
-    address: BinaryNumberGadget<F, 4>,
+    // 9 bits so that the RIP-7212 address 0x100 is representable.
+    address: BinaryNumberGadget<F, 9>,
+    /// Whether the ECRecover call recovered an address, or the P256VERIFY
+    /// call verified the signature.
+    is_verified: Cell<F>,
+    /// RLCs of the first words of the input of ECRecover and P256VERIFY, the
+    /// last word being derived from the RLC of the whole input.
+    input_word_rlcs: [Cell<F>; 4],
 }
 
 impl<F: Field> PrecompileGadget<F> {
@@ -26,7 +33,7 @@ impl<F: Field> PrecompileGadget<F> {
         _cd_offset: Expression<F>,
         cd_length: Expression<F>,
         _rd_offset: Expression<F>,
-        _rd_length: Expression<F>,
+        rd_has_length: Expression<F>,
         precompile_return_length: Expression<F>,
         // input bytes to precompile call.
         input_bytes_rlc: Expression<F>,
@@ -36,11 +43,62 @@ impl<F: Field> PrecompileGadget<F> {
         _return_bytes_rlc: Expression<F>,
     ) -> Self {
         let address = BinaryNumberGadget::construct(cb, callee_address.expr());
+        let is_verified = cb.query_bool();
+        let input_word_rlcs = [(); 4].map(|_| cb.query_cell_phase2());
+        let [r_32, r_64, r_96, r_128] = {
+            let r_32 = cb.challenges().keccak_powers_of_randomness::<32>()[31].clone();
+            let r_64 = r_32.clone() * r_32.clone();
+            let r_96 = r_64.clone() * r_32.clone();
+            let r_128 = r_64.clone() * r_64.clone();
+            [r_32, r_64, r_96, r_128]
+        };
 
         cb.condition(address.value_equals(PrecompileCalls::ECRecover), |cb| {
             cb.constrain_next_step(ExecutionState::PrecompileEcRecover, None, |_cb| {});
         });
 
+        // The address recovered by ECRecover is looked up from the sig table,
+        // which only holds the signatures verified by the sig circuit. The
+        // recovery id `v` isn't bound to the recovered key, only required to
+        // be 27 or 28, and the failed recoveries aren't proved. The input must
+        // be the 128 bytes of the 4 words `msg_hash | v | r | s`, with a
+        // message hash lower than the secp256k1 order.
+        cb.condition(
+            and::expr([
+                address.value_equals(PrecompileCalls::ECRecover),
+                is_success.expr(),
+            ]),
+            |cb| {
+                cb.require_equal(
+                    "ECRecover returns the 32 bytes of the address iff it recovers it",
+                    precompile_return_length.expr(),
+                    is_verified.expr() * 32.expr(),
+                );
+            },
+        );
+        cb.condition(
+            and::expr([
+                address.value_equals(PrecompileCalls::ECRecover),
+                is_success.expr(),
+                is_verified.expr(),
+                rd_has_length.expr(),
+            ]),
+            |cb| {
+                let [msg_hash_rlc, sig_v, sig_r_rlc, _] =
+                    input_word_rlcs.clone().map(|cell| cell.expr());
+                cb.require_equal("ECRecover input is 128 bytes", cd_length.expr(), 128.expr());
+                cb.require_zero(
+                    "ECRecover v is 27 or 28",
+                    (sig_v.expr() - 27.expr()) * (sig_v.expr() - 28.expr()),
+                );
+                let sig_s_rlc = input_bytes_rlc.expr()
+                    - msg_hash_rlc.expr() * r_96.clone()
+                    - sig_v * r_64.clone()
+                    - sig_r_rlc.expr() * r_32.clone();
+                cb.sig_table_lookup(msg_hash_rlc, sig_r_rlc, sig_s_rlc, output_bytes_rlc.expr());
+            },
+        );
+
         cb.condition(address.value_equals(PrecompileCalls::Sha256), |cb| {
             cb.constrain_next_step(ExecutionState::PrecompileSha256, None, |_cb| {});
         });
@@ -51,16 +109,16 @@ impl<F: Field> PrecompileGadget<F> {
 
         cb.condition(address.value_equals(PrecompileCalls::Identity), |cb| {
             cb.constrain_next_step(ExecutionState::PrecompileIdentity, None, |_cb| {});
-            cb.condition(is_success, |cb| {
+            cb.condition(is_success.expr(), |cb| {
                 cb.require_equal(
                     "input and output bytes are the same",
-                    input_bytes_rlc,
-                    output_bytes_rlc,
+                    input_bytes_rlc.expr(),
+                    output_bytes_rlc.expr(),
                 );
                 cb.require_equal(
                     "input length and precompile return length are the same",
-                    cd_length,
-                    precompile_return_length,
+                    cd_length.expr(),
+                    precompile_return_length.expr(),
                 );
             });
         });
@@ -85,7 +143,60 @@ impl<F: Field> PrecompileGadget<F> {
             cb.constrain_next_step(ExecutionState::PrecompileBlake2f, None, |_cb| {});
         });
 
-        Self { address }
+        cb.condition(address.value_equals(PrecompileCalls::P256Verify), |cb| {
+            cb.constrain_next_step(ExecutionState::PrecompileP256Verify, None, |_cb| {});
+        });
+
+        // The valid signatures of P256VERIFY are looked up from the P256
+        // verify table. The invalid ones aren't proved, and the input must be
+        // the 160 bytes of the 5 words `msg_hash | r | s | x | y`.
+        cb.condition(
+            and::expr([
+                address.value_equals(PrecompileCalls::P256Verify),
+                is_success.expr(),
+            ]),
+            |cb| {
+                cb.require_equal(
+                    "P256VERIFY returns 32 bytes iff the signature is valid",
+                    precompile_return_length.expr(),
+                    is_verified.expr() * 32.expr(),
+                );
+                cb.condition(is_verified.expr(), |cb| {
+                    let [msg_hash_rlc, sig_r_rlc, sig_s_rlc, pk_x_rlc] =
+                        input_word_rlcs.clone().map(|cell| cell.expr());
+                    cb.require_equal(
+                        "P256VERIFY input is 160 bytes",
+                        cd_length.expr(),
+                        160.expr(),
+                    );
+                    let pk_y_rlc = input_bytes_rlc.expr()
+                        - msg_hash_rlc.expr() * r_128
+                        - sig_r_rlc.expr() * r_96
+                        - sig_s_rlc.expr() * r_64
+                        - pk_x_rlc.expr() * r_32;
+                    cb.p256_verify_table_lookup(
+                        msg_hash_rlc,
+                        sig_r_rlc,
+                        sig_s_rlc,
+                        pk_x_rlc,
+                        pk_y_rlc,
+                    );
+                    cb.condition(rd_has_length, |cb| {
+                        cb.require_equal(
+                            "P256VERIFY returns 1 for a valid signature",
+                            output_bytes_rlc,
+                            1.expr(),
+                        );
+                    });
+                });
+            },
+        );
+
+        Self {
+            address,
+            is_verified,
+            input_word_rlcs,
+        }
     }
 
     pub(crate) fn assign(
@@ -93,7 +204,31 @@ impl<F: Field> PrecompileGadget<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         address: PrecompileCalls,
+        input_bytes: &[u8],
+        precompile_return_length: u64,
     ) -> Result<(), halo2_proofs::plonk::Error> {
-        self.address.assign(region, offset, address)
+        self.address.assign(region, offset, address)?;
+
+        let is_verified = matches!(
+            address,
+            PrecompileCalls::ECRecover | PrecompileCalls::P256Verify
+        ) && precompile_return_length != 0;
+        self.is_verified
+            .assign(region, offset, Value::known(F::from(is_verified as u64)))?;
+        let mut input_bytes = input_bytes.to_vec();
+        input_bytes.resize(input_bytes.len().max(128), 0);
+        for (i, cell) in self.input_word_rlcs.iter().enumerate() {
+            let word_rlc = if is_verified {
+                region
+                    .challenges()
+                    .keccak_input()
+                    .map(|r| rlc::value(input_bytes[32 * i..32 * (i + 1)].iter().rev(), r))
+            } else {
+                Value::known(F::zero())
+            };
+            cell.assign(region, offset, word_rlc)?;
+        }
+
+        Ok(())
     }
 }
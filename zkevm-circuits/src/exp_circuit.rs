@@ -10,6 +10,10 @@ pub use dev::ExpCircuit as TestExpCircuit;
 
 use crate::{
     evm_circuit::util::constraint_builder::BaseConstraintBuilder,
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{ExpTable, LookupTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness,
@@ -551,3 +555,17 @@ impl<F: Field> SubCircuit<F> for ExpCircuit<F> {
         config.assign_exp_events(layouter, &self.exp_events, self.max_exp_rows)
     }
 }
+
+impl<F: Field> SuperSubCircuit<F> for ExpCircuit<F> {
+    const NAME: &'static str = "exp circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[(SharedTable::Exp, 1)];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Exp];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        _args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        ExpCircuitConfig::new(meta, *tables.exp())
+    }
+}
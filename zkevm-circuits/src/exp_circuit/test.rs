@@ -5,7 +5,7 @@ use crate::{
     util::{unusable_rows, SubCircuit},
 };
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{CircuitInputBuilder, CircuitsParams, ExpEventBuilder},
     evm::OpcodeId,
     mock::BlockData,
 };
@@ -109,6 +109,29 @@ fn exp_circuit_multiple() {
     ]);
 }
 
+#[test]
+fn exp_circuit_from_events() {
+    let events = [(3, 7), (41, 259), (2, 0), (2, 1)]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (base, exponent))| {
+            ExpEventBuilder::new(base.into(), exponent.into())
+                .identifier(i + 1)
+                .build()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let circuit = ExpCircuit::<Fr>::new(events.clone(), 1000);
+    let prover = MockProver::<Fr>::run(18, &circuit, vec![]).unwrap();
+    prover.assert_satisfied_par();
+
+    let mut events = events;
+    events[1].steps[3].d += Word::one();
+    let circuit = ExpCircuit::<Fr>::new(events, 1000);
+    let prover = MockProver::<Fr>::run(18, &circuit, vec![]).unwrap();
+    assert!(prover.verify_par().is_err());
+}
+
 #[test]
 fn variadic_size_check() {
     let k = 20;
@@ -33,6 +33,10 @@ use crate::{
         get_num_bits_per_rho_pi_lookup, get_num_bits_per_theta_c_lookup, get_num_rows_per_round,
         split, split_uniform, transform, transform_to, Part,
     },
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{KeccakTable, LookupTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness,
@@ -65,6 +69,7 @@ pub struct KeccakCircuitConfig<F> {
     normalize_6: [TableColumn; 2],
     chi_base_table: [TableColumn; 2],
     pack_table: [TableColumn; 2],
+    num_rows_per_round: usize,
     _marker: PhantomData<F>,
 }
 
@@ -74,6 +79,8 @@ pub struct KeccakCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// Challenges randomness
     pub challenges: Challenges<Expression<F>>,
+    /// Number of rows per keccak round, see [`get_num_rows_per_round`]
+    pub num_rows_per_round: usize,
 }
 
 impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
@@ -85,11 +92,12 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         Self::ConfigArgs {
             keccak_table,
             challenges,
+            num_rows_per_round,
         }: Self::ConfigArgs,
     ) -> Self {
         assert!(
-            get_num_rows_per_round() > NUM_BYTES_PER_WORD,
-            "KeccakCircuit requires KECCAK_ROWS>=9"
+            (NUM_BYTES_PER_WORD + 1..=32).contains(&num_rows_per_round),
+            "KeccakCircuit requires 9 <= KECCAK_ROWS <= 32, got {num_rows_per_round}"
         );
         let q_enable = keccak_table.q_enable;
 
@@ -112,7 +120,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         let chi_base_table = array_init::array_init(|_| meta.lookup_table_column());
         let pack_table = array_init::array_init(|_| meta.lookup_table_column());
 
-        let mut cell_manager = CellManager::new(get_num_rows_per_round());
+        let mut cell_manager = CellManager::new(num_rows_per_round);
         let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
         let mut total_lookup_counter = 0;
 
@@ -134,7 +142,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
             for j in 0..5 {
                 let cell = cell_manager.query_cell(meta);
                 s[i][j] = cell.expr();
-                s_next[i][j] = cell.at_offset(meta, get_num_rows_per_round() as i32).expr();
+                s_next[i][j] = cell.at_offset(meta, num_rows_per_round as i32).expr();
             }
         }
         // Absorb data
@@ -145,7 +153,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         let mut absorb_data_next = vec![0u64.expr(); NUM_WORDS_TO_ABSORB];
         let mut absorb_result_next = vec![0u64.expr(); NUM_WORDS_TO_ABSORB];
         for i in 0..NUM_WORDS_TO_ABSORB {
-            let rot = ((i + 1) * get_num_rows_per_round()) as i32;
+            let rot = ((i + 1) * num_rows_per_round) as i32;
             absorb_from_next[i] = absorb_from.at_offset(meta, rot).expr();
             absorb_data_next[i] = absorb_data.at_offset(meta, rot).expr();
             absorb_result_next[i] = absorb_result.at_offset(meta, rot).expr();
@@ -163,7 +171,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         // rlc.
         cell_manager.start_region();
         let mut lookup_counter = 0;
-        let part_size = get_num_bits_per_absorb_lookup();
+        let part_size = get_num_bits_per_absorb_lookup(num_rows_per_round);
         let input = absorb_from.expr() + absorb_data.expr();
         let absorb_fat = split::expr(meta, &mut cell_manager, &mut cb, input, 0, part_size);
         cell_manager.start_region();
@@ -237,7 +245,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         // that allows us to also calculate the rotated value "for free".
         cell_manager.start_region();
         let mut lookup_counter = 0;
-        let part_size_c = get_num_bits_per_theta_c_lookup();
+        let part_size_c = get_num_bits_per_theta_c_lookup(num_rows_per_round);
         let mut c_parts = Vec::new();
         for s in s.iter() {
             // Calculate c and split into parts
@@ -295,7 +303,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         // `s[j][2 * i + 3 * j) % 5] = normalize(rot(s[i][j], RHOM[i][j]))`.
         cell_manager.start_region();
         let mut lookup_counter = 0;
-        let part_size = get_num_bits_per_base_chi_lookup();
+        let part_size = get_num_bits_per_base_chi_lookup(num_rows_per_round);
         // To combine the rho/pi/chi steps we have to ensure a specific layout so
         // query those cells here first.
         // For chi we have to do `s[i][j] ^ ((~s[(i+1)%5][j]) & s[(i+2)%5][j])`. `j`
@@ -325,7 +333,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
                     if row_idx == 0 {
                         num_columns += 1;
                     }
-                    row_idx = (((row_idx as usize) + 1) % get_num_rows_per_round()) as i32;
+                    row_idx = (((row_idx as usize) + 1) % num_rows_per_round) as i32;
                 }
             }
         }
@@ -379,7 +387,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         // s[(i+2)%5][j])` five times, on each row (no selector needed).
         // This is calculated by making use of `CHI_BASE_LOOKUP_TABLE`.
         let mut lookup_counter = 0;
-        let part_size_base = get_num_bits_per_base_chi_lookup();
+        let part_size_base = get_num_bits_per_base_chi_lookup(num_rows_per_round);
         for idx in 0..num_columns {
             // First fetch the cells we wan to use
             let mut input: [Expression<F>; 5] = array_init::array_init(|_| 0.expr());
@@ -428,7 +436,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         // iota
         // Simply do the single xor on state [0][0].
         cell_manager.start_region();
-        let part_size = get_num_bits_per_absorb_lookup();
+        let part_size = get_num_bits_per_absorb_lookup(num_rows_per_round);
         let input = s[0][0].clone() + round_cst_expr.clone();
         let iota_parts = split::expr(meta, &mut cell_manager, &mut cb, input, 0, part_size);
         cell_manager.start_region();
@@ -461,7 +469,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         let squeeze_from = cell_manager.query_cell(meta);
         let mut squeeze_from_prev = vec![0u64.expr(); NUM_WORDS_TO_SQUEEZE];
         for (idx, squeeze_from_prev) in squeeze_from_prev.iter_mut().enumerate() {
-            let rot = (-(idx as i32) - 1) * get_num_rows_per_round() as i32;
+            let rot = (-(idx as i32) - 1) * num_rows_per_round as i32;
             *squeeze_from_prev = squeeze_from.at_offset(meta, rot).expr();
         }
         // Squeeze
@@ -541,7 +549,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         let mut hash_bytes = Vec::new();
         for i in 0..NUM_WORDS_TO_SQUEEZE {
             for byte in squeeze_bytes.iter() {
-                let rot = (-(i as i32) - 1) * get_num_rows_per_round() as i32;
+                let rot = (-(i as i32) - 1) * num_rows_per_round as i32;
                 hash_bytes.push(byte.cell.at_offset(meta, rot).expr());
             }
         }
@@ -602,7 +610,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         // Enforce logic for when this block is the last block for a hash
         let last_is_padding_in_block = is_paddings.last().unwrap().at_offset(
             meta,
-            -(((NUM_ROUNDS + 1 - NUM_WORDS_TO_ABSORB) * get_num_rows_per_round()) as i32),
+            -(((NUM_ROUNDS + 1 - NUM_WORDS_TO_ABSORB) * num_rows_per_round) as i32),
         );
         meta.create_gate("is final", |meta| {
             let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
@@ -620,7 +628,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
             );
             // For all the rows of a round, only the first row can have `is_final == 1`.
             cb.condition(
-                (1..get_num_rows_per_round() as i32)
+                (1..num_rows_per_round as i32)
                     .map(|i| meta.query_fixed(q_enable, Rotation(-i)))
                     .fold(0.expr(), |acc, elem| acc + elem),
                 |cb| {
@@ -639,7 +647,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         let prev_is_padding = is_paddings
             .last()
             .unwrap()
-            .at_offset(meta, -(get_num_rows_per_round() as i32));
+            .at_offset(meta, -(num_rows_per_round as i32));
         meta.create_gate("padding", |meta| {
             let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
             let q_padding = meta.query_fixed(q_padding, Rotation::cur());
@@ -729,13 +737,10 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
             let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
 
             let q_padding = meta.query_fixed(q_padding, Rotation::cur());
-            let start_new_hash_prev =
-                start_new_hash(meta, Rotation(-(get_num_rows_per_round() as i32)));
-            let length_prev =
-                meta.query_advice(length, Rotation(-(get_num_rows_per_round() as i32)));
+            let start_new_hash_prev = start_new_hash(meta, Rotation(-(num_rows_per_round as i32)));
+            let length_prev = meta.query_advice(length, Rotation(-(num_rows_per_round as i32)));
             let length = meta.query_advice(length, Rotation::cur());
-            let data_rlc_prev =
-                meta.query_advice(data_rlc, Rotation(-(get_num_rows_per_round() as i32)));
+            let data_rlc_prev = meta.query_advice(data_rlc, Rotation(-(num_rows_per_round as i32)));
             let data_rlcs: Vec<_> = (0..NUM_BYTES_PER_WORD + 1)
                 .map(|i| meta.query_advice(data_rlc, Rotation(i as i32)))
                 .collect();
@@ -832,18 +837,33 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         log::debug!("Total Lookups: {}", total_lookup_counter);
         log::debug!("Total Columns: {}", cell_manager.get_width());
         log::debug!("num unused cells: {}", cell_manager.get_num_unused_cells());
-        log::debug!("part_size absorb: {}", get_num_bits_per_absorb_lookup());
-        log::debug!("part_size theta: {}", get_num_bits_per_theta_c_lookup());
+        log::debug!(
+            "part_size absorb: {}",
+            get_num_bits_per_absorb_lookup(num_rows_per_round)
+        );
+        log::debug!(
+            "part_size theta: {}",
+            get_num_bits_per_theta_c_lookup(num_rows_per_round)
+        );
         log::debug!(
             "part_size theta c: {}",
-            get_num_bits_per_lookup(THETA_C_LOOKUP_RANGE)
+            get_num_bits_per_lookup(THETA_C_LOOKUP_RANGE, num_rows_per_round)
+        );
+        log::debug!(
+            "part_size theta t: {}",
+            get_num_bits_per_lookup(4, num_rows_per_round)
+        );
+        log::debug!(
+            "part_size rho/pi: {}",
+            get_num_bits_per_rho_pi_lookup(num_rows_per_round)
+        );
+        log::debug!(
+            "part_size chi base: {}",
+            get_num_bits_per_base_chi_lookup(num_rows_per_round)
         );
-        log::debug!("part_size theta t: {}", get_num_bits_per_lookup(4));
-        log::debug!("part_size rho/pi: {}", get_num_bits_per_rho_pi_lookup());
-        log::debug!("part_size chi base: {}", get_num_bits_per_base_chi_lookup());
         log::debug!(
             "uniform part sizes: {:?}",
-            target_part_sizes(get_num_bits_per_theta_c_lookup())
+            target_part_sizes(get_num_bits_per_theta_c_lookup(num_rows_per_round))
         );
 
         KeccakCircuitConfig {
@@ -862,6 +882,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
             normalize_6,
             chi_base_table,
             pack_table,
+            num_rows_per_round,
             _marker: PhantomData,
         }
     }
@@ -962,14 +983,33 @@ impl<F: Field> KeccakCircuitConfig<F> {
 
     /// Load the auxiliary table for keccak table.
     pub fn load_aux_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        load_normalize_table(layouter, "normalize_6", &self.normalize_6, 6u64)?;
-        load_normalize_table(layouter, "normalize_4", &self.normalize_4, 4u64)?;
-        load_normalize_table(layouter, "normalize_3", &self.normalize_3, 3u64)?;
+        let num_rows_per_round = self.num_rows_per_round;
+        load_normalize_table(
+            layouter,
+            "normalize_6",
+            &self.normalize_6,
+            6u64,
+            num_rows_per_round,
+        )?;
+        load_normalize_table(
+            layouter,
+            "normalize_4",
+            &self.normalize_4,
+            4u64,
+            num_rows_per_round,
+        )?;
+        load_normalize_table(
+            layouter,
+            "normalize_3",
+            &self.normalize_3,
+            3u64,
+            num_rows_per_round,
+        )?;
         load_lookup_table(
             layouter,
             "chi base",
             &self.chi_base_table,
-            get_num_bits_per_base_chi_lookup(),
+            get_num_bits_per_base_chi_lookup(num_rows_per_round),
             &CHI_BASE_LOOKUP_TABLE,
         )?;
         load_pack_table(layouter, &self.pack_table)
@@ -987,44 +1027,53 @@ impl<F: Field> KeccakCircuitConfig<F> {
 }
 
 /// KeccakCircuit
-#[derive(Default, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct KeccakCircuit<F: Field> {
     inputs: Vec<Vec<u8>>,
     num_rows: usize,
+    num_rows_per_round: usize,
     _marker: PhantomData<F>,
 }
 
+impl<F: Field> Default for KeccakCircuit<F> {
+    fn default() -> Self {
+        Self::new(0, vec![])
+    }
+}
+
 impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
     type Config = KeccakCircuitConfig<F>;
 
     fn unusable_rows() -> usize {
-        keccak_unusable_rows()
+        keccak_unusable_rows(get_num_rows_per_round(0))
     }
 
     /// The `block.circuits_params.keccak_padding` parmeter, when enabled, sets
     /// up the circuit to support a fixed number of permutations/keccak_f's,
     /// independently of the permutations required by `inputs`.
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        Self::new(
+        Self::new_with_rows_per_round(
             block.circuits_params.max_keccak_rows,
+            get_num_rows_per_round(block.circuits_params.keccak_rows_per_round),
             block.keccak_inputs.clone(),
         )
     }
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
+        let num_rows_per_round =
+            get_num_rows_per_round(block.circuits_params.keccak_rows_per_round);
         let aux_tables_rows = vec![
-            normalize_table_size(6),
-            normalize_table_size(4),
-            normalize_table_size(3),
-            lookup_table_size(CHI_BASE_LOOKUP_TABLE.len()),
+            normalize_table_size(6, num_rows_per_round),
+            normalize_table_size(4, num_rows_per_round),
+            normalize_table_size(3, num_rows_per_round),
+            lookup_table_size(CHI_BASE_LOOKUP_TABLE.len(), num_rows_per_round),
         ];
         (
             block
                 .keccak_inputs
                 .iter()
-                .map(|bytes| (bytes.len() as f64 / 136.0).ceil() as usize * rows_per_chunk)
+                .map(|bytes| keccak_rows(bytes.len(), num_rows_per_round))
                 .sum(),
             max(
                 block.circuits_params.max_keccak_rows,
@@ -1040,18 +1089,58 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
         challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
+        if self.num_rows_per_round != config.num_rows_per_round {
+            log::error!(
+                "keccak circuit built for {} rows per round, configured with {}",
+                self.num_rows_per_round,
+                config.num_rows_per_round
+            );
+            return Err(Error::Synthesis);
+        }
         config.load_aux_tables(layouter)?;
         let witness = self.generate_witness(*challenges);
         config.assign(layouter, witness.as_slice())
     }
 }
 
+impl<F: Field> SuperSubCircuit<F> for KeccakCircuit<F> {
+    const NAME: &'static str = "keccak circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[(SharedTable::Keccak, 1)];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Keccak];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        KeccakCircuitConfig::new(
+            meta,
+            KeccakCircuitConfigArgs {
+                keccak_table: tables.keccak().clone(),
+                challenges: args.challenges.clone(),
+                num_rows_per_round: args.keccak_rows_per_round,
+            },
+        )
+    }
+}
+
 impl<F: Field> KeccakCircuit<F> {
-    /// Creates a new circuit instance
+    /// Creates a new circuit instance with the default rows per round
     pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+        Self::new_with_rows_per_round(num_rows, get_num_rows_per_round(0), inputs)
+    }
+
+    /// Creates a new circuit instance using `num_rows_per_round` rows for each
+    /// keccak round.  It must match the rows per round of the config.
+    pub fn new_with_rows_per_round(
+        num_rows: usize,
+        num_rows_per_round: usize,
+        inputs: Vec<Vec<u8>>,
+    ) -> Self {
         KeccakCircuit {
             inputs,
             num_rows,
+            num_rows_per_round,
             _marker: PhantomData,
         }
     }
@@ -1060,7 +1149,7 @@ impl<F: Field> KeccakCircuit<F> {
     pub fn capacity(&self) -> Option<usize> {
         if self.num_rows > 0 {
             // Subtract two for unusable rows
-            Some(self.num_rows / ((NUM_ROUNDS + 1) * get_num_rows_per_round()) - 2)
+            Some(self.num_rows / ((NUM_ROUNDS + 1) * self.num_rows_per_round) - 2)
         } else {
             None
         }
@@ -1068,7 +1157,17 @@ impl<F: Field> KeccakCircuit<F> {
 
     /// Sets the witness using the data to be hashed
     pub(crate) fn generate_witness(&self, challenges: Challenges<Value<F>>) -> Vec<KeccakRow<F>> {
-        multi_keccak(self.inputs.as_slice(), challenges, self.capacity())
-            .expect("Too many inputs for given capacity")
+        multi_keccak(
+            self.inputs.as_slice(),
+            challenges,
+            self.capacity(),
+            self.num_rows_per_round,
+        )
+        .expect("Too many inputs for given capacity")
     }
 }
+
+/// Number of rows of the keccak circuit used to hash an input of `len` bytes.
+pub(crate) fn keccak_rows(len: usize, num_rows_per_round: usize) -> usize {
+    (len as f64 / 136.0).ceil() as usize * (NUM_ROUNDS + 1) * num_rows_per_round
+}
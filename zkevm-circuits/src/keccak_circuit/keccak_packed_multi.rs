@@ -12,35 +12,47 @@ use std::{env::var, vec};
 
 const MAX_DEGREE: usize = 9;
 
-pub(crate) fn get_num_rows_per_round() -> usize {
+/// Number of rows per round: `keccak_rows_per_round` when non-zero (see
+/// `CircuitsParams::keccak_rows_per_round`), otherwise the `KECCAK_ROWS` env
+/// var or the default.
+pub fn get_num_rows_per_round(keccak_rows_per_round: usize) -> usize {
+    if keccak_rows_per_round > 0 {
+        return keccak_rows_per_round;
+    }
     var("KECCAK_ROWS")
         .unwrap_or_else(|_| format!("{DEFAULT_KECCAK_ROWS}"))
         .parse()
         .expect("Cannot parse KECCAK_ROWS env var as usize")
 }
 
-pub(crate) fn keccak_unusable_rows() -> usize {
+pub(crate) fn keccak_unusable_rows(num_rows_per_round: usize) -> usize {
     const UNUSABLE_ROWS_BY_KECCAK_ROWS: [usize; 24] = [
         53, 67, 63, 59, 45, 79, 77, 75, 73, 71, 69, 67, 65, 63, 61, 59, 57, 71, 89, 107, 107, 107,
         107, 107,
     ];
-    UNUSABLE_ROWS_BY_KECCAK_ROWS[get_num_rows_per_round() - NUM_BYTES_PER_WORD - 1]
+    UNUSABLE_ROWS_BY_KECCAK_ROWS[num_rows_per_round - NUM_BYTES_PER_WORD - 1]
 }
 
-pub(crate) fn get_num_bits_per_absorb_lookup() -> usize {
-    get_num_bits_per_lookup(ABSORB_LOOKUP_RANGE)
+pub(crate) fn get_num_bits_per_absorb_lookup(num_rows_per_round: usize) -> usize {
+    get_num_bits_per_lookup(ABSORB_LOOKUP_RANGE, num_rows_per_round)
 }
 
-pub(crate) fn get_num_bits_per_theta_c_lookup() -> usize {
-    get_num_bits_per_lookup(THETA_C_LOOKUP_RANGE)
+pub(crate) fn get_num_bits_per_theta_c_lookup(num_rows_per_round: usize) -> usize {
+    get_num_bits_per_lookup(THETA_C_LOOKUP_RANGE, num_rows_per_round)
 }
 
-pub(crate) fn get_num_bits_per_rho_pi_lookup() -> usize {
-    get_num_bits_per_lookup(CHI_BASE_LOOKUP_RANGE.max(RHO_PI_LOOKUP_RANGE))
+pub(crate) fn get_num_bits_per_rho_pi_lookup(num_rows_per_round: usize) -> usize {
+    get_num_bits_per_lookup(
+        CHI_BASE_LOOKUP_RANGE.max(RHO_PI_LOOKUP_RANGE),
+        num_rows_per_round,
+    )
 }
 
-pub(crate) fn get_num_bits_per_base_chi_lookup() -> usize {
-    get_num_bits_per_lookup(CHI_BASE_LOOKUP_RANGE.max(RHO_PI_LOOKUP_RANGE))
+pub(crate) fn get_num_bits_per_base_chi_lookup(num_rows_per_round: usize) -> usize {
+    get_num_bits_per_lookup(
+        CHI_BASE_LOOKUP_RANGE.max(RHO_PI_LOOKUP_RANGE),
+        num_rows_per_round,
+    )
 }
 
 /// AbsorbData
@@ -492,9 +504,13 @@ pub(crate) mod transform_to {
     }
 }
 
-fn keccak_rows<F: Field>(bytes: &[u8], challenges: Challenges<Value<F>>) -> Vec<KeccakRow<F>> {
+fn keccak_rows<F: Field>(
+    bytes: &[u8],
+    challenges: Challenges<Value<F>>,
+    num_rows_per_round: usize,
+) -> Vec<KeccakRow<F>> {
     let mut rows = Vec::new();
-    keccak(&mut rows, bytes, challenges);
+    keccak(&mut rows, bytes, challenges, num_rows_per_round);
     rows
 }
 
@@ -502,6 +518,7 @@ pub(crate) fn keccak<F: Field>(
     rows: &mut Vec<KeccakRow<F>>,
     bytes: &[u8],
     challenges: Challenges<Value<F>>,
+    num_rows_per_round: usize,
 ) {
     let mut bits = into_bits(bytes);
     let mut s = [[F::zero(); 5]; 5];
@@ -545,7 +562,7 @@ pub(crate) fn keccak<F: Field>(
         let mut round_lengths = Vec::new();
         let mut round_data_rlcs = Vec::new();
         for round in 0..NUM_ROUNDS + 1 {
-            let mut cell_manager = CellManager::new(get_num_rows_per_round());
+            let mut cell_manager = CellManager::new(num_rows_per_round);
             let mut region = KeccakRegion::new();
 
             let mut absorb_row = AbsorbData::default();
@@ -571,7 +588,7 @@ pub(crate) fn keccak<F: Field>(
 
             // Absorb
             cell_manager.start_region();
-            let part_size = get_num_bits_per_absorb_lookup();
+            let part_size = get_num_bits_per_absorb_lookup(num_rows_per_round);
             let input = absorb_row.from + absorb_row.absorb;
             let absorb_fat = split::value(&mut cell_manager, &mut region, input, 0, part_size);
             cell_manager.start_region();
@@ -594,7 +611,7 @@ pub(crate) fn keccak<F: Field>(
                 transform::value(&mut cell_manager, &mut region, packed, false, |v| *v, true);
             cell_manager.start_region();
             let mut is_paddings = Vec::new();
-            let mut data_rlcs = vec![Value::known(F::zero()); get_num_rows_per_round()];
+            let mut data_rlcs = vec![Value::known(F::zero()); num_rows_per_round];
             for _ in input_bytes.iter() {
                 is_paddings.push(cell_manager.query_cell_value());
             }
@@ -630,7 +647,7 @@ pub(crate) fn keccak<F: Field>(
 
             if round != NUM_ROUNDS {
                 // Theta
-                let part_size = get_num_bits_per_theta_c_lookup();
+                let part_size = get_num_bits_per_theta_c_lookup(num_rows_per_round);
                 let mut bcf = Vec::new();
                 for s in &s {
                     let c = s[0] + s[1] + s[2] + s[3] + s[4];
@@ -663,7 +680,7 @@ pub(crate) fn keccak<F: Field>(
                 cell_manager.start_region();
 
                 // Rho/Pi
-                let part_size = get_num_bits_per_base_chi_lookup();
+                let part_size = get_num_bits_per_base_chi_lookup(num_rows_per_round);
                 let target_word_sizes = target_part_sizes(part_size);
                 let num_word_parts = target_word_sizes.len();
                 let mut rho_pi_chi_cells: [[[Vec<Cell<F>>; 5]; 5]; 3] =
@@ -680,7 +697,7 @@ pub(crate) fn keccak<F: Field>(
                                 rho_pi_chi_cells[p][i][j]
                                     .push(cell_manager.query_cell_value_at_row(row_idx as i32));
                             }
-                            row_idx = (row_idx + 1) % get_num_rows_per_round();
+                            row_idx = (row_idx + 1) % num_rows_per_round;
                         }
                     }
                 }
@@ -711,7 +728,7 @@ pub(crate) fn keccak<F: Field>(
                 cell_manager.start_region();
 
                 // Chi
-                let part_size_base = get_num_bits_per_base_chi_lookup();
+                let part_size_base = get_num_bits_per_base_chi_lookup(num_rows_per_round);
                 let three_packed = pack::<F>(&vec![3u8; part_size_base]);
                 let mut os = [[F::zero(); 5]; 5];
                 for j in 0..5 {
@@ -743,7 +760,7 @@ pub(crate) fn keccak<F: Field>(
                 cell_manager.start_region();
 
                 // iota
-                let part_size = get_num_bits_per_absorb_lookup();
+                let part_size = get_num_bits_per_absorb_lookup(num_rows_per_round);
                 let input = s[0][0] + pack_u64::<F>(ROUND_CST[round]);
                 let iota_parts =
                     split::value::<F>(&mut cell_manager, &mut region, input, 0, part_size);
@@ -801,7 +818,7 @@ pub(crate) fn keccak<F: Field>(
 
         for round in 0..NUM_ROUNDS + 1 {
             let round_cst = pack_u64(ROUND_CST[round]);
-            for row_idx in 0..get_num_rows_per_round() {
+            for row_idx in 0..num_rows_per_round {
                 rows.push(KeccakRow {
                     q_enable: row_idx == 0,
                     q_round: row_idx == 0 && round < NUM_ROUNDS,
@@ -843,15 +860,16 @@ pub fn multi_keccak<F: Field>(
     bytes: &[Vec<u8>],
     challenges: Challenges<Value<F>>,
     capacity: Option<usize>,
+    num_rows_per_round: usize,
 ) -> Result<Vec<KeccakRow<F>>, Error> {
     log::info!("multi_keccak assign with capacity: {:?}", capacity);
     let mut rows: Vec<KeccakRow<F>> = Vec::new();
     if let Some(capacity) = capacity {
-        rows.reserve((1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round());
+        rows.reserve((1 + capacity * (NUM_ROUNDS + 1)) * num_rows_per_round);
     }
     // Dummy first row so that the initial data is absorbed
     // The initial data doesn't really matter, `is_final` just needs to be disabled.
-    for idx in 0..get_num_rows_per_round() {
+    for idx in 0..num_rows_per_round {
         rows.push(KeccakRow {
             q_enable: idx == 0,
             q_round: false,
@@ -883,22 +901,22 @@ pub fn multi_keccak<F: Field>(
     // TODO: optimize the `extend` using Iter?
     let real_rows: Vec<_> = bytes
         .par_iter()
-        .flat_map_iter(|bytes| keccak_rows(bytes, challenges))
+        .flat_map_iter(|bytes| keccak_rows(bytes, challenges, num_rows_per_round))
         .collect();
     rows.extend(real_rows.into_iter());
     debug!("keccak rows len without padding: {}", rows.len());
     if let Some(capacity) = capacity {
         let padding_rows = {
             let mut rows = Vec::new();
-            keccak(&mut rows, &[], challenges);
+            keccak(&mut rows, &[], challenges, num_rows_per_round);
             rows
         };
         // Pad with no data hashes to the expected capacity
-        while rows.len() < (1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round() {
+        while rows.len() < (1 + capacity * (NUM_ROUNDS + 1)) * num_rows_per_round {
             rows.extend(padding_rows.clone());
         }
         // Check that we are not over capacity
-        if rows.len() > (1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round() {
+        if rows.len() > (1 + capacity * (NUM_ROUNDS + 1)) * num_rows_per_round {
             return Err(Error::BoundsFailure);
         }
     }
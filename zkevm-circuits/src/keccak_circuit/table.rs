@@ -13,19 +13,27 @@ pub(crate) fn load_normalize_table<F: Field>(
     name: &str,
     tables: &[TableColumn; 2],
     range: u64,
+    num_rows_per_round: usize,
 ) -> Result<(), Error> {
     let log_height = get_degree();
-    load_normalize_table_impl(layouter, name, tables, range, log_height)
+    load_normalize_table_impl(
+        layouter,
+        name,
+        tables,
+        range,
+        log_height,
+        num_rows_per_round,
+    )
 }
 
-pub(crate) fn normalize_table_size(range: usize) -> usize {
+pub(crate) fn normalize_table_size(range: usize, num_rows_per_round: usize) -> usize {
     let log_height = get_degree();
-    let part_size = get_num_bits_per_lookup_impl(range, log_height);
+    let part_size = get_num_bits_per_lookup_impl(range, log_height, num_rows_per_round);
     (0..part_size).fold(1usize, |acc, _| acc * range)
 }
 
-pub(crate) fn lookup_table_size(lookup_table_len: usize) -> usize {
-    let part_size = get_num_bits_per_base_chi_lookup();
+pub(crate) fn lookup_table_size(lookup_table_len: usize, num_rows_per_round: usize) -> usize {
+    let part_size = get_num_bits_per_base_chi_lookup(num_rows_per_round);
     (0..part_size).fold(1usize, |acc, _| acc * lookup_table_len)
 }
 
@@ -36,9 +44,10 @@ fn load_normalize_table_impl<F: Field>(
     tables: &[TableColumn; 2],
     range: u64,
     log_height: usize,
+    num_rows_per_round: usize,
 ) -> Result<(), Error> {
     assert!(range <= BIT_SIZE as u64);
-    let part_size = get_num_bits_per_lookup_impl(range as usize, log_height);
+    let part_size = get_num_bits_per_lookup_impl(range as usize, log_height, num_rows_per_round);
     layouter.assign_table(
         || format!("{} table", name),
         |mut table| {
@@ -250,7 +259,8 @@ mod tests {
 
     impl TableTestCircuit {
         fn expected_num_entries(&self) -> usize {
-            let num_bits = get_num_bits_per_lookup_impl(self.range, self.log_height);
+            let num_bits =
+                get_num_bits_per_lookup_impl(self.range, self.log_height, DEFAULT_KECCAK_ROWS);
             self.range.pow(num_bits as u32)
         }
     }
@@ -279,9 +289,11 @@ mod tests {
                     &config,
                     self.range as u64,
                     self.log_height,
+                    DEFAULT_KECCAK_ROWS,
                 )?;
             } else {
-                let num_bits = get_num_bits_per_lookup_impl(self.range, self.log_height);
+                let num_bits =
+                    get_num_bits_per_lookup_impl(self.range, self.log_height, DEFAULT_KECCAK_ROWS);
                 load_lookup_table(
                     &mut layouter,
                     "chi base",
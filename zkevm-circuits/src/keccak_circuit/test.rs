@@ -56,6 +56,71 @@ fn packed_multi_keccak_simple() {
     verify::<Fr>(k, inputs, true);
 }
 
+/// Keccak circuit configured with `ROWS` rows per round, independently of the
+/// `KECCAK_ROWS` env var.
+#[derive(Clone)]
+struct KeccakRowsCircuit<const ROWS: usize>(KeccakCircuit<Fr>);
+
+impl<const ROWS: usize> Circuit<Fr> for KeccakRowsCircuit<ROWS> {
+    type Config = (KeccakCircuitConfig<Fr>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self(KeccakCircuit::new_with_rows_per_round(0, ROWS, vec![]))
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+        let config = {
+            let challenges = challenges.exprs(meta);
+            KeccakCircuitConfig::new(
+                meta,
+                KeccakCircuitConfigArgs {
+                    keccak_table,
+                    challenges,
+                    num_rows_per_round: ROWS,
+                },
+            )
+        };
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+        self.0.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
+
+#[test]
+fn keccak_circuit_rows_per_round_param() {
+    let k = 19;
+    let inputs = vec![(0u8..200).collect::<Vec<_>>()];
+    let circuit = KeccakRowsCircuit::<25>(KeccakCircuit::new_with_rows_per_round(
+        2usize.pow(k),
+        25,
+        inputs,
+    ));
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+    prover.assert_satisfied_par();
+}
+
+#[test]
+fn keccak_circuit_rows_per_round_mismatch() {
+    let k = 19;
+    let inputs = vec![(0u8..200).collect::<Vec<_>>()];
+    let circuit = KeccakRowsCircuit::<25>(KeccakCircuit::new_with_rows_per_round(
+        2usize.pow(k),
+        DEFAULT_KECCAK_ROWS,
+        inputs,
+    ));
+    assert!(MockProver::<Fr>::run(k, &circuit, vec![]).is_err());
+}
+
 #[test]
 fn variadic_size_check() {
     let k = 19;
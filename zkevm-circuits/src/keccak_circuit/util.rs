@@ -217,14 +217,18 @@ pub(crate) fn get_degree() -> usize {
 
 /// Returns how many bits we can process in a single lookup given the range of
 /// values the bit can have and the height of the circuit (via KECCAK_DEGREE).
-pub fn get_num_bits_per_lookup(range: usize) -> usize {
+pub fn get_num_bits_per_lookup(range: usize, num_rows_per_round: usize) -> usize {
     let log_height = get_degree();
-    get_num_bits_per_lookup_impl(range, log_height)
+    get_num_bits_per_lookup_impl(range, log_height, num_rows_per_round)
 }
 
 // Implementation of the above without environment dependency.
-pub(crate) fn get_num_bits_per_lookup_impl(range: usize, log_height: usize) -> usize {
-    let num_unusable_rows = keccak_unusable_rows();
+pub(crate) fn get_num_bits_per_lookup_impl(
+    range: usize,
+    log_height: usize,
+    num_rows_per_round: usize,
+) -> usize {
+    let num_unusable_rows = keccak_unusable_rows(num_rows_per_round);
     let height = 2usize.pow(log_height as u32);
     let mut num_bits = 1;
     while range.pow(num_bits + 1) + num_unusable_rows <= height {
@@ -323,11 +327,14 @@ mod tests {
     #[test]
     fn num_bits_per_lookup() {
         // Typical values.
-        assert_eq!(get_num_bits_per_lookup_impl(3, 19), 11);
-        assert_eq!(get_num_bits_per_lookup_impl(4, 19), 9);
-        assert_eq!(get_num_bits_per_lookup_impl(5, 19), 8);
-        assert_eq!(get_num_bits_per_lookup_impl(6, 19), 7);
+        assert_eq!(get_num_bits_per_lookup_impl(3, 19, DEFAULT_KECCAK_ROWS), 11);
+        assert_eq!(get_num_bits_per_lookup_impl(4, 19, DEFAULT_KECCAK_ROWS), 9);
+        assert_eq!(get_num_bits_per_lookup_impl(5, 19, DEFAULT_KECCAK_ROWS), 8);
+        assert_eq!(get_num_bits_per_lookup_impl(6, 19, DEFAULT_KECCAK_ROWS), 7);
         // The largest possible value does not overflow u64.
-        assert_eq!(get_num_bits_per_lookup_impl(3, 32) * BIT_COUNT, 60);
+        assert_eq!(
+            get_num_bits_per_lookup_impl(3, 32, DEFAULT_KECCAK_ROWS) * BIT_COUNT,
+            60
+        );
     }
 }
@@ -20,16 +20,21 @@
 #![deny(clippy::debug_assert_with_mut_call)]
 
 pub mod bytecode_circuit;
+pub mod circuit_cost;
 pub mod copy_circuit;
 pub mod evm_circuit;
 pub mod exp_circuit;
 pub mod keccak_circuit;
+pub mod mem_profile;
 pub mod mpt_circuit;
 pub mod pi_circuit;
 pub mod poseidon_circuit;
+pub mod proof_envelope;
 pub mod rlp_circuit_fsm;
+pub mod row_estimator;
 // we don't use this for aggregation
 //pub mod root_circuit;
+pub mod sig_circuit;
 pub mod state_circuit;
 pub mod super_circuit;
 pub mod table;
@@ -40,6 +45,9 @@ pub mod test_util;
 #[cfg(any(feature = "test", test))]
 mod stats;
 
+#[cfg(any(feature = "test", test))]
+pub mod test_vectors;
+
 pub mod tx_circuit;
 pub mod util;
 pub mod witness;
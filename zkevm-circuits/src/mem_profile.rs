@@ -0,0 +1,214 @@
+//! Peak heap usage of each phase of the proving pipeline, to find out which
+//! phases the streaming and lazy redesigns of the witness should target.
+//!
+//! The phases are delimited with [`enter`], which is a no-op unless the
+//! `mem-profile` feature is enabled. With the feature, a binary registers
+//! `ProfilingAllocator` as its global allocator, and prints [`report`] at the
+//! end of its run:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: ProfilingAllocator = ProfilingAllocator;
+//!
+//! let block = {
+//!     let _phase = mem_profile::enter(Phase::BusMapping);
+//!     builder.handle_block(&eth_block, &geth_traces)?;
+//!     block_convert(&builder.block, &builder.code_db)?
+//! };
+//! eprintln!("{}", mem_profile::report());
+//! ```
+//!
+//! The heap usage is counted for the whole process, so phases running
+//! concurrently are attributed each other's allocations.
+
+use std::fmt;
+#[cfg(feature = "mem-profile")]
+use std::sync::{atomic::Ordering, Mutex};
+
+/// Phase of the proving pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Fetching the block and tracing its txs
+    Tracing,
+    /// Handling the traces with the circuit input builder, and converting
+    /// them into the witness block
+    BusMapping,
+    /// Building the circuits from the witness, and generating the keys, which
+    /// assigns the fixed columns
+    Assignment,
+    /// Assigning the advice columns and computing the proof. halo2 has no
+    /// hooks in its prover, so its FFTs and MSMs are reported together.
+    Proving,
+}
+
+/// Heap usage during one phase, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseUsage {
+    /// Profiled phase
+    pub phase: Phase,
+    /// Heap allocated when the phase started
+    pub start: usize,
+    /// Highest heap allocated during the phase
+    pub peak: usize,
+    /// Heap allocated when the phase ended
+    pub end: usize,
+}
+
+/// Heap usage of the phases, in the order they ended.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemReport(pub Vec<PhaseUsage>);
+
+impl fmt::Display for MemReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MIB: f64 = (1 << 20) as f64;
+        writeln!(
+            f,
+            "{:<12} {:>12} {:>12} {:>12}",
+            "phase", "start (MiB)", "peak (MiB)", "end (MiB)"
+        )?;
+        for usage in &self.0 {
+            writeln!(
+                f,
+                "{:<12} {:>12.1} {:>12.1} {:>12.1}",
+                format!("{:?}", usage.phase),
+                usage.start as f64 / MIB,
+                usage.peak as f64 / MIB,
+                usage.end as f64 / MIB
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Profiles its phase until it is dropped.
+#[must_use = "the phase ends when the guard is dropped"]
+pub struct PhaseGuard {
+    #[cfg(feature = "mem-profile")]
+    phase: Phase,
+    #[cfg(feature = "mem-profile")]
+    start: usize,
+    #[cfg(feature = "mem-profile")]
+    outer_peak: usize,
+}
+
+/// Start profiling `phase`, until the returned guard is dropped. Phases can
+/// be nested, the peak of the outer phase includes the peak of the inner one.
+pub fn enter(phase: Phase) -> PhaseGuard {
+    #[cfg(feature = "mem-profile")]
+    {
+        let start = allocator::CURRENT.load(Ordering::Relaxed);
+        let outer_peak = allocator::PEAK.swap(start, Ordering::Relaxed);
+        PhaseGuard {
+            phase,
+            start,
+            outer_peak,
+        }
+    }
+    #[cfg(not(feature = "mem-profile"))]
+    {
+        let _ = phase;
+        PhaseGuard {}
+    }
+}
+
+#[cfg(feature = "mem-profile")]
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        let peak = allocator::PEAK.fetch_max(self.outer_peak, Ordering::Relaxed);
+        let usage = PhaseUsage {
+            phase: self.phase,
+            start: self.start,
+            peak,
+            end: allocator::CURRENT.load(Ordering::Relaxed),
+        };
+        REPORT
+            .lock()
+            .expect("the report lock is never poisoned")
+            .push(usage);
+    }
+}
+
+#[cfg(feature = "mem-profile")]
+static REPORT: Mutex<Vec<PhaseUsage>> = Mutex::new(Vec::new());
+
+/// Heap usage of the phases profiled so far. It is empty without the
+/// `mem-profile` feature, and all zeros if `ProfilingAllocator` isn't the
+/// global allocator.
+pub fn report() -> MemReport {
+    #[cfg(feature = "mem-profile")]
+    {
+        MemReport(
+            REPORT
+                .lock()
+                .expect("the report lock is never poisoned")
+                .clone(),
+        )
+    }
+    #[cfg(not(feature = "mem-profile"))]
+    MemReport::default()
+}
+
+#[cfg(feature = "mem-profile")]
+pub use allocator::ProfilingAllocator;
+
+#[cfg(feature = "mem-profile")]
+#[allow(unsafe_code)] // implementing GlobalAlloc is unsafe
+mod allocator {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// Bytes allocated on the heap
+    pub(super) static CURRENT: AtomicUsize = AtomicUsize::new(0);
+    /// Highest value of `CURRENT` since the start of the current phase
+    pub(super) static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    fn grow(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    /// Wrapper of the system allocator counting the heap usage.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct ProfilingAllocator;
+
+    unsafe impl GlobalAlloc for ProfilingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            shrink(layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                if new_size > layout.size() {
+                    grow(new_size - layout.size());
+                } else {
+                    shrink(layout.size() - new_size);
+                }
+            }
+            new_ptr
+        }
+    }
+}
@@ -1,4 +1,9 @@
 //! wrapping of mpt-circuit
+#[cfg(any(feature = "test", test))]
+use crate::super_circuit::{
+    registry::{SharedTable, SharedTables, SuperSubCircuit},
+    SuperCircuitConfigArgs,
+};
 use crate::{
     table::{MptTable, PoseidonTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
@@ -133,6 +138,33 @@ impl<F: Field + Hashable> SubCircuit<F> for MptCircuit<F> {
     }
 }
 
+#[cfg(any(feature = "test", test))]
+impl<F: Field + Hashable> SuperSubCircuit<F> for MptCircuit<F> {
+    const NAME: &'static str = "zktrie circuit";
+    const TABLES: &'static [(SharedTable, u32)] =
+        &[(SharedTable::Poseidon, 1), (SharedTable::Mpt, 1)];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        MptCircuitConfig::new(
+            meta,
+            MptCircuitConfigArgs {
+                poseidon_table: *tables.poseidon(),
+                mpt_table: *tables.mpt(),
+                challenges: args.challenges.clone(),
+            },
+        )
+    }
+
+    // The rows of the mpt circuit are not counted in the super circuit yet.
+    fn min_num_rows(_block: &witness::Block<F>) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
 #[cfg(any(feature = "test", test))]
 impl<F: Field + Hashable> Circuit<F> for MptCircuit<F> {
     type Config = (MptCircuitConfig, Challenges);
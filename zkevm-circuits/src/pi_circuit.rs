@@ -1,20 +1,26 @@
 //! Public Input Circuit implementation
 
+mod logs_bloom;
+
 use std::{iter, marker::PhantomData};
 
 use crate::{
     evm_circuit::util::constraint_builder::ConstrainBuilderCommon,
-    table::{BlockContextFieldTag, KeccakTable},
+    table::{
+        BlockContextFieldTag, KeccakTable, LogsBloomTable, ReceiptFieldTag, ReceiptTable, RwTable,
+        RwTableTag,
+    },
 };
 use bus_mapping::circuit_input_builder::get_dummy_tx_hash;
-use eth_types::{Address, Field, Hash, ToBigEndian, Word, H256};
-use ethers_core::utils::keccak256;
+use eth_types::{
+    evm_types::gas_utils::MIN_BLOB_BASE_FEE, Address, Field, Hash, ToBigEndian, Word, H256,
+};
 use halo2_proofs::plonk::{Assigned, Expression, Fixed, Instance};
 // Address, BigEndianHash, Field, ToBigEndian, ToLittleEndian, ToScalar, Word, H256,
 
 use crate::{
     table::{BlockTable, LookupTable, TxTable},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{fast_mock, Challenges, SubCircuit, SubCircuitConfig},
 };
 #[cfg(feature = "onephase")]
 use halo2_proofs::plonk::FirstPhase as SecondPhase;
@@ -24,11 +30,16 @@ use halo2_proofs::plonk::SecondPhase;
 #[cfg(feature = "reject-eip2718")]
 use crate::tx_circuit::{TX_HASH_OFFSET, TX_LEN};
 use crate::{
-    evm_circuit::{util::constraint_builder::BaseConstraintBuilder, EvmCircuitExports},
+    evm_circuit::{util::constraint_builder::BaseConstraintBuilder, EvmCircuit, EvmCircuitExports},
     state_circuit::StateCircuitExports,
-    witness::{self, Block, BlockContext, BlockContexts, Transaction},
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SubCircuitRegistry, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
+    witness::{self, Block, BlockContext, BlockContexts, Receipt, Rw, Transaction},
 };
 use bus_mapping::util::read_env_var;
+use ethers_core::types::Bloom;
 use gadgets::util::{not, select, Expr};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
@@ -37,6 +48,8 @@ use halo2_proofs::{
 };
 use once_cell::sync::Lazy;
 
+#[cfg(feature = "cancun")]
+use crate::table::BlockContextFieldTag::BlobBaseFee;
 use crate::table::BlockContextFieldTag::{
     BaseFee, BlockHash, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumTxs, Number,
     Timestamp,
@@ -45,21 +58,35 @@ use gadgets::binary_number::{BinaryNumberChip, BinaryNumberConfig};
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
 use itertools::Itertools;
+use logs_bloom::{
+    LogItemWithBlock, LogsBloomConfig, BYTE_BITS_TABLE_ROWS, LOGS_BLOOM_BYTES_NUM, LOG_ITEM_ROWS,
+};
+use strum::EnumCount;
 
 /// Fixed by the spec
+#[cfg(not(feature = "cancun"))]
 const BLOCK_LEN: usize = 10;
+#[cfg(feature = "cancun")]
+const BLOCK_LEN: usize = 11;
 const NUM_HISTORY_HASHES: usize = 1;
 const BYTE_POW_BASE: u64 = 256;
-const BLOCK_HEADER_BYTES_NUM: usize = 124;
+// block_hash || parent_hash || number || timestamp || base_fee || gas_limit ||
+// num_txs || num_l1_msgs || receipts_root || logs_bloom
+const BLOCK_HEADER_BYTES_NUM: usize = 124 + KECCAK_DIGEST_SIZE + LOGS_BLOOM_BYTES_NUM;
 // chain_id || coinbase || difficulty
 const BLOCK_HEADER_CONST_BYTES_NUM: usize = 84;
 const KECCAK_DIGEST_SIZE: usize = 32;
+// status || keccak(revert data)
+const TX_RESULT_BYTES_NUM: usize = 33;
 const RPI_CELL_IDX: usize = 0;
 const RPI_RLC_ACC_CELL_IDX: usize = 1;
 const ZERO_BYTE_GAS_COST: u64 = 4;
 const NONZERO_BYTE_GAS_COST: u64 = 16;
 
+#[cfg(not(feature = "cancun"))]
 const PARENT_HASH_OFFSET: usize = 9;
+#[cfg(feature = "cancun")]
+const PARENT_HASH_OFFSET: usize = 10;
 const BLOCK_NUM_OFFSET: usize = 2;
 const TIMESTAMP_OFFSET: usize = 1;
 const BASE_FEE_OFFSET: usize = 5;
@@ -103,7 +130,7 @@ impl Default for PublicData {
 
 impl PublicData {
     /// Compute the raw_public_inputs bytes from the verifier's perspective.
-    fn raw_public_input_bytes(&self, max_txs: usize) -> Vec<u8> {
+    pub(crate) fn raw_public_input_bytes(&self, max_txs: usize) -> Vec<u8> {
         let dummy_tx_hash = get_dummy_tx_hash(self.chain_id.as_u64());
         let withdraw_trie_root = self.withdraw_trie_root;
 
@@ -153,6 +180,8 @@ impl PublicData {
                     .chain(block.gas_limit.to_be_bytes())
                     .chain(num_txs.to_be_bytes())
                     .chain(num_l1_msgs.to_be_bytes())
+                    .chain(block.eth_block.receipts_root.to_fixed_bytes())
+                    .chain(self.logs_bloom(*block_num))
             }))
             // Tx Hashes
             .chain(
@@ -165,20 +194,63 @@ impl PublicData {
                     .into_iter()
                     .flat_map(|_| dummy_tx_hash.to_fixed_bytes()),
             )
+            // Tx results
+            .chain(self.tx_receipts(max_txs).flat_map(|receipt| {
+                iter::once(receipt.status).chain(receipt.revert_data_hash.to_fixed_bytes())
+            }))
             .collect::<Vec<u8>>();
 
         assert_eq!(
             result.len(),
             BLOCK_HEADER_BYTES_NUM * self.block_ctxs.ctxs.len()
                 + KECCAK_DIGEST_SIZE * 3
-                + KECCAK_DIGEST_SIZE * max_txs
+                + (KECCAK_DIGEST_SIZE + TX_RESULT_BYTES_NUM) * max_txs
         );
         result
     }
 
+    /// Receipts of the txs, padded with empty receipts to `max_txs`.
+    fn tx_receipts(&self, max_txs: usize) -> impl Iterator<Item = Receipt> + '_ {
+        self.transactions
+            .iter()
+            .map(|tx| tx.receipt.clone())
+            .chain(iter::repeat_with(Receipt::default))
+            .take(max_txs)
+    }
+
+    /// Logs bloom of the block `block_num`, as the union of the blooms of
+    /// the receipts of its txs.
+    fn logs_bloom(&self, block_num: u64) -> [u8; LOGS_BLOOM_BYTES_NUM] {
+        self.transactions
+            .iter()
+            .filter(|tx| tx.block_number == block_num)
+            .fold(Bloom::zero(), |bloom, tx| bloom | tx.receipt.bloom)
+            .to_fixed_bytes()
+    }
+
+    /// Addresses and topics of the logs of the txs, with the bloom of their
+    /// block.
+    fn log_items(&self) -> Vec<LogItemWithBlock> {
+        self.transactions
+            .iter()
+            .flat_map(|tx| {
+                let bloom = self.logs_bloom(tx.block_number);
+                tx.receipt
+                    .log_items()
+                    .into_iter()
+                    .map(move |item| LogItemWithBlock {
+                        tx_id: tx.id,
+                        block_number: tx.block_number,
+                        item,
+                        bloom,
+                    })
+            })
+            .collect()
+    }
+
     fn get_pi(&self, max_txs: usize) -> H256 {
         let rpi_bytes = self.raw_public_input_bytes(max_txs);
-        let rpi_keccak = keccak256(rpi_bytes);
+        let rpi_keccak = fast_mock::keccak256(&rpi_bytes);
         H256(rpi_keccak)
     }
 }
@@ -193,6 +265,7 @@ impl Default for BlockContext {
             number: Default::default(),
             timestamp: Default::default(),
             base_fee: Default::default(),
+            blob_base_fee: MIN_BLOB_BASE_FEE.into(),
             history_hashes: vec![],
             eth_block: Default::default(),
         }
@@ -202,17 +275,13 @@ impl Default for BlockContext {
 /// Config for PiCircuit
 #[derive(Clone, Debug)]
 pub struct PiCircuitConfig<F: Field> {
-    /// Max number of supported transactions
-    max_txs: usize,
-    /// Max number of supported calldata bytes
-    max_calldata: usize,
     /// Max number of supported inner blocks in a batch
     max_inner_blocks: usize,
 
     /// dedicated column to store the chain_id, difficulty, coinbase constants
     constant: Column<Fixed>,
 
-    raw_public_inputs: Column<Advice>, // block, history_hashes, states, tx hashes
+    raw_public_inputs: Column<Advice>, // block, history_hashes, states, tx hashes, tx results
     rpi_field_bytes: Column<Advice>,   // rpi in bytes
     rpi_field_bytes_acc: Column<Advice>,
     rpi_rlc_acc: Column<Advice>, // RLC(rpi) as the input to Keccak table
@@ -235,22 +304,21 @@ pub struct PiCircuitConfig<F: Field> {
     q_not_end: Selector,
     q_keccak: Selector,
 
+    logs_bloom: LogsBloomConfig<F>,
+
     pi: Column<Instance>, // hi(keccak(rpi)), lo(keccak(rpi))
 
     // External tables
     block_table: BlockTable,
     tx_table: TxTable,
     keccak_table: KeccakTable,
+    receipt_table: ReceiptTable,
 
     _marker: PhantomData<F>,
 }
 
 /// Circuit configuration arguments
 pub struct PiCircuitConfigArgs<F: Field> {
-    /// Max number of supported transactions
-    pub max_txs: usize,
-    /// Max number of supported calldata bytes
-    pub max_calldata: usize,
     /// Max number of supported blocks in a batch
     pub max_inner_blocks: usize,
     /// TxTable
@@ -259,6 +327,12 @@ pub struct PiCircuitConfigArgs<F: Field> {
     pub block_table: BlockTable,
     /// Keccak Table
     pub keccak_table: KeccakTable,
+    /// ReceiptTable
+    pub receipt_table: ReceiptTable,
+    /// RwTable
+    pub rw_table: RwTable,
+    /// LogsBloomTable
+    pub logs_bloom_table: LogsBloomTable,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
 }
@@ -270,12 +344,13 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
     fn new(
         meta: &mut ConstraintSystem<F>,
         Self::ConfigArgs {
-            max_txs,
-            max_calldata,
             max_inner_blocks,
             block_table,
             tx_table,
             keccak_table,
+            receipt_table,
+            rw_table,
+            logs_bloom_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -293,6 +368,7 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         // Annotate table columns
         tx_table.annotate_columns(meta);
         block_table.annotate_columns(meta);
+        receipt_table.annotate_columns(meta);
 
         let q_field_start = meta.complex_selector();
         let q_field_step = meta.complex_selector();
@@ -315,6 +391,7 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         meta.enable_equality(rpi_rlc_acc);
         meta.enable_equality(block_table.value); // copy block to rpi
         meta.enable_equality(tx_table.value); // copy tx hashes to rpi
+        meta.enable_equality(receipt_table.value); // copy tx results to rpi
         meta.enable_equality(pi);
 
         // field bytes
@@ -489,14 +566,37 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
                 cb.gate(q_block_tag)
             }
         );
+        // There are no blob txs on L2, so the excess blob gas stays zero and
+        // the blob base fee is the minimum one. As the RLC of a word equal to
+        // one is one, the field is not added to the public input.
+        #[cfg(feature = "cancun")]
+        meta.create_gate("block_table.value == 1 for BlobBaseFee", |meta| {
+            let q_block_tag = meta.query_fixed(q_block_tag, Rotation::cur());
+            let is_blob_base_fee_field = block_tag_bits
+                .value_equals(BlockContextFieldTag::BlobBaseFee, Rotation::cur())(
+                meta
+            );
+            let blob_base_fee = meta.query_advice(block_table.value, Rotation::cur());
+
+            vec![q_block_tag * is_blob_base_fee_field * (blob_base_fee - MIN_BLOB_BASE_FEE.expr())]
+        });
+
+        let logs_bloom = LogsBloomConfig::configure(
+            meta,
+            rpi_bytes,
+            logs_bloom_table,
+            &rw_table,
+            &tx_table,
+            &keccak_table,
+            &challenges,
+        );
 
         Self {
-            max_txs,
-            max_calldata,
             max_inner_blocks,
             block_table,
             tx_table,
             keccak_table,
+            receipt_table,
             constant,
             raw_public_inputs: rpi,
             rpi_field_bytes: rpi_bytes,
@@ -512,6 +612,7 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             q_start,
             q_not_end,
             q_keccak,
+            logs_bloom,
             cum_num_txs,
             block_tag_bits,
             q_block_tag,
@@ -536,8 +637,10 @@ impl<F: Field> PiCircuitConfig<F> {
     fn assign(
         &self,
         region: &mut Region<'_, F>,
+        max_txs: usize,
         public_data: &PublicData,
         block_value_cells: &[AssignedCell<F, F>],
+        receipt_value_cells: &[AssignedCell<F, F>],
         challenges: &Challenges<Value<F>>,
     ) -> Result<(KeccakExport<F>, Connections<F>), Error> {
         let block_values = &public_data.block_ctxs;
@@ -706,6 +809,7 @@ impl<F: Field> PiCircuitConfig<F> {
                 challenges,
                 false,
             )?;
+            let block_number_cell = cells[RPI_CELL_IDX].clone();
             block_copy_cells.push((
                 cells[RPI_CELL_IDX].clone(),
                 block_table_offset + BLOCK_NUM_OFFSET,
@@ -792,6 +896,41 @@ impl<F: Field> PiCircuitConfig<F> {
                 false,
             )?;
 
+            // receipts_root, which is not computed from the receipts of the
+            // block, as there is no circuit for the receipts trie yet.
+            self.assign_field_in_pi(
+                region,
+                &mut offset,
+                &block.eth_block.receipts_root.to_fixed_bytes(),
+                &mut rpi_rlc_acc,
+                &mut rpi_length_acc,
+                false,
+                is_rpi_padding,
+                challenges,
+                false,
+            )?;
+
+            // logs_bloom, bound to the logs of the txs of the block
+            let logs_bloom = public_data.logs_bloom(block.number.as_u64());
+            let logs_bloom_offset = offset;
+            self.assign_field_in_pi(
+                region,
+                &mut offset,
+                &logs_bloom,
+                &mut rpi_rlc_acc,
+                &mut rpi_length_acc,
+                false,
+                is_rpi_padding,
+                challenges,
+                false,
+            )?;
+            self.logs_bloom.assign_bloom(
+                region,
+                logs_bloom_offset,
+                &logs_bloom,
+                &block_number_cell,
+            )?;
+
             // chain_id
             let chain_id_cells = self.assign_field_in_pi(
                 region,
@@ -861,11 +1000,10 @@ impl<F: Field> PiCircuitConfig<F> {
         // assign tx hashes
         let num_txs = tx_hashes.len();
         let mut rpi_rlc_cell = None;
-        for tx_hash in tx_hashes.into_iter().chain(
-            (0..self.max_txs - num_txs)
-                .into_iter()
-                .map(|_| dummy_tx_hash),
-        ) {
+        for tx_hash in tx_hashes
+            .into_iter()
+            .chain((0..max_txs - num_txs).into_iter().map(|_| dummy_tx_hash))
+        {
             let cells = self.assign_field_in_pi(
                 region,
                 &mut offset,
@@ -881,11 +1019,49 @@ impl<F: Field> PiCircuitConfig<F> {
             rpi_rlc_cell = Some(cells[RPI_RLC_ACC_CELL_IDX].clone());
         }
 
+        // assign tx results, copied from the receipt table
+        for (i, receipt) in public_data.tx_receipts(max_txs).enumerate() {
+            let status_cells = self.assign_field_in_pi(
+                region,
+                &mut offset,
+                &[receipt.status],
+                &mut rpi_rlc_acc,
+                &mut rpi_length_acc,
+                false,
+                false,
+                challenges,
+                false,
+            )?;
+            let revert_data_hash_cells = self.assign_field_in_pi(
+                region,
+                &mut offset,
+                &receipt.revert_data_hash.to_fixed_bytes(),
+                &mut rpi_rlc_acc,
+                &mut rpi_length_acc,
+                false,
+                false,
+                challenges,
+                false,
+            )?;
+            for (cells, tag) in [
+                (&status_cells, ReceiptFieldTag::Status),
+                (&revert_data_hash_cells, ReceiptFieldTag::RevertDataHash),
+            ] {
+                // +1 for receipt table's first row of all-zeros
+                let row_offset = 1 + i * ReceiptFieldTag::COUNT + tag as usize - 1;
+                region.constrain_equal(
+                    cells[RPI_CELL_IDX].cell(),
+                    receipt_value_cells[row_offset].cell(),
+                )?;
+            }
+            rpi_rlc_cell = Some(revert_data_hash_cells[RPI_RLC_ACC_CELL_IDX].clone());
+        }
+
         debug_assert_eq!(
             offset,
             (BLOCK_HEADER_BYTES_NUM + BLOCK_HEADER_CONST_BYTES_NUM) * self.max_inner_blocks
                 + KECCAK_DIGEST_SIZE * 3
-                + KECCAK_DIGEST_SIZE * self.max_txs
+                + (KECCAK_DIGEST_SIZE + TX_RESULT_BYTES_NUM) * max_txs
         );
 
         for i in 0..(offset - 1) {
@@ -920,7 +1096,7 @@ impl<F: Field> PiCircuitConfig<F> {
             self.raw_public_inputs,
             keccak_row,
         )?;
-        let keccak = public_data.get_pi(self.max_txs);
+        let keccak = public_data.get_pi(max_txs);
         let keccak_rlc =
             keccak
                 .to_fixed_bytes()
@@ -1158,8 +1334,18 @@ impl<F: Field> PiCircuitConfig<F> {
                 .filter(|tx| tx.block_number == block_ctx.number.as_u64())
                 .count();
             let tag = [
-                Coinbase, Timestamp, Number, Difficulty, GasLimit, BaseFee, ChainId, NumTxs,
-                CumNumTxs, BlockHash,
+                Coinbase,
+                Timestamp,
+                Number,
+                Difficulty,
+                GasLimit,
+                BaseFee,
+                ChainId,
+                NumTxs,
+                CumNumTxs,
+                #[cfg(feature = "cancun")]
+                BlobBaseFee,
+                BlockHash,
             ];
             let mut cum_num_txs_field = F::from(cum_num_txs as u64);
             cum_num_txs += num_txs;
@@ -1218,8 +1404,11 @@ pub struct PiCircuit<F: Field> {
     max_txs: usize,
     max_calldata: usize,
     max_inner_blocks: usize,
+    max_log_items: usize,
     /// PublicInputs data known by the verifier
     pub public_data: PublicData,
+    /// TxLog rws of the block, loaded in the rw table by the test circuit
+    tx_log_rws: Vec<Rw>,
 
     _marker: PhantomData<F>,
 
@@ -1248,11 +1437,22 @@ impl<F: Field> PiCircuit<F> {
             prev_state_root: H256(block.mpt_updates.old_root().to_be_bytes()),
             withdraw_trie_root: H256(block.withdraw_root.to_be_bytes()),
         };
+        let max_log_items = match block.circuits_params.max_log_items {
+            0 => public_data.log_items().len(),
+            max_log_items => max_log_items,
+        };
         Self {
             public_data,
             max_txs,
             max_calldata,
             max_inner_blocks,
+            max_log_items,
+            tx_log_rws: block
+                .rws
+                .0
+                .get(&RwTableTag::TxLog)
+                .cloned()
+                .unwrap_or_default(),
             _marker: PhantomData,
             connections: Default::default(),
         }
@@ -1321,14 +1521,24 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let row_num = |inner_block_num, tx_num| -> usize {
-            BLOCK_HEADER_BYTES_NUM * inner_block_num + KECCAK_DIGEST_SIZE * tx_num + 33
+        let row_num = |inner_block_num, tx_num, log_item_num| -> usize {
+            (BLOCK_HEADER_BYTES_NUM * inner_block_num
+                + (KECCAK_DIGEST_SIZE + TX_RESULT_BYTES_NUM) * tx_num
+                + 33)
+                .max(LOG_ITEM_ROWS * log_item_num)
+                .max(BYTE_BITS_TABLE_ROWS)
         };
+        let num_log_items = block
+            .txs
+            .iter()
+            .map(|tx| tx.receipt.log_items().len())
+            .sum::<usize>();
         (
-            row_num(block.context.ctxs.len(), block.txs.len()),
+            row_num(block.context.ctxs.len(), block.txs.len(), num_log_items),
             row_num(
                 block.circuits_params.max_inner_blocks,
                 block.circuits_params.max_txs,
+                num_log_items.max(block.circuits_params.max_log_items),
             ),
         )
     }
@@ -1369,6 +1579,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 // Annotate columns
                 config.tx_table.annotate_columns_in_region(&mut region);
                 config.block_table.annotate_columns_in_region(&mut region);
+                config.receipt_table.annotate_columns_in_region(&mut region);
 
                 // assign block table
                 let block_value_cells = config.assign_block_table(
@@ -1377,11 +1588,20 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     self.max_inner_blocks,
                     challenges,
                 )?;
+                // assign receipt table
+                let receipt_value_cells = config.receipt_table.assign(
+                    &mut region,
+                    &self.public_data.transactions,
+                    self.max_txs,
+                    challenges,
+                )?;
                 // assign pi cols
                 let ((keccak_hi_cell, keccak_lo_cell), conn) = config.assign(
                     &mut region,
+                    self.max_txs,
                     &self.public_data,
                     &block_value_cells,
+                    &receipt_value_cells,
                     challenges,
                 )?;
 
@@ -1394,6 +1614,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
         //       block_table.value (tag = 'Number') for tags except
         //       history_hashes
 
+        config.logs_bloom.load_byte_bits_table(layouter)?;
+        config.logs_bloom.assign_items(
+            layouter,
+            &self.public_data.log_items(),
+            self.max_log_items,
+            challenges,
+        )?;
+
         // Constrain raw_public_input cells to public inputs
         for (i, pi_cell) in pi_cells.iter().enumerate() {
             layouter.constrain_instance(pi_cell.cell(), config.pi, i)?;
@@ -1403,39 +1631,103 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
     }
 }
 
+impl<F: Field> SuperSubCircuit<F> for PiCircuit<F> {
+    const NAME: &'static str = "pi circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[
+        (SharedTable::Block, 1),
+        (SharedTable::Keccak, 1),
+        (SharedTable::Tx, 1),
+        (SharedTable::Receipt, 1),
+        (SharedTable::Rw, 1),
+        (SharedTable::LogsBloom, 1),
+    ];
+    const ASSIGNS: &'static [SharedTable] = &[
+        SharedTable::Tx,
+        SharedTable::Block,
+        SharedTable::Receipt,
+        SharedTable::LogsBloom,
+    ];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        PiCircuitConfig::new(
+            meta,
+            PiCircuitConfigArgs {
+                max_inner_blocks: args.max_inner_blocks,
+                block_table: tables.block().clone(),
+                keccak_table: tables.keccak().clone(),
+                tx_table: tables.tx().clone(),
+                receipt_table: tables.receipt().clone(),
+                rw_table: tables.rw().clone(),
+                logs_bloom_table: tables.logs_bloom().clone(),
+                challenges: args.challenges.clone(),
+            },
+        )
+    }
+
+    // The PI circuit has the hardcoded constants for the RegionIndex of the
+    // block and tx tables (which are 0 and 1). The tx table is assigned in
+    // its load() function which doesn't emit the cells, so it is loaded
+    // before any sub-circuit is synthesized, and its cells are constructed
+    // manually to set up copy constraints with the PI cells.
+    fn load_tables(
+        &self,
+        tables: &SharedTables,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        tables.tx().load(
+            layouter,
+            &self.public_data.transactions,
+            self.max_txs,
+            self.max_calldata,
+            self.public_data.chain_id.as_u64(),
+            challenges,
+        )
+    }
+
+    fn connect<R: SubCircuitRegistry<F>>(
+        &self,
+        circuits: &R,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let evm_exports = circuits
+            .find::<EvmCircuit<F>>()
+            .map(|evm_circuit| evm_circuit.exports.borrow());
+        self.connect_export(
+            layouter,
+            // TODO: enable this after zktrie deletion deployed inside l2geth and
+            // test data regenerated.
+            None,
+            evm_exports.as_ref().and_then(|exports| exports.as_ref()),
+        )
+    }
+}
+
 // We define the PiTestCircuit as a wrapper over PiCircuit extended to take the
-// generic const parameters MAX_TXS and MAX_CALLDATA.  This is necessary because
-// the trait Circuit requires an implementation of `configure` that doesn't take
-// any circuit parameters, and the PiCircuit defines gates that use rotations
-// that depend on MAX_TXS and MAX_CALLDATA, so these two values are required
-// during the configuration.
+// generic const parameter MAX_INNER_BLOCKS.  This is necessary because the trait
+// Circuit requires an implementation of `configure` that doesn't take any
+// circuit parameters, and the PiCircuit lays out the rows of the block headers
+// depending on MAX_INNER_BLOCKS.  The max number of txs and of calldata bytes are
+// only used to pad the tx hashes and results, so they are taken from the
+// CircuitsParams of the block instead.
 /// Test Circuit for PiCircuit
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 #[derive(Default, Clone)]
-pub struct PiTestCircuit<
-    F: Field,
-    const MAX_TXS: usize,
-    const MAX_CALLDATA: usize,
-    const MAX_INNER_BLOCKS: usize,
->(pub PiCircuit<F>);
+pub struct PiTestCircuit<F: Field, const MAX_INNER_BLOCKS: usize>(pub PiCircuit<F>);
 
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
-impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_INNER_BLOCKS: usize>
-    SubCircuit<F> for PiTestCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>
-{
+impl<F: Field, const MAX_INNER_BLOCKS: usize> SubCircuit<F> for PiTestCircuit<F, MAX_INNER_BLOCKS> {
     type Config = PiCircuitConfig<F>;
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        assert_eq!(block.circuits_params.max_txs, MAX_TXS);
-        assert_eq!(block.circuits_params.max_calldata, MAX_CALLDATA);
-
         Self(PiCircuit::new_from_block(block))
     }
 
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        assert_eq!(block.circuits_params.max_txs, MAX_TXS);
-        assert_eq!(block.circuits_params.max_calldata, MAX_CALLDATA);
-
         PiCircuit::min_num_rows_block(block)
     }
 
@@ -1455,9 +1747,7 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_INNER_
 }
 
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
-impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_INNER_BLOCKS: usize>
-    Circuit<F> for PiTestCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>
-{
+impl<F: Field, const MAX_INNER_BLOCKS: usize> Circuit<F> for PiTestCircuit<F, MAX_INNER_BLOCKS> {
     type Config = (PiCircuitConfig<F>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -1469,18 +1759,22 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_INNER_
         let block_table = BlockTable::construct(meta);
         let tx_table = TxTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        let receipt_table = ReceiptTable::construct(meta);
+        let rw_table = RwTable::construct(meta);
+        let logs_bloom_table = LogsBloomTable::construct(meta);
         let challenges = Challenges::construct(meta);
         let challenge_exprs = challenges.exprs(meta);
         (
             PiCircuitConfig::new(
                 meta,
                 PiCircuitConfigArgs {
-                    max_txs: MAX_TXS,
-                    max_calldata: MAX_CALLDATA,
                     max_inner_blocks: MAX_INNER_BLOCKS,
                     block_table,
                     keccak_table,
                     tx_table,
+                    receipt_table,
+                    rw_table,
+                    logs_bloom_table,
                     challenges: challenge_exprs,
                 },
             ),
@@ -1504,11 +1798,21 @@ impl<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize, const MAX_INNER_
             self.0.public_data.chain_id.as_u64(),
             &challenges,
         )?;
+        // assign rw table
+        config.rw_table.load(
+            &mut layouter,
+            &self.0.tx_log_rws,
+            self.0.tx_log_rws.len() + 1,
+            challenges.evm_word(),
+        )?;
         // assign keccak table
         let rpi_bytes = self.0.public_data.raw_public_input_bytes(self.0.max_txs);
-        config
-            .keccak_table
-            .dev_load(&mut layouter, vec![&rpi_bytes], &challenges)?;
+        let log_items = self.0.public_data.log_items();
+        config.keccak_table.dev_load(
+            &mut layouter,
+            iter::once(&rpi_bytes).chain(log_items.iter().map(|item| &item.item.bytes)),
+            &challenges,
+        )?;
 
         self.0.synthesize_sub(&config, &challenges, &mut layouter)?;
 
@@ -1524,18 +1828,15 @@ mod pi_circuit_test {
     // use rand_chacha::ChaCha20Rng;
     // use rand::SeedableRng;
 
-    fn run<
-        F: Field,
-        const MAX_TXS: usize,
-        const MAX_CALLDATA: usize,
-        const MAX_INNER_BLOCKS: usize,
-    >(
+    fn run<F: Field, const MAX_INNER_BLOCKS: usize>(
         k: u32,
+        max_txs: usize,
+        max_calldata: usize,
         block: Block<F>,
     ) -> Result<(), Vec<VerifyFailure>> {
-        let circuit = PiTestCircuit::<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(PiCircuit::new(
-            MAX_TXS,
-            MAX_CALLDATA,
+        let circuit = PiTestCircuit::<F, MAX_INNER_BLOCKS>(PiCircuit::new(
+            max_txs,
+            max_calldata,
             MAX_INNER_BLOCKS,
             &block,
         ));
@@ -1604,11 +1905,111 @@ mod pi_circuit_test {
 
         let k = 16;
         assert_eq!(
-            run::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(k, block),
+            run::<Fr, MAX_INNER_BLOCKS>(k, MAX_TXS, MAX_CALLDATA, block),
             Ok(())
         );
     }
 
+    #[cfg(feature = "scroll")]
+    fn block_with_code<F: Field>(bytecode: eth_types::Bytecode) -> Block<F> {
+        use mock::test_ctx::helpers::tx_from_1_to_0;
+        use std::env::set_var;
+
+        use crate::witness::block_convert;
+        use bus_mapping::mock::BlockData;
+        use eth_types::geth_types::GethData;
+        use mock::{
+            test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_CHAIN_ID,
+            MOCK_DIFFICULTY,
+        };
+
+        let mut difficulty_be_bytes = [0u8; 32];
+        let mut chain_id_be_bytes = [0u8; 32];
+        MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+        MOCK_CHAIN_ID.to_big_endian(&mut chain_id_be_bytes);
+        set_var("CHAIN_ID", hex::encode(chain_id_be_bytes));
+        set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+
+        let test_ctx = TestContext::<2, 1>::new(
+            Some(vec![Word::zero()]),
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _txs| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let block: GethData = test_ctx.into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert(&builder.block, &builder.code_db).unwrap()
+    }
+
+    #[cfg(feature = "scroll")]
+    fn block_with_log1<F: Field>() -> Block<F> {
+        use eth_types::bytecode;
+
+        block_with_code(bytecode! {
+            PUSH32(0x1234)
+            PUSH1(0)
+            PUSH1(0)
+            LOG1
+            STOP
+        })
+    }
+
+    #[cfg(feature = "scroll")]
+    #[test]
+    fn serial_test_logs_bloom_pi() {
+        use halo2_proofs::halo2curves::bn256::Fr;
+        use pretty_assertions::assert_eq;
+
+        let block = block_with_log1::<Fr>();
+        assert_eq!(block.txs[0].receipt.log_items().len(), 2);
+
+        let k = 16;
+        assert_eq!(run::<Fr, 4>(k, 4, 20, block), Ok(()));
+    }
+
+    #[cfg(feature = "scroll")]
+    #[test]
+    fn serial_test_tampered_log_pi() {
+        use halo2_proofs::halo2curves::bn256::Fr;
+
+        let mut block = block_with_log1::<Fr>();
+        // the bloom of the receipt stays the one of the log written by the tx
+        block.txs[0].receipt.logs[0].topics[0] = H256::from_low_u64_be(0x5678);
+
+        let k = 16;
+        assert!(run::<Fr, 4>(k, 4, 20, block).is_err());
+    }
+
+    #[cfg(feature = "scroll")]
+    #[test]
+    fn serial_test_revert_data_hash_pi() {
+        use eth_types::{bytecode, word};
+        use ethers_core::utils::keccak256;
+        use halo2_proofs::halo2curves::bn256::Fr;
+        use pretty_assertions::assert_eq;
+
+        let block = block_with_code::<Fr>(bytecode! {
+            PUSH32(word!("0xdeadbeef"))
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x04)
+            PUSH1(0x1c)
+            REVERT
+        });
+        assert_eq!(block.txs[0].receipt.status, 0);
+        assert_eq!(
+            block.txs[0].receipt.revert_data_hash,
+            H256(keccak256([0xde, 0xad, 0xbe, 0xef]))
+        );
+
+        let k = 16;
+        assert_eq!(run::<Fr, 4>(k, 4, 20, block), Ok(()));
+    }
+
     // fn run_size_check<F: Field, const MAX_TXS: usize, const MAX_CALLDATA: usize>(
     // public_data: [PublicData; 2],
     // ) {
@@ -0,0 +1,674 @@
+//! Binding of the logs bloom of each block in the public input to the
+//! addresses and topics of the logs of its txs.
+//!
+//! The bits of the bloom bytes in the public input are decomposed on the rows
+//! of the bloom field. Each address or topic of a log (an item) is laid out
+//! on `LOG_ITEM_ROWS` rows of its own region, where its bytes and the bytes
+//! of its keccak hash are accumulated:
+//!
+//! | j  | byte | hash_byte | hi5 | lo3 | bit_index          | bloom_byte |
+//! | 0  | b0   | h0        |     | h0&7|                    |            |
+//! | 1  | b1   | h1        | h1>>3 | h1&7 | (h0&7)*256 + h1 | bloom[255 - bit_index/8] |
+//! | 2  | b2   | h2        |     | h2&7|                    |            |
+//! | 3  | b3   | h3        | h3>>3 | h3&7 | (h2&7)*256 + h3 | bloom[255 - bit_index/8] |
+//! | .. | ..   | ..        |     |     |                    |            |
+//! | 31 | b31  | h31       |     |     |                    |            |
+//!
+//! The last row of an item looks it up in the rw table (the log is written by
+//! the tx), in the keccak table (the hash is the one of the item) and in the
+//! tx table (the tx is in the block of the bloom), and is the row of the
+//! `LogsBloomTable` looked up by the EVM circuit. The three bits set by the
+//! item are looked up in the bloom bytes of its block, and each bit set in
+//! the bloom of a block is looked up in the bits set by its items, so the
+//! bloom is exactly the one of the logs of the block.
+
+use crate::{
+    table::{
+        KeccakTable, LogsBloomTable, LookupTable, RwTable, RwTableTag, TxFieldTag, TxLogFieldTag,
+        TxTable,
+    },
+    util::{build_tx_log_expression, Challenges},
+    witness::LogItem,
+};
+use eth_types::Field;
+use ethers_core::utils::keccak256;
+use gadgets::util::{not, select, Expr};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+#[cfg(feature = "onephase")]
+use halo2_proofs::plonk::FirstPhase as SecondPhase;
+#[cfg(not(feature = "onephase"))]
+use halo2_proofs::plonk::SecondPhase;
+
+/// Number of bytes of a logs bloom
+pub(super) const LOGS_BLOOM_BYTES_NUM: usize = 256;
+/// Rows of a log item, one per byte of the topic or of the left-padded address
+pub(super) const LOG_ITEM_ROWS: usize = 32;
+/// Rows of the fixed table of the bits of the bytes
+pub(super) const BYTE_BITS_TABLE_ROWS: usize = 256 * 8;
+/// Number of pairs of hash bytes whose low 11 bits set a bit of the bloom
+const NUM_BLOOM_BITS_PER_ITEM: usize = 3;
+
+/// A log item to assign, with the tx and block it belongs to
+#[derive(Clone, Debug)]
+pub(super) struct LogItemWithBlock {
+    pub(super) tx_id: usize,
+    pub(super) block_number: u64,
+    pub(super) item: LogItem,
+    pub(super) bloom: [u8; LOGS_BLOOM_BYTES_NUM],
+}
+
+/// Config of the logs bloom in the PiCircuit
+#[derive(Clone, Debug)]
+pub(super) struct LogsBloomConfig<F: Field> {
+    // bloom rows, on the rows of the bloom field of the public input
+    q_bloom: Selector,
+    q_bloom_not_last: Selector,
+    bloom_byte_index: Column<Fixed>,
+    bloom_bits: [Column<Advice>; 8],
+    bloom_block_number: Column<Advice>,
+
+    // item rows
+    q_item_first: Selector,
+    q_item_not_last: Selector,
+    q_item_last: Selector,
+    q_hash_hi: Selector,
+    q_hash_lo: Selector,
+    is_enabled: Column<Advice>,
+    is_topic: Column<Advice>,
+    block_number: Column<Advice>,
+    byte: Column<Advice>,
+    hash_byte: Column<Advice>,
+    value_acc: Column<Advice>,
+    input_rlc_acc: Column<Advice>,
+    hash_rlc_acc: Column<Advice>,
+    hash_byte_hi5: Column<Advice>,
+    hash_byte_lo3: Column<Advice>,
+    bit_index: Column<Advice>,
+    bloom_byte: Column<Advice>,
+
+    // byte, byte >> 3, byte & 7, k, (byte >> k) & 1
+    byte_bits_table: [TableColumn; 5],
+
+    pub(super) logs_bloom_table: LogsBloomTable,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> LogsBloomConfig<F> {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        rpi_field_bytes: Column<Advice>,
+        logs_bloom_table: LogsBloomTable,
+        rw_table: &RwTable,
+        tx_table: &TxTable,
+        keccak_table: &KeccakTable,
+        challenges: &Challenges<Expression<F>>,
+    ) -> Self {
+        let q_bloom = meta.complex_selector();
+        let q_bloom_not_last = meta.selector();
+        let bloom_byte_index = meta.fixed_column();
+        let bloom_bits = [(); 8].map(|_| meta.advice_column());
+        let bloom_block_number = meta.advice_column_in(SecondPhase);
+
+        let q_item_first = meta.selector();
+        let q_item_not_last = meta.selector();
+        let q_item_last = meta.complex_selector();
+        let q_hash_hi = meta.complex_selector();
+        let q_hash_lo = meta.complex_selector();
+        let is_enabled = meta.advice_column();
+        let is_topic = meta.advice_column();
+        let block_number = meta.advice_column();
+        let byte = meta.advice_column();
+        let hash_byte = meta.advice_column();
+        let value_acc = meta.advice_column_in(SecondPhase);
+        let input_rlc_acc = meta.advice_column_in(SecondPhase);
+        let hash_rlc_acc = meta.advice_column_in(SecondPhase);
+        let hash_byte_hi5 = meta.advice_column();
+        let hash_byte_lo3 = meta.advice_column();
+        let bit_index = meta.advice_column();
+        let bloom_byte = meta.advice_column();
+
+        let byte_bits_table = [(); 5].map(|_| meta.lookup_table_column());
+
+        meta.enable_equality(bloom_block_number);
+        logs_bloom_table.annotate_columns(meta);
+
+        meta.create_gate("logs bloom byte = sum(bit_k * 2^k)", |meta| {
+            let q_bloom = meta.query_selector(q_bloom);
+            let bits = bloom_bits.map(|bit| meta.query_advice(bit, Rotation::cur()));
+            let byte = meta.query_advice(rpi_field_bytes, Rotation::cur());
+
+            let mut constraints = bits
+                .iter()
+                .map(|bit| q_bloom.clone() * bit.clone() * not::expr(bit.clone()))
+                .collect::<Vec<_>>();
+            let bits_value = bits
+                .into_iter()
+                .rev()
+                .fold(0.expr(), |acc, bit| acc * 2.expr() + bit);
+            constraints.push(q_bloom * (byte - bits_value));
+            constraints
+        });
+        meta.create_gate("logs bloom block_number' = block_number", |meta| {
+            let q_bloom_not_last = meta.query_selector(q_bloom_not_last);
+            let block_number_next = meta.query_advice(bloom_block_number, Rotation::next());
+            let block_number = meta.query_advice(bloom_block_number, Rotation::cur());
+
+            vec![q_bloom_not_last * (block_number_next - block_number)]
+        });
+
+        let table_columns = <LogsBloomTable as LookupTable<F>>::advice_columns(&logs_bloom_table);
+        let table_exprs = |meta: &mut VirtualCells<'_, F>| {
+            table_columns
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect::<Vec<_>>()
+        };
+
+        meta.create_gate("log item accumulators start from its bytes", |meta| {
+            let q_item_first = meta.query_selector(q_item_first);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let hash_byte = meta.query_advice(hash_byte, Rotation::cur());
+
+            vec![
+                q_item_first.clone()
+                    * (meta.query_advice(value_acc, Rotation::cur()) - byte.clone()),
+                q_item_first.clone() * (meta.query_advice(input_rlc_acc, Rotation::cur()) - byte),
+                q_item_first * (meta.query_advice(hash_rlc_acc, Rotation::cur()) - hash_byte),
+            ]
+        });
+        meta.create_gate("log item accumulators", |meta| {
+            let q_item_not_last = meta.query_selector(q_item_not_last);
+            let is_topic_expr = meta.query_advice(is_topic, Rotation::cur());
+            let byte_next = meta.query_advice(byte, Rotation::next());
+            let hash_byte_next = meta.query_advice(hash_byte, Rotation::next());
+            let value_acc_cur = meta.query_advice(value_acc, Rotation::cur());
+            let input_rlc_acc_cur = meta.query_advice(input_rlc_acc, Rotation::cur());
+            let hash_rlc_acc_cur = meta.query_advice(hash_rlc_acc, Rotation::cur());
+
+            // the item is the same on all of its rows
+            let mut constraints = [is_enabled, is_topic, block_number]
+                .into_iter()
+                .chain(table_columns.iter().copied())
+                .map(|column| {
+                    q_item_not_last.clone()
+                        * (meta.query_advice(column, Rotation::next())
+                            - meta.query_advice(column, Rotation::cur()))
+                })
+                .collect::<Vec<_>>();
+            let t = select::expr(is_topic_expr, challenges.evm_word(), 256.expr());
+            constraints.extend([
+                q_item_not_last.clone()
+                    * (meta.query_advice(value_acc, Rotation::next())
+                        - (value_acc_cur * t + byte_next.clone())),
+                q_item_not_last.clone()
+                    * (meta.query_advice(input_rlc_acc, Rotation::next())
+                        - (input_rlc_acc_cur * challenges.keccak_input() + byte_next)),
+                q_item_not_last
+                    * (meta.query_advice(hash_rlc_acc, Rotation::next())
+                        - (hash_rlc_acc_cur * challenges.evm_word() + hash_byte_next)),
+            ]);
+            constraints
+        });
+        meta.create_gate("log item is the value of its LogsBloomTable row", |meta| {
+            let q_item_last = meta.query_selector(q_item_last);
+            let is_enabled = meta.query_advice(is_enabled, Rotation::cur());
+            let is_topic = meta.query_advice(is_topic, Rotation::cur());
+            let [tx_id, _log_id, field_tag, _index, value] =
+                <[Expression<F>; 5]>::try_from(table_exprs(meta)).unwrap();
+
+            vec![
+                q_item_last.clone() * is_enabled.clone() * not::expr(is_enabled.clone()),
+                q_item_last.clone() * is_topic.clone() * not::expr(is_topic.clone()),
+                q_item_last.clone()
+                    * is_enabled.clone()
+                    * (field_tag
+                        - select::expr(
+                            is_topic,
+                            TxLogFieldTag::Topic.expr(),
+                            TxLogFieldTag::Address.expr(),
+                        )),
+                q_item_last.clone()
+                    * is_enabled.clone()
+                    * (value - meta.query_advice(value_acc, Rotation::cur())),
+                q_item_last * not::expr(is_enabled) * tx_id,
+            ]
+        });
+        meta.create_gate("log item bit_index = (lo3_prev << 8) + hash_byte", |meta| {
+            let q_hash_lo = meta.query_selector(q_hash_lo);
+            let is_enabled = meta.query_advice(is_enabled, Rotation::cur());
+            let lo3_prev = meta.query_advice(hash_byte_lo3, Rotation::prev());
+            let hash_byte = meta.query_advice(hash_byte, Rotation::cur());
+            let bit_index = meta.query_advice(bit_index, Rotation::cur());
+
+            vec![q_hash_lo * is_enabled * (bit_index - (lo3_prev * 256.expr() + hash_byte))]
+        });
+
+        meta.lookup_any("log item in rw table", |meta| {
+            let enable =
+                meta.query_selector(q_item_last) * meta.query_advice(is_enabled, Rotation::cur());
+            let [tx_id, log_id, field_tag, index, value] =
+                <[Expression<F>; 5]>::try_from(table_exprs(meta)).unwrap();
+
+            vec![
+                (
+                    1.expr(),
+                    meta.query_fixed(rw_table.q_enable, Rotation::cur()),
+                ),
+                (
+                    1.expr(),
+                    meta.query_advice(rw_table.is_write, Rotation::cur()),
+                ),
+                (
+                    RwTableTag::TxLog.expr(),
+                    meta.query_advice(rw_table.tag, Rotation::cur()),
+                ),
+                (tx_id, meta.query_advice(rw_table.id, Rotation::cur())),
+                (
+                    build_tx_log_expression(index, field_tag, log_id),
+                    meta.query_advice(rw_table.address, Rotation::cur()),
+                ),
+                (value, meta.query_advice(rw_table.value, Rotation::cur())),
+            ]
+            .into_iter()
+            .map(|(arg, table)| (enable.clone() * arg, table))
+            .collect()
+        });
+        meta.lookup_any("log item tx in block", |meta| {
+            let enable =
+                meta.query_selector(q_item_last) * meta.query_advice(is_enabled, Rotation::cur());
+
+            vec![
+                (
+                    1.expr(),
+                    meta.query_fixed(tx_table.q_enable, Rotation::cur()),
+                ),
+                (
+                    meta.query_advice(logs_bloom_table.tx_id, Rotation::cur()),
+                    meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                ),
+                (
+                    TxFieldTag::BlockNumber.expr(),
+                    meta.query_fixed(tx_table.tag, Rotation::cur()),
+                ),
+                (0.expr(), meta.query_advice(tx_table.index, Rotation::cur())),
+                (
+                    meta.query_advice(block_number, Rotation::cur()),
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                ),
+            ]
+            .into_iter()
+            .map(|(arg, table)| (enable.clone() * arg, table))
+            .collect()
+        });
+        meta.lookup_any("keccak(log item)", |meta| {
+            let enable =
+                meta.query_selector(q_item_last) * meta.query_advice(is_enabled, Rotation::cur());
+            let is_topic = meta.query_advice(is_topic, Rotation::cur());
+
+            vec![
+                1.expr(), // is_enabled
+                1.expr(), // is_final
+                meta.query_advice(input_rlc_acc, Rotation::cur()),
+                select::expr(is_topic, 32.expr(), 20.expr()),
+                meta.query_advice(hash_rlc_acc, Rotation::cur()),
+            ]
+            .into_iter()
+            .zip(keccak_table.table_exprs(meta))
+            .map(|(arg, table)| (enable.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup("log item hash byte = (hi5 << 3) + lo3", |meta| {
+            let enable = (meta.query_selector(q_hash_hi) + meta.query_selector(q_hash_lo))
+                * meta.query_advice(is_enabled, Rotation::cur());
+
+            vec![
+                (
+                    enable.clone() * meta.query_advice(hash_byte, Rotation::cur()),
+                    byte_bits_table[0],
+                ),
+                (
+                    enable.clone() * meta.query_advice(hash_byte_hi5, Rotation::cur()),
+                    byte_bits_table[1],
+                ),
+                (
+                    enable * meta.query_advice(hash_byte_lo3, Rotation::cur()),
+                    byte_bits_table[2],
+                ),
+            ]
+        });
+        meta.lookup("log item bit is set in bloom_byte", |meta| {
+            let enable =
+                meta.query_selector(q_hash_lo) * meta.query_advice(is_enabled, Rotation::cur());
+
+            vec![
+                (
+                    enable.clone() * meta.query_advice(bloom_byte, Rotation::cur()),
+                    byte_bits_table[0],
+                ),
+                (
+                    enable.clone() * meta.query_advice(hash_byte_lo3, Rotation::cur()),
+                    byte_bits_table[3],
+                ),
+                (enable, byte_bits_table[4]),
+            ]
+        });
+        // bloom[255 - bit_index / 8] |= 1 << (bit_index % 8), where
+        // bit_index / 8 = (lo3_prev << 5) + hi5 and bit_index % 8 = lo3
+        meta.lookup_any("log item bloom_byte in logs bloom", |meta| {
+            let enable =
+                meta.query_selector(q_hash_lo) * meta.query_advice(is_enabled, Rotation::cur());
+            let q_bloom = meta.query_selector(q_bloom);
+            let byte_index = (LOGS_BLOOM_BYTES_NUM - 1).expr()
+                - (meta.query_advice(hash_byte_lo3, Rotation::prev()) * 32.expr()
+                    + meta.query_advice(hash_byte_hi5, Rotation::cur()));
+
+            vec![
+                (enable.clone(), q_bloom.clone()),
+                (
+                    enable.clone() * meta.query_advice(block_number, Rotation::cur()),
+                    q_bloom.clone() * meta.query_advice(bloom_block_number, Rotation::cur()),
+                ),
+                (
+                    enable.clone() * byte_index,
+                    q_bloom.clone() * meta.query_fixed(bloom_byte_index, Rotation::cur()),
+                ),
+                (
+                    enable * meta.query_advice(bloom_byte, Rotation::cur()),
+                    q_bloom * meta.query_advice(rpi_field_bytes, Rotation::cur()),
+                ),
+            ]
+        });
+        for (k, bit) in bloom_bits.into_iter().enumerate() {
+            meta.lookup_any("logs bloom bit is set by a log item", |meta| {
+                let enable = meta.query_selector(q_bloom) * meta.query_advice(bit, Rotation::cur());
+                let index = (LOGS_BLOOM_BYTES_NUM - 1).expr()
+                    - meta.query_fixed(bloom_byte_index, Rotation::cur());
+                let table_enable =
+                    meta.query_selector(q_hash_lo) * meta.query_advice(is_enabled, Rotation::cur());
+
+                vec![
+                    (enable.clone(), table_enable.clone()),
+                    (
+                        enable.clone() * meta.query_advice(bloom_block_number, Rotation::cur()),
+                        table_enable.clone() * meta.query_advice(block_number, Rotation::cur()),
+                    ),
+                    (
+                        enable * (index * 8.expr() + k.expr()),
+                        table_enable * meta.query_advice(bit_index, Rotation::cur()),
+                    ),
+                ]
+            });
+        }
+
+        Self {
+            q_bloom,
+            q_bloom_not_last,
+            bloom_byte_index,
+            bloom_bits,
+            bloom_block_number,
+            q_item_first,
+            q_item_not_last,
+            q_item_last,
+            q_hash_hi,
+            q_hash_lo,
+            is_enabled,
+            is_topic,
+            block_number,
+            byte,
+            hash_byte,
+            value_acc,
+            input_rlc_acc,
+            hash_rlc_acc,
+            hash_byte_hi5,
+            hash_byte_lo3,
+            bit_index,
+            bloom_byte,
+            byte_bits_table,
+            logs_bloom_table,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assign the bits of the bloom bytes, which are assigned in the public
+    /// input from `offset`, and the block number of the bloom, copied from
+    /// the number field of the block.
+    pub(super) fn assign_bloom(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bloom: &[u8],
+        block_number_cell: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        for (j, byte) in bloom.iter().enumerate() {
+            let row = offset + j;
+            self.q_bloom.enable(region, row)?;
+            if j < LOGS_BLOOM_BYTES_NUM - 1 {
+                self.q_bloom_not_last.enable(region, row)?;
+            }
+            region.assign_fixed(
+                || "logs bloom byte index",
+                self.bloom_byte_index,
+                row,
+                || Value::known(F::from(j as u64)),
+            )?;
+            for (k, column) in self.bloom_bits.iter().enumerate() {
+                region.assign_advice(
+                    || format!("logs bloom bit {}", k),
+                    *column,
+                    row,
+                    || Value::known(F::from(((byte >> k) & 1) as u64)),
+                )?;
+            }
+            if j == 0 {
+                block_number_cell.copy_advice(
+                    || "logs bloom block number",
+                    region,
+                    self.bloom_block_number,
+                    row,
+                )?;
+            } else {
+                region.assign_advice(
+                    || "logs bloom block number",
+                    self.bloom_block_number,
+                    row,
+                    || block_number_cell.value().copied(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Assign the log items of the txs, padded with disabled items to
+    /// `max_log_items`.
+    pub(super) fn assign_items(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        items: &[LogItemWithBlock],
+        max_log_items: usize,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        assert!(
+            items.len() <= max_log_items,
+            "{} log items exceed max_log_items {}",
+            items.len(),
+            max_log_items
+        );
+        layouter.assign_region(
+            || "pi logs bloom items",
+            |mut region| {
+                self.logs_bloom_table
+                    .annotate_columns_in_region(&mut region);
+                for slot in 0..max_log_items {
+                    self.assign_item(
+                        &mut region,
+                        slot * LOG_ITEM_ROWS,
+                        items.get(slot),
+                        challenges,
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn assign_item(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        item: Option<&LogItemWithBlock>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let (is_topic, bytes, hash) = match item {
+            Some(item) => {
+                let mut bytes = [0u8; LOG_ITEM_ROWS];
+                bytes[LOG_ITEM_ROWS - item.item.bytes.len()..].copy_from_slice(&item.item.bytes);
+                (
+                    item.item.field_tag == TxLogFieldTag::Topic,
+                    bytes,
+                    keccak256(&item.item.bytes),
+                )
+            }
+            None => (false, [0u8; LOG_ITEM_ROWS], [0u8; 32]),
+        };
+        let t = if is_topic {
+            challenges.evm_word()
+        } else {
+            Value::known(F::from(256))
+        };
+        let table_values = match item {
+            Some(item) => [
+                Value::known(F::from(item.tx_id as u64)),
+                Value::known(F::from(item.item.log_id as u64)),
+                Value::known(F::from(item.item.field_tag as u64)),
+                Value::known(F::from(item.item.index as u64)),
+                item.item.value(challenges.evm_word()),
+            ],
+            None => [Value::known(F::zero()); 5],
+        };
+
+        let mut value_acc = Value::known(F::zero());
+        let mut input_rlc_acc = Value::known(F::zero());
+        let mut hash_rlc_acc = Value::known(F::zero());
+        for j in 0..LOG_ITEM_ROWS {
+            let row = offset + j;
+            let byte = F::from(bytes[j] as u64);
+            let hash_byte = hash[j];
+            value_acc = value_acc * t + Value::known(byte);
+            input_rlc_acc = input_rlc_acc * challenges.keccak_input() + Value::known(byte);
+            hash_rlc_acc =
+                hash_rlc_acc * challenges.evm_word() + Value::known(F::from(hash_byte as u64));
+
+            if j == 0 {
+                self.q_item_first.enable(region, row)?;
+            }
+            if j == LOG_ITEM_ROWS - 1 {
+                self.q_item_last.enable(region, row)?;
+            } else {
+                self.q_item_not_last.enable(region, row)?;
+            }
+            region.assign_fixed(
+                || "logs bloom table q_enable",
+                self.logs_bloom_table.q_enable,
+                row,
+                || Value::known(F::from((j == LOG_ITEM_ROWS - 1) as u64)),
+            )?;
+
+            let is_hash_row = j < 2 * NUM_BLOOM_BITS_PER_ITEM;
+            let is_hash_lo = is_hash_row && j % 2 == 1;
+            if is_hash_row {
+                if is_hash_lo {
+                    self.q_hash_lo.enable(region, row)?;
+                } else {
+                    self.q_hash_hi.enable(region, row)?;
+                }
+            }
+            let (hi5, lo3) = if is_hash_row {
+                (hash_byte >> 3, hash_byte & 7)
+            } else {
+                (0, 0)
+            };
+            let (bit_index, bloom_byte) = match item {
+                Some(item) if is_hash_lo => {
+                    let bit_index = ((hash[j - 1] & 7) as usize) << 8 | hash_byte as usize;
+                    (
+                        bit_index,
+                        item.bloom[LOGS_BLOOM_BYTES_NUM - 1 - bit_index / 8],
+                    )
+                }
+                _ => (0, 0),
+            };
+
+            for (column, value) in
+                <LogsBloomTable as LookupTable<F>>::advice_columns(&self.logs_bloom_table)
+                    .into_iter()
+                    .zip(table_values)
+                    .chain([
+                        (
+                            self.is_enabled,
+                            Value::known(F::from(item.is_some() as u64)),
+                        ),
+                        (self.is_topic, Value::known(F::from(is_topic as u64))),
+                        (
+                            self.block_number,
+                            Value::known(F::from(item.map_or(0, |item| item.block_number))),
+                        ),
+                        (self.byte, Value::known(byte)),
+                        (self.hash_byte, Value::known(F::from(hash_byte as u64))),
+                        (self.value_acc, value_acc),
+                        (self.input_rlc_acc, input_rlc_acc),
+                        (self.hash_rlc_acc, hash_rlc_acc),
+                        (self.hash_byte_hi5, Value::known(F::from(hi5 as u64))),
+                        (self.hash_byte_lo3, Value::known(F::from(lo3 as u64))),
+                        (self.bit_index, Value::known(F::from(bit_index as u64))),
+                        (self.bloom_byte, Value::known(F::from(bloom_byte as u64))),
+                    ])
+            {
+                region.assign_advice(|| format!("log item row {}", row), column, row, || value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the fixed table of the bits of the bytes.
+    pub(super) fn load_byte_bits_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_table(
+            || "byte bits table",
+            |mut table| {
+                for byte in 0..256u64 {
+                    for k in 0..8u64 {
+                        let row = (byte * 8 + k) as usize;
+                        for (column, value) in self.byte_bits_table.iter().zip([
+                            byte,
+                            byte >> 3,
+                            byte & 7,
+                            k,
+                            (byte >> k) & 1,
+                        ]) {
+                            table.assign_cell(
+                                || format!("byte bits table row {}", row),
+                                *column,
+                                row,
+                                || Value::known(F::from(value)),
+                            )?;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -1,6 +1,10 @@
 //! wrapping of mpt-circuit
 use crate::{
     bytecode_circuit::bytecode_unroller::HASHBLOCK_BYTES_IN_FIELD,
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::PoseidonTable,
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness,
@@ -152,6 +156,31 @@ impl<F: Field> SubCircuit<F> for PoseidonCircuit<F> {
     }
 }
 
+impl<F: Field> SuperSubCircuit<F> for PoseidonCircuit<F> {
+    const NAME: &'static str = "poseidon circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[(SharedTable::Poseidon, 1)];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Poseidon];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        _args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        PoseidonCircuitConfig::new(
+            meta,
+            PoseidonCircuitConfigArgs {
+                poseidon_table: *tables.poseidon(),
+            },
+        )
+    }
+
+    // The rows of the poseidon circuit are not counted in the super circuit
+    // yet.
+    fn min_num_rows(_block: &witness::Block<F>) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
 #[cfg(any(feature = "test", test))]
 impl<F: Field + Hashable> Circuit<F> for PoseidonCircuit<F> {
     type Config = (PoseidonCircuitConfig<F>, Challenges);
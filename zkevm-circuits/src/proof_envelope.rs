@@ -0,0 +1,233 @@
+//! Versioned envelope carrying a proof together with the metadata a verifier
+//! needs to check that it is compatible with the proof before verifying it.
+
+use bus_mapping::circuit_input_builder::CircuitsParams;
+use eth_types::{Bytes, Field, ToScalar, Word, H256};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Version of the [`ProofEnvelope`] format produced by this crate.
+pub const PROOF_ENVELOPE_VERSION: u32 = 1;
+
+/// A proof with the metadata describing what it proves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    /// Version of the envelope format
+    pub version: u32,
+    /// Hash identifying the circuit the proof was made for, e.g. the hash of
+    /// its verifying key
+    pub circuit_version: H256,
+    /// Parameters of the circuit
+    pub circuits_params: CircuitsParams,
+    /// Chain id of the proven blocks
+    pub chain_id: u64,
+    /// Number of the first proven block
+    pub start_block: u64,
+    /// Number of the last proven block
+    pub end_block: u64,
+    /// Public inputs of the proof, one vector per instance column
+    pub instances: Vec<Vec<Word>>,
+    /// Proof bytes
+    pub proof: Bytes,
+}
+
+impl ProofEnvelope {
+    /// Create an envelope in the current format.
+    pub fn new<F: Field>(
+        circuit_version: H256,
+        circuits_params: CircuitsParams,
+        chain_id: u64,
+        (start_block, end_block): (u64, u64),
+        instances: &[Vec<F>],
+        proof: Vec<u8>,
+    ) -> Self {
+        Self {
+            version: PROOF_ENVELOPE_VERSION,
+            circuit_version,
+            circuits_params,
+            chain_id,
+            start_block,
+            end_block,
+            instances: instances
+                .iter()
+                .map(|column| {
+                    column
+                        .iter()
+                        .map(|value| Word::from_little_endian(&value.to_repr()))
+                        .collect()
+                })
+                .collect(),
+            proof: proof.into(),
+        }
+    }
+
+    /// Public inputs of the proof as field elements. Returns `None` if one of
+    /// them is not a canonical field element.
+    pub fn instances<F: Field>(&self) -> Option<Vec<Vec<F>>> {
+        self.instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|value| value.to_scalar())
+                    .collect::<Option<Vec<F>>>()
+            })
+            .collect()
+    }
+}
+
+/// What a verifier expects from the proofs it accepts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierSetup {
+    /// Hash identifying the circuit of the verifier
+    pub circuit_version: H256,
+    /// Parameters of the circuit of the verifier
+    pub circuits_params: CircuitsParams,
+    /// Chain id of the verified blocks
+    pub chain_id: u64,
+}
+
+/// Reason why a [`ProofEnvelope`] is not compatible with a [`VerifierSetup`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The envelope format version is not supported
+    UnsupportedVersion(u32),
+    /// The proof is for another circuit
+    CircuitVersionMismatch {
+        /// Circuit of the verifier
+        expected: H256,
+        /// Circuit of the proof
+        found: H256,
+    },
+    /// The proof is for a circuit with other parameters
+    CircuitsParamsMismatch,
+    /// The proof is for another chain
+    ChainIdMismatch {
+        /// Chain id of the verifier
+        expected: u64,
+        /// Chain id of the proof
+        found: u64,
+    },
+    /// The end block is before the start block
+    InvalidBlockRange(u64, u64),
+    /// A public input is not a canonical field element
+    InvalidInstance,
+}
+
+impl Display for EnvelopeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+/// Check that the envelope is compatible with the verifier setup, before
+/// verifying the proof it carries against its public inputs.
+pub fn verify_envelope<F: Field>(
+    envelope: &ProofEnvelope,
+    setup: &VerifierSetup,
+) -> Result<Vec<Vec<F>>, EnvelopeError> {
+    if envelope.version != PROOF_ENVELOPE_VERSION {
+        return Err(EnvelopeError::UnsupportedVersion(envelope.version));
+    }
+    if envelope.circuit_version != setup.circuit_version {
+        return Err(EnvelopeError::CircuitVersionMismatch {
+            expected: setup.circuit_version,
+            found: envelope.circuit_version,
+        });
+    }
+    if envelope.circuits_params != setup.circuits_params {
+        return Err(EnvelopeError::CircuitsParamsMismatch);
+    }
+    if envelope.chain_id != setup.chain_id {
+        return Err(EnvelopeError::ChainIdMismatch {
+            expected: setup.chain_id,
+            found: envelope.chain_id,
+        });
+    }
+    if envelope.end_block < envelope.start_block {
+        return Err(EnvelopeError::InvalidBlockRange(
+            envelope.start_block,
+            envelope.end_block,
+        ));
+    }
+
+    envelope.instances().ok_or(EnvelopeError::InvalidInstance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    fn setup() -> VerifierSetup {
+        VerifierSetup {
+            circuit_version: H256::repeat_byte(0xab),
+            circuits_params: CircuitsParams::default(),
+            chain_id: 534352,
+        }
+    }
+
+    fn envelope() -> ProofEnvelope {
+        let setup = setup();
+        ProofEnvelope::new(
+            setup.circuit_version,
+            setup.circuits_params,
+            setup.chain_id,
+            (10, 12),
+            &[vec![Fr::from(1), -Fr::from(1)]],
+            vec![1, 2, 3],
+        )
+    }
+
+    #[test]
+    fn envelope_serde_roundtrip() {
+        let envelope = envelope();
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProofEnvelope>(&json).unwrap(),
+            envelope
+        );
+        assert_eq!(
+            verify_envelope::<Fr>(&envelope, &setup()),
+            Ok(vec![vec![Fr::from(1), -Fr::from(1)]])
+        );
+    }
+
+    #[test]
+    fn envelope_incompatible() {
+        let mut envelope = envelope();
+        envelope.version += 1;
+        assert_eq!(
+            verify_envelope::<Fr>(&envelope, &setup()),
+            Err(EnvelopeError::UnsupportedVersion(
+                PROOF_ENVELOPE_VERSION + 1
+            ))
+        );
+
+        let mut envelope = self::envelope();
+        envelope.circuits_params.max_txs += 1;
+        assert_eq!(
+            verify_envelope::<Fr>(&envelope, &setup()),
+            Err(EnvelopeError::CircuitsParamsMismatch)
+        );
+
+        let mut envelope = self::envelope();
+        envelope.chain_id = 1;
+        assert_eq!(
+            verify_envelope::<Fr>(&envelope, &setup()),
+            Err(EnvelopeError::ChainIdMismatch {
+                expected: 534352,
+                found: 1
+            })
+        );
+
+        let mut envelope = self::envelope();
+        envelope.instances[0][0] = Word::MAX;
+        assert_eq!(
+            verify_envelope::<Fr>(&envelope, &setup()),
+            Err(EnvelopeError::InvalidInstance)
+        );
+    }
+}
@@ -27,6 +27,10 @@ use strum::IntoEnumIterator;
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{LookupTable, RlpFsmRlpTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness::{
@@ -1880,3 +1884,23 @@ impl<F: Field> SubCircuit<F> for RlpCircuit<F, Transaction> {
         (sm_rows, max_num_rows)
     }
 }
+
+impl<F: Field> SuperSubCircuit<F> for RlpCircuit<F, Transaction> {
+    const NAME: &'static str = "rlp circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[(SharedTable::Rlp, 1)];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Rlp];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        RlpCircuitConfig::new(
+            meta,
+            RlpCircuitConfigArgs {
+                rlp_table: *tables.rlp(),
+                challenges: args.challenges.clone(),
+            },
+        )
+    }
+}
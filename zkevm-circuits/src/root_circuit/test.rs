@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 pub use super::*;
 use crate::super_circuit::{test::block_1tx, SuperCircuit};
-use bus_mapping::circuit_input_builder::CircuitsParams;
+use bus_mapping::circuit_input_builder::{CircuitProfile, CircuitsParams};
 use halo2_proofs::{
     circuit::Value,
     dev::MockProver,
@@ -23,6 +23,7 @@ fn test_root_circuit() {
         const MAX_TXS: usize = 1;
         const MAX_CALLDATA: usize = 32;
         const TEST_MOCK_RANDOMNESS: u64 = 0x100;
+        const MAX_INNER_BLOCKS: usize = 64;
         let circuits_params = CircuitsParams {
             max_txs: MAX_TXS,
             max_calldata: MAX_CALLDATA,
@@ -32,9 +33,13 @@ fn test_root_circuit() {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            keccak_rows_per_round: 0,
+            max_ecrecover_sigs: 0,
+            max_log_items: 0,
+            profile: CircuitProfile::Full,
         };
         let (k, circuit, instance, _) =
-            SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, TEST_MOCK_RANDOMNESS>::build(
+            SuperCircuit::<_, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>::build(
                 block_1tx(),
                 circuits_params,
             )
@@ -0,0 +1,381 @@
+//! Estimation of the rows that each transaction of a block, and each execution
+//! state within it, consumes in the sub-circuits. A sequencer can use it to
+//! find the transactions that do not fit in the circuits before building a
+//! block.
+//!
+//! The rows of the EVM, state, copy and exp circuits are attributed exactly to
+//! the step that generates them. The rows of the keccak and tx circuits are
+//! approximated from the inputs of each transaction, and the rows that do not
+//! depend on the transactions (fixed tables, padding, bytecodes) are not
+//! counted.
+
+pub mod packing;
+
+use crate::{
+    evm_circuit::step::ExecutionState,
+    exp_circuit::param::OFFSET_INCREMENT,
+    keccak_circuit::{keccak_packed_multi::get_num_rows_per_round, keccak_rows},
+    tx_circuit::{max_rlp_bytes, TX_LEN},
+    witness::{block_convert, Block},
+};
+use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams, CopyDataType};
+use eth_types::Field;
+use std::{collections::HashMap, ops::AddAssign};
+
+/// Rows used in each sub-circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RowUsage {
+    /// Rows of the EVM circuit
+    pub evm: usize,
+    /// Rows of the state circuit, one per rw operation
+    pub state: usize,
+    /// Rows of the copy circuit
+    pub copy: usize,
+    /// Rows of the exp circuit
+    pub exp: usize,
+    /// Rows of the keccak circuit
+    pub keccak: usize,
+    /// Rows of the tx circuit
+    pub tx: usize,
+}
+
+impl AddAssign for RowUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.evm += rhs.evm;
+        self.state += rhs.state;
+        self.copy += rhs.copy;
+        self.exp += rhs.exp;
+        self.keccak += rhs.keccak;
+        self.tx += rhs.tx;
+    }
+}
+
+impl RowUsage {
+    /// Rows available in each sub-circuit with the given parameters. A
+    /// parameter at 0 leaves the sub-circuit unbounded.
+    pub fn capacity(params: &CircuitsParams) -> Self {
+        Self {
+            evm: params.max_evm_rows,
+            state: params.max_rws,
+            copy: params.max_copy_rows,
+            exp: params.max_exp_steps,
+            keccak: params.max_keccak_rows,
+            tx: params.max_txs * TX_LEN
+                + params.max_calldata
+                + max_rlp_bytes(params.max_txs, params.max_calldata),
+        }
+    }
+
+    /// Names of the sub-circuits in which `self` uses more rows than
+    /// available in `capacity`.
+    pub fn exceeded(&self, capacity: &Self) -> Vec<&'static str> {
+        [
+            ("evm", self.evm, capacity.evm),
+            ("state", self.state, capacity.state),
+            ("copy", self.copy, capacity.copy),
+            ("exp", self.exp, capacity.exp),
+            ("keccak", self.keccak, capacity.keccak),
+            ("tx", self.tx, capacity.tx),
+        ]
+        .into_iter()
+        .filter(|(_, rows, available)| *available > 0 && rows > available)
+        .map(|(name, _, _)| name)
+        .collect()
+    }
+
+    /// Whether `self` fits in `capacity`.
+    pub fn fits(&self, capacity: &Self) -> bool {
+        self.exceeded(capacity).is_empty()
+    }
+}
+
+/// Rows used by a transaction.
+#[derive(Clone, Debug, Default)]
+pub struct TxRowUsage {
+    /// Rows used by the whole transaction
+    pub total: RowUsage,
+    /// Rows used by the steps of each execution state, which for most states
+    /// corresponds to an opcode
+    pub by_state: HashMap<ExecutionState, RowUsage>,
+}
+
+/// Rows used by the transactions of a block.
+#[derive(Clone, Debug, Default)]
+pub struct RowEstimate {
+    /// Rows used by each transaction, in block order
+    pub txs: Vec<TxRowUsage>,
+}
+
+impl RowEstimate {
+    /// Rows used by all the transactions.
+    pub fn total(&self) -> RowUsage {
+        let mut total = RowUsage::default();
+        for tx in &self.txs {
+            total += tx.total;
+        }
+        total
+    }
+
+    /// Indices of the transactions that alone exceed `capacity`, which can't
+    /// be included in any block.
+    pub fn txs_exceeding(&self, capacity: &RowUsage) -> Vec<usize> {
+        self.txs
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| !tx.total.fits(capacity))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Number of transactions, from the first one, that fit together in
+    /// `capacity`.
+    pub fn txs_fitting(&self, capacity: &RowUsage) -> usize {
+        let mut total = RowUsage::default();
+        self.txs
+            .iter()
+            .take_while(|tx| {
+                total += tx.total;
+                total.fits(capacity)
+            })
+            .count()
+    }
+}
+
+/// Estimate the rows used by the transactions of the witness block.
+pub fn estimate_rows<F: Field>(block: &Block<F>) -> RowEstimate {
+    // Copy and exp events are identified by the rw counter of the step that
+    // generates them, so attribute each one to the last step starting at or
+    // before that counter.
+    let mut steps = Vec::new();
+    for (tx_index, tx) in block.txs.iter().enumerate() {
+        for step in &tx.steps {
+            steps.push((step.rw_counter, tx_index, step.execution_state));
+        }
+    }
+    steps.sort_by_key(|(rw_counter, _, _)| *rw_counter);
+    let keccak_rows_per_round = get_num_rows_per_round(block.circuits_params.keccak_rows_per_round);
+    let step_at = |rw_counter: usize| {
+        let index = steps.partition_point(|(start, _, _)| *start <= rw_counter);
+        steps
+            .get(index.checked_sub(1)?)
+            .map(|(_, tx, state)| (*tx, *state))
+    };
+
+    let mut estimate = RowEstimate {
+        txs: vec![TxRowUsage::default(); block.txs.len()],
+    };
+    let mut add = |tx_index: usize, state: ExecutionState, usage: RowUsage| {
+        let tx = &mut estimate.txs[tx_index];
+        tx.total += usage;
+        *tx.by_state.entry(state).or_default() += usage;
+    };
+
+    for (tx_index, tx) in block.txs.iter().enumerate() {
+        for step in &tx.steps {
+            add(
+                tx_index,
+                step.execution_state,
+                RowUsage {
+                    evm: step.execution_state.get_step_height(),
+                    state: step.rw_indices.len(),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    for event in &block.copy_events {
+        if let Some((tx_index, state)) = step_at(event.rw_counter_start.0) {
+            add(
+                tx_index,
+                state,
+                RowUsage {
+                    copy: event.num_rows(),
+                    keccak: if event.dst_type == CopyDataType::RlcAcc {
+                        keccak_rows(event.bytes.len(), keccak_rows_per_round)
+                    } else {
+                        0
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    for event in &block.exp_events {
+        if let Some((tx_index, state)) = step_at(event.identifier) {
+            add(
+                tx_index,
+                state,
+                RowUsage {
+                    exp: event.steps.len() * OFFSET_INCREMENT,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    // The tx circuit and the hashes of the signed and unsigned RLP encodings
+    // are not tied to a step.
+    for (tx, usage) in block.txs.iter().zip(estimate.txs.iter_mut()) {
+        usage.total += RowUsage {
+            keccak: keccak_rows(tx.rlp_signed.len(), keccak_rows_per_round)
+                + keccak_rows(tx.rlp_unsigned.len(), keccak_rows_per_round),
+            tx: TX_LEN + tx.call_data.len() + tx.rlp_signed.len(),
+            ..Default::default()
+        };
+    }
+    estimate
+}
+
+/// Estimate the rows used by the transactions handled by the builder.
+pub fn estimate_rows_from_builder<F: Field>(
+    builder: &CircuitInputBuilder,
+) -> Result<RowEstimate, bus_mapping::Error> {
+    let block = block_convert::<F>(&builder.block, &builder.code_db)?;
+    Ok(estimate_rows(&block))
+}
+
+#[cfg(test)]
+mod row_estimator_tests {
+    use super::*;
+    use bus_mapping::{circuit_input_builder::MemoryCopy, mock::BlockData};
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn estimate(code: eth_types::Bytecode) -> (RowEstimate, Block<Fr>) {
+        estimate_block(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+                .unwrap()
+                .into(),
+        )
+    }
+
+    fn estimate_block(block: GethData) -> (RowEstimate, Block<Fr>) {
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let witness = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        (estimate_rows(&witness), witness)
+    }
+
+    #[test]
+    fn row_estimate_matches_block_rows() {
+        let (estimate, block) = estimate(bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            PUSH1(0x03)
+            PUSH1(0x02)
+            EXP
+            STOP
+        });
+        let total = estimate.total();
+
+        let steps = block.txs.iter().flat_map(|tx| &tx.steps);
+        assert_eq!(
+            total.evm,
+            steps
+                .clone()
+                .map(|step| step.execution_state.get_step_height())
+                .sum::<usize>()
+        );
+        assert_eq!(
+            total.state,
+            steps.map(|step| step.rw_indices.len()).sum::<usize>()
+        );
+        assert_eq!(
+            total.copy,
+            block
+                .copy_events
+                .iter()
+                .map(|event| event.num_rows())
+                .sum::<usize>()
+        );
+        let by_state = &estimate.txs[0].by_state;
+        assert!(by_state[&ExecutionState::SHA3].copy > 0);
+        assert!(by_state[&ExecutionState::SHA3].keccak > 0);
+        assert!(by_state[&ExecutionState::EXP].exp > 0);
+    }
+
+    #[test]
+    fn row_estimate_predicted_by_memory_copies() {
+        for (opcode, state) in [
+            (OpcodeId::CALLDATACOPY, ExecutionState::CALLDATACOPY),
+            (OpcodeId::CODECOPY, ExecutionState::CODECOPY),
+            (OpcodeId::RETURNDATACOPY, ExecutionState::RETURNDATACOPY),
+        ] {
+            // copies the return data of the identity precompile, given the
+            // calldata of the tx as input
+            let mut code = bytecode! {
+                CALLDATASIZE
+                PUSH1(0x00)
+                PUSH1(0x00)
+                CALLDATACOPY
+                PUSH1(0x00)
+                PUSH1(0x00)
+                CALLDATASIZE
+                PUSH1(0x00)
+                PUSH1(0x04)
+                GAS
+                STATICCALL
+                POP
+            };
+            code.push(1, 0x45u64); // length
+            code.push(1, 0x00u64); // offset
+            code.push(1, 0x31u64); // memory offset
+            code.write_op(opcode);
+            code.write_op(OpcodeId::STOP);
+
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(code),
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[1].address)
+                        .to(accs[0].address)
+                        .input(vec![0xab; 0x45].into());
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap()
+            .into();
+            let step = block.geth_traces[0]
+                .struct_logs
+                .iter()
+                .rev()
+                .find(|step| step.op == opcode)
+                .unwrap();
+            let memory_copy = MemoryCopy::from_stack(opcode, &step.stack)
+                .unwrap()
+                .unwrap();
+            assert_eq!(memory_copy.num_copy_bytes(), 0x45);
+
+            let (estimate, _) = estimate_block(block);
+            let copy_rows = estimate.txs[0].by_state[&state].copy;
+            if opcode == OpcodeId::CALLDATACOPY {
+                // the calldata is copied at the start of the code too
+                assert_eq!(copy_rows, memory_copy.copy_rows() + 2 * 0x45);
+            } else {
+                assert_eq!(copy_rows, memory_copy.copy_rows());
+            }
+        }
+    }
+
+    #[test]
+    fn row_estimate_capacity() {
+        let (estimate, _) = estimate(bytecode! { STOP });
+        let total = estimate.total();
+
+        assert_eq!(estimate.txs_fitting(&total), 1);
+        assert!(estimate.txs_exceeding(&total).is_empty());
+
+        let capacity = RowUsage {
+            state: total.state - 1,
+            ..total
+        };
+        assert_eq!(total.exceeded(&capacity), vec!["state"]);
+        assert_eq!(estimate.txs_fitting(&capacity), 0);
+        assert_eq!(estimate.txs_exceeding(&capacity), vec![0]);
+    }
+}
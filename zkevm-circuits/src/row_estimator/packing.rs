@@ -0,0 +1,144 @@
+//! Packing of pending transactions into chunks that fit in the circuits.
+
+use super::{RowEstimate, RowUsage};
+use bus_mapping::circuit_input_builder::CircuitsParams;
+
+/// Limits of a chunk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PackingBudget {
+    /// Rows available in each sub-circuit, 0 for an unbounded sub-circuit
+    pub rows: RowUsage,
+    /// Maximum number of transactions, 0 for no maximum
+    pub max_txs: usize,
+}
+
+impl PackingBudget {
+    /// Budget of a chunk proved with the given parameters.
+    pub fn from_params(params: &CircuitsParams) -> Self {
+        Self {
+            rows: RowUsage::capacity(params),
+            max_txs: params.max_txs,
+        }
+    }
+}
+
+/// Chunk of a [`PackingPlan`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Chunk {
+    /// Indices of the transactions of the chunk, in the pending order
+    pub txs: Vec<usize>,
+    /// Rows used by the transactions of the chunk
+    pub rows: RowUsage,
+}
+
+/// Assignment of the pending transactions to chunks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackingPlan {
+    /// Chunks to produce, in order
+    pub chunks: Vec<Chunk>,
+    /// Indices of the transactions that alone exceed the budget, and can't be
+    /// included in any chunk
+    pub rejected: Vec<usize>,
+}
+
+/// Pack the transactions, given by the rows they use, into chunks under the
+/// budget. The transactions are taken in order, so that transactions of the
+/// same sender stay in nonce order, and a new chunk is started as soon as the
+/// next transaction does not fit in the current one.
+pub fn pack_txs(txs: impl IntoIterator<Item = RowUsage>, budget: &PackingBudget) -> PackingPlan {
+    let mut plan = PackingPlan::default();
+    let mut chunk = Chunk::default();
+    for (index, rows) in txs.into_iter().enumerate() {
+        if !rows.fits(&budget.rows) {
+            log::debug!(
+                "tx {} exceeds the budget in {:?}",
+                index,
+                rows.exceeded(&budget.rows)
+            );
+            plan.rejected.push(index);
+            continue;
+        }
+        let mut rows_with_tx = chunk.rows;
+        rows_with_tx += rows;
+        let is_full = budget.max_txs > 0 && chunk.txs.len() == budget.max_txs;
+        if is_full || !rows_with_tx.fits(&budget.rows) {
+            plan.chunks.push(std::mem::take(&mut chunk));
+            rows_with_tx = rows;
+        }
+        chunk.txs.push(index);
+        chunk.rows = rows_with_tx;
+    }
+    if !chunk.txs.is_empty() {
+        plan.chunks.push(chunk);
+    }
+    plan
+}
+
+impl RowEstimate {
+    /// Pack the estimated transactions into chunks under the budget.
+    pub fn pack(&self, budget: &PackingBudget) -> PackingPlan {
+        pack_txs(self.txs.iter().map(|tx| tx.total), budget)
+    }
+}
+
+#[cfg(test)]
+mod packing_tests {
+    use super::*;
+
+    fn rows(evm: usize, state: usize) -> RowUsage {
+        RowUsage {
+            evm,
+            state,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pack_txs_under_row_budget() {
+        let budget = PackingBudget {
+            rows: rows(100, 50),
+            max_txs: 0,
+        };
+        let plan = pack_txs(
+            [
+                rows(60, 10),
+                rows(30, 30),
+                rows(20, 20),
+                rows(101, 1),
+                rows(10, 10),
+            ],
+            &budget,
+        );
+        assert_eq!(plan.rejected, vec![3]);
+        assert_eq!(
+            plan.chunks,
+            vec![
+                Chunk {
+                    txs: vec![0, 1],
+                    rows: rows(90, 40),
+                },
+                Chunk {
+                    txs: vec![2, 4],
+                    rows: rows(30, 30),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pack_txs_under_tx_budget() {
+        let budget = PackingBudget {
+            rows: rows(0, 0),
+            max_txs: 2,
+        };
+        let plan = pack_txs(vec![rows(1, 1); 5], &budget);
+        assert!(plan.rejected.is_empty());
+        assert_eq!(
+            plan.chunks
+                .iter()
+                .map(|chunk| chunk.txs.clone())
+                .collect::<Vec<_>>(),
+            vec![vec![0, 1], vec![2, 3], vec![4]]
+        );
+    }
+}
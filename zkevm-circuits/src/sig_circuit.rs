@@ -0,0 +1,246 @@
+//! Circuit verifying the signatures recovered by the ECRecover calls and the
+//! ones of the P256VERIFY calls, which the EVM circuit looks up from the sig
+//! and P256 verify tables.
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::SigCircuit as TestSigCircuit;
+
+use crate::{
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
+    table::{KeccakTable, LookupTable, P256VerifyTable, SigTable},
+    tx_circuit::sign_verify::{SignVerifyChip, SignVerifyConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use bus_mapping::precompile::P256VerifyInput;
+use eth_types::{sign_types::SignData, Field};
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, Expression},
+};
+use log::error;
+
+/// Config of the sig circuit
+#[derive(Clone, Debug)]
+pub struct SigCircuitConfig<F: Field> {
+    sign_verify: SignVerifyConfig<F>,
+    /// The sig table, holding the verified signatures
+    pub sig_table: SigTable,
+    /// The P256 verify table, holding the valid P256VERIFY inputs
+    pub p256_verify_table: P256VerifyTable,
+}
+
+/// Circuit configuration arguments
+pub struct SigCircuitConfigArgs<F: Field> {
+    /// KeccakTable, hashing the recovered public keys
+    pub keccak_table: KeccakTable,
+    /// SigTable
+    pub sig_table: SigTable,
+    /// P256VerifyTable
+    pub p256_verify_table: P256VerifyTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for SigCircuitConfig<F> {
+    type ConfigArgs = SigCircuitConfigArgs<F>;
+
+    /// Return a new SigCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            keccak_table,
+            sig_table,
+            p256_verify_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let sign_verify = SignVerifyConfig::new(meta, keccak_table, challenges);
+        sig_table.annotate_columns(meta);
+        p256_verify_table.annotate_columns(meta);
+
+        Self {
+            sign_verify,
+            sig_table,
+            p256_verify_table,
+        }
+    }
+}
+
+/// Sig circuit, verifying the signatures of the ECRecover calls of a block.
+/// The rows of the sig table are copied from the cells of the verified
+/// signatures, so that an ECRecover call can only return the address of a
+/// public key verifying its signature.
+///
+/// Without the `enable-sign-verify` feature the signatures aren't verified,
+/// and the sig table is assigned from the witness.
+///
+/// The P256VERIFY signatures are never verified, as there is no P-256 curve
+/// for the ECDSA chip yet: with the `enable-sign-verify` feature, a block with
+/// a valid P256VERIFY call is refused rather than proved unsoundly, and without
+/// it the P256 verify table is assigned from the witness.
+#[derive(Clone, Debug, Default)]
+pub struct SigCircuit<F: Field> {
+    /// Max number of verified signatures
+    pub max_verif: usize,
+    /// Signatures recovered by the ECRecover calls
+    pub signatures: Vec<SignData>,
+    /// Inputs of the P256VERIFY calls with a valid signature
+    pub p256_verify_inputs: Vec<P256VerifyInput>,
+    sign_verify: SignVerifyChip<F>,
+}
+
+impl<F: Field> SigCircuit<F> {
+    /// Return a new SigCircuit
+    pub fn new(
+        max_verif: usize,
+        signatures: Vec<SignData>,
+        p256_verify_inputs: Vec<P256VerifyInput>,
+    ) -> Self {
+        Self {
+            max_verif,
+            signatures,
+            p256_verify_inputs,
+            sign_verify: SignVerifyChip::new(max_verif),
+        }
+    }
+
+    /// Return the minimum number of rows required to verify `num_verif`
+    /// signatures.
+    pub fn min_num_rows(num_verif: usize) -> usize {
+        #[cfg(feature = "enable-sign-verify")]
+        let min_rows = std::cmp::max(num_verif, SignVerifyChip::<F>::min_num_rows(num_verif));
+        #[cfg(not(feature = "enable-sign-verify"))]
+        let min_rows = num_verif;
+        min_rows
+    }
+}
+
+impl<F: Field> SubCircuit<F> for SigCircuit<F> {
+    type Config = SigCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // The rlc column of the SignVerifyConfig is queried at 3 distinct
+        // rotations, plus the blinding rows.
+        7
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let signatures = block.ecrecover_sign_datas();
+        let max_verif = match block.circuits_params.max_ecrecover_sigs {
+            0 => signatures.len(),
+            max_verif => max_verif,
+        };
+        Self::new(max_verif, signatures, block.p256_verify_inputs())
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let num_verif = block.ecrecover_sign_datas().len();
+        (
+            Self::min_num_rows(num_verif),
+            Self::min_num_rows(std::cmp::max(
+                num_verif,
+                block.circuits_params.max_ecrecover_sigs,
+            )),
+        )
+    }
+
+    /// Make the assignments to the SigCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.signatures.len() > self.max_verif {
+            error!(
+                "signatures.len() = {} > max_verif = {}",
+                self.signatures.len(),
+                self.max_verif
+            );
+            return Err(Error::Synthesis);
+        }
+
+        #[cfg(feature = "enable-sign-verify")]
+        {
+            if !self.p256_verify_inputs.is_empty() {
+                error!(
+                    "{} P256VERIFY signatures can't be verified",
+                    self.p256_verify_inputs.len()
+                );
+                return Err(Error::Synthesis);
+            }
+            config.sign_verify.load_range(layouter)?;
+            let assigned_sig_verifs = self.sign_verify.assign(
+                &config.sign_verify,
+                layouter,
+                &self.signatures,
+                challenges,
+            )?;
+            self.sign_verify.assert_sig_is_valid(
+                &config.sign_verify,
+                layouter,
+                &assigned_sig_verifs[..self.signatures.len()],
+            )?;
+            layouter.assign_region(
+                || "sig table",
+                |mut region| {
+                    let rows = assigned_sig_verifs[..self.signatures.len()]
+                        .iter()
+                        .map(|sig_verif| sig_verif.sig_table_row.clone().map(|cell| cell.value))
+                        .collect::<Vec<_>>();
+                    let cells = config.sig_table.assign(&mut region, &rows)?;
+                    for (sig_verif, row_cells) in assigned_sig_verifs.iter().zip(cells) {
+                        for (verified, cell) in sig_verif.sig_table_row.iter().zip(row_cells) {
+                            region.constrain_equal(verified.cell, cell.cell())?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+        #[cfg(not(feature = "enable-sign-verify"))]
+        {
+            config
+                .sig_table
+                .dev_load(layouter, &self.signatures, challenges)?;
+            config
+                .p256_verify_table
+                .dev_load(layouter, &self.p256_verify_inputs, challenges)
+        }
+    }
+}
+
+impl<F: Field> SuperSubCircuit<F> for SigCircuit<F> {
+    const NAME: &'static str = "sig circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[
+        (SharedTable::Keccak, 1),
+        (SharedTable::Sig, 1),
+        (SharedTable::P256Verify, 1),
+    ];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Sig, SharedTable::P256Verify];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        SigCircuitConfig::new(
+            meta,
+            SigCircuitConfigArgs {
+                keccak_table: tables.keccak().clone(),
+                sig_table: tables.sig().clone(),
+                p256_verify_table: tables.p256_verify().clone(),
+                challenges: args.challenges.clone(),
+            },
+        )
+    }
+}
@@ -0,0 +1,58 @@
+pub use super::SigCircuit;
+
+use crate::{
+    sig_circuit::{SigCircuitConfig, SigCircuitConfigArgs},
+    table::{KeccakTable, P256VerifyTable, SigTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for SigCircuit<F> {
+    type Config = (SigCircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let keccak_table = KeccakTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
+        let p256_verify_table = P256VerifyTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            SigCircuitConfig::new(
+                meta,
+                SigCircuitConfigArgs {
+                    keccak_table,
+                    sig_table,
+                    p256_verify_table,
+                    challenges,
+                },
+            )
+        };
+
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+        config.sign_verify.keccak_table.dev_load(
+            &mut layouter,
+            &keccak_inputs_sign_verify(&self.signatures),
+            &challenges,
+        )?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
@@ -0,0 +1,81 @@
+#![allow(unused_imports)]
+use crate::{
+    sig_circuit::SigCircuit,
+    tx_circuit::sign_verify::TOTAL_NUM_ROWS,
+    util::{unusable_rows, SubCircuit},
+};
+use bus_mapping::precompile::P256VerifyInput;
+use eth_types::{
+    sign_types::{sign, SignData},
+    Bytes,
+};
+use halo2_proofs::{
+    arithmetic::Field as HaloField,
+    dev::{MockProver, VerifyFailure},
+    halo2curves::{
+        bn256::Fr,
+        group::{prime::PrimeCurveAffine, Curve},
+        secp256k1::{self, Secp256k1Affine},
+    },
+};
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+#[test]
+fn sig_circuit_unusable_rows() {
+    assert_eq!(
+        SigCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, SigCircuit::<Fr>>(),
+    )
+}
+
+fn gen_sign_datas(num_sigs: usize) -> Vec<SignData> {
+    let mut rng = XorShiftRng::seed_from_u64(2);
+    (0..num_sigs)
+        .map(|_| {
+            let sk = secp256k1::Fq::random(&mut rng);
+            let pk = (Secp256k1Affine::generator() * sk).to_affine();
+            let msg_hash = secp256k1::Fq::random(&mut rng);
+            let randomness = secp256k1::Fq::random(&mut rng);
+            SignData {
+                signature: sign(randomness, sk, msg_hash),
+                pk,
+                msg: Bytes::default(),
+                msg_hash,
+            }
+        })
+        .collect()
+}
+
+fn run(max_verif: usize, signatures: Vec<SignData>) -> Result<(), Vec<VerifyFailure>> {
+    let circuit = SigCircuit::<Fr>::new(max_verif, signatures, vec![]);
+    let prover = MockProver::run(TOTAL_NUM_ROWS as u32, &circuit, vec![]).unwrap();
+    prover.verify_par()
+}
+
+#[test]
+fn sig_circuit_valid_signatures() {
+    assert_eq!(run(2, gen_sign_datas(2)), Ok(()));
+}
+
+#[test]
+fn sig_circuit_too_many_signatures() {
+    let circuit = SigCircuit::<Fr>::new(1, gen_sign_datas(2), vec![]);
+    assert!(MockProver::run(TOTAL_NUM_ROWS as u32, &circuit, vec![]).is_err());
+}
+
+#[cfg(feature = "enable-sign-verify")]
+#[test]
+fn sig_circuit_forged_pub_key() {
+    let mut signatures = gen_sign_datas(2);
+    signatures[1].pk = (signatures[1].pk + Secp256k1Affine::generator()).to_affine();
+    assert!(run(2, signatures).is_err());
+}
+
+#[cfg(feature = "enable-sign-verify")]
+#[test]
+fn sig_circuit_refuses_p256_verify() {
+    let input = P256VerifyInput::parse(&[1; P256VerifyInput::LEN]).unwrap();
+    let circuit = SigCircuit::<Fr>::new(1, gen_sign_datas(1), vec![input]);
+    assert!(MockProver::run(TOTAL_NUM_ROWS as u32, &circuit, vec![]).is_err());
+}
@@ -18,13 +18,20 @@ use self::{
     lexicographic_ordering::LimbIndex,
 };
 use crate::{
-    evm_circuit::{param::N_BYTES_WORD, util::rlc},
+    evm_circuit::param::N_BYTES_WORD,
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{AccountFieldTag, LookupTable, MPTProofType, MptTable, RwTable, RwTableTag},
-    util::{Challenges, Expr, SubCircuit, SubCircuitConfig},
-    witness::{self, MptUpdates, Rw, RwMap},
+    util::{
+        rw_fingerprint::{RwFingerprintConfig, RwFingerprints},
+        word, Challenges, Expr, SubCircuit, SubCircuitConfig,
+    },
+    witness::{self, MptUpdates, Rw, RwMap, RwRow},
 };
 use constraint_builder::{ConstraintBuilder, Queries};
-use eth_types::{Address, Field, ToLittleEndian};
+use eth_types::{Address, Field};
 use gadgets::{
     batched_is_zero::{BatchedIsZeroChip, BatchedIsZeroConfig},
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
@@ -72,6 +79,7 @@ pub struct StateCircuitConfig<F> {
     not_first_access: Column<Advice>,
     lookups: LookupsConfig,
     power_of_randomness: [Expression<F>; N_BYTES_WORD - 1],
+    rw_fingerprint: RwFingerprintConfig,
     // External tables
     mpt_table: MptTable,
 }
@@ -151,12 +159,10 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
             rw_counter,
         };
 
-        let lexicographic_ordering = LexicographicOrderingConfig::configure(
-            meta,
-            sort_keys,
-            lookups,
-            power_of_randomness.clone(),
-        );
+        let lexicographic_ordering =
+            LexicographicOrderingConfig::configure(meta, sort_keys, lookups);
+
+        let rw_fingerprint = RwFingerprintConfig::configure(meta, &rw_table, &challenges);
 
         // annotate columns
         rw_table.annotate_columns(meta);
@@ -173,6 +179,7 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
             not_first_access: meta.advice_column(),
             lookups,
             power_of_randomness,
+            rw_fingerprint,
             rw_table,
             mpt_table,
         };
@@ -235,7 +242,7 @@ impl<F: Field> StateCircuitConfig<F> {
         let rows_len = rows.len();
 
         let mut state_root =
-            randomness.map(|randomness| rlc::value(&updates.old_root().to_le_bytes(), randomness));
+            randomness.map(|randomness| word::rlc(&updates.old_root(), randomness));
 
         let mut start_state_root: Option<AssignedCell<_, F>> = None;
         let mut end_state_root: Option<AssignedCell<_, F>> = None;
@@ -279,8 +286,7 @@ impl<F: Field> StateCircuitConfig<F> {
                 let index = self
                     .lexicographic_ordering
                     .assign(region, offset, row, prev_row)?;
-                let is_first_access =
-                    !matches!(index, LimbIndex::RwCounter0 | LimbIndex::RwCounter1);
+                let is_first_access = !matches!(index, LimbIndex::RwCounter);
 
                 region.assign_advice(
                     || "not_first_access",
@@ -464,7 +470,10 @@ pub struct StateCircuit<F> {
     pub rows: Vec<Rw>,
     pub(crate) updates: MptUpdates,
     pub(crate) n_rows: usize,
+    /// Rows of the mpt table, which the super circuit loads with the updates
+    pub(crate) max_mpt_rows: usize,
     pub(crate) exports: std::cell::RefCell<Option<StateCircuitExports<Assigned<F>>>>,
+    pub(crate) rw_fingerprints: std::cell::RefCell<Option<RwFingerprints<Assigned<F>>>>,
     #[cfg(any(feature = "test", test, feature = "test-circuits"))]
     overrides: HashMap<(dev::AdviceColumn, isize), F>,
     _marker: PhantomData<F>,
@@ -484,7 +493,9 @@ impl<F: Field> StateCircuit<F> {
             rows,
             updates,
             exports: std::cell::RefCell::new(None),
+            rw_fingerprints: std::cell::RefCell::new(None),
             n_rows,
+            max_mpt_rows: n_rows,
             #[cfg(any(feature = "test", test, feature = "test-circuits"))]
             overrides: HashMap::new(),
             _marker: PhantomData::default(),
@@ -492,17 +503,56 @@ impl<F: Field> StateCircuit<F> {
     }
 }
 
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+impl<F: Field> StateCircuit<F> {
+    /// Apply the `overrides` of the rw table columns to the values of its
+    /// rows, so that the fingerprint matches the assigned table.
+    fn override_rw_table_rows(&self, config: &StateCircuitConfig<F>, rows: &mut [RwRow<Value<F>>]) {
+        let padding_length = RwMap::padding_len(self.rows.len(), self.n_rows);
+        for ((column, row_offset), &f) in &self.overrides {
+            let offset = isize::try_from(padding_length).unwrap() + *row_offset;
+            let row = match usize::try_from(offset)
+                .ok()
+                .and_then(|offset| rows.get_mut(offset))
+            {
+                Some(row) => row,
+                None => continue,
+            };
+            let rw_table = &config.rw_table;
+            for (table_column, value) in [
+                (rw_table.rw_counter, &mut row.rw_counter),
+                (rw_table.is_write, &mut row.is_write),
+                (rw_table.tag, &mut row.tag),
+                (rw_table.id, &mut row.id),
+                (rw_table.address, &mut row.address),
+                (rw_table.field_tag, &mut row.field_tag),
+                (rw_table.storage_key, &mut row.storage_key),
+                (rw_table.value, &mut row.value),
+                (rw_table.value_prev, &mut row.value_prev),
+                (rw_table.aux1, &mut row.aux1),
+                (rw_table.aux2, &mut row.aux2),
+            ] {
+                if table_column == column.value(config) {
+                    *value = Value::known(f);
+                }
+            }
+        }
+    }
+}
+
 impl<F: Field> SubCircuit<F> for StateCircuit<F> {
     type Config = StateCircuitConfig<F>;
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        let rows = block.rws.table_assignments();
+        let rows = block.sorted_rws().to_vec();
         let updates = block.mpt_updates.clone();
         Self {
             rows,
             updates,
             exports: std::cell::RefCell::new(None),
+            rw_fingerprints: std::cell::RefCell::new(None),
             n_rows: block.circuits_params.max_rws,
+            max_mpt_rows: block.circuits_params.max_mpt_rows,
             #[cfg(any(feature = "test", test, feature = "test-circuits"))]
             overrides: HashMap::new(),
             _marker: PhantomData::default(),
@@ -570,6 +620,24 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
                     self.exports.borrow_mut().replace(exports);
                 }
 
+                let (rows, _) = RwMap::table_assignments_prepad(&self.rows, self.n_rows);
+                #[allow(unused_mut)]
+                let mut rows: Vec<_> = rows
+                    .iter()
+                    .map(|row| row.table_assignment(randomness))
+                    .collect();
+                #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+                self.override_rw_table_rows(config, &mut rows);
+                let rw_fingerprints = config.rw_fingerprint.assign(
+                    &mut region,
+                    &rows,
+                    Value::known(F::one()),
+                    challenges,
+                )?;
+                if self.rw_fingerprints.borrow().is_none() {
+                    self.rw_fingerprints.borrow_mut().replace(rw_fingerprints);
+                }
+
                 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
                 {
                     let padding_length = RwMap::padding_len(self.rows.len(), self.n_rows);
@@ -598,10 +666,43 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
     }
 }
 
+impl<F: Field> SuperSubCircuit<F> for StateCircuit<F> {
+    const NAME: &'static str = "state circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[(SharedTable::Rw, 1), (SharedTable::Mpt, 1)];
+    const ASSIGNS: &'static [SharedTable] = &[SharedTable::Rw, SharedTable::Mpt];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        StateCircuitConfig::new(
+            meta,
+            StateCircuitConfigArgs {
+                rw_table: *tables.rw(),
+                mpt_table: *tables.mpt(),
+                challenges: args.challenges.clone(),
+            },
+        )
+    }
+
+    fn load_tables(
+        &self,
+        tables: &SharedTables,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        tables.mpt().load(
+            layouter,
+            &self.updates,
+            self.max_mpt_rows,
+            challenges.evm_word(),
+        )
+    }
+}
+
 fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>) -> Queries<F> {
     let first_different_limb = c.lexicographic_ordering.first_different_limb;
-    let final_bits_sum = meta.query_advice(first_different_limb.bits[3], Rotation::cur())
-        + meta.query_advice(first_different_limb.bits[4], Rotation::cur());
 
     Queries {
         selector: meta.query_fixed(c.selector, Rotation::cur()),
@@ -640,15 +741,12 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>)
             .bits
             .map(|bit| meta.query_advice(bit, Rotation::cur())),
         id: MpiQueries::new(meta, c.sort_keys.id),
-        // this isn't binary! only 0 if most significant 3 bits are all 0 and at most 1 of the two
-        // least significant bits is 1.
-        // TODO: this can mask off just the top 3 bits if you want, since the 4th limb index is
-        // Address9, which is always 0 for Rw::Stack rows.
-        is_tag_and_id_unchanged: 4.expr()
-            * (meta.query_advice(first_different_limb.bits[0], Rotation::cur())
-                + meta.query_advice(first_different_limb.bits[1], Rotation::cur())
-                + meta.query_advice(first_different_limb.bits[2], Rotation::cur()))
-            + final_bits_sum.clone() * (1.expr() - final_bits_sum),
+        // this isn't binary! only 0 if most significant 3 bits are all 0, i.e. the first
+        // different limb is LimbIndex::Tag or LimbIndex::Id. The latter also holds the most
+        // significant bytes of the address, which are always 0 for Rw::Stack rows.
+        is_tag_and_id_unchanged: meta.query_advice(first_different_limb.bits[0], Rotation::cur())
+            + meta.query_advice(first_different_limb.bits[1], Rotation::cur())
+            + meta.query_advice(first_different_limb.bits[2], Rotation::cur()),
         address: MpiQueries::new(meta, c.sort_keys.address),
         storage_key: RlcQueries::new(meta, c.sort_keys.storage_key),
         initial_value: meta.query_advice(c.initial_value, Rotation::cur()),
@@ -130,16 +130,19 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::TxLog), |cb| {
             cb.build_tx_log_constraints(q)
         });
+        self.condition(q.tag_matches(RwTableTag::TransientStorage), |cb| {
+            cb.build_transient_storage_constraints(q)
+        });
     }
 
     fn build_general_constraints(&mut self, q: &Queries<F>) {
         // tag value in RwTableTag range is enforced in BinaryNumberChip
         self.require_boolean("is_write is boolean", q.is_write());
 
-        // 1 if first_different_limb is in the rw counter, 0 otherwise (i.e. any of the
-        // 4 most significant bits are 0)
+        // 1 if first_different_limb is the rw counter, 0 otherwise (i.e. any of its 4
+        // bits is 0)
         self.require_equal(
-            "not_first_access when first 16 limbs are same",
+            "not_first_access when first 15 limbs are same",
             q.not_first_access.clone(),
             q.first_different_limb[0].clone()
                 * q.first_different_limb[1].clone()
@@ -523,6 +526,27 @@ impl<F: Field> ConstraintBuilder<F> {
         );
     }
 
+    fn build_transient_storage_constraints(&mut self, q: &Queries<F>) {
+        self.require_zero("field_tag is 0 for TransientStorage", q.field_tag());
+        // The transient storage is empty at the start of each tx, whose id is
+        // a key of the rows, and is discarded at its end without any row.
+        self.require_zero("initial TransientStorage value is 0", q.initial_value());
+        self.require_equal(
+            "state_root is unchanged for TransientStorage",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        // The reversion of a write is a write back to its previous value, so
+        // the values of an access group chain through reverts.
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
     fn require_zero(&mut self, name: &'static str, e: Expression<F>) {
         self.constraints.push((name, self.condition.clone() * e));
     }
@@ -80,8 +80,7 @@ pub enum AdviceColumn {
     LimbIndexBit0, // most significant bit
     LimbIndexBit1,
     LimbIndexBit2,
-    LimbIndexBit3,
-    LimbIndexBit4, // least significant bit
+    LimbIndexBit3, // least significant bit
     InitialValue,
     IsZero, // committed_value and value are 0
     // NonEmptyWitness is the BatchedIsZero chip witness that contains the
@@ -113,7 +112,6 @@ impl AdviceColumn {
             Self::LimbIndexBit1 => config.lexicographic_ordering.first_different_limb.bits[1],
             Self::LimbIndexBit2 => config.lexicographic_ordering.first_different_limb.bits[2],
             Self::LimbIndexBit3 => config.lexicographic_ordering.first_different_limb.bits[3],
-            Self::LimbIndexBit4 => config.lexicographic_ordering.first_different_limb.bits[4],
             Self::InitialValue => config.initial_value,
             Self::IsZero => config.is_non_exist.is_zero,
             Self::NonEmptyWitness => config.is_non_exist.nonempty_witness,
@@ -15,58 +15,46 @@ use strum_macros::EnumIter;
 // We use this chip to show that the rows of the rw table are in lexicographic
 // order, i.e. ordered by (tag, id, address, field_tag, storage_key, and
 // rw_counter). We do this by packing these 6 fields into a 512 bit value X, and
-// then showing that X_cur > X_prev. Let A0, A1, ..., A31 be the 32 16-bit limbs
-// of X_cur and B0, B1, ..., B31 be 32 16-bit limbs of X_prev, in big endian
+// then showing that X_cur > X_prev. Let A0, A1, ..., A15 be the 16 32-bit limbs
+// of X_cur and B0, B1, ..., B15 be 16 32-bit limbs of X_prev, in big endian
 // order.
 
-// Let
-// C0 = A0 - B0,
-// C1 = C0 << 16 + A1 - B1,
-// ...
-// C31 = C30 << 16 + A31 - B21.
-
-// X_cur > X_prev iff one of C0, ..., C31 is non-zero and fits into 16 bits.
-// C16, ..., C31 do not necessarily fit into a field element, so to check that
-// Cn fits into 16 bits, we use an RLC to check that Cn-1 = 0 and then do a
-// lookup for An-Bn.
+// X_cur > X_prev iff there is an index i such that A0 = B0, ..., Ai-1 = Bi-1
+// and Ai - Bi is in [1, 2^32). Every 32-bit limb is built from 16-bit limbs of
+// the sort keys that are range checked, so the limb differences are in
+// (-2^32, 2^32), and we can check that the limbs before i are equal by packing
+// their differences into field elements: a packed difference of up to 7 limbs
+// is less than 2^224 in absolute value and is zero iff all its limb
+// differences are zero.
 
 // We show this with following advice columns and constraints:
 // - first_different_limb: first index where the limbs differ. We use a BinaryNumberChip here to
 //   reduce the degree of the constraints.
-// - limb_difference: the difference between the limbs at first_different_limb.
-// - limb_difference_inverse: the inverse of limb_difference
-
-//  1. limb_difference fits into 16 bits.
-//  2. limb_difference is not zero because its inverse exists.
-//  3. RLC of the pairwise limb differences before the first_different_limb is
-//     zero.
-//  4. limb_difference equals the difference of the limbs at
+// - limb_difference_hi, limb_difference_lo: the 16-bit limbs of the difference minus 1 between the
+//   limbs at first_different_limb.
+
+//  1. limb_difference_hi and limb_difference_lo fit into 16 bits, so the limb
+//     difference is in [1, 2^32].
+//  2. The packed limb differences before the first_different_limb are zero.
+//  3. The limb difference equals the difference of the limbs at
 //     first_different_limb.
 
+// Compared to an RLC of the limb differences, the packing doesn't need a
+// challenge, and comparing 32-bit limbs halves the limb indices, which lowers
+// the degree of the constraints selecting them.
+
 #[derive(Clone, Copy, Debug, EnumIter)]
 pub enum LimbIndex {
+    /// 0, tag, and the 2 most significant bytes of id
     Tag,
-    Id1,
-    Id0,
-    Address9,
-    Address8,
-    Address7,
-    Address6,
-    Address5,
+    /// 2 least significant bytes of id and 2 most significant bytes of address
+    Id,
     Address4,
     Address3,
     Address2,
     Address1,
-    Address0,
+    /// 2 least significant bytes of address, 0, and field_tag
     FieldTag,
-    StorageKey15,
-    StorageKey14,
-    StorageKey13,
-    StorageKey12,
-    StorageKey11,
-    StorageKey10,
-    StorageKey9,
-    StorageKey8,
     StorageKey7,
     StorageKey6,
     StorageKey5,
@@ -75,18 +63,17 @@ pub enum LimbIndex {
     StorageKey2,
     StorageKey1,
     StorageKey0,
-    RwCounter1,
-    RwCounter0,
+    RwCounter,
 }
 
 impl_expr!(LimbIndex);
 
-impl AsBits<5> for LimbIndex {
-    fn as_bits(&self) -> [bool; 5] {
-        let mut bits = [false; 5];
+impl AsBits<4> for LimbIndex {
+    fn as_bits(&self) -> [bool; 4] {
+        let mut bits = [false; 4];
         let mut x = *self as u8;
-        for i in 0..5 {
-            bits[4 - i] = x % 2 == 1;
+        for i in 0..4 {
+            bits[3 - i] = x % 2 == 1;
             x /= 2;
         }
         assert_eq!(x, 0);
@@ -94,12 +81,16 @@ impl AsBits<5> for LimbIndex {
     }
 }
 
+// Groups of consecutive limbs whose differences are packed into one field
+// element. The first group holds all the keys before storage_key.
+const LIMB_GROUPS: [std::ops::Range<usize>; 3] = [0..7, 7..14, 14..16];
+
 #[derive(Clone, Copy)]
 pub struct Config {
     pub(crate) selector: Column<Fixed>,
-    pub first_different_limb: BinaryNumberConfig<LimbIndex, 5>,
-    limb_difference: Column<Advice>,
-    limb_difference_inverse: Column<Advice>,
+    pub first_different_limb: BinaryNumberConfig<LimbIndex, 4>,
+    limb_difference_hi: Column<Advice>,
+    limb_difference_lo: Column<Advice>,
 }
 
 impl Config {
@@ -107,30 +98,28 @@ impl Config {
         meta: &mut ConstraintSystem<F>,
         keys: SortKeysConfig,
         lookup: lookups::Config,
-        powers_of_randomness: [Expression<F>; N_BYTES_WORD - 1],
     ) -> Self {
         let selector = meta.fixed_column();
         let first_different_limb = BinaryNumberChip::configure(meta, selector, None);
-        let limb_difference = meta.advice_column();
-        let limb_difference_inverse = meta.advice_column();
+        let limb_difference_hi = meta.advice_column();
+        let limb_difference_lo = meta.advice_column();
 
         let config = Config {
             selector,
             first_different_limb,
-            limb_difference,
-            limb_difference_inverse,
+            limb_difference_hi,
+            limb_difference_lo,
         };
 
-        lookup.range_check_u16(meta, "limb_difference fits into u16", |meta| {
-            meta.query_advice(limb_difference, Rotation::cur())
-        });
-
-        meta.create_gate("limb_difference is not zero", |meta| {
-            let selector = meta.query_fixed(selector, Rotation::cur());
-            let limb_difference = meta.query_advice(limb_difference, Rotation::cur());
-            let limb_difference_inverse =
-                meta.query_advice(limb_difference_inverse, Rotation::cur());
-            vec![selector * (1.expr() - limb_difference * limb_difference_inverse)]
+        for column in [limb_difference_hi, limb_difference_lo] {
+            lookup.range_check_u16(meta, "limb_difference fits into u16", |meta| {
+                meta.query_advice(column, Rotation::cur())
+            });
+        }
+        // The field_tag is packed with the address, so it has to be bounded like
+        // the other keys.
+        lookup.range_check_u16(meta, "field_tag fits into u16", |meta| {
+            meta.query_advice(keys.field_tag, Rotation::cur())
         });
 
         meta.create_gate(
@@ -139,19 +128,26 @@ impl Config {
                 let selector = meta.query_fixed(selector, Rotation::cur());
                 let cur = Queries::new(meta, keys, Rotation::cur());
                 let prev = Queries::new(meta, keys, Rotation::prev());
+                let differences: Vec<_> = cur
+                    .be_limbs()
+                    .into_iter()
+                    .zip(prev.be_limbs())
+                    .map(|(cur_limb, prev_limb)| cur_limb - prev_limb)
+                    .collect();
 
                 let mut constraints = vec![];
-                for (i, rlc_expression) in
-                    LimbIndex::iter().zip(rlc_limb_differences(cur, prev, powers_of_randomness))
+                for (i, packed_differences) in
+                    LimbIndex::iter().zip(packed_limb_differences(&differences))
                 {
-                    // E.g. if first_different_limb = 5, four limb differences before need to be 0.
-                    // Using RLC, we check that (cur_1 - prev_1) + r(cur_2 - prev_2) + r^2(cur_3 -
-                    // prev_3) + r^3(cur_4 - prev_4) = 0.
-                    constraints.push(
-                        selector.clone()
-                            * first_different_limb.value_equals(i, Rotation::cur())(meta)
-                            * rlc_expression,
-                    )
+                    // E.g. if first_different_limb = 9, the packed differences of the limbs 0
+                    // to 6, and of the limbs 7 and 8, need to be 0.
+                    for packed_difference in packed_differences {
+                        constraints.push(
+                            selector.clone()
+                                * first_different_limb.value_equals(i, Rotation::cur())(meta)
+                                * packed_difference,
+                        )
+                    }
                 }
                 constraints
             },
@@ -163,7 +159,9 @@ impl Config {
                 let selector = meta.query_fixed(selector, Rotation::cur());
                 let cur = Queries::new(meta, keys, Rotation::cur());
                 let prev = Queries::new(meta, keys, Rotation::prev());
-                let limb_difference = meta.query_advice(limb_difference, Rotation::cur());
+                let limb_difference = 1.expr()
+                    + meta.query_advice(limb_difference_hi, Rotation::cur()) * (1u64 << 16).expr()
+                    + meta.query_advice(limb_difference_lo, Rotation::cur());
 
                 let mut constraints = vec![];
                 for ((i, cur_limb), prev_limb) in
@@ -207,25 +205,35 @@ impl Config {
             .zip(&prev_be_limbs)
             .find(|((_, a), b)| a != b);
         let ((index, cur_limb), prev_limb) = if cfg!(test) {
-            find_result.unwrap_or(((LimbIndex::RwCounter0, &0), &0))
+            find_result.unwrap_or(((LimbIndex::RwCounter, &0), &0))
         } else {
             find_result.expect("repeated rw counter")
         };
 
         BinaryNumberChip::construct(self.first_different_limb).assign(region, offset, &index)?;
 
-        let limb_difference = F::from(*cur_limb as u64) - F::from(*prev_limb as u64);
+        // When the rows are out of order, the difference is not in [1, 2^32] and
+        // limb_difference_lo takes the whole difference, failing its range check.
+        let difference_minus_one = i64::from(*cur_limb) - i64::from(*prev_limb) - 1;
+        let (hi, lo) = if difference_minus_one >= 0 {
+            (
+                F::from((difference_minus_one >> 16) as u64),
+                F::from((difference_minus_one & 0xffff) as u64),
+            )
+        } else {
+            (F::zero(), -F::from(difference_minus_one.unsigned_abs()))
+        };
         region.assign_advice(
-            || "limb_difference",
-            self.limb_difference,
+            || "limb_difference_hi",
+            self.limb_difference_hi,
             offset,
-            || Value::known(limb_difference),
+            || Value::known(hi),
         )?;
         region.assign_advice(
-            || "limb_difference_inverse",
-            self.limb_difference_inverse,
+            || "limb_difference_lo",
+            self.limb_difference_lo,
             offset,
-            || Value::known(limb_difference.invert().unwrap()),
+            || Value::known(lo),
         )?;
 
         Ok(index)
@@ -234,8 +242,8 @@ impl Config {
     /// Annotates columns of this gadget embedded within a circuit region.
     pub fn annotate_columns_in_region<F: Field>(&self, region: &mut Region<F>, prefix: &str) {
         [
-            (self.limb_difference, "LO_limb_difference"),
-            (self.limb_difference_inverse, "LO_limb_difference_inverse"),
+            (self.limb_difference_hi, "LO_limb_difference_hi"),
+            (self.limb_difference_lo, "LO_limb_difference_lo"),
         ]
         .iter()
         .for_each(|(col, ann)| region.name_column(|| format!("{}_{}", prefix, ann), *col));
@@ -249,7 +257,7 @@ impl Config {
 
 struct Queries<F: Field> {
     tag: Expression<F>,       // 4 bits
-    field_tag: Expression<F>, // 8 bits, so we can pack tag + field_tag into one limb.
+    field_tag: Expression<F>, // 16 bits, so we can pack address + field_tag into one limb.
     id_limbs: [Expression<F>; N_LIMBS_ID],
     address_limbs: [Expression<F>; N_LIMBS_ACCOUNT_ADDRESS],
     storage_key_bytes: [Expression<F>; N_BYTES_WORD],
@@ -279,6 +287,7 @@ impl<F: Field> Queries<F> {
             .collect()
     }
 
+    // The 32-bit limbs, each packing two consecutive 16-bit limbs of the keys.
     fn be_limbs(&self) -> Vec<Expression<F>> {
         once(&self.tag)
             .chain(self.id_limbs.iter().rev())
@@ -286,12 +295,13 @@ impl<F: Field> Queries<F> {
             .chain(once(&self.field_tag))
             .chain(&self.storage_key_be_limbs())
             .chain(self.rw_counter_limbs.iter().rev())
-            .cloned()
+            .tuples()
+            .map(|(hi, lo)| (1u64 << 16).expr() * hi.clone() + lo.clone())
             .collect()
     }
 }
 
-fn rw_to_be_limbs(row: &Rw) -> Vec<u16> {
+fn rw_to_be_limbs(row: &Rw) -> Vec<u32> {
     let mut be_bytes = vec![0u8];
     be_bytes.push(row.tag() as u8);
     be_bytes.extend_from_slice(&(row.id().unwrap_or_default() as u32).to_be_bytes());
@@ -302,30 +312,33 @@ fn rw_to_be_limbs(row: &Rw) -> Vec<u16> {
     be_bytes.extend_from_slice(&((row.rw_counter() as u32).to_be_bytes()));
 
     be_bytes
-        .iter()
-        .tuples()
-        .map(|(hi, lo)| u16::from_be_bytes([*hi, *lo]))
+        .chunks(4)
+        .map(|limb| u32::from_be_bytes(limb.try_into().unwrap()))
         .collect()
 }
 
-// Returns a vector of length 32 with the rlc of the limb differences between
-// from 0 to i-l. 0 for i=0,
-fn rlc_limb_differences<F: Field>(
-    cur: Queries<F>,
-    prev: Queries<F>,
-    powers_of_randomness: [Expression<F>; 31],
-) -> Vec<Expression<F>> {
+// Returns, for each limb index i, the packed differences of the limbs before i:
+// one for each group of limbs before the group of i, and one for the limbs
+// before i in its group, if any.
+fn packed_limb_differences<F: Field>(differences: &[Expression<F>]) -> Vec<Vec<Expression<F>>> {
+    let pack = |limbs: &[Expression<F>]| {
+        limbs.iter().fold(0.expr(), |packed, difference| {
+            packed * (1u64 << 32).expr() + difference.clone()
+        })
+    };
     let mut result = vec![];
-    let mut partial_sum = 0u64.expr();
-    let powers_of_randomness = once(1.expr()).chain(powers_of_randomness.into_iter());
-    for ((cur_limb, prev_limb), power_of_randomness) in cur
-        .be_limbs()
-        .iter()
-        .zip(&prev.be_limbs())
-        .zip(powers_of_randomness)
-    {
-        result.push(partial_sum.clone());
-        partial_sum = partial_sum + power_of_randomness * (cur_limb.clone() - prev_limb.clone());
+    for (group_index, group) in LIMB_GROUPS.iter().enumerate() {
+        let previous_groups: Vec<_> = LIMB_GROUPS[..group_index]
+            .iter()
+            .map(|previous_group| pack(&differences[previous_group.clone()]))
+            .collect();
+        for i in group.clone() {
+            let mut packed_differences = previous_groups.clone();
+            if i > group.start {
+                packed_differences.push(pack(&differences[group.start..i]));
+            }
+            result.push(packed_differences);
+        }
     }
     result
 }
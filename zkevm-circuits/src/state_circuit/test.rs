@@ -646,15 +646,16 @@ fn lexicographic_ordering_previous_limb_differences_nonzero() {
             rw_counter: 2,
             is_write: true,
             account_address: address!("0x0000000000000000000000000000000000000001"),
-            field_tag: AccountFieldTag::Nonce,
+            field_tag: AccountFieldTag::CodeHash,
             value: Word::zero(),
             value_prev: Word::zero(),
         },
     ];
 
-    // overriding first_different_limb to be in AddressLimb0 instead of Tag. The
-    // limb difference between the two rows here is still 1, so no additional
-    // overrides are needed.
+    // overriding first_different_limb to be in FieldTag, which holds the least
+    // significant bytes of the address, instead of Tag. The limb difference
+    // between the two rows here is still 2^16, so no additional overrides are
+    // needed.
     let overrides = HashMap::from([
         ((AdviceColumn::LimbIndexBit1, 1), Fr::one()),
         ((AdviceColumn::LimbIndexBit2, 1), Fr::one()),
@@ -937,6 +938,54 @@ fn bad_initial_tx_log_value() {
     );
 }
 
+#[test]
+fn transient_storage_ok() {
+    let address = address!("0x0000000000000000000000000000000000000010");
+    let row = |rw_counter, is_write, tx_id, value: u64, value_prev: u64| Rw::TransientStorage {
+        rw_counter,
+        is_write,
+        tx_id,
+        account_address: address,
+        storage_key: Word::from(0x40),
+        value: Word::from(value),
+        value_prev: Word::from(value_prev),
+    };
+    let rows = vec![
+        row(1, true, 1, 5, 0),
+        row(2, true, 1, 6, 5),
+        // reversion of the second write
+        row(3, true, 1, 5, 6),
+        row(4, false, 1, 5, 5),
+        // the transient storage of the next tx starts empty
+        row(5, false, 2, 0, 0),
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn bad_initial_transient_storage_value() {
+    let rows = vec![Rw::TransientStorage {
+        rw_counter: 1,
+        is_write: true,
+        tx_id: 2,
+        account_address: address!("0x0000000000000000000000000000000000000010"),
+        storage_key: Word::from(0x40),
+        value: Word::from(5),
+        value_prev: Word::zero(),
+    }];
+
+    let overrides = HashMap::from([
+        ((AdviceColumn::InitialValue, 0), Fr::from(10)),
+        ((AdviceColumn::ValuePrev, 0), Fr::from(10)),
+    ]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial TransientStorage value is 0",
+    );
+}
+
 #[test]
 fn variadic_size_check() {
     let mut rows = vec![
@@ -962,6 +1011,7 @@ fn variadic_size_check() {
         updates,
         overrides: HashMap::default(),
         n_rows: N_ROWS,
+        max_mpt_rows: N_ROWS,
         exports: Default::default(),
         _marker: std::marker::PhantomData::default(),
     };
@@ -991,6 +1041,7 @@ fn variadic_size_check() {
         updates,
         overrides: HashMap::default(),
         n_rows: N_ROWS,
+        max_mpt_rows: N_ROWS,
         exports: Default::default(),
         _marker: std::marker::PhantomData::default(),
     };
@@ -1030,6 +1081,7 @@ fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockP
         updates,
         overrides,
         n_rows: N_ROWS,
+        max_mpt_rows: N_ROWS,
         exports: Default::default(),
         _marker: std::marker::PhantomData::default(),
     };
@@ -50,383 +50,203 @@
 //!   - [x] Tx Circuit
 //!   - [ ] MPT Circuit
 
+#[cfg(feature = "circuit-profiles")]
+pub mod profile;
+pub mod registry;
 #[cfg(any(feature = "test", test))]
 pub(crate) mod test;
 
-#[cfg(feature = "poseidon-codehash")]
-use crate::bytecode_circuit::circuit::to_poseidon_hash::{
-    ToHashBlockBytecodeCircuitConfigArgs, ToHashBlockCircuitConfig, HASHBLOCK_BYTES_IN_FIELD,
-};
-#[cfg(not(feature = "poseidon-codehash"))]
-use crate::bytecode_circuit::circuit::BytecodeCircuitConfig;
 use crate::{
-    bytecode_circuit::circuit::{BytecodeCircuit, BytecodeCircuitConfigArgs},
-    copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
-    evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
-    exp_circuit::{ExpCircuit, ExpCircuitConfig},
-    keccak_circuit::{KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs},
-    poseidon_circuit::{PoseidonCircuit, PoseidonCircuitConfig, PoseidonCircuitConfigArgs},
-    tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
-    util::{log2_ceil, SubCircuit, SubCircuitConfig},
-    witness::{block_convert, Block},
+    bytecode_circuit::circuit::BytecodeCircuit,
+    circuit_cost::{Checkpoint, CostReport},
+    copy_circuit::CopyCircuit,
+    evm_circuit::EvmCircuit,
+    exp_circuit::ExpCircuit,
+    keccak_circuit::{keccak_packed_multi::get_num_rows_per_round, KeccakCircuit},
+    pi_circuit::PiCircuit,
+    poseidon_circuit::PoseidonCircuit,
+    rlp_circuit_fsm::RlpCircuit,
+    sig_circuit::SigCircuit,
+    state_circuit::StateCircuit,
+    tx_circuit::{TxCircuit, TxCircuitConfig},
+    util::{circuit_config_digest, circuit_stats, log2_ceil, SubCircuit, SubCircuitConfig},
+    witness::{block_convert, Block, Transaction},
 };
 
 #[cfg(feature = "zktrie")]
-use crate::mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs};
+use crate::mpt_circuit::MptCircuit;
 
 #[cfg(not(feature = "onephase"))]
 use crate::util::Challenges;
 #[cfg(feature = "onephase")]
 use crate::util::MockChallenges as Challenges;
 
-use crate::{
-    state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
-    table::{
-        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, PoseidonTable,
-        RlpFsmRlpTable as RlpTable, RwTable, TxTable,
-    },
-};
-
-use crate::util::circuit_stats;
 use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
     mock::BlockData,
+    progress::ProgressReporter,
 };
-use eth_types::{geth_types::GethData, Field};
+use eth_types::{geth_types::GethData, Field, H256};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error, Expression},
 };
 use itertools::Itertools;
+use registry::{SharedTables, SubCircuitRegistry, TableSchema};
 use snark_verifier_sdk::CircuitExt;
-
-use crate::{
-    pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
-    rlp_circuit_fsm::{RlpCircuit, RlpCircuitConfig, RlpCircuitConfigArgs},
-    witness::Transaction,
-};
+use std::marker::PhantomData;
+
+/// Sub-circuits of the zkEVM, in the order they are configured and
+/// synthesized.
+#[cfg(not(feature = "zktrie"))]
+pub type DefaultSubCircuits<F> = (
+    KeccakCircuit<F>,
+    PoseidonCircuit<F>,
+    RlpCircuit<F, Transaction>,
+    PiCircuit<F>,
+    TxCircuit<F>,
+    BytecodeCircuit<F>,
+    CopyCircuit<F>,
+    StateCircuit<F>,
+    ExpCircuit<F>,
+    SigCircuit<F>,
+    EvmCircuit<F>,
+);
+
+/// Sub-circuits of the zkEVM, in the order they are configured and
+/// synthesized.
+#[cfg(feature = "zktrie")]
+pub type DefaultSubCircuits<F> = (
+    KeccakCircuit<F>,
+    PoseidonCircuit<F>,
+    RlpCircuit<F, Transaction>,
+    PiCircuit<F>,
+    TxCircuit<F>,
+    BytecodeCircuit<F>,
+    CopyCircuit<F>,
+    MptCircuit<F>,
+    StateCircuit<F>,
+    ExpCircuit<F>,
+    SigCircuit<F>,
+    EvmCircuit<F>,
+);
 
 /// Configuration of the Super Circuit
 #[derive(Clone)]
-pub struct SuperCircuitConfig<F: Field> {
-    block_table: BlockTable,
-    mpt_table: MptTable,
-    rlp_table: RlpTable,
-    tx_table: TxTable,
-    poseidon_table: PoseidonTable,
-    evm_circuit: EvmCircuitConfig<F>,
-    state_circuit: StateCircuitConfig<F>,
-    tx_circuit: TxCircuitConfig<F>,
-    #[cfg(not(feature = "poseidon-codehash"))]
-    bytecode_circuit: BytecodeCircuitConfig<F>,
-    #[cfg(feature = "poseidon-codehash")]
-    bytecode_circuit: ToHashBlockCircuitConfig<F, HASHBLOCK_BYTES_IN_FIELD>,
-    copy_circuit: CopyCircuitConfig<F>,
-    keccak_circuit: KeccakCircuitConfig<F>,
-    poseidon_circuit: PoseidonCircuitConfig<F>,
-    pi_circuit: PiCircuitConfig<F>,
-    exp_circuit: ExpCircuitConfig<F>,
-    rlp_circuit: RlpCircuitConfig<F>,
-    /// Mpt Circuit
-    #[cfg(feature = "zktrie")]
-    mpt_circuit: MptCircuitConfig,
+pub struct SuperCircuitConfig<F: Field, R: SubCircuitRegistry<F> = DefaultSubCircuits<F>> {
+    tables: SharedTables,
+    sub_circuits: R::Config,
+    /// Size of the constraint system before the tables and sub-circuits,
+    /// and after each of them
+    checkpoints: (Checkpoint, Vec<(&'static str, Checkpoint)>),
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, R: SubCircuitRegistry<F>> SuperCircuitConfig<F, R> {
+    /// Cost of each table and sub-circuit, in the order they are configured.
+    /// `meta` is the constraint system this config was configured in.
+    pub fn cost_report(&self, meta: &ConstraintSystem<F>) -> CostReport {
+        CostReport::new(meta, self.checkpoints.0, &self.checkpoints.1)
+    }
+
+    /// Schemas of the shared tables, in the order they are constructed.
+    pub fn table_schemas(&self) -> Vec<TableSchema> {
+        self.tables.schemas::<F>()
+    }
 }
 
 /// Circuit configuration arguments
 pub struct SuperCircuitConfigArgs<F: Field> {
-    /// Max txs
-    pub max_txs: usize,
-    /// Max calldata
-    pub max_calldata: usize,
     /// Max inner blocks
     pub max_inner_blocks: usize,
     /// Mock randomness
     pub mock_randomness: u64,
     /// Challenges
     pub challenges: crate::util::Challenges<Expression<F>>,
+    /// Rows per round of the keccak circuit, see [`get_num_rows_per_round`]
+    pub keccak_rows_per_round: usize,
 }
 
-impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
+impl<F: Field, R: SubCircuitRegistry<F>> SubCircuitConfig<F> for SuperCircuitConfig<F, R> {
     type ConfigArgs = SuperCircuitConfigArgs<F>;
 
     /// Configure SuperCircuitConfig
-    fn new(
-        meta: &mut ConstraintSystem<F>,
-        Self::ConfigArgs {
-            max_txs,
-            max_calldata,
-            max_inner_blocks,
-            mock_randomness: _mock_randomness,
-            challenges,
-        }: Self::ConfigArgs,
-    ) -> Self {
-        let log_circuit_info = |meta: &ConstraintSystem<F>, tag: &str| {
+    fn new(meta: &mut ConstraintSystem<F>, args: Self::ConfigArgs) -> Self {
+        let start = Checkpoint::new(meta);
+        let mut checkpoints = Vec::new();
+        let mut log_circuit_info = |meta: &ConstraintSystem<F>, tag: &'static str| {
             log::debug!("circuit info after {}: {:#?}", tag, circuit_stats(meta));
+            checkpoints.push((tag, Checkpoint::new(meta)));
         };
-        let tx_table = TxTable::construct(meta);
-        log_circuit_info(meta, "tx table");
-        let rw_table = RwTable::construct(meta);
-        log_circuit_info(meta, "rw table");
-
-        let mpt_table = MptTable::construct(meta);
-        log_circuit_info(meta, "mpt table");
-        let poseidon_table = PoseidonTable::construct(meta);
-        log_circuit_info(meta, "poseidon table");
-
-        let bytecode_table = BytecodeTable::construct(meta);
-        log_circuit_info(meta, "bytecode table");
-        let block_table = BlockTable::construct(meta);
-        log_circuit_info(meta, "block table");
-        let q_copy_table = meta.fixed_column();
-        log::debug!("q_copy_table {:?}", q_copy_table);
-        let copy_table = CopyTable::construct(meta, q_copy_table);
-        log_circuit_info(meta, "copy table");
-        let exp_table = ExpTable::construct(meta);
-        log_circuit_info(meta, "exp table");
-        let rlp_table = RlpTable::construct(meta);
-        log_circuit_info(meta, "rlp table");
-        let keccak_table = KeccakTable::construct(meta);
-        log_circuit_info(meta, "keccak table");
-
-        let keccak_circuit = KeccakCircuitConfig::new(
-            meta,
-            KeccakCircuitConfigArgs {
-                keccak_table: keccak_table.clone(),
-                challenges: challenges.clone(),
-            },
-        );
-        log_circuit_info(meta, "keccak circuit");
-
-        let poseidon_circuit =
-            PoseidonCircuitConfig::new(meta, PoseidonCircuitConfigArgs { poseidon_table });
-        log_circuit_info(meta, "poseidon circuit");
-
-        let rlp_circuit = RlpCircuitConfig::new(
-            meta,
-            RlpCircuitConfigArgs {
-                rlp_table,
-                challenges: challenges.clone(),
-            },
-        );
-        log_circuit_info(meta, "rlp circuit");
-
-        let pi_circuit = PiCircuitConfig::new(
-            meta,
-            PiCircuitConfigArgs {
-                max_txs,
-                max_calldata,
-                max_inner_blocks,
-                block_table: block_table.clone(),
-                keccak_table: keccak_table.clone(),
-                tx_table: tx_table.clone(),
-                challenges: challenges.clone(),
-            },
-        );
-        log_circuit_info(meta, "pi circuit");
-
-        let tx_circuit = TxCircuitConfig::new(
-            meta,
-            TxCircuitConfigArgs {
-                block_table: block_table.clone(),
-                tx_table: tx_table.clone(),
-                keccak_table: keccak_table.clone(),
-                rlp_table,
-                challenges: challenges.clone(),
-            },
-        );
-        log_circuit_info(meta, "tx circuit");
-
-        #[cfg(not(feature = "poseidon-codehash"))]
-        let bytecode_circuit = BytecodeCircuitConfig::new(
-            meta,
-            BytecodeCircuitConfigArgs {
-                bytecode_table: bytecode_table.clone(),
-                keccak_table: keccak_table.clone(),
-                challenges: challenges.clone(),
-            },
-        );
-        #[cfg(feature = "poseidon-codehash")]
-        let bytecode_circuit = ToHashBlockCircuitConfig::new(
-            meta,
-            ToHashBlockBytecodeCircuitConfigArgs {
-                base_args: BytecodeCircuitConfigArgs {
-                    bytecode_table: bytecode_table.clone(),
-                    keccak_table: keccak_table.clone(),
-                    challenges: challenges.clone(),
-                },
-                poseidon_table,
-            },
-        );
-
-        log_circuit_info(meta, "bytecode circuit");
-
-        let copy_circuit = CopyCircuitConfig::new(
-            meta,
-            CopyCircuitConfigArgs {
-                tx_table: tx_table.clone(),
-                rw_table,
-                bytecode_table: bytecode_table.clone(),
-                copy_table,
-                q_enable: q_copy_table,
-                challenges: challenges.clone(),
-            },
-        );
-        log_circuit_info(meta, "copy circuit");
-
-        #[cfg(feature = "zktrie")]
-        let mpt_circuit = MptCircuitConfig::new(
-            meta,
-            MptCircuitConfigArgs {
-                poseidon_table,
-                mpt_table,
-                challenges: challenges.clone(),
-            },
-        );
-        #[cfg(feature = "zktrie")]
-        log_circuit_info(meta, "zktrie circuit");
-
-        let state_circuit = StateCircuitConfig::new(
-            meta,
-            StateCircuitConfigArgs {
-                rw_table,
-                mpt_table,
-                challenges: challenges.clone(),
-            },
-        );
-        log_circuit_info(meta, "state circuit");
-
-        let exp_circuit = ExpCircuitConfig::new(meta, exp_table);
-        log_circuit_info(meta, "exp circuit");
-
-        let evm_circuit = EvmCircuitConfig::new(
-            meta,
-            EvmCircuitConfigArgs {
-                challenges,
-                tx_table: tx_table.clone(),
-                rw_table,
-                bytecode_table,
-                block_table: block_table.clone(),
-                copy_table,
-                keccak_table,
-                exp_table,
-            },
-        );
-        log_circuit_info(meta, "evm circuit");
+        let tables = SharedTables::construct(meta, &R::tables(), &mut log_circuit_info);
+        let sub_circuits = R::configure(meta, &tables, &args, &mut log_circuit_info);
 
         #[cfg(feature = "onephase")]
         if meta.max_phase() != 0 {
             log::warn!("max_phase: {}", meta.max_phase());
         }
 
-        SuperCircuitConfig::<F> {
-            block_table,
-            mpt_table,
-            tx_table,
-            rlp_table,
-            poseidon_table,
-            evm_circuit,
-            state_circuit,
-            copy_circuit,
-            bytecode_circuit,
-            keccak_circuit,
-            poseidon_circuit,
-            pi_circuit,
-            rlp_circuit,
-            tx_circuit,
-            exp_circuit,
-            #[cfg(feature = "zktrie")]
-            mpt_circuit,
+        SuperCircuitConfig {
+            tables,
+            sub_circuits,
+            checkpoints: (start, checkpoints),
+            _marker: PhantomData,
         }
     }
 }
 
-/// The Super Circuit contains all the zkEVM circuits
+/// The Super Circuit contains all the zkEVM circuits, or the ones of the
+/// registry `R`.
+///
+/// The max number of txs and of calldata bytes aren't parameters of the
+/// configuration: they're read from the [`CircuitsParams`] of the block, and
+/// the tx and PI circuits pad their rows up to them while assigning. Blocks
+/// with any number of txs up to the same `CircuitsParams` share the same
+/// verifying key.
 #[derive(Clone, Default, Debug)]
 pub struct SuperCircuit<
     F: Field,
-    const MAX_TXS: usize,
-    const MAX_CALLDATA: usize,
     const MAX_INNER_BLOCKS: usize,
     const MOCK_RANDOMNESS: u64,
+    R: SubCircuitRegistry<F> = DefaultSubCircuits<F>,
 > {
-    /// EVM Circuit
-    pub evm_circuit: EvmCircuit<F>,
-    /// State Circuit
-    pub state_circuit: StateCircuit<F>,
-    /// The transaction circuit that will be used in the `synthesize` step.
-    pub tx_circuit: TxCircuit<F>,
-    /// Public Input Circuit
-    pub pi_circuit: PiCircuit<F>,
-    /// Bytecode Circuit
-    pub bytecode_circuit: BytecodeCircuit<F>,
-    /// Copy Circuit
-    pub copy_circuit: CopyCircuit<F>,
-    /// Exp Circuit
-    pub exp_circuit: ExpCircuit<F>,
-    /// Keccak Circuit
-    pub keccak_circuit: KeccakCircuit<F>,
-    /// Poseidon hash Circuit
-    pub poseidon_circuit: PoseidonCircuit<F>,
-    /// Rlp Circuit
-    pub rlp_circuit: RlpCircuit<F, Transaction>,
-    /// Mpt Circuit
-    #[cfg(feature = "zktrie")]
-    pub mpt_circuit: MptCircuit<F>,
+    /// Sub-circuits, filled with the witness of the block
+    pub sub_circuits: R,
+    /// Witness of the shared tables which none of the sub-circuits assign,
+    /// only kept for the registries leaving some of them unassigned
+    unassigned_tables: Option<Block<F>>,
+    /// Progress reported while assigning the sub-circuits, and its
+    /// cancellation
+    progress: ProgressReporter,
 }
 
 impl<
         F: Field,
-        const MAX_TXS: usize,
-        const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        R: SubCircuitRegistry<F>,
+    > SuperCircuit<F, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, R>
 {
     /// Return the number of rows required to verify a given block
     pub fn get_num_rows_required(block: &Block<F>) -> usize {
         let num_rows_evm_circuit = EvmCircuit::<F>::get_num_rows_required(block);
-        assert_eq!(block.circuits_params.max_txs, MAX_TXS);
         let num_rows_tx_circuit =
             TxCircuitConfig::<F>::get_num_rows_required(block.circuits_params.max_txs);
         num_rows_evm_circuit.max(num_rows_tx_circuit)
     }
-    /// Return the minimum number of rows required to prove the block
-    pub fn min_num_rows_block_subcircuits(block: &Block<F>) -> (Vec<usize>, Vec<usize>) {
-        let evm = EvmCircuit::min_num_rows_block(block);
-        let state = StateCircuit::min_num_rows_block(block);
-        let bytecode = BytecodeCircuit::min_num_rows_block(block);
-        let copy = CopyCircuit::min_num_rows_block(block);
-        let keccak = KeccakCircuit::min_num_rows_block(block);
-        let tx = TxCircuit::min_num_rows_block(block);
-        let rlp = RlpCircuit::min_num_rows_block(block);
-        let exp = ExpCircuit::min_num_rows_block(block);
-        let pi = PiCircuit::min_num_rows_block(block);
-        let poseidon = (0, 0); //PoseidonCircuit::min_num_rows_block(block);
-        #[cfg(feature = "zktrie")]
-        let mpt = (0, 0); //MptCircuit::min_num_rows_block(block);
-
-        let rows: Vec<(usize, usize)> = vec![
-            evm,
-            state,
-            bytecode,
-            copy,
-            keccak,
-            tx,
-            rlp,
-            exp,
-            pi,
-            poseidon,
-            #[cfg(feature = "zktrie")]
-            mpt,
-        ];
-        let (rows_without_padding, rows_with_padding): (Vec<usize>, Vec<usize>) =
-            rows.into_iter().unzip();
+    /// Return the minimum number of rows required to prove the block by each
+    /// sub-circuit, named as in [`registry::SuperSubCircuit::NAME`]
+    pub fn min_num_rows_block_subcircuits(block: &Block<F>) -> Vec<(&'static str, usize, usize)> {
+        let rows = R::names()
+            .into_iter()
+            .zip(R::min_num_rows_block(block))
+            .map(|(name, (rows, rows_with_padding))| (name, rows, rows_with_padding))
+            .collect();
         log::debug!(
-            "subcircuit rows(without padding): {:?}",
-            rows_without_padding
+            "subcircuit rows (without padding, with padding): {:?}",
+            rows
         );
-        log::debug!("subcircuit rows(with    padding): {:?}", rows_with_padding);
-        (rows_without_padding, rows_with_padding)
+        rows
     }
 }
 
@@ -435,78 +255,38 @@ impl<
 // allow us to generalize integration tests.
 impl<
         F: Field,
-        const MAX_TXS: usize,
-        const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > SubCircuit<F> for SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        R: SubCircuitRegistry<F>,
+    > SubCircuit<F> for SuperCircuit<F, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, R>
 {
-    type Config = SuperCircuitConfig<F>;
+    type Config = SuperCircuitConfig<F, R>;
 
     fn unusable_rows() -> usize {
-        itertools::max([
-            EvmCircuit::<F>::unusable_rows(),
-            StateCircuit::<F>::unusable_rows(),
-            TxCircuit::<F>::unusable_rows(),
-            PiCircuit::<F>::unusable_rows(),
-            BytecodeCircuit::<F>::unusable_rows(),
-            CopyCircuit::<F>::unusable_rows(),
-            ExpCircuit::<F>::unusable_rows(),
-            KeccakCircuit::<F>::unusable_rows(),
-        ])
-        .unwrap()
+        R::unusable_rows()
     }
 
     fn new_from_block(block: &Block<F>) -> Self {
-        let evm_circuit = EvmCircuit::new_from_block(block);
-        let state_circuit = StateCircuit::new_from_block(block);
-        let tx_circuit = TxCircuit::new_from_block(block);
-        let pi_circuit = PiCircuit::new_from_block(block);
-        let bytecode_circuit = BytecodeCircuit::new_from_block(block);
-        let copy_circuit = CopyCircuit::new_from_block_no_external(block);
-        let exp_circuit = ExpCircuit::new_from_block(block);
-        let keccak_circuit = KeccakCircuit::new_from_block(block);
-        let poseidon_circuit = PoseidonCircuit::new_from_block(block);
-        let rlp_circuit = RlpCircuit::new_from_block(block);
-        #[cfg(feature = "zktrie")]
-        let mpt_circuit = MptCircuit::new_from_block(block);
-        SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS> {
-            evm_circuit,
-            state_circuit,
-            tx_circuit,
-            pi_circuit,
-            bytecode_circuit,
-            copy_circuit,
-            exp_circuit,
-            keccak_circuit,
-            poseidon_circuit,
-            rlp_circuit,
-            #[cfg(feature = "zktrie")]
-            mpt_circuit,
+        Self {
+            sub_circuits: R::new_from_block(block),
+            unassigned_tables: (!R::unassigned_tables().is_empty()).then(|| block.clone()),
+            progress: ProgressReporter::default(),
         }
     }
 
-    /// Returns suitable inputs for the SuperCircuit.
+    /// Returns suitable inputs for the SuperCircuit. Only the PI circuit has
+    /// public inputs: the hi and lo halves of the keccak of all the public data
+    /// of the block, so that the verifier reads a single instance column.
     fn instance(&self) -> Vec<Vec<F>> {
-        let mut instance = Vec::new();
-        instance.extend_from_slice(&self.keccak_circuit.instance());
-        instance.extend_from_slice(&self.pi_circuit.instance());
-        instance.extend_from_slice(&self.tx_circuit.instance());
-        instance.extend_from_slice(&self.bytecode_circuit.instance());
-        instance.extend_from_slice(&self.copy_circuit.instance());
-        instance.extend_from_slice(&self.state_circuit.instance());
-        instance.extend_from_slice(&self.exp_circuit.instance());
-        instance.extend_from_slice(&self.evm_circuit.instance());
-
-        instance
+        self.sub_circuits.instance()
     }
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &Block<F>) -> (usize, usize) {
-        let (rows_without_padding, rows_with_padding) = Self::min_num_rows_block_subcircuits(block);
+        let rows = Self::min_num_rows_block_subcircuits(block);
         (
-            itertools::max(rows_without_padding).unwrap(),
-            itertools::max(rows_with_padding).unwrap(),
+            rows.iter().map(|(_, rows, _)| *rows).max().unwrap(),
+            rows.iter().map(|(_, _, rows)| *rows).max().unwrap(),
         )
     }
 
@@ -517,54 +297,29 @@ impl<
         challenges: &crate::util::Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        self.keccak_circuit
-            .synthesize_sub(&config.keccak_circuit, challenges, layouter)?;
-        self.poseidon_circuit
-            .synthesize_sub(&config.poseidon_circuit, challenges, layouter)?;
-        self.bytecode_circuit
-            .synthesize_sub(&config.bytecode_circuit, challenges, layouter)?;
-        self.tx_circuit
-            .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
-        self.state_circuit
-            .synthesize_sub(&config.state_circuit, challenges, layouter)?;
-        self.copy_circuit
-            .synthesize_sub(&config.copy_circuit, challenges, layouter)?;
-        self.exp_circuit
-            .synthesize_sub(&config.exp_circuit, challenges, layouter)?;
-        self.evm_circuit
-            .synthesize_sub(&config.evm_circuit, challenges, layouter)?;
-
-        self.pi_circuit
-            .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
-
-        self.pi_circuit.connect_export(
-            layouter,
-            // TODO: enable this after zktrie deletion deployed inside l2geth and
-            // test data regenerated.
-            None,
-            self.evm_circuit.exports.borrow().as_ref(),
-        )?;
-
-        self.rlp_circuit
-            .synthesize_sub(&config.rlp_circuit, challenges, layouter)?;
-        // load both poseidon table and zktrie table
-        #[cfg(feature = "zktrie")]
-        self.mpt_circuit
-            .synthesize_sub(&config.mpt_circuit, challenges, layouter)?;
-
-        Ok(())
+        self.sub_circuits
+            .load_tables(&config.tables, challenges, layouter)?;
+        if let Some(block) = &self.unassigned_tables {
+            config.tables.load_from_witness(
+                &R::unassigned_tables(),
+                block,
+                challenges,
+                layouter,
+            )?;
+        }
+        self.sub_circuits
+            .synthesize(&config.sub_circuits, challenges, layouter, &self.progress)
     }
 }
 
 impl<
         F: Field,
-        const MAX_TXS: usize,
-        const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > Circuit<F> for SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        R: SubCircuitRegistry<F>,
+    > Circuit<F> for SuperCircuit<F, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, R>
 {
-    type Config = (SuperCircuitConfig<F>, Challenges);
+    type Config = (SuperCircuitConfig<F, R>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -578,11 +333,10 @@ impl<
             SuperCircuitConfig::new(
                 meta,
                 SuperCircuitConfigArgs {
-                    max_txs: MAX_TXS,
-                    max_calldata: MAX_CALLDATA,
                     max_inner_blocks: MAX_INNER_BLOCKS,
                     mock_randomness: MOCK_RANDOMNESS,
                     challenges: challenge_exprs,
+                    keccak_rows_per_round: get_num_rows_per_round(0),
                 },
             ),
             challenges,
@@ -595,42 +349,16 @@ impl<
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let challenges = challenges.values(&layouter);
-
-        let block = self.evm_circuit.block.as_ref().unwrap();
-
-        // PI circuit had the hardcoded constants for RegionIndex of block table
-        // and tx table (which are 0 and 1).
-        // The reason for that is the assignment of block/tx tables are done in
-        // their load() functions which however do not emit the cells.
-        // To set up copy constraints between pi cells and block/tx table cells,
-        // we need to construct them manually.
-        config.tx_table.load(
-            &mut layouter,
-            &block.txs,
-            block.circuits_params.max_txs,
-            block.circuits_params.max_calldata,
-            block.chain_id.as_u64(),
-            &challenges,
-        )?;
-
-        config.mpt_table.load(
-            &mut layouter,
-            &self.state_circuit.updates,
-            block.circuits_params.max_mpt_rows,
-            challenges.evm_word(),
-        )?;
-
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
 }
 
 impl<
         F: Field,
-        const MAX_TXS: usize,
-        const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > CircuitExt<F> for SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        R: SubCircuitRegistry<F>,
+    > CircuitExt<F> for SuperCircuit<F, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, R>
 {
     fn num_instance(&self) -> Vec<usize> {
         self.instances().iter().map(|l| l.len()).collect_vec()
@@ -643,12 +371,26 @@ impl<
 
 impl<
         F: Field,
-        const MAX_TXS: usize,
-        const MAX_CALLDATA: usize,
         const MAX_INNER_BLOCKS: usize,
         const MOCK_RANDOMNESS: u64,
-    > SuperCircuit<F, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+        R: SubCircuitRegistry<F>,
+    > SuperCircuit<F, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, R>
 {
+    /// Report the progress of the assignment of the sub-circuits to
+    /// `progress`, and fail the synthesis between two sub-circuits once
+    /// `progress` is cancelled.
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Digest of the super circuit configuration for `params`, see
+    /// [`circuit_config_digest`]. CI compares it to detect the changes of
+    /// the constraint system without generating a key.
+    pub fn config_digest(params: &CircuitsParams) -> H256 {
+        circuit_config_digest::<F, Self>(params)
+    }
+
     /// From the witness data, generate a SuperCircuit instance with all of the
     /// sub-circuits filled with their corresponding witnesses.
     ///
@@ -681,8 +423,6 @@ impl<
     ) -> Result<(u32, Self, Vec<Vec<F>>), bus_mapping::Error> {
         let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
         block.randomness = F::from(MOCK_RANDOMNESS);
-        assert_eq!(block.circuits_params.max_txs, MAX_TXS);
-        assert_eq!(block.circuits_params.max_calldata, MAX_CALLDATA);
         Self::build_from_witness_block(block)
     }
     /// ..
@@ -694,12 +434,19 @@ impl<
             block.circuits_params
         );
 
-        let (_, rows_needed) = Self::min_num_rows_block(&block);
+        let rows = Self::min_num_rows_block_subcircuits(&block);
+        for (name, circuit_rows, _) in &rows {
+            tracing::info!(
+                circuit = name,
+                monotonic_counter.rows_assigned = *circuit_rows as u64,
+                "sub-circuit rows"
+            );
+        }
+        let rows_needed = rows.iter().map(|(_, _, rows)| *rows).max().unwrap();
         let k = log2_ceil(Self::unusable_rows() + rows_needed);
         log::debug!("super circuit needs k = {}", k);
 
-        let circuit =
-            SuperCircuit::<_, MAX_TXS, MAX_CALLDATA,MAX_INNER_BLOCKS,  MOCK_RANDOMNESS>::new_from_block(&block);
+        let circuit = Self::new_from_block(&block);
 
         let instance = circuit.instance();
         Ok((k, circuit, instance))
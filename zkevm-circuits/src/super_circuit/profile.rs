@@ -0,0 +1,85 @@
+//! Minimal profiles of the super circuit, registering a few sub-circuits for
+//! targeted audits and lighter test runs.
+//!
+//! The tables the sub-circuits of a profile look up into, but which none of
+//! them assign, are loaded straight from the witness, so their content is
+//! trusted instead of proved. The keys are generated without the witness, so
+//! the minimal profiles are only checked with the mock prover.
+//!
+//! The profile is selected by [`CircuitsParams::profile`].
+
+use super::{DefaultSubCircuits, SuperCircuit};
+use crate::{
+    evm_circuit::EvmCircuit, pi_circuit::PiCircuit, state_circuit::StateCircuit,
+    super_circuit::registry::SubCircuitRegistry, tx_circuit::TxCircuit, util::log2_ceil,
+    witness::block_convert,
+};
+use bus_mapping::{
+    circuit_input_builder::{CircuitInputBuilder, CircuitProfile, CircuitsParams},
+    mock::BlockData,
+};
+use eth_types::geth_types::GethData;
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
+
+/// Sub-circuits of [`CircuitProfile::EvmState`]
+pub type EvmStateSubCircuits<F> = (StateCircuit<F>, EvmCircuit<F>);
+
+/// Sub-circuits of [`CircuitProfile::TxPi`]. The PI circuit is registered
+/// first, as it expects the tx table in the first region.
+pub type TxPiSubCircuits<F> = (PiCircuit<F>, TxCircuit<F>);
+
+/// Build the super circuit of the profile selected by `circuits_params` for
+/// the block, and check it with the mock prover.
+#[allow(clippy::type_complexity)]
+pub fn mock_prove<const MAX_INNER_BLOCKS: usize, const MOCK_RANDOMNESS: u64>(
+    geth_data: GethData,
+    circuits_params: CircuitsParams,
+) -> Result<(CircuitInputBuilder, Result<(), Vec<VerifyFailure>>), bus_mapping::Error> {
+    let block_data = BlockData::new_from_geth_data_with_params(geth_data.clone(), circuits_params);
+    let mut builder = block_data.new_circuit_input_builder();
+    builder.handle_block(&geth_data.eth_block, &geth_data.geth_traces)?;
+
+    let result = match circuits_params.profile {
+        CircuitProfile::Full => {
+            mock_prove_registry::<DefaultSubCircuits<Fr>, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>(
+                &builder,
+            )?
+        }
+        CircuitProfile::EvmState => {
+            mock_prove_registry::<EvmStateSubCircuits<Fr>, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>(
+                &builder,
+            )?
+        }
+        CircuitProfile::TxPi => {
+            mock_prove_registry::<TxPiSubCircuits<Fr>, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>(&builder)?
+        }
+    };
+    Ok((builder, result))
+}
+
+fn mock_prove_registry<
+    R: SubCircuitRegistry<Fr>,
+    const MAX_INNER_BLOCKS: usize,
+    const MOCK_RANDOMNESS: u64,
+>(
+    builder: &CircuitInputBuilder,
+) -> Result<Result<(), Vec<VerifyFailure>>, bus_mapping::Error> {
+    let mut block = block_convert(&builder.block, &builder.code_db)?;
+    block.randomness = Fr::from(MOCK_RANDOMNESS);
+    // The tables loaded from the witness take as many rows as the
+    // sub-circuits assigning them in the full circuit.
+    let rows_needed = DefaultSubCircuits::<Fr>::min_num_rows_block(&block)
+        .into_iter()
+        .map(|(_, rows)| rows)
+        .max()
+        .unwrap_or_default();
+    let k = log2_ceil(DefaultSubCircuits::<Fr>::unusable_rows() + rows_needed);
+
+    let (_, circuit, instance) =
+        SuperCircuit::<Fr, MAX_INNER_BLOCKS, MOCK_RANDOMNESS, R>::build_from_witness_block(block)?;
+    let prover = MockProver::run(k, &circuit, instance).expect("the mock prover should run");
+    Ok(prover.verify_par())
+}
@@ -0,0 +1,660 @@
+//! Registry of the sub-circuits of the super circuit.
+//!
+//! Each sub-circuit implements [`SuperSubCircuit`], declaring the shared
+//! tables it uses and how to configure it with them. A registry is a tuple of
+//! sub-circuits, configured and synthesized in its order, so that a fork can
+//! add or remove sub-circuits by defining its own tuple:
+//!
+//! ```ignore
+//! type L2SubCircuits<F> = (KeccakCircuit<F>, ..., EvmCircuit<F>, L2Circuit<F>);
+//! type L2SuperCircuit<F> = SuperCircuit<F, 1, 0x100, L2SubCircuits<F>>;
+//! ```
+//!
+//! Only the shared tables declared by the registered sub-circuits are
+//! constructed, and the ones none of them assign are loaded from the witness.
+//! Reordering the registry changes the verifying key.
+//!
+//! Each shared table has a schema version, bumped on any change of its
+//! columns or of their order, and the sub-circuits declare the version they
+//! were written against. Composing sub-circuits written against another
+//! version than the current one panics when the super circuit is configured,
+//! instead of silently looking up into mismatching columns.
+
+use super::SuperCircuitConfigArgs;
+use crate::{
+    table::{
+        BlockTable, BytecodeTable, ColumnSchema, CopyTable, ExpTable, KeccakTable, LogsBloomTable,
+        LookupTable, MptTable, P256VerifyTable, PoseidonTable, ReceiptTable,
+        RlpFsmRlpTable as RlpTable, RwTable, SigTable, TxTable,
+    },
+    util::{Challenges, SubCircuit},
+    witness::Block,
+};
+use bus_mapping::progress::{ProgressReporter, Stage};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{Column, ConstraintSystem, Error, Fixed},
+};
+use serde::{Deserialize, Serialize};
+use std::{any::Any, collections::HashSet, fmt::Debug};
+
+/// Lookup tables shared between the sub-circuits, in the order they are
+/// constructed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SharedTable {
+    /// Fields of the txs and their call data
+    Tx,
+    /// Rws sorted by the state circuit
+    Rw,
+    /// Rws in the chronological order of the EVM circuit
+    ChronologicalRw,
+    /// State updates proved by the MPT circuit
+    Mpt,
+    /// Poseidon hashes
+    Poseidon,
+    /// Bytecodes by code hash
+    Bytecode,
+    /// Block header fields
+    Block,
+    /// Copy events, with the fixed column enabling them
+    Copy,
+    /// Exponentiations
+    Exp,
+    /// RLP encoding of the txs
+    Rlp,
+    /// Keccak hashes
+    Keccak,
+    /// Tx receipts
+    Receipt,
+    /// Signatures recovered by the ECRecover calls
+    Sig,
+    /// Valid signatures of the P256VERIFY calls
+    P256Verify,
+    /// Addresses and topics of the logs, bound to the logs bloom of their
+    /// block
+    LogsBloom,
+}
+
+impl SharedTable {
+    /// Version of the schema of the table. It must be bumped on any change of
+    /// the columns of the table or of their order, along with the versions
+    /// declared by the sub-circuits using the table once they are updated.
+    pub fn version(self) -> u32 {
+        match self {
+            SharedTable::Tx => 1,
+            SharedTable::Rw => 1,
+            SharedTable::ChronologicalRw => 1,
+            SharedTable::Mpt => 1,
+            SharedTable::Poseidon => 1,
+            SharedTable::Bytecode => 1,
+            SharedTable::Block => 1,
+            SharedTable::Copy => 1,
+            SharedTable::Exp => 1,
+            SharedTable::Rlp => 1,
+            SharedTable::Keccak => 1,
+            SharedTable::Receipt => 1,
+            SharedTable::Sig => 1,
+            SharedTable::P256Verify => 1,
+            SharedTable::LogsBloom => 1,
+        }
+    }
+}
+
+/// Machine-readable descriptor of the columns of a shared table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    /// The table
+    pub table: SharedTable,
+    /// Version of the schema
+    pub version: u32,
+    /// Columns of the table, in the order of its lookups
+    pub columns: Vec<ColumnSchema>,
+}
+
+/// The shared tables constructed for the registered sub-circuits.
+#[derive(Clone, Debug, Default)]
+pub struct SharedTables {
+    tx: Option<TxTable>,
+    rw: Option<RwTable>,
+    chronological_rw: Option<RwTable>,
+    mpt: Option<MptTable>,
+    poseidon: Option<PoseidonTable>,
+    bytecode: Option<BytecodeTable>,
+    block: Option<BlockTable>,
+    copy: Option<(Column<Fixed>, CopyTable)>,
+    exp: Option<ExpTable>,
+    rlp: Option<RlpTable>,
+    keccak: Option<KeccakTable>,
+    receipt: Option<ReceiptTable>,
+    sig: Option<SigTable>,
+    p256_verify: Option<P256VerifyTable>,
+    logs_bloom: Option<LogsBloomTable>,
+}
+
+macro_rules! table_getters {
+    ($($name:ident: $table:ty => $tag:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("The `", stringify!($tag), "` table, which must be declared by the sub-circuit using it.")]
+            pub fn $name(&self) -> &$table {
+                self.$name.as_ref().unwrap_or_else(|| {
+                    panic!("the {:?} table is used but not declared", SharedTable::$tag)
+                })
+            }
+        )*
+    };
+}
+
+impl SharedTables {
+    /// Construct the tables in `used`, calling `checkpoint` after each of
+    /// them.
+    pub(crate) fn construct<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        used: &HashSet<SharedTable>,
+        mut checkpoint: impl FnMut(&ConstraintSystem<F>, &'static str),
+    ) -> Self {
+        let mut tables = Self::default();
+        if used.contains(&SharedTable::Tx) {
+            tables.tx = Some(TxTable::construct(meta));
+            checkpoint(meta, "tx table");
+        }
+        if used.contains(&SharedTable::Rw) {
+            tables.rw = Some(RwTable::construct(meta));
+            checkpoint(meta, "rw table");
+        }
+        // The EVM circuit reads the rws in chronological order from its own
+        // table, tied to the sorted one of the state circuit by their
+        // fingerprints.
+        if used.contains(&SharedTable::ChronologicalRw) {
+            tables.chronological_rw = Some(RwTable::construct(meta));
+            checkpoint(meta, "chronological rw table");
+        }
+        if used.contains(&SharedTable::Mpt) {
+            tables.mpt = Some(MptTable::construct(meta));
+            checkpoint(meta, "mpt table");
+        }
+        if used.contains(&SharedTable::Poseidon) {
+            tables.poseidon = Some(PoseidonTable::construct(meta));
+            checkpoint(meta, "poseidon table");
+        }
+        if used.contains(&SharedTable::Bytecode) {
+            tables.bytecode = Some(BytecodeTable::construct(meta));
+            checkpoint(meta, "bytecode table");
+        }
+        if used.contains(&SharedTable::Block) {
+            tables.block = Some(BlockTable::construct(meta));
+            checkpoint(meta, "block table");
+        }
+        if used.contains(&SharedTable::Copy) {
+            let q_copy_table = meta.fixed_column();
+            log::debug!("q_copy_table {:?}", q_copy_table);
+            tables.copy = Some((q_copy_table, CopyTable::construct(meta, q_copy_table)));
+            checkpoint(meta, "copy table");
+        }
+        if used.contains(&SharedTable::Exp) {
+            tables.exp = Some(ExpTable::construct(meta));
+            checkpoint(meta, "exp table");
+        }
+        if used.contains(&SharedTable::Rlp) {
+            tables.rlp = Some(RlpTable::construct(meta));
+            checkpoint(meta, "rlp table");
+        }
+        if used.contains(&SharedTable::Keccak) {
+            tables.keccak = Some(KeccakTable::construct(meta));
+            checkpoint(meta, "keccak table");
+        }
+        if used.contains(&SharedTable::Receipt) {
+            tables.receipt = Some(ReceiptTable::construct(meta));
+            checkpoint(meta, "receipt table");
+        }
+        if used.contains(&SharedTable::Sig) {
+            tables.sig = Some(SigTable::construct(meta));
+            checkpoint(meta, "sig table");
+        }
+        if used.contains(&SharedTable::P256Verify) {
+            tables.p256_verify = Some(P256VerifyTable::construct(meta));
+            checkpoint(meta, "p256 verify table");
+        }
+        if used.contains(&SharedTable::LogsBloom) {
+            tables.logs_bloom = Some(LogsBloomTable::construct(meta));
+            checkpoint(meta, "logs bloom table");
+        }
+        tables
+    }
+
+    /// Load the tables in `unassigned` straight from the witness, for the
+    /// registries without the sub-circuits assigning them. Their content is
+    /// trusted instead of proved.
+    pub(crate) fn load_from_witness<F: Field>(
+        &self,
+        unassigned: &HashSet<SharedTable>,
+        block: &Block<F>,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let params = &block.circuits_params;
+        if unassigned.contains(&SharedTable::Tx) {
+            self.tx().load(
+                layouter,
+                &block.txs,
+                params.max_txs,
+                params.max_calldata,
+                block.chain_id.as_u64(),
+                challenges,
+            )?;
+        }
+        if unassigned.contains(&SharedTable::Rw) {
+            self.rw().load(
+                layouter,
+                block.sorted_rws(),
+                params.max_rws,
+                challenges.evm_word(),
+            )?;
+        }
+        if unassigned.contains(&SharedTable::ChronologicalRw) {
+            self.chronological_rw().load(
+                layouter,
+                &block.rws.table_assignments_chronological(),
+                params.max_rws,
+                challenges.evm_word(),
+            )?;
+        }
+        if unassigned.contains(&SharedTable::Mpt) {
+            self.mpt().load(
+                layouter,
+                &block.mpt_updates,
+                params.max_mpt_rows,
+                challenges.evm_word(),
+            )?;
+        }
+        if unassigned.contains(&SharedTable::Poseidon) {
+            self.poseidon().dev_load(
+                layouter,
+                block.bytecodes.values().map(|bytecode| &bytecode.bytes),
+            )?;
+        }
+        if unassigned.contains(&SharedTable::Bytecode) {
+            self.bytecode()
+                .dev_load(layouter, block.bytecodes.values(), challenges)?;
+        }
+        if unassigned.contains(&SharedTable::Block) {
+            self.block().dev_load(
+                layouter,
+                &block.context,
+                &block.txs,
+                params.max_inner_blocks,
+                challenges,
+            )?;
+        }
+        if unassigned.contains(&SharedTable::Copy) {
+            self.copy().dev_load(layouter, block, challenges)?;
+        }
+        if unassigned.contains(&SharedTable::Exp) {
+            self.exp().dev_load(layouter, block)?;
+        }
+        if unassigned.contains(&SharedTable::Rlp) {
+            self.rlp()
+                .dev_load(layouter, block.txs.clone(), challenges)?;
+        }
+        if unassigned.contains(&SharedTable::Keccak) {
+            self.keccak()
+                .dev_load(layouter, &block.keccak_inputs, challenges)?;
+        }
+        if unassigned.contains(&SharedTable::Receipt) {
+            self.receipt()
+                .load(layouter, &block.txs, params.max_txs, challenges)?;
+        }
+        if unassigned.contains(&SharedTable::Sig) {
+            self.sig()
+                .dev_load(layouter, &block.ecrecover_sign_datas(), challenges)?;
+        }
+        if unassigned.contains(&SharedTable::P256Verify) {
+            self.p256_verify()
+                .dev_load(layouter, &block.p256_verify_inputs(), challenges)?;
+        }
+        if unassigned.contains(&SharedTable::LogsBloom) {
+            self.logs_bloom()
+                .dev_load(layouter, &block.txs, challenges)?;
+        }
+        Ok(())
+    }
+
+    /// Schemas of the constructed tables, in the order they are constructed.
+    pub fn schemas<F: Field>(&self) -> Vec<TableSchema> {
+        fn schema<F: Field>(table: SharedTable, columns: &impl LookupTable<F>) -> TableSchema {
+            TableSchema {
+                table,
+                version: table.version(),
+                columns: columns.column_schemas(),
+            }
+        }
+
+        [
+            self.tx.as_ref().map(|t| schema::<F>(SharedTable::Tx, t)),
+            self.rw.as_ref().map(|t| schema::<F>(SharedTable::Rw, t)),
+            self.chronological_rw
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::ChronologicalRw, t)),
+            self.mpt.as_ref().map(|t| schema::<F>(SharedTable::Mpt, t)),
+            self.poseidon
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::Poseidon, t)),
+            self.bytecode
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::Bytecode, t)),
+            self.block
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::Block, t)),
+            self.copy
+                .as_ref()
+                .map(|(_, t)| schema::<F>(SharedTable::Copy, t)),
+            self.exp.as_ref().map(|t| schema::<F>(SharedTable::Exp, t)),
+            self.rlp.as_ref().map(|t| schema::<F>(SharedTable::Rlp, t)),
+            self.keccak
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::Keccak, t)),
+            self.receipt
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::Receipt, t)),
+            self.sig.as_ref().map(|t| schema::<F>(SharedTable::Sig, t)),
+            self.p256_verify
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::P256Verify, t)),
+            self.logs_bloom
+                .as_ref()
+                .map(|t| schema::<F>(SharedTable::LogsBloom, t)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    table_getters!(
+        tx: TxTable => Tx,
+        rw: RwTable => Rw,
+        chronological_rw: RwTable => ChronologicalRw,
+        mpt: MptTable => Mpt,
+        poseidon: PoseidonTable => Poseidon,
+        bytecode: BytecodeTable => Bytecode,
+        block: BlockTable => Block,
+        exp: ExpTable => Exp,
+        rlp: RlpTable => Rlp,
+        keccak: KeccakTable => Keccak,
+        receipt: ReceiptTable => Receipt,
+        sig: SigTable => Sig,
+        p256_verify: P256VerifyTable => P256Verify,
+        logs_bloom: LogsBloomTable => LogsBloom,
+    );
+
+    /// The copy table, which must be declared by the sub-circuit using it.
+    pub fn copy(&self) -> &CopyTable {
+        &self.q_copy_and_copy().1
+    }
+
+    /// The fixed column enabling the rows of the copy table.
+    pub fn q_copy(&self) -> Column<Fixed> {
+        self.q_copy_and_copy().0
+    }
+
+    fn q_copy_and_copy(&self) -> &(Column<Fixed>, CopyTable) {
+        self.copy
+            .as_ref()
+            .unwrap_or_else(|| panic!("the {:?} table is used but not declared", SharedTable::Copy))
+    }
+}
+
+/// A sub-circuit which can be registered in the super circuit.
+pub trait SuperSubCircuit<F: Field>: SubCircuit<F> + Clone + Debug + Default + 'static {
+    /// Name of the sub-circuit in logs and reports
+    const NAME: &'static str;
+    /// Shared tables the sub-circuit assigns or looks up into, with the
+    /// version of their schema it was written against
+    const TABLES: &'static [(SharedTable, u32)];
+    /// Shared tables the sub-circuit assigns. The declared tables which no
+    /// registered sub-circuit assigns are loaded from the witness.
+    const ASSIGNS: &'static [SharedTable] = &[];
+
+    /// Configure the sub-circuit with the shared tables it declares.
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config;
+
+    /// Build the sub-circuit from the witness block.
+    fn from_block(block: &Block<F>) -> Self {
+        Self::new_from_block(block)
+    }
+
+    /// Rows the sub-circuit needs in the super circuit, without and with
+    /// padding.
+    fn min_num_rows(block: &Block<F>) -> (usize, usize) {
+        Self::min_num_rows_block(block)
+    }
+
+    /// Assign the shared tables filled from the witness of this sub-circuit.
+    /// It is called for all sub-circuits before any of them is synthesized.
+    fn load_tables(
+        &self,
+        _tables: &SharedTables,
+        _challenges: &Challenges<Value<F>>,
+        _layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Constrain the cells shared with the other registered sub-circuits. It
+    /// is called for all sub-circuits after all of them are synthesized.
+    fn connect<R: SubCircuitRegistry<F>>(
+        &self,
+        _circuits: &R,
+        _layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Sub-circuits of a super circuit, implemented for tuples of
+/// [`SuperSubCircuit`]s.
+pub trait SubCircuitRegistry<F: Field>: Clone + Debug + Default + 'static {
+    /// Configurations of the sub-circuits
+    type Config: Clone;
+
+    /// Names of the sub-circuits, in order.
+    fn names() -> Vec<&'static str>;
+
+    /// Shared tables declared by the sub-circuits.
+    fn tables() -> HashSet<SharedTable>;
+
+    /// Shared tables assigned by the sub-circuits.
+    fn assigned_tables() -> HashSet<SharedTable>;
+
+    /// Shared tables declared but not assigned by the sub-circuits, which are
+    /// loaded from the witness.
+    fn unassigned_tables() -> HashSet<SharedTable> {
+        &Self::tables() - &Self::assigned_tables()
+    }
+
+    /// Configure the sub-circuits, calling `checkpoint` after each of them.
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+        checkpoint: &mut impl FnMut(&ConstraintSystem<F>, &'static str),
+    ) -> Self::Config;
+
+    /// Build the sub-circuits from the witness block.
+    fn new_from_block(block: &Block<F>) -> Self;
+
+    /// Highest number of unusable rows of the sub-circuits.
+    fn unusable_rows() -> usize;
+
+    /// Rows each sub-circuit needs, without and with padding.
+    fn min_num_rows_block(block: &Block<F>) -> Vec<(usize, usize)>;
+
+    /// Instance columns of the sub-circuits, in order.
+    fn instance(&self) -> Vec<Vec<F>>;
+
+    /// Assign the shared tables filled from the witness of the sub-circuits.
+    fn load_tables(
+        &self,
+        tables: &SharedTables,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error>;
+
+    /// Synthesize the sub-circuits, then connect them. The synthesis is
+    /// reported to `progress` before each sub-circuit, and fails once
+    /// `progress` is cancelled.
+    fn synthesize(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+        progress: &ProgressReporter,
+    ) -> Result<(), Error>;
+
+    /// The registered sub-circuit of type `C`, if any.
+    fn find<C: 'static>(&self) -> Option<&C>;
+}
+
+/// Panic if the sub-circuit `name` was written against another version of
+/// the schema of one of its `tables` than the current one.
+pub(crate) fn check_table_versions(name: &str, tables: &[(SharedTable, u32)]) {
+    for (table, version) in tables {
+        assert_eq!(
+            *version,
+            table.version(),
+            "the {} was written against version {} of the {:?} table schema, which is now at version {}",
+            name,
+            version,
+            table,
+            table.version()
+        );
+    }
+}
+
+/// Report the assignment of `done` out of `total` sub-circuits, failing the
+/// synthesis if it was cancelled.
+fn report_assignment(progress: &ProgressReporter, done: usize, total: usize) -> Result<(), Error> {
+    progress
+        .report(Stage::Assignment, done, total)
+        .map_err(|_| Error::Synthesis)
+}
+
+macro_rules! impl_registry {
+    ($($circuit:ident $config:ident),+) => {
+        impl<F: Field, $($circuit: SuperSubCircuit<F>),+> SubCircuitRegistry<F> for ($($circuit,)+)
+        where
+            $($circuit::Config: Clone,)+
+        {
+            type Config = ($($circuit::Config,)+);
+
+            fn names() -> Vec<&'static str> {
+                vec![$($circuit::NAME),+]
+            }
+
+            fn tables() -> HashSet<SharedTable> {
+                [$($circuit::TABLES),+]
+                    .concat()
+                    .into_iter()
+                    .map(|(table, _)| table)
+                    .collect()
+            }
+
+            fn assigned_tables() -> HashSet<SharedTable> {
+                [$($circuit::ASSIGNS),+].concat().into_iter().collect()
+            }
+
+            fn configure(
+                meta: &mut ConstraintSystem<F>,
+                tables: &SharedTables,
+                args: &SuperCircuitConfigArgs<F>,
+                checkpoint: &mut impl FnMut(&ConstraintSystem<F>, &'static str),
+            ) -> Self::Config {
+                ($({
+                    check_table_versions($circuit::NAME, $circuit::TABLES);
+                    let config = $circuit::configure(meta, tables, args);
+                    checkpoint(meta, $circuit::NAME);
+                    config
+                },)+)
+            }
+
+            fn new_from_block(block: &Block<F>) -> Self {
+                ($($circuit::from_block(block),)+)
+            }
+
+            fn unusable_rows() -> usize {
+                itertools::max([$($circuit::unusable_rows()),+]).unwrap()
+            }
+
+            fn min_num_rows_block(block: &Block<F>) -> Vec<(usize, usize)> {
+                vec![$($circuit::min_num_rows(block)),+]
+            }
+
+            #[allow(non_snake_case)]
+            fn instance(&self) -> Vec<Vec<F>> {
+                let ($($circuit,)+) = self;
+                [$($circuit.instance()),+].concat()
+            }
+
+            #[allow(non_snake_case)]
+            fn load_tables(
+                &self,
+                tables: &SharedTables,
+                challenges: &Challenges<Value<F>>,
+                layouter: &mut impl Layouter<F>,
+            ) -> Result<(), Error> {
+                let ($($circuit,)+) = self;
+                $($circuit.load_tables(tables, challenges, layouter)?;)+
+                Ok(())
+            }
+
+            #[allow(non_snake_case)]
+            fn synthesize(
+                &self,
+                config: &Self::Config,
+                challenges: &Challenges<Value<F>>,
+                layouter: &mut impl Layouter<F>,
+                progress: &ProgressReporter,
+            ) -> Result<(), Error> {
+                let ($($circuit,)+) = self;
+                let ($($config,)+) = config;
+                let total = Self::names().len();
+                let mut done = 0;
+                $(
+                    report_assignment(progress, done, total)?;
+                    {
+                        let _span = tracing::info_span!("synthesize", circuit = $circuit::NAME)
+                            .entered();
+                        $circuit.synthesize_sub($config, challenges, layouter)?;
+                    }
+                    done += 1;
+                )+
+                report_assignment(progress, done, total)?;
+                $($circuit.connect(self, layouter)?;)+
+                Ok(())
+            }
+
+            #[allow(non_snake_case)]
+            fn find<C: 'static>(&self) -> Option<&C> {
+                let ($($circuit,)+) = self;
+                None$(.or_else(|| ($circuit as &dyn Any).downcast_ref::<C>()))+
+            }
+        }
+    };
+}
+
+macro_rules! impl_registries {
+    ($circuit:ident $config:ident) => {
+        impl_registry!($circuit $config);
+    };
+    ($circuit:ident $config:ident, $($rest:ident $rest_config:ident),+) => {
+        impl_registry!($circuit $config, $($rest $rest_config),+);
+        impl_registries!($($rest $rest_config),+);
+    };
+}
+
+impl_registries!(
+    A a, B b, C c, D d, E e, G g, H h, I i, J j, K k, L l, M m, N n, O o, P p, Q q
+);
@@ -1,46 +1,154 @@
 pub use super::*;
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{circuit_input_builder::CircuitProfile, evm::OpcodeId};
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use log::error;
 use mock::{eth, TestContext, MOCK_CHAIN_ID, MOCK_DIFFICULTY};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
-use std::{collections::HashMap, env::set_var};
+use std::{
+    collections::{HashMap, HashSet},
+    env::set_var,
+};
 
+use crate::{
+    circuit_cost::PartCost,
+    table::{ColumnKind, ColumnSchema},
+};
 use eth_types::{address, bytecode, geth_types::GethData, Bytecode, Word};
+use registry::SharedTable;
 
 #[test]
 fn super_circuit_degree() {
     let mut cs = ConstraintSystem::<Fr>::default();
-    SuperCircuit::<_, 1, 32, 64, 0x100>::configure(&mut cs);
+    SuperCircuit::<_, 64, 0x100>::configure(&mut cs);
     log::info!("super circuit degree: {}", cs.degree());
     log::info!("super circuit minimum_rows: {}", cs.minimum_rows());
     assert!(cs.degree() <= 9);
 }
 
-fn test_super_circuit<
-    const MAX_TXS: usize,
-    const MAX_CALLDATA: usize,
-    const MAX_INNER_BLOCKS: usize,
-    const MOCK_RANDOMNESS: u64,
->(
-    block: GethData,
-    circuits_params: CircuitsParams,
-) {
+#[test]
+fn super_circuit_single_instance_column() {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    SuperCircuit::<_, 64, 0x100>::configure(&mut cs);
+    assert_eq!(cs.num_instance_columns, 1);
+}
+
+#[test]
+fn super_circuit_cost_report() {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    let (config, _) = SuperCircuit::<_, 64, 0x100>::configure(&mut cs);
+    let report = config.cost_report(&cs);
+    assert_eq!(report.degree, cs.degree());
+    assert_eq!(report.parts.last().unwrap().name, "evm circuit");
+    let total = |cost: fn(&PartCost) -> usize| report.parts.iter().map(cost).sum::<usize>();
+    assert_eq!(total(|part| part.advice_columns), cs.num_advice_columns);
+    assert_eq!(total(|part| part.lookups), cs.lookups.len());
+    assert!(report
+        .parts
+        .iter()
+        .all(|part| part.gate_degree <= report.degree && part.lookup_degree <= report.degree));
+}
+
+#[test]
+fn super_circuit_custom_registry() {
+    type Registry = (KeccakCircuit<Fr>, ExpCircuit<Fr>);
+    assert_eq!(Registry::names(), vec!["keccak circuit", "exp circuit"]);
+    assert_eq!(
+        Registry::tables(),
+        HashSet::from([SharedTable::Keccak, SharedTable::Exp])
+    );
+    assert!(Registry::unassigned_tables().is_empty());
+
+    let mut cs = ConstraintSystem::<Fr>::default();
+    let (config, _) = SuperCircuit::<_, 64, 0x100, Registry>::configure(&mut cs);
+    let names: Vec<_> = config
+        .cost_report(&cs)
+        .parts
+        .iter()
+        .map(|part| part.name)
+        .collect();
+    assert_eq!(
+        names,
+        vec!["exp table", "keccak table", "keccak circuit", "exp circuit"]
+    );
+}
+
+#[test]
+fn super_circuit_table_schemas() {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    let (config, _) = SuperCircuit::<_, 64, 0x100>::configure(&mut cs);
+    let schemas = config.table_schemas();
+    assert_eq!(schemas.len(), DefaultSubCircuits::<Fr>::tables().len());
+    for schema in &schemas {
+        assert_eq!(schema.version, schema.table.version());
+        assert!(schema
+            .columns
+            .iter()
+            .map(|column| &column.name)
+            .all_unique());
+    }
+    let tx_table = &schemas[0];
+    assert_eq!(tx_table.table, SharedTable::Tx);
+    assert_eq!(
+        tx_table.columns[0],
+        ColumnSchema {
+            name: "q_enable".to_string(),
+            kind: ColumnKind::Fixed
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "written against version 0 of the Tx table schema")]
+fn super_circuit_table_version_mismatch() {
+    registry::check_table_versions("test circuit", &[(SharedTable::Tx, 0)]);
+}
+
+#[test]
+fn super_circuit_config_digest() {
+    type Super = SuperCircuit<Fr, 64, 0x100>;
+    let params = CircuitsParams {
+        max_txs: 1,
+        max_calldata: 32,
+        max_inner_blocks: 64,
+        ..Default::default()
+    };
+    let digest = Super::config_digest(&params);
+    assert_eq!(digest, Super::config_digest(&params));
+
+    let other_params = CircuitsParams {
+        max_rws: params.max_rws + 1,
+        ..params
+    };
+    assert_ne!(digest, Super::config_digest(&other_params));
+    // the max number of txs is a runtime parameter, but still part of the
+    // verifying key
+    let more_txs_params = CircuitsParams {
+        max_txs: params.max_txs + 1,
+        ..params
+    };
+    assert_ne!(digest, Super::config_digest(&more_txs_params));
+}
+
+fn set_mock_env() {
     let mut difficulty_be_bytes = [0u8; 32];
     let mut chain_id_be_bytes = [0u8; 32];
     MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
     MOCK_CHAIN_ID.to_big_endian(&mut chain_id_be_bytes);
     set_var("CHAIN_ID", hex::encode(chain_id_be_bytes));
     set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+}
+
+fn test_super_circuit<const MAX_INNER_BLOCKS: usize, const MOCK_RANDOMNESS: u64>(
+    block: GethData,
+    circuits_params: CircuitsParams,
+) {
+    set_mock_env();
 
     let (k, circuit, instance, _) =
-        SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>::build(
-            block,
-            circuits_params,
-        )
-        .unwrap();
+        SuperCircuit::<Fr, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>::build(block, circuits_params)
+            .unwrap();
     let prover = MockProver::run(k, &circuit, instance).unwrap();
     prover.assert_satisfied_par();
     let res = prover.verify_par();
@@ -205,13 +313,14 @@ fn serial_test_super_circuit_1tx_1max_tx() {
         max_mpt_rows: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_rlp_rows: 500,
     };
-    test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
-        block,
-        circuits_params,
-    );
+    test_super_circuit::<MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(block, circuits_params);
 }
 
 #[ignore]
@@ -232,15 +341,16 @@ fn serial_test_super_circuit_1tx_deploy_2max_tx() {
         max_mpt_rows: 512,
         max_bytecode: 512,
         max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_exp_steps: 256,
         max_evm_rows: 0,
         max_rlp_rows: 500,
     };
-    test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
-        block,
-        circuits_params,
-    );
+    test_super_circuit::<MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(block, circuits_params);
 }
 
 #[ignore]
@@ -261,13 +371,14 @@ fn serial_test_super_circuit_1tx_2max_tx() {
         max_mpt_rows: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_rlp_rows: 500,
     };
-    test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
-        block,
-        circuits_params,
-    );
+    test_super_circuit::<MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(block, circuits_params);
 }
 
 #[ignore]
@@ -288,15 +399,16 @@ fn serial_test_super_circuit_2tx_4max_tx() {
         max_bytecode: 512,
         max_mpt_rows: 512,
         max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_exp_steps: 256,
         max_evm_rows: 0,
         max_rlp_rows: 800,
     };
-    test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
-        block,
-        circuits_params,
-    );
+    test_super_circuit::<MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(block, circuits_params);
 }
 
 #[ignore]
@@ -317,11 +429,40 @@ fn serial_test_super_circuit_2tx_2max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        keccak_rows_per_round: 0,
+        max_ecrecover_sigs: 0,
+        max_log_items: 0,
+        profile: CircuitProfile::Full,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_rlp_rows: 500,
     };
-    test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
-        block,
-        circuits_params,
-    );
+    test_super_circuit::<MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(block, circuits_params);
+}
+
+#[ignore]
+#[cfg(feature = "circuit-profiles")]
+#[test]
+fn serial_test_super_circuit_minimal_profiles() {
+    set_mock_env();
+    for circuit_profile in [CircuitProfile::EvmState, CircuitProfile::TxPi] {
+        let circuits_params = CircuitsParams {
+            max_txs: 1,
+            max_calldata: 256,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_exp_steps: 256,
+            max_bytecode: 512,
+            max_mpt_rows: 512,
+            max_inner_blocks: 1,
+            max_rlp_rows: 500,
+            profile: circuit_profile,
+            ..Default::default()
+        };
+        let (_, result) =
+            profile::mock_prove::<1, TEST_MOCK_RANDOMNESS>(block_1tx(), circuits_params).unwrap();
+        if let Err(err) = result {
+            error!("Verification failures of {:?}: {:#?}", circuit_profile, err);
+            panic!("Failed verification");
+        }
+    }
 }
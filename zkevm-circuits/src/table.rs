@@ -5,22 +5,33 @@ use crate::{
     evm_circuit::util::rlc,
     exp_circuit::param::{OFFSET_INCREMENT, ROWS_PER_STEP},
     impl_expr,
-    util::{build_tx_log_address, Challenges},
+    tx_circuit::max_rlp_bytes,
+    util::{
+        build_tx_log_address, fast_mock,
+        word::{self, WordLoHi},
+        Challenges,
+    },
     witness::{
-        Block, BlockContext, BlockContexts, Bytecode, MptUpdateRow, MptUpdates, RlpFsmWitnessGen,
-        Rw, RwMap, RwRow, Transaction,
+        Block, BlockContext, BlockContexts, Bytecode, MptUpdateRow, MptUpdates, Receipt,
+        RlpFsmWitnessGen, Rw, RwMap, RwRow, Transaction,
     },
 };
-use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent, CopyStep, ExpEvent};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent, CopyStep, ExpEvent},
+    precompile::{P256VerifyInput, PrecompileCalls},
+};
 use core::iter::once;
-use eth_types::{Field, ToLittleEndian, ToScalar, ToWord, Word, U256};
+use eth_types::{
+    sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
+    Field, ToScalar, ToWord, Word, U256,
+};
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
-    util::{split_u256, split_u256_limb64},
+    util::Expr,
 };
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{Layouter, Region, Value},
+    circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{Advice, Any, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
@@ -33,9 +44,33 @@ use halo2_proofs::plonk::SecondPhase;
 
 use itertools::Itertools;
 use keccak256::plain::Keccak;
+use serde::{Deserialize, Serialize};
 use std::array;
 use strum_macros::{EnumCount, EnumIter};
 
+/// Type of a column of a lookup table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnKind {
+    /// Advice column assigned in `phase`
+    Advice {
+        /// Phase of the column
+        phase: u8,
+    },
+    /// Fixed column
+    Fixed,
+    /// Instance column
+    Instance,
+}
+
+/// Column of a lookup table, as exported in its schema.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    /// Annotation of the column
+    pub name: String,
+    /// Type of the column
+    pub kind: ColumnKind,
+}
+
 /// Trait used to define lookup tables
 pub trait LookupTable<F: Field> {
     /// Returns the list of ALL the table columns following the table order.
@@ -87,6 +122,24 @@ pub trait LookupTable<F: Field> {
             .zip(self.annotations().iter())
             .for_each(|(&col, ann)| region.name_column(|| ann, col))
     }
+
+    /// Returns the name and type of each column, following the table order.
+    fn column_schemas(&self) -> Vec<ColumnSchema> {
+        self.columns()
+            .iter()
+            .zip(self.annotations())
+            .map(|(col, name)| ColumnSchema {
+                name,
+                kind: match col.column_type() {
+                    Any::Advice(advice) => ColumnKind::Advice {
+                        phase: advice.phase(),
+                    },
+                    Any::Fixed => ColumnKind::Fixed,
+                    Any::Instance => ColumnKind::Instance,
+                },
+            })
+            .collect()
+    }
 }
 
 impl<F: Field, C: Into<Column<Any>> + Copy, const W: usize> LookupTable<F> for [C; W] {
@@ -131,7 +184,8 @@ pub enum TxFieldTag {
     CallDataRLC,
     /// CallDataLength
     CallDataLength,
-    /// Gas cost for transaction call data (4 for byte == 0, 16 otherwise)
+    /// Gas cost for transaction call data (4 for byte == 0, 16 otherwise),
+    /// which is 4 per token of the call data of EIP-7623
     CallDataGasCost,
     /// Gas cost for rlp-encoded bytes of unsigned transaction (4 for byte == 0, 16 otherwise)
     TxDataGasCost,
@@ -160,11 +214,56 @@ pub enum TxFieldTag {
     TxHashRLC,
     /// TxHash: Hash of the transaction with the signature
     TxHash,
+    /// TxType: the [`eth_types::geth_types::TxType`] of the transaction, so
+    /// that unsigned L1 message txs can be told apart.
+    TxType,
+    /// IsSystem: whether the tx is a gas-free system tx, which must have a
+    /// zero gas price and is not checked against the base fee.
+    IsSystem,
+    /// IsInvalid: whether the tx fails the nonce or the balance check and is
+    /// included as a no-op.
+    IsInvalid,
+    /// IsPrague: whether the timestamp of the block of the tx reaches the
+    /// Prague time, so that a valid tx pays at least the floor cost of its
+    /// call data of EIP-7623.
+    IsPrague,
+    /// AuthorizationListLength: the number of authorizations of an EIP-7702
+    /// set-code tx, zero for the other types of tx.
+    #[cfg(feature = "prague")]
+    AuthorizationListLength,
+    /// Authorization: a field of an authorization of a set-code tx, at the
+    /// index given by [`AuthorizationField::index`].
+    #[cfg(feature = "prague")]
+    Authorization,
     /// The block number in which this tx is included.
     BlockNumber,
+    /// RlpByte: byte at the index of the signed RLP encoding of the tx, whose
+    /// length, RLC and hash are TxHashLength, TxHashRLC and TxHash.
+    RlpByte,
 }
 impl_expr!(TxFieldTag);
 
+/// Field of an authorization of an EIP-7702 set-code tx in the rows of
+/// [`TxFieldTag::Authorization`].
+#[cfg(feature = "prague")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+pub enum AuthorizationField {
+    /// Authority of the authorization, zero if it isn't valid on the chain
+    Authority = 0,
+    /// Address the authority delegates to
+    Address,
+    /// Nonce of the authority the authorization is valid for
+    Nonce,
+}
+
+#[cfg(feature = "prague")]
+impl AuthorizationField {
+    /// Index in the tx table of the field of the `i`th authorization.
+    pub const fn index(self, i: usize) -> usize {
+        3 * i + self as usize
+    }
+}
+
 impl From<TxFieldTag> for usize {
     fn from(t: TxFieldTag) -> Self {
         t as usize
@@ -183,7 +282,7 @@ pub struct TxTable {
     pub tx_id: Column<Advice>,
     /// Tag (TxContextFieldTag)
     pub tag: Column<Fixed>,
-    /// Index for Tag = CallData
+    /// Index for Tag = CallData, RlpByte and the fields of the authorizations
     pub index: Column<Advice>,
     /// Value
     pub value: Column<Advice>,
@@ -205,6 +304,10 @@ impl TxTable {
 
     /// Assign the `TxTable` from a list of block `Transaction`s, following the
     /// same layout that the Tx Circuit uses.
+    ///
+    /// Besides the fields of the txs, the table holds the bytes of their
+    /// signed RLP encodings, so that a circuit hashing the raw txs and one
+    /// reading their fields look up the same table.
     pub fn load<F: Field>(
         &self,
         layouter: &mut impl Layouter<F>,
@@ -227,6 +330,15 @@ impl TxTable {
             sum_txs_calldata,
             max_calldata,
         );
+        #[cfg(feature = "prague")]
+        for tx in txs {
+            assert!(
+                tx.authorization_list.len() <= eth_types::eip7702::MAX_AUTHORIZATIONS_PER_TX,
+                "authorization_list.len() <= MAX_AUTHORIZATIONS_PER_TX: tx.id={}, len={}",
+                tx.id,
+                tx.authorization_list.len(),
+            );
+        }
 
         fn assign_row<F: Field>(
             region: &mut Region<'_, F>,
@@ -277,11 +389,14 @@ impl TxTable {
                 offset += 1;
 
                 // Tx Table contains an initial region that has a size parametrized by max_txs
-                // with all the tx data except for calldata, and then a second
+                // with all the tx data except for calldata, then a second
                 // region that has a size parametrized by max_calldata with all
-                // the tx calldata.  This is required to achieve a constant fixed column tag
-                // regardless of the number of input txs or the calldata size of each tx.
+                // the tx calldata, and a third one with the bytes of the signed RLP
+                // encoding of all the txs.  This is required to achieve a constant fixed
+                // column tag regardless of the number of input txs or the calldata size of
+                // each tx.
                 let mut calldata_assignments: Vec<[Value<F>; 4]> = Vec::new();
+                let mut rlp_bytes_assignments: Vec<[Value<F>; 4]> = Vec::new();
                 // Assign Tx data (all tx fields except for calldata)
                 let padding_txs = (txs.len()..max_txs)
                     .into_iter()
@@ -309,9 +424,44 @@ impl TxTable {
                         offset += 1;
                     }
                     calldata_assignments.extend(tx_calldata.iter());
+                    rlp_bytes_assignments.extend(tx.table_assignments_rlp_bytes());
+                }
+                // Assign Tx calldata, padded with zero rows to max_calldata
+                let zero_row = |tag: TxFieldTag| {
+                    [
+                        Value::known(F::zero()),
+                        Value::known(F::from(tag as u64)),
+                        Value::known(F::zero()),
+                        Value::known(F::zero()),
+                    ]
+                };
+                let calldata_padding =
+                    repeat(zero_row(TxFieldTag::CallData)).take(max_calldata - sum_txs_calldata);
+                for row in calldata_assignments.into_iter().chain(calldata_padding) {
+                    assign_row(
+                        &mut region,
+                        offset,
+                        self.q_enable,
+                        &advice_columns,
+                        &self.tag,
+                        &row,
+                        "",
+                    )?;
+                    offset += 1;
                 }
-                // Assign Tx calldata
-                for row in calldata_assignments.into_iter() {
+                // Assign the bytes of the signed RLP encoding of the txs
+                let max_rlp_bytes = max_rlp_bytes(max_txs, max_calldata);
+                if rlp_bytes_assignments.len() > max_rlp_bytes {
+                    log::error!(
+                        "rlp_bytes = {} > max_rlp_bytes = {}",
+                        rlp_bytes_assignments.len(),
+                        max_rlp_bytes,
+                    );
+                    return Err(Error::Synthesis);
+                }
+                let rlp_bytes_padding = repeat(zero_row(TxFieldTag::RlpByte))
+                    .take(max_rlp_bytes - rlp_bytes_assignments.len());
+                for row in rlp_bytes_assignments.into_iter().chain(rlp_bytes_padding) {
                     assign_row(
                         &mut region,
                         offset,
@@ -361,6 +511,501 @@ impl<F: Field> LookupTable<F> for TxTable {
     }
 }
 
+/// Tag used to identify each field of the receipt of a transaction in a row
+/// of the receipt table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, EnumCount)]
+pub enum ReceiptFieldTag {
+    /// Whether the tx succeeded
+    Status = 1,
+    /// Gas used by the txs of the block up to this one included
+    CumulativeGasUsed,
+    /// Number of logs of the tx
+    LogLength,
+    /// RLC of the hash of the data the tx reverted with, zero when it didn't
+    /// end with a REVERT
+    RevertDataHash,
+}
+impl_expr!(ReceiptFieldTag);
+
+/// Table with the status, cumulative gas used, number of logs and revert data
+/// hash of the receipts of the transactions of a block.
+///
+/// The tx ids and tags are fixed, one row per field of each of the `max_txs`
+/// txs, so that the EVM circuit, which looks up the fields of each tx at its
+/// EndTx step, determines the values of all the rows of the real txs.
+/// The logs are bound to the logs bloom of their block by the PI circuit
+/// through the [`LogsBloomTable`]. The receipts root of each block is hashed
+/// into the public input, but the receipts trie isn't computed, so the root
+/// isn't tied to the receipts by the circuits.
+#[derive(Clone, Debug)]
+pub struct ReceiptTable {
+    /// q_enable
+    pub q_enable: Column<Fixed>,
+    /// Tx ID
+    pub tx_id: Column<Fixed>,
+    /// Tag (ReceiptFieldTag)
+    pub tag: Column<Fixed>,
+    /// Value
+    pub value: Column<Advice>,
+}
+
+impl ReceiptTable {
+    /// Construct a new ReceiptTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            tx_id: meta.fixed_column(),
+            tag: meta.fixed_column(),
+            value: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Assign the `ReceiptTable` from the receipts of a list of block
+    /// `Transaction`s, padded with empty receipts to `max_txs`.
+    pub fn load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        txs: &[Transaction],
+        max_txs: usize,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "receipt table",
+            |mut region| {
+                self.assign(&mut region, txs, max_txs, challenges)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the `ReceiptTable` like [`ReceiptTable::load`] from the offset
+    /// 0 of `region`, returning the cells of the value column, starting with
+    /// the all-zero row.
+    pub fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        txs: &[Transaction],
+        max_txs: usize,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert!(
+            txs.len() <= max_txs,
+            "txs.len() <= max_txs: txs.len()={}, max_txs={}",
+            txs.len(),
+            max_txs
+        );
+
+        let mut rows = vec![[(); 3].map(|_| Value::known(F::zero()))];
+        for tx_id in 1..=max_txs {
+            let receipt = match txs.get(tx_id - 1) {
+                Some(tx) => tx.receipt.clone(),
+                None => Receipt {
+                    id: tx_id,
+                    ..Default::default()
+                },
+            };
+            debug_assert_eq!(receipt.id, tx_id);
+            rows.extend(receipt.table_assignments(*challenges));
+        }
+        let mut value_cells = Vec::with_capacity(rows.len());
+        for (offset, row) in rows.iter().enumerate() {
+            region.assign_fixed(
+                || format!("receipt table q_enable row {}", offset),
+                self.q_enable,
+                offset,
+                || Value::known(F::one()),
+            )?;
+            for (column, value) in [self.tx_id, self.tag].iter().zip(row) {
+                region.assign_fixed(
+                    || format!("receipt table row {}", offset),
+                    *column,
+                    offset,
+                    || *value,
+                )?;
+            }
+            value_cells.push(region.assign_advice(
+                || format!("receipt table row {}", offset),
+                self.value,
+                offset,
+                || row[2],
+            )?);
+        }
+        Ok(value_cells)
+    }
+}
+
+impl<F: Field> LookupTable<F> for ReceiptTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.tx_id.into(),
+            self.tag.into(),
+            self.value.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("tx_id"),
+            String::from("tag"),
+            String::from("value"),
+        ]
+    }
+}
+
+/// Table of the addresses and topics of the logs of the txs, with one row
+/// per item, whose 3 bits are constrained to be set in the logs bloom of the
+/// block of its tx by the PI circuit, which also constrains that the bloom
+/// has no other bit set.
+///
+/// The EVM circuit looks up each address and topic of the persistent logs,
+/// so that the table holds all of them.
+#[derive(Clone, Debug)]
+pub struct LogsBloomTable {
+    /// q_enable
+    pub q_enable: Column<Fixed>,
+    /// Tx ID
+    pub tx_id: Column<Advice>,
+    /// Id of the log in the tx, starting at 1
+    pub log_id: Column<Advice>,
+    /// TxLogFieldTag, Address or Topic
+    pub field_tag: Column<Advice>,
+    /// Index of the topic, 0 for the address
+    pub index: Column<Advice>,
+    /// Address, or RLC of the topic
+    pub value: Column<Advice>,
+}
+
+impl LogsBloomTable {
+    /// Construct a new LogsBloomTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            tx_id: meta.advice_column(),
+            log_id: meta.advice_column(),
+            field_tag: meta.advice_column(),
+            index: meta.advice_column(),
+            value: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Assign the `LogsBloomTable` from the receipts of a list of block
+    /// `Transaction`s, without the bloom of their blocks.
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        txs: &[Transaction],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "logs bloom table",
+            |mut region| {
+                let items = txs
+                    .iter()
+                    .flat_map(|tx| repeat(tx.id).zip(tx.receipt.log_items()));
+                for (offset, (tx_id, item)) in items.enumerate() {
+                    region.assign_fixed(
+                        || format!("logs bloom table q_enable row {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    for (column, value) in [
+                        (self.tx_id, Value::known(F::from(tx_id as u64))),
+                        (self.log_id, Value::known(F::from(item.log_id as u64))),
+                        (self.field_tag, Value::known(F::from(item.field_tag as u64))),
+                        (self.index, Value::known(F::from(item.index as u64))),
+                        (self.value, item.value(challenges.evm_word())),
+                    ] {
+                        region.assign_advice(
+                            || format!("logs bloom table row {}", offset),
+                            column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: Field> LookupTable<F> for LogsBloomTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.tx_id.into(),
+            self.log_id.into(),
+            self.field_tag.into(),
+            self.index.into(),
+            self.value.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("tx_id"),
+            String::from("log_id"),
+            String::from("field_tag"),
+            String::from("index"),
+            String::from("value"),
+        ]
+    }
+}
+
+/// Lookup table of the signatures verified by the sig circuit, with one row
+/// per signature recovered by an ECRecover call. The message hash, the `r`
+/// and `s` of the signature and the recovered address are RLCs with the
+/// keccak challenge of their big-endian bytes, like the call data and return
+/// data they are read from.
+///
+/// The recovery id `v` isn't in the table, as the circuit doesn't constrain
+/// the parity of the point `R` of the signature: both public keys verifying
+/// a signature could be recovered from it.
+#[derive(Clone, Debug)]
+pub struct SigTable {
+    /// q_enable
+    pub q_enable: Column<Fixed>,
+    /// RLC of the message hash, reduced modulo the secp256k1 order
+    pub msg_hash_rlc: Column<Advice>,
+    /// RLC of `r`
+    pub sig_r_rlc: Column<Advice>,
+    /// RLC of `s`
+    pub sig_s_rlc: Column<Advice>,
+    /// RLC of the recovered address
+    pub recovered_addr_rlc: Column<Advice>,
+}
+
+impl SigTable {
+    /// Construct a new SigTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [msg_hash_rlc, sig_r_rlc, sig_s_rlc, recovered_addr_rlc] = [(); 4].map(|_| {
+            let column = meta.advice_column_in(SecondPhase);
+            meta.enable_equality(column);
+            column
+        });
+        Self {
+            q_enable: meta.fixed_column(),
+            msg_hash_rlc,
+            sig_r_rlc,
+            sig_s_rlc,
+            recovered_addr_rlc,
+        }
+    }
+
+    /// Values of the row of a signature in the table.
+    pub fn row<F: Field>(sign_data: &SignData, challenges: &Challenges<Value<F>>) -> [Value<F>; 4] {
+        let pk_be = pk_bytes_swap_endianness(&pk_bytes_le(&sign_data.pk));
+        let mut keccak = Keccak::default();
+        keccak.update(&pk_be);
+        let pk_hash = keccak.digest();
+        // The little-endian bytes of the scalars are the big-endian bytes
+        // reversed, as expected by `rlc::value`.
+        [
+            sign_data.msg_hash.to_bytes(),
+            sign_data.signature.0.to_bytes(),
+            sign_data.signature.1.to_bytes(),
+        ]
+        .map(|le_bytes| {
+            challenges
+                .keccak_input()
+                .map(|r| rlc::value(le_bytes.iter(), r))
+        })
+        .into_iter()
+        .chain(once(
+            challenges
+                .keccak_input()
+                .map(|r| rlc::value(pk_hash[12..].iter().rev(), r)),
+        ))
+        .collect_vec()
+        .try_into()
+        .unwrap()
+    }
+
+    /// Assign the rows of `sign_datas` to the `SigTable` without proving
+    /// their signatures.
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        sign_datas: &[SignData],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sig table (dev load)",
+            |mut region| {
+                let rows = sign_datas
+                    .iter()
+                    .map(|sign_data| Self::row(sign_data, challenges))
+                    .collect_vec();
+                self.assign(&mut region, &rows)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign `rows` from the offset 0 of `region`, returning their cells.
+    pub fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        rows: &[[Value<F>; 4]],
+    ) -> Result<Vec<[AssignedCell<F, F>; 4]>, Error> {
+        let columns = [
+            self.msg_hash_rlc,
+            self.sig_r_rlc,
+            self.sig_s_rlc,
+            self.recovered_addr_rlc,
+        ];
+        let mut cells = Vec::with_capacity(rows.len());
+        for (offset, row) in rows.iter().enumerate() {
+            region.assign_fixed(
+                || format!("sig table q_enable row {}", offset),
+                self.q_enable,
+                offset,
+                || Value::known(F::one()),
+            )?;
+            let row_cells = columns
+                .iter()
+                .zip(row)
+                .map(|(column, value)| {
+                    region.assign_advice(
+                        || format!("sig table row {}", offset),
+                        *column,
+                        offset,
+                        || *value,
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            cells.push(row_cells.try_into().unwrap());
+        }
+        Ok(cells)
+    }
+}
+
+impl<F: Field> LookupTable<F> for SigTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.msg_hash_rlc.into(),
+            self.sig_r_rlc.into(),
+            self.sig_s_rlc.into(),
+            self.recovered_addr_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("msg_hash_rlc"),
+            String::from("sig_r_rlc"),
+            String::from("sig_s_rlc"),
+            String::from("recovered_addr_rlc"),
+        ]
+    }
+}
+
+/// Lookup table of the P256VERIFY calls with a valid signature, with one row
+/// per call. The 5 words of the input are RLCs with the keccak challenge of
+/// their big-endian bytes, like the call data they are read from.
+#[derive(Clone, Debug)]
+pub struct P256VerifyTable {
+    /// q_enable
+    pub q_enable: Column<Fixed>,
+    /// RLC of the message hash
+    pub msg_hash_rlc: Column<Advice>,
+    /// RLC of `r`
+    pub sig_r_rlc: Column<Advice>,
+    /// RLC of `s`
+    pub sig_s_rlc: Column<Advice>,
+    /// RLC of the `x` coordinate of the public key
+    pub pk_x_rlc: Column<Advice>,
+    /// RLC of the `y` coordinate of the public key
+    pub pk_y_rlc: Column<Advice>,
+}
+
+impl P256VerifyTable {
+    /// Construct a new P256VerifyTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [msg_hash_rlc, sig_r_rlc, sig_s_rlc, pk_x_rlc, pk_y_rlc] =
+            [(); 5].map(|_| meta.advice_column_in(SecondPhase));
+        Self {
+            q_enable: meta.fixed_column(),
+            msg_hash_rlc,
+            sig_r_rlc,
+            sig_s_rlc,
+            pk_x_rlc,
+            pk_y_rlc,
+        }
+    }
+
+    /// Assign the rows of `inputs` to the `P256VerifyTable` without proving
+    /// their signatures.
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[P256VerifyInput],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "p256 verify table (dev load)",
+            |mut region| {
+                let columns = <Self as LookupTable<F>>::advice_columns(self);
+                for (offset, input) in inputs.iter().enumerate() {
+                    region.assign_fixed(
+                        || format!("p256 verify table q_enable row {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    for (column, word) in
+                        columns
+                            .iter()
+                            .zip([input.msg_hash, input.r, input.s, input.x, input.y])
+                    {
+                        region.assign_advice(
+                            || format!("p256 verify table row {}", offset),
+                            *column,
+                            offset,
+                            || {
+                                challenges
+                                    .keccak_input()
+                                    .map(|r| rlc::value(word.iter().rev(), r))
+                            },
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: Field> LookupTable<F> for P256VerifyTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.msg_hash_rlc.into(),
+            self.sig_r_rlc.into(),
+            self.sig_s_rlc.into(),
+            self.pk_x_rlc.into(),
+            self.pk_y_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("msg_hash_rlc"),
+            String::from("sig_r_rlc"),
+            String::from("sig_s_rlc"),
+            String::from("pk_x_rlc"),
+            String::from("pk_y_rlc"),
+        ]
+    }
+}
+
 /// Tag to identify the operation type in a RwTable row
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
 pub enum RwTableTag {
@@ -386,6 +1031,8 @@ pub enum RwTableTag {
     TxLog,
     /// Tx Receipt operation
     TxReceipt,
+    /// Transient Storage operation of EIP-1153
+    TransientStorage,
 }
 impl_expr!(RwTableTag);
 
@@ -399,6 +1046,7 @@ impl RwTableTag {
                 | RwTableTag::TxRefund
                 | RwTableTag::Account
                 | RwTableTag::AccountStorage
+                | RwTableTag::TransientStorage
         )
     }
 }
@@ -507,6 +1155,10 @@ pub enum CallContextFieldTag {
     MemorySize,
     /// ReversibleWriteCounter
     ReversibleWriteCounter,
+    /// L1Fee
+    L1Fee,
+    /// RevertDataHash
+    RevertDataHash,
 }
 impl_expr!(CallContextFieldTag);
 
@@ -1202,6 +1854,9 @@ pub enum BlockContextFieldTag {
     /// In a multi-block setup, this variant represents the cumulative number of
     /// txs included up to this block, including the txs in this block.
     CumNumTxs,
+    /// Blob base fee of EIP-7516, derived from the excess blob gas of the
+    /// header.
+    BlobBaseFee,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -1365,15 +2020,10 @@ impl KeccakTable {
             .keccak_input()
             .map(|challenge| rlc::value(input.iter().rev(), challenge));
         let input_len = F::from(input.len() as u64);
-        let mut keccak = Keccak::default();
-        keccak.update(input);
-        let output = keccak.digest();
-        let output_rlc = challenges.evm_word().map(|challenge| {
-            rlc::value(
-                &Word::from_big_endian(output.as_slice()).to_le_bytes(),
-                challenge,
-            )
-        });
+        let output = fast_mock::keccak256(input);
+        let output_rlc = challenges
+            .evm_word()
+            .map(|challenge| word::rlc(&Word::from_big_endian(&output), challenge));
 
         vec![[
             Value::known(F::one()),
@@ -1470,6 +2120,48 @@ impl KeccakTable {
     }
 }
 
+/// Which side of a precompile call is looked up in the copy table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrecompileIo {
+    /// The bytes copied from the caller's memory into the precompile.
+    Input,
+    /// The bytes the precompile returned, copied into the callee's memory.
+    Output,
+}
+
+impl PrecompileIo {
+    /// Returns the input expressions of a lookup into
+    /// [`CopyTable::precompile_rlc_table_exprs`], matching the first row of
+    /// the copy event of `precompile` called as `call_id` by `caller_id`:
+    /// - `Memory(caller_id) => Precompile(call_id)` for the input;
+    /// - `Precompile(call_id) => Memory(call_id)` for the output.
+    pub fn lookup_input_exprs<F: Field>(
+        self,
+        precompile: PrecompileCalls,
+        caller_id: Expression<F>,
+        call_id: Expression<F>,
+        length: Expression<F>,
+        rlc: Expression<F>,
+    ) -> Vec<Expression<F>> {
+        let precompile_tag = CopyDataType::Precompile(precompile).expr();
+        let memory_tag = CopyDataType::Memory.expr();
+        let (src_id, src_tag, dst_tag) = match self {
+            Self::Input => (caller_id, memory_tag, precompile_tag),
+            Self::Output => (call_id.clone(), precompile_tag, memory_tag),
+        };
+        vec![
+            1.expr(), // q_enable
+            1.expr(), // is_first
+            src_id,
+            src_tag,
+            call_id,
+            dst_tag,
+            length,
+            rlc,
+        ]
+    }
+}
+
 /// Copy Table, used to verify copies of byte chunks between Memory, Bytecode,
 /// TxLogs and TxCallData.
 #[derive(Clone, Copy, Debug)]
@@ -1572,7 +2264,7 @@ impl CopyTable {
             // is_first
             let is_first = Value::known(if step_idx == 0 { F::one() } else { F::zero() });
             // is last
-            let is_last = if step_idx == copy_event.bytes.len() * 2 - 1 {
+            let is_last = if step_idx == copy_event.num_rows() - 1 {
                 Value::known(F::one())
             } else {
                 Value::known(F::zero())
@@ -1615,7 +2307,7 @@ impl CopyTable {
             };
 
             // bytes_left
-            let bytes_left = u64::try_from(copy_event.bytes.len() * 2 - step_idx).unwrap() / 2;
+            let bytes_left = u64::try_from(copy_event.num_rows() - step_idx).unwrap() / 2;
             // value
             let value = Value::known(F::from(copy_step.value as u64));
             // value_acc
@@ -1652,11 +2344,19 @@ impl CopyTable {
                         "rlc_acc",
                     ),
                     (
-                        Value::known(F::from(copy_event.rw_counter(step_idx))),
+                        Value::known(F::from(
+                            copy_event
+                                .rw_counter(step_idx)
+                                .expect("copy event types are checked by bus-mapping"),
+                        )),
                         "rw_counter",
                     ),
                     (
-                        Value::known(F::from(copy_event.rw_counter_increase_left(step_idx))),
+                        Value::known(F::from(
+                            copy_event
+                                .rw_counter_increase_left(step_idx)
+                                .expect("copy event types are checked by bus-mapping"),
+                        )),
                         "rwc_inc_left",
                     ),
                 ],
@@ -1672,6 +2372,58 @@ impl CopyTable {
         assignments
     }
 
+    /// Returns the columns of the copy table matched by a lookup of the RLC
+    /// of the bytes a precompile received or returned, on the first row of
+    /// its copy event: `q_enable`, `is_first`, the source and destination ids
+    /// and tags, the length and `rlc_acc`. Unlike a full copy lookup, it
+    /// leaves out the addresses and rw counters, which the precompile
+    /// doesn't know.
+    pub fn precompile_rlc_table_exprs<F: Field>(
+        &self,
+        meta: &mut VirtualCells<F>,
+    ) -> Vec<Expression<F>> {
+        vec![
+            meta.query_fixed(self.q_enable, Rotation::cur()),
+            meta.query_advice(self.is_first, Rotation::cur()),
+            meta.query_advice(self.id, Rotation::cur()),
+            self.tag.value(Rotation::cur())(meta),
+            meta.query_advice(self.id, Rotation::next()),
+            self.tag.value(Rotation::next())(meta),
+            meta.query_advice(self.bytes_left, Rotation::cur()),
+            meta.query_advice(self.rlc_acc, Rotation::cur()),
+        ]
+    }
+
+    /// Returns the (input, table) expression pairs of a lookup from a
+    /// precompile verifying sub-circuit into the copy table. The lookup checks
+    /// that `rlc` is the RLC accumulator of the `length` bytes the call
+    /// `call_id` by `caller_id` to `precompile` received as input or returned
+    /// as output.
+    ///
+    /// The copy circuit constrains `rlc_acc` of `Memory => Precompile` and
+    /// `Precompile => Memory` copy events, so all precompile circuits should
+    /// connect through this lookup instead of wiring their own. Input
+    /// expressions are multiplied by `condition`, rows where it's 0 match the
+    /// all-zero row of the table.
+    #[allow(clippy::too_many_arguments)]
+    pub fn precompile_rlc_lookup<F: Field>(
+        &self,
+        meta: &mut VirtualCells<F>,
+        condition: Expression<F>,
+        io: PrecompileIo,
+        precompile: PrecompileCalls,
+        caller_id: Expression<F>,
+        call_id: Expression<F>,
+        length: Expression<F>,
+        rlc: Expression<F>,
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        io.lookup_input_exprs(precompile, caller_id, call_id, length, rlc)
+            .into_iter()
+            .map(|input| condition.clone() * input)
+            .zip(self.precompile_rlc_table_exprs(meta))
+            .collect()
+    }
+
     /// Assign the `CopyTable` from a `Block`.
     pub fn dev_load<F: Field>(
         &self,
@@ -1816,7 +2568,7 @@ impl ExpTable {
     /// exponentiation table.
     pub fn assignments<F: Field>(exp_event: &ExpEvent) -> Vec<[F; 5]> {
         let mut assignments = Vec::new();
-        let base_limbs = split_u256_limb64(&exp_event.base);
+        let base_limbs = word::limbs64(exp_event.base);
         let identifier = F::from(exp_event.identifier as u64);
         let mut exponent = exp_event.exponent;
         for (step_idx, exp_step) in exp_event.steps.iter().rev().enumerate() {
@@ -1825,32 +2577,24 @@ impl ExpTable {
             } else {
                 F::zero()
             };
-            let (exp_lo, exp_hi) = split_u256(&exp_step.d);
-            let (exponent_lo, exponent_hi) = split_u256(&exponent);
+            let exp = WordLoHi::<F>::from_word(exp_step.d);
+            let exponent_lo_hi = WordLoHi::<F>::from_word(exponent);
 
             // row 1
             assignments.push([
                 identifier,
                 is_last,
                 base_limbs[0].as_u64().into(),
-                exponent_lo
-                    .to_scalar()
-                    .expect("exponent should fit to scalar"),
-                exp_lo
-                    .to_scalar()
-                    .expect("exponentiation lo should fit to scalar"),
+                exponent_lo_hi.lo,
+                exp.lo,
             ]);
             // row 2
             assignments.push([
                 identifier,
                 F::zero(),
                 base_limbs[1].as_u64().into(),
-                exponent_hi
-                    .to_scalar()
-                    .expect("exponent hi should fit to scalar"),
-                exp_hi
-                    .to_scalar()
-                    .expect("exponentiation hi should fit to scalar"),
+                exponent_lo_hi.hi,
+                exp.hi,
             ]);
             // row 3
             assignments.push([
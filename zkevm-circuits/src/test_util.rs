@@ -4,16 +4,25 @@ use crate::{
     copy_circuit::CopyCircuit,
     evm_circuit::EvmCircuit,
     state_circuit::StateCircuit,
+    table::RwTableTag,
     util::{log2_ceil, SubCircuit},
     witness::{Block, Rw},
 };
-use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
-use eth_types::geth_types::GethData;
+use bus_mapping::{
+    circuit_input_builder::{CircuitsParams, PrecompileEvent},
+    mock::BlockData,
+};
+use eth_types::{geth_types::GethData, H256};
+use ethers_core::utils::keccak256;
 
 use halo2_proofs::{
     circuit::Value,
-    dev::{unwrap_value, MockProver},
-    halo2curves::bn256::Fr,
+    dev::{unwrap_value, MockProver, VerifyFailure},
+    halo2curves::{
+        bn256::Fr,
+        group::{prime::PrimeCurveAffine, Curve},
+        secp256k1::Secp256k1Affine,
+    },
 };
 use mock::TestContext;
 
@@ -185,10 +194,9 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
-    /// Triggers the `CircuitTestBuilder` to convert the [`TestContext`] if any,
-    /// into a [`Block`] and apply the default or provided block_modifiers or
-    /// circuit checks to the provers generated for the State and EVM circuits.
-    pub fn run(self) {
+    /// Convert the [`TestContext`] if any, into a [`Block`] and apply the
+    /// block_modifiers to it.
+    fn build_block(&mut self) -> (Block<Fr>, CircuitsParams) {
         let params = if let Some(block) = self.block.as_ref() {
             block.circuits_params
         } else {
@@ -196,10 +204,10 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         };
         log::debug!("params in CircuitTestBuilder: {:?}", params);
 
-        let block: Block<Fr> = if self.block.is_some() {
-            self.block.unwrap()
-        } else if self.test_ctx.is_some() {
-            let block: GethData = self.test_ctx.unwrap().into();
+        let block: Block<Fr> = if let Some(block) = self.block.take() {
+            block
+        } else if let Some(test_ctx) = self.test_ctx.take() {
+            let block: GethData = test_ctx.into();
             let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), params)
                 .new_circuit_input_builder();
             builder
@@ -209,7 +217,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             let mut block =
                 crate::witness::block_convert(&builder.block, &builder.code_db).unwrap();
 
-            for modifier_fn in self.block_modifiers {
+            for modifier_fn in self.block_modifiers.drain(..) {
                 modifier_fn.as_ref()(&mut block);
             }
             block
@@ -217,48 +225,230 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             panic!("No attribute to build a block was passed to the CircuitTestBuilder")
         };
 
-        const NUM_BLINDING_ROWS: usize = 64;
-        // Run evm circuit test
-        {
-            let k = block.get_test_degree();
-            let (active_gate_rows, active_lookup_rows) = EvmCircuit::<Fr>::get_active_rows(&block);
+        (block, params)
+    }
+
+    /// Apply the circuit checks to the provers generated for the EVM, State
+    /// and Copy circuits.
+    fn check_block(&self, block: &Block<Fr>, params: &CircuitsParams) {
+        let (prover, active_gate_rows, active_lookup_rows) = evm_circuit_prover(block);
+        self.evm_checks.as_ref()(prover, &active_gate_rows, &active_lookup_rows);
+
+        let (prover, rows) = state_circuit_prover(block, params.max_rws);
+        self.state_checks.as_ref()(prover, &rows, &rows);
+
+        let (prover, rows) = copy_circuit_prover(block);
+        self.copy_checks.as_ref()(prover, &rows, &rows);
+    }
+
+    /// Triggers the `CircuitTestBuilder` to convert the [`TestContext`] if any,
+    /// into a [`Block`] and apply the default or provided block_modifiers or
+    /// circuit checks to the provers generated for the State and EVM circuits.
+    pub fn run(mut self) {
+        let (block, params) = self.build_block();
+        self.check_block(&block, &params);
+    }
+
+    /// Builds and checks the [`Block`] like [`CircuitTestBuilder::run`], then
+    /// asserts that each of the `mutations` of it is rejected by the EVM,
+    /// State or Copy circuit, with a failure of the constraint or lookup it
+    /// is paired with: the name of a lookup, or a part of the name of a
+    /// constraint. This guards against missing constraints.
+    pub fn run_with_mutations(mut self, mutations: &[(WitnessMutation, &str)]) {
+        let (block, params) = self.build_block();
+        self.check_block(&block, &params);
+
+        for (mutation, name) in mutations {
+            let mut block = block.clone();
+            mutation.apply(&mut block);
 
-            let circuit = EvmCircuit::get_test_cicuit_from_block(block.clone());
-            let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+            let (prover, gate_rows, lookup_rows) = evm_circuit_prover(&block);
+            let evm_result =
+                prover.verify_at_rows_par(gate_rows.into_iter(), lookup_rows.into_iter());
+            let (prover, rows) = state_circuit_prover(&block, params.max_rws);
+            let state_result =
+                prover.verify_at_rows_par(rows.clone().into_iter(), rows.into_iter());
+            let (prover, rows) = copy_circuit_prover(&block);
+            let copy_result = prover.verify_at_rows_par(rows.clone().into_iter(), rows.into_iter());
 
-            self.evm_checks.as_ref()(prover, &active_gate_rows, &active_lookup_rows)
+            let failures = [evm_result, state_result, copy_result]
+                .into_iter()
+                .filter_map(Result::err)
+                .flatten()
+                .collect::<Vec<_>>();
+            assert!(
+                !failures.is_empty(),
+                "mutation {mutation:?} is not rejected by any circuit"
+            );
+            assert!(
+                failures
+                    .iter()
+                    .any(|failure| failure_matches(failure, name)),
+                "mutation {mutation:?} is not rejected by {name}: {failures:#?}"
+            );
         }
+    }
+}
 
-        // Run state circuit test
-        {
-            let rows_needed = StateCircuit::<Fr>::min_num_rows_block(&block).1;
-            let k = log2_ceil(rows_needed + NUM_BLINDING_ROWS);
-            let state_circuit = StateCircuit::<Fr>::new(block.rws.clone(), params.max_rws);
-            let instance = state_circuit.instance();
-            let prover = MockProver::<Fr>::run(k, &state_circuit, instance).unwrap();
-            // Skip verification of Start rows to accelerate testing
-            let non_start_rows_len = state_circuit
-                .rows
-                .iter()
-                .filter(|rw| !matches!(rw, Rw::Start { .. }))
-                .count();
-            let rows = (params.max_rws - non_start_rows_len..params.max_rws).collect();
-
-            self.state_checks.as_ref()(prover, &rows, &rows);
+/// Whether `failure` is a failure of the lookup `name`, or of a constraint
+/// whose name contains `name`.
+fn failure_matches(failure: &VerifyFailure, name: &str) -> bool {
+    match failure {
+        VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+            // fields of halo2_proofs::dev::metadata::Constraint aren't public, so we have
+            // to match off of its format string.
+            format!("{}", constraint).contains(name)
         }
+        VerifyFailure::Lookup {
+            name: lookup_name, ..
+        } => *lookup_name == name,
+        _ => false,
+    }
+}
+
+const NUM_BLINDING_ROWS: usize = 64;
+
+fn evm_circuit_prover(block: &Block<Fr>) -> (MockProver<Fr>, Vec<usize>, Vec<usize>) {
+    let k = block.get_test_degree();
+    let (active_gate_rows, active_lookup_rows) = EvmCircuit::<Fr>::get_active_rows(block);
+
+    let circuit = EvmCircuit::get_test_cicuit_from_block(block.clone());
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+
+    (prover, active_gate_rows, active_lookup_rows)
+}
+
+fn state_circuit_prover(block: &Block<Fr>, max_rws: usize) -> (MockProver<Fr>, Vec<usize>) {
+    let rows_needed = StateCircuit::<Fr>::min_num_rows_block(block).1;
+    let k = log2_ceil(rows_needed + NUM_BLINDING_ROWS);
+    let state_circuit = StateCircuit::<Fr>::new(block.rws.clone(), max_rws);
+    let instance = state_circuit.instance();
+    let prover = MockProver::<Fr>::run(k, &state_circuit, instance).unwrap();
+    // Skip verification of Start rows to accelerate testing
+    let non_start_rows_len = state_circuit
+        .rows
+        .iter()
+        .filter(|rw| !matches!(rw, Rw::Start { .. }))
+        .count();
+    let rows = (max_rws - non_start_rows_len..max_rws).collect();
+
+    (prover, rows)
+}
+
+fn copy_circuit_prover(block: &Block<Fr>) -> (MockProver<Fr>, Vec<usize>) {
+    let (active_rows, max_rows) = CopyCircuit::<Fr>::min_num_rows_block(block);
+    let k1 = block.get_test_degree();
+    let k2 = log2_ceil(max_rows + NUM_BLINDING_ROWS);
+    let k = k1.max(k2);
+    let copy_circuit = CopyCircuit::<Fr>::new_from_block(block);
+    let instance = copy_circuit.instance();
+    let prover = MockProver::<Fr>::run(k, &copy_circuit, instance).unwrap();
+    let rows = (0..active_rows).collect();
 
-        // Run copy circuit test
-        {
-            let (active_rows, max_rows) = CopyCircuit::<Fr>::min_num_rows_block(&block);
-            let k1 = block.get_test_degree();
-            let k2 = log2_ceil(max_rows + NUM_BLINDING_ROWS);
-            let k = k1.max(k2);
-            let copy_circuit = CopyCircuit::<Fr>::new_from_block(&block);
-            let instance = copy_circuit.instance();
-            let prover = MockProver::<Fr>::run(k, &copy_circuit, instance).unwrap();
-            let rows = (0..active_rows).collect();
-
-            self.copy_checks.as_ref()(prover, &rows, &rows);
+    (prover, rows)
+}
+
+/// A targeted mutation of a valid witness [`Block`], for
+/// [`CircuitTestBuilder::run_with_mutations`].
+#[derive(Clone, Debug)]
+pub enum WitnessMutation {
+    /// Change the value of the `index`-th Rw of the `tag` table. Numeric
+    /// values are incremented and booleans are flipped.
+    RwValue {
+        /// Rw table of the Rw
+        tag: RwTableTag,
+        /// Index of the Rw in its table
+        index: usize,
+    },
+    /// Skip a copy step by removing the `byte_index`-th byte of a copy event.
+    SkipCopyStep {
+        /// Index of the copy event in the block
+        event_index: usize,
+        /// Index of the byte in the copy event
+        byte_index: usize,
+    },
+    /// Decrement the gas left of an execution step.
+    GasLeft {
+        /// Index of the tx in the block
+        tx_index: usize,
+        /// Index of the step in the tx
+        step_index: usize,
+    },
+    /// Replace the public key recovered by an ECRecover call with another
+    /// key, which doesn't verify the signature.
+    EcrecoverPubKey {
+        /// Index of the precompile event in the block
+        event_index: usize,
+    },
+    /// Remove a precompile event, as if the call it was recorded for wasn't
+    /// verified.
+    DropPrecompileEvent {
+        /// Index of the precompile event in the block
+        event_index: usize,
+    },
+    /// Replace the revert data hash in the receipt of a tx with the hash of
+    /// other data.
+    RevertDataHash {
+        /// Index of the tx in the block
+        tx_index: usize,
+    },
+}
+
+impl WitnessMutation {
+    /// Apply the mutation to the block.
+    pub fn apply(&self, block: &mut Block<Fr>) {
+        match *self {
+            Self::RwValue { tag, index } => block.update_rws(|rws| {
+                let rw = &mut rws
+                    .0
+                    .get_mut(&tag)
+                    .unwrap_or_else(|| panic!("no {tag:?} rws in the block"))[index];
+                match rw {
+                    Rw::Start { .. } => panic!("Start rws have no value"),
+                    Rw::TxAccessListAccount { is_warm, .. }
+                    | Rw::TxAccessListAccountStorage { is_warm, .. } => *is_warm = !*is_warm,
+                    Rw::TxRefund { value, .. } | Rw::TxReceipt { value, .. } => {
+                        *value = value.wrapping_add(1)
+                    }
+                    Rw::Account { value, .. }
+                    | Rw::AccountStorage { value, .. }
+                    | Rw::CallContext { value, .. }
+                    | Rw::Stack { value, .. }
+                    | Rw::TxLog { value, .. }
+                    | Rw::TransientStorage { value, .. } => {
+                        *value = value.overflowing_add(1.into()).0
+                    }
+                    Rw::Memory { byte, .. } => *byte = byte.wrapping_add(1),
+                }
+            }),
+            Self::SkipCopyStep {
+                event_index,
+                byte_index,
+            } => {
+                block.copy_events[event_index].bytes.remove(byte_index);
+            }
+            Self::GasLeft {
+                tx_index,
+                step_index,
+            } => {
+                let step = &mut block.txs[tx_index].steps[step_index];
+                step.gas_left = step.gas_left.wrapping_sub(1);
+            }
+            Self::EcrecoverPubKey { event_index } => {
+                let PrecompileEvent::Ecrecover(sign_data) =
+                    &mut block.precompile_events[event_index]
+                else {
+                    panic!("precompile event {event_index} isn't an ECRecover");
+                };
+                sign_data.pk = (sign_data.pk + Secp256k1Affine::generator()).to_affine();
+            }
+            Self::DropPrecompileEvent { event_index } => {
+                block.precompile_events.remove(event_index);
+            }
+            Self::RevertDataHash { tx_index } => {
+                let receipt = &mut block.txs[tx_index].receipt;
+                receipt.revert_data_hash = H256(keccak256(receipt.revert_data_hash));
+            }
         }
     }
 }
@@ -0,0 +1,385 @@
+//! Test vectors of the witness of a curated set of txs, exported as JSON so
+//! that other provers and implementations of the spec can check their witness
+//! against the one of this crate.
+//!
+//! A vector holds the rows of the rw table in the order of the state circuit,
+//! the copy events the copy table is built from, and the public inputs of the
+//! block. Words are exported as is rather than as the field elements they are
+//! assigned as, so that the vectors don't depend on the challenges of a proof.
+
+use crate::{
+    pi_circuit::PiCircuit,
+    util::SubCircuit,
+    witness::{block_convert, Block, Rw},
+};
+use bus_mapping::{
+    circuit_input_builder::{CircuitsParams, CopyEvent, NumberOrHash},
+    mock::BlockData,
+    Error,
+};
+use eth_types::{address, bytecode, geth_types::GethData, Address, Bytes, Field, ToWord, Word};
+use halo2_proofs::halo2curves::bn256::Fr;
+use mock::{eth, TestContext};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+/// Version of the format of the [`TestVector`]s.
+pub const TEST_VECTORS_VERSION: u32 = 1;
+
+/// Row of the rw table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RwRowVector {
+    /// rw counter of the operation
+    pub rw_counter: usize,
+    /// Whether the operation is a write
+    pub is_write: bool,
+    /// Tag of the row, e.g. `Stack` or `CallContext`
+    pub tag: String,
+    /// Call id for the call scoped tags, tx id for the tx scoped ones
+    pub id: usize,
+    /// Address of the row, e.g. the stack pointer or the account address
+    pub address: Address,
+    /// Field tag of the row, as assigned in the table
+    pub field_tag: u64,
+    /// Storage key of the `AccountStorage` and `TxAccessListAccountStorage`
+    /// rows
+    pub storage_key: Word,
+    /// Value after the operation
+    pub value: Word,
+    /// Value before the operation, for the tags which have one
+    pub value_prev: Option<Word>,
+    /// Value of the storage slot at the start of the tx, for the
+    /// `AccountStorage` rows
+    pub committed_value: Option<Word>,
+}
+
+impl From<&Rw> for RwRowVector {
+    fn from(rw: &Rw) -> Self {
+        Self {
+            rw_counter: rw.rw_counter(),
+            is_write: rw.is_write(),
+            tag: format!("{:?}", rw.tag()),
+            id: rw.id().unwrap_or_default(),
+            address: rw.address().unwrap_or_default(),
+            field_tag: rw.field_tag().unwrap_or_default(),
+            storage_key: rw.storage_key().unwrap_or_default(),
+            value: rw.value_word(),
+            value_prev: rw.value_prev_word(),
+            committed_value: rw.committed_value_word(),
+        }
+    }
+}
+
+/// Copy event, from which the rows of the copy table are built.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopyEventVector {
+    /// Type of the source, e.g. `Memory` or `Bytecode`
+    pub src_type: String,
+    /// Call id of a memory source, tx id of a calldata source, code hash of a
+    /// bytecode source
+    pub src_id: Word,
+    /// First address of the copy in the source
+    pub src_addr: u64,
+    /// End of the source, past which the copied bytes are zeros
+    pub src_addr_end: u64,
+    /// Type of the destination
+    pub dst_type: String,
+    /// Id of the destination, as for the source
+    pub dst_id: Word,
+    /// First address of the copy in the destination
+    pub dst_addr: u64,
+    /// Log id of a tx log destination
+    pub log_id: Option<u64>,
+    /// rw counter of the first operation of the copy
+    pub rw_counter_start: usize,
+    /// Copied bytes
+    pub bytes: Bytes,
+    /// Whether each copied byte is an opcode rather than push data, for a
+    /// bytecode source
+    pub is_code: Vec<bool>,
+}
+
+fn number_or_hash_word(id: &NumberOrHash) -> Word {
+    match id {
+        NumberOrHash::Number(number) => Word::from(*number),
+        NumberOrHash::Hash(hash) => hash.to_word(),
+    }
+}
+
+impl From<&CopyEvent> for CopyEventVector {
+    fn from(event: &CopyEvent) -> Self {
+        Self {
+            src_type: format!("{:?}", event.src_type),
+            src_id: number_or_hash_word(&event.src_id),
+            src_addr: event.src_addr,
+            src_addr_end: event.src_addr_end,
+            dst_type: format!("{:?}", event.dst_type),
+            dst_id: number_or_hash_word(&event.dst_id),
+            dst_addr: event.dst_addr,
+            log_id: event.log_id,
+            rw_counter_start: event.rw_counter_start.0,
+            bytes: event
+                .bytes
+                .iter()
+                .map(|(byte, _)| *byte)
+                .collect::<Vec<_>>()
+                .into(),
+            is_code: event.bytes.iter().map(|(_, is_code)| *is_code).collect(),
+        }
+    }
+}
+
+/// Expected witness of a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// Version of the format of the vector
+    pub version: u32,
+    /// Name of the vector, which names its file
+    pub name: String,
+    /// Number of txs the public inputs are padded to
+    pub max_txs: usize,
+    /// Rows of the rw table, in the order of the state circuit
+    pub rws: Vec<RwRowVector>,
+    /// Copy events of the block, in order
+    pub copy_events: Vec<CopyEventVector>,
+    /// Bytes hashed into the public inputs
+    pub raw_public_inputs: Bytes,
+    /// Public inputs of the block: the high and low 128 bits of the keccak
+    /// hash of `raw_public_inputs`
+    pub public_inputs: Vec<Word>,
+}
+
+impl TestVector {
+    /// Test vector of a witness block.
+    pub fn new<F: Field>(name: &str, block: &Block<F>) -> Self {
+        let max_txs = block.circuits_params.max_txs;
+        let pi_circuit = PiCircuit::new_from_block(block);
+        Self {
+            version: TEST_VECTORS_VERSION,
+            name: name.to_string(),
+            max_txs,
+            rws: block.sorted_rws().iter().map(RwRowVector::from).collect(),
+            copy_events: block
+                .copy_events
+                .iter()
+                .map(CopyEventVector::from)
+                .collect(),
+            raw_public_inputs: pi_circuit
+                .public_data
+                .raw_public_input_bytes(max_txs)
+                .into(),
+            public_inputs: pi_circuit
+                .instance()
+                .concat()
+                .iter()
+                .map(|value| Word::from_little_endian(&value.to_repr()))
+                .collect(),
+        }
+    }
+
+    /// Test vector of the block of a [`TestContext`].
+    pub fn from_test_context<const NACC: usize, const NTX: usize>(
+        name: &str,
+        ctx: TestContext<NACC, NTX>,
+    ) -> Result<Self, Error> {
+        Ok(Self::new(name, &test_context_block(ctx)?))
+    }
+}
+
+/// Witness block of a [`TestContext`], with room for 4096 rw operations.
+pub fn test_context_block<const NACC: usize, const NTX: usize>(
+    ctx: TestContext<NACC, NTX>,
+) -> Result<Block<Fr>, Error> {
+    let block: GethData = ctx.into();
+    let mut builder = BlockData::new_from_geth_data_with_params(
+        block.clone(),
+        CircuitsParams {
+            max_rws: 4096,
+            ..Default::default()
+        },
+    )
+    .new_circuit_input_builder();
+    builder.handle_block(&block.eth_block, &block.geth_traces)?;
+    block_convert(&builder.block, &builder.code_db)
+}
+
+/// Witness blocks of the curated txs, by name: a transfer, storage writes and
+/// a log, memory copies, a reverted call with a create, and a call to a
+/// precompile whose output is copied back.
+pub fn curated_blocks() -> Result<Vec<(&'static str, Block<Fr>)>, Error> {
+    let transfer = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP }).unwrap();
+
+    let storage_and_log = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+        .op_sstore(0x00, 0x2a)
+        .op_sload(0x00)
+        PUSH1(0x00)
+        MSTORE
+        .op_log1(0x00, 0x20, 0x1234)
+        STOP
+    })
+    .unwrap();
+
+    let memory_copies = TestContext::<2, 1>::new(
+        None,
+        mock::test_ctx::helpers::account_0_code_account_1_no_code(bytecode! {
+            .op_codecopy(0x00, 0x00, 0x10)
+            .op_calldatacopy(0x20, 0x00, 0x08)
+            .op_sha3(0x00, 0x28)
+            POP
+            .op_return(0x00, 0x28)
+        }),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .input(vec![1, 2, 3, 4, 5, 6, 7, 8].into());
+        },
+        |block, _txs| block.number(0xcafeu64),
+    )
+    .unwrap();
+
+    // The callee writes to storage and logs before reverting, and the init
+    // code returns a single zero byte as the created contract.
+    let callee_address = address!("0x00000000000000000000000000000000000000bb");
+    let reverted_call_and_create = TestContext::<3, 1>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(address!("0x000000000000000000000000000000000000cafe"))
+                .balance(eth(10));
+            accs[1]
+                .address(address!("0x00000000000000000000000000000000000000aa"))
+                .code(bytecode! {
+                    .op_call(0x10000, callee_address, 0x00, 0x00, 0x00, 0x00, 0x00)
+                    POP
+                    .op_mstore(0x00, 0x60016000f3u64)
+                    .op_create(0x00, 0x1b, 0x05)
+                    STOP
+                });
+            accs[2].address(callee_address).code(bytecode! {
+                .op_sstore(0x00, 0x01)
+                .op_log0(0x00, 0x00)
+                .op_revert(0x00, 0x00)
+            });
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[0].address)
+                .to(accs[1].address)
+                .gas(Word::from(1_000_000));
+        },
+        |block, _txs| block.number(0xcafeu64),
+    )
+    .unwrap();
+
+    // Calls the identity precompile on 8 bytes and copies its output back.
+    let identity = Address::from_low_u64_be(0x04);
+    let precompile_and_returndatacopy = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+        .op_mstore(0x00, 0x0102030405060708u64)
+        .op_staticcall(0x10000, identity, 0x18, 0x08, 0x00, 0x00)
+        POP
+        .op_returndatacopy(0x20, 0x00, 0x08)
+        STOP
+    })
+    .unwrap();
+
+    Ok(vec![
+        ("transfer", test_context_block(transfer)?),
+        ("storage_and_log", test_context_block(storage_and_log)?),
+        ("memory_copies", test_context_block(memory_copies)?),
+        (
+            "reverted_call_and_create",
+            test_context_block(reverted_call_and_create)?,
+        ),
+        (
+            "precompile_and_returndatacopy",
+            test_context_block(precompile_and_returndatacopy)?,
+        ),
+    ])
+}
+
+/// Test vectors of the [`curated_blocks`].
+pub fn curated_test_vectors() -> Result<Vec<TestVector>, Error> {
+    Ok(curated_blocks()?
+        .iter()
+        .map(|(name, block)| TestVector::new(name, block))
+        .collect())
+}
+
+/// Write each of the `vectors` as `<name>.json` in `dir`, which is created if
+/// missing.
+pub fn export_test_vectors(vectors: &[TestVector], dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for vector in vectors {
+        let file = fs::File::create(dir.join(format!("{}.json", vector.name)))?;
+        serde_json::to_writer_pretty(file, vector)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    // Exports the curated test vectors to `TEST_VECTORS_DIR`, `test-vectors` by
+    // default, see the `test_vectors` target of the Makefile.
+    #[ignore]
+    #[test]
+    fn export_curated_test_vectors() {
+        let dir = env::var("TEST_VECTORS_DIR").unwrap_or_else(|_| "test-vectors".to_string());
+        export_test_vectors(&curated_test_vectors().unwrap(), Path::new(&dir)).unwrap();
+    }
+
+    #[test]
+    fn test_vectors_round_trip_through_json() {
+        for vector in curated_test_vectors().unwrap() {
+            let json = serde_json::to_string(&vector).unwrap();
+            assert_eq!(serde_json::from_str::<TestVector>(&json).unwrap(), vector);
+
+            assert_eq!(vector.public_inputs.len(), 2);
+            // Each operation has its own rw counter.
+            let mut rw_counters: Vec<_> = vector
+                .rws
+                .iter()
+                .filter(|rw| rw.tag != "Start")
+                .map(|rw| rw.rw_counter)
+                .collect();
+            let num_rws = rw_counters.len();
+            rw_counters.sort_unstable();
+            rw_counters.dedup();
+            assert_eq!(rw_counters.len(), num_rws, "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn test_vectors_of_memory_copies_and_precompile() {
+        let vectors = curated_test_vectors().unwrap();
+        let vector = |name: &str| vectors.iter().find(|vector| vector.name == name).unwrap();
+
+        // CODECOPY, CALLDATACOPY and SHA3
+        let copy_types: Vec<_> = vector("memory_copies")
+            .copy_events
+            .iter()
+            .map(|event| (event.src_type.as_str(), event.dst_type.as_str()))
+            .collect();
+        for copy_type in [
+            ("Bytecode", "Memory"),
+            ("TxCalldata", "Memory"),
+            ("Memory", "RlcAcc"),
+        ] {
+            assert!(copy_types.contains(&copy_type), "{:?}", copy_types);
+        }
+        // The identity precompile returns its input, copied by RETURNDATACOPY.
+        let returndatacopy = vector("precompile_and_returndatacopy")
+            .copy_events
+            .last()
+            .unwrap()
+            .clone();
+        assert_eq!(returndatacopy.dst_addr, 0x20);
+        assert_eq!(
+            returndatacopy.bytes,
+            Bytes::from(vec![1, 2, 3, 4, 5, 6, 7, 8])
+        );
+    }
+}
@@ -15,17 +15,27 @@ mod test;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 pub use dev::TxCircuit as TestTxCircuit;
 
+#[cfg(feature = "prague")]
+use crate::table::AuthorizationField;
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    super_circuit::{
+        registry::{SharedTable, SharedTables, SuperSubCircuit},
+        SuperCircuitConfigArgs,
+    },
     table::{
         BlockTable, KeccakTable, LookupTable, RlpFsmRlpTable as RlpTable, TxFieldTag, TxTable,
     },
-    util::{keccak, random_linear_combine_word as rlc, SubCircuit, SubCircuitConfig},
+    util::{keccak, word, SubCircuit, SubCircuitConfig},
     witness,
     witness::{rlp_fsm::Tag, RlpTag, Transaction},
 };
-use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
-use eth_types::{sign_types::SignData, Address, Field, ToAddress, ToLittleEndian, ToScalar};
+use bus_mapping::{
+    circuit_input_builder::keccak_inputs_sign_verify, l2_predeployed::system_sender,
+};
+#[cfg(feature = "prague")]
+use eth_types::eip7702::MAX_AUTHORIZATIONS_PER_TX;
+use eth_types::{sign_types::SignData, Address, Field, ToAddress, ToScalar};
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
     is_equal::{IsEqualChip, IsEqualConfig, IsEqualInstruction},
@@ -44,11 +54,13 @@ use std::{
     iter,
     marker::PhantomData,
 };
+#[cfg(feature = "prague")]
+use strum::IntoEnumIterator;
 
 use crate::{
     table::TxFieldTag::{
         BlockNumber, CallData, CallDataGasCost, CallDataLength, CallDataRLC, CalleeAddress,
-        CallerAddress, Gas, GasPrice, IsCreate, Nonce, SigR, SigS, SigV, TxDataGasCost,
+        CallerAddress, Gas, GasPrice, IsCreate, Nonce, RlpByte, SigR, SigS, SigV, TxDataGasCost,
         TxHashLength, TxHashRLC, TxSignHash, TxSignLength, TxSignRLC,
     },
     util::is_zero::{IsZeroChip, IsZeroConfig},
@@ -60,7 +72,10 @@ use halo2_proofs::plonk::SecondPhase;
 use halo2_proofs::plonk::{Fixed, TableColumn};
 
 use crate::{
-    table::{BlockContextFieldTag::CumNumTxs, TxFieldTag::ChainID},
+    table::{
+        BlockContextFieldTag::{CumNumTxs, Timestamp},
+        TxFieldTag::ChainID,
+    },
     util::rlc_be_bytes,
     witness::{
         Format::{L1MsgHash, TxHashEip155, TxHashPreEip155, TxSignEip155, TxSignPreEip155},
@@ -72,17 +87,102 @@ use eth_types::geth_types::{
     TxType,
     TxType::{Eip155, L1Msg, PreEip155},
 };
-use gadgets::comparator::{ComparatorChip, ComparatorConfig, ComparatorInstruction};
+use gadgets::{
+    comparator::{ComparatorChip, ComparatorConfig, ComparatorInstruction},
+    less_than::{LtChip, LtConfig, LtInstruction},
+};
 
 /// Number of rows of one tx occupies in the fixed part of tx table
-pub const TX_LEN: usize = 22;
+#[cfg(not(feature = "prague"))]
+pub const TX_LEN: usize = 26;
+/// Number of rows of one tx occupies in the fixed part of tx table, including
+/// the AuthorizationListLength row of EIP-7702 and the rows of the fields of
+/// up to [`MAX_AUTHORIZATIONS_PER_TX`] authorizations
+#[cfg(feature = "prague")]
+pub const TX_LEN: usize = 27 + 3 * MAX_AUTHORIZATIONS_PER_TX;
+/// Offset of the BlockNumber row, the last of the rows of a tx. The rows from
+/// CallerAddress to AuthorizationListLength are at the offset of their tag
+/// from the Nonce tag.
+const BLOCK_NUMBER_OFFSET: usize = TX_LEN - 1;
 /// Offset of TxHash tag in the tx table
 pub const TX_HASH_OFFSET: usize = 21;
+/// Number of rows between the GasPrice and the IsSystem rows of a tx
+const IS_SYSTEM_GAS_PRICE_DISTANCE: i32 = 20;
+/// Number of rows between the CallerAddress and the IsSystem rows of a tx
+const IS_SYSTEM_CALLER_DISTANCE: i32 = 19;
+/// Upper bound of the length of the signed RLP encoding of a tx of `tx_type`
+/// without its call data, access list and authorization list: the type byte,
+/// the headers of the lists and the call data, and the largest encoding of
+/// each of the other fields.
+pub const fn tx_rlp_len_without_data(tx_type: TxType) -> usize {
+    match tx_type {
+        // list, nonce, gas price, gas, to, value, call data, v, r and s
+        TxType::Eip155 | TxType::PreEip155 => 9 + 9 + 33 + 9 + 21 + 33 + 9 + 9 + 33 + 33,
+        // type, list, chain id, nonce, gas price, gas, to, value, call data,
+        // access list, y parity, r and s
+        TxType::Eip2930 => 1 + 9 + 9 + 9 + 33 + 9 + 21 + 33 + 9 + 9 + 1 + 33 + 33,
+        // the EIP-2930 fields with the gas price split in the max priority fee
+        // and the max fee
+        TxType::Eip1559 => tx_rlp_len_without_data(TxType::Eip2930) + 33,
+        // type, list, queue index, gas, to, value, call data and sender
+        TxType::L1Msg => 1 + 9 + 9 + 9 + 21 + 33 + 9 + 21,
+        // the EIP-1559 fields and the authorization list
+        #[cfg(feature = "prague")]
+        TxType::Eip7702 => tx_rlp_len_without_data(TxType::Eip1559) + 9,
+    }
+}
+
+/// Upper bound of the length of the signed RLP encoding of a tx without its
+/// call data, access list and authorization list, for any tx type.
+#[cfg(not(feature = "prague"))]
+pub const MAX_TX_RLP_LEN_WITHOUT_DATA: usize = tx_rlp_len_without_data(TxType::Eip1559);
+/// Upper bound of the length of the signed RLP encoding of a tx without its
+/// call data, access list and authorization list, for any tx type.
+#[cfg(feature = "prague")]
+pub const MAX_TX_RLP_LEN_WITHOUT_DATA: usize = tx_rlp_len_without_data(TxType::Eip7702);
+
+/// Upper bound of the length of the RLP encoding of an access list item: the
+/// headers of the item and its storage keys, and the address.
+const ACCESS_LIST_ITEM_RLP_LEN: usize = 9 + 21 + 9;
+/// Length of the RLP encoding of a storage key of an access list item
+const STORAGE_KEY_RLP_LEN: usize = 33;
+/// Upper bound of the length of the RLP encoding of an EIP-7702
+/// authorization: the header, chain id, address, nonce, y parity, r and s.
+#[cfg(feature = "prague")]
+const AUTHORIZATION_RLP_LEN: usize = 9 + 33 + 21 + 9 + 2 + 33 + 33;
+
+/// Upper bound of the number of bytes of the signed RLP encoding of the tx
+/// taken by its call data, access list and authorization list, which are
+/// counted against the `max_calldata` budget of the rlp bytes of the tx table.
+pub fn tx_rlp_data_len(tx: &Transaction) -> usize {
+    let access_list_len: usize = tx
+        .access_list
+        .0
+        .iter()
+        .map(|item| ACCESS_LIST_ITEM_RLP_LEN + item.storage_keys.len() * STORAGE_KEY_RLP_LEN)
+        .sum();
+    #[cfg(feature = "prague")]
+    let access_list_len = access_list_len + tx.authorization_list.len() * AUTHORIZATION_RLP_LEN;
+    tx.call_data.len() + access_list_len
+}
+
+/// Upper bound of the length of the signed RLP encoding of the tx.
+pub fn tx_rlp_len_bound(tx: &Transaction) -> usize {
+    tx_rlp_len_without_data(tx.tx_type) + tx_rlp_data_len(tx)
+}
+
+/// Number of rows of the section of the tx table holding the bytes of the
+/// signed RLP encoding of the txs. The txs fit in it as long as the sum of
+/// their [`tx_rlp_data_len`] is at most `max_calldata`.
+pub fn max_rlp_bytes(max_txs: usize, max_calldata: usize) -> usize {
+    max_txs * MAX_TX_RLP_LEN_WITHOUT_DATA + max_calldata
+}
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum LookupCondition {
     // lookup into tx table
     TxCalldata,
+    TxRlpBytes,
     // lookup into rlp table
     L1MsgHash,
     RlpSignTag,
@@ -120,6 +220,7 @@ pub struct TxCircuitConfig<F: Field> {
     /// Columns used to reduce degree
     is_tag_block_num: Column<Advice>,
     is_calldata: Column<Advice>,
+    is_rlp_byte: Column<Advice>,
     is_caller_address: Column<Advice>,
     is_l1_msg: Column<Advice>,
     is_chain_id: Column<Advice>,
@@ -132,6 +233,9 @@ pub struct TxCircuitConfig<F: Field> {
     /// An accumulator value used to correctly calculate the calldata gas cost
     /// for a tx.
     calldata_gas_cost_acc: Column<Advice>,
+    /// RLC of the bytes of the signed RLP encoding of a tx up to the row,
+    /// equal to TxHashRLC on its last byte.
+    rlp_bytes_rlc: Column<Advice>,
 
     /// Columns for ensuring that BlockNum is correct
     is_padding_tx: Column<Advice>,
@@ -140,6 +244,14 @@ pub struct TxCircuitConfig<F: Field> {
     /// Cumulative number of txs up to a block
     cum_num_txs: Column<Advice>,
 
+    /// Columns for deriving IsPrague from the timestamp of the block
+    block_timestamp: Column<Advice>,
+    /// Timestamp from which the blocks are on Prague, 2^64 if the chain isn't
+    /// on Prague
+    prague_time: Column<Fixed>,
+    /// Whether the timestamp of the block is below the Prague time
+    timestamp_lt_prague_time: LtConfig<F, 8>,
+
     /// Address recovered by SignVerifyChip
     sv_address: Column<Advice>,
     sign_verify: SignVerifyConfig<F>,
@@ -177,7 +289,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             block_table,
             keccak_table,
             rlp_table,
-            challenges: _,
+            challenges,
         }: Self::ConfigArgs,
     ) -> Self {
         let q_enable = tx_table.q_enable;
@@ -193,9 +305,15 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let cum_num_txs = meta.advice_column();
         let is_padding_tx = meta.advice_column();
 
+        // columns for constraining IsPrague is valid
+        let block_timestamp = meta.advice_column();
+        let prague_time = meta.fixed_column();
+
         // columns for accumulating length and gas_cost of call_data
         let is_final = meta.advice_column();
         let calldata_gas_cost_acc = meta.advice_column();
+        // column for accumulating the RLC of the signed RLP bytes
+        let rlp_bytes_rlc = meta.advice_column_in(SecondPhase);
 
         // fixed column for showing (tx_id' - tx_id) < 2^16
         let u16_table = meta.lookup_table_column();
@@ -203,11 +321,13 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         // booleans to reduce degree
         let is_l1_msg = meta.advice_column();
         let is_calldata = meta.advice_column();
+        let is_rlp_byte = meta.advice_column();
         let is_caller_address = meta.advice_column();
         let is_chain_id = meta.advice_column();
         let is_tag_block_num = meta.advice_column();
         let lookup_conditions = [
             LookupCondition::TxCalldata,
+            LookupCondition::TxRlpBytes,
             LookupCondition::L1MsgHash,
             LookupCondition::RlpSignTag,
             LookupCondition::RlpHashTag,
@@ -267,7 +387,16 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         is_tx_tag!(is_hash_rlc, TxHashRLC);
         is_tx_tag!(is_sign_hash, TxSignHash);
         is_tx_tag!(is_hash, TxHash);
+        is_tx_tag!(is_tx_type_tag, TxType);
+        is_tx_tag!(is_system_tag, IsSystem);
+        is_tx_tag!(is_invalid_tag, IsInvalid);
+        is_tx_tag!(is_prague_tag, IsPrague);
+        #[cfg(feature = "prague")]
+        is_tx_tag!(is_auth_list_len_tag, AuthorizationListLength);
+        #[cfg(feature = "prague")]
+        is_tx_tag!(is_authorization_tag, Authorization);
         is_tx_tag!(is_block_num, BlockNumber);
+        is_tx_tag!(is_rlp_byte_tag, RlpByte);
 
         // testing if value is zero for tags
         let value_is_zero = IsZeroChip::configure(
@@ -320,7 +449,10 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enable, Rotation::cur()),
-                not::expr(meta.query_advice(is_calldata, Rotation::next())),
+                not::expr(
+                    meta.query_advice(is_calldata, Rotation::next())
+                        + meta.query_advice(is_rlp_byte, Rotation::next()),
+                ),
             ]))
         });
 
@@ -354,6 +486,15 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 (is_tx_gas_cost(meta), Null),
                 (is_block_num(meta), Null),
                 (is_chain_id_expr(meta), Null),
+                (is_tx_type_tag(meta), Null),
+                (is_system_tag(meta), Null),
+                (is_invalid_tag(meta), Null),
+                (is_prague_tag(meta), Null),
+                #[cfg(feature = "prague")]
+                (is_auth_list_len_tag(meta), Null),
+                #[cfg(feature = "prague")]
+                (is_authorization_tag(meta), Null),
+                (is_rlp_byte_tag(meta), Null),
             ];
 
             cb.require_boolean(
@@ -446,6 +587,18 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("is_rlp_byte", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "is_rlp_byte",
+                is_rlp_byte_tag(meta),
+                meta.query_advice(is_rlp_byte, Rotation::cur()),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("is_caller_address", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -482,6 +635,117 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("tx type in tx table", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.condition(is_tx_type_tag(meta), |cb| {
+                cb.require_equal(
+                    "value == tx_type",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                    meta.query_advice(tx_type, Rotation::cur()),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("is_system tx has zero gas price", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_system = meta.query_advice(tx_table.value, Rotation::cur());
+            cb.condition(is_system_tag(meta), |cb| {
+                cb.require_boolean("is_system is boolean", is_system.expr());
+            });
+            // we rely on the assumption that GasPrice and CallerAddress are
+            // assigned IS_SYSTEM_GAS_PRICE_DISTANCE and
+            // IS_SYSTEM_CALLER_DISTANCE rows before IsSystem
+            cb.condition(and::expr([is_system_tag(meta), is_system.expr()]), |cb| {
+                cb.require_zero(
+                    "GasPrice.value == 0",
+                    meta.query_advice(tx_table.value, Rotation(-IS_SYSTEM_GAS_PRICE_DISTANCE)),
+                );
+                cb.require_equal(
+                    "CallerAddress.value == system sender",
+                    meta.query_advice(tx_table.value, Rotation(-IS_SYSTEM_CALLER_DISTANCE)),
+                    Expression::Constant(
+                        system_sender::ADDRESS
+                            .to_scalar()
+                            .expect("system sender address -> Scalar conversion failure"),
+                    ),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("is_invalid is boolean", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.condition(is_invalid_tag(meta), |cb| {
+                cb.require_boolean(
+                    "is_invalid is boolean",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("is_prague is boolean", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.condition(is_prague_tag(meta), |cb| {
+                cb.require_boolean(
+                    "is_prague is boolean",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        #[cfg(feature = "prague")]
+        meta.create_gate("authorization list of set-code tx", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.condition(is_auth_list_len_tag(meta), |cb| {
+                let len = meta.query_advice(tx_table.value, Rotation::cur());
+                cb.require_zero(
+                    "only set-code txs have authorizations",
+                    len.expr()
+                        * (meta.query_advice(tx_type, Rotation::cur())
+                            - usize::from(TxType::Eip7702).expr()),
+                );
+                cb.require_in_set(
+                    "authorization list length <= MAX_AUTHORIZATIONS_PER_TX",
+                    len.expr(),
+                    (0..=MAX_AUTHORIZATIONS_PER_TX).map(|n| n.expr()).collect(),
+                );
+
+                // The fields of the authorizations follow, those past the
+                // length of the list being zero.
+                for i in 0..MAX_AUTHORIZATIONS_PER_TX {
+                    let is_padding = ((i + 1)..=MAX_AUTHORIZATIONS_PER_TX)
+                        .fold(1.expr(), |acc, n| acc * (len.expr() - n.expr()));
+                    for field in AuthorizationField::iter() {
+                        let index = field.index(i);
+                        let rotation = Rotation(1 + index as i32);
+                        cb.require_equal(
+                            "index of the authorization field",
+                            meta.query_advice(tx_table.index, rotation),
+                            index.expr(),
+                        );
+                        cb.require_zero(
+                            "field of a padding authorization is zero",
+                            meta.query_advice(tx_table.value, rotation) * is_padding.expr(),
+                        );
+                    }
+                }
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("is_l1_msg", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -512,6 +776,21 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        meta.create_gate("rlp bytes lookup into tx table condition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "condition",
+                is_hash_length(meta),
+                meta.query_advice(
+                    lookup_conditions[&LookupCondition::TxRlpBytes],
+                    Rotation::cur(),
+                ),
+            );
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("sign tag lookup into RLP table condition", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -630,6 +909,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             &lookup_conditions,
             is_final,
             calldata_gas_cost_acc,
+            rlp_bytes_rlc,
             tx_table.clone(),
             keccak_table.clone(),
             rlp_table,
@@ -643,7 +923,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             let mut cb = BaseConstraintBuilder::default();
 
             // the offset between CallerAddress and BlockNumber
-            let offset = usize::from(BlockNumber) - usize::from(CallerAddress);
+            let offset = BLOCK_NUMBER_OFFSET - (usize::from(CallerAddress) - usize::from(Nonce));
             // if tag == CallerAddress
             cb.condition(is_tag_caller_addr.expr(), |cb| {
                 cb.require_equal(
@@ -697,6 +977,74 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 .collect::<Vec<_>>()
         });
 
+        ///////////////////////////////////////////////////////////////////////
+        ///////////////  constraints on IsPrague  /////////////////////////////
+        ///////////////////////////////////////////////////////////////////////
+        meta.lookup_any("block timestamp in block table", |meta| {
+            let is_tag_block_num = meta.query_advice(is_tag_block_num, Rotation::cur());
+            let block_num = meta.query_advice(tx_table.value, Rotation::cur());
+            let block_timestamp = meta.query_advice(block_timestamp, Rotation::cur());
+
+            let input_expr = vec![Timestamp.expr(), block_num, block_timestamp];
+            let table_expr = block_table.table_exprs(meta);
+            let condition = and::expr([
+                is_tag_block_num,
+                not::expr(meta.query_advice(is_padding_tx, Rotation::cur())),
+                meta.query_fixed(q_enable, Rotation::cur()),
+            ]);
+
+            input_expr
+                .into_iter()
+                .zip(table_expr.into_iter())
+                .map(|(input, table)| (input * condition.clone(), table))
+                .collect::<Vec<_>>()
+        });
+
+        // block_timestamp < prague_time
+        let timestamp_lt_prague_time = LtChip::configure(
+            meta,
+            |meta| {
+                meta.query_fixed(q_enable, Rotation::cur())
+                    * meta.query_advice(is_tag_block_num, Rotation::cur())
+            },
+            |meta| meta.query_advice(block_timestamp, Rotation::cur()),
+            |meta| meta.query_fixed(prague_time, Rotation::cur()),
+        );
+
+        // the difference of the lt chip is in u64, as 4 limbs of 2 bytes
+        for limb in timestamp_lt_prague_time.diff.chunks(2) {
+            meta.lookup("timestamp_lt_prague_time diff limb in u16", |meta| {
+                let q_enable = and::expr([
+                    meta.query_fixed(q_enable, Rotation::cur()),
+                    meta.query_advice(is_tag_block_num, Rotation::cur()),
+                ]);
+                let limb = meta.query_advice(limb[0], Rotation::cur())
+                    + meta.query_advice(limb[1], Rotation::cur()) * 256.expr();
+
+                vec![(q_enable * limb, u16_table)]
+            });
+        }
+
+        meta.create_gate("is_prague = block_timestamp >= prague_time", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            // the offset between IsPrague and BlockNumber
+            let offset =
+                BLOCK_NUMBER_OFFSET - (usize::from(TxFieldTag::IsPrague) - usize::from(Nonce));
+            cb.condition(is_block_num(meta), |cb| {
+                cb.require_equal(
+                    "is_prague = not(block_timestamp < prague_time)",
+                    meta.query_advice(tx_table.value, Rotation(-(offset as i32))),
+                    not::expr(timestamp_lt_prague_time.is_lt(meta, None)),
+                );
+            });
+
+            cb.gate(and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                not::expr(meta.query_advice(is_padding_tx, Rotation::cur())),
+            ]))
+        });
+
         ////////////////////////////////////////////////////////////////////////
         ///////////  CallData length and gas_cost calculation  /////////////////
         ////////////////////////////////////////////////////////////////////////
@@ -707,15 +1055,21 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             |meta| meta.query_advice(tx_table.tx_id, Rotation::next()),
         );
 
+        // tx ids increase over the call data and over the rlp bytes, so that the bytes of a
+        // tx are in a single run of rows
         meta.lookup("tx_id_diff must in u16", |meta| {
             let q_enable = meta.query_fixed(q_enable, Rotation::next());
-            let is_calldata = meta.query_advice(is_calldata, Rotation::cur());
+            let is_calldata_or_rlp_byte = meta.query_advice(is_calldata, Rotation::cur())
+                + meta.query_advice(is_rlp_byte, Rotation::cur());
             let tx_id = meta.query_advice(tx_table.tx_id, Rotation::cur());
             let tx_id_next = meta.query_advice(tx_table.tx_id, Rotation::next());
             let tx_id_next_is_zero = tx_id_is_zero.expr(Rotation::next())(meta);
 
-            let lookup_condition =
-                and::expr([q_enable, is_calldata, not::expr(tx_id_next_is_zero)]);
+            let lookup_condition = and::expr([
+                q_enable,
+                is_calldata_or_rlp_byte,
+                not::expr(tx_id_next_is_zero),
+            ]);
 
             vec![(lookup_condition * (tx_id_next - tx_id), u16_table)]
         });
@@ -764,6 +1118,123 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             ]))
         });
 
+        ////////////////////////////////////////////////////////////////////////
+        ///////////   Bytes of the signed RLP encoding     /////////////////////
+        ////////////////////////////////////////////////////////////////////////
+        // The rows of a run of rlp bytes start at index 0, the run following a final row
+        // or the rows before the rlp bytes.
+        meta.create_gate("tx rlp bytes start", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_zero(
+                "index::next == 0",
+                meta.query_advice(tx_table.index, Rotation::next()),
+            );
+            cb.require_equal(
+                "rlp_bytes_rlc::next == value::next",
+                meta.query_advice(rlp_bytes_rlc, Rotation::next()),
+                meta.query_advice(tx_table.value, Rotation::next()),
+            );
+
+            let is_rlp_byte_cur = meta.query_advice(is_rlp_byte, Rotation::cur());
+            cb.gate(and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(is_rlp_byte, Rotation::next()),
+                not::expr(and::expr([
+                    is_rlp_byte_cur,
+                    not::expr(meta.query_advice(is_final, Rotation::cur())),
+                ])),
+            ]))
+        });
+
+        meta.create_gate("tx rlp bytes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_final_cur = meta.query_advice(is_final, Rotation::cur());
+            let tx_id_is_zero_cur = tx_id_is_zero.expr(Rotation::cur())(meta);
+            cb.require_boolean("is_final is boolean", is_final_cur.clone());
+
+            cb.condition(
+                and::expr([
+                    not::expr(tx_id_is_zero_cur.expr()),
+                    not::expr(is_final_cur.expr()),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "index::next == index::cur + 1",
+                        meta.query_advice(tx_table.index, Rotation::next()),
+                        meta.query_advice(tx_table.index, Rotation::cur()) + 1.expr(),
+                    );
+                    cb.require_equal(
+                        "tx_id::next == tx_id::cur",
+                        tx_id_unchanged.is_equal_expression.clone(),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "rlp_bytes_rlc::next == rlp_bytes_rlc::cur * r + value::next",
+                        meta.query_advice(rlp_bytes_rlc, Rotation::next()),
+                        meta.query_advice(rlp_bytes_rlc, Rotation::cur())
+                            * challenges.keccak_input()
+                            + meta.query_advice(tx_table.value, Rotation::next()),
+                    );
+                },
+            );
+
+            // on the final rlp byte, tx_id must change.
+            cb.condition(
+                and::expr([not::expr(tx_id_is_zero_cur.expr()), is_final_cur]),
+                |cb| {
+                    cb.require_zero(
+                        "tx_id changes at is_final == 1",
+                        tx_id_unchanged.is_equal_expression.clone(),
+                    );
+                },
+            );
+
+            // the padding rows with tx_id == 0 are after all the rlp bytes of the txs.
+            cb.condition(
+                and::expr([
+                    tx_id_is_zero_cur,
+                    meta.query_advice(is_rlp_byte, Rotation::next()),
+                ]),
+                |cb| {
+                    cb.require_zero(
+                        "tx_id::next == 0",
+                        meta.query_advice(tx_table.tx_id, Rotation::next()),
+                    );
+                },
+            );
+
+            cb.gate(and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(is_rlp_byte, Rotation::cur()),
+            ]))
+        });
+
+        // value < 2^8 iff value < 2^16 and value * 2^8 < 2^16
+        meta.lookup("rlp byte value in u16", |meta| {
+            let condition = and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(is_rlp_byte, Rotation::cur()),
+            ]);
+
+            vec![(
+                condition * meta.query_advice(tx_table.value, Rotation::cur()),
+                u16_table,
+            )]
+        });
+        meta.lookup("rlp byte value * 2^8 in u16", |meta| {
+            let condition = and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(is_rlp_byte, Rotation::cur()),
+            ]);
+
+            vec![(
+                condition * meta.query_advice(tx_table.value, Rotation::cur()) * 256.expr(),
+                u16_table,
+            )]
+        });
+
         ////////////////////////////////////////////////////////////////////////
         ///////////   SignVerify recover CallerAddress    //////////////////////
         ////////////////////////////////////////////////////////////////////////
@@ -820,7 +1291,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
-        let sign_verify = SignVerifyConfig::new(meta, keccak_table.clone());
+        let sign_verify = SignVerifyConfig::new(meta, keccak_table.clone(), challenges.clone());
         #[cfg(feature = "reject-eip2718")]
         meta.create_gate(
             "caller address == sv_address if it's not zero and tx_type != L1Msg",
@@ -857,15 +1328,20 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             value_is_zero,
             tx_id_unchanged,
             is_calldata,
+            is_rlp_byte,
             is_caller_address,
             tx_id_cmp_cum_num_txs,
             cum_num_txs,
+            block_timestamp,
+            prague_time,
+            timestamp_lt_prague_time,
             is_padding_tx,
             lookup_conditions,
             is_l1_msg,
             is_chain_id,
             is_final,
             calldata_gas_cost_acc,
+            rlp_bytes_rlc,
             sv_address,
             sign_verify,
             block_table,
@@ -889,6 +1365,7 @@ impl<F: Field> TxCircuitConfig<F> {
         lookup_conditions: &HashMap<LookupCondition, Column<Advice>>,
         is_final: Column<Advice>,
         calldata_gas_cost_acc: Column<Advice>,
+        rlp_bytes_rlc: Column<Advice>,
         tx_table: TxTable,
         keccak_table: KeccakTable,
         rlp_table: RlpTable,
@@ -971,6 +1448,40 @@ impl<F: Field> TxCircuitConfig<F> {
             .collect()
         });
 
+        // lookup to check that the bytes of the signed RLP encoding of the tx are in the
+        // tx table, with the length and the RLC of the tx's TxHashLength and TxHashRLC.
+        meta.lookup_any("tx rlp bytes in TxTable", |meta| {
+            let enable = and::expr(vec![
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(
+                    lookup_conditions[&LookupCondition::TxRlpBytes],
+                    Rotation::cur(),
+                ),
+            ]);
+            // we rely on the assumption that TxHashRLC is next to TxHashLength
+            vec![
+                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                RlpByte.expr(),
+                meta.query_advice(tx_table.value, Rotation::cur()) - 1.expr(), /* index starts
+                                                                                * from 0 */
+                1.expr(),                                            // is_final = true
+                meta.query_advice(tx_table.value, Rotation::next()), // tx_hash_rlc
+            ]
+            .into_iter()
+            .zip(
+                vec![
+                    meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                    meta.query_fixed(tx_table.tag, Rotation::cur()),
+                    meta.query_advice(tx_table.index, Rotation::cur()),
+                    meta.query_advice(is_final, Rotation::cur()),
+                    meta.query_advice(rlp_bytes_rlc, Rotation::cur()),
+                ]
+                .into_iter(),
+            )
+            .map(|(arg, table)| (enable.clone() * arg, table))
+            .collect()
+        });
+
         /////////////////////////////////////////////////////////////////
         /////////////////    RLP table lookups     //////////////////////
         ///////////////// ////////////////////////////////////////////////
@@ -1135,6 +1646,7 @@ impl<F: Field> TxCircuitConfig<F> {
         cum_num_txs: Option<usize>,
         is_final: Option<bool>,
         calldata_gas_cost_acc: Option<u64>,
+        rlp_bytes_rlc: Option<Value<F>>,
     ) -> Result<(), Error> {
         // assign to tag, rlp_tag, is_none
         let tag_chip = BinaryNumberChip::construct(self.tx_tag_bits);
@@ -1167,6 +1679,7 @@ impl<F: Field> TxCircuitConfig<F> {
         if tag == CallData {
             conditions = vec![
                 (LookupCondition::TxCalldata, Value::known(F::zero())),
+                (LookupCondition::TxRlpBytes, Value::known(F::zero())),
                 (LookupCondition::L1MsgHash, Value::known(F::zero())),
                 (LookupCondition::RlpSignTag, Value::known(F::zero())),
                 (LookupCondition::RlpHashTag, Value::known(F::zero())),
@@ -1184,6 +1697,11 @@ impl<F: Field> TxCircuitConfig<F> {
                     Value::known(F::zero())
                 }
             });
+            // lookup to Tx table for the signed RLP bytes
+            conditions.insert(
+                LookupCondition::TxRlpBytes,
+                Value::known(F::from((tag == TxHashLength) as u64)),
+            );
             // lookup to RLP table for signing (non L1 msg)
             conditions.insert(LookupCondition::RlpSignTag, {
                 let sign_set = [
@@ -1280,6 +1798,12 @@ impl<F: Field> TxCircuitConfig<F> {
             *offset,
             || Value::known(F::from((tag == CallData) as u64)),
         )?;
+        region.assign_advice(
+            || "is_rlp_byte",
+            self.is_rlp_byte,
+            *offset,
+            || Value::known(F::from((tag == RlpByte) as u64)),
+        )?;
 
         // assign to is_zero/is_equal chips
         let tx_id_is_zero_chip = IsZeroChip::construct(self.tx_id_is_zero.clone());
@@ -1309,6 +1833,12 @@ impl<F: Field> TxCircuitConfig<F> {
             *offset,
             || Value::known(F::from(calldata_gas_cost_acc.unwrap_or_default())),
         )?;
+        region.assign_advice(
+            || "rlp_bytes_rlc",
+            self.rlp_bytes_rlc,
+            *offset,
+            || rlp_bytes_rlc.unwrap_or(Value::known(F::zero())),
+        )?;
 
         // assign to
         region.assign_advice(
@@ -1336,66 +1866,31 @@ impl<F: Field> TxCircuitConfig<F> {
         Ok(())
     }
 
-    fn assign_calldata_zeros(
+    /// Assigns a row with tx_id 0 padding the rows of the call data or of the
+    /// rlp bytes in the tx table.
+    fn assign_zero_row(
         &self,
         region: &mut Region<'_, F>,
-        start: usize,
-        end: usize,
+        offset: &mut usize,
+        tx_id_next: usize,
+        tag: TxFieldTag,
     ) -> Result<(), Error> {
-        // let rlp_data = F::from( as u64);
-        let tag = F::from(CallData as u64);
-        let tx_id_is_zero_chip = IsZeroChip::construct(self.tx_id_is_zero.clone());
-        let value_is_zero_chip = IsZeroChip::construct(self.value_is_zero.clone());
-        let tx_id_unchanged = IsEqualChip::construct(self.tx_id_unchanged.clone());
-        let tag_chip = BinaryNumberChip::construct(self.tx_tag_bits);
-
-        for offset in start..end {
-            region.assign_fixed(
-                || "q_enable",
-                self.tx_table.q_enable,
-                offset,
-                || Value::known(F::one()),
-            )?;
-            region.assign_advice(
-                || "rlp_tag",
-                self.rlp_tag,
-                offset,
-                || Value::known(F::from(usize::from(Null) as u64)),
-            )?;
-            region.assign_fixed(|| "tag", self.tx_table.tag, offset, || Value::known(tag))?;
-            tag_chip.assign(region, offset, &CallData)?;
-            // no need to assign tx_id_is_zero_chip for real prover as tx_id = 0
-            tx_id_is_zero_chip.assign(region, offset, Value::known(F::zero()))?;
-            // no need to assign value_is_zero_chip for real prover as value = 0
-            value_is_zero_chip.assign(region, offset, Value::known(F::zero()))?;
-            tx_id_unchanged.assign(
-                region,
-                offset,
-                Value::known(F::zero()),
-                Value::known(F::zero()),
-            )?;
-
-            for (col, value) in [
-                (self.tx_table.tx_id, F::zero()),
-                (self.tx_table.index, F::zero()),
-                (self.tx_table.value, F::zero()),
-                (self.is_final, F::one()),
-                (self.is_calldata, F::one()),
-                (self.calldata_gas_cost_acc, F::zero()),
-            ] {
-                region.assign_advice(|| "", col, offset, || Value::known(value))?;
-            }
-            for col in self.lookup_conditions.values() {
-                region.assign_advice(
-                    || "lookup condition",
-                    *col,
-                    offset,
-                    || Value::known(F::zero()),
-                )?;
-            }
-        }
-
-        Ok(())
+        self.assign_row(
+            region,
+            offset,
+            None,
+            0, // tx_id
+            tx_id_next,
+            tag,
+            Value::known(F::zero()),
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            None,
+        )
     }
 
     fn assign_paddings(
@@ -1441,6 +1936,9 @@ pub struct TxCircuit<F: Field> {
     pub txs: Vec<Transaction>,
     /// Chain ID
     pub chain_id: u64,
+    /// Timestamp from which the blocks are on Prague, `None` if the chain
+    /// isn't on Prague
+    pub prague_time: Option<u64>,
     /// Size
     pub size: usize,
 }
@@ -1463,6 +1961,7 @@ impl<F: Field> TxCircuit<F> {
             txs,
             size: Self::min_num_rows(max_txs, max_calldata),
             chain_id,
+            prague_time: None,
         }
     }
 
@@ -1508,7 +2007,7 @@ impl<F: Field> TxCircuit<F> {
     /// Return the minimum number of rows required to prove an input of a
     /// particular size.
     pub fn min_num_rows(txs_len: usize, call_data_len: usize) -> usize {
-        let tx_table_len = txs_len * TX_LEN + call_data_len;
+        let tx_table_len = txs_len * TX_LEN + call_data_len + max_rlp_bytes(txs_len, call_data_len);
         #[cfg(feature = "enable-sign-verify")]
         let min_rows = std::cmp::max(tx_table_len, SignVerifyChip::<F>::min_num_rows(txs_len));
         #[cfg(not(feature = "enable-sign-verify"))]
@@ -1527,29 +2026,38 @@ impl<F: Field> TxCircuit<F> {
             .map(|tx| tx.block_number)
             .collect::<BTreeSet<u64>>();
         let mut num_txs_in_blocks = BTreeMap::new();
+        let mut block_timestamps = BTreeMap::new();
         for tx in self.txs.iter() {
             if let Some(num_txs) = num_txs_in_blocks.get_mut(&tx.block_number) {
                 *num_txs += 1;
             } else {
                 num_txs_in_blocks.insert(tx.block_number, 1_usize);
             }
+            block_timestamps.insert(tx.block_number, tx.block_timestamp);
         }
+        let rows = block_nums
+            .iter()
+            .scan(0, |cum_num_txs, block_num| {
+                *cum_num_txs += num_txs_in_blocks[block_num];
+                Some([
+                    (CumNumTxs, *block_num, *cum_num_txs as u64),
+                    (Timestamp, *block_num, block_timestamps[block_num]),
+                ])
+            })
+            .flatten();
 
         layouter.assign_region(
             || "dev block table",
             |mut region| {
-                for (offset, (block_num, cum_num_txs)) in iter::once((0, 0))
-                    .chain(block_nums.iter().scan(0, |cum_num_txs, block_num| {
-                        *cum_num_txs += num_txs_in_blocks[block_num];
-                        Some((*block_num, *cum_num_txs))
-                    }))
+                for (offset, (tag, block_num, value)) in iter::once((CumNumTxs, 0, 0))
+                    .chain(rows.clone())
                     .enumerate()
                 {
                     region.assign_fixed(
                         || "block_table.tag",
                         config.block_table.tag,
                         offset,
-                        || Value::known(F::from(CumNumTxs as u64)),
+                        || Value::known(F::from(tag as u64)),
                     )?;
                     region.assign_advice(
                         || "block_table.index",
@@ -1561,7 +2069,7 @@ impl<F: Field> TxCircuit<F> {
                         || "block_table.value",
                         config.block_table.value,
                         offset,
-                        || Value::known(F::from(cum_num_txs as u64)),
+                        || Value::known(F::from(value)),
                     )?;
                 }
                 Ok(())
@@ -1607,6 +2115,7 @@ impl<F: Field> TxCircuit<F> {
                     None,
                     None,
                     None,
+                    None,
                 )?;
 
                 // Assign all tx fields except for call data
@@ -1643,7 +2152,7 @@ impl<F: Field> TxCircuit<F> {
                         })
                     };
                     log::debug!("calldata len: {}", tx.call_data.len());
-                    for (tag, rlp_tag, is_none, value) in [
+                    let mut fields = vec![
                         // need to be in same order as that tx table load function uses
                         (
                             Nonce,
@@ -1663,7 +2172,7 @@ impl<F: Field> TxCircuit<F> {
                             Some(tx.gas_price.is_zero()),
                             challenges
                                 .evm_word()
-                                .map(|challenge| rlc(tx.gas_price.to_le_bytes(), challenge)),
+                                .map(|challenge| word::rlc(&tx.gas_price, challenge)),
                         ),
                         (
                             CallerAddress,
@@ -1694,7 +2203,7 @@ impl<F: Field> TxCircuit<F> {
                             Some(tx.value.is_zero()),
                             challenges
                                 .evm_word()
-                                .map(|challenge| rlc(tx.value.to_le_bytes(), challenge)),
+                                .map(|challenge| word::rlc(&tx.value, challenge)),
                         ),
                         (
                             CallDataRLC,
@@ -1733,7 +2242,7 @@ impl<F: Field> TxCircuit<F> {
                             Some(tx.r.is_zero()),
                             challenges
                                 .evm_word()
-                                .map(|challenge| rlc(tx.r.to_le_bytes(), challenge)),
+                                .map(|challenge| word::rlc(&tx.r, challenge)),
                         ),
                         (
                             SigS,
@@ -1741,7 +2250,7 @@ impl<F: Field> TxCircuit<F> {
                             Some(tx.s.is_zero()),
                             challenges
                                 .evm_word()
-                                .map(|challenge| rlc(tx.s.to_le_bytes(), challenge)),
+                                .map(|challenge| word::rlc(&tx.s, challenge)),
                         ),
                         (
                             TxSignLength,
@@ -1790,12 +2299,53 @@ impl<F: Field> TxCircuit<F> {
                             }),
                         ),
                         (
-                            BlockNumber,
+                            TxFieldTag::TxType,
+                            None,
+                            None,
+                            Value::known(F::from(usize::from(tx.tx_type) as u64)),
+                        ),
+                        (
+                            TxFieldTag::IsSystem,
+                            None,
+                            None,
+                            Value::known(F::from(tx.is_system as u64)),
+                        ),
+                        (
+                            TxFieldTag::IsInvalid,
+                            None,
+                            None,
+                            Value::known(F::from(tx.is_invalid as u64)),
+                        ),
+                        (
+                            TxFieldTag::IsPrague,
                             None,
                             None,
-                            Value::known(F::from(tx.block_number)),
+                            Value::known(F::from(tx.is_prague as u64)),
                         ),
-                    ] {
+                        #[cfg(feature = "prague")]
+                        (
+                            TxFieldTag::AuthorizationListLength,
+                            None,
+                            None,
+                            Value::known(F::from(tx.authorization_list.len() as u64)),
+                        ),
+                    ];
+                    #[cfg(feature = "prague")]
+                    fields.extend(tx.authorization_fields().into_iter().map(|(_, value)| {
+                        (
+                            TxFieldTag::Authorization,
+                            None,
+                            None,
+                            Value::known(value.to_scalar().expect("authorization field too big")),
+                        )
+                    }));
+                    fields.push((
+                        BlockNumber,
+                        None,
+                        None,
+                        Value::known(F::from(tx.block_number)),
+                    ));
+                    for (tag, rlp_tag, is_none, value) in fields {
                         let tx_id_next = match tag {
                             BlockNumber => {
                                 if i == sigs.len() - 1 {
@@ -1825,6 +2375,7 @@ impl<F: Field> TxCircuit<F> {
                             Some(cum_num_txs),
                             None,
                             None,
+                            None,
                         )?;
                         // Ref. spec 0. Copy constraints using fixed offsets
                         // between the tx rows and the SignVerifyChip
@@ -1848,6 +2399,30 @@ impl<F: Field> TxCircuit<F> {
                                     // TODO: do lookup to SignVerify table instead.
                                 }
                             }
+                            BlockNumber => {
+                                // the chain isn't on Prague as long as the
+                                // timestamps are below 2^64
+                                let prague_time =
+                                    self.prague_time.map_or(F::from_u128(1 << 64), F::from);
+                                region.assign_advice(
+                                    || "block_timestamp",
+                                    config.block_timestamp,
+                                    offset - 1,
+                                    || Value::known(F::from(tx.block_timestamp)),
+                                )?;
+                                region.assign_fixed(
+                                    || "prague_time",
+                                    config.prague_time,
+                                    offset - 1,
+                                    || Value::known(prague_time),
+                                )?;
+                                LtChip::construct(config.timestamp_lt_prague_time).assign(
+                                    &mut region,
+                                    offset - 1,
+                                    F::from(tx.block_timestamp),
+                                    prague_time,
+                                )?;
+                            }
                             _ => {}
                         }
                     }
@@ -1896,12 +2471,74 @@ impl<F: Field> TxCircuit<F> {
                             None,
                             Some(is_final),
                             Some(calldata_gas_cost),
+                            None,
                         )?;
                     }
                 }
 
                 debug_assert_eq!(offset, self.max_txs * TX_LEN + 1 + calldata_count);
 
+                // Assign the zero rows padding the call data, the last of them followed by
+                // the first rlp byte of the first tx
+                for index in calldata_count..self.max_calldata {
+                    let tx_id_next = if index == self.max_calldata - 1 {
+                        !sigs.is_empty() as usize
+                    } else {
+                        0
+                    };
+                    config.assign_zero_row(&mut region, &mut offset, tx_id_next, CallData)?;
+                }
+
+                // Assign the bytes of the signed RLP encoding of all the txs
+                let max_rlp_bytes = max_rlp_bytes(self.max_txs, self.max_calldata);
+                let mut rlp_bytes_count = 0;
+                for i in 0..sigs.len() {
+                    let tx = if i < self.txs.len() {
+                        &self.txs[i]
+                    } else {
+                        &padding_txs[i - self.txs.len()]
+                    };
+                    let rlp_length = tx.rlp_signed.len();
+                    rlp_bytes_count += rlp_length;
+                    if rlp_bytes_count > max_rlp_bytes {
+                        error!(
+                            "rlp_bytes_count = {} > max_rlp_bytes = {}",
+                            rlp_bytes_count, max_rlp_bytes
+                        );
+                        return Err(Error::Synthesis);
+                    }
+                    let mut rlp_bytes_rlc = Value::known(F::zero());
+                    for (index, byte) in tx.rlp_signed.iter().enumerate() {
+                        let is_final = index == rlp_length - 1;
+                        let tx_id_next = match (is_final, i == sigs.len() - 1) {
+                            (false, _) => i + 1,
+                            (true, false) => i + 2,
+                            (true, true) => 0,
+                        };
+                        rlp_bytes_rlc = rlp_bytes_rlc * challenges.keccak_input()
+                            + Value::known(F::from(*byte as u64));
+                        config.assign_row(
+                            &mut region,
+                            &mut offset,
+                            Some(tx),
+                            i + 1,      // tx_id
+                            tx_id_next, // tx_id_next
+                            RlpByte,
+                            Value::known(F::from(*byte as u64)),
+                            None,
+                            None,
+                            None,
+                            None,
+                            Some(is_final),
+                            None,
+                            Some(rlp_bytes_rlc),
+                        )?;
+                    }
+                }
+                for _ in rlp_bytes_count..max_rlp_bytes {
+                    config.assign_zero_row(&mut region, &mut offset, 0, RlpByte)?;
+                }
+
                 Ok(offset)
             },
         )?;
@@ -1914,20 +2551,9 @@ impl<F: Field> TxCircuit<F> {
             );
         }
         layouter.assign_region(
-            || "tx table (calldata zeros and paddings)",
+            || "tx table paddings",
             |mut region| {
-                config.assign_calldata_zeros(
-                    &mut region,
-                    0,
-                    self.max_calldata + self.max_txs * TX_LEN + 1 - last_off,
-                )?;
-                config.assign_paddings(
-                    &mut region,
-                    self.max_calldata + self.max_txs * TX_LEN + 1 - last_off,
-                    self.size - config.minimum_rows - last_off,
-                )?;
-
-                Ok(())
+                config.assign_paddings(&mut region, 0, self.size - config.minimum_rows - last_off)
             },
         )
     }
@@ -1949,12 +2575,15 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
                 );
             }
         }
-        Self::new(
-            block.circuits_params.max_txs,
-            block.circuits_params.max_calldata,
-            block.chain_id.as_u64(),
-            block.txs.clone(),
-        )
+        Self {
+            prague_time: block.prague_time,
+            ..Self::new(
+                block.circuits_params.max_txs,
+                block.circuits_params.max_calldata,
+                block.chain_id.as_u64(),
+                block.txs.clone(),
+            )
+        }
     }
 
     /// Return the minimum number of rows required to prove the block
@@ -2066,10 +2695,31 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         }
         Ok(())
     }
+}
 
-    fn instance(&self) -> Vec<Vec<F>> {
-        // The maingate expects an instance column, but we don't use it, so we return an
-        // "empty" instance column
-        vec![vec![]]
+impl<F: Field> SuperSubCircuit<F> for TxCircuit<F> {
+    const NAME: &'static str = "tx circuit";
+    const TABLES: &'static [(SharedTable, u32)] = &[
+        (SharedTable::Block, 1),
+        (SharedTable::Tx, 1),
+        (SharedTable::Keccak, 1),
+        (SharedTable::Rlp, 1),
+    ];
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &SharedTables,
+        args: &SuperCircuitConfigArgs<F>,
+    ) -> Self::Config {
+        TxCircuitConfig::new(
+            meta,
+            TxCircuitConfigArgs {
+                block_table: tables.block().clone(),
+                tx_table: tables.tx().clone(),
+                keccak_table: tables.keccak().clone(),
+                rlp_table: *tables.rlp(),
+                challenges: args.challenges.clone(),
+            },
+        )
     }
 }
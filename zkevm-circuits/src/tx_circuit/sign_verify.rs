@@ -20,7 +20,7 @@ use eth_types::{
     Field,
 };
 use halo2_base::{
-    gates::{range::RangeConfig, GateInstructions},
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
     utils::modulus,
     AssignedValue, Context, QuantumCell, SKIP_FIRST_PASS,
 };
@@ -39,7 +39,7 @@ use halo2_proofs::plonk::SecondPhase;
 use halo2_proofs::{
     circuit::{Cell, Layouter, Value},
     halo2curves::secp256k1::{Fp, Fq, Secp256k1Affine},
-    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
     poly::Rotation,
 };
 
@@ -50,12 +50,12 @@ use std::{iter, marker::PhantomData};
 
 // Hard coded parameters.
 // FIXME: allow for a configurable param.
-const MAX_NUM_SIG: usize = 32;
+pub(crate) const MAX_NUM_SIG: usize = 32;
 // Each ecdsa signature requires 534042 cells
 // We set CELLS_PER_SIG = 535000 to allows for a few buffer
 const CELLS_PER_SIG: usize = 535000;
 // Total number of rows allocated for ecdsa chip
-const TOTAL_NUM_ROWS: usize = 19;
+pub(crate) const TOTAL_NUM_ROWS: usize = 19;
 
 fn calc_required_advices(num_verif: usize) -> usize {
     let mut num_adv = 1;
@@ -151,11 +151,19 @@ pub(crate) struct SignVerifyConfig<F: Field> {
     rlc_column: Column<Advice>,
     /// selector for keccak lookup table
     q_keccak: Selector,
-    keccak_table: KeccakTable,
+    /// selectors for the powers of the challenges in the RLC column
+    q_power_first: Selector,
+    q_keccak_power: Selector,
+    q_evm_power: Selector,
+    pub(crate) keccak_table: KeccakTable,
 }
 
 impl<F: Field> SignVerifyConfig<F> {
-    pub(crate) fn new(meta: &mut ConstraintSystem<F>, keccak_table: KeccakTable) -> Self {
+    pub(crate) fn new(
+        meta: &mut ConstraintSystem<F>,
+        keccak_table: KeccakTable,
+        challenges: Challenges<Expression<F>>,
+    ) -> Self {
         #[cfg(feature = "onephase")]
         let num_advice = [calc_required_advices(MAX_NUM_SIG)];
         #[cfg(not(feature = "onephase"))]
@@ -192,9 +200,6 @@ impl<F: Field> SignVerifyConfig<F> {
             TOTAL_NUM_ROWS, // maximum k of the chip
         );
 
-        // we are not really using this instance column
-        let instance = meta.instance_column();
-        meta.enable_equality(instance);
         // we will need one fixed column to check if ecdsa is valid
         let fixed_column = meta.fixed_column();
         meta.enable_equality(fixed_column);
@@ -242,10 +247,39 @@ impl<F: Field> SignVerifyConfig<F> {
             input.into_iter().zip(table).collect()
         });
 
+        // The RLCs are inner products with powers of the challenges witnessed
+        // by the ecdsa chip, which are copied to the rlc column to be
+        // constrained to be the successive powers of the challenges.
+        // Layout:
+        // | q_power_first | q_keccak_power |  rlc  |
+        // | ------------- | -------------- | ----- |
+        // |       1       |       1        |   1   |
+        // |               |       1        |   r   |
+        // |               |                |  r^2  |
+        let q_power_first = meta.selector();
+        let q_keccak_power = meta.selector();
+        let q_evm_power = meta.selector();
+        meta.create_gate("powers of the challenges", |meta| {
+            let q_power_first = meta.query_selector(q_power_first);
+            let q_keccak_power = meta.query_selector(q_keccak_power);
+            let q_evm_power = meta.query_selector(q_evm_power);
+            let power = meta.query_advice(rlc_column, Rotation::cur());
+            let next_power = meta.query_advice(rlc_column, Rotation::next());
+
+            vec![
+                q_power_first * (power.clone() - 1.expr()),
+                q_keccak_power * (next_power.clone() - power.clone() * challenges.keccak_input()),
+                q_evm_power * (next_power - power * challenges.evm_word()),
+            ]
+        });
+
         Self {
             ecdsa_config,
             keccak_table,
             q_keccak,
+            q_power_first,
+            q_keccak_power,
+            q_evm_power,
             fixed_column,
             rlc_column,
         }
@@ -261,6 +295,8 @@ impl<F: Field> SignVerifyConfig<F> {
 pub(crate) struct AssignedECDSA<'v, F: Field, FC: FieldChip<F>> {
     pk: EcPoint<F, FC::FieldPoint<'v>>,
     msg_hash: CRTInteger<'v, F>,
+    sig_r: CRTInteger<'v, F>,
+    sig_s: CRTInteger<'v, F>,
     sig_is_valid: AssignedValue<'v, F>,
 }
 
@@ -333,12 +369,17 @@ pub(crate) struct AssignedSignatureVerify<F: Field> {
     pub(crate) msg_rlc: Value<F>,
     pub(crate) msg_hash_rlc: AssignedValueNoTimer<F>,
     pub(crate) sig_is_valid: AssignedValueNoTimer<F>,
+    /// Row of the signature in the sig table
+    pub(crate) sig_table_row: [AssignedValueNoTimer<F>; 4],
 }
 
-struct SignDataDecomposed<'a: 'v, 'v, F: Field> {
-    pk_hash_cells: Vec<QuantumCell<'a, 'v, F>>,
-    msg_hash_cells: Vec<QuantumCell<'a, 'v, F>>,
-    pk_cells: Vec<QuantumCell<'a, 'v, F>>,
+/// Range checked bytes of the signature data, little-endian.
+struct SignDataDecomposed<'v, F: Field> {
+    pk_hash_cells: Vec<AssignedValue<'v, F>>,
+    msg_hash_cells: Vec<AssignedValue<'v, F>>,
+    pk_cells: Vec<AssignedValue<'v, F>>,
+    sig_r_cells: Vec<AssignedValue<'v, F>>,
+    sig_s_cells: Vec<AssignedValue<'v, F>>,
     address: AssignedValue<'v, F>,
     is_address_zero: AssignedValue<'v, F>,
 }
@@ -358,7 +399,8 @@ impl<F: Field> SignVerifyChip<F> {
     fn assign_ecdsa<'v>(
         &self,
         ctx: &mut Context<'v, F>,
-        ecdsa_chip: &FpChip<F>,
+        ecc_chip: &EccChip<F, FpChip<F>>,
+        fq_chip: &FqChip<F>,
         sign_data: &SignData,
     ) -> Result<AssignedECDSA<'v, F, FpChip<F>>, Error> {
         log::trace!("start ecdsa assignment");
@@ -370,11 +412,6 @@ impl<F: Field> SignVerifyChip<F> {
         } = sign_data;
         let (sig_r, sig_s) = signature;
 
-        // build ecc chip from Fp chip
-        let ecc_chip = EccChip::<F, FpChip<F>>::construct(ecdsa_chip.clone());
-        // build Fq chip from Fp chip
-        let fq_chip = FqChip::construct(ecdsa_chip.range.clone(), 88, 3, modulus::<Fq>());
-
         log::trace!("r: {:?}", sig_r);
         log::trace!("s: {:?}", sig_s);
         log::trace!("msg: {:?}", msg_hash);
@@ -408,6 +445,8 @@ impl<F: Field> SignVerifyChip<F> {
         Ok(AssignedECDSA {
             pk: pk_assigned,
             msg_hash,
+            sig_r: integer_r,
+            sig_s: integer_s,
             sig_is_valid: ecdsa_is_valid,
         })
     }
@@ -465,17 +504,35 @@ impl<F: Field> SignVerifyChip<F> {
         Ok(())
     }
 
-    /// Input the signature data,
+    /// Load `bytes` in cells range checked to be bytes.
+    fn load_bytes<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        range_chip: &RangeConfig<F>,
+        bytes: &[u8],
+    ) -> Vec<AssignedValue<'v, F>> {
+        bytes
+            .iter()
+            .map(|&byte| {
+                let cell = range_chip
+                    .gate
+                    .load_witness(ctx, Value::known(F::from(byte as u64)));
+                range_chip.range_check(ctx, &cell, 8);
+                cell
+            })
+            .collect()
+    }
+
+    /// Input the signature data, and the public key and message hash assigned
+    /// for its verification,
     /// Output the cells for byte decomposition of the keys and messages
-    fn sign_data_decomposition<'a: 'v, 'v>(
+    fn sign_data_decomposition<'v>(
         &self,
         ctx: &mut Context<'v, F>,
         ecdsa_chip: &FpChip<F>,
         sign_data: Option<&SignData>,
-    ) -> Result<SignDataDecomposed<'a, 'v, F>, Error> {
-        // build ecc chip from Fp chip
-        let ecc_chip = EccChip::<F, FpChip<F>>::construct(ecdsa_chip.clone());
-
+        assigned_ecdsa: &AssignedECDSA<'v, F, FpChip<F>>,
+    ) -> Result<SignDataDecomposed<'v, F>, Error> {
         let zero = ecdsa_chip.range.gate.load_zero(ctx);
 
         let (padding, sign_data) = match sign_data {
@@ -497,29 +554,25 @@ impl<F: Field> SignVerifyChip<F> {
         // ================================================
         let pk_le = pk_bytes_le(&sign_data.pk);
         let pk_be = pk_bytes_swap_endianness(&pk_le);
-        let pk_hash = (!padding)
+        let mut pk_hash = (!padding)
             .then(|| {
                 let mut keccak = Keccak::default();
                 keccak.update(&pk_be);
                 let hash: [_; 32] = keccak.digest().try_into().expect("vec to array of size 32");
                 hash
             })
-            .unwrap_or_default()
-            .map(|byte| Value::known(F::from(byte as u64)));
+            .unwrap_or_default();
 
         log::trace!("pk hash {:0x?}", pk_hash);
-        let pk_hash_cells = pk_hash
-            .iter()
-            .map(|&x| QuantumCell::Witness(x))
-            .rev()
-            .collect_vec();
+        pk_hash.reverse();
+        let pk_hash_cells = self.load_bytes(ctx, &ecdsa_chip.range, &pk_hash);
 
         // address is the random linear combination of the public key
         // it is fine to use a phase 1 gate here
         let address = ecdsa_chip.range.gate.inner_product(
             ctx,
             powers_of_256_cells[..20].to_vec(),
-            pk_hash_cells[..20].to_vec(),
+            pk_hash_cells[..20].iter().map(QuantumCell::Existing),
         );
         log::trace!("address: {:?}", address.value());
 
@@ -533,57 +586,61 @@ impl<F: Field> SignVerifyChip<F> {
         // ================================================
         // message hash cells
         // ================================================
-        let assigned_msg_hash_le = (!padding)
+        let msg_hash_le = (!padding)
             .then(|| sign_data.msg_hash.to_bytes())
-            .unwrap_or_default()
-            .iter()
-            .map(|&x| QuantumCell::Witness(Value::known(F::from_u128(x as u128))))
-            .collect_vec();
-
-        // assert the assigned_msg_hash_le is the right decomposition of msg_hash
-        // msg_hash is an overflowing integer with 3 limbs, of sizes 88, 88, and 80
-        let assigned_msg_hash = ecdsa_chip.load_private(
-            ctx,
-            FqChip::<F>::fe_to_witness(&Value::known(sign_data.msg_hash)),
-        );
+            .unwrap_or_default();
+        let msg_hash_cells = self.load_bytes(ctx, &ecdsa_chip.range, &msg_hash_le);
 
+        // assert the msg_hash_cells are the right decomposition of msg_hash
+        // msg_hash is an overflowing integer with 3 limbs, of sizes 88, 88, and 80.
+        // The msg_hash verified by the ecdsa chip is reused, so that its limbs aren't
+        // loaded and range checked again.
         self.assert_crt_int_byte_repr(
             ctx,
             &ecdsa_chip.range,
-            &assigned_msg_hash,
-            &assigned_msg_hash_le,
+            &assigned_ecdsa.msg_hash,
+            &msg_hash_cells
+                .iter()
+                .map(QuantumCell::Existing)
+                .collect_vec(),
             &powers_of_256_cells,
             &Some(&is_address_zero_cell),
         )?;
 
         // ================================================
-        // pk cells
+        // signature cells
         // ================================================
-        let pk_x_le = sign_data
-            .pk
-            .x
-            .to_bytes()
-            .iter()
-            .map(|&x| QuantumCell::Witness(Value::known(F::from_u128(x as u128))))
-            .collect_vec();
+        // r and s are looked up by the ECRecover calls from the sig table, so
+        // they are decomposed like the message hash, without overriding.
+        let (sig_r, sig_s) = sign_data.signature;
+        let sig_r_cells = self.load_bytes(ctx, &ecdsa_chip.range, &sig_r.to_bytes());
+        let sig_s_cells = self.load_bytes(ctx, &ecdsa_chip.range, &sig_s.to_bytes());
+        for (crt_int, cells) in [
+            (&assigned_ecdsa.sig_r, &sig_r_cells),
+            (&assigned_ecdsa.sig_s, &sig_s_cells),
+        ] {
+            self.assert_crt_int_byte_repr(
+                ctx,
+                &ecdsa_chip.range,
+                crt_int,
+                &cells.iter().map(QuantumCell::Existing).collect_vec(),
+                &powers_of_256_cells,
+                &None,
+            )?;
+        }
 
-        let pk_y_le = sign_data
-            .pk
-            .y
-            .to_bytes()
-            .iter()
-            .map(|&x| QuantumCell::Witness(Value::known(F::from_u128(x as u128))))
-            .collect_vec();
-        let pk_assigned = ecc_chip.load_private(
-            ctx,
-            (Value::known(sign_data.pk.x), Value::known(sign_data.pk.y)),
-        );
+        // ================================================
+        // pk cells
+        // ================================================
+        let pk_x_le = self.load_bytes(ctx, &ecdsa_chip.range, &sign_data.pk.x.to_bytes());
+        let pk_y_le = self.load_bytes(ctx, &ecdsa_chip.range, &sign_data.pk.y.to_bytes());
 
+        // same for the pk verified by the ecdsa chip
         self.assert_crt_int_byte_repr(
             ctx,
             &ecdsa_chip.range,
-            &pk_assigned.x,
-            &pk_x_le,
+            &assigned_ecdsa.pk.x,
+            &pk_x_le.iter().map(QuantumCell::Existing).collect_vec(),
             &powers_of_256_cells,
             &None,
         )?;
@@ -591,8 +648,8 @@ impl<F: Field> SignVerifyChip<F> {
         self.assert_crt_int_byte_repr(
             ctx,
             &ecdsa_chip.range,
-            &pk_assigned.y,
-            &pk_y_le,
+            &assigned_ecdsa.pk.y,
+            &pk_y_le.iter().map(QuantumCell::Existing).collect_vec(),
             &powers_of_256_cells,
             &None,
         )?;
@@ -601,84 +658,76 @@ impl<F: Field> SignVerifyChip<F> {
         log::trace!("finished data decomposition");
         Ok(SignDataDecomposed {
             pk_hash_cells,
-            msg_hash_cells: assigned_msg_hash_le,
+            msg_hash_cells,
             pk_cells: assigned_pk_le_selected,
+            sig_r_cells,
+            sig_s_cells,
             address,
             is_address_zero,
         })
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn assign_sig_verify<'a: 'v, 'v>(
+    fn assign_sig_verify<'v>(
         &self,
         ctx: &mut Context<'v, F>,
         rlc_chip: &RangeConfig<F>,
         sign_data: Option<&SignData>,
-        sign_data_decomposed: &SignDataDecomposed<'a, 'v, F>,
+        sign_data_decomposed: &SignDataDecomposed<'v, F>,
         challenges: &Challenges<Value<F>>,
         sig_is_valid: &AssignedValue<'v, F>,
+        keccak_challenge_powers: &[AssignedValue<'v, F>],
+        evm_challenge_powers: &[AssignedValue<'v, F>],
     ) -> Result<([AssignedValue<'v, F>; 3], AssignedSignatureVerify<F>), Error> {
         let (_padding, sign_data) = match sign_data {
             Some(sign_data) => (false, sign_data.clone()),
             None => (true, SignData::default()),
         };
 
-        // ================================================
-        // step 0. powers of aux parameters
-        // ================================================
-        let evm_challenge_powers = iter::successors(Some(Value::known(F::one())), |coeff| {
-            Some(challenges.evm_word() * coeff)
-        })
-        .take(32)
-        .map(|x| QuantumCell::Witness(x))
-        .collect_vec();
-
-        log::trace!("evm challenge: {:?} ", challenges.evm_word());
+        // inner product of little-endian bytes with the powers of a challenge
+        let mut bytes_rlc = |bytes: &[AssignedValue<'v, F>], powers: &[AssignedValue<'v, F>]| {
+            rlc_chip.gate.inner_product(
+                ctx,
+                bytes.iter().map(QuantumCell::Existing),
+                powers[..bytes.len()].iter().map(QuantumCell::Existing),
+            )
+        };
 
-        let keccak_challenge_powers = iter::successors(Some(Value::known(F::one())), |coeff| {
-            Some(challenges.keccak_input() * coeff)
-        })
-        .take(64)
-        .map(|x| QuantumCell::Witness(x))
-        .collect_vec();
         // ================================================
         // step 1 random linear combination of message hash
         // ================================================
         // Ref. spec SignVerifyChip 3. Verify that the signed message in the ecdsa_chip
         // with RLC encoding corresponds to msg_hash_rlc
-        let msg_hash_rlc = rlc_chip.gate.inner_product(
-            ctx,
-            sign_data_decomposed
-                .msg_hash_cells
-                .iter()
-                .take(32)
-                .cloned()
-                .collect_vec(),
-            evm_challenge_powers.clone(),
-        );
+        let msg_hash_rlc = bytes_rlc(&sign_data_decomposed.msg_hash_cells, evm_challenge_powers);
 
         log::trace!("assigned msg hash rlc: {:?}", msg_hash_rlc.value());
 
         // ================================================
         // step 2 random linear combination of pk
         // ================================================
-        let pk_rlc = rlc_chip.gate.inner_product(
-            ctx,
-            sign_data_decomposed.pk_cells.clone(),
-            keccak_challenge_powers,
-        );
+        let pk_rlc = bytes_rlc(&sign_data_decomposed.pk_cells, keccak_challenge_powers);
         log::trace!("pk rlc: {:?}", pk_rlc.value());
 
         // ================================================
         // step 3 random linear combination of pk_hash
         // ================================================
-        let pk_hash_rlc = rlc_chip.gate.inner_product(
-            ctx,
-            sign_data_decomposed.pk_hash_cells.clone(),
-            evm_challenge_powers.clone(),
-        );
+        let pk_hash_rlc = bytes_rlc(&sign_data_decomposed.pk_hash_cells, evm_challenge_powers);
 
         log::trace!("pk hash rlc halo2ecc: {:?}", pk_hash_rlc.value());
+
+        // ================================================
+        // step 4 row of the sig table
+        // ================================================
+        // The fields read by the ECRecover calls are combined with the keccak
+        // challenge, like the call data and return data they are read from.
+        let sig_table_row: [AssignedValueNoTimer<F>; 4] = [
+            &sign_data_decomposed.msg_hash_cells[..],
+            &sign_data_decomposed.sig_r_cells[..],
+            &sign_data_decomposed.sig_s_cells[..],
+            &sign_data_decomposed.pk_hash_cells[..20],
+        ]
+        .map(|bytes| bytes_rlc(bytes, keccak_challenge_powers).into());
+
         log::trace!("finished sign verify");
         Ok((
             [
@@ -694,10 +743,38 @@ impl<F: Field> SignVerifyChip<F> {
                     .map(|r| rlc::value(sign_data.msg.iter().rev(), r)),
                 msg_hash_rlc: msg_hash_rlc.into(),
                 sig_is_valid: sig_is_valid.clone().into(),
+                sig_table_row,
             },
         ))
     }
 
+    /// Copy the powers of a challenge to the rlc column, where they are
+    /// constrained by the `q_power` selector.
+    fn assign_challenge_powers(
+        &self,
+        config: &SignVerifyConfig<F>,
+        ctx: &mut Context<F>,
+        offset: &mut usize,
+        q_power: Selector,
+        powers: &[AssignedValue<F>],
+    ) -> Result<(), Error> {
+        config.q_power_first.enable(&mut ctx.region, *offset)?;
+        for (i, power) in powers.iter().enumerate() {
+            if i + 1 < powers.len() {
+                q_power.enable(&mut ctx.region, *offset + i)?;
+            }
+            let tmp_cell = ctx.region.assign_advice(
+                || "challenge power",
+                config.rlc_column,
+                *offset + i,
+                || power.value,
+            )?;
+            ctx.region.constrain_equal(power.cell, tmp_cell.cell())?;
+        }
+        *offset += powers.len();
+        Ok(())
+    }
+
     pub(crate) fn assign(
         &self,
         config: &SignVerifyConfig<F>,
@@ -725,6 +802,10 @@ impl<F: Field> SignVerifyChip<F> {
                 }
 
                 let mut ctx = ecdsa_chip.new_context(region);
+                // the chips are built once for the whole batch: all the
+                // verifications share the range check table and fixed column of ecdsa_chip
+                let ecc_chip = EccChip::<F, FpChip<F>>::construct(ecdsa_chip.clone());
+                let fq_chip = FqChip::construct(ecdsa_chip.range.clone(), 88, 3, modulus::<Fq>());
 
                 // ================================================
                 // step 1: assert the signature is valid in circuit
@@ -738,7 +819,8 @@ impl<F: Field> SignVerifyChip<F> {
                         // padding (enabled when address == 0)
                         SignData::default()
                     };
-                    let assigned_ecdsa = self.assign_ecdsa(&mut ctx, ecdsa_chip, &signature)?;
+                    let assigned_ecdsa =
+                        self.assign_ecdsa(&mut ctx, &ecc_chip, &fq_chip, &signature)?;
                     assigned_ecdsas.push(assigned_ecdsa);
                 }
 
@@ -746,10 +828,14 @@ impl<F: Field> SignVerifyChip<F> {
                 // step 2: decompose the keys and messages
                 // ================================================
                 let mut sign_data_decomposed_vec = Vec::new();
-                for i in 0..assigned_ecdsas.len() {
+                for (i, assigned_ecdsa) in assigned_ecdsas.iter().enumerate() {
                     let sign_data = signatures.get(i); // None when padding (enabled when address == 0)
-                    let sign_data_decomposed =
-                        self.sign_data_decomposition(&mut ctx, ecdsa_chip, sign_data)?;
+                    let sign_data_decomposed = self.sign_data_decomposition(
+                        &mut ctx,
+                        ecdsa_chip,
+                        sign_data,
+                        assigned_ecdsa,
+                    )?;
                     sign_data_decomposed_vec.push(sign_data_decomposed);
                 }
 
@@ -767,6 +853,17 @@ impl<F: Field> SignVerifyChip<F> {
                 // ================================================
                 // step 3: compute RLC of keys and messages
                 // ================================================
+                let [keccak_challenge_powers, evm_challenge_powers] =
+                    [(challenges.keccak_input(), 64), (challenges.evm_word(), 32)].map(
+                        |(challenge, len)| {
+                            iter::successors(Some(Value::known(F::one())), |coeff| {
+                                Some(challenge * coeff)
+                            })
+                            .take(len)
+                            .map(|power| ecdsa_chip.range.gate.load_witness(&mut ctx, power))
+                            .collect_vec()
+                        },
+                    );
                 let mut assigned_sig_verifs = Vec::new();
                 let mut deferred_keccak_check = Vec::new();
                 for (i, e) in assigned_ecdsas.iter().enumerate() {
@@ -779,6 +876,8 @@ impl<F: Field> SignVerifyChip<F> {
                         sign_data_decomposed,
                         challenges,
                         &e.sig_is_valid,
+                        &keccak_challenge_powers,
+                        &evm_challenge_powers,
                     )?;
                     assigned_sig_verifs.push(assigned_sig_verif);
                     deferred_keccak_check.push(to_be_keccak_checked);
@@ -800,6 +899,16 @@ impl<F: Field> SignVerifyChip<F> {
                     )?;
                 }
 
+                // ================================================
+                // step 5: constrain the powers of the challenges
+                // ================================================
+                for (q_power, powers) in [
+                    (config.q_keccak_power, &keccak_challenge_powers),
+                    (config.q_evm_power, &evm_challenge_powers),
+                ] {
+                    self.assign_challenge_powers(config, &mut ctx, &mut offset, q_power, powers)?;
+                }
+
                 // IMPORTANT: this assigns all constants to the fixed columns
                 // IMPORTANT: this copies cells to the lookup advice column to perform range
                 // check lookups
@@ -993,7 +1102,7 @@ mod sign_verify_tests {
             let keccak_table = KeccakTable::construct(meta);
             let challenges = Challenges::construct(meta);
 
-            let sign_verify = SignVerifyConfig::new(meta, keccak_table);
+            let sign_verify = SignVerifyConfig::new(meta, keccak_table, challenges.exprs(meta));
 
             TestCircuitSignVerifyConfig {
                 sign_verify,
@@ -1049,7 +1158,6 @@ mod sign_verify_tests {
     }
 
     fn run<F: Field>(k: u32, max_verif: usize, signatures: Vec<SignData>) {
-        // SignVerifyChip -> ECDSAChip -> MainGate instance column
         let circuit = TestCircuitSignVerify::<F> {
             sign_verify: SignVerifyChip {
                 max_verif,
@@ -1058,7 +1166,7 @@ mod sign_verify_tests {
             signatures,
         };
 
-        let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
+        let prover = match MockProver::run(k, &circuit, vec![]) {
             Ok(prover) => prover,
             Err(e) => panic!("{:#?}", e),
         };
@@ -1,7 +1,14 @@
 #![allow(unused_imports)]
 
 use ethers_core::{
-    types::{NameOrAddress, Signature, Transaction as EthTransaction, TransactionRequest},
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessList, AccessListItem, Eip2930TransactionRequest},
+        },
+        Eip1559TransactionRequest, NameOrAddress, Signature, Transaction as EthTransaction,
+        TransactionRequest,
+    },
     utils::{keccak256, rlp, rlp::Decodable},
 };
 use std::cmp::max;
@@ -109,15 +116,27 @@ fn run<F: Field>(
     chain_id: u64,
     max_txs: usize,
     max_calldata: usize,
+) -> Result<(), Vec<VerifyFailure>> {
+    run_with_prague_time(txs, chain_id, None, max_txs, max_calldata)
+}
+
+fn run_with_prague_time<F: Field>(
+    txs: Vec<Transaction>,
+    chain_id: u64,
+    prague_time: Option<u64>,
+    max_txs: usize,
+    max_calldata: usize,
 ) -> Result<(), Vec<VerifyFailure>> {
     let k = max(
         19,
         log2_ceil(TxCircuit::<F>::min_num_rows(max_txs, max_calldata)),
     );
-    // SignVerifyChip -> ECDSAChip -> MainGate instance column
-    let circuit = TxCircuit::<F>::new(max_txs, max_calldata, chain_id, txs);
+    let circuit = TxCircuit::<F> {
+        prague_time,
+        ..TxCircuit::new(max_txs, max_calldata, chain_id, txs)
+    };
 
-    let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
+    let prover = match MockProver::run(k, &circuit, vec![]) {
         Ok(prover) => prover,
         Err(e) => panic!("{:#?}", e),
     };
@@ -216,6 +235,57 @@ fn tx_circuit_bad_address() {
     .is_err(),);
 }
 
+#[test]
+fn tx_circuit_system_tx_from_other_sender() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let mut mock_tx = mock::CORRECT_MOCK_TXS[0].clone();
+    // sign the tx again with a zero gas price
+    (mock_tx.v, mock_tx.r, mock_tx.s, mock_tx.hash) = (None, None, None, None);
+    let mut tx: Transaction = mock_tx.gas_price(U256::zero()).build().into();
+    // a zero priced tx which isn't sent by the system sender
+    tx.is_system = true;
+
+    assert!(run::<Fr>(vec![tx], chain_id, MAX_TXS, MAX_CALLDATA).is_err());
+}
+
+#[test]
+fn tx_circuit_is_prague() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let mut tx: Transaction = mock::CORRECT_MOCK_TXS[0].clone().into();
+    tx.block_timestamp = 1000;
+
+    for (prague_time, is_prague) in [(None, false), (Some(1000), true)] {
+        tx.is_prague = is_prague;
+        assert_eq!(
+            run_with_prague_time::<Fr>(
+                vec![tx.clone()],
+                chain_id,
+                prague_time,
+                MAX_TXS,
+                MAX_CALLDATA
+            ),
+            Ok(())
+        );
+
+        // the flag doesn't follow the timestamp of the block
+        tx.is_prague = !is_prague;
+        assert!(run_with_prague_time::<Fr>(
+            vec![tx.clone()],
+            chain_id,
+            prague_time,
+            MAX_TXS,
+            MAX_CALLDATA
+        )
+        .is_err());
+    }
+}
+
 #[test]
 fn tx_circuit_to_is_zero() {
     const MAX_TXS: usize = 1;
@@ -230,3 +300,90 @@ fn tx_circuit_to_is_zero() {
         Ok(())
     );
 }
+
+/// Typed tx of `tx_type` with an access list and the largest signature
+#[cfg(test)]
+fn build_typed_tx(tx_type: TxType) -> Transaction {
+    let access_list = AccessList(vec![
+        AccessListItem {
+            address: address!("0x5f65f7b609678448494de4c87521cdf6cef1e932"),
+            storage_keys: vec![H256::repeat_byte(0xff); 3],
+        },
+        AccessListItem {
+            address: address!("0x95ad61b0a150d79219dcf64e1e6cc01f0b64c4ce"),
+            storage_keys: vec![],
+        },
+    ]);
+    let to = address!("0x5f65f7b609678448494de4c87521cdf6cef1e932");
+    let typed_tx = match tx_type {
+        TxType::Eip2930 => TypedTransaction::Eip2930(Eip2930TransactionRequest::new(
+            TransactionRequest::new()
+                .nonce(u64::MAX)
+                .gas_price(U256::MAX)
+                .gas(u64::MAX)
+                .to(to)
+                .value(U256::MAX)
+                .data(vec![0xff; 100])
+                .chain_id(u64::MAX),
+            access_list.clone(),
+        )),
+        TxType::Eip1559 => TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .nonce(u64::MAX)
+                .max_priority_fee_per_gas(U256::MAX)
+                .max_fee_per_gas(U256::MAX)
+                .gas(u64::MAX)
+                .to(to)
+                .value(U256::MAX)
+                .data(vec![0xff; 100])
+                .access_list(access_list.clone())
+                .chain_id(u64::MAX),
+        ),
+        _ => unreachable!("{:?} is not a typed tx with an access list", tx_type),
+    };
+    let sig = Signature {
+        r: U256::MAX,
+        s: U256::MAX,
+        v: 1,
+    };
+
+    let mut tx = Transaction::new_from_rlp_bytes(
+        tx_type,
+        typed_tx.rlp_signed(&sig).to_vec(),
+        typed_tx.rlp().to_vec(),
+    );
+    tx.call_data = vec![0xff; 100];
+    tx.call_data_length = tx.call_data.len();
+    tx.access_list = access_list;
+    tx
+}
+
+#[test]
+fn tx_circuit_rlp_len_is_bounded() {
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let txs = [
+        Transaction::dummy(chain_id),
+        build_pre_eip155_tx(),
+        build_l1_msg_tx(),
+        build_typed_tx(TxType::Eip2930),
+        build_typed_tx(TxType::Eip1559),
+    ]
+    .into_iter()
+    .chain(
+        mock::CORRECT_MOCK_TXS
+            .iter()
+            .cloned()
+            .map(Transaction::from),
+    );
+
+    for tx in txs {
+        assert!(
+            tx.rlp_signed.len() <= tx_rlp_len_bound(&tx),
+            "{:?}: {} > {}",
+            tx.tx_type,
+            tx.rlp_signed.len(),
+            tx_rlp_len_bound(&tx)
+        );
+        assert!(tx_rlp_len_without_data(tx.tx_type) <= MAX_TX_RLP_LEN_WITHOUT_DATA);
+    }
+}
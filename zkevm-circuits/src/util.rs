@@ -1,13 +1,15 @@
 //! Common utility traits and functions.
 use std::collections::BTreeSet;
 
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{circuit_input_builder::CircuitsParams, evm::OpcodeId};
 use halo2_proofs::{
-    arithmetic::FieldExt,
+    arithmetic::{CurveAffine, FieldExt},
     circuit::{Layouter, Value},
-    plonk::{Challenge, Circuit, ConstraintSystem, Error, Expression, FirstPhase, VirtualCells},
+    plonk::{
+        Challenge, Circuit, ConstraintSystem, Error, Expression, FirstPhase, VerifyingKey,
+        VirtualCells,
+    },
 };
-use keccak256::plain::Keccak;
 
 #[cfg(feature = "onephase")]
 use halo2_proofs::plonk::FirstPhase as SecondPhase;
@@ -15,12 +17,19 @@ use halo2_proofs::plonk::FirstPhase as SecondPhase;
 use halo2_proofs::plonk::SecondPhase;
 
 use crate::{evm_circuit::util::rlc, table::TxLogFieldTag, witness};
-use eth_types::{Field, ToAddress, Word};
+use eth_types::{Field, ToAddress, Word, H256};
 pub use ethers_core::types::{Address, U256};
+use ethers_core::utils::keccak256;
 pub use gadgets::util::Expr;
 
+/// Fast path of the witness generation for the tests
+pub(crate) mod fast_mock;
 /// A wrapper of is_zero in gadgets which gives is_zero at any rotation
 pub mod is_zero;
+/// Grand-product fingerprint of the rows of a rw table
+pub mod rw_fingerprint;
+/// Encodings of the EVM words in the field
+pub mod word;
 
 pub(crate) fn query_expression<F: FieldExt, T>(
     meta: &mut ConstraintSystem<F>,
@@ -34,16 +43,8 @@ pub(crate) fn query_expression<F: FieldExt, T>(
     expr.unwrap()
 }
 
-pub(crate) fn random_linear_combine_word<F: FieldExt>(bytes: [u8; 32], randomness: F) -> F {
-    rlc::value(&bytes, randomness)
-}
-
 pub(crate) fn rlc_be_bytes<F: Field>(bytes: &[u8], rand: Value<F>) -> Value<F> {
-    rand.map(|rand| {
-        bytes
-            .iter()
-            .fold(F::zero(), |acc, byte| acc * rand + F::from(*byte as u64))
-    })
+    rand.map(|rand| rlc::value(bytes.iter().rev(), rand))
 }
 
 /// All challenges used in `SuperCircuit`.
@@ -52,6 +53,7 @@ pub struct Challenges<T = Challenge> {
     evm_word: T,
     keccak_input: T,
     lookup_input: T,
+    permutation: T,
 }
 
 /// ..
@@ -60,6 +62,7 @@ pub struct MockChallenges {
     evm_word: u64,
     keccak_input: u64,
     lookup_input: u64,
+    permutation: u64,
 }
 
 impl MockChallenges {
@@ -69,6 +72,7 @@ impl MockChallenges {
             evm_word: 0x100,
             keccak_input: 0x100,
             lookup_input: 0x100,
+            permutation: 0x100,
         }
     }
     /// ..
@@ -77,6 +81,7 @@ impl MockChallenges {
             evm_word: Expression::Constant(F::from(self.evm_word)),
             keccak_input: Expression::Constant(F::from(self.keccak_input)),
             lookup_input: Expression::Constant(F::from(self.lookup_input)),
+            permutation: Expression::Constant(F::from(self.permutation)),
         }
     }
     /// ..
@@ -85,6 +90,7 @@ impl MockChallenges {
             evm_word: Value::known(F::from(self.evm_word)),
             keccak_input: Value::known(F::from(self.keccak_input)),
             lookup_input: Value::known(F::from(self.lookup_input)),
+            permutation: Value::known(F::from(self.permutation)),
         }
     }
 }
@@ -103,19 +109,26 @@ impl Challenges {
             evm_word: meta.challenge_usable_after(FirstPhase),
             keccak_input: meta.challenge_usable_after(FirstPhase),
             lookup_input: meta.challenge_usable_after(SecondPhase),
+            permutation: meta.challenge_usable_after(SecondPhase),
         }
     }
 
     /// Returns `Expression` of challenges from `ConstraintSystem`.
     pub fn exprs<F: FieldExt>(&self, meta: &mut ConstraintSystem<F>) -> Challenges<Expression<F>> {
-        let [evm_word, keccak_input, lookup_input] = query_expression(meta, |meta| {
-            [self.evm_word, self.keccak_input, self.lookup_input]
-                .map(|challenge| meta.query_challenge(challenge))
+        let [evm_word, keccak_input, lookup_input, permutation] = query_expression(meta, |meta| {
+            [
+                self.evm_word,
+                self.keccak_input,
+                self.lookup_input,
+                self.permutation,
+            ]
+            .map(|challenge| meta.query_challenge(challenge))
         });
         Challenges {
             evm_word,
             keccak_input,
             lookup_input,
+            permutation,
         }
     }
 
@@ -125,6 +138,7 @@ impl Challenges {
             evm_word: layouter.get_challenge(self.evm_word),
             keccak_input: layouter.get_challenge(self.keccak_input),
             lookup_input: layouter.get_challenge(self.lookup_input),
+            permutation: layouter.get_challenge(self.permutation),
         }
     }
 }
@@ -145,16 +159,28 @@ impl<T: Clone> Challenges<T> {
         self.lookup_input.clone()
     }
 
+    /// Returns challenge of `permutation`, the point at which the
+    /// fingerprints of rw tables are evaluated.
+    pub fn permutation(&self) -> T {
+        self.permutation.clone()
+    }
+
     /// Returns the challenges indexed by the challenge index
-    pub fn indexed(&self) -> [&T; 3] {
-        [&self.evm_word, &self.keccak_input, &self.lookup_input]
+    pub fn indexed(&self) -> [&T; 4] {
+        [
+            &self.evm_word,
+            &self.keccak_input,
+            &self.lookup_input,
+            &self.permutation,
+        ]
     }
 
-    /// ..
+    /// .. The `permutation` challenge is set to `lookup_input`.
     pub fn mock(evm_word: T, keccak_input: T, lookup_input: T) -> Self {
         Self {
             evm_word,
             keccak_input,
+            permutation: lookup_input.clone(),
             lookup_input,
         }
     }
@@ -254,9 +280,7 @@ pub fn log2_ceil(n: usize) -> u32 {
 }
 
 pub(crate) fn keccak(msg: &[u8]) -> Word {
-    let mut keccak = Keccak::default();
-    keccak.update(msg);
-    Word::from_big_endian(keccak.digest().as_slice())
+    Word::from_big_endian(&fast_mock::keccak256(msg))
 }
 
 pub(crate) fn is_push_with_data(byte: u8) -> bool {
@@ -346,3 +370,24 @@ pub(crate) fn unusable_rows<F: Field, C: Circuit<F>>() -> usize {
 
     cs.blinding_factors() + 1
 }
+
+/// Returns a digest of the constraint system of a circuit, its columns, gates,
+/// lookups and permuted columns, along with `params`. It is computed without
+/// any key, but misses the values of the fixed columns and the copy
+/// constraints, so a change of a fixed table doesn't change it: use
+/// [`vk_fingerprint`] to detect any change of the verifying key.
+pub fn circuit_config_digest<F: Field, C: Circuit<F>>(params: &CircuitsParams) -> H256 {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+
+    H256(keccak256(format!("{:?} {:?}", cs.pinned(), params)))
+}
+
+/// Returns a fingerprint of the verifying key of a circuit built for `params`.
+/// It hashes the pinned verifying key, from which halo2 derives the transcript
+/// representation of the key: the domain, the constraint system and the
+/// commitments of the fixed columns and of the permutation. A proof made with
+/// another key is rejected by comparing fingerprints.
+pub fn vk_fingerprint<C: CurveAffine>(vk: &VerifyingKey<C>, params: &CircuitsParams) -> H256 {
+    H256(keccak256(format!("{:?} {:?}", vk.pinned(), params)))
+}
@@ -0,0 +1,200 @@
+//! Fast path of the witness generation for the `MockProver` tests, which
+//! spend most of their time recomputing the same random linear combinations
+//! and keccak digests.
+//!
+//! With the `fast-mock` feature, which is meant for the tests only:
+//! - [`rlc`] looks up `byte * r^i` in tables built once per challenge value with additions only,
+//!   which leaves a single multiplication per 32 bytes,
+//! - [`keccak256`] memoizes the digests in a map shared by all the threads, so that the blocks
+//!   replayed by several tests are hashed once. The map is cleared once its inputs exceed 64 MiB.
+//!
+//! Both return the same values as the plain computations, so the witness
+//! doesn't depend on the mode.
+
+use halo2_proofs::arithmetic::FieldExt;
+
+/// Random linear combination `values[0] + values[1] * r + ...` of `values`
+/// with the challenge `randomness`.
+pub(crate) fn rlc<F: FieldExt>(values: &[u8], randomness: F) -> F {
+    #[cfg(feature = "fast-mock")]
+    {
+        powers::rlc(values, randomness)
+    }
+    #[cfg(not(feature = "fast-mock"))]
+    {
+        values.iter().rev().fold(F::zero(), |acc, value| {
+            acc * randomness + F::from(*value as u64)
+        })
+    }
+}
+
+/// Keccak digest of `input`.
+pub(crate) fn keccak256(input: &[u8]) -> [u8; 32] {
+    #[cfg(feature = "fast-mock")]
+    {
+        digests::keccak256(input)
+    }
+    #[cfg(not(feature = "fast-mock"))]
+    {
+        ethers_core::utils::keccak256(input)
+    }
+}
+
+#[cfg(feature = "fast-mock")]
+mod powers {
+    use halo2_proofs::arithmetic::FieldExt;
+    use std::{any::Any, cell::RefCell};
+
+    /// Bytes combined with a lookup in each table, the tables of a longer
+    /// input would take more memory than they save multiplications.
+    const CHUNK_LEN: usize = 32;
+
+    /// Terms of the random linear combination of a chunk of bytes.
+    struct PowersTable<F> {
+        randomness: F,
+        /// `byte * randomness^i` of each byte, at each position `i` of a chunk
+        terms: Vec<[F; 256]>,
+        /// `randomness^CHUNK_LEN`, which shifts a chunk past the previous one
+        chunk_shift: F,
+    }
+
+    impl<F: FieldExt> PowersTable<F> {
+        fn new(randomness: F) -> Self {
+            let mut power = F::one();
+            let terms = (0..CHUNK_LEN)
+                .map(|_| {
+                    let mut row = [F::zero(); 256];
+                    for byte in 1..256 {
+                        row[byte] = row[byte - 1] + power;
+                    }
+                    power *= randomness;
+                    row
+                })
+                .collect();
+            Self {
+                randomness,
+                terms,
+                chunk_shift: power,
+            }
+        }
+
+        fn rlc(&self, values: &[u8]) -> F {
+            values
+                .chunks(CHUNK_LEN)
+                .rev()
+                .fold(F::zero(), |acc, chunk| {
+                    chunk
+                        .iter()
+                        .zip(self.terms.iter())
+                        .fold(acc * self.chunk_shift, |acc, (byte, row)| {
+                            acc + row[*byte as usize]
+                        })
+                })
+        }
+    }
+
+    thread_local! {
+        /// Table of the last challenge, which only changes between circuits.
+        /// The field isn't known here, so the table is type-erased.
+        static TABLE: RefCell<Option<Box<dyn Any>>> = RefCell::new(None);
+    }
+
+    pub(super) fn rlc<F: FieldExt>(values: &[u8], randomness: F) -> F {
+        TABLE.with(|table| {
+            let mut table = table.borrow_mut();
+            let is_stale = table
+                .as_ref()
+                .and_then(|table| table.downcast_ref::<PowersTable<F>>())
+                .map_or(true, |table| table.randomness != randomness);
+            if is_stale {
+                *table = Some(Box::new(PowersTable::new(randomness)));
+            }
+            table
+                .as_ref()
+                .and_then(|table| table.downcast_ref::<PowersTable<F>>())
+                .expect("the table of the challenge was just built")
+                .rlc(values)
+        })
+    }
+}
+
+#[cfg(feature = "fast-mock")]
+mod digests {
+    use once_cell::sync::Lazy;
+    use std::{collections::HashMap, sync::Mutex};
+
+    /// Bytes of the inputs kept before the cache is cleared, to bound its
+    /// memory when many different blocks are replayed.
+    const MAX_CACHED_BYTES: usize = 64 << 20;
+
+    #[derive(Default)]
+    struct Digests {
+        digests: HashMap<Vec<u8>, [u8; 32]>,
+        /// Sum of the lengths of the keys of `digests`
+        cached_bytes: usize,
+    }
+
+    static DIGESTS: Lazy<Mutex<Digests>> = Lazy::new(|| Mutex::new(Digests::default()));
+
+    pub(super) fn keccak256(input: &[u8]) -> [u8; 32] {
+        if let Some(digest) = DIGESTS
+            .lock()
+            .expect("the digests lock is never poisoned")
+            .digests
+            .get(input)
+        {
+            return *digest;
+        }
+        // hashed without the lock, so that threads hashing different inputs
+        // don't wait for each other
+        let digest = ethers_core::utils::keccak256(input);
+        if input.len() > MAX_CACHED_BYTES {
+            return digest;
+        }
+        let mut cache = DIGESTS.lock().expect("the digests lock is never poisoned");
+        if cache.cached_bytes + input.len() > MAX_CACHED_BYTES {
+            *cache = Digests::default();
+        }
+        if cache.digests.insert(input.to_vec(), digest).is_none() {
+            cache.cached_bytes += input.len();
+        }
+        digest
+    }
+}
+
+#[cfg(all(test, feature = "fast-mock"))]
+mod fast_mock_tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn rlc_matches_horner() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        for randomness in [
+            Fr::zero(),
+            Fr::one(),
+            Fr::from(0x100),
+            Fr::from(rng.gen::<u64>()),
+        ] {
+            for len in [0, 1, 31, 32, 33, 64, 100] {
+                let values: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+                let expected = values.iter().rev().fold(Fr::zero(), |acc, value| {
+                    acc * randomness + Fr::from(*value as u64)
+                });
+                assert_eq!(rlc(&values, randomness), expected, "len {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn cached_keccak_matches_digest() {
+        let inputs: [&[u8]; 3] = [b"", b"abc", &[0xff; 200]];
+        for input in inputs {
+            let expected = ethers_core::utils::keccak256(input);
+            assert_eq!(keccak256(input), expected);
+            assert_eq!(keccak256(input), expected);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Grand-product fingerprint of the rows of a rw table.
+//!
+//! The fingerprint of a list of rws is `prev ⋅ ∏ (α - rlc(row, γ))`, with `α`
+//! the permutation challenge and `γ` the lookup challenge. It doesn't depend
+//! on the order of the rows, so the EVM circuit, which reads the rws in
+//! chronological order, and the state circuit, which sorts them by key, reach
+//! the same fingerprint exactly when their tables hold the same rows.
+//!
+//! `prev` is the fingerprint of the rws of the previous chunks, so that the
+//! tables of consecutive chunks can be proven to be pieces of one global
+//! list of rws: the fingerprint at the end of a chunk is the `prev` of the
+//! next one.
+
+use crate::{
+    table::RwTable,
+    util::{Challenges, Expr},
+    witness::{Rw, RwMap, RwRow},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Cell, Region, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, Fixed},
+    poly::Rotation,
+};
+
+#[cfg(feature = "onephase")]
+use halo2_proofs::plonk::FirstPhase as ThirdPhase;
+#[cfg(not(feature = "onephase"))]
+use halo2_proofs::plonk::ThirdPhase;
+
+/// Fingerprint of the rows of a rw table prepadded to `n_rows` like
+/// [`RwMap::table_assignments_prepad`], following the fingerprint `prev` of
+/// the previous chunks.
+pub fn rw_fingerprint<F: Field>(
+    rows: &[Rw],
+    n_rows: usize,
+    prev: F,
+    challenges: &Challenges<F>,
+) -> F {
+    let (rows, _) = RwMap::table_assignments_prepad(rows, n_rows);
+    rows.iter().fold(prev, |acc, row| {
+        acc * (challenges.permutation()
+            - row
+                .table_assignment_aux(challenges.evm_word())
+                .rlc(challenges.lookup_input()))
+    })
+}
+
+/// Cells of the fingerprints at the start and at the end of a rw table.
+#[derive(Clone, Debug)]
+pub struct RwFingerprints<V> {
+    /// Fingerprint of the rws of the previous chunks
+    pub prev: (Cell, Value<V>),
+    /// Fingerprint including the rows of the table
+    pub next: (Cell, Value<V>),
+}
+
+/// Config of the running product computing the fingerprint of a rw table.
+#[derive(Clone, Copy, Debug)]
+pub struct RwFingerprintConfig {
+    q_first: Column<Fixed>,
+    q_enable: Column<Fixed>,
+    prev: Column<Advice>,
+    acc: Column<Advice>,
+}
+
+impl RwFingerprintConfig {
+    /// Configure the fingerprint of the rows of `rw_table` enabled by its
+    /// `q_enable`, starting at its first row.
+    pub fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        rw_table: &RwTable,
+        challenges: &Challenges<Expression<F>>,
+    ) -> Self {
+        let config = Self {
+            q_first: meta.fixed_column(),
+            q_enable: rw_table.q_enable,
+            prev: meta.advice_column_in(ThirdPhase),
+            acc: meta.advice_column_in(ThirdPhase),
+        };
+        meta.enable_equality(config.prev);
+        meta.enable_equality(config.acc);
+
+        meta.create_gate("rw fingerprint", |meta| {
+            let q_first = meta.query_fixed(config.q_first, Rotation::cur());
+            let q_enable = meta.query_fixed(config.q_enable, Rotation::cur());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+            let acc_prev = meta.query_advice(config.acc, Rotation::prev());
+            let prev = meta.query_advice(config.prev, Rotation::cur());
+
+            // Same compression as `RwRow::rlc`.
+            let row = [
+                rw_table.rw_counter,
+                rw_table.is_write,
+                rw_table.tag,
+                rw_table.id,
+                rw_table.address,
+                rw_table.field_tag,
+                rw_table.storage_key,
+                rw_table.value,
+                rw_table.value_prev,
+                rw_table.aux1,
+                rw_table.aux2,
+            ]
+            .iter()
+            .rev()
+            .fold(0.expr(), |acc, column| {
+                acc * challenges.lookup_input() + meta.query_advice(*column, Rotation::cur())
+            }) * challenges.lookup_input()
+                + 1.expr();
+            let factor = challenges.permutation() - row;
+
+            vec![
+                q_first.clone() * (acc.clone() - prev * factor.clone()),
+                (q_enable - q_first) * (acc - acc_prev * factor),
+            ]
+        });
+
+        config
+    }
+
+    /// Assign the running product over the rows of the rw table, assigned
+    /// from the offset 0 of `region`.
+    pub fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        rows: &[RwRow<Value<F>>],
+        prev: Value<F>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<RwFingerprints<Assigned<F>>, Error> {
+        region.name_column(|| "RW_FINGERPRINT_prev", self.prev);
+        region.name_column(|| "RW_FINGERPRINT_acc", self.acc);
+
+        region.assign_fixed(
+            || "rw fingerprint q_first",
+            self.q_first,
+            0,
+            || Value::known(F::one()),
+        )?;
+        let prev = region.assign_advice(|| "rw fingerprint prev", self.prev, 0, || prev)?;
+
+        let gamma = challenges.lookup_input();
+        let mut acc = prev.value().copied();
+        let mut next = None;
+        for (offset, row) in rows.iter().enumerate() {
+            let row_rlc = [
+                row.rw_counter,
+                row.is_write,
+                row.tag,
+                row.id,
+                row.address,
+                row.field_tag,
+                row.storage_key,
+                row.value,
+                row.value_prev,
+                row.aux1,
+                row.aux2,
+            ]
+            .iter()
+            .rev()
+            .fold(Value::known(F::zero()), |acc, value| acc * gamma + value)
+                * gamma
+                + Value::known(F::one());
+            acc = acc * (challenges.permutation() - row_rlc);
+            next = Some(region.assign_advice(|| "rw fingerprint acc", self.acc, offset, || acc)?);
+        }
+        let next: AssignedCell<F, F> = next.expect("rw table has at least one padding row");
+
+        Ok(RwFingerprints {
+            prev: (prev.cell(), prev.value_field()),
+            next: (next.cell(), next.value_field()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod rw_fingerprint_tests {
+    use super::*;
+    use crate::witness::block_convert;
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    #[test]
+    fn rw_fingerprint_is_independent_of_order() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            SSTORE
+            PUSH1(0x02)
+            SLOAD
+            PUSH1(0x00)
+            MSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+        let challenges = Challenges::mock(Fr::from(0x100), Fr::from(0x200), Fr::from(0x300));
+        let n_rows = block.circuits_params.max_rws;
+        let sorted = rw_fingerprint(block.sorted_rws(), n_rows, Fr::one(), &challenges);
+        let chronological = block.rws.table_assignments_chronological();
+        assert_eq!(
+            rw_fingerprint(&chronological, n_rows, Fr::one(), &challenges),
+            sorted
+        );
+
+        let mut tampered = chronological;
+        match tampered
+            .iter_mut()
+            .find(|rw| matches!(rw, Rw::Stack { .. }))
+            .unwrap()
+        {
+            Rw::Stack { value, .. } => *value += Word::one(),
+            _ => unreachable!(),
+        }
+        assert_ne!(
+            rw_fingerprint(&tampered, n_rows, Fr::one(), &challenges),
+            sorted
+        );
+    }
+}
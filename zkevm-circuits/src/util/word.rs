@@ -0,0 +1,95 @@
+//! Encodings of the EVM words in the field, shared by the witness conversion
+//! and the circuits so that a word is assigned the same way wherever it is
+//! looked up:
+//! - as its low and high 128 bits, with [`WordLoHi`],
+//! - as its 64-bit limbs, with [`limbs64`],
+//! - as the random linear combination of its little-endian bytes, with [`rlc`].
+
+use super::fast_mock;
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::{arithmetic::FieldExt, circuit::Value};
+
+/// Word split into its low and high 128 bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WordLoHi<T> {
+    /// Low 128 bits
+    pub lo: T,
+    /// High 128 bits
+    pub hi: T,
+}
+
+impl<T> WordLoHi<T> {
+    /// Word of the halves `lo` and `hi`.
+    pub fn new(lo: T, hi: T) -> Self {
+        Self { lo, hi }
+    }
+
+    /// Apply `f` to both halves.
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> WordLoHi<U> {
+        WordLoHi::new(f(self.lo), f(self.hi))
+    }
+
+    /// Low and high halves.
+    pub fn to_lo_hi(self) -> (T, T) {
+        (self.lo, self.hi)
+    }
+}
+
+impl From<Word> for WordLoHi<Word> {
+    fn from(word: Word) -> Self {
+        Self::new(
+            Word([word.0[0], word.0[1], 0, 0]),
+            Word([word.0[2], word.0[3], 0, 0]),
+        )
+    }
+}
+
+impl<F: Field> WordLoHi<F> {
+    /// Halves of `word` as field elements.
+    pub fn from_word(word: Word) -> Self {
+        WordLoHi::<Word>::from(word).map(|half| F::from_u128(half.as_u128()))
+    }
+}
+
+/// 64-bit limbs of `word`, from the least significant.
+pub fn limbs64(word: Word) -> [Word; 4] {
+    word.0.map(|limb| Word([limb, 0, 0, 0]))
+}
+
+/// Random linear combination of the little-endian bytes of `word` with
+/// `randomness`.
+pub fn rlc<F: FieldExt>(word: &Word, randomness: F) -> F {
+    fast_mock::rlc(&word.to_le_bytes(), randomness)
+}
+
+/// [`rlc`] of `word` with a challenge, which is unknown at keygen.
+pub fn rlc_value<F: FieldExt>(word: &Word, challenge: Value<F>) -> Value<F> {
+    challenge.map(|randomness| rlc(word, randomness))
+}
+
+#[cfg(test)]
+mod word_tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn word_lo_hi_matches_limbs() {
+        let word = Word::from_big_endian(&(1..=32).collect::<Vec<u8>>());
+        let limbs = limbs64(word);
+        let WordLoHi { lo, hi } = WordLoHi::<Word>::from(word);
+        assert_eq!(lo, limbs[0] + (limbs[1] << 64));
+        assert_eq!(hi, limbs[2] + (limbs[3] << 64));
+        assert_eq!(lo + (hi << 128), word);
+        assert_eq!(
+            WordLoHi::<Fr>::from_word(word),
+            WordLoHi::new(Fr::from_u128(lo.as_u128()), Fr::from_u128(hi.as_u128()))
+        );
+    }
+
+    #[test]
+    fn rlc_of_word_is_little_endian() {
+        let word = Word::from(0x0102u64);
+        assert_eq!(rlc(&word, Fr::from(0x100)), Fr::from(0x0102));
+        assert_eq!(rlc(&word, Fr::from(10)), Fr::from(2 + 10));
+    }
+}
@@ -18,7 +18,7 @@ mod mpt;
 pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates};
 
 mod receipt;
-pub use receipt::Receipt;
+pub use receipt::{LogItem, Receipt};
 
 pub(crate) mod rlp_fsm;
 pub use rlp_fsm::{
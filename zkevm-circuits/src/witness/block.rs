@@ -4,17 +4,21 @@ use std::collections::BTreeMap;
 #[cfg(any(feature = "test", test))]
 use crate::evm_circuit::{detect_fixed_table_tags, EvmCircuit};
 
-use crate::{evm_circuit::util::rlc, table::BlockContextFieldTag, util::SubCircuit};
+use crate::{
+    table::BlockContextFieldTag,
+    util::{word, SubCircuit},
+};
 use bus_mapping::{
-    circuit_input_builder::{self, CircuitsParams, CopyEvent, ExpEvent},
+    circuit_input_builder::{self, CircuitsParams, CopyEvent, ExpEvent, PrecompileEvent},
+    precompile::P256VerifyInput,
     Error,
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
+use eth_types::{sign_types::SignData, Address, Field, ToScalar, Word, U256};
 use halo2_proofs::circuit::Value;
 
 use super::{
     mpt::ZktrieState as MptState, step::step_convert, tx::tx_convert, Bytecode, ExecStep,
-    MptUpdates, RwMap, Transaction,
+    MptUpdates, Rw, RwMap, Transaction,
 };
 use crate::util::{Challenges, DEFAULT_RAND};
 
@@ -41,8 +45,12 @@ pub struct Block<F> {
     pub end_block_not_last: ExecStep,
     /// Last EndBlock step that appears in the last EVM row.
     pub end_block_last: ExecStep,
-    /// Read write events in the RwTable
+    /// Read write events in the RwTable. Edit them with
+    /// [`Block::update_rws`], which keeps their sorted copy in sync.
     pub rws: RwMap,
+    /// Read write events of `rws` sorted in RwTable order, computed once
+    /// when the block is converted
+    sorted_rws: Vec<Rw>,
     /// Bytecode used in the block
     pub bytecodes: BTreeMap<Word, Bytecode>,
     /// The block context
@@ -51,6 +59,8 @@ pub struct Block<F> {
     pub copy_events: Vec<CopyEvent>,
     /// Exponentiation traces for the exponentiation circuit's table.
     pub exp_events: Vec<ExpEvent>,
+    /// Events of the precompile calls verified outside of the EVM circuit.
+    pub precompile_events: Vec<PrecompileEvent>,
     /// Pad exponentiation circuit to make selectors fixed.
     pub exp_circuit_pad_to: usize,
     /// Circuit Setup Parameters
@@ -69,6 +79,9 @@ pub struct Block<F> {
     pub mpt_updates: MptUpdates,
     /// Chain ID
     pub chain_id: Word,
+    /// Timestamp from which the blocks are on Prague, `None` if the chain
+    /// isn't on Prague
+    pub prague_time: Option<u64>,
 }
 
 /// ...
@@ -94,6 +107,39 @@ impl BlockContexts {
 }
 
 impl<F: Field> Block<F> {
+    /// Read write events of the block sorted in RwTable order.
+    pub fn sorted_rws(&self) -> &[Rw] {
+        &self.sorted_rws
+    }
+
+    /// Edit the read write events of the block, and sort them again.
+    pub fn update_rws(&mut self, update: impl FnOnce(&mut RwMap)) {
+        update(&mut self.rws);
+        self.sorted_rws = self.rws.table_assignments();
+    }
+
+    /// Signatures recovered by the ECRecover calls of the block.
+    pub fn ecrecover_sign_datas(&self) -> Vec<SignData> {
+        self.precompile_events
+            .iter()
+            .filter_map(|event| match event {
+                PrecompileEvent::Ecrecover(sign_data) => Some(sign_data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Inputs of the P256VERIFY calls of the block with a valid signature.
+    pub fn p256_verify_inputs(&self) -> Vec<P256VerifyInput> {
+        self.precompile_events
+            .iter()
+            .filter_map(|event| match event {
+                PrecompileEvent::P256Verify(input) => Some(input.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// For each tx, for each step, print the rwc at the beginning of the step,
     /// and all the rw operations of the step.
     pub(crate) fn debug_print_txs_steps_rw_ops(&self) {
@@ -111,7 +157,7 @@ impl<F: Field> Block<F> {
 
 #[cfg(feature = "test")]
 use crate::exp_circuit::param::OFFSET_INCREMENT;
-use crate::tx_circuit::TX_LEN;
+use crate::tx_circuit::{max_rlp_bytes, TX_LEN};
 #[cfg(feature = "test")]
 use crate::util::log2_ceil;
 
@@ -134,10 +180,14 @@ impl<F: Field> Block<F> {
             .map(|bytecode| bytecode.bytes.len() + 1)
             .sum();
         let num_rows_required_for_copy_table: usize =
-            self.copy_events.iter().map(|c| c.bytes.len() * 2).sum();
+            self.copy_events.iter().map(CopyEvent::num_rows).sum();
         let num_rows_required_for_keccak_table: usize = self.keccak_inputs.len();
-        let num_rows_required_for_tx_table: usize =
-            TX_LEN * self.circuits_params.max_txs + self.circuits_params.max_calldata;
+        let num_rows_required_for_tx_table: usize = TX_LEN * self.circuits_params.max_txs
+            + self.circuits_params.max_calldata
+            + max_rlp_bytes(
+                self.circuits_params.max_txs,
+                self.circuits_params.max_calldata,
+            );
         let num_rows_required_for_exp_table: usize = self
             .exp_events
             .iter()
@@ -188,6 +238,8 @@ pub struct BlockContext {
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
+    /// The blob base fee, the price of a unit of blob gas
+    pub blob_base_fee: Word,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
     /// The chain id
@@ -226,7 +278,7 @@ impl BlockContext {
                 [
                     Value::known(F::from(BlockContextFieldTag::Difficulty as u64)),
                     Value::known(current_block_number),
-                    randomness.map(|rand| rlc::value(&self.difficulty.to_le_bytes(), rand)),
+                    randomness.map(|rand| word::rlc(&self.difficulty, rand)),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::GasLimit as u64)),
@@ -236,13 +288,12 @@ impl BlockContext {
                 [
                     Value::known(F::from(BlockContextFieldTag::BaseFee as u64)),
                     Value::known(current_block_number),
-                    randomness
-                        .map(|randomness| rlc::value(&self.base_fee.to_le_bytes(), randomness)),
+                    randomness.map(|randomness| word::rlc(&self.base_fee, randomness)),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::ChainId as u64)),
                     Value::known(current_block_number),
-                    randomness.map(|rand| rlc::value(&self.chain_id.to_le_bytes(), rand)),
+                    randomness.map(|rand| word::rlc(&self.chain_id, rand)),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::NumTxs as u64)),
@@ -254,6 +305,12 @@ impl BlockContext {
                     Value::known(current_block_number),
                     Value::known(F::from(cum_num_txs as u64)),
                 ],
+                #[cfg(feature = "cancun")]
+                [
+                    Value::known(F::from(BlockContextFieldTag::BlobBaseFee as u64)),
+                    Value::known(current_block_number),
+                    randomness.map(|rand| word::rlc(&self.blob_base_fee, rand)),
+                ],
             ],
             self.block_hash_assignments(randomness),
         ]
@@ -285,7 +342,7 @@ impl BlockContext {
                 [
                     Value::known(F::from(BlockContextFieldTag::BlockHash as u64)),
                     Value::known(F::from(block_number)),
-                    randomness.map(|randomness| rlc::value(&hash.to_le_bytes(), randomness)),
+                    randomness.map(|randomness| word::rlc(&hash, randomness)),
                 ]
             })
             .collect()
@@ -308,6 +365,7 @@ impl From<&circuit_input_builder::Block> for BlockContexts {
                             timestamp: block.timestamp,
                             difficulty: block.difficulty,
                             base_fee: block.base_fee,
+                            blob_base_fee: block.blob_base_fee,
                             history_hashes: block.history_hashes.clone(),
                             chain_id: block.chain_id,
                             eth_block: block.eth_block.clone(),
@@ -320,13 +378,30 @@ impl From<&circuit_input_builder::Block> for BlockContexts {
 }
 
 /// Convert a block struct in bus-mapping to a witness block used in circuits
+#[tracing::instrument(skip_all, fields(txs = block.txs().len()))]
 pub fn block_convert<F: Field>(
     block: &circuit_input_builder::Block,
     code_db: &bus_mapping::state_db::CodeDB,
 ) -> Result<Block<F>, Error> {
+    // calls to the precompiles registered in the stub mode are traced but
+    // have no constraint
+    if let Some(address) = block.stub_precompile_calls.first() {
+        return Err(Error::UnsupportedPrecompile(*address));
+    }
+    // the fields of the authorizations of a set-code tx are in the fixed rows
+    // of the tx in the tx table
+    #[cfg(feature = "prague")]
+    if let Some(tx) = block
+        .txs()
+        .iter()
+        .find(|tx| tx.authorization_list.len() > eth_types::eip7702::MAX_AUTHORIZATIONS_PER_TX)
+    {
+        return Err(Error::TooManyAuthorizations(tx.authorization_list.len()));
+    }
     let rws = RwMap::from(&block.container);
+    let sorted_rws = rws.table_assignments_from_container(&block.container);
     #[cfg(debug_assertions)]
-    rws.check_value();
+    RwMap::check_value_sorted(&sorted_rws);
     let num_txs = block.txs().len();
     let last_block_num = block
         .headers
@@ -350,7 +425,7 @@ pub fn block_convert<F: Field>(
         block.circuits_params.max_rws
     };
     let mpt_updates = MptUpdates::from_rws_with_mock_state_roots(
-        &rws.table_assignments(),
+        &sorted_rws,
         block.prev_state_root,
         block.end_state_root(),
     );
@@ -389,6 +464,7 @@ pub fn block_convert<F: Field>(
         randomness: F::from_u128(DEFAULT_RAND),
         context: block.into(),
         rws,
+        sorted_rws,
         txs: block
             .txs()
             .iter()
@@ -399,7 +475,14 @@ pub fn block_convert<F: Field>(
                 } else {
                     last_block_num + 1
                 };
-                tx_convert(tx, idx + 1, chain_id.as_u64(), next_block_num)
+                let block_timestamp = block.headers[&tx.block_num].timestamp.as_u64();
+                tx_convert(
+                    tx,
+                    idx + 1,
+                    chain_id.as_u64(),
+                    block_timestamp,
+                    next_block_num,
+                )
             })
             .collect(),
         sigs: block.txs().iter().map(|tx| tx.signature).collect(),
@@ -421,6 +504,7 @@ pub fn block_convert<F: Field>(
             .collect(),
         copy_events: block.copy_events.clone(),
         exp_events: block.exp_events.clone(),
+        precompile_events: block.precompile_events.clone(),
         sha3_inputs: block.sha3_inputs.clone(),
         circuits_params: CircuitsParams {
             max_rws,
@@ -433,6 +517,7 @@ pub fn block_convert<F: Field>(
         keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
         mpt_updates,
         chain_id,
+        prague_time: block.prague_time,
     })
 }
 
@@ -1,8 +1,11 @@
 use bus_mapping::evm::OpcodeId;
-use eth_types::{Field, ToLittleEndian, Word};
+use eth_types::{Field, Word};
 use halo2_proofs::circuit::Value;
 
-use crate::{evm_circuit::util::rlc, table::BytecodeFieldTag, util::Challenges};
+use crate::{
+    table::BytecodeFieldTag,
+    util::{word, Challenges},
+};
 
 /// Bytecode
 #[derive(Clone, Debug)]
@@ -24,12 +27,12 @@ impl Bytecode {
         let hash = if cfg!(feature = "poseidon-codehash") {
             challenges
                 .evm_word()
-                .map(|_challenge| rlc::value(&self.hash.to_le_bytes(), F::from(256u64)))
-            //Value::known(rlc::value(&self.hash.to_le_bytes(), F::from(256u64)))
+                .map(|_challenge| word::rlc(&self.hash, F::from(256u64)))
+            //Value::known(word::rlc(&self.hash, F::from(256u64)))
         } else {
             challenges
                 .evm_word()
-                .map(|challenge| rlc::value(&self.hash.to_le_bytes(), challenge))
+                .map(|challenge| word::rlc(&self.hash, challenge))
         };
 
         rows.push([
@@ -1,8 +1,9 @@
 use crate::{
-    evm_circuit::{util::rlc, witness::Rw},
+    evm_circuit::witness::Rw,
     table::{AccountFieldTag, MPTProofType as ProofType},
+    util::word,
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
+use eth_types::{Address, Field, ToScalar, Word, U256};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
 use mpt_zktrie::{serde::SMTTrace, state, state::witness::WitnessGenerator, MPTProofType};
@@ -211,7 +212,7 @@ impl MptUpdate {
                 if cfg!(feature = "poseidon-codehash") {
                     x.to_scalar().unwrap()
                 } else {
-                    rlc::value(&x.to_le_bytes(), word_randomness)
+                    word::rlc(&x, word_randomness)
                 }
             }
             Key::Account {
@@ -219,7 +220,7 @@ impl MptUpdate {
                     AccountFieldTag::Nonce | AccountFieldTag::NonExisting | AccountFieldTag::CodeSize,
                 ..
             } => x.to_scalar().unwrap(),
-            _ => rlc::value(&x.to_le_bytes(), word_randomness),
+            _ => word::rlc(&x, word_randomness),
         };
 
         (assign(self.new_value), assign(self.old_value))
@@ -227,8 +228,8 @@ impl MptUpdate {
 
     pub(crate) fn root_assignments<F: Field>(&self, word_randomness: F) -> (F, F) {
         (
-            rlc::value(&self.new_root.to_le_bytes(), word_randomness),
-            rlc::value(&self.old_root.to_le_bytes(), word_randomness),
+            word::rlc(&self.new_root, word_randomness),
+            word::rlc(&self.old_root, word_randomness),
         )
     }
 }
@@ -290,9 +291,7 @@ impl Key {
     fn storage_key<F: Field>(&self, randomness: F) -> F {
         match self {
             Self::Account { .. } => F::zero(),
-            Self::AccountStorage { storage_key, .. } => {
-                rlc::value(&storage_key.to_le_bytes(), randomness)
-            }
+            Self::AccountStorage { storage_key, .. } => word::rlc(&storage_key, randomness),
         }
     }
 }
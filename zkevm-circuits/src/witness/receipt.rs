@@ -1,10 +1,17 @@
+use crate::{
+    table::{ReceiptFieldTag, TxLogFieldTag},
+    util::{rlc_be_bytes, Challenges},
+};
+use bus_mapping::circuit_input_builder::TxReceipt;
+use eth_types::Field;
 use ethers_core::{
-    types::{Bloom, Log},
+    types::{Bloom, Log, H256},
     utils::rlp::{Encodable, RlpStream},
 };
+use halo2_proofs::circuit::Value;
 
 /// EVM log's receipt.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Receipt {
     /// Denotes the ID of the tx.
     pub id: usize,
@@ -12,10 +19,127 @@ pub struct Receipt {
     pub status: u8,
     /// Denotes the cumulative gas used by the tx execution.
     pub cumulative_gas_used: u64,
-    /// Represents the 256-bytes bloom filter.
+    /// Represents the 256-bytes bloom filter of the logs, bound by the PI
+    /// circuit to the addresses and topics of the logs.
     pub bloom: Bloom,
     /// List of logs generated by the tx.
     pub logs: Vec<Log>,
+    /// Hash of the data the tx reverted with, zero when it didn't end with a
+    /// REVERT. It is not part of the receipt in the receipts trie.
+    pub revert_data_hash: H256,
+}
+
+impl Receipt {
+    /// Receipt of the tx `id` collected by bus-mapping, with the bloom filter
+    /// of its logs.
+    pub fn new(id: usize, receipt: &TxReceipt) -> Self {
+        let logs: Vec<Log> = receipt
+            .logs
+            .iter()
+            .map(|log| Log {
+                address: log.address,
+                topics: log.topics.clone(),
+                data: log.data.clone().into(),
+                ..Default::default()
+            })
+            .collect();
+        Self {
+            id,
+            status: receipt.status as u8,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            bloom: receipt.logs_bloom(),
+            logs,
+            revert_data_hash: receipt.revert_data_hash,
+        }
+    }
+
+    /// Addresses and topics of the logs, in the order of their tx log rws.
+    pub fn log_items(&self) -> Vec<LogItem> {
+        self.logs
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, log)| {
+                let address = LogItem {
+                    log_id: idx + 1,
+                    field_tag: TxLogFieldTag::Address,
+                    index: 0,
+                    bytes: log.address.as_bytes().to_vec(),
+                };
+                let topics = log
+                    .topics
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, topic)| LogItem {
+                        log_id: idx + 1,
+                        field_tag: TxLogFieldTag::Topic,
+                        index,
+                        bytes: topic.as_bytes().to_vec(),
+                    });
+                std::iter::once(address).chain(topics)
+            })
+            .collect()
+    }
+
+    /// Assignments for the receipt table, one row per field of
+    /// [`ReceiptFieldTag`] as `[tx_id, tag, value]`.
+    pub fn table_assignments<F: Field>(
+        &self,
+        challenges: Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 3]> {
+        [
+            (
+                ReceiptFieldTag::Status,
+                Value::known(F::from(self.status as u64)),
+            ),
+            (
+                ReceiptFieldTag::CumulativeGasUsed,
+                Value::known(F::from(self.cumulative_gas_used)),
+            ),
+            (
+                ReceiptFieldTag::LogLength,
+                Value::known(F::from(self.logs.len() as u64)),
+            ),
+            (
+                ReceiptFieldTag::RevertDataHash,
+                rlc_be_bytes(self.revert_data_hash.as_bytes(), challenges.evm_word()),
+            ),
+        ]
+        .map(|(tag, value)| {
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(tag as u64)),
+                value,
+            ]
+        })
+        .to_vec()
+    }
+}
+
+/// Address or topic of a log, which sets 3 bits of the logs bloom of its
+/// block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogItem {
+    /// Id of the log in its tx, starting at 1
+    pub log_id: usize,
+    /// Whether the item is the address or a topic of the log
+    pub field_tag: TxLogFieldTag,
+    /// Index of the topic, 0 for the address
+    pub index: usize,
+    /// Big-endian bytes of the address or topic
+    pub bytes: Vec<u8>,
+}
+
+impl LogItem {
+    /// Value of the item in the tx log rw: the address, or the RLC of the
+    /// topic with the `evm_word` challenge.
+    pub fn value<F: Field>(&self, evm_word: Value<F>) -> Value<F> {
+        match self.field_tag {
+            TxLogFieldTag::Topic => rlc_be_bytes(&self.bytes, evm_word),
+            _ => Value::known(self.bytes.iter().fold(F::zero(), |acc, byte| {
+                acc * F::from(256) + F::from(*byte as u64)
+            })),
+        }
+    }
 }
 
 impl Encodable for Receipt {
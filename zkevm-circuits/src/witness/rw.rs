@@ -1,15 +1,17 @@
 #![allow(missing_docs)]
 use std::collections::HashMap;
 
-use bus_mapping::operation::{self, AccountField, CallContextField, TxLogField, TxReceiptField};
-use eth_types::{Address, Field, ToAddress, ToLittleEndian, ToScalar, Word, U256};
+use bus_mapping::{
+    exec_trace::OperationRef,
+    operation::{self, AccountField, CallContextField, TxLogField, TxReceiptField},
+};
+use eth_types::{Address, Field, ToAddress, ToScalar, Word, U256};
 use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
 use itertools::Itertools;
 
 use crate::{
-    evm_circuit::util::rlc,
     table::{AccountFieldTag, CallContextFieldTag, RwTableTag, TxLogFieldTag, TxReceiptFieldTag},
-    util::build_tx_log_address,
+    util::{build_tx_log_address, word},
 };
 
 use super::MptUpdates;
@@ -42,12 +44,15 @@ impl RwMap {
     }
     /// Check value in the same way like StateCircuit
     pub fn check_value(&self) {
+        Self::check_value_sorted(&self.table_assignments());
+    }
+    /// Check value of already sorted `rows` in the same way like StateCircuit
+    pub fn check_value_sorted(rows: &[Rw]) {
         let mock_rand = Fr::from(0x1000u64);
         let err_msg_first = "first access reads don't change value";
         let err_msg_non_first = "non-first access reads don't change value";
-        let rows = self.table_assignments();
         let updates = MptUpdates::from_rws_with_mock_state_roots(
-            &rows,
+            rows,
             0xcafeu64.into(),
             0xdeadbeefu64.into(),
         );
@@ -125,18 +130,57 @@ impl RwMap {
     /// Build Rws for assignment
     pub fn table_assignments(&self) -> Vec<Rw> {
         let mut rows: Vec<Rw> = self.0.values().flatten().cloned().collect();
-        rows.sort_by_key(|row| {
-            (
-                row.tag() as u64,
-                row.id().unwrap_or_default(),
-                row.address().unwrap_or_default(),
-                row.field_tag().unwrap_or_default(),
-                row.storage_key().unwrap_or_default(),
-                row.rw_counter(),
-            )
-        });
+        rows.sort_by_key(Rw::sort_key);
+        rows
+    }
+    /// Build Rws in the order of their rw counter, the order in which the EVM
+    /// circuit reads them, without the Start rows.
+    pub fn table_assignments_chronological(&self) -> Vec<Rw> {
+        let mut rows: Vec<Rw> = self
+            .0
+            .values()
+            .flatten()
+            .filter(|rw| !matches!(rw, Rw::Start { .. }))
+            .cloned()
+            .collect();
+        rows.sort_by_key(Rw::rw_counter);
         rows
     }
+    /// Build Rws for assignment from the `container` this map was converted
+    /// from.  Only the container's operation references get sorted, and the
+    /// result is meant to be computed once per block and shared by the state
+    /// circuit and the mpt witness generation.
+    pub fn table_assignments_from_container(
+        &self,
+        container: &operation::OperationContainer,
+    ) -> Vec<Rw> {
+        let rw_of =
+            |op_ref: &OperationRef| self[(RwTableTag::from(op_ref.target()), op_ref.as_usize())];
+        container
+            .sorted_refs_by_key(|op_ref| rw_of(op_ref).sort_key())
+            .iter()
+            .map(rw_of)
+            .collect()
+    }
+}
+
+impl From<operation::Target> for RwTableTag {
+    fn from(target: operation::Target) -> Self {
+        match target {
+            operation::Target::Memory => RwTableTag::Memory,
+            operation::Target::Stack => RwTableTag::Stack,
+            operation::Target::Storage => RwTableTag::AccountStorage,
+            operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
+            operation::Target::TxAccessListAccountStorage => RwTableTag::TxAccessListAccountStorage,
+            operation::Target::TxRefund => RwTableTag::TxRefund,
+            operation::Target::Account => RwTableTag::Account,
+            operation::Target::CallContext => RwTableTag::CallContext,
+            operation::Target::TxReceipt => RwTableTag::TxReceipt,
+            operation::Target::TxLog => RwTableTag::TxLog,
+            operation::Target::TransientStorage => RwTableTag::TransientStorage,
+            operation::Target::Start => RwTableTag::Start,
+        }
+    }
 }
 
 /// Read-write records in execution. Rws are used for connecting evm circuit and
@@ -238,6 +282,16 @@ pub enum Rw {
         field_tag: TxReceiptFieldTag,
         value: u64,
     },
+    /// TransientStorage
+    TransientStorage {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        storage_key: Word,
+        value: Word,
+        value_prev: Word,
+    },
 }
 
 /// Rw table row assignment
@@ -372,6 +426,15 @@ impl Rw {
         }
     }
 
+    pub fn transient_storage_value_pair(&self) -> (Word, Word) {
+        match self {
+            Self::TransientStorage {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     pub fn call_context_value(&self) -> Word {
         match self {
             Self::CallContext { value, .. } => *value,
@@ -417,10 +480,7 @@ impl Rw {
             id: F::from(self.id().unwrap_or_default() as u64),
             address: self.address().unwrap_or_default().to_scalar().unwrap(),
             field_tag: F::from(self.field_tag().unwrap_or_default()),
-            storage_key: rlc::value(
-                &self.storage_key().unwrap_or_default().to_le_bytes(),
-                randomness,
-            ),
+            storage_key: word::rlc(&self.storage_key().unwrap_or_default(), randomness),
             value: self.value_assignment(randomness),
             value_prev: self.value_prev_assignment(randomness).unwrap_or_default(),
             aux1: F::zero(), // only used for AccountStorage::tx_id, which moved to key1.
@@ -438,12 +498,8 @@ impl Rw {
             id: Value::known(F::from(self.id().unwrap_or_default() as u64)),
             address: Value::known(self.address().unwrap_or_default().to_scalar().unwrap()),
             field_tag: Value::known(F::from(self.field_tag().unwrap_or_default())),
-            storage_key: randomness.map(|randomness| {
-                rlc::value(
-                    &self.storage_key().unwrap_or_default().to_le_bytes(),
-                    randomness,
-                )
-            }),
+            storage_key: randomness
+                .map(|randomness| word::rlc(&self.storage_key().unwrap_or_default(), randomness)),
             value: randomness.map(|randomness| self.value_assignment(randomness)),
             value_prev: randomness
                 .map(|randomness| self.value_prev_assignment(randomness).unwrap_or_default()),
@@ -468,7 +524,8 @@ impl Rw {
             | Self::Account { rw_counter, .. }
             | Self::CallContext { rw_counter, .. }
             | Self::TxLog { rw_counter, .. }
-            | Self::TxReceipt { rw_counter, .. } => *rw_counter,
+            | Self::TxReceipt { rw_counter, .. }
+            | Self::TransientStorage { rw_counter, .. } => *rw_counter,
         }
     }
 
@@ -484,7 +541,8 @@ impl Rw {
             | Self::Account { is_write, .. }
             | Self::CallContext { is_write, .. }
             | Self::TxLog { is_write, .. }
-            | Self::TxReceipt { is_write, .. } => *is_write,
+            | Self::TxReceipt { is_write, .. }
+            | Self::TransientStorage { is_write, .. } => *is_write,
         }
     }
 
@@ -501,9 +559,23 @@ impl Rw {
             Self::CallContext { .. } => RwTableTag::CallContext,
             Self::TxLog { .. } => RwTableTag::TxLog,
             Self::TxReceipt { .. } => RwTableTag::TxReceipt,
+            Self::TransientStorage { .. } => RwTableTag::TransientStorage,
         }
     }
 
+    /// The key the RwTable rows are sorted by: (tag, id, address, field_tag,
+    /// storage_key, rw_counter).
+    pub fn sort_key(&self) -> (u64, usize, Address, u64, Word, usize) {
+        (
+            self.tag() as u64,
+            self.id().unwrap_or_default(),
+            self.address().unwrap_or_default(),
+            self.field_tag().unwrap_or_default(),
+            self.storage_key().unwrap_or_default(),
+            self.rw_counter(),
+        )
+    }
+
     pub fn id(&self) -> Option<usize> {
         match self {
             Self::AccountStorage { tx_id, .. }
@@ -511,7 +583,8 @@ impl Rw {
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
-            | Self::TxReceipt { tx_id, .. } => Some(*tx_id),
+            | Self::TxReceipt { tx_id, .. }
+            | Self::TransientStorage { tx_id, .. } => Some(*tx_id),
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
@@ -532,6 +605,9 @@ impl Rw {
             }
             | Self::AccountStorage {
                 account_address, ..
+            }
+            | Self::TransientStorage {
+                account_address, ..
             } => Some(*account_address),
             Self::Memory { memory_address, .. } => Some(U256::from(*memory_address).to_address()),
             Self::Stack { stack_pointer, .. } => {
@@ -565,14 +641,16 @@ impl Rw {
             | Self::TxAccessListAccount { .. }
             | Self::TxAccessListAccountStorage { .. }
             | Self::TxRefund { .. }
-            | Self::TxLog { .. } => None,
+            | Self::TxLog { .. }
+            | Self::TransientStorage { .. } => None,
         }
     }
 
     pub fn storage_key(&self) -> Option<Word> {
         match self {
             Self::AccountStorage { storage_key, .. }
-            | Self::TxAccessListAccountStorage { storage_key, .. } => Some(*storage_key),
+            | Self::TxAccessListAccountStorage { storage_key, .. }
+            | Self::TransientStorage { storage_key, .. } => Some(*storage_key),
             Self::Start { .. }
             | Self::CallContext { .. }
             | Self::Stack { .. }
@@ -598,10 +676,12 @@ impl Rw {
                         if cfg!(feature = "poseidon-codehash") {
                             value.to_scalar().unwrap()
                         } else {
-                            rlc::value(&value.to_le_bytes(), randomness)
+                            word::rlc(&value, randomness)
                         }
                     }
-                    CallContextFieldTag::Value => rlc::value(&value.to_le_bytes(), randomness),
+                    CallContextFieldTag::Value | CallContextFieldTag::RevertDataHash => {
+                        word::rlc(&value, randomness)
+                    }
                     _ => value.to_scalar().unwrap(),
                 }
             }
@@ -609,27 +689,27 @@ impl Rw {
                 value, field_tag, ..
             } => match field_tag {
                 AccountFieldTag::KeccakCodeHash | AccountFieldTag::Balance => {
-                    rlc::value(&value.to_le_bytes(), randomness)
+                    word::rlc(&value, randomness)
                 }
                 AccountFieldTag::CodeHash => {
                     if cfg!(feature = "poseidon-codehash") {
                         value.to_scalar().unwrap()
                     } else {
-                        rlc::value(&value.to_le_bytes(), randomness)
+                        word::rlc(&value, randomness)
                     }
                 }
                 AccountFieldTag::Nonce
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value.to_scalar().unwrap(),
             },
-            Self::AccountStorage { value, .. } | Self::Stack { value, .. } => {
-                rlc::value(&value.to_le_bytes(), randomness)
-            }
+            Self::AccountStorage { value, .. }
+            | Self::Stack { value, .. }
+            | Self::TransientStorage { value, .. } => word::rlc(&value, randomness),
 
             Self::TxLog {
                 field_tag, value, ..
             } => match field_tag {
-                TxLogFieldTag::Topic => rlc::value(&value.to_le_bytes(), randomness),
+                TxLogFieldTag::Topic => word::rlc(&value, randomness),
                 _ => value.to_scalar().unwrap(),
             },
 
@@ -648,21 +728,21 @@ impl Rw {
                 ..
             } => Some(match field_tag {
                 AccountFieldTag::KeccakCodeHash | AccountFieldTag::Balance => {
-                    rlc::value(&value_prev.to_le_bytes(), randomness)
+                    word::rlc(&value_prev, randomness)
                 }
                 AccountFieldTag::CodeHash => {
                     if cfg!(feature = "poseidon-codehash") {
                         value_prev.to_scalar().unwrap()
                     } else {
-                        rlc::value(&value_prev.to_le_bytes(), randomness)
+                        word::rlc(&value_prev, randomness)
                     }
                 }
                 AccountFieldTag::Nonce
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value_prev.to_scalar().unwrap(),
             }),
-            Self::AccountStorage { value_prev, .. } => {
-                Some(rlc::value(&value_prev.to_le_bytes(), randomness))
+            Self::AccountStorage { value_prev, .. } | Self::TransientStorage { value_prev, .. } => {
+                Some(word::rlc(&value_prev, randomness))
             }
             Self::TxAccessListAccount { is_warm_prev, .. }
             | Self::TxAccessListAccountStorage { is_warm_prev, .. } => {
@@ -678,11 +758,60 @@ impl Rw {
         }
     }
 
+    /// Value of the row, as a word rather than the field element it is
+    /// assigned as.
+    pub fn value_word(&self) -> Word {
+        match self {
+            Self::Start { .. } => Word::zero(),
+            Self::TxAccessListAccount { is_warm, .. }
+            | Self::TxAccessListAccountStorage { is_warm, .. } => Word::from(*is_warm as u64),
+            Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => Word::from(*value),
+            Self::Account { value, .. }
+            | Self::AccountStorage { value, .. }
+            | Self::CallContext { value, .. }
+            | Self::Stack { value, .. }
+            | Self::TxLog { value, .. }
+            | Self::TransientStorage { value, .. } => *value,
+            Self::Memory { byte, .. } => Word::from(*byte),
+        }
+    }
+
+    /// Previous value of the row, for the tags which have one, as a word.
+    pub fn value_prev_word(&self) -> Option<Word> {
+        match self {
+            Self::Account { value_prev, .. }
+            | Self::AccountStorage { value_prev, .. }
+            | Self::TransientStorage { value_prev, .. } => Some(*value_prev),
+            Self::TxAccessListAccount { is_warm_prev, .. }
+            | Self::TxAccessListAccountStorage { is_warm_prev, .. } => {
+                Some(Word::from(*is_warm_prev as u64))
+            }
+            Self::TxRefund { value_prev, .. } => Some(Word::from(*value_prev)),
+            Self::Start { .. }
+            | Self::Stack { .. }
+            | Self::Memory { .. }
+            | Self::CallContext { .. }
+            | Self::TxLog { .. }
+            | Self::TxReceipt { .. } => None,
+        }
+    }
+
+    /// Value of the storage slot of the row at the start of its tx, only for
+    /// the `AccountStorage` rows.
+    pub fn committed_value_word(&self) -> Option<Word> {
+        match self {
+            Self::AccountStorage {
+                committed_value, ..
+            } => Some(*committed_value),
+            _ => None,
+        }
+    }
+
     fn committed_value_assignment<F: Field>(&self, randomness: F) -> Option<F> {
         match self {
             Self::AccountStorage {
                 committed_value, ..
-            } => Some(rlc::value(&committed_value.to_le_bytes(), randomness)),
+            } => Some(word::rlc(&committed_value, randomness)),
             _ => None,
         }
     }
@@ -828,6 +957,8 @@ impl From<&operation::OperationContainer> for RwMap {
                         CallContextField::ReversibleWriteCounter => {
                             CallContextFieldTag::ReversibleWriteCounter
                         }
+                        CallContextField::L1Fee => CallContextFieldTag::L1Fee,
+                        CallContextField::RevertDataHash => CallContextFieldTag::RevertDataHash,
                     },
                     value: op.op().value,
                 })
@@ -901,6 +1032,22 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::TransientStorage,
+            container
+                .transient_storage
+                .iter()
+                .map(|op| Rw::TransientStorage {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    storage_key: op.op().key,
+                    value: op.op().value,
+                    value_prev: op.op().value_prev,
+                })
+                .collect(),
+        );
 
         Self(rws)
     }
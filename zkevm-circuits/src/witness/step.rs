@@ -5,7 +5,6 @@ use bus_mapping::{
         NonceUintOverflowError, OogError,
     },
     evm::OpcodeId,
-    operation,
     precompile::PrecompileCalls,
 };
 
@@ -192,9 +191,13 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
                     OpcodeId::SLOAD => ExecutionState::SLOAD,
                     OpcodeId::SSTORE => ExecutionState::SSTORE,
+                    OpcodeId::TLOAD => ExecutionState::TLOAD,
+                    OpcodeId::TSTORE => ExecutionState::TSTORE,
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
+                    OpcodeId::BLOBHASH => ExecutionState::BLOBHASH,
+                    OpcodeId::BLOBBASEFEE => ExecutionState::BLOBBASEFEE,
                     OpcodeId::ISZERO => ExecutionState::ISZERO,
                     OpcodeId::CALL
                     | OpcodeId::CALLCODE
@@ -225,6 +228,7 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                 PrecompileCalls::Bn128Mul => ExecutionState::PrecompileBn256ScalarMul,
                 PrecompileCalls::Bn128Pairing => ExecutionState::PrecompileBn256Pairing,
                 PrecompileCalls::Blake2F => ExecutionState::PrecompileBlake2f,
+                PrecompileCalls::P256Verify => ExecutionState::PrecompileP256Verify,
             },
             circuit_input_builder::ExecState::BeginTx => ExecutionState::BeginTx,
             circuit_input_builder::ExecState::EndTx => ExecutionState::EndTx,
@@ -239,24 +243,7 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
         rw_indices: step
             .bus_mapping_instance
             .iter()
-            .map(|x| {
-                let tag = match x.target() {
-                    operation::Target::Memory => RwTableTag::Memory,
-                    operation::Target::Stack => RwTableTag::Stack,
-                    operation::Target::Storage => RwTableTag::AccountStorage,
-                    operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
-                    operation::Target::TxAccessListAccountStorage => {
-                        RwTableTag::TxAccessListAccountStorage
-                    }
-                    operation::Target::TxRefund => RwTableTag::TxRefund,
-                    operation::Target::Account => RwTableTag::Account,
-                    operation::Target::CallContext => RwTableTag::CallContext,
-                    operation::Target::TxReceipt => RwTableTag::TxReceipt,
-                    operation::Target::TxLog => RwTableTag::TxLog,
-                    operation::Target::Start => RwTableTag::Start,
-                };
-                (tag, x.as_usize())
-            })
+            .map(|x| (x.target().into(), x.as_usize()))
             .collect(),
         copy_rw_counter_delta: step.copy_rw_counter_delta,
         execution_state: ExecutionState::from(step),
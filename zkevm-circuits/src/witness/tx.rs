@@ -1,7 +1,9 @@
+#[cfg(feature = "prague")]
+use crate::table::AuthorizationField;
 use crate::{
-    evm_circuit::{step::ExecutionState, util::rlc},
+    evm_circuit::step::ExecutionState,
     table::TxContextFieldTag,
-    util::{rlc_be_bytes, Challenges},
+    util::{fast_mock, rlc_be_bytes, word, Challenges},
     witness::{
         rlp_fsm::SmState,
         DataTable, Format,
@@ -16,13 +18,16 @@ use crate::{
     },
 };
 use bus_mapping::circuit_input_builder::{self, get_dummy_tx, get_dummy_tx_hash, TxL1Fee};
+#[cfg(feature = "prague")]
+use eth_types::eip7702::{Authorization, MAX_AUTHORIZATIONS_PER_TX};
 use eth_types::{
     evm_types::gas_utils::tx_data_gas_cost,
     geth_types::{TxType, TxType::Eip155},
     sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
-    Address, Error, Field, Signature, ToBigEndian, ToLittleEndian, ToScalar, ToWord, Word, H256,
+    AccessList, Address, Error, Field, Signature, ToBigEndian, ToLittleEndian, ToScalar, ToWord,
+    Word, H256,
 };
-use ethers_core::{types::TransactionRequest, utils::keccak256};
+use ethers_core::types::TransactionRequest;
 use halo2_proofs::{
     circuit::Value,
     halo2curves::{group::ff::PrimeField, secp256k1},
@@ -32,7 +37,7 @@ use num::Integer;
 use num_bigint::BigUint;
 use std::{cmp::Ordering, collections::BTreeMap};
 
-use super::{step::step_convert, Call, ExecStep};
+use super::{step::step_convert, Call, ExecStep, Receipt};
 
 /// Transaction in a witness block
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -63,6 +68,8 @@ pub struct Transaction {
     pub call_data: Vec<u8>,
     /// The call data length
     pub call_data_length: usize,
+    /// The access list, empty for the txs without one
+    pub access_list: AccessList,
     /// The gas cost for transaction call data
     pub call_data_gas_cost: u64,
     /// The gas cost for rlp-encoded bytes of unsigned tx
@@ -83,6 +90,20 @@ pub struct Transaction {
     pub l1_fee: TxL1Fee,
     /// Committed values of L1 fee
     pub l1_fee_committed: TxL1Fee,
+    /// Whether it's a gas-free system tx
+    pub is_system: bool,
+    /// Whether it fails the intrinsic checks and is included as a no-op
+    pub is_invalid: bool,
+    /// Whether its block is on Prague, paying the call data floor cost
+    pub is_prague: bool,
+    /// The timestamp of its block, which is on Prague once it reaches the
+    /// Prague time of the chain
+    pub block_timestamp: u64,
+    /// The authorization list of an EIP-7702 set-code tx
+    #[cfg(feature = "prague")]
+    pub authorization_list: Vec<Authorization>,
+    /// The receipt of the transaction
+    pub receipt: Receipt,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -131,7 +152,7 @@ impl Transaction {
             Error::Signature(libsecp256k1::Error::InvalidSignature),
         )?;
         let msg = self.rlp_unsigned.clone().into();
-        let msg_hash = keccak256(&self.rlp_unsigned);
+        let msg_hash = fast_mock::keccak256(&self.rlp_unsigned);
         let v = self.tx_type.get_recovery_id(self.v);
         let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
         // msg_hash = msg_hash % q
@@ -155,10 +176,10 @@ impl Transaction {
         &self,
         challenges: Challenges<Value<F>>,
     ) -> Vec<[Value<F>; 4]> {
-        let tx_hash_be_bytes = keccak256(&self.rlp_signed);
-        let tx_sign_hash_be_bytes = keccak256(&self.rlp_unsigned);
+        let tx_hash_be_bytes = fast_mock::keccak256(&self.rlp_signed);
+        let tx_sign_hash_be_bytes = fast_mock::keccak256(&self.rlp_unsigned);
 
-        let ret = vec![
+        let mut ret = vec![
             [
                 Value::known(F::from(self.id as u64)),
                 Value::known(F::from(TxContextFieldTag::Nonce as u64)),
@@ -177,7 +198,7 @@ impl Transaction {
                 Value::known(F::zero()),
                 challenges
                     .evm_word()
-                    .map(|challenge| rlc::value(&self.gas_price.to_le_bytes(), challenge)),
+                    .map(|challenge| word::rlc(&self.gas_price, challenge)),
             ],
             [
                 Value::known(F::from(self.id as u64)),
@@ -208,7 +229,7 @@ impl Transaction {
                 Value::known(F::zero()),
                 challenges
                     .evm_word()
-                    .map(|challenge| rlc::value(&self.value.to_le_bytes(), challenge)),
+                    .map(|challenge| word::rlc(&self.value, challenge)),
             ],
             [
                 Value::known(F::from(self.id as u64)),
@@ -296,15 +317,89 @@ impl Transaction {
             ],
             [
                 Value::known(F::from(self.id as u64)),
-                Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+                Value::known(F::from(TxContextFieldTag::TxType as u64)),
                 Value::known(F::zero()),
-                Value::known(F::from(self.block_number)),
+                Value::known(F::from(u64::from(self.tx_type))),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::IsSystem as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.is_system as u64)),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::IsInvalid as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.is_invalid as u64)),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::IsPrague as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.is_prague as u64)),
+            ],
+            #[cfg(feature = "prague")]
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::AuthorizationListLength as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.authorization_list.len() as u64)),
             ],
         ];
+        #[cfg(feature = "prague")]
+        ret.extend(
+            self.authorization_fields()
+                .into_iter()
+                .map(|(index, value)| {
+                    [
+                        Value::known(F::from(self.id as u64)),
+                        Value::known(F::from(TxContextFieldTag::Authorization as u64)),
+                        Value::known(F::from(index as u64)),
+                        Value::known(value.to_scalar().unwrap()),
+                    ]
+                }),
+        );
+        ret.push([
+            Value::known(F::from(self.id as u64)),
+            Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+            Value::known(F::zero()),
+            Value::known(F::from(self.block_number)),
+        ]);
 
         ret
     }
 
+    /// Index and value of the fields of the authorizations of a set-code tx
+    /// in the tx table, padded with zeros to [`MAX_AUTHORIZATIONS_PER_TX`]
+    /// authorizations. The authority of an authorization which isn't valid on
+    /// the chain of the tx is zero.
+    #[cfg(feature = "prague")]
+    pub fn authorization_fields(&self) -> Vec<(usize, Word)> {
+        (0..MAX_AUTHORIZATIONS_PER_TX)
+            .flat_map(|i| {
+                let (authority, address, nonce) = self
+                    .authorization_list
+                    .get(i)
+                    .map(|auth| {
+                        (
+                            auth.valid_authority(self.chain_id.into())
+                                .unwrap_or_default(),
+                            auth.address,
+                            auth.nonce.as_u64(),
+                        )
+                    })
+                    .unwrap_or_default();
+                [
+                    (AuthorizationField::Authority, authority.to_word()),
+                    (AuthorizationField::Address, address.to_word()),
+                    (AuthorizationField::Nonce, nonce.into()),
+                ]
+                .map(|(field, value)| (field.index(i), value))
+            })
+            .collect()
+    }
+
     /// Assignments for tx table
     pub fn table_assignments_dyn<F: Field>(
         &self,
@@ -324,6 +419,22 @@ impl Transaction {
             .collect()
     }
 
+    /// Assignments for the bytes of the signed RLP encoding in tx table
+    pub fn table_assignments_rlp_bytes<F: Field>(&self) -> Vec<[Value<F>; 4]> {
+        self.rlp_signed
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| {
+                [
+                    Value::known(F::from(self.id as u64)),
+                    Value::known(F::from(TxContextFieldTag::RlpByte as u64)),
+                    Value::known(F::from(idx as u64)),
+                    Value::known(F::from(*byte as u64)),
+                ]
+            })
+            .collect()
+    }
+
     pub(crate) fn gen_rlp_witness<F: Field>(
         &self,
         is_hash: bool,
@@ -337,6 +448,8 @@ impl Transaction {
                     TxType::PreEip155 => TxHashPreEip155,
                     TxType::Eip1559 => TxHashEip1559,
                     TxType::L1Msg => L1MsgHash,
+                    #[cfg(feature = "prague")]
+                    TxType::Eip7702 => unimplemented!("set-code txs have no rlp format yet"),
                     _ => unreachable!("tx type {:?} not supported", self.tx_type),
                 },
             )
@@ -740,6 +853,10 @@ impl<F: Field> RlpFsmWitnessGen<F> for Transaction {
                 unimplemented!("eip2930 not supported now")
             }
             TxType::L1Msg => (L1MsgHash, None),
+            #[cfg(feature = "prague")]
+            TxType::Eip7702 => {
+                unimplemented!("set-code txs have no rlp format yet")
+            }
         };
 
         let get_table = |rlp_bytes: &Vec<u8>, format: Format| {
@@ -811,6 +928,7 @@ impl From<MockTransaction> for Transaction {
             value: mock_tx.value,
             call_data: mock_tx.input.to_vec(),
             call_data_length: mock_tx.input.len(),
+            access_list: AccessList::default(),
             call_data_gas_cost: tx_data_gas_cost(&mock_tx.input),
             tx_data_gas_cost: tx_data_gas_cost(&rlp_signed),
             chain_id: mock_tx.chain_id.as_u64(),
@@ -821,6 +939,13 @@ impl From<MockTransaction> for Transaction {
             s: sig.s,
             l1_fee: Default::default(),
             l1_fee_committed: Default::default(),
+            is_system: false,
+            is_invalid: false,
+            is_prague: false,
+            block_timestamp: 0,
+            #[cfg(feature = "prague")]
+            authorization_list: vec![],
+            receipt: Default::default(),
             calls: vec![],
             steps: vec![],
         }
@@ -831,6 +956,7 @@ pub(super) fn tx_convert(
     tx: &circuit_input_builder::Transaction,
     id: usize,
     chain_id: u64,
+    block_timestamp: u64,
     next_block_num: u64,
 ) -> Transaction {
     debug_assert_eq!(
@@ -854,6 +980,7 @@ pub(super) fn tx_convert(
         value: tx.value,
         call_data: tx.input.clone(),
         call_data_length: tx.input.len(),
+        access_list: tx.access_list.clone(),
         call_data_gas_cost: tx_data_gas_cost(&tx.input),
         tx_data_gas_cost: tx_data_gas_cost(&tx.rlp_bytes),
         chain_id,
@@ -864,6 +991,13 @@ pub(super) fn tx_convert(
         s: tx.signature.s,
         l1_fee: tx.l1_fee,
         l1_fee_committed: tx.l1_fee_committed,
+        is_system: tx.is_system,
+        is_invalid: tx.is_invalid,
+        is_prague: tx.is_prague,
+        block_timestamp,
+        #[cfg(feature = "prague")]
+        authorization_list: tx.authorization_list.clone(),
+        receipt: Receipt::new(id, &tx.receipt),
         calls: tx
             .calls()
             .iter()
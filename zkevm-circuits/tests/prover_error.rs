@@ -75,6 +75,7 @@ fn prover_error() {
                 balance: acc.balance,
                 code: acc.code,
                 storage: acc.storage,
+                storage_snapshot: None,
             };
             accounts.push(account);
         }
@@ -104,9 +105,7 @@ fn prover_error() {
         block.randomness = Fr::from(MOCK_RANDOMNESS);
         block
     };
-    let circuit = SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, 64, MOCK_RANDOMNESS>::new_from_block(
-        &block_witness,
-    );
+    let circuit = SuperCircuit::<_, 64, MOCK_RANDOMNESS>::new_from_block(&block_witness);
     let res = MockProver::run(k, &circuit, circuit.instance())
         .expect("MockProver::run")
         .verify_par();
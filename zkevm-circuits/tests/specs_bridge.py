@@ -0,0 +1,110 @@
+#!/usr/bin/env python3
+"""Bridge between the test vectors of zkevm-circuits and the Python reference
+of zkevm-specs, used by the `specs_conformance` test.
+
+Reads a test vector, as written by `zkevm_circuits::test_vectors`, on stdin,
+assigns its rw table to the state circuit of the specs and verifies it.
+Prints `{"accepted": true}` if the specs accept the rw table, or
+`{"accepted": false, "reason": "..."}` if they reject it. Any other failure,
+like an unknown tag, exits with a non-zero status, so that it isn't taken for
+a rejection.
+
+The specs are imported from the `zkevm_specs` package, e.g. installed with
+`pip install -e` from a checkout of zkevm-specs.
+"""
+
+import json
+import sys
+
+from zkevm_specs.state_circuit import (
+    AccountOp,
+    CallContextOp,
+    MemoryOp,
+    StackOp,
+    StartOp,
+    StorageOp,
+    TxAccessListAccountOp,
+    TxAccessListAccountStorageOp,
+    TxLogOp,
+    TxReceiptOp,
+    TxRefundOp,
+    assign_state_circuit,
+    verify_circuit,
+)
+from zkevm_specs.util import FQ, RLC
+from zkevm_specs.evm_circuit import RW
+
+# Randomness of the RLCs of the words, which the acceptance doesn't depend on.
+RANDOMNESS = FQ(0x100)
+
+
+def word(value):
+    return int(value, 16)
+
+
+def address(value):
+    return int(value, 16)
+
+
+def op(row):
+    """Operation of the specs for a row of the rw table of a test vector."""
+    rw = RW.Write if row["is_write"] else RW.Read
+    rw_counter = row["rw_counter"]
+    tag = row["tag"]
+    value = word(row["value"])
+    if tag == "Start":
+        return StartOp(rw_counter)
+    if tag == "Memory":
+        return MemoryOp(rw_counter, rw, row["id"], address(row["address"]), value)
+    if tag == "Stack":
+        return StackOp(rw_counter, rw, row["id"], address(row["address"]), RLC(value, RANDOMNESS))
+    if tag == "AccountStorage":
+        return StorageOp(
+            rw_counter,
+            rw,
+            row["id"],
+            address(row["address"]),
+            RLC(word(row["storage_key"]), RANDOMNESS),
+            RLC(value, RANDOMNESS),
+            RLC(word(row["committed_value"]), RANDOMNESS),
+        )
+    if tag == "CallContext":
+        return CallContextOp(rw_counter, rw, row["id"], row["field_tag"], value)
+    if tag == "Account":
+        return AccountOp(rw_counter, rw, address(row["address"]), row["field_tag"], value)
+    if tag == "TxRefund":
+        return TxRefundOp(rw_counter, rw, row["id"], value)
+    if tag == "TxAccessListAccount":
+        return TxAccessListAccountOp(rw_counter, rw, row["id"], address(row["address"]), value)
+    if tag == "TxAccessListAccountStorage":
+        return TxAccessListAccountStorageOp(
+            rw_counter,
+            rw,
+            row["id"],
+            address(row["address"]),
+            RLC(word(row["storage_key"]), RANDOMNESS),
+            value,
+        )
+    if tag == "TxLog":
+        # The address of a log row packs its log id, field tag and index.
+        packed = address(row["address"])
+        index, field_tag, log_id = packed & 0xFFFFFFFF, (packed >> 32) & 0xFFFF, packed >> 48
+        return TxLogOp(rw_counter, rw, row["id"], log_id, field_tag, index, value)
+    if tag == "TxReceipt":
+        return TxReceiptOp(rw_counter, rw, row["id"], row["field_tag"], value)
+    raise ValueError(f"unknown rw table tag {tag}")
+
+
+def main():
+    vector = json.load(sys.stdin)
+    ops = [op(row) for row in vector["rws"]]
+    try:
+        verify_circuit(assign_state_circuit(ops, RANDOMNESS), RANDOMNESS)
+    except AssertionError as error:
+        print(json.dumps({"accepted": False, "reason": str(error) or repr(error)}))
+        return
+    print(json.dumps({"accepted": True}))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,179 @@
+// Conformance of the witness with the Python reference of zkevm-specs. The rw
+// table of each curated block, and of copies of them with a corrupted
+// operation, is checked by the state circuit and by the state circuit of the
+// specs, through `specs_bridge.py`, and the test fails where they disagree on
+// accepting it.
+//
+// The specs are run with `ZKEVM_SPECS_PYTHON`, `python3` by default, which
+// must be able to import `zkevm_specs`, so the test is ignored by default and
+// run with `cargo test --test specs_conformance -- --ignored`.
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use serde::Deserialize;
+use std::{
+    env,
+    io::Write,
+    process::{Command, Stdio},
+};
+use zkevm_circuits::{
+    state_circuit::StateCircuit,
+    test_vectors::{curated_blocks, TestVector},
+    util::SubCircuit,
+    witness::{Block, Rw},
+};
+
+const BRIDGE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/specs_bridge.py");
+
+/// Degree of the state circuit, for the 4096 rws of the curated blocks
+const STATE_CIRCUIT_DEGREE: u32 = 14;
+
+#[derive(Deserialize)]
+struct SpecsResult {
+    accepted: bool,
+    #[serde(default)]
+    reason: String,
+}
+
+fn python() -> String {
+    env::var("ZKEVM_SPECS_PYTHON").unwrap_or_else(|_| "python3".to_string())
+}
+
+fn specs_available() -> bool {
+    Command::new(python())
+        .args(["-c", "import zkevm_specs"])
+        .output()
+        .map_or(false, |output| output.status.success())
+}
+
+fn specs_verify(vector: &TestVector) -> SpecsResult {
+    let mut child = Command::new(python())
+        .arg(BRIDGE)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&serde_json::to_vec(vector).unwrap())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "specs bridge failed on {}: {}",
+        vector.name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn circuit_accepts(block: &Block<Fr>) -> bool {
+    let circuit = StateCircuit::<Fr>::new(block.rws.clone(), block.circuits_params.max_rws);
+    let instance = circuit.instance();
+    MockProver::<Fr>::run(STATE_CIRCUIT_DEGREE, &circuit, instance)
+        .unwrap()
+        .verify()
+        .is_ok()
+}
+
+/// Copy of `block` with the value of its first operation matching `is_target`
+/// changed by `corrupt`, or `None` if no operation matches.
+fn corrupted(
+    block: &Block<Fr>,
+    is_target: impl Fn(&Rw) -> bool,
+    corrupt: impl Fn(&mut Rw),
+) -> Option<Block<Fr>> {
+    let mut block = block.clone();
+    let mut found = false;
+    block.update_rws(|rws| {
+        if let Some(rw) = rws
+            .0
+            .values_mut()
+            .flatten()
+            .filter(|rw| is_target(rw))
+            .min_by_key(|rw| rw.rw_counter())
+        {
+            corrupt(rw);
+            found = true;
+        }
+    });
+    found.then_some(block)
+}
+
+/// The curated blocks, and copies of them where their first stack read and
+/// first memory read return another value than the one last written.
+fn cases() -> Vec<(String, Block<Fr>)> {
+    let mut cases = Vec::new();
+    for (name, block) in curated_blocks().unwrap() {
+        let stack_read = corrupted(
+            &block,
+            |rw| {
+                matches!(
+                    rw,
+                    Rw::Stack {
+                        is_write: false,
+                        ..
+                    }
+                )
+            },
+            |rw| {
+                if let Rw::Stack { value, .. } = rw {
+                    *value = value.overflowing_add(1.into()).0;
+                }
+            },
+        );
+        let memory_read = corrupted(
+            &block,
+            |rw| {
+                matches!(
+                    rw,
+                    Rw::Memory {
+                        is_write: false,
+                        ..
+                    }
+                )
+            },
+            |rw| {
+                if let Rw::Memory { byte, .. } = rw {
+                    *byte = byte.wrapping_add(1);
+                }
+            },
+        );
+        for (corruption, block) in [("stack_read", stack_read), ("memory_read", memory_read)] {
+            if let Some(block) = block {
+                cases.push((format!("{}_corrupted_{}", name, corruption), block));
+            }
+        }
+        cases.push((name.to_string(), block));
+    }
+    cases
+}
+
+#[test]
+#[ignore = "requires the zkevm_specs Python package"]
+fn witness_conforms_to_specs() {
+    assert!(specs_available(), "{} can't import zkevm_specs", python());
+
+    let divergences: Vec<_> = cases()
+        .into_iter()
+        .filter_map(|(name, block)| {
+            let specs = specs_verify(&TestVector::new(&name, &block));
+            let circuit = circuit_accepts(&block);
+            (specs.accepted != circuit).then(|| {
+                format!(
+                    "{}: circuit {}, specs {} {}",
+                    name,
+                    if circuit { "accepts" } else { "rejects" },
+                    if specs.accepted { "accept" } else { "reject" },
+                    specs.reason
+                )
+            })
+        })
+        .collect();
+    assert!(
+        divergences.is_empty(),
+        "the circuit and the specs diverge:\n{}",
+        divergences.join("\n")
+    );
+}
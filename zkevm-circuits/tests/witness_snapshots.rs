@@ -0,0 +1,133 @@
+// Snapshots of the witness built for a curated set of txs, so that changes of
+// its layout show up as snapshot diffs in review. After an intended change,
+// update the snapshots with `cargo insta test -p zkevm-circuits --test
+// witness_snapshots` followed by `cargo insta review`.
+use bus_mapping::{
+    circuit_input_builder::{CircuitsParams, CopyEvent},
+    mock::BlockData,
+};
+use eth_types::{address, bytecode, geth_types::GethData, Word};
+use halo2_proofs::halo2curves::bn256::Fr;
+use mock::{eth, TestContext};
+use zkevm_circuits::witness::{block_convert, ExecStep, Rw};
+
+/// The parts of the witness block whose layout is snapshotted
+#[derive(Debug)]
+#[allow(dead_code)] // the fields are only read by the Debug snapshot
+struct WitnessSnapshot {
+    /// Steps of all the txs, in order
+    steps: Vec<ExecStep>,
+    /// Read write events in rw table order
+    rws: Vec<Rw>,
+    copy_events: Vec<CopyEvent>,
+}
+
+fn witness<const NACC: usize, const NTX: usize>(ctx: TestContext<NACC, NTX>) -> WitnessSnapshot {
+    let block: GethData = ctx.into();
+    let mut builder = BlockData::new_from_geth_data_with_params(
+        block.clone(),
+        CircuitsParams {
+            max_rws: 4096,
+            ..Default::default()
+        },
+    )
+    .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+    WitnessSnapshot {
+        steps: block
+            .txs
+            .iter()
+            .flat_map(|tx| tx.steps.iter().cloned())
+            .collect(),
+        rws: block.sorted_rws().to_vec(),
+        copy_events: block.copy_events,
+    }
+}
+
+#[test]
+fn transfer() {
+    let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP }).unwrap();
+    insta::assert_debug_snapshot!(witness(ctx));
+}
+
+#[test]
+fn storage_and_log() {
+    let code = bytecode! {
+        .op_sstore(0x00, 0x2a)
+        .op_sload(0x00)
+        PUSH1(0x00)
+        MSTORE
+        .op_log1(0x00, 0x20, 0x1234)
+        STOP
+    };
+    let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+    insta::assert_debug_snapshot!(witness(ctx));
+}
+
+#[test]
+fn memory_copies() {
+    let code = bytecode! {
+        .op_codecopy(0x00, 0x00, 0x10)
+        .op_calldatacopy(0x20, 0x00, 0x08)
+        .op_sha3(0x00, 0x28)
+        POP
+        .op_return(0x00, 0x28)
+    };
+    let ctx = TestContext::<2, 1>::new(
+        None,
+        mock::test_ctx::helpers::account_0_code_account_1_no_code(code),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .input(vec![1, 2, 3, 4, 5, 6, 7, 8].into());
+        },
+        |block, _txs| block,
+    )
+    .unwrap();
+    insta::assert_debug_snapshot!(witness(ctx));
+}
+
+#[test]
+fn reverted_call_and_create() {
+    let callee_address = address!("0x00000000000000000000000000000000000000bb");
+    // the callee writes to storage and logs before reverting
+    let callee = bytecode! {
+        .op_sstore(0x00, 0x01)
+        .op_log0(0x00, 0x00)
+        .op_revert(0x00, 0x00)
+    };
+    // the init code returns a single zero byte as the created contract
+    let caller = bytecode! {
+        .op_call(0x10000, callee_address, 0x00, 0x00, 0x00, 0x00, 0x00)
+        POP
+        .op_mstore(0x00, 0x60016000f3u64)
+        .op_create(0x00, 0x1b, 0x05)
+        STOP
+    };
+    let ctx = TestContext::<3, 1>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(address!("0x000000000000000000000000000000000000cafe"))
+                .balance(eth(10));
+            accs[1]
+                .address(address!("0x00000000000000000000000000000000000000aa"))
+                .code(caller);
+            accs[2].address(callee_address).code(callee);
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[0].address)
+                .to(accs[1].address)
+                .gas(Word::from(1_000_000));
+        },
+        |block, _txs| block,
+    )
+    .unwrap();
+    insta::assert_debug_snapshot!(witness(ctx));
+}
@@ -0,0 +1,90 @@
+//! Inputs of the witness generation of a block: the block, its execution
+//! traces and the state it accesses. They are gathered once from a geth node
+//! and stored in a file, so that the witness can be rebuilt offline.
+
+use anyhow::{Context, Result};
+use bus_mapping::{
+    circuit_input_builder::{
+        build_state_code_db, get_state_accesses, BlockHead, BuilderClient, CircuitInputBuilder,
+    },
+    rpc::GethClient,
+};
+use eth_types::{Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, Transaction};
+use ethers_providers::Http;
+use prover_server::prover::CIRCUITS_PARAMS;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs::File, io::BufReader, path::Path};
+use url::Url;
+use zkevm_circuits::mem_profile::{self, Phase};
+
+/// Everything needed to build the witness of a block.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockInputs {
+    /// Chain id
+    pub chain_id: u64,
+    /// Block with its transactions
+    pub block: Block<Transaction>,
+    /// Execution trace of each transaction
+    pub traces: Vec<GethExecTrace>,
+    /// State accessed by the block, as `eth_getProof` results at the parent
+    /// block
+    pub proofs: Vec<EIP1186ProofResponse>,
+    /// Code of the accessed contracts
+    pub codes: HashMap<Address, Bytes>,
+}
+
+impl BlockInputs {
+    /// Fetch the inputs of block `block_num` from the geth node at `url`.
+    pub async fn fetch(url: Url, block_num: u64) -> Result<Self> {
+        let _phase = mem_profile::enter(Phase::Tracing);
+        let geth_client = GethClient::new(Http::new(url));
+        let chain_id = geth_client.get_chain_id().await?;
+        let client = BuilderClient::new(geth_client, CIRCUITS_PARAMS).await?;
+
+        let (block, traces, _, _) = client.get_block(block_num).await?;
+        let access_set = get_state_accesses(&block, &traces)?;
+        let (proofs, codes) = client.get_state(block_num, access_set.into()).await?;
+
+        Ok(Self {
+            chain_id,
+            block,
+            traces,
+            proofs,
+            codes: codes
+                .into_iter()
+                .map(|(address, code)| (address, code.into()))
+                .collect(),
+        })
+    }
+
+    /// Read inputs stored by [`BlockInputs::store`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("cannot parse {}", path.display()))
+    }
+
+    /// Write the inputs as JSON.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("cannot create {}", path.display()))?;
+        Ok(serde_json::to_writer(file, self)?)
+    }
+
+    /// Generate the circuit inputs of the block.
+    pub fn build(&self) -> Result<CircuitInputBuilder> {
+        let _phase = mem_profile::enter(Phase::BusMapping);
+        let (state_db, code_db) = build_state_code_db(
+            self.proofs.clone(),
+            self.codes
+                .iter()
+                .map(|(address, code)| (*address, code.to_vec()))
+                .collect(),
+        );
+        let head = BlockHead::new(self.chain_id.into(), Vec::new(), &self.block)?;
+        let mut builder =
+            CircuitInputBuilder::new_from_headers(CIRCUITS_PARAMS, state_db, code_db, &[head]);
+        builder.handle_block(&self.block, &self.traces)?;
+        Ok(builder)
+    }
+}
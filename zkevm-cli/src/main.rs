@@ -0,0 +1,280 @@
+//! Command line interface to the witness generation and the circuits.
+
+mod inputs;
+mod proof;
+mod verifier_gas;
+mod witness;
+mod witness_diff;
+
+use anyhow::{bail, Context, Result};
+use bus_mapping::circuit_input_builder::{audit_rw_counters, export_witness};
+use clap::{Args, Parser, Subcommand};
+use env_logger::Env;
+use inputs::BlockInputs;
+use std::{fs::File, io::BufReader, path::PathBuf};
+use url::Url;
+use witness_diff::GoldenWitness;
+
+#[cfg(feature = "mem-profile")]
+#[global_allocator]
+static ALLOCATOR: zkevm_circuits::mem_profile::ProfilingAllocator =
+    zkevm_circuits::mem_profile::ProfilingAllocator;
+
+#[derive(Parser, Debug)]
+#[clap(name = "zkevm-cli", author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate and inspect the witness of a block
+    #[clap(subcommand)]
+    Witness(WitnessCommand),
+    /// Prove a block with the super circuit
+    Prove {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        params: ParamsArgs,
+        /// Where to write the proof envelope
+        #[clap(long, short)]
+        out: PathBuf,
+    },
+    /// Print the columns, constraints and estimated proving cost of each
+    /// table and sub-circuit of the super circuit
+    Cost,
+    /// Print the column schemas of the shared tables of the super circuit as
+    /// JSON
+    Schema,
+    /// Verify a proof of a block
+    Verify {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        params: ParamsArgs,
+        /// Proof envelope written by `prove`
+        #[clap(long)]
+        proof: PathBuf,
+    },
+    /// Simulate the EVM verifier of the circuit of a block on revm, and
+    /// report the gas it uses per part of the verification
+    VerifierGas {
+        #[clap(flatten)]
+        input: InputArgs,
+        #[clap(flatten)]
+        params: ParamsArgs,
+        /// Raw proof of the block made with the EVM transcript, proven with
+        /// it if not given
+        #[clap(long)]
+        proof: Option<PathBuf>,
+        /// Where to write the Yul source of the verifier
+        #[clap(long)]
+        yul: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum WitnessCommand {
+    /// Gather the inputs of a block and check that its witness builds
+    Gen {
+        /// Geth node to fetch the block from
+        #[clap(long, requires = "block", required_unless_present = "trace_file")]
+        rpc: Option<Url>,
+        /// Number of the block to fetch
+        #[clap(long)]
+        block: Option<u64>,
+        /// Block inputs file to read instead of fetching the block
+        #[clap(long, conflicts_with = "rpc")]
+        trace_file: Option<PathBuf>,
+        /// Where to write the block inputs
+        #[clap(long, short)]
+        out: Option<PathBuf>,
+    },
+    /// Print the size of the witness and the rows it needs per circuit
+    Stats {
+        #[clap(flatten)]
+        input: InputArgs,
+    },
+    /// Print parts of the witness
+    #[clap(subcommand)]
+    Dump(DumpCommand),
+    /// Check the rw counters of the operations of each step, and print the
+    /// first step breaking their invariants
+    Audit {
+        #[clap(flatten)]
+        input: InputArgs,
+    },
+    /// Save the steps and rw operations of the witness as a golden witness
+    Golden {
+        #[clap(flatten)]
+        input: InputArgs,
+        /// Where to write the golden witness
+        #[clap(long, short)]
+        out: PathBuf,
+    },
+    /// Compare the witness with a golden witness, saved by another version
+    /// of the witness generation
+    Diff {
+        #[clap(flatten)]
+        input: InputArgs,
+        /// Golden witness written by `witness golden`
+        #[clap(long)]
+        golden: PathBuf,
+    },
+    /// Export the rw activity of each step and the circuit cost of each
+    /// transaction as JSON, for explorers and indexers
+    Export {
+        #[clap(flatten)]
+        input: InputArgs,
+        /// Where to write the export
+        #[clap(long, short)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DumpCommand {
+    /// Print the rw operations of each step of a transaction
+    Rw {
+        #[clap(flatten)]
+        input: InputArgs,
+        /// Index of the transaction in the block, from 0
+        #[clap(long)]
+        tx: usize,
+    },
+}
+
+#[derive(Args, Debug)]
+struct InputArgs {
+    /// Block inputs file written by `witness gen`
+    #[clap(long, short)]
+    input: PathBuf,
+}
+
+impl InputArgs {
+    fn load(&self) -> Result<BlockInputs> {
+        BlockInputs::load(&self.input)
+    }
+}
+
+#[derive(Args, Debug)]
+struct ParamsArgs {
+    /// KZG params file, generated from the seed if not given
+    #[clap(long)]
+    params: Option<PathBuf>,
+    /// Seed of the params generation and of the prover rng
+    #[clap(long, default_value_t = 0x5962be5d763d318d)]
+    seed: u64,
+    /// Degree of the super circuit
+    #[clap(long, default_value_t = 20)]
+    degree: u32,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    match Cli::parse().command {
+        Command::Witness(WitnessCommand::Gen {
+            rpc,
+            block,
+            trace_file,
+            out,
+        }) => {
+            let inputs = match (rpc, block, trace_file) {
+                (Some(rpc), Some(block), _) => BlockInputs::fetch(rpc, block).await?,
+                (_, _, Some(trace_file)) => BlockInputs::load(&trace_file)?,
+                _ => unreachable!("checked by clap"),
+            };
+            let block = witness::witness_block(&inputs)?;
+            log::info!(
+                "witness of block {} built: {} txs",
+                inputs.block.number.unwrap_or_default(),
+                block.txs.len()
+            );
+            if let Some(out) = out {
+                inputs.store(&out)?;
+            }
+        }
+        Command::Witness(WitnessCommand::Stats { input }) => {
+            witness::print_stats(&witness::witness_block(&input.load()?)?);
+        }
+        Command::Witness(WitnessCommand::Dump(DumpCommand::Rw { input, tx })) => {
+            witness::dump_tx_rws(&witness::witness_block(&input.load()?)?, tx)?;
+        }
+        Command::Witness(WitnessCommand::Audit { input }) => {
+            audit_rw_counters(&input.load()?.build()?.block)?;
+            println!("the rw counters of the witness are consistent");
+        }
+        Command::Witness(WitnessCommand::Golden { input, out }) => {
+            GoldenWitness::new(&witness::witness_block(&input.load()?)?).store(&out)?;
+        }
+        Command::Witness(WitnessCommand::Diff { input, golden }) => {
+            let golden = GoldenWitness::load(&golden)?;
+            let current = GoldenWitness::new(&witness::witness_block(&input.load()?)?);
+            let diffs = witness_diff::diff(&golden, &current);
+            if !diffs.is_empty() {
+                witness_diff::print_diff(&golden, &current, &diffs);
+                bail!(
+                    "the witness differs from the golden witness in {} txs",
+                    diffs.len()
+                );
+            }
+            println!("the witness matches the golden witness");
+        }
+        Command::Witness(WitnessCommand::Export { input, out }) => {
+            let export = export_witness(&input.load()?.build()?.block);
+            let file =
+                File::create(&out).with_context(|| format!("cannot create {}", out.display()))?;
+            serde_json::to_writer(file, &export)?;
+        }
+        Command::Prove { input, params, out } => {
+            let inputs = input.load()?;
+            let kzg_params =
+                proof::load_params(params.params.as_deref(), params.seed, params.degree)?;
+            let envelope = proof::prove(&kzg_params, &inputs, params.seed)?;
+            let file =
+                File::create(&out).with_context(|| format!("cannot create {}", out.display()))?;
+            serde_json::to_writer(file, &envelope)?;
+        }
+        Command::Cost => print!("{}", proof::cost_report()),
+        Command::Schema => println!("{}", serde_json::to_string_pretty(&proof::table_schemas())?),
+        Command::Verify {
+            input,
+            params,
+            proof: proof_path,
+        } => {
+            let inputs = input.load()?;
+            let kzg_params =
+                proof::load_params(params.params.as_deref(), params.seed, params.degree)?;
+            let file = File::open(&proof_path)
+                .with_context(|| format!("cannot open {}", proof_path.display()))?;
+            let envelope = serde_json::from_reader(BufReader::new(file))?;
+            proof::verify(&kzg_params, &inputs, &envelope)?;
+            println!("proof verified");
+        }
+        Command::VerifierGas {
+            input,
+            params,
+            proof,
+            yul,
+        } => {
+            let inputs = input.load()?;
+            let kzg_params =
+                proof::load_params(params.params.as_deref(), params.seed, params.degree)?;
+            let report = verifier_gas::verifier_gas(
+                &kzg_params,
+                &inputs,
+                proof.as_deref(),
+                yul.as_deref(),
+                params.seed,
+            )?;
+            print!("{}", report);
+        }
+    }
+    #[cfg(feature = "mem-profile")]
+    eprint!("{}", zkevm_circuits::mem_profile::report());
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! Proving and verification of a block with the super circuit.
+
+use crate::inputs::BlockInputs;
+use anyhow::{ensure, Context, Result};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, VerifyingKey,
+    },
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use prover_server::prover::{ProverCircuit, CIRCUITS_PARAMS};
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use std::{fs::File, io::BufReader, path::Path};
+use zkevm_circuits::{
+    circuit_cost::CostReport,
+    mem_profile::{self, Phase},
+    proof_envelope::{verify_envelope, ProofEnvelope, VerifierSetup},
+    super_circuit::registry::TableSchema,
+    util::vk_fingerprint,
+};
+
+/// Columns, constraints and estimated proving cost of each table and
+/// sub-circuit of the super circuit.
+pub fn cost_report() -> CostReport {
+    let mut meta = ConstraintSystem::default();
+    let (config, _) = ProverCircuit::configure(&mut meta);
+    config.cost_report(&meta)
+}
+
+/// Schemas of the shared tables of the super circuit.
+pub fn table_schemas() -> Vec<TableSchema> {
+    let mut meta = ConstraintSystem::default();
+    let (config, _) = ProverCircuit::configure(&mut meta);
+    config.table_schemas()
+}
+
+/// Read the KZG params from `path`, or generate them from `seed` if no path
+/// is given, and downsize them to `degree`.
+pub fn load_params(path: Option<&Path>, seed: u64, degree: u32) -> Result<ParamsKZG<Bn256>> {
+    let mut params = match path {
+        Some(path) => {
+            let file =
+                File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+            ParamsKZG::<Bn256>::read(&mut BufReader::new(file))?
+        }
+        None => {
+            log::warn!("generating params from the seed, do not use them in production");
+            ParamsKZG::<Bn256>::setup(degree, XorShiftRng::seed_from_u64(seed))
+        }
+    };
+    ensure!(
+        params.k() >= degree,
+        "params of degree {} < {}",
+        params.k(),
+        degree
+    );
+    params.downsize(degree);
+    Ok(params)
+}
+
+/// Build the circuit of the block with its instances, and its verifying key.
+pub fn circuit_and_vk(
+    params: &ParamsKZG<Bn256>,
+    inputs: &BlockInputs,
+) -> Result<(ProverCircuit, Vec<Vec<Fr>>, VerifyingKey<G1Affine>)> {
+    let builder = inputs.build()?;
+    let _phase = mem_profile::enter(Phase::Assignment);
+    let (k, circuit, instances) = ProverCircuit::build_from_circuit_input_builder(&builder)?;
+    ensure!(
+        k <= params.k(),
+        "block needs a circuit of degree {}, the params have degree {}",
+        k,
+        params.k()
+    );
+    let vk = keygen_vk(params, &circuit)?;
+    Ok((circuit, instances, vk))
+}
+
+/// Prove the block with the super circuit.
+pub fn prove(params: &ParamsKZG<Bn256>, inputs: &BlockInputs, seed: u64) -> Result<ProofEnvelope> {
+    let (circuit, instances, vk) = circuit_and_vk(params, inputs)?;
+    let pk = {
+        let _phase = mem_profile::enter(Phase::Assignment);
+        keygen_pk(params, vk, &circuit)?
+    };
+    let _phase = mem_profile::enter(Phase::Proving);
+
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<
+        KZGCommitmentScheme<Bn256>,
+        ProverSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        XorShiftRng,
+        Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+        ProverCircuit,
+    >(
+        params,
+        &pk,
+        &[circuit],
+        &[&instances.iter().map(Vec::as_slice).collect::<Vec<_>>()],
+        XorShiftRng::seed_from_u64(seed),
+        &mut transcript,
+    )?;
+
+    let block_number = inputs.block.number.unwrap_or_default().as_u64();
+    Ok(ProofEnvelope::new(
+        vk_fingerprint(pk.get_vk(), &CIRCUITS_PARAMS),
+        CIRCUITS_PARAMS,
+        inputs.chain_id,
+        (block_number, block_number),
+        &instances,
+        transcript.finalize(),
+    ))
+}
+
+/// Check that the proof is compatible with the circuit of the block, and
+/// verify it.
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    inputs: &BlockInputs,
+    envelope: &ProofEnvelope,
+) -> Result<()> {
+    let (_, _, vk) = circuit_and_vk(params, inputs)?;
+    let setup = VerifierSetup {
+        circuit_version: vk_fingerprint(&vk, &CIRCUITS_PARAMS),
+        circuits_params: CIRCUITS_PARAMS,
+        chain_id: inputs.chain_id,
+    };
+    let instances = verify_envelope::<Fr>(envelope, &setup)?;
+
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(envelope.proof.as_ref());
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+        SingleStrategy<'_, Bn256>,
+    >(
+        params.verifier_params(),
+        &vk,
+        SingleStrategy::new(params),
+        &[&instances.iter().map(Vec::as_slice).collect::<Vec<_>>()],
+        &mut transcript,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,141 @@
+//! Gas report of the EVM verifier of the super circuit.
+//!
+//! The Yul verifier generated by snark-verifier is deployed and called on
+//! revm with the instances and the proof of a block. The gas of the call is
+//! broken down into the parts that depend on the layout of the public inputs
+//! (calldata and hashing of the instances into the transcript) and the ones
+//! that don't (pairing check and the rest of the execution, mostly the MSMs).
+//! The proof must be made with the keccak transcript of the EVM verifier, so
+//! the proofs of the proof envelopes can't be used.
+
+use crate::{inputs::BlockInputs, proof::circuit_and_vk};
+use anyhow::{bail, Context, Result};
+use eth_types::evm_types::{gas_utils::tx_data_gas_cost, GasCost};
+use halo2_proofs::{halo2curves::bn256::Bn256, plonk::keygen_pk, poly::kzg::commitment::ParamsKZG};
+use prover_server::prover::ProverCircuit;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use snark_verifier::loader::evm::{deploy_and_call, encode_calldata};
+use snark_verifier_sdk::evm::{gen_evm_proof_shplonk, gen_evm_verifier_shplonk};
+use std::{fmt, fs, path::Path};
+
+/// Gas of each pair of points given to the pairing precompile (EIP-1108)
+const PAIRING_PER_PAIR_GAS: u64 = 34000;
+/// The SHPLONK decider checks a single pairing equation of 2 pairs.
+const SHPLONK_PAIRS: u64 = 2;
+
+/// Gas used by the EVM verifier to verify a proof.
+#[derive(Debug)]
+pub struct VerifierGasReport {
+    /// Size of the deployment code of the verifier
+    pub deployment_size: usize,
+    /// Number of instances of the proof
+    pub num_instances: usize,
+    /// Size of the proof
+    pub proof_size: usize,
+    /// Gas used by the verifying tx
+    pub total: u64,
+    /// Intrinsic gas of the tx
+    pub tx: u64,
+    /// Calldata gas of the instances
+    pub calldata_instances: u64,
+    /// Calldata gas of the proof
+    pub calldata_proof: u64,
+    /// Keccak gas of the instances, hashed once into the transcript
+    pub keccak_instances: u64,
+    /// Gas of the pairing check
+    pub pairing: u64,
+}
+
+impl VerifierGasReport {
+    /// Gas of the verifier execution left out of the other parts.
+    pub fn execution(&self) -> u64 {
+        self.total.saturating_sub(
+            self.tx
+                + self.calldata_instances
+                + self.calldata_proof
+                + self.keccak_instances
+                + self.pairing,
+        )
+    }
+}
+
+impl fmt::Display for VerifierGasReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "verifier deployment size: {} bytes",
+            self.deployment_size
+        )?;
+        writeln!(f, "instances: {}", self.num_instances)?;
+        writeln!(f, "proof size: {} bytes", self.proof_size)?;
+        writeln!(f, "| part | gas | share |")?;
+        writeln!(f, "|---|---|---|")?;
+        for (name, gas) in [
+            ("tx", self.tx),
+            ("calldata of the instances", self.calldata_instances),
+            ("calldata of the proof", self.calldata_proof),
+            ("keccak of the instances", self.keccak_instances),
+            ("pairing check", self.pairing),
+            ("execution", self.execution()),
+        ] {
+            writeln!(
+                f,
+                "| {} | {} | {:.1}% |",
+                name,
+                gas,
+                100.0 * gas as f64 / self.total.max(1) as f64
+            )?;
+        }
+        writeln!(f, "| total | {} | 100.0% |", self.total)
+    }
+}
+
+/// Deploy the EVM verifier of the circuit of the block and report the gas
+/// used to verify `proof`, or a proof of the block made with the EVM
+/// transcript if no proof is given. The Yul source of the verifier is
+/// written to `yul` if given.
+pub fn verifier_gas(
+    params: &ParamsKZG<Bn256>,
+    inputs: &BlockInputs,
+    proof: Option<&Path>,
+    yul: Option<&Path>,
+    seed: u64,
+) -> Result<VerifierGasReport> {
+    let (circuit, instances, vk) = circuit_and_vk(params, inputs)?;
+    let num_instance = instances.iter().map(Vec::len).collect();
+    let deployment_code = gen_evm_verifier_shplonk::<ProverCircuit>(params, &vk, num_instance, yul);
+
+    let proof = match proof {
+        Some(path) => fs::read(path).with_context(|| format!("cannot read {}", path.display()))?,
+        None => {
+            let pk = keygen_pk(params, vk, &circuit)?;
+            gen_evm_proof_shplonk(
+                params,
+                &pk,
+                circuit,
+                instances.clone(),
+                &mut XorShiftRng::seed_from_u64(seed),
+            )
+        }
+    };
+
+    let instance_bytes = encode_calldata(&instances, &[]);
+    let deployment_size = deployment_code.len();
+    let total = match deploy_and_call(deployment_code, encode_calldata(&instances, &proof)) {
+        Ok(gas) => gas,
+        Err(error) => bail!("the verifier rejected the proof: {}", error),
+    };
+
+    Ok(VerifierGasReport {
+        deployment_size,
+        num_instances: instances.iter().map(Vec::len).sum(),
+        proof_size: proof.len(),
+        total,
+        tx: GasCost::TX.as_u64(),
+        calldata_instances: tx_data_gas_cost(&instance_bytes),
+        calldata_proof: tx_data_gas_cost(&proof),
+        keccak_instances: GasCost::COPY_SHA3.as_u64() * (instance_bytes.len() as u64 / 32),
+        pairing: GasCost::PRECOMPILE_BN256PAIRING.as_u64() + SHPLONK_PAIRS * PAIRING_PER_PAIR_GAS,
+    })
+}
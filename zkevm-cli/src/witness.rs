@@ -0,0 +1,77 @@
+//! Inspection of the witness of a block.
+
+use crate::inputs::BlockInputs;
+use anyhow::{bail, Result};
+use halo2_proofs::halo2curves::bn256::Fr;
+use prover_server::prover::ProverCircuit;
+use strum::IntoEnumIterator;
+use zkevm_circuits::{
+    mem_profile::{self, Phase},
+    table::RwTableTag,
+    util::log2_ceil,
+    witness::{block_convert, Block},
+};
+
+/// Build the witness block from the inputs.
+pub fn witness_block(inputs: &BlockInputs) -> Result<Block<Fr>> {
+    let builder = inputs.build()?;
+    let _phase = mem_profile::enter(Phase::BusMapping);
+    Ok(block_convert(&builder.block, &builder.code_db)?)
+}
+
+/// Print the size of each part of the witness and the rows it needs in each
+/// sub-circuit.
+pub fn print_stats(block: &Block<Fr>) {
+    println!("txs: {}", block.txs.len());
+    println!(
+        "steps: {}",
+        block.txs.iter().map(|tx| tx.steps.len()).sum::<usize>()
+    );
+    println!("bytecodes: {}", block.bytecodes.len());
+    println!("copy events: {}", block.copy_events.len());
+    println!("exp events: {}", block.exp_events.len());
+    println!("keccak inputs: {}", block.keccak_inputs.len());
+
+    println!("rws:");
+    for tag in RwTableTag::iter() {
+        let count = block.rws.0.get(&tag).map_or(0, Vec::len);
+        if count > 0 {
+            println!("  {:?}: {}", tag, count);
+        }
+    }
+
+    println!("rows (without padding / with padding):");
+    let rows = ProverCircuit::min_num_rows_block_subcircuits(block);
+    for (name, rows, rows_padded) in &rows {
+        println!("  {}: {} / {}", name, rows, rows_padded);
+    }
+    let max_rows = rows
+        .iter()
+        .map(|(_, _, rows)| *rows)
+        .max()
+        .unwrap_or_default();
+    println!(
+        "max rows: {} (k >= {})",
+        max_rows,
+        log2_ceil(max_rows.max(1))
+    );
+}
+
+/// Print the rw operations of each step of the transaction `tx_index`
+/// (0-based).
+pub fn dump_tx_rws(block: &Block<Fr>, tx_index: usize) -> Result<()> {
+    let tx = match block.txs.get(tx_index) {
+        Some(tx) => tx,
+        None => bail!("the block has {} txs", block.txs.len()),
+    };
+    for step in &tx.steps {
+        println!(
+            "step {:?} pc: {} rwc: {}",
+            step.execution_state, step.program_counter, step.rw_counter
+        );
+        for rw_ref in &step.rw_indices {
+            println!("  - {:?}", block.rws[*rw_ref]);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,246 @@
+//! Comparison of the witness of a block with a golden witness saved by
+//! another version of the circuit input builder, to track down regressions
+//! of refactors of the witness generation.
+
+use anyhow::{Context, Result};
+use halo2_proofs::halo2curves::bn256::Fr;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::BufReader, path::Path};
+use zkevm_circuits::witness::Block;
+
+/// Step of a golden witness, with its rw operations. Fields which aren't
+/// numbers are stored in their debug format, so that the golden witness
+/// doesn't depend on the serialization of the witness types.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenStep {
+    /// Execution state of the step
+    pub execution_state: String,
+    /// Opcode of the step, if any
+    pub opcode: String,
+    /// Index of the call of the step in its tx
+    pub call_index: usize,
+    /// Program counter
+    pub program_counter: u64,
+    /// rw counter before the step
+    pub rw_counter: usize,
+    /// Stack pointer
+    pub stack_pointer: usize,
+    /// Gas left before the step
+    pub gas_left: u64,
+    /// Gas cost of the step
+    pub gas_cost: u64,
+    /// Memory size in bytes
+    pub memory_size: u64,
+    /// Reversible write counter before the step
+    pub reversible_write_counter: usize,
+    /// Number of reversible writes of the step
+    pub reversible_write_counter_delta: usize,
+    /// Log id within the tx
+    pub log_id: usize,
+    /// Number of rw operations of the step done by a copy event
+    pub copy_rw_counter_delta: u64,
+    /// rw operations of the step, in order
+    pub rws: Vec<String>,
+}
+
+impl GoldenStep {
+    /// Fields of the step other than its rw operations, by name.
+    fn fields(&self) -> [(&'static str, String); 13] {
+        [
+            ("execution_state", self.execution_state.clone()),
+            ("opcode", self.opcode.clone()),
+            ("call_index", self.call_index.to_string()),
+            ("program_counter", self.program_counter.to_string()),
+            ("rw_counter", self.rw_counter.to_string()),
+            ("stack_pointer", self.stack_pointer.to_string()),
+            ("gas_left", self.gas_left.to_string()),
+            ("gas_cost", self.gas_cost.to_string()),
+            ("memory_size", self.memory_size.to_string()),
+            (
+                "reversible_write_counter",
+                self.reversible_write_counter.to_string(),
+            ),
+            (
+                "reversible_write_counter_delta",
+                self.reversible_write_counter_delta.to_string(),
+            ),
+            ("log_id", self.log_id.to_string()),
+            (
+                "copy_rw_counter_delta",
+                self.copy_rw_counter_delta.to_string(),
+            ),
+        ]
+    }
+}
+
+/// Steps of the txs of a block, as compared by [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenWitness {
+    /// Steps of each tx
+    pub txs: Vec<Vec<GoldenStep>>,
+}
+
+impl GoldenWitness {
+    /// Golden witness of a witness block.
+    pub fn new(block: &Block<Fr>) -> Self {
+        let txs = block
+            .txs
+            .iter()
+            .map(|tx| {
+                tx.steps
+                    .iter()
+                    .map(|step| GoldenStep {
+                        execution_state: format!("{:?}", step.execution_state),
+                        opcode: step
+                            .opcode
+                            .map(|opcode| format!("{:?}", opcode))
+                            .unwrap_or_default(),
+                        call_index: step.call_index,
+                        program_counter: step.program_counter,
+                        rw_counter: step.rw_counter,
+                        stack_pointer: step.stack_pointer,
+                        gas_left: step.gas_left,
+                        gas_cost: step.gas_cost,
+                        memory_size: step.memory_size,
+                        reversible_write_counter: step.reversible_write_counter,
+                        reversible_write_counter_delta: step.reversible_write_counter_delta,
+                        log_id: step.log_id,
+                        copy_rw_counter_delta: step.copy_rw_counter_delta,
+                        rws: step
+                            .rw_indices
+                            .iter()
+                            .map(|rw_ref| format!("{:?}", block.rws[*rw_ref]))
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { txs }
+    }
+
+    /// Read a golden witness written by [`GoldenWitness::store`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("cannot parse {}", path.display()))
+    }
+
+    /// Write the golden witness as JSON.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("cannot create {}", path.display()))?;
+        Ok(serde_json::to_writer_pretty(file, self)?)
+    }
+}
+
+/// Difference between a golden and a current step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepDiff {
+    /// A field differs, with its golden and current values
+    Field(&'static str, String, String),
+    /// The rw operation at this index of the step differs, with its golden
+    /// and current values, `None` if the step has fewer rw operations
+    Rw(usize, Option<String>, Option<String>),
+}
+
+/// Differences of a tx between the golden and the current witness.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxDiff {
+    /// Index of the tx in the block
+    pub tx_index: usize,
+    /// Differing steps, with their index in the tx and the golden step
+    pub steps: Vec<(usize, GoldenStep, Vec<StepDiff>)>,
+    /// Index from which the execution diverges: the steps have a different
+    /// execution state or program counter, or one of the txs has no more
+    /// steps. The steps after it are not compared.
+    pub diverges_at: Option<usize>,
+}
+
+fn diff_steps(golden: &GoldenStep, current: &GoldenStep) -> Vec<StepDiff> {
+    let mut diffs: Vec<_> = golden
+        .fields()
+        .into_iter()
+        .zip(current.fields())
+        .filter(|((_, golden), (_, current))| golden != current)
+        .map(|((name, golden), (_, current))| StepDiff::Field(name, golden, current))
+        .collect();
+    for index in 0..golden.rws.len().max(current.rws.len()) {
+        let (golden, current) = (golden.rws.get(index), current.rws.get(index));
+        if golden != current {
+            diffs.push(StepDiff::Rw(index, golden.cloned(), current.cloned()));
+        }
+    }
+    diffs
+}
+
+/// Compare the steps of each tx of `current` to the ones of `golden`. Only
+/// the txs which differ are returned.
+pub fn diff(golden: &GoldenWitness, current: &GoldenWitness) -> Vec<TxDiff> {
+    let empty = Vec::new();
+    (0..golden.txs.len().max(current.txs.len()))
+        .filter_map(|tx_index| {
+            let golden = golden.txs.get(tx_index).unwrap_or(&empty);
+            let current = current.txs.get(tx_index).unwrap_or(&empty);
+            let mut tx_diff = TxDiff {
+                tx_index,
+                ..Default::default()
+            };
+            for (index, (golden, current)) in golden.iter().zip(current).enumerate() {
+                let diffs = diff_steps(golden, current);
+                if diffs.is_empty() {
+                    continue;
+                }
+                tx_diff.steps.push((index, golden.clone(), diffs));
+                if golden.execution_state != current.execution_state
+                    || golden.program_counter != current.program_counter
+                {
+                    tx_diff.diverges_at = Some(index);
+                    break;
+                }
+            }
+            if tx_diff.diverges_at.is_none() && golden.len() != current.len() {
+                tx_diff.diverges_at = Some(golden.len().min(current.len()));
+            }
+            (!tx_diff.steps.is_empty() || tx_diff.diverges_at.is_some()).then_some(tx_diff)
+        })
+        .collect()
+}
+
+/// Print the differences found by [`diff`].
+pub fn print_diff(golden: &GoldenWitness, current: &GoldenWitness, diffs: &[TxDiff]) {
+    for tx_diff in diffs {
+        let tx_index = tx_diff.tx_index;
+        println!("tx {}:", tx_index);
+        for (index, step, step_diffs) in &tx_diff.steps {
+            println!(
+                "  step {} {} pc: {} rwc: {}",
+                index, step.execution_state, step.program_counter, step.rw_counter
+            );
+            for step_diff in step_diffs {
+                match step_diff {
+                    StepDiff::Field(name, golden, current) => {
+                        println!("    {}: {} -> {}", name, golden, current)
+                    }
+                    StepDiff::Rw(index, golden, current) => {
+                        println!("    rw {}:", index);
+                        if let Some(golden) = golden {
+                            println!("      - {}", golden);
+                        }
+                        if let Some(current) = current {
+                            println!("      + {}", current);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(index) = tx_diff.diverges_at {
+            let steps = |witness: &GoldenWitness| witness.txs.get(tx_index).map_or(0, Vec::len);
+            println!(
+                "  execution diverges at step {} (golden: {} steps, current: {} steps)",
+                index,
+                steps(golden),
+                steps(current)
+            );
+        }
+    }
+}
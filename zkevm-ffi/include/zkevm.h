@@ -0,0 +1,48 @@
+/* C ABI of the verification of the proofs of the super circuit, implemented
+ * by the zkevm-ffi crate. Link with the static or shared library built by
+ * `cargo build --release -p zkevm-ffi`. */
+
+#ifndef ZKEVM_H
+#define ZKEVM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Outcome of verify_super_proof. The negative values are for inputs which
+ * could not be read. */
+typedef enum {
+  ZKEVM_VERIFY_VALID = 0,
+  ZKEVM_VERIFY_INVALID = 1,
+  ZKEVM_VERIFY_NULL_POINTER = -1,
+  ZKEVM_VERIFY_INVALID_PARAMS = -2,
+  ZKEVM_VERIFY_INVALID_VERIFYING_KEY = -3,
+  ZKEVM_VERIFY_INVALID_PUBLIC_INPUTS = -4,
+  ZKEVM_VERIFY_PANIC = -5,
+} zkevm_verify_result;
+
+/* Verify a proof of the super circuit.
+ * - params: KZG params, as written by ParamsKZG::write, of a degree at least
+ *   the one of the verifying key.
+ * - vk: verifying key, as written by VerifyingKey::write with
+ *   SerdeFormat::RawBytes.
+ * - proof: proof made with the Blake2b transcript and the SHPLONK multiopen.
+ * - public_inputs: the number of instance columns, then the length of each
+ *   column followed by its values, the numbers as little endian uint32 and
+ *   the values as 32 bytes little endian field elements.
+ * A buffer may be NULL if its length is 0. The buffers are only read during
+ * the call. */
+zkevm_verify_result verify_super_proof(const uint8_t *params, size_t params_len,
+                                       const uint8_t *vk, size_t vk_len,
+                                       const uint8_t *proof, size_t proof_len,
+                                       const uint8_t *public_inputs,
+                                       size_t public_inputs_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ZKEVM_H */
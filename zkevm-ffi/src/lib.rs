@@ -0,0 +1,244 @@
+//! C ABI of the verification of the proofs of the super circuit of the
+//! prover, so that node software written in other languages can verify them
+//! in process instead of calling a prover service. The declarations are in
+//! `include/zkevm.h`.
+//!
+//! The inputs are the serialized forms halo2 reads:
+//! - the KZG params, as written by `ParamsKZG::write`, of a degree at least the one of the
+//!   verifying key,
+//! - the verifying key, as written by `VerifyingKey::write` in the `SerdeFormat::RawBytes` format,
+//! - the proof, made with the Blake2b transcript and the SHPLONK multiopen,
+//! - the public inputs, as encoded by [`encode_public_inputs`].
+
+#![deny(missing_docs)]
+
+use eth_types::{ToScalar, Word};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{verify_proof, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+    SerdeFormat,
+};
+use prover_server::prover::ProverCircuit;
+use std::{convert::TryInto, panic, slice};
+
+/// Outcome of [`verify_super_proof`]. The negative values are for inputs
+/// which could not be read.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyResult {
+    /// The proof is valid for the public inputs
+    Valid = 0,
+    /// The proof is invalid for the public inputs
+    Invalid = 1,
+    /// A pointer is null while its length is not zero
+    NullPointer = -1,
+    /// The params can't be read or are of a lower degree than the verifying
+    /// key
+    InvalidParams = -2,
+    /// The verifying key can't be read
+    InvalidVerifyingKey = -3,
+    /// The public inputs are malformed or not canonical field elements
+    InvalidPublicInputs = -4,
+    /// The verification panicked
+    Panic = -5,
+}
+
+/// Encode the public inputs of a proof, one vector per instance column: the
+/// number of columns, then the length of each column followed by its values,
+/// the numbers as little endian `u32` and the values as 32 bytes little
+/// endian field elements.
+pub fn encode_public_inputs(instances: &[Vec<Fr>]) -> Vec<u8> {
+    let mut bytes = (instances.len() as u32).to_le_bytes().to_vec();
+    for column in instances {
+        bytes.extend((column.len() as u32).to_le_bytes());
+        for value in column {
+            bytes.extend(value.to_bytes());
+        }
+    }
+    bytes
+}
+
+/// Decode public inputs encoded by [`encode_public_inputs`]. Returns `None` if
+/// they are truncated, followed by other bytes, or not canonical field
+/// elements.
+pub fn decode_public_inputs(mut bytes: &[u8]) -> Option<Vec<Vec<Fr>>> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        if bytes.len() < len {
+            return None;
+        }
+        let (head, tail) = bytes.split_at(len);
+        *bytes = tail;
+        Some(head)
+    }
+    fn take_u32(bytes: &mut &[u8]) -> Option<usize> {
+        Some(u32::from_le_bytes(take(bytes, 4)?.try_into().ok()?) as usize)
+    }
+
+    let num_columns = take_u32(&mut bytes)?;
+    let mut instances = Vec::new();
+    for _ in 0..num_columns {
+        let len = take_u32(&mut bytes)?;
+        let column = take(&mut bytes, len.checked_mul(32)?)?
+            .chunks(32)
+            .map(|value| Word::from_little_endian(value).to_scalar())
+            .collect::<Option<Vec<Fr>>>()?;
+        instances.push(column);
+    }
+    bytes.is_empty().then_some(instances)
+}
+
+/// Verify a proof of the super circuit of the prover, from the serialized
+/// inputs described in the crate documentation.
+pub fn verify(params: &[u8], vk: &[u8], proof: &[u8], public_inputs: &[u8]) -> VerifyResult {
+    let mut params = match ParamsKZG::<Bn256>::read(&mut &params[..]) {
+        Ok(params) => params,
+        Err(_) => return VerifyResult::InvalidParams,
+    };
+    let vk = match VerifyingKey::<G1Affine>::read::<_, ProverCircuit>(
+        &mut &vk[..],
+        SerdeFormat::RawBytes,
+    ) {
+        Ok(vk) => vk,
+        Err(_) => return VerifyResult::InvalidVerifyingKey,
+    };
+    let degree = vk.get_domain().k();
+    if params.k() < degree {
+        return VerifyResult::InvalidParams;
+    }
+    params.downsize(degree);
+    let instances = match decode_public_inputs(public_inputs) {
+        Some(instances) => instances,
+        None => return VerifyResult::InvalidPublicInputs,
+    };
+
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+    let result = verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+        SingleStrategy<'_, Bn256>,
+    >(
+        params.verifier_params(),
+        &vk,
+        SingleStrategy::new(&params),
+        &[&instances.iter().map(Vec::as_slice).collect::<Vec<_>>()],
+        &mut transcript,
+    );
+    match result {
+        Ok(()) => VerifyResult::Valid,
+        Err(_) => VerifyResult::Invalid,
+    }
+}
+
+/// Slice of the C buffer `(ptr, len)`, which may be null if empty.
+///
+/// # Safety
+/// `ptr` must be valid for reads of `len` bytes if not null.
+unsafe fn buffer<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Verify a proof of the super circuit of the prover, see [`verify`].
+///
+/// # Safety
+/// Each pointer must be valid for reads of its length in bytes, or be null if
+/// its length is zero, and the buffers must not be written to during the
+/// call.
+#[no_mangle]
+pub unsafe extern "C" fn verify_super_proof(
+    params: *const u8,
+    params_len: usize,
+    vk: *const u8,
+    vk_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    public_inputs: *const u8,
+    public_inputs_len: usize,
+) -> VerifyResult {
+    let (params, vk, proof, public_inputs) = match (
+        buffer(params, params_len),
+        buffer(vk, vk_len),
+        buffer(proof, proof_len),
+        buffer(public_inputs, public_inputs_len),
+    ) {
+        (Some(params), Some(vk), Some(proof), Some(public_inputs)) => {
+            (params, vk, proof, public_inputs)
+        }
+        _ => return VerifyResult::NullPointer,
+    };
+    // Unwinding into the caller is undefined behavior.
+    panic::catch_unwind(|| verify(params, vk, proof, public_inputs)).unwrap_or(VerifyResult::Panic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn public_inputs_roundtrip() {
+        let instances = vec![vec![Fr::from(1), -Fr::from(1)], vec![], vec![Fr::from(7)]];
+        let bytes = encode_public_inputs(&instances);
+        assert_eq!(bytes.len(), 4 + 3 * 4 + 3 * 32);
+        assert_eq!(decode_public_inputs(&bytes), Some(instances));
+
+        assert_eq!(decode_public_inputs(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(
+            decode_public_inputs(&[bytes.clone(), vec![0]].concat()),
+            None
+        );
+        // The modulus is not a canonical field element.
+        let mut non_canonical = encode_public_inputs(&[vec![-Fr::from(1)]]);
+        non_canonical[8] += 1;
+        assert_eq!(decode_public_inputs(&non_canonical), None);
+    }
+
+    #[test]
+    fn verify_super_proof_rejects_unreadable_inputs() {
+        let public_inputs = encode_public_inputs(&[]);
+        unsafe {
+            assert_eq!(
+                verify_super_proof(
+                    ptr::null(),
+                    1,
+                    ptr::null(),
+                    0,
+                    ptr::null(),
+                    0,
+                    ptr::null(),
+                    0
+                ),
+                VerifyResult::NullPointer
+            );
+            assert_eq!(
+                verify_super_proof(
+                    [0u8; 4].as_ptr(),
+                    4,
+                    ptr::null(),
+                    0,
+                    ptr::null(),
+                    0,
+                    public_inputs.as_ptr(),
+                    public_inputs.len()
+                ),
+                VerifyResult::InvalidParams
+            );
+        }
+    }
+}
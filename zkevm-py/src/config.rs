@@ -0,0 +1,132 @@
+//! Config of the txs to trace, built up from Python.
+
+use crate::{address, runtime_error, word};
+use eth_types::{
+    geth_types::{Account, BlockConstants, Transaction},
+    Bytes, Word, U64,
+};
+use external_tracer::{TraceConfig, TraceConfigBuilder};
+use pyo3::{prelude::*, types::PyDict};
+
+/// Config of the txs to trace: the block constants, the accounts before the
+/// block and the txs. The defaults are filled in as by the
+/// [`TraceConfigBuilder`].
+#[pyclass(name = "TraceConfig")]
+#[derive(Clone)]
+pub struct PyTraceConfig {
+    builder: TraceConfigBuilder,
+    chain_id: Word,
+    block_constants: BlockConstants,
+}
+
+impl PyTraceConfig {
+    /// Fill in the defaults and validate the config.
+    pub(crate) fn build(&self) -> PyResult<TraceConfig> {
+        self.builder
+            .clone()
+            .chain_id(self.chain_id)
+            .block_constants(self.block_constants.clone())
+            .build()
+            .map_err(runtime_error)
+    }
+}
+
+#[pymethods]
+impl PyTraceConfig {
+    #[new]
+    #[pyo3(signature = (
+        chain_id = 1,
+        number = 1,
+        timestamp = 0,
+        coinbase = None,
+        gas_limit = 0,
+        base_fee = 0,
+        difficulty = 0,
+    ))]
+    fn new(
+        chain_id: u64,
+        number: u64,
+        timestamp: u64,
+        coinbase: Option<&str>,
+        gas_limit: u64,
+        base_fee: u64,
+        difficulty: u64,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            builder: TraceConfigBuilder::new(),
+            chain_id: chain_id.into(),
+            block_constants: BlockConstants {
+                coinbase: coinbase.map(address).transpose()?.unwrap_or_default(),
+                timestamp: timestamp.into(),
+                number: U64::from(number),
+                difficulty: difficulty.into(),
+                gas_limit: gas_limit.into(),
+                base_fee: base_fee.into(),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Add an account, replacing the account at the same address. The
+    /// storage maps slots to values, as ints or hex strings.
+    #[pyo3(signature = (address, balance = None, nonce = 0, code = None, storage = None))]
+    fn account(
+        &mut self,
+        address: &str,
+        balance: Option<&PyAny>,
+        nonce: u64,
+        code: Option<&[u8]>,
+        storage: Option<&PyDict>,
+    ) -> PyResult<()> {
+        let account = Account {
+            address: crate::address(address)?,
+            nonce: nonce.into(),
+            balance: balance.map(word).transpose()?.unwrap_or_default(),
+            code: Bytes::from(code.unwrap_or_default().to_vec()),
+            storage: storage
+                .into_iter()
+                .flatten()
+                .map(|(key, value)| Ok((word(key)?, word(value)?)))
+                .collect::<PyResult<_>>()?,
+            storage_snapshot: None,
+        };
+        self.builder = std::mem::take(&mut self.builder).account(account);
+        Ok(())
+    }
+
+    /// Add a tx after the txs already added, creating a contract if `to` is
+    /// not given.
+    #[pyo3(signature = (
+        sender,
+        to = None,
+        gas = 1_000_000,
+        value = None,
+        gas_price = None,
+        data = None,
+        nonce = 0,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn transaction(
+        &mut self,
+        sender: &str,
+        to: Option<&str>,
+        gas: u64,
+        value: Option<&PyAny>,
+        gas_price: Option<&PyAny>,
+        data: Option<&[u8]>,
+        nonce: u64,
+    ) -> PyResult<()> {
+        let transaction = Transaction {
+            from: address(sender)?,
+            to: to.map(address).transpose()?,
+            nonce: nonce.into(),
+            gas_limit: gas.into(),
+            value: value.map(word).transpose()?.unwrap_or_default(),
+            gas_price: gas_price.map(word).transpose()?.unwrap_or_default(),
+            call_data: Bytes::from(data.unwrap_or_default().to_vec()),
+            ..Default::default()
+        };
+        self.builder = std::mem::take(&mut self.builder).transaction(transaction);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Python bindings of the trace and the witness generation, for the analyses
+//! scripted around geth traces.
+//!
+//! The module is built with maturin, see the README for its use.
+
+mod config;
+mod witness;
+
+use config::PyTraceConfig;
+use eth_types::{Address, Word};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+use serde::Serialize;
+use std::fmt::Display;
+use witness::PyWitness;
+
+/// Python exception for a failure of the tracer or of the witness
+/// generation.
+fn runtime_error(error: impl Display) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+/// Word from a Python int or a hex string.
+fn word(value: &PyAny) -> PyResult<Word> {
+    if let Ok(hex) = value.extract::<&str>() {
+        return Word::from_str_radix(hex.trim_start_matches("0x"), 16)
+            .map_err(|error| PyValueError::new_err(format!("invalid word {}: {}", hex, error)));
+    }
+    let bytes: &[u8] = value.call_method1("to_bytes", (32, "big"))?.extract()?;
+    Ok(Word::from_big_endian(bytes))
+}
+
+/// Address from a hex string.
+fn address(value: &str) -> PyResult<Address> {
+    value
+        .parse()
+        .map_err(|error| PyValueError::new_err(format!("invalid address {}: {}", value, error)))
+}
+
+/// Python object with the JSON serialization of `value`.
+fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(runtime_error)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.into())
+}
+
+/// Trace the txs of the config with geth's struct logger, returning the
+/// trace of each tx as parsed JSON.
+#[pyfunction]
+fn trace(py: Python<'_>, config: &PyTraceConfig) -> PyResult<PyObject> {
+    let config = config.build()?;
+    let traces = py
+        .allow_threads(|| external_tracer::trace(&config))
+        .map_err(runtime_error)?;
+    to_python(py, &traces)
+}
+
+/// Trace the txs of the config and generate the witness of their block.
+#[pyfunction]
+fn witness(py: Python<'_>, config: &PyTraceConfig) -> PyResult<PyWitness> {
+    let config = config.build()?;
+    py.allow_threads(|| PyWitness::from_trace_config(config))
+}
+
+#[pymodule]
+fn zkevm(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyTraceConfig>()?;
+    m.add_class::<PyWitness>()?;
+    m.add_function(wrap_pyfunction!(trace, m)?)?;
+    m.add_function(wrap_pyfunction!(witness, m)?)?;
+    Ok(())
+}
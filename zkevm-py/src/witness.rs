@@ -0,0 +1,170 @@
+//! Witness of a block and its row statistics, as computed by `zkevm-cli
+//! witness stats`.
+
+use crate::runtime_error;
+use bus_mapping::{circuit_input_builder::CircuitInputBuilder, mock::BlockData};
+use eth_types::{geth_types::GethData, Block, Transaction, U64};
+use external_tracer::TraceConfig;
+use halo2_proofs::halo2curves::bn256::Fr;
+use prover_server::prover::{ProverCircuit, CIRCUITS_PARAMS};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use zkevm_circuits::{
+    table::RwTableTag,
+    util::log2_ceil,
+    witness::{self, block_convert},
+};
+
+/// Block holding the txs of the config, as geth would report it.
+fn eth_block(config: &TraceConfig) -> Block<Transaction> {
+    let number = U64::from(config.block_constants.number.as_u64());
+    let transactions = config
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| Transaction {
+            transaction_index: Some(U64::from(index)),
+            block_number: Some(number),
+            chain_id: Some(config.chain_id),
+            ..Transaction::from(tx)
+        })
+        .collect();
+    Block {
+        author: Some(config.block_constants.coinbase),
+        timestamp: config.block_constants.timestamp,
+        number: Some(number),
+        difficulty: config.block_constants.difficulty,
+        gas_limit: config.block_constants.gas_limit,
+        base_fee_per_gas: Some(config.block_constants.base_fee),
+        transactions,
+        ..Block::default()
+    }
+}
+
+/// Witness of a block, with the rows it needs in each sub-circuit of the
+/// super circuit of the prover.
+#[pyclass(name = "Witness")]
+pub struct PyWitness {
+    block: witness::Block<Fr>,
+}
+
+impl PyWitness {
+    fn from_builder(builder: &CircuitInputBuilder) -> PyResult<Self> {
+        let block = block_convert(&builder.block, &builder.code_db).map_err(runtime_error)?;
+        Ok(Self { block })
+    }
+
+    /// Trace the txs of the config and generate the witness of their block.
+    pub(crate) fn from_trace_config(config: TraceConfig) -> PyResult<Self> {
+        let geth_traces = external_tracer::trace(&config).map_err(runtime_error)?;
+        let geth_data = GethData {
+            chain_id: config.chain_id,
+            history_hashes: config.history_hashes.clone(),
+            eth_block: eth_block(&config),
+            geth_traces,
+            accounts: config.accounts.into_values().collect(),
+        };
+        let block_data = BlockData::new_from_geth_data_with_params(geth_data, CIRCUITS_PARAMS);
+        let mut builder = block_data.new_circuit_input_builder();
+        builder.block.gas_free_txs = config.gas_free_txs;
+        builder.block.prague_time = config
+            .chain_config
+            .as_ref()
+            .and_then(|chain_config| chain_config.prague_time);
+        builder
+            .handle_block(&block_data.eth_block, &block_data.geth_traces)
+            .map_err(runtime_error)?;
+        Self::from_builder(&builder)
+    }
+}
+
+#[pymethods]
+impl PyWitness {
+    /// Generate the witness of a block from the captured node output read by
+    /// `CircuitInputBuilder::from_trace_json`: the block, its struct logger
+    /// traces and its prestate traces.
+    #[staticmethod]
+    fn from_trace_json(
+        py: Python<'_>,
+        chain_id: u64,
+        block: &[u8],
+        traces: &[u8],
+        prestate: &[u8],
+    ) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let builder = CircuitInputBuilder::from_trace_json(
+                chain_id.into(),
+                CIRCUITS_PARAMS,
+                block,
+                traces,
+                prestate,
+            )
+            .map_err(runtime_error)?;
+            Self::from_builder(&builder)
+        })
+    }
+
+    /// Number of txs
+    #[getter]
+    fn num_txs(&self) -> usize {
+        self.block.txs.len()
+    }
+
+    /// Number of steps of all the txs
+    #[getter]
+    fn num_steps(&self) -> usize {
+        self.block.txs.iter().map(|tx| tx.steps.len()).sum()
+    }
+
+    /// Number of bytecodes
+    #[getter]
+    fn num_bytecodes(&self) -> usize {
+        self.block.bytecodes.len()
+    }
+
+    /// Number of copy events
+    #[getter]
+    fn num_copy_events(&self) -> usize {
+        self.block.copy_events.len()
+    }
+
+    /// Number of exponentiation events
+    #[getter]
+    fn num_exp_events(&self) -> usize {
+        self.block.exp_events.len()
+    }
+
+    /// Number of keccak inputs
+    #[getter]
+    fn num_keccak_inputs(&self) -> usize {
+        self.block.keccak_inputs.len()
+    }
+
+    /// Number of rw operations of each tag, leaving out the tags without
+    /// operations.
+    fn rws(&self) -> HashMap<String, usize> {
+        RwTableTag::iter()
+            .filter_map(|tag| {
+                let count = self.block.rws.0.get(&tag).map_or(0, Vec::len);
+                (count > 0).then(|| (format!("{:?}", tag), count))
+            })
+            .collect()
+    }
+
+    /// Rows needed by each sub-circuit, as `(name, rows, rows with padding)`.
+    fn rows(&self) -> Vec<(&'static str, usize, usize)> {
+        ProverCircuit::min_num_rows_block_subcircuits(&self.block)
+    }
+
+    /// Degree of the super circuit needed by the block.
+    fn degree(&self) -> u32 {
+        let max_rows = self
+            .rows()
+            .iter()
+            .map(|(_, _, rows)| *rows)
+            .max()
+            .unwrap_or_default();
+        log2_ceil(max_rows.max(1))
+    }
+}
@@ -0,0 +1,42 @@
+"""Smoke tests of the Python bindings, run by `make python_bindings` once the
+module is installed with `maturin develop`."""
+
+import pytest
+
+import zkevm
+
+SENDER = "0x00000000000000000000000000000000000000fe"
+CONTRACT = "0x00000000000000000000000000000000000000ff"
+# PUSH1 1 PUSH1 0 SSTORE STOP
+CODE = bytes.fromhex("600160005500")
+
+
+def config():
+    config = zkevm.TraceConfig(number=1)
+    config.account(SENDER, balance=10**18)
+    config.account(CONTRACT, code=CODE, storage={0: 7})
+    config.transaction(SENDER, to=CONTRACT, gas=100_000)
+    return config
+
+
+def test_trace():
+    traces = zkevm.trace(config())
+    assert len(traces) == 1
+    ops = [step["op"] for step in traces[0]["structLogs"]]
+    assert ops == ["PUSH1", "PUSH1", "SSTORE", "STOP"]
+
+
+def test_witness():
+    witness = zkevm.witness(config())
+    assert witness.num_txs == 1
+    assert witness.rws()["AccountStorage"] >= 1
+    rows = {name: rows for name, rows, _ in witness.rows()}
+    assert rows["evm circuit"] > 0
+    assert witness.degree() > 0
+
+
+def test_invalid_config():
+    config = zkevm.TraceConfig()
+    config.transaction(SENDER, to=CONTRACT)
+    with pytest.raises(RuntimeError, match="MissingSender"):
+        zkevm.trace(config)